@@ -0,0 +1,97 @@
+//! Бенчмарки математики: перемножение матриц и преобразование вершин.
+//!
+//! Запуск: `cargo bench --features bench --bench math`.
+
+use std::hint::black_box;
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use g3d::{HVec3, Point3, Transform3D, UVec3};
+
+/// Типичная цепочка преобразований модели: поворот, масштаб, перенос и перспектива.
+fn model_view_projection() -> Transform3D {
+    Transform3D::rotation_y_deg(30.0)
+        .multiply(Transform3D::rotation_x_deg(20.0))
+        .multiply(Transform3D::scale_uniform(2.0))
+        .multiply(Transform3D::translation(1.0, -2.0, 10.0))
+        .multiply(Transform3D::perspective(
+            60_f32.to_radians(),
+            16.0 / 9.0,
+            1.0,
+            100.0,
+        ))
+}
+
+/// Вершины на сетке в кубе [-1, 1]^3.
+fn grid_points(count: usize) -> Vec<Point3> {
+    let side = (count as f32).cbrt().ceil() as usize;
+    let coord = |i: usize| i as f32 / side as f32 * 2.0 - 1.0;
+    (0..count)
+        .map(|i| {
+            Point3::new(
+                coord(i % side),
+                coord(i / side % side),
+                coord(i / side / side),
+            )
+        })
+        .collect()
+}
+
+fn bench_transform_multiply(c: &mut Criterion) {
+    let a = Transform3D::rotation_around_axis(UVec3::new(1.0, 1.0, 0.0), 0.7);
+    let b = Transform3D::perspective(60_f32.to_radians(), 16.0 / 9.0, 1.0, 100.0);
+    c.bench_function("transform3d_multiply", |bencher| {
+        bencher.iter(|| black_box(a).multiply(black_box(b)))
+    });
+}
+
+fn bench_vertex_transforms(c: &mut Criterion) {
+    let transform = model_view_projection();
+    let mut group = c.benchmark_group("vertex_transform");
+    for count in [1_000, 10_000, 100_000] {
+        let points = grid_points(count);
+        let hvecs: Vec<HVec3> = points.iter().map(|&point| HVec3::from(point)).collect();
+        group.throughput(Throughput::Elements(count as u64));
+
+        group.bench_with_input(
+            BenchmarkId::new("point3", count),
+            &points,
+            |bencher, points| {
+                bencher.iter(|| {
+                    points
+                        .iter()
+                        .filter_map(|&point| point.apply_transform(transform).ok())
+                        .collect::<Vec<_>>()
+                })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("per_vertex", count),
+            &hvecs,
+            |bencher, hvecs| {
+                bencher.iter(|| {
+                    hvecs
+                        .iter()
+                        .map(|&hvec| transform.apply_to_hvec(hvec))
+                        .collect::<Vec<_>>()
+                })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("batched", count),
+            &hvecs,
+            |bencher, hvecs| bencher.iter(|| transform.apply_to_slice(hvecs)),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("batched_in_place", count),
+            &hvecs,
+            |bencher, hvecs| {
+                let mut buffer = hvecs.clone();
+                bencher.iter(|| transform.apply_to_slice_in_place(&mut buffer))
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_transform_multiply, bench_vertex_transforms);
+criterion_main!(benches);
@@ -0,0 +1,109 @@
+//! Бенчмарки растеризатора: скорость заливки треугольников и рендер целой сцены.
+//!
+//! Запуск: `cargo bench --features bench --bench render`.
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use egui::Color32;
+use g3d::{
+    Aabb, Canvas, Mesh, Model, Point3, Polygon, Scene, SceneRenderer, ShadingType, Vec3,
+    testing::{self, ReferenceScene},
+};
+
+/// Разрешения, на которых меряется рендер.
+const RESOLUTIONS: [(usize, usize); 3] = [(320, 240), (640, 480), (1280, 720)];
+
+/// Сцена из одного квадрата (двух треугольников), закрывающего почти весь кадр.
+fn fullscreen_quad_scene() -> Scene {
+    let mut mesh = Mesh::from_polygons(
+        vec![
+            Point3::new(-8.0, -5.0, 0.0),
+            Point3::new(8.0, -5.0, 0.0),
+            Point3::new(8.0, 5.0, 0.0),
+            Point3::new(-8.0, 5.0, 0.0),
+        ],
+        vec![Polygon::triangle(0, 1, 2), Polygon::triangle(0, 2, 3)],
+    );
+    mesh.generate_normals();
+    let mut quad = Model::from_mesh(mesh);
+    quad.material.color = Color32::from_rgb(200, 120, 60);
+    quad.material.double_sided = true;
+
+    let mut scene = ReferenceScene::SolidCube.scene();
+    scene.models = vec![quad];
+    scene
+}
+
+/// Эталонная сцена с плотной сферой вместо куба.
+fn dense_sphere_scene() -> Scene {
+    let mut sphere = Model::from_mesh(Mesh::from_implicit(
+        |p| Vec3::from(p).length() - 3.0,
+        Aabb::new(Point3::new(-3.5, -3.5, -3.5), Point3::new(3.5, 3.5, 3.5)),
+        32,
+    ));
+    sphere.material.color = Color32::from_rgb(200, 120, 60);
+
+    let mut scene = ReferenceScene::LambertCube.scene();
+    scene.models = vec![sphere];
+    scene
+}
+
+fn bench_fill_rate(c: &mut Criterion) {
+    let scene = fullscreen_quad_scene();
+    let mut group = c.benchmark_group("triangle_fill");
+    for shading in [
+        ShadingType::None,
+        ShadingType::GouraudLambert,
+        ShadingType::PhongToonShading(4),
+    ] {
+        let renderer = SceneRenderer {
+            shading_type: shading,
+            ..ReferenceScene::SolidCube.renderer()
+        };
+        for (width, height) in RESOLUTIONS {
+            let mut canvas = Canvas::new(width, height);
+            group.throughput(Throughput::Elements((width * height) as u64));
+            group.bench_function(
+                BenchmarkId::new(format!("{:?}", shading), format!("{}x{}", width, height)),
+                |bencher| {
+                    bencher.iter(|| {
+                        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero())
+                    })
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+fn bench_scene_render(c: &mut Criterion) {
+    let scenes = [
+        (
+            "lambert_cube",
+            ReferenceScene::LambertCube.scene(),
+            ReferenceScene::LambertCube.renderer(),
+        ),
+        (
+            "wireframe_cube",
+            ReferenceScene::WireframeCube.scene(),
+            ReferenceScene::WireframeCube.renderer(),
+        ),
+        (
+            "lambert_sphere",
+            dense_sphere_scene(),
+            ReferenceScene::LambertCube.renderer(),
+        ),
+    ];
+    let mut group = c.benchmark_group("scene_render");
+    for (name, scene, renderer) in &scenes {
+        for (width, height) in RESOLUTIONS {
+            group.bench_function(
+                BenchmarkId::new(*name, format!("{}x{}", width, height)),
+                |bencher| bencher.iter(|| testing::render_headless(scene, renderer, width, height)),
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_fill_rate, bench_scene_render);
+criterion_main!(benches);
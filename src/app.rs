@@ -21,6 +21,8 @@ pub struct AthenianApp {
     /// Холст
     canvas: g3d::Canvas,
     texture_handle: Option<egui::TextureHandle>,
+    /// Состояние последнего кадра, чтобы не перерисовывать неизменную сцену
+    frame_state: g3d::FrameState,
 
     // отображение холста
     display_canvas_width: f32,
@@ -52,6 +54,21 @@ pub struct AthenianApp {
     surface_y_min: f32,
     surface_y_max: f32,
     surface_divisions: usize,
+    /// Палитра раскраски графика по высоте, `None` - обычный материал.
+    surface_palette: Option<g3d::Palette>,
+    /// Количество изолиний графика, 0 - без изолиний.
+    surface_isolines: usize,
+
+    // постепенная загрузка .obj файла
+    obj_loader: Option<g3d::ObjStreamLoader>,
+    /// Идентификатор загружаемой модели на сцене, если уже что-то прочитано.
+    obj_loading_model_id: Option<g3d::ModelId>,
+    /// Ориентация системы координат загружаемых и сохраняемых .obj файлов.
+    obj_handedness: g3d::Handedness,
+    /// Ось, направленная вверх в загружаемых .obj файлах.
+    obj_up_axis: g3d::Axis,
+    /// Множитель перевода единиц загружаемых .obj файлов в единицы сцены.
+    obj_unit_scale: f32,
 }
 
 impl AthenianApp {
@@ -67,11 +84,8 @@ impl Default for AthenianApp {
         let mut scene = g3d::Scene::default();
 
         // Добавляем базовый источник света
-        let light = g3d::LightSource {
-            position: g3d::Point3::new(5.0, 5.0, 5.0),
-            color: egui::Color32::WHITE,
-            intensity: 1.0,
-        };
+        let light =
+            g3d::LightSource::new(g3d::Point3::new(5.0, 5.0, 5.0), egui::Color32::WHITE, 1.0);
         scene.lights.push(light);
 
         Self {
@@ -87,6 +101,7 @@ impl Default for AthenianApp {
             // Холст
             canvas: g3d::Canvas::new(800, 600),
             texture_handle: Default::default(),
+            frame_state: Default::default(),
 
             // отображение холста
             display_canvas_width: 0.0,
@@ -121,6 +136,15 @@ impl Default for AthenianApp {
             surface_y_min: -2.0,
             surface_y_max: 2.0,
             surface_divisions: 50,
+            surface_palette: Some(g3d::Palette::default()),
+            surface_isolines: 0,
+
+            // постепенная загрузка .obj файла
+            obj_loader: None,
+            obj_loading_model_id: None,
+            obj_handedness: g3d::Handedness::RightHanded,
+            obj_up_axis: g3d::Axis::Y,
+            obj_unit_scale: 1.0,
         }
     }
 }
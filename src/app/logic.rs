@@ -41,17 +41,37 @@ impl AthenianApp {
     }
 
     /// Рендеринг сцены с учетом всех настроек
-    pub fn render_scene(&mut self) {
+    ///
+    /// Сцена перерисовывается, только если что-то изменилось с прошлого кадра.
+    /// Возвращает `true`, если холст был перерисован.
+    pub fn render_scene(&mut self) -> bool {
         // Рендерим в зависимости от выбранного режима
         let show_custom_axis = self.instrument == Instrument::RotateAroundCustomLine;
 
-        self.scene_renderer.render(
+        // выделить выбранную модель
+        for (i, model) in self.scene.models.iter_mut().enumerate() {
+            model.flags.selected = Some(i) == self.selected_3d_model_index;
+        }
+
+        // манипулятор текущего инструмента
+        let gizmo_mode = match self.instrument {
+            Instrument::Move3D => Some(g3d::GizmoMode::Translate),
+            Instrument::Rotate3D => Some(g3d::GizmoMode::Rotate),
+            Instrument::Scale3D => Some(g3d::GizmoMode::Scale),
+            _ => None,
+        };
+        self.scene_renderer.gizmo = gizmo_mode.map(g3d::Gizmo::new);
+        // буфер идентификаторов нужен для выбора моделей кликом
+        self.canvas.set_id_buffer_enabled(true);
+
+        self.scene_renderer.render_if_changed(
             &self.scene,
             &mut self.canvas,
+            &mut self.frame_state,
             show_custom_axis,
             self.axis_point1,
             self.axis_point2,
-        );
+        )
     }
 }
 
@@ -72,19 +92,32 @@ impl AthenianApp {
     /// Обработать клики по холсту.
     fn handle_click(&mut self, response: &Response) {
         if response.clicked_by(egui::PointerButton::Primary) {
-            if let Some(_pos) = response.hover_pos() {
+            if let Some(pos) = response.hover_pos() {
                 match &self.instrument {
                     _ => {
-                        // Если есть фигура на сцене, автоматически выбираем её
-                        if !self.scene.models.is_empty() {
-                            self.selected_3d_model_index = Some(0);
-                        }
+                        // выбираем модель, нарисованную под курсором
+                        self.selected_3d_model_index = self
+                            .model_at(response.rect, pos)
+                            .and_then(|id| self.scene.model_index(id));
                     }
                 }
             }
         }
     }
 
+    /// Модель, нарисованная под точкой `pos` холста, отображённого в `rect`.
+    fn model_at(&self, rect: egui::Rect, pos: egui::Pos2) -> Option<g3d::ModelId> {
+        let [width, height] = self.canvas.size();
+        if width == 0 || height == 0 || rect.width() <= 0.0 || rect.height() <= 0.0 {
+            return None;
+        }
+        let x = ((pos.x - rect.min.x) / rect.width() * width as f32) as usize;
+        let y = ((pos.y - rect.min.y) / rect.height() * height as f32) as usize;
+        self.canvas
+            .id(x.min(width - 1), y.min(height - 1))
+            .and_then(|index| self.scene.id_at(index))
+    }
+
     /// Обработать перетаскивание по холсту.
     fn handle_drag(&mut self, response: &Response) {
         if response.drag_stopped_by(egui::PointerButton::Primary) {
@@ -228,10 +261,11 @@ impl AthenianApp {
 
 impl AthenianApp {
     /// Добавить фигуру (заменяет текущую)
-    pub fn set_model(&mut self, model: g3d::Model) {
+    pub fn set_model(&mut self, model: g3d::Model) -> g3d::ModelId {
         self.scene.models.clear();
-        self.scene.models.push(model);
+        let id = self.scene.add_model(model);
         self.selected_3d_model_index = Some(0); // Автоматически выбираем добавленную фигуру
+        id
     }
 
     /// Получить текущую выбранную модель (мутабельно)
@@ -246,6 +280,18 @@ impl AthenianApp {
             .and_then(|index| self.scene.models.get(index))
     }
 
+    /// Снять пробу отражений из центра выбранной модели и сделать её картой отражений сцены.
+    pub fn capture_reflection_probe(&mut self) {
+        let Some(index) = self.selected_3d_model_index else {
+            return;
+        };
+        let position = self.scene.models[index].get_position();
+        let probe =
+            self.scene_renderer
+                .capture_reflection_probe(&self.scene, position, 64, Some(index));
+        self.scene.reflection_map = Some(probe);
+    }
+
     pub fn add_tetrahedron(&mut self) {
         let mesh = g3d::Mesh::tetrahedron();
         let model = g3d::Model::from_mesh(mesh);
@@ -277,7 +323,7 @@ impl AthenianApp {
     }
 
     pub fn add_model(&mut self, model: g3d::Model) {
-        self.scene.models.push(model);
+        self.scene.add_model(model);
         self.selected_3d_model_index = Some(self.scene.models.len() - 1);
     }
 
@@ -315,11 +361,8 @@ impl AthenianApp {
     // === ОПЕРАЦИИ С ОСВЕЩЕНИЕМ ===
 
     pub fn add_light_source(&mut self) {
-        let new_light = g3d::LightSource {
-            position: g3d::Point3::new(3.0, 3.0, 3.0),
-            color: egui::Color32::WHITE,
-            intensity: 1.0,
-        };
+        let new_light =
+            g3d::LightSource::new(g3d::Point3::new(3.0, 3.0, 3.0), egui::Color32::WHITE, 1.0);
         self.scene.lights.push(new_light);
         self.selected_light_index = Some(self.scene.lights.len() - 1);
     }
@@ -327,7 +370,10 @@ impl AthenianApp {
     // === ОПЕРАЦИИ С КАМЕРОЙ ===
 
     pub fn reset_camera(&mut self) {
-        self.scene.camera = g3d::Camera::default();
+        let mut camera = g3d::Camera::default();
+        // камера наводится на содержимое сцены, на пустой сцене остаётся стандартной
+        self.scene.frame_all(&mut camera);
+        self.scene.camera = camera;
     }
 
     pub fn load_obj_file(&mut self) {
@@ -336,22 +382,75 @@ impl AthenianApp {
             .pick_file();
 
         if let Some(path) = file_path {
-            match g3d::Model::load_from_obj(path.to_str().unwrap()) {
-                Ok(model) => {
-                    self.set_model(model);
-                    println!("Модель успешно загружена");
-                }
-                Err(g3d::ObjLoadError::FileNotFound) => {
-                    eprintln!("Файл не найден");
+            match g3d::ObjStreamLoader::open(path.to_str().unwrap()) {
+                Ok(loader) => {
+                    // сама загрузка идёт по частям в `continue_obj_loading`
+                    self.obj_loader = Some(
+                        loader
+                            .with_handedness(self.obj_handedness)
+                            .with_up_axis(self.obj_up_axis)
+                            .with_unit_scale(self.obj_unit_scale),
+                    );
+                    self.obj_loading_model_id = None;
                 }
-                Err(g3d::ObjLoadError::InvalidFormat) => {
-                    eprintln!("Неверный формат OBJ файла");
-                }
-                Err(g3d::ObjLoadError::UnsupportedFeature) => {
-                    eprintln!("Файл содержит неподдерживаемые функции");
+                Err(err) => report_obj_load_error(err),
+            }
+        }
+    }
+
+    /// Прочитать очередную часть загружаемого .obj файла и показать уже загруженную часть модели.
+    ///
+    /// Возвращает `true`, если загрузка ещё идёт.
+    pub fn continue_obj_loading(&mut self) -> bool {
+        let Some(loader) = self.obj_loader.as_mut() else {
+            return false;
+        };
+
+        let finished = match loader.load_chunk(OBJ_LINES_PER_FRAME) {
+            Ok(finished) => finished,
+            Err(err) => {
+                report_obj_load_error(err);
+                self.obj_loader = None;
+                return false;
+            }
+        };
+
+        // обновить модель на сцене, если геометрия пересобрана
+        if let Some(mesh) = loader.updated_preview() {
+            match self.obj_loading_model_id {
+                // меняется только геометрия, правки модели пользователем (в том числе
+                // положение, поворот и масштаб в локальной системе координат Mesh'а) сохраняются
+                Some(id) => match self.scene.model_mut(id) {
+                    Some(loading_model) => {
+                        let frame = loading_model.mesh.local_frame;
+                        loading_model.mesh = mesh;
+                        loading_model.mesh.local_frame = frame;
+                    }
+                    None => {
+                        // модель убрали со сцены, дальше загружать некуда
+                        println!("Загрузка модели прервана: модель удалена со сцены");
+                        self.obj_loader = None;
+                        self.obj_loading_model_id = None;
+                        return false;
+                    }
+                },
+                None => {
+                    self.obj_loading_model_id = Some(self.set_model(g3d::Model::from_mesh(mesh)))
                 }
             }
         }
+
+        if finished {
+            if self.obj_loading_model_id.is_some() {
+                println!("Модель успешно загружена");
+            } else {
+                report_obj_load_error(g3d::ObjLoadError::InvalidFormat);
+            }
+            self.obj_loader = None;
+            self.obj_loading_model_id = None;
+        }
+
+        !finished
     }
 
     pub fn save_obj_file(&mut self) {
@@ -363,7 +462,8 @@ impl AthenianApp {
                 .save_file();
 
             if let Some(path) = file_path {
-                match model.save_to_obj(path.to_str().unwrap()) {
+                match model.save_to_obj_with_handedness(path.to_str().unwrap(), self.obj_handedness)
+                {
                     Ok(()) => {
                         println!("Модель успешно сохранена");
                     }
@@ -447,7 +547,8 @@ impl AthenianApp {
                 .save_file();
 
             if let Some(path) = file_path {
-                match model.save_to_obj(path.to_str().unwrap()) {
+                match model.save_to_obj_with_handedness(path.to_str().unwrap(), self.obj_handedness)
+                {
                     Ok(()) => println!("Модель вращения сохранена в {}", path.display()),
                     Err(e) => eprintln!("Ошибка сохранения: {:?}", e),
                 }
@@ -479,13 +580,41 @@ impl AthenianApp {
 
     /// Создать модель из функции двух переменных
     pub fn create_function_model(&mut self) {
-        let mesh = self.selected_surface_function.generate_surface_mesh(
-            (self.surface_x_min, self.surface_x_max),
-            (self.surface_y_min, self.surface_y_max),
-            (self.surface_divisions, self.surface_divisions),
+        let x_range = (self.surface_x_min, self.surface_x_max);
+        let y_range = (self.surface_y_min, self.surface_y_max);
+        let divisions = (self.surface_divisions, self.surface_divisions);
+
+        let mut model = match self.surface_palette {
+            Some(palette) => self
+                .selected_surface_function
+                .generate_height_colored_model(x_range, y_range, divisions, palette),
+            None => g3d::Model::from_mesh(
+                self.selected_surface_function
+                    .generate_surface_mesh(x_range, y_range, divisions),
+            ),
+        };
+        // график - незамкнутая поверхность, видимая с обеих сторон
+        model.material.double_sided = true;
+
+        // изолинии равномерно между минимальной и максимальной высотой графика
+        let (min, max) = model
+            .mesh
+            .get_local_vertex_iter()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), vertex| {
+                (min.min(vertex.z), max.max(vertex.z))
+            });
+        let count = self.surface_isolines;
+        let levels: Vec<f32> = (1..=count)
+            .map(|k| min + (max - min) * k as f32 / (count + 1) as f32)
+            .collect();
+        self.scene.polylines = self.selected_surface_function.generate_isolines(
+            x_range,
+            y_range,
+            divisions,
+            &levels,
+            g3d::LineStyle::new(egui::Color32::BLACK, 1.0),
         );
 
-        let model = g3d::Model::from_mesh(mesh);
         self.set_model(model);
     }
 
@@ -511,6 +640,24 @@ impl AthenianApp {
         }
     }
 
+    /// Загрузить панораму (equirectangular) в качестве фона сцены.
+    pub fn load_background_panorama(&mut self) {
+        let file_path = rfd::FileDialog::new()
+            .add_filter("Image files", &["png", "jpg", "jpeg", "bmp", "tga"])
+            .pick_file();
+
+        if let Some(path) = file_path {
+            match self.load_texture_from_file(path.to_str().unwrap()) {
+                Ok(texture) => {
+                    self.scene.background = g3d::Background::Equirectangular(texture);
+                }
+                Err(e) => {
+                    eprintln!("Ошибка загрузки фона: {}", e);
+                }
+            }
+        }
+    }
+
     // Вспомогательный метод для загрузки текстуры из файла
     fn load_texture_from_file(&self, file_path: &str) -> Result<g3d::Texture, String> {
         let file = File::open(file_path).map_err(|e| format!("Не удалось открыть файл: {}", e))?;
@@ -543,23 +690,13 @@ impl AthenianApp {
 impl AthenianApp {
     /// Установить перспективную проекцию
     pub fn set_perspective_projection(&mut self) {
-        todo!()
-        // self.camera.projection_type = g3d::ProjectionType::Perspective;
-
-        // // Настройка камеры для перспективы
-        // self.camera.position = Point3::new(0.0, 0.0, 10.0); // Камера смотрит вдоль Z
-        // self.camera.set_fov_degrees(60.0);
-        // self.camera
-        //     .set_aspect_ratio(self.painter_width / self.painter_height);
+        self.scene_renderer.projection_type = g3d::ProjectionType::Perspective;
     }
 
     /// Установить изометрическую проекцию
     pub fn set_isometric_projection(&mut self) {
-        todo!()
-        // self.camera.projection_type = g3d::ProjectionType::Isometric;
-
-        // // Для изометрии используем специальные углы
-        // self.camera.position = Point3::new(0.0, 0.0, 0.0); // Положение не важно для нашей простой проекции
+        self.scene_renderer.projection_type =
+            g3d::ProjectionType::Axonometric(g3d::Axonometry::Isometric);
     }
 }
 
@@ -674,3 +811,62 @@ impl CenterAxis {
         }
     }
 }
+
+/// Сколько строк .obj файла читается за один кадр при постепенной загрузке.
+const OBJ_LINES_PER_FRAME: usize = 50_000;
+
+/// Вывести сообщение об ошибке загрузки .obj файла.
+fn report_obj_load_error(err: g3d::ObjLoadError) {
+    match err {
+        g3d::ObjLoadError::FileNotFound => {
+            eprintln!("Файл не найден");
+        }
+        g3d::ObjLoadError::InvalidFormat => {
+            eprintln!("Неверный формат OBJ файла");
+        }
+        g3d::ObjLoadError::UnsupportedFeature => {
+            eprintln!("Файл содержит неподдерживаемые функции");
+        }
+    }
+}
+
+#[cfg(test)]
+mod logic_tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_obj_streaming_keeps_model_transform() {
+        // по 4 строки на треугольник, загрузка идёт в несколько кадров
+        let path = std::env::temp_dir().join("g3d_app_obj_streaming.obj");
+        let mut file = std::io::BufWriter::new(File::create(&path).unwrap());
+        for i in 0..40_000 {
+            let x = i as f32 * 1e-3;
+            writeln!(file, "v {} 0 0\nv {} 1 0\nv {} 0 1\nf -3 -2 -1", x, x, x).unwrap();
+        }
+        drop(file);
+
+        let mut app = AthenianApp {
+            obj_loader: Some(g3d::ObjStreamLoader::open(path.to_str().unwrap()).unwrap()),
+            ..Default::default()
+        };
+        while app.scene.models.is_empty() {
+            assert!(
+                app.continue_obj_loading(),
+                "модель появилась до конца загрузки"
+            );
+        }
+        let polygons = app.scene.models[0].mesh.polygon_count();
+        let position = g3d::Point3::new(1.0, 2.0, 3.0);
+        app.scene.models[0].set_position(position);
+
+        // после пересборки геометрии модель остаётся на месте
+        while app.scene.models[0].mesh.polygon_count() == polygons {
+            app.continue_obj_loading();
+        }
+        assert_eq!(app.scene.models[0].get_position(), position);
+        while app.continue_obj_loading() {}
+        assert_eq!(app.scene.models[0].mesh.polygon_count(), 40_000);
+        assert_eq!(app.scene.models[0].get_position(), position);
+    }
+}
@@ -7,6 +7,11 @@ use crate::app::{AthenianApp, logic};
 impl eframe::App for AthenianApp {
     /// Главный цикл UI.
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // пока модель загружается, нужно перерисовывать кадры
+        if self.continue_obj_loading() {
+            ctx.request_repaint();
+        }
+
         self.show_top_panel(ctx);
         self.show_left_panel(ctx);
         self.show_bottom_panel(ctx);
@@ -59,6 +64,10 @@ impl AthenianApp {
                             self.show_rendering_controls(ui);
                         });
 
+                        egui::CollapsingHeader::new("Статистика кадра").show(ui, |ui| {
+                            ui.label(self.frame_state.stats().to_string());
+                        });
+
                         egui::CollapsingHeader::new("Освещение").show(ui, |ui| {
                             self.show_lighting_controls(ui);
                         });
@@ -97,9 +106,42 @@ impl AthenianApp {
         ui.separator();
 
         ui.label("Загрузка моделей:");
+        egui::ComboBox::from_label("Система координат OBJ")
+            .selected_text(self.obj_handedness.to_string())
+            .show_ui(ui, |ui| {
+                for handedness in [g3d::Handedness::LeftHanded, g3d::Handedness::RightHanded] {
+                    ui.selectable_value(
+                        &mut self.obj_handedness,
+                        handedness,
+                        handedness.to_string(),
+                    );
+                }
+            });
+        egui::ComboBox::from_label("Ось вверх в OBJ")
+            .selected_text(self.obj_up_axis.to_string())
+            .show_ui(ui, |ui| {
+                for axis in [g3d::Axis::X, g3d::Axis::Y, g3d::Axis::Z] {
+                    ui.selectable_value(&mut self.obj_up_axis, axis, axis.to_string());
+                }
+            });
+        ui.horizontal(|ui| {
+            ui.label("Масштаб единиц:");
+            ui.add(
+                egui::DragValue::new(&mut self.obj_unit_scale)
+                    .speed(0.01)
+                    .range(0.0001..=1000.0),
+            );
+        });
         if ui.button("Загрузить OBJ").clicked() {
             self.load_obj_file();
         }
+        if let Some(loader) = &self.obj_loader {
+            ui.label(format!(
+                "Загрузка... вершин: {}, полигонов: {}",
+                loader.vertex_count(),
+                loader.polygon_count()
+            ));
+        }
         if ui.button("Сохранить OBJ").clicked() {
             self.save_obj_file();
         }
@@ -363,6 +405,31 @@ impl AthenianApp {
             ui.add(egui::Slider::new(&mut self.surface_divisions, 10..=200).step_by(5.0));
         });
 
+        ui.horizontal(|ui| {
+            ui.label("Изолиний:");
+            ui.add(egui::Slider::new(&mut self.surface_isolines, 0..=20));
+        });
+
+        egui::ComboBox::from_label("Раскраска по высоте")
+            .selected_text(match self.surface_palette {
+                Some(palette) => palette.to_string(),
+                None => "Нет".to_string(),
+            })
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut self.surface_palette, None, "Нет");
+                for palette in [
+                    g3d::Palette::Viridis,
+                    g3d::Palette::Turbo,
+                    g3d::Palette::Grayscale,
+                ] {
+                    ui.selectable_value(
+                        &mut self.surface_palette,
+                        Some(palette),
+                        palette.to_string(),
+                    );
+                }
+            });
+
         if ui.button("Построить график").clicked() {
             self.create_function_model();
         }
@@ -370,6 +437,42 @@ impl AthenianApp {
 
     /// Показать управление выбранной моделью
     fn show_current_model_controls(&mut self, ui: &mut egui::Ui) {
+        if let Some(model) = self.get_selected_model_mut() {
+            ui.checkbox(&mut model.flags.visible, "Видимость");
+            ui.checkbox(&mut model.flags.wireframe_only, "Только каркас");
+            ui.checkbox(&mut model.flags.casts_shadows, "Отбрасывает тени");
+            ui.checkbox(&mut model.material.double_sided, "Двусторонний материал");
+            egui::ComboBox::from_label("Отсечение граней")
+                .selected_text(model.flags.cull_mode.to_string())
+                .show_ui(ui, |ui| {
+                    for cull_mode in [
+                        g3d::CullMode::Back,
+                        g3d::CullMode::Front,
+                        g3d::CullMode::None,
+                    ] {
+                        ui.selectable_value(
+                            &mut model.flags.cull_mode,
+                            cull_mode,
+                            cull_mode.to_string(),
+                        );
+                    }
+                });
+            ui.horizontal(|ui| {
+                ui.label(format!("Уровней детализации: {}", model.lod_count()));
+                if ui.button("Сгенерировать").clicked() {
+                    model.generate_lods(4, 0.5, 10.0);
+                }
+            });
+            ui.horizontal(|ui| {
+                if ui.button("Сгладить").clicked() {
+                    model.mesh.smooth_laplacian(1, 0.5);
+                }
+                if ui.button("Добавить шум").clicked() {
+                    model.mesh.displace_noise(0.05, 4.0, 0);
+                }
+            });
+            ui.separator();
+        }
         self.show_transform_controls(ui);
         self.show_material_controls(ui);
     }
@@ -514,6 +617,17 @@ impl AthenianApp {
 
     /// Показать управление материалами и текстурами.
     fn show_material_controls(&mut self, ui: &mut egui::Ui) {
+        // проба снимается из центра выбранной модели
+        ui.horizontal(|ui| {
+            if ui.button("Снять пробу отражений").clicked() {
+                self.capture_reflection_probe();
+            }
+            if self.scene.reflection_map.is_some() && ui.button("Убрать пробу").clicked()
+            {
+                self.scene.reflection_map = None;
+            }
+        });
+
         let material = &mut self.get_selected_model_mut().unwrap().material;
 
         ui.label("Материал:");
@@ -522,6 +636,20 @@ impl AthenianApp {
             ui.color_edit_button_srgba(&mut material.color);
             ui.label("Цвет");
         });
+        ui.add(egui::Slider::new(&mut material.opacity, 0.0..=1.0).text("Непрозрачность"));
+        ui.add(
+            egui::Slider::new(&mut material.reflectivity, 0.0..=1.0).text("Отражающая способность"),
+        );
+        ui.horizontal(|ui| {
+            ui.color_edit_button_srgba(&mut material.emission);
+            ui.label("Свечение");
+        });
+        if material.emission != egui::Color32::BLACK {
+            ui.add(
+                egui::Slider::new(&mut material.emission_strength, 0.0..=10.0)
+                    .text("Яркость свечения"),
+            );
+        }
 
         ui.separator();
 
@@ -566,21 +694,293 @@ impl AthenianApp {
         let mut height = self.canvas.size()[1];
         ui.add(egui::Slider::new(&mut height, 10..=2000).text("Высота:"));
 
-        if [width, height] != self.canvas.size() {
-            self.canvas = g3d::Canvas::new(width, height);
-        }
+        self.canvas.resize(width, height);
 
         ui.separator();
 
         // сам рендер
         ui.checkbox(&mut self.scene_renderer.render_wireframe, "Рендер каркаса");
         ui.checkbox(&mut self.scene_renderer.render_normals, "Рендер нормалей");
+        let light_gizmos = &mut self.scene_renderer.light_gizmos;
+        ui.checkbox(&mut light_gizmos.show_lights, "Источники света");
+        ui.checkbox(&mut light_gizmos.show_disabled, "Выключенные источники");
+        ui.checkbox(&mut light_gizmos.show_directions, "Направления света");
+        ui.add(
+            egui::Slider::new(&mut light_gizmos.sphere_radius, 0.0..=5.0).text("Сферы источников:"),
+        );
         ui.checkbox(&mut self.scene_renderer.render_solid, "Рендер полигонов");
         ui.checkbox(
             &mut self.scene_renderer.backface_culling,
             "Отсечение задних граней",
         );
-        ui.checkbox(&mut self.scene_renderer.z_buffer_enabled, "Z-буфер");
+        egui::ComboBox::from_label("Упорядочивание граней")
+            .selected_text(self.scene_renderer.depth_mode.to_string())
+            .show_ui(ui, |ui| {
+                for mode in [
+                    g3d::DepthMode::ZBuffer,
+                    g3d::DepthMode::Painter,
+                    g3d::DepthMode::None,
+                ] {
+                    ui.selectable_value(
+                        &mut self.scene_renderer.depth_mode,
+                        mode,
+                        mode.to_string(),
+                    );
+                }
+            });
+        if self.scene_renderer.depth_mode == g3d::DepthMode::ZBuffer {
+            egui::ComboBox::from_label("Проверка глубины")
+                .selected_text(self.scene_renderer.depth_func.to_string())
+                .show_ui(ui, |ui| {
+                    for depth_func in [
+                        g3d::DepthFunc::Less,
+                        g3d::DepthFunc::LEqual,
+                        g3d::DepthFunc::Greater,
+                    ] {
+                        ui.selectable_value(
+                            &mut self.scene_renderer.depth_func,
+                            depth_func,
+                            depth_func.to_string(),
+                        );
+                    }
+                });
+            ui.checkbox(&mut self.scene_renderer.depth_write, "Запись глубины");
+        }
+        egui::ComboBox::from_label("Отладочный вид")
+            .selected_text(self.scene_renderer.debug_view.to_string())
+            .show_ui(ui, |ui| {
+                for debug_view in g3d::DebugView::ALL {
+                    ui.selectable_value(
+                        &mut self.scene_renderer.debug_view,
+                        debug_view,
+                        debug_view.to_string(),
+                    );
+                }
+            });
+        ui.checkbox(
+            &mut self.scene_renderer.hidden_line_removal,
+            "Скрывать невидимые рёбра",
+        );
+        ui.checkbox(
+            &mut self.scene_renderer.antialiased_lines,
+            "Сглаживание рёбер",
+        );
+
+        // автоматический выбор уровня детализации
+        let auto_lod = &mut self.scene_renderer.auto_lod;
+        ui.checkbox(&mut auto_lod.enabled, "LOD по размеру на экране");
+        if auto_lod.enabled {
+            ui.add(
+                egui::Slider::new(&mut auto_lod.pixels_per_triangle, 0.5..=64.0)
+                    .logarithmic(true)
+                    .text("Пикселей на треугольник:"),
+            );
+        }
+
+        // затенение окружением
+        let ssao = &mut self.scene_renderer.ssao;
+        ui.checkbox(&mut ssao.enabled, "Затенение окружением (SSAO)");
+        if ssao.enabled {
+            ui.add(
+                egui::Slider::new(&mut ssao.radius, 0.05..=5.0)
+                    .logarithmic(true)
+                    .text("Радиус SSAO:"),
+            );
+            ui.add(egui::Slider::new(&mut ssao.strength, 0.0..=2.0).text("Сила SSAO:"));
+            ui.add(egui::Slider::new(&mut ssao.samples, 4..=64).text("Выборок SSAO:"));
+        }
+
+        // свечение
+        let bloom = &mut self.scene_renderer.bloom;
+        ui.checkbox(&mut bloom.enabled, "Свечение (bloom)");
+        if bloom.enabled {
+            ui.add(egui::Slider::new(&mut bloom.threshold, 0.0..=4.0).text("Порог свечения:"));
+            ui.add(egui::Slider::new(&mut bloom.intensity, 0.0..=4.0).text("Сила свечения:"));
+            ui.add(egui::Slider::new(&mut bloom.radius, 1..=32).text("Радиус свечения:"));
+            ui.checkbox(&mut bloom.light_sources, "Свечение источников света");
+        }
+
+        // ограниченная палитра
+        let mut palette_enabled = self.scene_renderer.palette_output.is_some();
+        ui.checkbox(&mut palette_enabled, "Ограниченная палитра");
+        if palette_enabled != self.scene_renderer.palette_output.is_some() {
+            self.scene_renderer.palette_output = palette_enabled.then(|| {
+                g3d::PaletteOutput::new(
+                    g3d::IndexedPalette::uniform_rgb(2),
+                    g3d::DitherMode::default(),
+                )
+            });
+        }
+        if let Some(output) = &mut self.scene_renderer.palette_output {
+            let presets = [
+                ("Чёрно-белая", g3d::IndexedPalette::black_and_white()),
+                ("4 оттенка серого", g3d::IndexedPalette::grayscale(4)),
+                ("8 цветов", g3d::IndexedPalette::uniform_rgb(2)),
+                ("216 цветов", g3d::IndexedPalette::uniform_rgb(6)),
+            ];
+            let selected = presets
+                .iter()
+                .find(|(_, palette)| *palette == output.palette)
+                .map_or("Своя", |(name, _)| *name);
+            egui::ComboBox::from_label("Палитра")
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    for (name, palette) in presets {
+                        let is_selected = palette == output.palette;
+                        if ui.selectable_label(is_selected, name).clicked() {
+                            output.palette = palette;
+                        }
+                    }
+                });
+            egui::ComboBox::from_label("Дизеринг")
+                .selected_text(output.dither.to_string())
+                .show_ui(ui, |ui| {
+                    for dither in [
+                        g3d::DitherMode::None,
+                        g3d::DitherMode::Ordered,
+                        g3d::DitherMode::FloydSteinberg,
+                    ] {
+                        ui.selectable_value(&mut output.dither, dither, dither.to_string());
+                    }
+                });
+        }
+
+        // обводка контуров
+        let outline = &mut self.scene_renderer.outline;
+        egui::ComboBox::from_label("Обводка")
+            .selected_text(outline.mode.to_string())
+            .show_ui(ui, |ui| {
+                for mode in [
+                    g3d::OutlineMode::None,
+                    g3d::OutlineMode::Selection,
+                    g3d::OutlineMode::Contours,
+                ] {
+                    ui.selectable_value(&mut outline.mode, mode, mode.to_string());
+                }
+            });
+        if outline.mode != g3d::OutlineMode::None {
+            ui.add(egui::Slider::new(&mut outline.width, 1..=8).text("Толщина обводки:"));
+        }
+        if outline.mode == g3d::OutlineMode::Contours {
+            ui.horizontal(|ui| {
+                ui.label("Цвет контуров:");
+                ui.color_edit_button_srgba(&mut outline.color);
+            });
+        }
+
+        // обводка силуэтных рёбер
+        let mut silhouette_enabled = self.scene_renderer.silhouette.is_some();
+        ui.checkbox(&mut silhouette_enabled, "Силуэтные рёбра");
+        if silhouette_enabled != self.scene_renderer.silhouette.is_some() {
+            self.scene_renderer.silhouette =
+                silhouette_enabled.then(|| g3d::LineStyle::new(egui::Color32::BLACK, 2.0));
+        }
+        if let Some(style) = &mut self.scene_renderer.silhouette {
+            ui.add(egui::Slider::new(&mut style.thickness, 1.0..=8.0).text("Толщина силуэта:"));
+            ui.horizontal(|ui| {
+                ui.label("Цвет силуэта:");
+                ui.color_edit_button_srgba(&mut style.color);
+            });
+        }
+
+        // HDR и тональная компрессия
+        let mut hdr_enabled = self.scene_renderer.hdr.is_some();
+        ui.checkbox(&mut hdr_enabled, "HDR");
+        if hdr_enabled != self.scene_renderer.hdr.is_some() {
+            self.scene_renderer.hdr = hdr_enabled.then(g3d::HdrOptions::default);
+        }
+        if let Some(hdr) = &mut self.scene_renderer.hdr {
+            egui::ComboBox::from_label("Тональная компрессия")
+                .selected_text(hdr.tone_mapping.to_string())
+                .show_ui(ui, |ui| {
+                    for tone_mapping in [
+                        g3d::ToneMapping::Clamp,
+                        g3d::ToneMapping::Reinhard,
+                        g3d::ToneMapping::Aces,
+                    ] {
+                        ui.selectable_value(
+                            &mut hdr.tone_mapping,
+                            tone_mapping,
+                            tone_mapping.to_string(),
+                        );
+                    }
+                });
+            ui.add(
+                egui::Slider::new(&mut hdr.exposure, 0.05..=16.0)
+                    .logarithmic(true)
+                    .text("Экспозиция:"),
+            );
+            ui.add(egui::Slider::new(&mut hdr.gamma, 1.0..=3.0).text("Гамма:"));
+        }
+
+        // автоматические плоскости отсечения
+        let auto_clip = &mut self.scene_renderer.auto_clip_planes;
+        ui.checkbox(&mut auto_clip.enabled, "Авто near/far");
+        if auto_clip.enabled {
+            ui.add(
+                egui::Slider::new(&mut auto_clip.min_near, 0.001..=10.0)
+                    .logarithmic(true)
+                    .text("Мин. near:"),
+            );
+            ui.add(
+                egui::Slider::new(&mut auto_clip.max_far, 10.0..=10000.0)
+                    .logarithmic(true)
+                    .text("Макс. far:"),
+            );
+        }
+
+        ui.separator();
+
+        // сетка и оси
+        let grid_options = &mut self.scene_renderer.grid_options;
+        ui.checkbox(&mut grid_options.show_axes, "Оси координат");
+        ui.checkbox(&mut grid_options.show_axis_labels, "Подписи осей");
+        ui.checkbox(&mut grid_options.show_grid, "Опорная сетка");
+        if grid_options.show_grid {
+            egui::ComboBox::from_label("Плоскость сетки")
+                .selected_text(grid_options.plane.to_string())
+                .show_ui(ui, |ui| {
+                    for plane in [g3d::GridPlane::XY, g3d::GridPlane::XZ, g3d::GridPlane::YZ] {
+                        ui.selectable_value(&mut grid_options.plane, plane, plane.to_string());
+                    }
+                });
+            ui.add(egui::Slider::new(&mut grid_options.spacing, 0.1..=10.0).text("Шаг сетки:"));
+            ui.add(egui::Slider::new(&mut grid_options.extent, 1.0..=100.0).text("Размер сетки:"));
+            ui.checkbox(&mut grid_options.adaptive, "Адаптивная сетка");
+        }
+
+        ui.separator();
+
+        // фон сцены
+        ui.label("Фон:");
+        ui.horizontal(|ui| {
+            if ui.button("Цвет").clicked() {
+                self.scene.background = g3d::Background::default();
+            }
+            if ui.button("Градиент").clicked() {
+                self.scene.background = g3d::Background::VerticalGradient {
+                    top: egui::Color32::from_rgb(120, 160, 220),
+                    bottom: egui::Color32::GRAY,
+                };
+            }
+            if ui.button("Панорама...").clicked() {
+                self.load_background_panorama();
+            }
+        });
+        match &mut self.scene.background {
+            g3d::Background::Solid(color) => {
+                ui.color_edit_button_srgba(color);
+            }
+            g3d::Background::VerticalGradient { top, bottom } => {
+                ui.horizontal(|ui| {
+                    ui.color_edit_button_srgba(top);
+                    ui.color_edit_button_srgba(bottom);
+                });
+            }
+            _ => (),
+        }
+
+        ui.separator();
 
         ui.label("Шейдинг:");
         egui::ComboBox::from_label("Модель")
@@ -625,7 +1025,47 @@ impl AthenianApp {
                     g3d::ProjectionType::Parallel,
                     g3d::ProjectionType::Parallel.to_string(),
                 );
+                for axonometry in g3d::Axonometry::presets() {
+                    let selected = matches!(
+                        (self.scene_renderer.projection_type, axonometry),
+                        (g3d::ProjectionType::Axonometric(current), _)
+                            if std::mem::discriminant(&current) == std::mem::discriminant(&axonometry)
+                    );
+                    if ui.selectable_label(selected, axonometry.to_string()).clicked() && !selected {
+                        self.scene_renderer.projection_type =
+                            g3d::ProjectionType::Axonometric(axonometry);
+                    }
+                }
             });
+
+        // углы аксонометрии
+        if let g3d::ProjectionType::Axonometric(axonometry) =
+            &mut self.scene_renderer.projection_type
+        {
+            match axonometry {
+                g3d::Axonometry::Isometric => (),
+                g3d::Axonometry::Dimetric { tilt } => {
+                    ui.horizontal(|ui| {
+                        ui.label("Наклон:");
+                        ui.drag_angle(tilt);
+                    });
+                }
+                g3d::Axonometry::Trimetric { yaw, tilt } => {
+                    ui.horizontal(|ui| {
+                        ui.label("Поворот:");
+                        ui.drag_angle(yaw);
+                        ui.label("Наклон:");
+                        ui.drag_angle(tilt);
+                    });
+                }
+                g3d::Axonometry::Cavalier { angle } | g3d::Axonometry::Cabinet { angle } => {
+                    ui.horizontal(|ui| {
+                        ui.label("Угол оси глубины:");
+                        ui.drag_angle(angle);
+                    });
+                }
+            }
+        }
     }
 
     /// Показать управление освещением.
@@ -635,27 +1075,66 @@ impl AthenianApp {
         if ui.button("Добавить свет").clicked() {
             self.add_light_source();
         }
+        if ui.button("Запечь затенение окружением").clicked() {
+            self.scene.bake_ambient_occlusion(32);
+        }
 
         if !self.scene.lights.is_empty() {
             egui::ComboBox::from_label("Выбранный свет")
-                .selected_text(if let Some(light_index) = self.selected_light_index {
-                    format!("Свет {}", light_index)
-                } else {
-                    "Отсутствует".to_owned()
-                })
+                .selected_text(
+                    match self
+                        .selected_light_index
+                        .and_then(|index| self.scene.lights.get(index).map(|light| (index, light)))
+                    {
+                        Some((index, light)) => light_label(index, light),
+                        None => "Отсутствует".to_owned(),
+                    },
+                )
                 .show_ui(ui, |ui| {
                     ui.selectable_value(&mut self.selected_light_index, None, "None".to_owned());
-                    for (i, _) in self.scene.lights.iter().enumerate() {
+                    for (i, light) in self.scene.lights.iter().enumerate() {
                         ui.selectable_value(
                             &mut self.selected_light_index,
                             Some(i),
-                            format!("Свет {}", i),
+                            light_label(i, light),
                         );
                     }
                 });
 
+            // включение и выключение групп целиком
+            let mut groups: Vec<String> = self
+                .scene
+                .lights
+                .iter()
+                .filter_map(|light| light.group.clone())
+                .collect();
+            groups.sort();
+            groups.dedup();
+            for group in groups {
+                let mut enabled = self.scene.light_group(&group).any(|light| light.enabled);
+                if ui
+                    .checkbox(&mut enabled, format!("Группа «{}»", group))
+                    .changed()
+                {
+                    self.scene.set_light_group_enabled(&group, enabled);
+                }
+            }
+
             if let Some(index) = self.selected_light_index {
                 if let Some(light) = self.scene.lights.get_mut(index) {
+                    ui.checkbox(&mut light.enabled, "Включён");
+                    ui.horizontal(|ui| {
+                        ui.label("Имя:");
+                        ui.text_edit_singleline(&mut light.name);
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Группа:");
+                        let mut group = light.group.clone().unwrap_or_default();
+                        if ui.text_edit_singleline(&mut group).changed() {
+                            light.group = (!group.is_empty()).then_some(group);
+                        }
+                    });
+
                     ui.label("Позиция:");
                     ui.horizontal(|ui| {
                         ui.add(
@@ -688,6 +1167,40 @@ impl AthenianApp {
                 }
             }
         }
+
+        ui.separator();
+        self.show_environment_controls(ui);
+    }
+
+    /// Показать управление освещением окружением.
+    fn show_environment_controls(&mut self, ui: &mut egui::Ui) {
+        let mut enabled = self.scene.environment.is_some();
+        if ui
+            .checkbox(&mut enabled, "Освещение окружением (по фону)")
+            .changed()
+        {
+            self.scene.environment =
+                enabled.then(|| g3d::EnvironmentLight::new(self.scene.background.clone(), 0.3));
+        }
+
+        let background = &self.scene.background;
+        let Some(environment) = &mut self.scene.environment else {
+            return;
+        };
+        ui.add(egui::Slider::new(&mut environment.intensity, 0.0..=2.0).text("Яркость окружения"));
+        egui::ComboBox::from_label("Выборка окружения")
+            .selected_text(environment.sampling.to_string())
+            .show_ui(ui, |ui| {
+                for sampling in [
+                    g3d::EnvironmentSampling::SphericalHarmonics,
+                    g3d::EnvironmentSampling::Direct,
+                ] {
+                    ui.selectable_value(&mut environment.sampling, sampling, sampling.to_string());
+                }
+            });
+        if ui.button("Обновить по фону").clicked() {
+            environment.set_map(background.clone());
+        }
     }
 
     /// Показать управление камерой.
@@ -745,9 +1258,10 @@ impl AthenianApp {
             // Обработка ввода
             self.handle_input(&canvas_response, ctx);
 
-            // Рендеринг сцены
-            self.render_scene();
-            self.update_texture(ctx);
+            // Рендеринг сцены, текстура обновляется только при изменении холста
+            if self.render_scene() || self.texture_handle.is_none() {
+                self.update_texture(ctx);
+            }
 
             // Отображение текстуры
             if let Some(texture) = &self.texture_handle {
@@ -761,3 +1275,17 @@ impl AthenianApp {
         });
     }
 }
+
+/// Подпись источника света в списке: имя, если задано, иначе номер.
+fn light_label(index: usize, light: &g3d::LightSource) -> String {
+    let name = if light.name.is_empty() {
+        format!("Свет {}", index)
+    } else {
+        light.name.clone()
+    };
+    if light.enabled {
+        name
+    } else {
+        format!("{} (выкл.)", name)
+    }
+}
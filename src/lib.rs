@@ -3,20 +3,115 @@
 //! Содержит все необходимые классы для представления 3D моделей в пространстве,
 //! а так же вспомогательные классы по типу освещения и камеры для отрисовки этих
 //! моделей. Модели поддерживают шейдинг и текстурирование.
+//!
+//! # Стабильность API
+//!
+//! Всё, что экспортировано в корень библиотеки, является стабильным API: примитивы,
+//! модели и их построение, загрузка и сохранение .obj, камера, сцена и рендер сцены.
+//! Эти типы не ломаются между минорными версиями.
+//!
+//! Внутренности растеризатора (трейт шейдеров, функции интерполяции и отсечения,
+//! отладочные константы) доступны только в модуле `experimental` при включённой
+//! feature `experimental`. Они могут меняться в любой версии.
 
 // Модуль с реализациями заданных структур. Он не pub, так как ниже идёт re-export для более удобного API.
 mod library;
 
-// re-export всех примитивов в корень библиотеки
-pub use library::primitives::*;
+// --------------------------------------------------
+// Стабильный API
+// --------------------------------------------------
+
+// примитивы
+pub use library::primitives::{
+    Aabb, Curve3, DPoint3, DTransform3D, DVec3, HVec3, Line3, Plane, Point3, PointError,
+    ProjectionError, SectionBox, Transform3D, UVec3, UVecError, Vec3, VecError,
+};
+
+// цвета и палитры
+pub use library::color::{Color, ColorSpace, Palette};
+
+// модель и её построение
+pub use library::model::{
+    Axis, BillboardMode, BoneWeights, CullMode, GroundPlaneOptions, HalfEdge, HalfEdgeMesh,
+    Handedness, Joint, LodGroup, MAX_BONE_INFLUENCES, Material, Mesh, Model, ModelFlags, ModelId,
+    MorphTarget, ObjLoadError, ObjSaveError, ObjStreamLoader, Polygon, RayHit, Skeleton,
+    SurfaceFunction, Texture, TextureBlendMode, VoxelGrid, VoxelMeshing,
+};
+
+// камера и сцена
+pub use library::background::Background;
+pub use library::camera::{Axonometry, Camera, ProjectionType};
+pub use library::camera_track::{CameraKeyframe, CameraTrack, TrackInterpolation};
+pub use library::coord_frame::CoordFrame;
+pub use library::environment_light::{EnvironmentLight, EnvironmentSampling};
+pub use library::gizmo::{Gizmo, GizmoAxis, GizmoMode};
+pub use library::layers::LayerMask;
+pub use library::light_source::LightSource;
+pub use library::measurement::{AngleMeasurement, BoundsMeasurement, DistanceMeasurement};
+pub use library::physics::{Collider, PhysicsWorld, RigidBody};
+pub use library::polyline::{LineStyle, Polyline3};
+pub use library::scene::{DropMode, Scene};
+pub use library::scene_command::{
+    CommandStack, DEFAULT_HISTORY_LIMIT, SceneCommand, SceneCommandError,
+};
+pub use library::snapping::{SnapOptions, SnapQuery, SnapResult, SnapTarget};
+pub use library::texture_projector::TextureProjector;
+
+// рендер
+pub use library::canvas::{Canvas, DepthFunc, LineCap};
+pub use library::dithering::{DitherMode, IndexedPalette, PaletteOutput};
+pub use library::raytrace_renderer::{RaytraceRenderer, RaytraceShading};
+pub use library::render_job::{DEFAULT_TILE_SIZE, RenderJob, RenderProgress};
+pub use library::render_target::{BufferView, RenderBuffer, RenderTarget};
+pub use library::scene_renderer::{
+    AutoClipPlanes, AutoLod, BloomOptions, DEBUG_MISSING_COLOR, DebugView, DepthMode,
+    FragmentInput, FragmentShader, FrameState, GridOptions, GridPlane, LightGizmoOptions,
+    OutlineMode, OutlineOptions, RenderStats, SceneRenderer, ShadingType, SharedFragmentShader,
+    SharedVertexShader, SsaoOptions, VertexInput, VertexOutput, VertexShader,
+};
+pub use library::swap_canvas::SwapCanvas;
+pub use library::tone_mapping::{HdrOptions, ToneMapping};
+pub use library::turntable::{Turntable, TurntableError, TurntableSubject, render_turntable};
+
+// --------------------------------------------------
+// Тестирование рендера
+// --------------------------------------------------
+
+/// Эталонные сцены и сравнение рендера с эталонными изображениями.
+///
+/// Позволяет менять рендер, не ломая незаметно итоговую картинку: эталонные сцены
+/// рисуются без окна и сравниваются со встроенными PNG с заданным допуском.
+pub mod testing {
+    pub use crate::library::testing::*;
+}
+
+// --------------------------------------------------
+// Демонстрационные сцены
+// --------------------------------------------------
+
+/// Детерминированные демонстрационные сцены: комната Корнелла, галерея правильных
+/// многогранников и ландшафт из шума.
+///
+/// Одинаковый `seed` всегда даёт одинаковую сцену, поэтому примеры, бенчмарки и
+/// эталонные изображения могут использовать одно и то же содержимое.
+pub mod examples_scenes {
+    pub use crate::library::examples_scenes::*;
+}
+
+// --------------------------------------------------
+// Экспериментальный API
+// --------------------------------------------------
 
-// re-export модели в корень библиотеки
-pub use library::model::*;
+/// Внутренности растеризатора без гарантий стабильности.
+///
+/// Нужны для написания собственных шейдеров и проходов рендера поверх `Canvas`.
+/// Всё содержимое модуля может меняться в любой версии библиотеки.
+#[cfg(feature = "experimental")]
+pub mod experimental {
+    pub use crate::library::scene_renderer::{OVERDRAW_HEATMAP_LIMIT, RenderMeshCache, Shader};
 
-// re-export прочих структур в корень библиотеки
-pub use library::camera::*;
-pub use library::canvas::*;
-pub use library::coord_frame::*;
-pub use library::light_source::*;
-pub use library::scene::*;
-pub use library::scene_renderer::*;
+    /// Вспомогательные функции растеризации: интерполяция, отсечение, цвета.
+    pub mod raster {
+        pub use crate::library::utils::*;
+    }
+}
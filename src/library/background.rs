@@ -0,0 +1,251 @@
+//! Фон сцены: сплошной цвет, градиент или карта окружения.
+
+use std::{
+    f32::consts::{FRAC_1_PI, PI},
+    hash::{Hash, Hasher},
+};
+
+use egui::Color32;
+
+use crate::library::{state_hash::StateHash, utils};
+use crate::{Camera, Canvas, Point3, ProjectionType, Texture, Vec3};
+
+/// Фон, которым заполняется холст перед отрисовкой моделей.
+///
+/// Карты окружения выбирают цвет пикселя по направлению взгляда через него, поэтому
+/// поворачиваются вместе с камерой, но не зависят от её положения. Направление всегда
+/// считается как для перспективы, иначе при параллельной проекции фон был бы однотонным.
+#[derive(Debug, Clone)]
+pub enum Background {
+    /// Сплошной цвет.
+    Solid(Color32),
+    /// Вертикальный градиент на экране от `top` к `bottom`.
+    VerticalGradient { top: Color32, bottom: Color32 },
+    /// Равнопромежуточная (equirectangular) панорама: по горизонтали долгота, по вертикали широта.
+    Equirectangular(Texture),
+    /// Кубическая карта из 6 граней в порядке `+x`, `-x`, `+y`, `-y`, `+z`, `-z`.
+    CubeMap(Box<[Texture; 6]>),
+}
+
+impl Default for Background {
+    fn default() -> Self {
+        Self::Solid(Color32::GRAY)
+    }
+}
+
+impl Background {
+    /// Заполнить холст фоном для кадра, снятого камерой `camera`.
+    ///
+    /// Холст рисуется "вверх ногами" и переворачивается в конце рендера, поэтому
+    /// нижняя строка холста здесь соответствует верху изображения.
+    pub fn paint(&self, canvas: &mut Canvas, camera: &Camera) {
+        let (width, height) = (canvas.width(), canvas.height());
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        match self {
+            Self::Solid(color) => canvas.fill(*color),
+            Self::VerticalGradient { top, bottom } => {
+                let (xs, ys) = canvas.scissor_bounds();
+                for y in ys {
+                    let t = y as f32 / (height - 1).max(1) as f32;
+                    let color = utils::lerp_color(*bottom, *top, t);
+                    for x in xs.clone() {
+                        canvas[(x, y)] = color;
+                    }
+                }
+            }
+            Self::Equirectangular(_) | Self::CubeMap(_) => {
+                let Some(rays) = ScreenRays::new(camera, canvas) else {
+                    return;
+                };
+                let (xs, ys) = canvas.scissor_bounds();
+                for y in ys {
+                    for x in xs.clone() {
+                        canvas[(x, y)] = self.sample(rays.direction(x as f32, y as f32));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Цвет фона в направлении `direction` в **глобальных** координатах.
+    ///
+    /// Для сплошного цвета возвращает его, для градиента - цвет по высоте направления.
+    pub fn sample(&self, direction: Vec3) -> Color32 {
+        let Ok(direction) = direction.normalize() else {
+            return Color32::BLACK;
+        };
+        let direction = Vec3::from(direction);
+
+        match self {
+            Self::Solid(color) => *color,
+            Self::VerticalGradient { top, bottom } => {
+                utils::lerp_color(*bottom, *top, (direction.y + 1.0) / 2.0)
+            }
+            Self::Equirectangular(texture) => {
+                let u = direction.x.atan2(direction.z) / (2.0 * PI) + 0.5;
+                let v = 0.5 - direction.y.clamp(-1.0, 1.0).asin() * FRAC_1_PI;
+                texture.get_pixel_color(u.clamp(0.0, 1.0), v.clamp(0.0, 1.0))
+            }
+            Self::CubeMap(faces) => {
+                let (face, u, v) = cube_map_face(direction);
+                faces[face].get_pixel_color(u, v)
+            }
+        }
+    }
+}
+
+/// Грань кубической карты и текстурные координаты на ней для направления `direction`.
+fn cube_map_face(direction: Vec3) -> (usize, f32, f32) {
+    let Vec3 { x, y, z } = direction;
+    let (ax, ay, az) = (x.abs(), y.abs(), z.abs());
+    // (грань, горизонтальная координата, вертикальная координата сверху вниз, большая ось)
+    let (face, sc, tc, ma) = if ax >= ay && ax >= az {
+        if x > 0.0 {
+            (0, -z, -y, ax)
+        } else {
+            (1, z, -y, ax)
+        }
+    } else if ay >= az {
+        if y > 0.0 {
+            (2, x, z, ay)
+        } else {
+            (3, x, -z, ay)
+        }
+    } else if z > 0.0 {
+        (4, x, -y, az)
+    } else {
+        (5, -x, -y, az)
+    };
+
+    let to_uv = |value: f32| ((value / ma + 1.0) / 2.0).clamp(0.0, 1.0);
+    (face, to_uv(sc), to_uv(tc))
+}
+
+/// Направления лучей камеры через пиксели холста.
+///
+/// Точка на фиксированной глубине, спроецированная обратно из экранных координат,
+/// линейно зависит от `(x, y)`, поэтому достаточно трёх точек.
+struct ScreenRays {
+    origin: Vec3,
+    step_x: Vec3,
+    step_y: Vec3,
+}
+
+impl ScreenRays {
+    fn new(camera: &Camera, canvas: &Canvas) -> Option<Self> {
+        let screen_to_global = camera
+            .global_to_screen_transform(ProjectionType::Perspective, canvas)
+            .inverse()?;
+        let unproject = |x: f32, y: f32| -> Option<Vec3> {
+            let point = Point3::new(x, y, 0.0)
+                .apply_transform(screen_to_global)
+                .ok()?;
+            Some(point - camera.get_position())
+        };
+
+        let origin = unproject(0.0, 0.0)?;
+        Some(Self {
+            origin,
+            step_x: unproject(1.0, 0.0)? - origin,
+            step_y: unproject(0.0, 1.0)? - origin,
+        })
+    }
+
+    fn direction(&self, x: f32, y: f32) -> Vec3 {
+        self.origin + self.step_x * x + self.step_y * y
+    }
+}
+
+impl StateHash for Background {
+    fn hash_state<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Self::Solid(color) => color.hash(state),
+            Self::VerticalGradient { top, bottom } => {
+                top.hash(state);
+                bottom.hash(state);
+            }
+            Self::Equirectangular(texture) => texture.hash_state(state),
+            Self::CubeMap(faces) => {
+                for face in faces.iter() {
+                    face.hash_state(state);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod background_tests {
+    use super::*;
+    use image::{DynamicImage, Rgb, RgbImage};
+
+    fn solid_texture(color: [u8; 3]) -> Texture {
+        Texture::new(DynamicImage::ImageRgb8(RgbImage::from_pixel(
+            4,
+            4,
+            Rgb(color),
+        )))
+    }
+
+    #[test]
+    fn test_vertical_gradient() {
+        let mut canvas = Canvas::new(10, 20);
+        let background = Background::VerticalGradient {
+            top: Color32::WHITE,
+            bottom: Color32::BLACK,
+        };
+        background.paint(&mut canvas, &Camera::default());
+        canvas.invert_y();
+
+        assert_eq!(canvas[(5, 0)], Color32::WHITE, "сверху цвет top");
+        assert_eq!(canvas[(5, 19)], Color32::BLACK, "снизу цвет bottom");
+    }
+
+    #[test]
+    fn test_cube_map_samples_face_by_direction() {
+        let colors = [
+            [255, 0, 0],
+            [0, 255, 0],
+            [0, 0, 255],
+            [255, 255, 0],
+            [0, 255, 255],
+            [255, 0, 255],
+        ];
+        let background = Background::CubeMap(Box::new(colors.map(solid_texture)));
+        let directions = [
+            Vec3::new(1.0, 0.1, 0.2),
+            Vec3::new(-1.0, 0.1, 0.2),
+            Vec3::new(0.1, 1.0, 0.2),
+            Vec3::new(0.1, -1.0, 0.2),
+            Vec3::new(0.1, 0.2, 1.0),
+            Vec3::new(0.1, 0.2, -1.0),
+        ];
+        for (direction, [r, g, b]) in directions.into_iter().zip(colors) {
+            assert_eq!(
+                background.sample(direction),
+                Color32::from_rgb(r, g, b),
+                "направление {:?}",
+                direction
+            );
+        }
+
+        // камера по умолчанию смотрит вдоль +z, в центре экрана грань +z
+        let mut canvas = Canvas::new(30, 20);
+        background.paint(&mut canvas, &Camera::default());
+        assert_eq!(canvas[(15, 10)], Color32::from_rgb(0, 255, 255));
+    }
+
+    #[test]
+    fn test_equirectangular_poles() {
+        // верхняя половина панорамы белая, нижняя чёрная
+        let image = RgbImage::from_fn(8, 4, |_, y| if y < 2 { Rgb([255; 3]) } else { Rgb([0; 3]) });
+        let background = Background::Equirectangular(Texture::new(DynamicImage::ImageRgb8(image)));
+
+        assert_eq!(background.sample(Vec3::new(0.0, 1.0, 0.0)), Color32::WHITE);
+        assert_eq!(background.sample(Vec3::new(0.0, -1.0, 0.0)), Color32::BLACK);
+    }
+}
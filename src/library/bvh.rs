@@ -0,0 +1,287 @@
+//! Иерархия ограничивающих объёмов (BVH) треугольников сцены для трассировки лучей.
+//!
+//! Используется трассировщиком лучей и запеканием ambient occlusion.
+
+use crate::library::model::intersect_triangle;
+use crate::{Aabb, Line3, Model, Point3, Vec3};
+
+/// Максимальное количество треугольников в листе дерева.
+const LEAF_SIZE: usize = 4;
+
+/// Треугольник сцены в **глобальных** координатах.
+#[derive(Debug, Clone, Copy)]
+pub struct SceneTriangle {
+    /// Вершины треугольника.
+    pub vertexes: [Point3; 3],
+    /// Индексы вершин в Mesh'е модели.
+    pub indexes: [usize; 3],
+    /// Текстурные координаты углов треугольника с учётом UV-координат углов полигона.
+    pub texture_coords: Option<[(f32, f32); 3]>,
+    /// Индекс модели в `scene.models`.
+    pub model_index: usize,
+}
+
+/// Пересечение луча с треугольником.
+#[derive(Debug, Clone, Copy)]
+pub struct TriangleHit {
+    /// Расстояние от начала луча.
+    pub distance: f32,
+    /// Индекс треугольника в [`Bvh::triangles`].
+    pub triangle: usize,
+    /// Барицентрическая координата точки пересечения при второй вершине.
+    pub u: f32,
+    /// Барицентрическая координата точки пересечения при третьей вершине.
+    pub v: f32,
+}
+
+/// Узел дерева: ограничивающий параллелепипед и либо два потомка, либо диапазон треугольников.
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    aabb: Aabb,
+    /// Для листа - первый треугольник, иначе - индекс правого потомка (левый идёт следом за узлом).
+    start: usize,
+    /// Количество треугольников листа, 0 для внутреннего узла.
+    count: usize,
+}
+
+/// Дерево ограничивающих параллелепипедов над треугольниками сцены.
+#[derive(Debug, Clone)]
+pub struct Bvh {
+    pub triangles: Vec<SceneTriangle>,
+    nodes: Vec<Node>,
+}
+
+impl Bvh {
+    /// Построить дерево, разбивая треугольники пополам вдоль самой длинной оси.
+    pub fn new(mut triangles: Vec<SceneTriangle>) -> Self {
+        let mut nodes = Vec::with_capacity(triangles.len() * 2);
+        if !triangles.is_empty() {
+            let count = triangles.len();
+            Self::build(&mut triangles, 0, count, &mut nodes);
+        }
+        Self { triangles, nodes }
+    }
+
+    /// Построить дерево над треугольниками моделей, для которых `include` вернул `true`.
+    ///
+    /// [`SceneTriangle::model_index`] - индекс модели в `models`.
+    pub fn from_models(models: &[Model], include: impl Fn(&Model) -> bool) -> Self {
+        let mut triangles = Vec::new();
+        for (model_index, model) in models.iter().enumerate() {
+            if !include(model) {
+                continue;
+            }
+            let mesh = &model.mesh;
+            let vertexes: Vec<Point3> = mesh.get_global_vertex_iter().collect();
+            for polygon in mesh.get_polygon_iter() {
+                triangles.extend(polygon.corner_triangles().map(|corners| {
+                    let [a, b, c] = corners.map(|i| polygon.get_mesh_vertex_index(i));
+                    let texture_coords = corners.map(|i| polygon.get_texture_coord(mesh, i));
+                    SceneTriangle {
+                        vertexes: [vertexes[a], vertexes[b], vertexes[c]],
+                        indexes: [a, b, c],
+                        texture_coords: match texture_coords {
+                            [Some(ta), Some(tb), Some(tc)] => Some([ta, tb, tc]),
+                            _ => None,
+                        },
+                        model_index,
+                    }
+                }));
+            }
+        }
+        Self::new(triangles)
+    }
+
+    /// Построить поддерево над `triangles[start..start + count]`, возвращает индекс его корня.
+    fn build(
+        triangles: &mut [SceneTriangle],
+        start: usize,
+        count: usize,
+        nodes: &mut Vec<Node>,
+    ) -> usize {
+        let range = &mut triangles[start..start + count];
+        let aabb = Aabb::from_points(range.iter().flat_map(|triangle| triangle.vertexes)).unwrap();
+        let index = nodes.len();
+        nodes.push(Node { aabb, start, count });
+        if count <= LEAF_SIZE {
+            return index;
+        }
+
+        // разбиение по медиане центров вдоль самой длинной оси
+        let extents = aabb.max - aabb.min;
+        let axis = if extents.x >= extents.y && extents.x >= extents.z {
+            0
+        } else if extents.y >= extents.z {
+            1
+        } else {
+            2
+        };
+        let key = |triangle: &SceneTriangle| {
+            let [a, b, c] = triangle.vertexes;
+            let center = Vec3::from(a) + Vec3::from(b) + Vec3::from(c);
+            [center.x, center.y, center.z][axis]
+        };
+        let half = count / 2;
+        range.select_nth_unstable_by(half, |a, b| key(a).total_cmp(&key(b)));
+
+        Self::build(triangles, start, half, nodes);
+        let right = Self::build(triangles, start + half, count - half, nodes);
+        nodes[index].start = right;
+        nodes[index].count = 0;
+        index
+    }
+
+    /// Ближайшее пересечение луча с треугольниками не дальше `max_distance`.
+    ///
+    /// `filter` отбрасывает треугольники, которые не нужно учитывать.
+    pub fn intersect(
+        &self,
+        ray: &Line3,
+        max_distance: f32,
+        filter: impl Fn(&SceneTriangle) -> bool,
+    ) -> Option<TriangleHit> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        let mut nearest: Option<TriangleHit> = None;
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let node = self.nodes[index];
+            let limit = nearest.map_or(max_distance, |hit| hit.distance);
+            if !ray_hits_aabb(ray, &node.aabb, limit) {
+                continue;
+            }
+
+            if node.count == 0 {
+                stack.push(node.start);
+                stack.push(index + 1);
+                continue;
+            }
+
+            for triangle in node.start..node.start + node.count {
+                if !filter(&self.triangles[triangle]) {
+                    continue;
+                }
+                let [a, b, c] = self.triangles[triangle].vertexes;
+                let Some((distance, u, v)) = intersect_triangle(ray, a, b, c) else {
+                    continue;
+                };
+                let limit = nearest.map_or(max_distance, |hit| hit.distance);
+                if distance < limit {
+                    nearest = Some(TriangleHit {
+                        distance,
+                        triangle,
+                        u,
+                        v,
+                    });
+                }
+            }
+        }
+        nearest
+    }
+}
+
+/// Пересекает ли луч параллелепипед ближе `max_distance` (метод плит).
+fn ray_hits_aabb(ray: &Line3, aabb: &Aabb, max_distance: f32) -> bool {
+    let (mut t_min, mut t_max) = (0.0_f32, max_distance);
+    let axes = [
+        (ray.origin.x, ray.direction.x, aabb.min.x, aabb.max.x),
+        (ray.origin.y, ray.direction.y, aabb.min.y, aabb.max.y),
+        (ray.origin.z, ray.direction.z, aabb.min.z, aabb.max.z),
+    ];
+    for (origin, direction, min, max) in axes {
+        if direction.abs() < f32::EPSILON {
+            if origin < min || origin > max {
+                return false;
+            }
+            continue;
+        }
+        let (t1, t2) = ((min - origin) / direction, (max - origin) / direction);
+        t_min = t_min.max(t1.min(t2));
+        t_max = t_max.min(t1.max(t2));
+        if t_min > t_max {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod bvh_tests {
+    use super::*;
+    use crate::UVec3;
+
+    /// Ряд единичных треугольников в плоскости `z = i`.
+    fn triangle_stack(count: usize) -> Vec<SceneTriangle> {
+        (0..count)
+            .map(|i| {
+                let z = i as f32;
+                SceneTriangle {
+                    vertexes: [
+                        Point3::new(-1.0, -1.0, z),
+                        Point3::new(1.0, -1.0, z),
+                        Point3::new(0.0, 1.0, z),
+                    ],
+                    indexes: [0, 1, 2],
+                    texture_coords: None,
+                    model_index: i,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_nearest_hit_matches_brute_force() {
+        let bvh = Bvh::new(triangle_stack(37));
+        let ray = Line3::new(Point3::new(0.0, 0.0, 10.5), UVec3::new(0.0, 0.0, -1.0));
+
+        let hit = bvh.intersect(&ray, f32::INFINITY, |_| true).unwrap();
+        assert_eq!(bvh.triangles[hit.triangle].model_index, 10);
+        assert!((hit.distance - 0.5).abs() < 1e-5);
+
+        // отфильтрованные треугольники пропускаются
+        let hit = bvh
+            .intersect(&ray, f32::INFINITY, |triangle| triangle.model_index != 10)
+            .unwrap();
+        assert_eq!(bvh.triangles[hit.triangle].model_index, 9);
+
+        assert!(
+            bvh.intersect(&ray, 0.4, |_| true).is_none(),
+            "пересечения дальше предела не учитываются"
+        );
+    }
+
+    #[test]
+    fn test_ray_through_shared_edge_hits() {
+        // квадрат из двух треугольников с общей диагональю x + y = 1
+        let triangle = |vertexes| SceneTriangle {
+            vertexes,
+            indexes: [0, 1, 2],
+            texture_coords: None,
+            model_index: 0,
+        };
+        let bvh = Bvh::new(vec![
+            triangle([
+                Point3::new(0.0, 0.0, 0.0),
+                Point3::new(1.0, 0.0, 0.0),
+                Point3::new(0.0, 1.0, 0.0),
+            ]),
+            triangle([
+                Point3::new(1.0, 0.0, 0.0),
+                Point3::new(1.0, 1.0, 0.0),
+                Point3::new(0.0, 1.0, 0.0),
+            ]),
+        ]);
+
+        for i in 1..10 {
+            let x = i as f32 / 10.0;
+            let ray = Line3::new(Point3::new(x, 1.0 - x, 1.0), UVec3::new(0.0, 0.0, -1.0));
+            assert!(
+                bvh.intersect(&ray, f32::INFINITY, |_| true).is_some(),
+                "луч через общее ребро в x = {} проскочил между треугольниками",
+                x
+            );
+        }
+    }
+}
@@ -1,9 +1,12 @@
-use std::{fmt::Display, ops::Mul};
+use std::{f32::consts::FRAC_PI_4, fmt::Display, ops::Mul};
 
-use crate::{Canvas, CoordFrame, Line3, Point3, Transform3D, UVec3};
+use crate::{
+    Aabb, Canvas, CoordFrame, Line3, Plane, Point3, ProjectionError, Transform3D, UVec3, Vec3,
+};
 
 /// Камера в 3-х мерном пространстве.
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Camera {
     /// Координатная система камеры.
     ///
@@ -240,6 +243,32 @@ impl Camera {
         self.far_plane = far_plane.max(self.near_plane + 0.1);
     }
 
+    /// Устанавливает обе плоскости отсечения сразу.
+    ///
+    /// В отличие от последовательных `set_near_plane` и `set_far_plane`, не зависит
+    /// от текущего значения другой плоскости.
+    pub fn set_clip_planes(&mut self, near_plane: f32, far_plane: f32) {
+        debug_assert!(
+            0.0 < near_plane && near_plane < far_plane,
+            "ожидалось 0 < near {} < far {}",
+            near_plane,
+            far_plane
+        );
+
+        self.near_plane = near_plane;
+        self.far_plane = far_plane;
+    }
+
+    /// Возвращает ближнюю плоскость отсечения в **глобальных** координатах.
+    ///
+    /// Нормаль плоскости направлена от камеры, то есть в сторону видимой части пространства.
+    pub fn near_clip_plane(&self) -> Plane {
+        Plane::new(
+            self.get_position() + self.forward() * self.near_plane,
+            self.forward(),
+        )
+    }
+
     pub fn get_position(&self) -> Point3 {
         self.local_frame.origin
     }
@@ -337,6 +366,39 @@ impl Camera {
         (point - self.get_position()).length()
     }
 
+    /// Отодвинуть камеру вдоль её текущего направления так, чтобы параллелепипед `aabb`
+    /// целиком помещался в кадр при текущих угле обзора и соотношении сторон.
+    ///
+    /// Камера смотрит в центр `aabb`, а в кадр вписывается описанная вокруг него сфера,
+    /// увеличенная на долю `margin` (например, `0.1` - отступ в 10%). Если параллелепипед
+    /// не помещается до дальней плоскости отсечения, то она отодвигается.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::{Aabb, Camera, Point3};
+    ///
+    /// let mut camera = Camera::default();
+    /// let aabb = Aabb::new(Point3::new(9.0, -1.0, -1.0), Point3::new(11.0, 1.0, 1.0));
+    /// camera.frame_aabb(&aabb, 0.1);
+    ///
+    /// assert!(camera.get_position().x > 9.99 && camera.get_position().x < 10.01);
+    /// assert!(camera.get_position().z < -1.0);
+    /// ```
+    pub fn frame_aabb(&mut self, aabb: &Aabb, margin: f32) {
+        let center = aabb.center();
+        // точка или вырожденный параллелепипед всё равно должны оказаться перед камерой
+        let radius = (aabb.half_extents().length() * (1.0 + margin.max(0.0))).max(self.near_plane);
+        // сфера вписывается в меньший из вертикального и горизонтального углов обзора
+        let half_fov_y = self.fov / 2.0;
+        let half_fov_x = (half_fov_y.tan() * self.aspect_ratio).atan();
+        let distance = radius / half_fov_y.min(half_fov_x).sin();
+
+        self.set_position(center + self.backward() * distance);
+        if self.far_plane < distance + radius {
+            self.set_far_plane(distance + radius);
+        }
+    }
+
     /// Получить матрицу преобразования из локальных координат камеры в экранные (viewport, он же canvas)
     ///
     /// То есть, матрица производит следующие операции:
@@ -346,28 +408,62 @@ impl Camera {
         projection_type: ProjectionType,
         canvas: &Canvas,
     ) -> Transform3D {
+        match self.try_camera_to_screen_transform(projection_type, canvas) {
+            Ok(transform) => transform,
+            Err(_error) => {
+                // Например, canvas нулевого размера при запуске приложения.
+                #[cfg(debug_assertions)]
+                eprintln!(
+                    "Warning: не удалось построить матрицу проекции ({}), используется единичная",
+                    _error
+                );
+                Transform3D::identity()
+            }
+        }
+    }
+
+    /// Проверенный вариант преобразования из локальных координат камеры в экранные.
+    ///
+    /// Возвращает ошибку, если параметры камеры некорректны или canvas имеет нулевой размер.
+    pub fn try_camera_to_screen_transform(
+        &self,
+        projection_type: ProjectionType,
+        canvas: &Canvas,
+    ) -> Result<Transform3D, ProjectionError> {
+        if canvas.width() == 0 || canvas.height() == 0 {
+            return Err(ProjectionError::DegenerateViewport);
+        }
+
         // Матрица проекции координат камеры в NDC
         let proj_matrix = match projection_type {
-            ProjectionType::Parallel => Transform3D::parallel_from_fov(
+            ProjectionType::Parallel => Transform3D::try_parallel_from_fov(
                 self.get_fov(),
                 self.get_aspect_ratio(),
                 self.get_near_plane(),
                 self.get_far_plane(),
-            ),
-            ProjectionType::Perspective => Transform3D::perspective(
+            )?,
+            ProjectionType::Perspective => Transform3D::try_perspective(
                 self.get_fov(),
                 self.get_aspect_ratio(),
                 self.get_near_plane(),
                 self.get_far_plane(),
-            ),
+            )?,
+            ProjectionType::Axonometric(axonometry) => axonometry
+                .camera_transform(self.axonometric_pivot_depth())
+                .multiply(Transform3D::try_parallel_from_fov(
+                    self.get_fov(),
+                    self.get_aspect_ratio(),
+                    self.get_near_plane(),
+                    self.get_far_plane(),
+                )?),
         };
 
         let scale_x = canvas.width() as f32 / 2.0; // растянуть NDC по ширине
         let scale_y = canvas.height() as f32 / 2.0; // растянуть NDC по высоте
 
-        proj_matrix // вот тут получается NDC [-1, 1]
+        Ok(proj_matrix // вот тут получается NDC [-1, 1]
             .multiply(Transform3D::translation(-1.0, 1.0, 0.0))
-            .multiply(Transform3D::scale(-scale_x, scale_y, 1.0)) // теперь экранные
+            .multiply(Transform3D::scale(-scale_x, scale_y, 1.0))) // теперь экранные
     }
 
     /// Получить матрицу преобразования из глобальных координат в экранные (viewport, он же canvas)
@@ -383,6 +479,77 @@ impl Camera {
         to_camera_transform.multiply(self.camera_to_screen_transform(projection_type, canvas))
     }
 
+    /// Направление проецирования в **глобальных** координатах.
+    ///
+    /// Для параллельных проекций все точки проецируются вдоль одного направления: для
+    /// ортографической оно совпадает с направлением камеры, для аксонометрической учитывает
+    /// поворот или сдвиг осей. Для перспективной проекции направление зависит от точки,
+    /// поэтому возвращается `None`.
+    pub fn projection_direction(&self, projection_type: ProjectionType) -> Option<UVec3> {
+        match projection_type {
+            ProjectionType::Parallel => Some(self.get_direction()),
+            ProjectionType::Perspective => None,
+            ProjectionType::Axonometric(axonometry) => {
+                // направление, которое после преобразования осей смотрит вдоль -z камеры
+                let inverse = axonometry.camera_transform(0.0).inverse()?;
+                let local = UVec3::new(0.0, 0.0, -1.0).apply_transform(inverse).ok()?;
+                local
+                    .apply_transform(self.local_frame.local_to_global_matrix())
+                    .ok()
+            }
+        }
+    }
+
+    /// Углы области видимости (frustum) камеры в **глобальных** координатах.
+    ///
+    /// Первые 4 точки - углы ближней плоскости отсечения, следующие 4 - дальней, в порядке:
+    /// левый нижний, правый нижний, правый верхний, левый верхний. Для перспективной проекции
+    /// это усечённая пирамида, для параллельной - прямоугольный параллелепипед с сечением ближней
+    /// плоскости, а для аксонометрии - параллелепипед с учётом поворота или сдвига осей.
+    pub fn frustum_corners(&self, projection_type: ProjectionType) -> [Point3; 8] {
+        let tan_half_fov = (self.fov / 2.0).tan();
+        // половина высоты сечения на глубине depth
+        let half_height = |depth: f32| match projection_type {
+            ProjectionType::Perspective => depth * tan_half_fov,
+            ProjectionType::Parallel | ProjectionType::Axonometric(_) => {
+                self.near_plane * tan_half_fov
+            }
+        };
+
+        // в координатах камеры обзор вдоль -z, а право камеры - это -x
+        let mut corners = [Point3::zero(); 8];
+        for (i, depth) in [self.near_plane, self.far_plane].into_iter().enumerate() {
+            let half_height = half_height(depth);
+            let half_width = half_height * self.aspect_ratio;
+            for (j, (right, top)) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+                .into_iter()
+                .enumerate()
+            {
+                corners[i * 4 + j] = Point3::new(-right * half_width, top * half_height, -depth);
+            }
+        }
+
+        // аксонометрия поворачивает оси перед параллельной проекцией, область видимости - обратно
+        let mut to_global = self.local_frame.local_to_global_matrix();
+        if let ProjectionType::Axonometric(axonometry) = projection_type
+            && let Some(inverse) = axonometry
+                .camera_transform(self.axonometric_pivot_depth())
+                .inverse()
+        {
+            to_global = inverse.multiply(to_global);
+        }
+        corners.map(|corner| corner.apply_transform(to_global).unwrap())
+    }
+
+    /// Глубина точки на оси обзора, вокруг которой поворачиваются оси в аксонометрии.
+    ///
+    /// Это проекция начала глобальных координат на ось обзора, ограниченная плоскостями отсечения,
+    /// поэтому при смене пресета центр сцены остаётся на месте.
+    fn axonometric_pivot_depth(&self) -> f32 {
+        let depth = (Point3::zero() - self.get_position()).dot(Vec3::from(self.get_direction()));
+        depth.clamp(self.get_near_plane(), self.get_far_plane())
+    }
+
     /// Возвращает матрицу преобразований из экранных координат в локальные координаты камеры.
     pub fn screen_to_camera_transform(
         &self,
@@ -419,8 +586,8 @@ pub enum ProjectionType {
     Parallel,
     /// Перспективная проекция.
     Perspective,
-    // /// Аксонометрическая проекция.
-    // Axonimetrix,
+    /// Аксонометрическая проекция: параллельная проекция после поворота или сдвига осей.
+    Axonometric(Axonometry),
 }
 
 impl Display for ProjectionType {
@@ -428,6 +595,109 @@ impl Display for ProjectionType {
         match self {
             Self::Parallel => f.write_str("Параллельная"),
             Self::Perspective => f.write_str("Перспективная"),
+            Self::Axonometric(axonometry) => axonometry.fmt(f),
+        }
+    }
+}
+
+/// Вид аксонометрической проекции.
+///
+/// Прямоугольные аксонометрии (изометрия, диметрия, триметрия) поворачивают оси камеры
+/// сначала вокруг вертикальной оси на `yaw`, затем вокруг горизонтальной на `tilt`.
+/// Косоугольные (кавальерная и кабинетная) сдвигают ось глубины на экране под углом `angle`
+/// к горизонтали. Все углы в радианах.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Axonometry {
+    /// Изометрия: все три оси сокращаются одинаково.
+    Isometric,
+    /// Диметрия: поворот на 45° и наклон на `tilt`, две оси сокращаются одинаково.
+    Dimetric { tilt: f32 },
+    /// Триметрия: произвольный поворот, все оси сокращаются по-разному.
+    Trimetric { yaw: f32, tilt: f32 },
+    /// Кавальерная проекция: ось глубины не сокращается.
+    Cavalier { angle: f32 },
+    /// Кабинетная проекция: ось глубины сокращается вдвое.
+    Cabinet { angle: f32 },
+}
+
+impl Axonometry {
+    /// Наклон изометрии `asin(tan(30°))`, при котором все оси сокращаются одинаково.
+    pub const ISOMETRIC_TILT: f32 = 0.615_479_7;
+    /// Наклон диметрии по умолчанию `asin(1 / (2√2))` ≈ 20.7°.
+    pub const DIMETRIC_TILT: f32 = 0.361_367_1;
+
+    /// Все виды аксонометрии с углами по умолчанию.
+    pub fn presets() -> [Self; 5] {
+        [
+            Self::Isometric,
+            Self::Dimetric {
+                tilt: Self::DIMETRIC_TILT,
+            },
+            Self::Trimetric {
+                yaw: 30.0_f32.to_radians(),
+                tilt: 20.0_f32.to_radians(),
+            },
+            Self::Cavalier { angle: FRAC_PI_4 },
+            Self::Cabinet { angle: FRAC_PI_4 },
+        ]
+    }
+
+    /// Преобразование координат камеры, задающее аксонометрию.
+    ///
+    /// Применяется перед параллельной проекцией. Оси поворачиваются (или сдвигаются) вокруг
+    /// точки на оси обзора на глубине `pivot_depth`, поэтому она остаётся на месте экрана.
+    pub fn camera_transform(&self, pivot_depth: f32) -> Transform3D {
+        let axes = match *self {
+            Self::Isometric => Self::rotation(FRAC_PI_4, Self::ISOMETRIC_TILT),
+            Self::Dimetric { tilt } => Self::rotation(FRAC_PI_4, tilt),
+            Self::Trimetric { yaw, tilt } => Self::rotation(yaw, tilt),
+            Self::Cavalier { angle } => Self::oblique(angle, 1.0),
+            Self::Cabinet { angle } => Self::oblique(angle, 0.5),
+        };
+
+        // камера смотрит вдоль -z, поэтому точка поворота лежит на глубине -pivot_depth
+        Transform3D::translation(0.0, 0.0, pivot_depth)
+            .multiply(axes)
+            .multiply(Transform3D::translation(0.0, 0.0, -pivot_depth))
+    }
+
+    /// Поворот осей для прямоугольной аксонометрии.
+    fn rotation(yaw: f32, tilt: f32) -> Transform3D {
+        Transform3D::rotation_y_rad(yaw).multiply(Transform3D::rotation_x_rad(tilt))
+    }
+
+    /// Сдвиг оси глубины для косоугольной проекции с коэффициентом сокращения `factor`.
+    fn oblique(angle: f32, factor: f32) -> Transform3D {
+        let (sin, cos) = angle.sin_cos();
+        Transform3D::new([
+            1.0,
+            0.0,
+            0.0,
+            0.0, // первая строка
+            0.0,
+            1.0,
+            0.0,
+            0.0, // вторая строка
+            factor * cos,
+            factor * sin,
+            1.0,
+            0.0, // третья строка
+            0.0,
+            0.0,
+            0.0,
+            1.0, // перемещение
+        ])
+    }
+}
+
+impl Display for Axonometry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Isometric => f.write_str("Изометрическая"),
+            Self::Dimetric { .. } => f.write_str("Диметрическая"),
+            Self::Trimetric { .. } => f.write_str("Триметрическая"),
+            Self::Cavalier { .. } => f.write_str("Кавальерная"),
+            Self::Cabinet { .. } => f.write_str("Кабинетная"),
         }
     }
 }
@@ -526,6 +796,74 @@ mod camera_tests {
         assert!(camera_space_front.z < 0.0); // Should be in front (negative Z in camera space)
     }
 
+    #[test]
+    fn test_camera_view_matches_look_at() {
+        let position = Point3::new(2.0, 3.0, -4.0);
+        let target = Point3::new(0.0, 1.0, 0.0);
+        let direction = (target - position).normalize().unwrap();
+        let camera = Camera::new(position, direction, UVec3::up(), PI / 3.0, 1.0, 0.1, 100.0);
+
+        let view = camera.local_frame.global_to_local_matrix();
+        let look_at = Transform3D::look_at(position, target, UVec3::up());
+        for (got, expected) in view.m.iter().zip(look_at.m) {
+            assert!(
+                (got - expected).abs() < 1e-5,
+                "матрица вида камеры должна совпадать с look_at: {} != {}",
+                got,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_frustum_corners_project_to_screen_corners() {
+        let camera = Camera::new(
+            Point3::new(3.0, 2.0, -6.0),
+            Vec3::new(-0.3, -0.2, 1.0).normalize().unwrap(),
+            UVec3::up(),
+            50_f32.to_radians(),
+            16.0 / 9.0,
+            2.0,
+            30.0,
+        );
+        let canvas = Canvas::new(160, 90);
+
+        for projection_type in [
+            ProjectionType::Perspective,
+            ProjectionType::Parallel,
+            ProjectionType::Axonometric(Axonometry::Isometric),
+        ] {
+            let transform = camera.global_to_screen_transform(projection_type, &canvas);
+            let corners = camera.frustum_corners(projection_type);
+            let screen: Vec<Point3> = corners
+                .iter()
+                .map(|corner| corner.apply_transform(transform).unwrap())
+                .collect();
+            for i in 0..4 {
+                for point in [screen[i], screen[i + 4]] {
+                    let x = if i == 0 || i == 3 { 0.0 } else { 160.0 };
+                    // холст переворачивается по вертикали только в конце рендера
+                    let y = if i < 2 { 0.0 } else { 90.0 };
+                    assert!(
+                        (point.x - x).abs() < 1e-2 && (point.y - y).abs() < 1e-2,
+                        "{}: угол {} попал в {:?}",
+                        projection_type,
+                        i,
+                        point
+                    );
+                }
+            }
+            // ближняя и дальняя плоскости различаются по глубине
+            assert!((screen[0].z - screen[4].z).abs() > TOLERANCE);
+        }
+
+        // углы ближней плоскости лежат на ней
+        let near = camera.near_clip_plane();
+        for corner in &camera.frustum_corners(ProjectionType::Perspective)[..4] {
+            assert!(near.distance_to_point(*corner).abs() < 1e-3);
+        }
+    }
+
     #[test]
     fn test_camera_direction_vectors_orthonormal() {
         let camera = Camera::default();
@@ -821,6 +1159,32 @@ mod camera_tests {
         camera.set_near_plane(-1.0); // Should panic for negative near plane
     }
 
+    #[test]
+    fn test_camera_degenerate_canvas_fallback() {
+        let camera = Camera::default();
+        let canvas = Canvas::new(0, 10);
+
+        assert_eq!(
+            camera
+                .try_camera_to_screen_transform(ProjectionType::Perspective, &canvas)
+                .unwrap_err(),
+            ProjectionError::DegenerateViewport,
+            "canvas нулевой ширины должен давать ошибку"
+        );
+        assert_eq!(
+            camera.camera_to_screen_transform(ProjectionType::Perspective, &canvas),
+            Transform3D::identity(),
+            "при вырожденном canvas должна использоваться единичная матрица"
+        );
+
+        let canvas = Canvas::new(100, 50);
+        assert!(
+            camera
+                .try_camera_to_screen_transform(ProjectionType::Parallel, &canvas)
+                .is_ok()
+        );
+    }
+
     // ========================================
     // Движение камеры
     // ========================================
@@ -892,4 +1256,110 @@ mod camera_tests {
         assert_uvecs(camera.right(), UVec3::right(), TOLERANCE);
         assert_uvecs(camera.up(), UVec3::backward(), TOLERANCE);
     }
+
+    /// Длины проекций единичных глобальных осей на экран.
+    fn projected_axis_lengths(camera: &Camera, projection_type: ProjectionType) -> [f32; 3] {
+        let canvas = Canvas::new(900, 600);
+        let transform = camera.global_to_screen_transform(projection_type, &canvas);
+        let origin = Point3::zero().apply_transform(transform).unwrap();
+        [
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+        ]
+        .map(|axis| {
+            let end = (Point3::zero() + axis).apply_transform(transform).unwrap();
+            ((end.x - origin.x).powi(2) + (end.y - origin.y).powi(2)).sqrt()
+        })
+    }
+
+    #[test]
+    fn test_axonometric_presets() {
+        // соотношение сторон холста, чтобы пиксели были квадратными
+        let mut camera = Camera::default();
+        camera.set_aspect_ratio(1.5);
+        let canvas = Canvas::new(900, 600);
+
+        // центр сцены остаётся в центре экрана
+        for axonometry in Axonometry::presets() {
+            let transform =
+                camera.global_to_screen_transform(ProjectionType::Axonometric(axonometry), &canvas);
+            let center = Point3::zero().apply_transform(transform).unwrap();
+            assert!((center.x - 450.0).abs() < 1e-2 && (center.y - 300.0).abs() < 1e-2);
+        }
+
+        // в изометрии все оси сокращаются одинаково
+        let [x, y, z] =
+            projected_axis_lengths(&camera, ProjectionType::Axonometric(Axonometry::Isometric));
+        assert!(
+            (x - y).abs() < 1e-2 * x && (y - z).abs() < 1e-2 * x,
+            "оси изометрии должны быть равны: {} {} {}",
+            x,
+            y,
+            z
+        );
+
+        // в диметрии горизонтальные оси равны, вертикальная отличается
+        let dimetric = Axonometry::Dimetric {
+            tilt: Axonometry::DIMETRIC_TILT,
+        };
+        let [x, y, z] = projected_axis_lengths(&camera, ProjectionType::Axonometric(dimetric));
+        assert!((x - z).abs() < 1e-2 * x);
+        assert!((x - y).abs() > 1e-2 * x);
+
+        // в косоугольных проекциях ось глубины (направление камеры) сдвигается на экране
+        let angle = FRAC_PI_4;
+        let [x, y, z] = projected_axis_lengths(
+            &camera,
+            ProjectionType::Axonometric(Axonometry::Cavalier { angle }),
+        );
+        assert!((x - y).abs() < 1e-2 * x && (x - z).abs() < 1e-2 * x);
+        let [x, _, z] = projected_axis_lengths(
+            &camera,
+            ProjectionType::Axonometric(Axonometry::Cabinet { angle }),
+        );
+        assert!(
+            (z - x * 0.5).abs() < 1e-2 * x,
+            "ось глубины {} должна быть вдвое короче {}",
+            z,
+            x
+        );
+    }
+
+    #[test]
+    fn test_projection_direction() {
+        let camera = Camera::default();
+        let canvas = Canvas::new(900, 600);
+        assert_eq!(
+            camera.projection_direction(ProjectionType::Perspective),
+            None
+        );
+        assert_uvecs(
+            camera
+                .projection_direction(ProjectionType::Parallel)
+                .unwrap(),
+            camera.get_direction(),
+            TOLERANCE,
+        );
+
+        // точки вдоль направления проецирования попадают в один пиксель
+        for axonometry in Axonometry::presets() {
+            let projection_type = ProjectionType::Axonometric(axonometry);
+            let direction = Vec3::from(camera.projection_direction(projection_type).unwrap());
+            let transform = camera.global_to_screen_transform(projection_type, &canvas);
+            let near = Point3::new(1.0, 2.0, 0.0)
+                .apply_transform(transform)
+                .unwrap();
+            let far = (Point3::new(1.0, 2.0, 0.0) + direction * 5.0)
+                .apply_transform(transform)
+                .unwrap();
+            assert!(
+                (near.x - far.x).abs() < 1e-2 && (near.y - far.y).abs() < 1e-2,
+                "{}: {} и {} должны совпадать на экране",
+                axonometry,
+                near,
+                far
+            );
+        }
+    }
 }
@@ -0,0 +1,322 @@
+//! Анимация камеры по ключевым кадрам.
+
+use crate::{Camera, Point3, Vec3, library::utils};
+
+/// Способ интерполяции положения камеры между ключевыми кадрами.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub enum TrackInterpolation {
+    /// Линейная интерполяция, траектория - ломаная через ключевые точки.
+    Linear,
+    /// Сплайн Катмулла-Рома, траектория - гладкая кривая через ключевые точки.
+    #[default]
+    CatmullRom,
+}
+
+/// Ключевой кадр траектории камеры.
+#[derive(Debug, Clone, Copy)]
+pub struct CameraKeyframe {
+    /// Время кадра (в секундах или любых других единицах).
+    pub time: f32,
+    /// Состояние камеры в этот момент времени.
+    pub camera: Camera,
+}
+
+impl CameraKeyframe {
+    pub fn new(time: f32, camera: Camera) -> Self {
+        Self { time, camera }
+    }
+}
+
+/// Траектория камеры, проходящая через ключевые кадры.
+///
+/// Положение камеры интерполируется линейно или сплайном, ориентация (направление обзора
+/// и направление вверх) - сферической интерполяцией векторов. Угол обзора и плоскости
+/// отсечения интерполируются линейно, соотношение сторон берётся из предыдущего кадра.
+#[derive(Debug, Clone, Default)]
+pub struct CameraTrack {
+    /// Ключевые кадры, отсортированные по времени.
+    keyframes: Vec<CameraKeyframe>,
+    /// Способ интерполяции положения.
+    pub interpolation: TrackInterpolation,
+}
+
+impl CameraTrack {
+    /// Создать пустую траекторию.
+    pub fn new(interpolation: TrackInterpolation) -> Self {
+        Self {
+            keyframes: Vec::new(),
+            interpolation,
+        }
+    }
+
+    /// Добавить ключевой кадр. Кадр с уже существующим временем заменяется.
+    pub fn add_keyframe(&mut self, time: f32, camera: Camera) {
+        debug_assert!(
+            time.is_finite(),
+            "время кадра {} должно быть конечным",
+            time
+        );
+
+        let keyframe = CameraKeyframe::new(time, camera);
+        match self
+            .keyframes
+            .binary_search_by(|other| other.time.total_cmp(&time))
+        {
+            Ok(index) => self.keyframes[index] = keyframe,
+            Err(index) => self.keyframes.insert(index, keyframe),
+        }
+    }
+
+    /// Удалить ключевой кадр по индексу.
+    pub fn remove_keyframe(&mut self, index: usize) -> CameraKeyframe {
+        self.keyframes.remove(index)
+    }
+
+    /// Ключевые кадры, отсортированные по времени.
+    pub fn keyframes(&self) -> &[CameraKeyframe] {
+        &self.keyframes
+    }
+
+    /// Количество ключевых кадров.
+    pub fn len(&self) -> usize {
+        self.keyframes.len()
+    }
+
+    /// Нет ни одного ключевого кадра?
+    pub fn is_empty(&self) -> bool {
+        self.keyframes.is_empty()
+    }
+
+    /// Время первого ключевого кадра.
+    pub fn start_time(&self) -> Option<f32> {
+        self.keyframes.first().map(|keyframe| keyframe.time)
+    }
+
+    /// Время последнего ключевого кадра.
+    pub fn end_time(&self) -> Option<f32> {
+        self.keyframes.last().map(|keyframe| keyframe.time)
+    }
+
+    /// Длительность траектории.
+    pub fn duration(&self) -> f32 {
+        match (self.start_time(), self.end_time()) {
+            (Some(start), Some(end)) => end - start,
+            _ => 0.0,
+        }
+    }
+
+    /// Получить камеру в момент времени `time`.
+    ///
+    /// Вне диапазона времени траектории возвращается первый или последний кадр.
+    /// Возвращает `None`, если в траектории нет ключевых кадров.
+    pub fn sample(&self, time: f32) -> Option<Camera> {
+        let first = self.keyframes.first()?;
+        let last = self.keyframes.last()?;
+        if time <= first.time {
+            return Some(first.camera);
+        }
+        if time >= last.time {
+            return Some(last.camera);
+        }
+
+        // сегмент [i, i + 1], в котором находится time
+        let i = self
+            .keyframes
+            .partition_point(|keyframe| keyframe.time <= time)
+            - 1;
+        let (from, to) = (&self.keyframes[i], &self.keyframes[i + 1]);
+        let t = (time - from.time) / (to.time - from.time);
+
+        let position = match self.interpolation {
+            TrackInterpolation::Linear => {
+                let (p1, p2) = (from.camera.get_position(), to.camera.get_position());
+                p1 + (p2 - p1) * t
+            }
+            TrackInterpolation::CatmullRom => {
+                let p0 = self.keyframes[i.saturating_sub(1)].camera.get_position();
+                let p3 = self.keyframes[(i + 2).min(self.keyframes.len() - 1)]
+                    .camera
+                    .get_position();
+                catmull_rom(
+                    p0,
+                    from.camera.get_position(),
+                    to.camera.get_position(),
+                    p3,
+                    t,
+                )
+            }
+        };
+
+        Some(interpolate_camera(&from.camera, &to.camera, position, t))
+    }
+
+    /// Получить `frame_count` камер, равномерно распределённых по траектории.
+    ///
+    /// Первая и последняя камеры совпадают с первым и последним ключевыми кадрами.
+    pub fn sample_frames(&self, frame_count: usize) -> Vec<Camera> {
+        let (Some(start), Some(end)) = (self.start_time(), self.end_time()) else {
+            return Vec::new();
+        };
+
+        (0..frame_count)
+            .filter_map(|frame| {
+                let t = if frame_count > 1 {
+                    frame as f32 / (frame_count - 1) as f32
+                } else {
+                    0.0
+                };
+                self.sample(utils::lerp_float(start, end, t))
+            })
+            .collect()
+    }
+}
+
+/// Интерполяция параметров камеры между двумя кадрами, положение уже вычислено.
+fn interpolate_camera(from: &Camera, to: &Camera, position: Point3, t: f32) -> Camera {
+    let direction = from.get_direction().slerp(to.get_direction(), t);
+    let up = from.up().slerp(to.up(), t);
+    // направление вверх должно остаться перпендикулярным направлению обзора
+    let up = up
+        .rejection(direction)
+        .normalize()
+        .unwrap_or_else(|_| from.up());
+
+    let near = utils::lerp_float(from.get_near_plane(), to.get_near_plane(), t);
+    let far = utils::lerp_float(from.get_far_plane(), to.get_far_plane(), t);
+    Camera::new(
+        position,
+        direction,
+        up,
+        utils::lerp_float(from.get_fov(), to.get_fov(), t),
+        from.get_aspect_ratio(),
+        near,
+        far.max(near + 0.1),
+    )
+}
+
+/// Точка на сплайне Катмулла-Рома между `p1` и `p2`.
+fn catmull_rom(p0: Point3, p1: Point3, p2: Point3, p3: Point3, t: f32) -> Point3 {
+    let (p0, p1, p2, p3) = (
+        Vec3::from(p0),
+        Vec3::from(p1),
+        Vec3::from(p2),
+        Vec3::from(p3),
+    );
+    let t2 = t * t;
+    let t3 = t2 * t;
+
+    let result = (p1 * 2.0
+        + (p2 - p0) * t
+        + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2
+        + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3)
+        * 0.5;
+    Point3::from(result)
+}
+
+#[cfg(test)]
+mod camera_track_tests {
+    use super::*;
+    use crate::UVec3;
+
+    const TOLERANCE: f32 = 1e-4;
+
+    fn camera_at(position: Point3, direction: UVec3) -> Camera {
+        Camera::new(
+            position,
+            direction,
+            UVec3::up(),
+            60.0_f32.to_radians(),
+            16.0 / 9.0,
+            1.0,
+            100.0,
+        )
+    }
+
+    fn simple_track(interpolation: TrackInterpolation) -> CameraTrack {
+        let mut track = CameraTrack::new(interpolation);
+        track.add_keyframe(2.0, camera_at(Point3::new(10.0, 0.0, 0.0), UVec3::plus_x()));
+        track.add_keyframe(0.0, camera_at(Point3::zero(), UVec3::plus_z()));
+        track
+    }
+
+    #[test]
+    fn test_keyframes_sorted_and_replaced() {
+        let mut track = simple_track(TrackInterpolation::Linear);
+        assert_eq!(track.len(), 2);
+        assert_eq!(track.start_time(), Some(0.0));
+        assert_eq!(track.end_time(), Some(2.0));
+
+        track.add_keyframe(2.0, camera_at(Point3::zero(), UVec3::plus_x()));
+        assert_eq!(track.len(), 2, "кадр с тем же временем должен заменяться");
+        assert!(
+            track.keyframes()[1]
+                .camera
+                .get_position()
+                .approx_equal(Point3::zero(), TOLERANCE)
+        );
+    }
+
+    #[test]
+    fn test_sample_endpoints_and_clamp() {
+        let track = simple_track(TrackInterpolation::CatmullRom);
+        assert!(CameraTrack::default().sample(0.0).is_none());
+
+        let start = track.sample(-1.0).unwrap();
+        assert!(start.get_position().approx_equal(Point3::zero(), TOLERANCE));
+        let end = track.sample(5.0).unwrap();
+        assert!(
+            end.get_position()
+                .approx_equal(Point3::new(10.0, 0.0, 0.0), TOLERANCE)
+        );
+        assert!(end.get_direction().approx_equal(UVec3::plus_x(), TOLERANCE));
+    }
+
+    #[test]
+    fn test_sample_middle() {
+        let track = simple_track(TrackInterpolation::Linear);
+        let middle = track.sample(1.0).unwrap();
+
+        assert!(
+            middle
+                .get_position()
+                .approx_equal(Point3::new(5.0, 0.0, 0.0), TOLERANCE),
+            "ожидалась середина отрезка, но получено {}",
+            middle.get_position()
+        );
+        // направление повёрнуто ровно на половину угла
+        let expected = UVec3::new(1.0, 0.0, 1.0);
+        assert!(
+            middle.get_direction().approx_equal(expected, TOLERANCE),
+            "ожидалось направление {:?}, но получено {:?}",
+            expected,
+            middle.get_direction()
+        );
+        assert!(
+            middle.up().dot(middle.get_direction()).abs() < TOLERANCE,
+            "направление вверх должно быть перпендикулярно направлению обзора"
+        );
+    }
+
+    #[test]
+    fn test_catmull_rom_passes_through_keyframes() {
+        let mut track = CameraTrack::new(TrackInterpolation::CatmullRom);
+        let points = [
+            Point3::zero(),
+            Point3::new(5.0, 5.0, 0.0),
+            Point3::new(10.0, 0.0, 0.0),
+        ];
+        for (i, point) in points.iter().enumerate() {
+            track.add_keyframe(i as f32, camera_at(*point, UVec3::plus_z()));
+        }
+
+        let middle = track.sample(1.0).unwrap();
+        assert!(
+            middle.get_position().approx_equal(points[1], TOLERANCE),
+            "сплайн должен проходить через ключевые точки"
+        );
+
+        let frames = track.sample_frames(5);
+        assert_eq!(frames.len(), 5);
+        assert!(frames[4].get_position().approx_equal(points[2], TOLERANCE));
+    }
+}
@@ -1,8 +1,9 @@
 //! Реализация холста для 2D рисования.
 
-use egui::{Color32, ColorImage, Pos2, Vec2};
+use crate::{HdrOptions, Point3, Vec3, library::utils};
+use egui::{Color32, ColorImage, Pos2, Rect, Vec2};
 // use std::collections::VecDeque;
-use std::ops::{Index, IndexMut};
+use std::ops::{Index, IndexMut, Range};
 
 /// Холст для рисования 2D объектов.
 ///
@@ -13,28 +14,118 @@ pub struct Canvas {
     pixels: Vec<egui::Color32>,
     /// z-buffer для помощи в отрисовке.
     buffer: Vec<f32>,
+    /// Количество фрагментов, записанных в каждый пиксель за кадр (сложность глубины).
+    fragment_counts: Vec<u32>,
+    /// Количество фрагментов, не прошедших проверку z-буфера с последней очистки холста.
+    depth_rejections: u64,
+    /// Непрозрачность записываемых фрагментов полигонов.
+    opacity: f32,
+    /// Обновлять ли z-буфер при записи фрагментов.
+    depth_write: bool,
+    /// Условие прохождения проверки глубины.
+    depth_func: DepthFunc,
+    /// Область отсечения (scissor), заданная пользователем, если есть.
+    scissor: Option<Rect>,
+    /// Пиксели области отсечения по x и y: весь холст, если она не задана.
+    scissor_bounds: (Range<usize>, Range<usize>),
+    /// Буфер идентификаторов (индексов моделей) пикселей, если включён.
+    ids: Option<Vec<u32>>,
+    /// Идентификатор, записываемый вместе с фрагментами и линиями.
+    current_id: Option<u32>,
+    /// Линейные HDR-цвета пикселей, если включён HDR-режим.
+    hdr: Option<Vec<Vec3>>,
+    /// Тональная компрессия HDR-цветов в пиксели.
+    hdr_options: HdrOptions,
     width: usize,
     height: usize,
 }
 
+/// Условие прохождения проверки глубины фрагментом.
+///
+/// Значения z-буфера растут по направлению к камере, поэтому условия сформулированы
+/// через расстояние до камеры, а не через сами значения.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepthFunc {
+    /// Фрагмент проходит, если он ближе записанного.
+    #[default]
+    Less,
+    /// Фрагмент проходит, если он ближе записанного или на том же расстоянии.
+    LEqual,
+    /// Фрагмент проходит, если он дальше записанного. Видны самые дальние поверхности.
+    Greater,
+}
+
+impl DepthFunc {
+    /// Проходит ли фрагмент глубины `z` проверку против записанной глубины `stored`.
+    pub fn test(self, z: f32, stored: f32) -> bool {
+        match self {
+            Self::Less => z > stored,
+            Self::LEqual => z >= stored,
+            Self::Greater => z < stored,
+        }
+    }
+
+    /// Значение, которым очищается z-буфер: его проходит любой фрагмент.
+    pub fn clear_value(self) -> f32 {
+        match self {
+            Self::Less | Self::LEqual => f32::MIN,
+            Self::Greater => f32::MAX,
+        }
+    }
+}
+
+impl std::fmt::Display for DepthFunc {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Less => f.write_str("Ближе"),
+            Self::LEqual => f.write_str("Ближе или равно"),
+            Self::Greater => f.write_str("Дальше"),
+        }
+    }
+}
+
 impl Default for Canvas {
     fn default() -> Self {
         Self::new(900, 600)
     }
 }
 
+/// Форма концов толстой линии, см. [`Canvas::draw_depth_tested_thick_line`].
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineCap {
+    /// Линия обрывается точно в конечных точках.
+    #[default]
+    Butt,
+    /// Линия продлевается за конечные точки на половину толщины.
+    Square,
+    /// Концы линии скругляются полукругом диаметром в толщину линии.
+    Round,
+}
+
 // --------------------------------------------------
 // Создание и базовые методы
 // --------------------------------------------------
 
 impl Canvas {
+    /// Создать холст указанного размера.
+    ///
+    /// Холст нулевого размера допустим (например, окно ещё не получило размер при запуске):
+    /// на нём ничего не рисуется, а камера использует для него единичную матрицу проекции.
     pub fn new(width: usize, height: usize) -> Self {
-        debug_assert!(width > 0, "ширина холста не может быть нулевой");
-        debug_assert!(height > 0, "высота холста не может быть нулевой");
-
         Self {
             pixels: vec![Color32::GRAY; width * height],
             buffer: vec![f32::MIN; width * height],
+            fragment_counts: vec![0; width * height],
+            depth_rejections: 0,
+            opacity: 1.0,
+            depth_write: true,
+            depth_func: DepthFunc::default(),
+            scissor: None,
+            scissor_bounds: (0..width, 0..height),
+            ids: None,
+            current_id: None,
+            hdr: None,
+            hdr_options: HdrOptions::default(),
             width,
             height,
         }
@@ -74,20 +165,56 @@ impl Canvas {
         self.height
     }
 
-    /// Заполнить весь холст указанным цветом и очистить z-буфер.
+    /// Пиксели холста построчно.
+    pub fn pixels(&self) -> &[Color32] {
+        &self.pixels
+    }
+
+    /// Значения z-буфера построчно.
+    pub fn depth_buffer(&self) -> &[f32] {
+        &self.buffer
+    }
+
+    /// Заполнить весь холст (или его область отсечения) указанным цветом, очистить z-буфер
+    /// и счётчики фрагментов.
     pub fn clear(&mut self, color: Color32) {
-        self.pixels.fill(color);
+        self.fill(color);
         self.clear_z_buffer();
+        self.depth_rejections = 0;
+        let hdr_color = self.hdr_options.linearize(color);
+        for row in self.scissor_rows() {
+            self.fragment_counts[row.clone()].fill(0);
+            if let Some(ids) = &mut self.ids {
+                ids[row.clone()].fill(NO_ID);
+            }
+            if let Some(hdr) = &mut self.hdr {
+                hdr[row].fill(hdr_color);
+            }
+        }
     }
 
-    /// Очистить z-буфер.
+    /// Заполнить весь холст (или его область отсечения) указанным цветом, не трогая z-буфер
+    /// и счётчики фрагментов.
+    pub fn fill(&mut self, color: Color32) {
+        for row in self.scissor_rows() {
+            self.pixels[row].fill(color);
+        }
+    }
+
+    /// Очистить z-буфер (в области отсечения, если она задана).
     pub fn clear_z_buffer(&mut self) {
-        self.buffer.fill(f32::MIN);
+        let value = self.depth_func.clear_value();
+        for row in self.scissor_rows() {
+            self.buffer[row].fill(value);
+        }
     }
 
     /// Проверить и обновить значение z-буфера
     ///
-    /// Если новое значение z больше текущего, то возвращает true и обновляет буфер,
+    /// Пиксели вне области отсечения проверку не проходят.
+    ///
+    /// Если новое значение z проходит проверку глубины (по умолчанию - больше текущего, см.
+    /// [`DepthFunc`]), то возвращает true и обновляет буфер (если запись глубины не отключена),
     /// иначе возвращает false.
     pub fn test_and_set_z(&mut self, x: usize, y: usize, z: f32) -> bool {
         debug_assert!(
@@ -103,18 +230,27 @@ impl Canvas {
             self.height
         );
 
+        if !self.in_scissor(x, y) {
+            return false;
+        }
+
         let index = y * self.width + x;
-        if z > self.buffer[index] {
-            self.buffer[index] = z;
+        if self.depth_func.test(z, self.buffer[index]) {
+            if self.depth_write {
+                self.buffer[index] = z;
+            }
             true
         } else {
+            self.depth_rejections += 1;
             false
         }
     }
 
     /// Проверить значение z-буфера
     ///
-    /// Если новое значение z больше либо равно текущему, то возвращает true.
+    /// Пиксели вне области отсечения проверку не проходят.
+    ///
+    /// Если новое значение z проходит проверку глубины или равно текущему, то возвращает true.
     pub fn test_z(&self, x: usize, y: usize, z: f32) -> bool {
         debug_assert!(
             x < self.width,
@@ -129,23 +265,409 @@ impl Canvas {
             self.height
         );
 
+        if !self.in_scissor(x, y) {
+            return false;
+        }
+
         let index = y * self.width + x;
-        z >= self.buffer[index]
+        let stored = self.buffer[index];
+        z == stored || self.depth_func.test(z, stored)
     }
 
     /// Инверитровать изображение по оси Y.
+    ///
+    /// Вместе с пикселями переворачиваются и вспомогательные буферы, чтобы они соответствовали изображению.
+    /// Переворачивается весь холст, независимо от области отсечения.
     pub fn invert_y(&mut self) {
         for y in 0..(self.height / 2) {
             let op_y = self.height - y - 1;
             for x in 0..self.width {
-                let tmp = self[(x, y)];
-                self[(x, y)] = self[(x, op_y)];
-                self[(x, op_y)] = tmp;
+                let index = y * self.width + x;
+                let op_index = op_y * self.width + x;
+                self.pixels.swap(index, op_index);
+                self.buffer.swap(index, op_index);
+                self.fragment_counts.swap(index, op_index);
+                if let Some(ids) = &mut self.ids {
+                    ids.swap(index, op_index);
+                }
+                if let Some(hdr) = &mut self.hdr {
+                    hdr.swap(index, op_index);
+                }
             }
         }
     }
 }
 
+// --------------------------------------------------
+// Область отсечения (scissor)
+// --------------------------------------------------
+
+impl Canvas {
+    /// Задать прямоугольную область отсечения (scissor) холста или убрать её (`None`).
+    ///
+    /// Пока область задана, растеризация, очистка и рисование линий затрагивают только
+    /// пиксели внутри неё: так на одном холсте можно нарисовать несколько видов, не задевая
+    /// друг друга. Границы округляются до целых пикселей и обрезаются по холсту.
+    /// Прямая запись пикселей через индекс и [`Canvas::invert_y`] область не учитывают.
+    pub fn set_scissor(&mut self, scissor: Option<Rect>) {
+        self.scissor = scissor;
+        self.scissor_bounds = match scissor {
+            Some(rect) => self.pixel_bounds(rect),
+            None => (0..self.width, 0..self.height),
+        };
+    }
+
+    /// Текущая область отсечения, если задана.
+    pub fn scissor(&self) -> Option<Rect> {
+        self.scissor
+    }
+
+    /// Диапазоны пикселей по x и y внутри области отсечения, весь холст, если она не задана.
+    pub fn scissor_bounds(&self) -> (Range<usize>, Range<usize>) {
+        self.scissor_bounds.clone()
+    }
+
+    /// Находится ли пиксель `(x, y)` внутри области отсечения.
+    #[inline]
+    pub fn in_scissor(&self, x: usize, y: usize) -> bool {
+        let (xs, ys) = &self.scissor_bounds;
+        xs.contains(&x) && ys.contains(&y)
+    }
+
+    /// Подготовить холст к рисованию кадра вверх ногами, как это делают рендереры.
+    ///
+    /// Кадр рисуется перевёрнутым и в конце переворачивается через [`Canvas::end_flipped_frame`].
+    /// Если задана область отсечения, то холст переворачивается заранее, а область отражается,
+    /// поэтому после второго переворота пиксели вне области остаются нетронутыми.
+    /// Возвращает исходную область отсечения, которую нужно передать в `end_flipped_frame`.
+    pub fn begin_flipped_frame(&mut self) -> Option<Rect> {
+        let scissor = self.scissor?;
+        self.invert_y();
+        let height = self.height as f32;
+        self.set_scissor(Some(Rect::from_x_y_ranges(
+            scissor.x_range(),
+            height - scissor.max.y..=height - scissor.min.y,
+        )));
+        Some(scissor)
+    }
+
+    /// Перевернуть нарисованный кадр по оси Y и восстановить область отсечения `scissor`,
+    /// полученную от [`Canvas::begin_flipped_frame`].
+    pub fn end_flipped_frame(&mut self, scissor: Option<Rect>) {
+        self.invert_y();
+        self.set_scissor(scissor);
+    }
+
+    /// Диапазоны индексов пикселей строк холста внутри области отсечения.
+    fn scissor_rows(&self) -> impl Iterator<Item = Range<usize>> + use<> {
+        let (xs, ys) = self.scissor_bounds();
+        let width = self.width;
+        ys.map(move |y| y * width + xs.start..y * width + xs.end)
+    }
+}
+
+// --------------------------------------------------
+// Запись фрагментов
+// --------------------------------------------------
+
+impl Canvas {
+    /// Записать фрагмент полигона в пиксель `(x, y)`.
+    ///
+    /// В отличие от прямой записи через индекс, учитывает фрагмент в счётчике сложности глубины
+    /// и смешивает цвет с текущим, если задана непрозрачность меньше 1.
+    /// Проверка z-буфера должна быть произведена заранее.
+    #[inline]
+    pub fn set_fragment(&mut self, x: usize, y: usize, color: Color32) {
+        debug_assert!(self.check_bounds(x, y));
+        if !self.in_scissor(x, y) {
+            return;
+        }
+        let index = y * self.width + x;
+        self.pixels[index] = if self.opacity < 1.0 {
+            utils::lerp_color(self.pixels[index], color, self.opacity)
+        } else {
+            color
+        };
+        self.fragment_counts[index] += 1;
+        self.mark_id(index);
+        if let Some(hdr) = &mut self.hdr {
+            hdr[index] = self.hdr_options.linearize(self.pixels[index]);
+        }
+    }
+
+    /// Записать в пиксель `(x, y)` фрагмент с линейным HDR-цветом `color` (1 соответствует 255).
+    ///
+    /// В HDR-режиме цвет смешивается с HDR-цветом пикселя без потери яркости выше 1,
+    /// а в пиксель записывается результат тональной компрессии. Без HDR-режима цвет
+    /// обрезается до 8 бит, как в [`Canvas::set_fragment`].
+    #[inline]
+    pub fn set_fragment_hdr(&mut self, x: usize, y: usize, color: Vec3) {
+        debug_assert!(self.check_bounds(x, y));
+        if !self.in_scissor(x, y) {
+            return;
+        }
+        let index = y * self.width + x;
+        let Some(hdr) = &mut self.hdr else {
+            self.set_fragment(x, y, utils::vec_to_color(color));
+            return;
+        };
+
+        hdr[index] = if self.opacity < 1.0 {
+            hdr[index] + (color - hdr[index]) * self.opacity
+        } else {
+            color
+        };
+        self.pixels[index] = self.hdr_options.map(hdr[index]);
+        self.fragment_counts[index] += 1;
+        self.mark_id(index);
+    }
+
+    /// Смешать цвет пикселя `(x, y)` с цветом `color` в доле `amount` из [0, 1].
+    ///
+    /// В отличие от [`Canvas::set_fragment`], не считается фрагментом и не меняет
+    /// буфер идентификаторов: подходит для наложения эффектов поверх готового кадра.
+    pub fn blend_pixel(&mut self, x: usize, y: usize, color: Color32, amount: f32) {
+        debug_assert!(self.check_bounds(x, y));
+        if !self.in_scissor(x, y) {
+            return;
+        }
+        let index = y * self.width + x;
+        self.pixels[index] = utils::lerp_color(self.pixels[index], color, amount);
+        if let Some(hdr) = &mut self.hdr {
+            hdr[index] = hdr[index] + (self.hdr_options.linearize(color) - hdr[index]) * amount;
+        }
+    }
+
+    /// Прибавить к пикселю `(x, y)` линейный свет `light` (1 соответствует 255).
+    ///
+    /// Как и [`Canvas::blend_pixel`], не считается фрагментом. В HDR-режиме свет прибавляется
+    /// к HDR-цвету пикселя до тональной компрессии, иначе результат обрезается до 8 бит.
+    pub fn add_pixel_light(&mut self, x: usize, y: usize, light: Vec3) {
+        debug_assert!(self.check_bounds(x, y));
+        if !self.in_scissor(x, y) {
+            return;
+        }
+        let index = y * self.width + x;
+        match &mut self.hdr {
+            Some(hdr) => {
+                hdr[index] += light;
+                self.pixels[index] = self.hdr_options.map(hdr[index]);
+            }
+            None => {
+                self.pixels[index] =
+                    utils::vec_to_color(utils::color_to_vec(self.pixels[index]) + light);
+            }
+        }
+    }
+
+    /// Задать непрозрачность записываемых фрагментов в диапазоне [0, 1].
+    ///
+    /// 1 - фрагменты полностью заменяют пиксели, меньше - смешиваются с ними.
+    pub fn set_opacity(&mut self, opacity: f32) {
+        debug_assert!(
+            (0.0..=1.0).contains(&opacity),
+            "непрозрачность {} должна быть в диапазоне [0, 1]",
+            opacity
+        );
+        self.opacity = opacity.clamp(0.0, 1.0);
+    }
+
+    /// Текущая непрозрачность записываемых фрагментов.
+    pub fn opacity(&self) -> f32 {
+        self.opacity
+    }
+
+    /// Включить или отключить запись в z-буфер в `test_and_set_z`.
+    ///
+    /// Проверка глубины при этом продолжает работать.
+    pub fn set_depth_write(&mut self, depth_write: bool) {
+        self.depth_write = depth_write;
+    }
+
+    /// Включена ли запись в z-буфер.
+    pub fn depth_write(&self) -> bool {
+        self.depth_write
+    }
+
+    /// Задать условие проверки глубины.
+    ///
+    /// Применяется к последующим проверкам. Значение очистки z-буфера тоже зависит от условия,
+    /// поэтому его нужно задавать до очистки холста.
+    pub fn set_depth_func(&mut self, depth_func: DepthFunc) {
+        self.depth_func = depth_func;
+    }
+
+    /// Текущее условие проверки глубины.
+    pub fn depth_func(&self) -> DepthFunc {
+        self.depth_func
+    }
+
+    /// Сколько фрагментов было записано в пиксель `(x, y)` с последней очистки холста.
+    pub fn fragment_count(&self, x: usize, y: usize) -> u32 {
+        debug_assert!(self.check_bounds(x, y));
+        self.fragment_counts[y * self.width + x]
+    }
+
+    /// Сколько фрагментов было записано во все пиксели холста с последней очистки.
+    pub fn total_fragment_count(&self) -> u64 {
+        self.fragment_counts.iter().map(|&count| count as u64).sum()
+    }
+
+    /// Сколько фрагментов не прошло проверку z-буфера в `test_and_set_z` с последней очистки холста.
+    pub fn depth_rejection_count(&self) -> u64 {
+        self.depth_rejections
+    }
+
+    /// Заменить изображение тепловой картой сложности глубины.
+    ///
+    /// Количество фрагментов в пикселе переводится в цвет: 0 - чёрный, далее от синего к красному,
+    /// `max_count` и больше фрагментов - белый.
+    pub fn apply_overdraw_heatmap(&mut self, max_count: u32) {
+        debug_assert!(max_count > 0, "max_count должен быть положительным");
+        for row in self.scissor_rows() {
+            for (pixel, &count) in self.pixels[row.clone()]
+                .iter_mut()
+                .zip(self.fragment_counts[row].iter())
+            {
+                *pixel = utils::heat_color(count as f32 / max_count as f32);
+            }
+        }
+    }
+
+    /// Заменить изображение содержимым z-буфера в оттенках серого.
+    ///
+    /// Записанные глубины области отсечения нормализуются по их диапазону: ближайшая
+    /// точка белая, самая дальняя тёмно-серая. Пиксели, в которые глубина не записывалась, чёрные.
+    pub fn apply_depth_grayscale(&mut self) {
+        let clear_value = self.depth_func.clear_value();
+        let is_written = |z: f32| z != clear_value && z.is_finite();
+
+        let (mut min, mut max) = (f32::INFINITY, f32::NEG_INFINITY);
+        for row in self.scissor_rows() {
+            for &z in self.buffer[row].iter().filter(|&&z| is_written(z)) {
+                min = min.min(z);
+                max = max.max(z);
+            }
+        }
+        let range = max - min;
+
+        for row in self.scissor_rows() {
+            for (pixel, &z) in self.pixels[row.clone()]
+                .iter_mut()
+                .zip(self.buffer[row].iter())
+            {
+                *pixel = if !is_written(z) {
+                    Color32::BLACK
+                } else {
+                    // больше - ближе к камере
+                    let t = if range > 0.0 { (z - min) / range } else { 1.0 };
+                    Color32::from_gray(
+                        (DEPTH_GRAYSCALE_FAR + t * (255.0 - DEPTH_GRAYSCALE_FAR)) as u8,
+                    )
+                };
+            }
+        }
+    }
+}
+
+/// Яркость самой дальней записанной точки в [`Canvas::apply_depth_grayscale`],
+/// чтобы она отличалась от пустых пикселей.
+const DEPTH_GRAYSCALE_FAR: f32 = 40.0;
+
+// --------------------------------------------------
+// Буфер идентификаторов
+// --------------------------------------------------
+
+/// Значение буфера идентификаторов в пикселе, где нет ни одной модели.
+const NO_ID: u32 = u32::MAX;
+
+impl Canvas {
+    /// Включить или отключить буфер идентификаторов.
+    ///
+    /// Пока он включён, вместе с каждым фрагментом и пикселем линии записывается текущий
+    /// идентификатор (см. [`Canvas::set_current_id`]), так что по [`Canvas::id`] можно точно
+    /// узнать, что нарисовано в пикселе, в том числе у моделей, нарисованных только каркасом.
+    pub fn set_id_buffer_enabled(&mut self, enabled: bool) {
+        if enabled != self.ids.is_some() {
+            self.ids = enabled.then(|| vec![NO_ID; self.width * self.height]);
+        }
+    }
+
+    /// Включён ли буфер идентификаторов.
+    pub fn id_buffer_enabled(&self) -> bool {
+        self.ids.is_some()
+    }
+
+    /// Задать идентификатор (обычно индекс модели в сцене, см. [`crate::Scene::id_at`])
+    /// для последующих фрагментов и линий.
+    ///
+    /// `None` - последующие пиксели не меняют буфер идентификаторов (сетка, оси, манипуляторы).
+    pub fn set_current_id(&mut self, id: Option<usize>) {
+        debug_assert!(
+            id.is_none_or(|id| id < NO_ID as usize),
+            "идентификатор {:?} слишком большой",
+            id
+        );
+        self.current_id = id.map(|id| id as u32);
+    }
+
+    /// Идентификатор, нарисованный последним в пикселе `(x, y)`.
+    ///
+    /// Возвращает `None`, если буфер идентификаторов отключён или в пикселе ничего не нарисовано.
+    pub fn id(&self, x: usize, y: usize) -> Option<usize> {
+        debug_assert!(self.check_bounds(x, y));
+        let id = self.ids.as_ref()?[y * self.width + x];
+        (id != NO_ID).then_some(id as usize)
+    }
+
+    /// Записать текущий идентификатор в пиксель с индексом `index`.
+    #[inline]
+    fn mark_id(&mut self, index: usize) {
+        if let (Some(ids), Some(id)) = (&mut self.ids, self.current_id) {
+            ids[index] = id;
+        }
+    }
+}
+
+// --------------------------------------------------
+// HDR-режим
+// --------------------------------------------------
+
+impl Canvas {
+    /// Включить HDR-режим с параметрами `options` или отключить его (`None`).
+    ///
+    /// В HDR-режиме холст хранит для каждого пикселя линейный цвет без ограничения яркости,
+    /// в который шейдеры освещения записывают фрагменты через [`Canvas::set_fragment_hdr`].
+    /// Отображаемые пиксели получаются из него тональной компрессией, так что яркий свет
+    /// от нескольких источников не обрезается до белого.
+    pub fn set_hdr(&mut self, options: Option<HdrOptions>) {
+        match options {
+            Some(options) => {
+                self.hdr_options = options;
+                if self.hdr.is_none() {
+                    self.hdr = Some(
+                        self.pixels
+                            .iter()
+                            .map(|&color| options.linearize(color))
+                            .collect(),
+                    );
+                }
+            }
+            None => self.hdr = None,
+        }
+    }
+
+    /// Параметры HDR-режима, `None` если он отключён.
+    pub fn hdr_options(&self) -> Option<HdrOptions> {
+        self.hdr.as_ref().map(|_| self.hdr_options)
+    }
+
+    /// Линейные HDR-цвета пикселей построчно, `None` если HDR-режим отключён.
+    pub fn hdr_buffer(&self) -> Option<&[Vec3]> {
+        self.hdr.as_deref()
+    }
+}
+
 // --------------------------------------------------
 // Доступ к отдельным пикселям холста
 // --------------------------------------------------
@@ -175,11 +697,117 @@ impl IndexMut<(usize, usize)> for Canvas {
 // --------------------------------------------------
 
 impl Canvas {
+    /// Отсечение отрезка по границам холста (или его области отсечения) алгоритмом Лианга-Барски.
+    /// pos1 - первая точка отрезка;
+    /// pos2 - вторая точка отрезка;
+    ///
+    /// Возвращает параметры `(t0, t1)` видимой части отрезка `pos1 + t * (pos2 - pos1)`,
+    /// где `0 <= t0 <= t1 <= 1`, или `None`, если отрезок целиком вне холста.
+    pub fn clip_line_params(&self, pos1: Pos2, pos2: Pos2) -> Option<(f32, f32)> {
+        let (xs, ys) = &self.scissor_bounds;
+        Self::clip_line_params_in(pos1, pos2, xs, ys)
+    }
+
+    /// Отсечение отрезка по прямоугольнику пикселей `xs` x `ys` алгоритмом Лианга-Барски.
+    fn clip_line_params_in(
+        pos1: Pos2,
+        pos2: Pos2,
+        xs: &Range<usize>,
+        ys: &Range<usize>,
+    ) -> Option<(f32, f32)> {
+        if xs.is_empty() || ys.is_empty() {
+            return None;
+        }
+        // NaN и бесконечности в координатах: границы отрезка не определены
+        if !(pos1.x.is_finite() && pos1.y.is_finite() && pos2.x.is_finite() && pos2.y.is_finite()) {
+            return None;
+        }
+
+        let min_x = xs.start as f32;
+        let min_y = ys.start as f32;
+        let max_x = (xs.end - 1) as f32;
+        let max_y = (ys.end - 1) as f32;
+
+        let dx = pos2.x - pos1.x;
+        let dy = pos2.y - pos1.y;
+
+        // пары (p, q) для левой, правой, нижней и верхней границ
+        let checks = [
+            (-dx, pos1.x - min_x),
+            (dx, max_x - pos1.x),
+            (-dy, pos1.y - min_y),
+            (dy, max_y - pos1.y),
+        ];
+
+        let mut t0: f32 = 0.0;
+        let mut t1: f32 = 1.0;
+        for (p, q) in checks {
+            if p == 0.0 {
+                // отрезок параллелен границе и лежит снаружи
+                if q < 0.0 {
+                    return None;
+                }
+                continue;
+            }
+
+            let t = q / p;
+            if p < 0.0 {
+                // вход внутрь холста
+                if t > t1 {
+                    return None;
+                }
+                t0 = t0.max(t);
+            } else {
+                // выход из холста
+                if t < t0 {
+                    return None;
+                }
+                t1 = t1.min(t);
+            }
+        }
+
+        Some((t0, t1))
+    }
+
+    /// Отсечение отрезка по границам холста.
+    /// pos1 - первая точка отрезка;
+    /// pos2 - вторая точка отрезка;
+    ///
+    /// Возвращает видимую часть отрезка или `None`, если отрезок целиком вне холста.
+    pub fn clip_line(&self, pos1: Pos2, pos2: Pos2) -> Option<(Pos2, Pos2)> {
+        let (t0, t1) = self.clip_line_params(pos1, pos2)?;
+        let delta = pos2 - pos1;
+        Some((pos1 + delta * t0, pos1 + delta * t1))
+    }
+
+    /// Отсечение отрезка по границам всего холста без учёта области отсечения.
+    ///
+    /// Линии растеризуются по отрезку, отсечённому холстом, а область отсечения проверяется
+    /// попиксельно, поэтому пиксели линии не зависят от области отсечения (например,
+    /// при рендере по плиткам).
+    fn clip_line_to_canvas(&self, pos1: Pos2, pos2: Pos2) -> Option<(Pos2, Pos2)> {
+        let (t0, t1) = self.clip_line_params_to_canvas(pos1, pos2)?;
+        let delta = pos2 - pos1;
+        Some((pos1 + delta * t0, pos1 + delta * t1))
+    }
+
+    /// Параметры `(t0, t1)` части отрезка внутри всего холста без учёта области отсечения,
+    /// см. [`Canvas::clip_line_params`].
+    fn clip_line_params_to_canvas(&self, pos1: Pos2, pos2: Pos2) -> Option<(f32, f32)> {
+        Self::clip_line_params_in(pos1, pos2, &(0..self.width), &(0..self.height))
+    }
+
     /// Рисование линии алгоритмом Брезенхема.
     /// pos1 - первая точка линии;
     /// pos2 - вторая точка линии;
     /// color - цвет линии;
+    ///
+    /// Линия предварительно отсекается по границам холста.
     pub fn draw_sharp_line(&mut self, pos1: Pos2, pos2: Pos2, color: Color32) {
+        let Some((pos1, pos2)) = self.clip_line_to_canvas(pos1, pos2) else {
+            return;
+        };
+
         let mut x0 = pos1.x.round() as i32;
         let mut y0 = pos1.y.round() as i32;
         let x1 = pos2.x.round() as i32;
@@ -191,8 +819,9 @@ impl Canvas {
         let mut error = dx + dy;
 
         loop {
-            if (x0 as usize) < self.width && (y0 as usize) < self.height {
+            if x0 >= 0 && y0 >= 0 && self.in_scissor(x0 as usize, y0 as usize) {
                 self[(x0 as usize, y0 as usize)] = color;
+                self.mark_id(y0 as usize * self.width + x0 as usize);
             }
 
             let e2 = 2 * error;
@@ -213,11 +842,17 @@ impl Canvas {
         }
     }
 
-    /// Рисование линии алгоритмом Ву.
+    /// Рисование сглаженной линии алгоритмом Ву.
     /// pos1 - первая точка линии;
     /// pos2 - вторая точка линии;
     /// color - цвет линии;
+    ///
+    /// Линия предварительно отсекается по границам холста.
     pub fn draw_smooth_line_simple(&mut self, pos1: Pos2, pos2: Pos2, color: Color32) {
+        let Some((pos1, pos2)) = self.clip_line_to_canvas(pos1, pos2) else {
+            return;
+        };
+
         let mut x1 = pos1.x;
         let mut y1 = pos1.y;
         let mut x2 = pos2.x;
@@ -235,14 +870,17 @@ impl Canvas {
 
         let dx = x2 - x1;
         let dy = y2 - y1;
-        let gradient = dy / dx;
+        let gradient = if dx == 0.0 { 1.0 } else { dy / dx };
 
-        let mut intery = y1 + gradient;
+        let x_start = x1.round() as i32;
+        let x_end = x2.round() as i32;
+        let mut intery = y1 + gradient * (x_start as f32 - x1);
 
-        for x in (x1 as i32)..=(x2 as i32) {
-            let y_floor = intery as i32;
-            let intensity1 = 1.0 - (intery - y_floor as f32);
-            let intensity2 = intery - y_floor as f32;
+        for x in x_start..=x_end {
+            let y_floor = intery.floor();
+            let intensity2 = intery - y_floor;
+            let intensity1 = 1.0 - intensity2;
+            let y_floor = y_floor as i32;
 
             if steep {
                 self.set_pixel(y_floor, x, color, intensity1);
@@ -254,33 +892,267 @@ impl Canvas {
 
             intery += gradient;
         }
+    }
 
-        for x in (x1 as i32)..=(x2 as i32) {
-            let y_floor = intery as i32;
-            let intensity1 = 1.0 - (intery - y_floor as f32);
-            let intensity2 = intery - y_floor as f32;
+    /// Рисование линии: сглаженной (алгоритм Ву) или обычной (алгоритм Брезенхема).
+    /// pos1 - первая точка линии;
+    /// pos2 - вторая точка линии;
+    /// color - цвет линии;
+    /// antialiased - сглаживать ли линию;
+    pub fn draw_line(&mut self, pos1: Pos2, pos2: Pos2, color: Color32, antialiased: bool) {
+        if antialiased {
+            self.draw_smooth_line_simple(pos1, pos2, color);
+        } else {
+            self.draw_sharp_line(pos1, pos2, color);
+        }
+    }
 
-            if steep {
-                if (y_floor as usize) < self.height && (x as usize) < self.width {
-                    self.set_pixel(y_floor, x, color, intensity1);
-                }
-                if ((y_floor + 1) as usize) < self.height && (x as usize) < self.width {
-                    self.set_pixel(y_floor + 1, x, color, intensity2);
-                }
-            } else {
-                if (x as usize) < self.width && (y_floor as usize) < self.height {
-                    self.set_pixel(x, y_floor, color, intensity1);
+    /// Рисование линии с проверкой по z-буферу.
+    /// start - первая точка линии в экранных координатах (z - глубина);
+    /// end - вторая точка линии в экранных координатах (z - глубина);
+    /// color - цвет линии;
+    /// bias - смещение глубины в сторону камеры, чтобы рёбра не перекрывались собственными гранями;
+    ///
+    /// Линия предварительно отсекается по границам холста, глубина интерполируется
+    /// по отсечённой части. Пиксель рисуется только если линия в нём не дальше значения
+    /// z-буфера. Сам z-буфер не меняется.
+    pub fn draw_depth_tested_line(
+        &mut self,
+        start: Point3,
+        end: Point3,
+        color: Color32,
+        bias: f32,
+    ) {
+        let Some((t0, t1)) =
+            self.clip_line_params_to_canvas(Pos2::new(start.x, start.y), Pos2::new(end.x, end.y))
+        else {
+            return;
+        };
+        let delta = end - start;
+        let (start, end) = (start + delta * t0, start + delta * t1);
+
+        let dx = end.x - start.x;
+        let dy = end.y - start.y;
+        let steps = dx.abs().max(dy.abs()).ceil().max(1.0) as usize;
+
+        for i in 0..=steps {
+            let t = i as f32 / steps as f32;
+            let x = (start.x + dx * t).round();
+            let y = (start.y + dy * t).round();
+            if x < 0.0 || y < 0.0 {
+                continue;
+            }
+
+            let (x, y) = (x as usize, y as usize);
+            if !self.check_bounds(x, y) {
+                continue;
+            }
+
+            let z = start.z + (end.z - start.z) * t;
+            self.set_depth_tested_pixel(x, y, z + bias, color);
+        }
+    }
+
+    /// Рисование пунктирной линии с проверкой по z-буферу.
+    /// start - первая точка линии в экранных координатах (z - глубина);
+    /// end - вторая точка линии в экранных координатах (z - глубина);
+    /// dash - длина штриха в пикселях;
+    /// gap - длина промежутка между штрихами в пикселях;
+    /// color - цвет линии;
+    /// bias - смещение глубины в сторону камеры;
+    ///
+    /// Пунктир начинается со штриха в точке `start`. Сам z-буфер не меняется.
+    pub fn draw_depth_tested_dashed_line(
+        &mut self,
+        start: Point3,
+        end: Point3,
+        dash: f32,
+        gap: f32,
+        color: Color32,
+        bias: f32,
+    ) {
+        let length = Vec2::new(end.x - start.x, end.y - start.y).length();
+        let period = dash + gap;
+        if dash <= 0.0 || period <= 0.0 || length <= period {
+            self.draw_depth_tested_line(start, end, color, bias);
+            return;
+        }
+
+        let point_at = |distance: f32| start + (end - start) * (distance / length).min(1.0);
+        let mut distance = 0.0;
+        while distance < length {
+            self.draw_depth_tested_line(point_at(distance), point_at(distance + dash), color, bias);
+            distance += period;
+        }
+    }
+
+    /// Рисование толстой линии с проверкой по z-буферу.
+    /// start - первая точка линии в экранных координатах (z - глубина);
+    /// end - вторая точка линии в экранных координатах (z - глубина);
+    /// thickness - толщина линии в пикселях;
+    /// cap - форма концов линии;
+    /// color - цвет линии;
+    /// bias - смещение глубины в сторону камеры;
+    ///
+    /// Линия заливается как четырёхугольник, глубина интерполируется вдоль линии.
+    /// Сам z-буфер не меняется.
+    pub fn draw_depth_tested_thick_line(
+        &mut self,
+        start: Point3,
+        end: Point3,
+        thickness: f32,
+        cap: LineCap,
+        color: Color32,
+        bias: f32,
+    ) {
+        let half = thickness / 2.0;
+        let direction = Vec2::new(end.x - start.x, end.y - start.y).normalized();
+        if half <= 0.5 || !direction.is_finite() {
+            self.draw_depth_tested_line(start, end, color, bias);
+            return;
+        }
+
+        let direction = Vec3::new(direction.x, direction.y, 0.0) * half;
+        let normal = Vec3::new(-direction.y, direction.x, 0.0);
+        let (start, end) = match cap {
+            LineCap::Square => (start + -direction, end + direction),
+            LineCap::Butt | LineCap::Round => (start, end),
+        };
+        self.fill_depth_tested_polygon(
+            &[start + normal, end + normal, end + -normal, start + -normal],
+            color,
+            bias,
+        );
+        if cap == LineCap::Round {
+            let radii = Vec2::splat(half);
+            self.fill_depth_tested_ellipse(start, radii, color, bias);
+            self.fill_depth_tested_ellipse(end, radii, color, bias);
+        }
+    }
+
+    /// Заливка многоугольника с проверкой по z-буферу.
+    /// points - вершины многоугольника в экранных координатах (z - глубина);
+    /// color - цвет заливки;
+    /// bias - смещение глубины в сторону камеры;
+    ///
+    /// Заливаются пиксели, центры которых лежат внутри многоугольника по правилу чётности,
+    /// поэтому многоугольник может быть невыпуклым. Глубина интерполируется по рёбрам
+    /// и вдоль строк. Сам z-буфер не меняется.
+    pub fn fill_depth_tested_polygon(&mut self, points: &[Point3], color: Color32, bias: f32) {
+        if points.len() < 3 || self.width == 0 || self.height == 0 {
+            return;
+        }
+
+        let (min_y, max_y) = points.iter().fold((f32::MAX, f32::MIN), |(min, max), p| {
+            (min.min(p.y), max.max(p.y))
+        });
+        let from_y = min_y.ceil().clamp(0.0, self.height as f32) as usize;
+        let to_y = (max_y.floor() + 1.0).clamp(0.0, self.height as f32) as usize;
+
+        // пересечения строки с рёбрами: (x, z)
+        let mut crossings: Vec<(f32, f32)> = Vec::new();
+        for y in from_y..to_y {
+            let scan_y = y as f32;
+            crossings.clear();
+            for (i, &a) in points.iter().enumerate() {
+                let b = points[(i + 1) % points.len()];
+                // полуоткрытый интервал, чтобы общая вершина двух рёбер не считалась дважды
+                if (a.y <= scan_y) != (b.y <= scan_y) {
+                    let t = (scan_y - a.y) / (b.y - a.y);
+                    crossings.push((a.x + (b.x - a.x) * t, a.z + (b.z - a.z) * t));
                 }
-                if (x as usize) < self.width && ((y_floor + 1) as usize) < self.height {
-                    self.set_pixel(x, y_floor + 1, color, intensity2);
+            }
+            crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+            for span in crossings.chunks_exact(2) {
+                let ((x0, z0), (x1, z1)) = (span[0], span[1]);
+                let from_x = x0.ceil().clamp(0.0, self.width as f32) as usize;
+                let to_x = (x1.floor() + 1.0).clamp(0.0, self.width as f32) as usize;
+                for x in from_x..to_x {
+                    let t = if x1 > x0 {
+                        (x as f32 - x0) / (x1 - x0)
+                    } else {
+                        0.0
+                    };
+                    self.set_depth_tested_pixel(x, y, z0 + (z1 - z0) * t + bias, color);
                 }
             }
-            intery += gradient;
+        }
+    }
+
+    /// Заливка эллипса с проверкой по z-буферу.
+    /// center - центр эллипса в экранных координатах (z - глубина всего эллипса);
+    /// radii - полуоси эллипса по x и y в пикселях;
+    /// color - цвет заливки;
+    /// bias - смещение глубины в сторону камеры;
+    ///
+    /// Сам z-буфер не меняется.
+    pub fn fill_depth_tested_ellipse(
+        &mut self,
+        center: Point3,
+        radii: Vec2,
+        color: Color32,
+        bias: f32,
+    ) {
+        if radii.x <= 0.0 || radii.y <= 0.0 || self.width == 0 || self.height == 0 {
+            return;
+        }
+
+        let from_y = (center.y - radii.y).ceil().clamp(0.0, self.height as f32) as usize;
+        let to_y = ((center.y + radii.y).floor() + 1.0).clamp(0.0, self.height as f32) as usize;
+        for y in from_y..to_y {
+            let dy = (y as f32 - center.y) / radii.y;
+            let half_width = radii.x * (1.0 - dy * dy).max(0.0).sqrt();
+            let from_x = (center.x - half_width).ceil().clamp(0.0, self.width as f32) as usize;
+            let to_x =
+                ((center.x + half_width).floor() + 1.0).clamp(0.0, self.width as f32) as usize;
+            for x in from_x..to_x {
+                self.set_depth_tested_pixel(x, y, center.z + bias, color);
+            }
+        }
+    }
+
+    /// Рисование контура эллипса с проверкой по z-буферу.
+    /// center - центр эллипса в экранных координатах (z - глубина всего эллипса);
+    /// radii - полуоси эллипса по x и y в пикселях;
+    /// color - цвет контура;
+    /// bias - смещение глубины в сторону камеры;
+    ///
+    /// Контур рисуется ломаной с отрезками длиной около 2 пикселей. Сам z-буфер не меняется.
+    pub fn draw_depth_tested_ellipse(
+        &mut self,
+        center: Point3,
+        radii: Vec2,
+        color: Color32,
+        bias: f32,
+    ) {
+        if radii.x <= 0.0 || radii.y <= 0.0 {
+            return;
+        }
+
+        let perimeter =
+            std::f32::consts::TAU * ((radii.x * radii.x + radii.y * radii.y) / 2.0).sqrt();
+        let segments = ((perimeter / 2.0).ceil() as usize).max(8);
+        let point = |i: usize| {
+            let angle = i as f32 / segments as f32 * std::f32::consts::TAU;
+            center + Vec3::new(radii.x * angle.cos(), radii.y * angle.sin(), 0.0)
+        };
+        for i in 0..segments {
+            self.draw_depth_tested_line(point(i), point(i + 1), color, bias);
+        }
+    }
+
+    /// Записать пиксель линии или заливки, если он в пределах холста и проходит
+    /// проверку по z-буферу. Сам z-буфер не меняется.
+    fn set_depth_tested_pixel(&mut self, x: usize, y: usize, z: f32, color: Color32) {
+        if self.check_bounds(x, y) && self.test_z(x, y, z) {
+            self[(x, y)] = color;
+            self.mark_id(y * self.width + x);
         }
     }
 
     fn set_pixel(&mut self, x: i32, y: i32, color: Color32, intensity: f32) {
-        if x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height {
+        if x >= 0 && y >= 0 && self.in_scissor(x as usize, y as usize) {
             let background = self[(x as usize, y as usize)];
 
             let bg_r = background.r() as f32;
@@ -300,6 +1172,10 @@ impl Canvas {
 
             self[(x as usize, y as usize)] =
                 Color32::from_rgba_premultiplied(result_r, result_g, result_b, result_a);
+            // пиксель принадлежит линии, если она покрывает его больше чем наполовину
+            if intensity > 0.5 {
+                self.mark_id(y as usize * self.width + x as usize);
+            }
         }
     }
 
@@ -322,14 +1198,207 @@ impl Canvas {
             for y in from_y..to_y {
                 let dx = x as f32 - center.x;
                 let dy = y as f32 - center.y;
-                if (dx * dx + dy * dy).sqrt() <= radius {
+                if (dx * dx + dy * dy).sqrt() <= radius && self.in_scissor(x, y) {
                     self[(x, y)] = color;
+                    self.mark_id(y * self.width + x);
                 }
             }
         }
     }
 }
 
+// --------------------------------------------------
+// Наложение изображений
+// --------------------------------------------------
+
+impl Canvas {
+    /// Наложить небольшое изображение (иконку, глиф из атласа) на холст с альфа-смешиванием.
+    ///
+    /// `image` - изображение с premultiplied alpha, как и все цвета egui;
+    /// `pos` - положение левого верхнего угла изображения, может быть дробным;
+    /// `tint` - цвет, на который покомпонентно домножается изображение (`Color32::WHITE` - без изменений).
+    ///
+    /// При дробном `pos` изображение сэмплируется билинейно, поэтому сдвиг на долю пикселя
+    /// виден как плавное смещение, а не скачок. Z-буфер и счётчики фрагментов не изменяются.
+    pub fn blit_rgba(&mut self, image: &ColorImage, pos: Pos2, tint: Color32) {
+        let [image_width, image_height] = image.size;
+        if image_width == 0 || image_height == 0 || self.width == 0 || self.height == 0 {
+            return;
+        }
+
+        let tint = tint.to_array().map(|c| c as f32 / 255.0);
+        // пиксель изображения с учётом выхода за границы (прозрачный)
+        let texel = |x: i64, y: i64| -> [f32; 4] {
+            if x < 0 || y < 0 || x >= image_width as i64 || y >= image_height as i64 {
+                return [0.0; 4];
+            }
+            let color = image.pixels[y as usize * image_width + x as usize];
+            color.to_array().map(|c| c as f32)
+        };
+
+        // пиксели холста (в области отсечения), которые может затронуть изображение
+        let (xs, ys) = self.scissor_bounds();
+        let from_x = (pos.x.floor().max(0.0) as usize).max(xs.start);
+        let from_y = (pos.y.floor().max(0.0) as usize).max(ys.start);
+        let to_x = ((pos.x + image_width as f32).ceil() as usize).min(xs.end);
+        let to_y = ((pos.y + image_height as f32).ceil() as usize).min(ys.end);
+
+        for y in from_y..to_y {
+            for x in from_x..to_x {
+                // координаты пикселя холста в пикселях изображения
+                let u = x as f32 - pos.x;
+                let v = y as f32 - pos.y;
+                let (u0, v0) = (u.floor(), v.floor());
+                let (fu, fv) = (u - u0, v - v0);
+                let (u0, v0) = (u0 as i64, v0 as i64);
+
+                let c00 = texel(u0, v0);
+                let c10 = texel(u0 + 1, v0);
+                let c01 = texel(u0, v0 + 1);
+                let c11 = texel(u0 + 1, v0 + 1);
+                let src: [f32; 4] = std::array::from_fn(|i| {
+                    let top = utils::lerp_float(c00[i], c10[i], fu);
+                    let bottom = utils::lerp_float(c01[i], c11[i], fu);
+                    utils::lerp_float(top, bottom, fv) * tint[i]
+                });
+
+                let src_alpha = src[3] / 255.0;
+                if src_alpha <= 0.0 {
+                    continue;
+                }
+
+                // смешивание premultiplied цветов: src + dst * (1 - src_alpha)
+                let dst = self[(x, y)].to_array();
+                let out: [u8; 4] = std::array::from_fn(|i| {
+                    (src[i] + dst[i] as f32 * (1.0 - src_alpha))
+                        .round()
+                        .clamp(0.0, 255.0) as u8
+                });
+                self[(x, y)] = Color32::from_rgba_premultiplied(out[0], out[1], out[2], out[3]);
+            }
+        }
+    }
+
+    /// Скопировать пиксели холста `other` так, чтобы его левый верхний угол оказался в `offset`.
+    ///
+    /// Смещение может быть отрицательным, части `other` за границами холста отбрасываются.
+    /// Цвета копируются без смешивания, z-буфер и вспомогательные буферы не изменяются.
+    pub fn blit(&mut self, other: &Canvas, offset: (isize, isize)) {
+        let (offset_x, offset_y) = offset;
+        // пересечение холстов в координатах self в пределах области отсечения
+        let (xs, ys) = self.scissor_bounds();
+        let from_x = (offset_x.max(0) as usize).max(xs.start);
+        let from_y = (offset_y.max(0) as usize).max(ys.start);
+        let to_x =
+            ((offset_x + other.width as isize).clamp(0, self.width as isize) as usize).min(xs.end);
+        let to_y = ((offset_y + other.height as isize).clamp(0, self.height as isize) as usize)
+            .min(ys.end);
+        if from_x >= to_x || from_y >= to_y {
+            return;
+        }
+
+        for y in from_y..to_y {
+            let other_y = (y as isize - offset_y) as usize;
+            let other_x = (from_x as isize - offset_x) as usize;
+            let src = other_y * other.width + other_x;
+            let dst = y * self.width + from_x;
+            let len = to_x - from_x;
+            self.pixels[dst..dst + len].copy_from_slice(&other.pixels[src..src + len]);
+        }
+    }
+}
+
+// --------------------------------------------------
+// Размер и области холста
+// --------------------------------------------------
+
+impl Canvas {
+    /// Изменить размер холста, сохранив содержимое.
+    ///
+    /// Пиксели и буферы общей части старого и нового холста (от левого верхнего угла)
+    /// сохраняются, новые пиксели заполняются как в [`Canvas::new`].
+    pub fn resize(&mut self, width: usize, height: usize) {
+        if [width, height] == self.size() {
+            return;
+        }
+
+        let mut resized = Canvas::new(width, height);
+        resized.set_id_buffer_enabled(self.id_buffer_enabled());
+        resized.set_hdr(self.hdr_options());
+        resized.copy_region(self, 0, 0, width.min(self.width), height.min(self.height));
+        resized.depth_rejections = self.depth_rejections;
+        resized.opacity = self.opacity;
+        resized.depth_write = self.depth_write;
+        resized.depth_func = self.depth_func;
+        resized.set_scissor(self.scissor);
+        resized.current_id = self.current_id;
+        *self = resized;
+    }
+
+    /// Новый холст с копией области `rect` этого холста.
+    ///
+    /// Границы `rect` округляются до целых пикселей и обрезаются по холсту, поэтому размер
+    /// результата может быть меньше `rect` (вплоть до нулевого). Вместе с цветами копируются
+    /// z-буфер, счётчики фрагментов, буфер идентификаторов и HDR-цвета.
+    pub fn sub_canvas(&self, rect: Rect) -> Canvas {
+        let (xs, ys) = self.pixel_bounds(rect);
+        let mut sub = Canvas::new(xs.len(), ys.len());
+        sub.set_id_buffer_enabled(self.id_buffer_enabled());
+        sub.set_hdr(self.hdr_options());
+        sub.copy_region(self, xs.start, ys.start, xs.len(), ys.len());
+        sub
+    }
+
+    /// Залить прямоугольник `rect` цветом `color`.
+    ///
+    /// Границы `rect` округляются до целых пикселей и обрезаются по холсту и области отсечения.
+    /// Z-буфер и вспомогательные буферы не изменяются.
+    pub fn fill_rect(&mut self, rect: Rect, color: Color32) {
+        let (xs, ys) = self.pixel_bounds(rect);
+        let (scissor_xs, scissor_ys) = self.scissor_bounds();
+        let intersect = |a: Range<usize>, b: Range<usize>| {
+            let start = a.start.max(b.start);
+            start..a.end.min(b.end).max(start)
+        };
+        let (xs, ys) = (intersect(xs, scissor_xs), intersect(ys, scissor_ys));
+        for y in ys {
+            let row = y * self.width;
+            self.pixels[row + xs.start..row + xs.end].fill(color);
+        }
+    }
+
+    /// Диапазоны пикселей по x и y, покрываемые прямоугольником `rect` в пределах холста.
+    fn pixel_bounds(&self, rect: Rect) -> (Range<usize>, Range<usize>) {
+        let bound = |value: f32, max: usize| (value.round().max(0.0) as usize).min(max);
+        let from_x = bound(rect.min.x, self.width);
+        let from_y = bound(rect.min.y, self.height);
+        let to_x = bound(rect.max.x, self.width).max(from_x);
+        let to_y = bound(rect.max.y, self.height).max(from_y);
+        (from_x..to_x, from_y..to_y)
+    }
+
+    /// Скопировать в левый верхний угол холста область `width x height` холста `source`,
+    /// начинающуюся в `(x, y)`, вместе со всеми буферами.
+    fn copy_region(&mut self, source: &Canvas, x: usize, y: usize, width: usize, height: usize) {
+        debug_assert!(width <= self.width && height <= self.height);
+        debug_assert!(x + width <= source.width && y + height <= source.height);
+        for row in 0..height {
+            let src = (y + row) * source.width + x;
+            let dst = row * self.width;
+            self.pixels[dst..dst + width].copy_from_slice(&source.pixels[src..src + width]);
+            self.buffer[dst..dst + width].copy_from_slice(&source.buffer[src..src + width]);
+            self.fragment_counts[dst..dst + width]
+                .copy_from_slice(&source.fragment_counts[src..src + width]);
+            if let (Some(ids), Some(source_ids)) = (&mut self.ids, &source.ids) {
+                ids[dst..dst + width].copy_from_slice(&source_ids[src..src + width]);
+            }
+            if let (Some(hdr), Some(source_hdr)) = (&mut self.hdr, &source.hdr) {
+                hdr[dst..dst + width].copy_from_slice(&source_hdr[src..src + width]);
+            }
+        }
+    }
+}
+
 // --------------------------------------------------
 // Заливка
 // --------------------------------------------------
@@ -587,3 +1656,536 @@ impl Canvas {
 //         }
 //     }
 // }
+
+#[cfg(test)]
+mod canvas_tests {
+    use super::*;
+
+    #[test]
+    fn test_blit_rgba_integer_position() {
+        let mut canvas = Canvas::new(10, 10);
+        canvas.clear(Color32::BLACK);
+        let image = ColorImage::new([2, 2], vec![Color32::WHITE; 4]);
+
+        canvas.blit_rgba(&image, Pos2::new(3.0, 4.0), Color32::WHITE);
+        assert_eq!(canvas[(3, 4)], Color32::WHITE);
+        assert_eq!(canvas[(4, 5)], Color32::WHITE);
+        assert_eq!(
+            canvas[(5, 4)],
+            Color32::BLACK,
+            "изображение не должно расплываться"
+        );
+        assert_eq!(canvas[(2, 4)], Color32::BLACK);
+    }
+
+    #[test]
+    fn test_blit_rgba_tint_and_alpha() {
+        let mut canvas = Canvas::new(4, 4);
+        canvas.clear(Color32::BLACK);
+        let image = ColorImage::new([1, 1], vec![Color32::WHITE]);
+
+        // цвет изображения домножается на оттенок
+        canvas.blit_rgba(&image, Pos2::new(0.0, 0.0), Color32::RED);
+        assert_eq!(canvas[(0, 0)], Color32::RED);
+
+        // полупрозрачный пиксель смешивается с фоном
+        let half = Color32::from_rgba_premultiplied(128, 128, 128, 128);
+        let image = ColorImage::new([1, 1], vec![half]);
+        canvas.blit_rgba(&image, Pos2::new(1.0, 1.0), Color32::WHITE);
+        let blended = canvas[(1, 1)];
+        assert_eq!(blended.r(), 128);
+        assert_eq!(blended.a(), 255, "фон непрозрачен, результат тоже");
+    }
+
+    #[test]
+    fn test_blit_rgba_subpixel_and_clipping() {
+        let mut canvas = Canvas::new(4, 4);
+        canvas.clear(Color32::BLACK);
+        let image = ColorImage::new([1, 1], vec![Color32::WHITE]);
+
+        // сдвиг на половину пикселя делит яркость между соседними пикселями
+        canvas.blit_rgba(&image, Pos2::new(1.5, 1.0), Color32::WHITE);
+        let left = canvas[(1, 1)].r();
+        let right = canvas[(2, 1)].r();
+        assert!((127..=128).contains(&left) && (127..=128).contains(&right));
+
+        // частично за границей холста - без паники
+        canvas.blit_rgba(&image, Pos2::new(-0.5, 3.5), Color32::WHITE);
+        canvas.blit_rgba(&image, Pos2::new(100.0, 100.0), Color32::WHITE);
+    }
+
+    #[test]
+    fn test_depth_tested_line_hidden_behind_surface() {
+        let mut canvas = Canvas::new(20, 10);
+        canvas.clear(Color32::BLACK);
+        // "поверхность" на глубине 0.5 в левой половине холста
+        for y in 0..10 {
+            for x in 0..10 {
+                canvas.test_and_set_z(x, y, 0.5);
+            }
+        }
+
+        canvas.draw_depth_tested_line(
+            Point3::new(0.0, 5.0, 0.0),
+            Point3::new(19.0, 5.0, 0.0),
+            Color32::WHITE,
+            0.0,
+        );
+
+        assert_eq!(
+            canvas[(5, 5)],
+            Color32::BLACK,
+            "линия за поверхностью видна"
+        );
+        assert_eq!(
+            canvas[(15, 5)],
+            Color32::WHITE,
+            "линия без поверхности не видна"
+        );
+    }
+
+    #[test]
+    fn test_depth_tested_line_bias() {
+        let mut canvas = Canvas::new(10, 10);
+        canvas.clear(Color32::BLACK);
+        canvas.test_and_set_z(5, 5, 0.5);
+
+        // линия чуть дальше поверхности проходит тест только со смещением
+        let start = Point3::new(5.0, 5.0, 0.499);
+        canvas.draw_depth_tested_line(start, start, Color32::WHITE, 0.0);
+        assert_eq!(canvas[(5, 5)], Color32::BLACK);
+        canvas.draw_depth_tested_line(start, start, Color32::WHITE, 0.01);
+        assert_eq!(canvas[(5, 5)], Color32::WHITE);
+    }
+
+    #[test]
+    fn test_depth_tested_line_clipped_to_canvas() {
+        let mut canvas = Canvas::new(20, 10);
+        canvas.clear(Color32::BLACK);
+        // слева поверхность чуть дальше середины линии, справа - чуть ближе
+        for y in 0..10 {
+            for x in 0..20 {
+                canvas.test_and_set_z(x, y, if x < 10 { 0.4999 } else { 0.5001 });
+            }
+        }
+
+        // концы далеко за холстом: глубина в видимой части отсечённой линии около 0.5
+        canvas.draw_depth_tested_line(
+            Point3::new(-1.0e6, 5.0, 0.0),
+            Point3::new(1.0e6, 5.0, 1.0),
+            Color32::WHITE,
+            0.0,
+        );
+
+        assert_eq!(canvas[(5, 5)], Color32::WHITE);
+        assert_eq!(canvas[(15, 5)], Color32::BLACK);
+    }
+
+    #[test]
+    fn test_depth_tested_primitives_hidden_behind_surface() {
+        let fresh = || {
+            let mut canvas = Canvas::new(40, 20);
+            canvas.clear(Color32::BLACK);
+            // "поверхность" на глубине 0.5 в левой половине холста
+            for y in 0..20 {
+                for x in 0..20 {
+                    canvas.test_and_set_z(x, y, 0.5);
+                }
+            }
+            canvas
+        };
+        let visible = |canvas: &Canvas, x: Range<usize>| {
+            (0..20).any(|y| x.clone().any(|x| canvas[(x, y)] == Color32::WHITE))
+        };
+
+        let mut canvas = fresh();
+        canvas.fill_depth_tested_polygon(
+            &[
+                Point3::new(5.0, 5.0, 0.0),
+                Point3::new(35.0, 5.0, 0.0),
+                Point3::new(35.0, 15.0, 0.0),
+                Point3::new(5.0, 15.0, 0.0),
+            ],
+            Color32::WHITE,
+            0.0,
+        );
+        assert!(!visible(&canvas, 0..20) && canvas[(30, 10)] == Color32::WHITE);
+        assert_eq!(
+            canvas[(30, 16)],
+            Color32::BLACK,
+            "заливка вышла за многоугольник"
+        );
+        // ближе поверхности многоугольник виден целиком
+        canvas.fill_depth_tested_polygon(
+            &[
+                Point3::new(5.0, 5.0, 1.0),
+                Point3::new(35.0, 5.0, 1.0),
+                Point3::new(5.0, 15.0, 1.0),
+            ],
+            Color32::WHITE,
+            0.0,
+        );
+        assert_eq!(canvas[(6, 6)], Color32::WHITE);
+
+        let mut canvas = fresh();
+        let (start, end) = (Point3::new(2.0, 10.0, 0.0), Point3::new(37.0, 10.0, 0.0));
+        canvas.draw_depth_tested_thick_line(start, end, 5.0, LineCap::Round, Color32::WHITE, 0.0);
+        assert!(!visible(&canvas, 0..20));
+        assert_eq!(canvas[(30, 12)], Color32::WHITE);
+        assert_eq!(canvas[(30, 13)], Color32::BLACK);
+        assert_eq!(
+            canvas[(39, 10)],
+            Color32::WHITE,
+            "скругление конца не нарисовано"
+        );
+
+        let mut canvas = fresh();
+        canvas.draw_depth_tested_dashed_line(start, end, 4.0, 4.0, Color32::WHITE, 0.0);
+        assert!(!visible(&canvas, 0..20));
+        assert!(canvas[(26, 10)] == Color32::WHITE && canvas[(24, 10)] == Color32::BLACK);
+
+        let mut canvas = fresh();
+        let center = Point3::new(20.0, 10.0, 0.0);
+        canvas.fill_depth_tested_ellipse(center, Vec2::new(8.0, 4.0), Color32::WHITE, 0.0);
+        assert!(!visible(&canvas, 0..20));
+        assert_eq!(canvas[(27, 10)], Color32::WHITE);
+        assert_eq!(canvas[(22, 15)], Color32::BLACK);
+        canvas.clear(Color32::BLACK);
+        canvas.draw_depth_tested_ellipse(center, Vec2::new(8.0, 4.0), Color32::WHITE, 0.6);
+        assert!(visible(&canvas, 0..20), "смещение глубины не учтено");
+        assert!(canvas[(28, 10)] == Color32::WHITE && canvas[(25, 10)] == Color32::BLACK);
+    }
+
+    #[test]
+    fn test_clip_line_inside() {
+        let canvas = Canvas::new(100, 50);
+        let (a, b) = canvas
+            .clip_line(Pos2::new(10.0, 10.0), Pos2::new(20.0, 30.0))
+            .unwrap();
+        assert_eq!(a, Pos2::new(10.0, 10.0));
+        assert_eq!(b, Pos2::new(20.0, 30.0));
+    }
+
+    #[test]
+    fn test_clip_line_far_endpoints() {
+        let canvas = Canvas::new(100, 50);
+        let (a, b) = canvas
+            .clip_line(Pos2::new(-1e4, 25.0), Pos2::new(1e4, 25.0))
+            .unwrap();
+        assert!(a.x.abs() < 1e-2 && (a.y - 25.0).abs() < 1e-2);
+        assert!((b.x - 99.0).abs() < 1e-2 && (b.y - 25.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn test_clip_line_outside() {
+        let canvas = Canvas::new(100, 50);
+        assert!(
+            canvas
+                .clip_line(Pos2::new(-10.0, -10.0), Pos2::new(200.0, -5.0))
+                .is_none()
+        );
+        assert!(
+            canvas
+                .clip_line(Pos2::new(150.0, 0.0), Pos2::new(150.0, 40.0))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_clip_line_non_finite() {
+        let canvas = Canvas::new(100, 50);
+        let inside = Pos2::new(10.0, 10.0);
+        for bad in [
+            Pos2::new(f32::NAN, 10.0),
+            Pos2::new(20.0, f32::NAN),
+            Pos2::new(f32::INFINITY, 10.0),
+            Pos2::new(20.0, f32::NEG_INFINITY),
+        ] {
+            assert!(canvas.clip_line(inside, bad).is_none(), "{:?}", bad);
+            assert!(canvas.clip_line(bad, inside).is_none(), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn test_sharp_line_clipped() {
+        let mut canvas = Canvas::new(20, 10);
+        canvas.clear(Color32::BLACK);
+        canvas.draw_sharp_line(
+            Pos2::new(-500.0, 5.0),
+            Pos2::new(500.0, 5.0),
+            Color32::WHITE,
+        );
+        for x in 0..20 {
+            assert_eq!(
+                canvas[(x, 5)],
+                Color32::WHITE,
+                "пиксель ({}, 5) не закрашен",
+                x
+            );
+        }
+        assert_eq!(canvas[(0, 4)], Color32::BLACK);
+    }
+
+    #[test]
+    fn test_smooth_line_vertical() {
+        let mut canvas = Canvas::new(10, 10);
+        canvas.clear(Color32::BLACK);
+        canvas.draw_smooth_line_simple(Pos2::new(3.0, 0.0), Pos2::new(3.0, 9.0), Color32::WHITE);
+        for y in 0..10 {
+            assert_eq!(
+                canvas[(3, y)],
+                Color32::WHITE,
+                "пиксель (3, {}) не закрашен",
+                y
+            );
+        }
+    }
+
+    #[test]
+    fn test_transparent_fragment_blending() {
+        let mut canvas = Canvas::new(4, 4);
+        canvas.clear(Color32::BLACK);
+        canvas.set_opacity(0.5);
+        canvas.set_fragment(1, 1, Color32::WHITE);
+        let blended = canvas[(1, 1)];
+        assert!(
+            (120..=135).contains(&blended.r()),
+            "ожидался серый цвет, но получен {:?}",
+            blended
+        );
+
+        canvas.set_opacity(1.0);
+        canvas.set_fragment(1, 1, Color32::WHITE);
+        assert_eq!(canvas[(1, 1)], Color32::WHITE);
+    }
+
+    #[test]
+    fn test_depth_write_disabled() {
+        let mut canvas = Canvas::new(4, 4);
+        canvas.set_depth_write(false);
+        assert!(canvas.test_and_set_z(0, 0, 0.5));
+        // буфер не изменился, поэтому более дальний фрагмент тоже проходит
+        assert!(canvas.test_and_set_z(0, 0, 0.1));
+
+        canvas.set_depth_write(true);
+        assert!(canvas.test_and_set_z(0, 0, 0.5));
+        assert!(!canvas.test_and_set_z(0, 0, 0.1));
+    }
+
+    #[test]
+    fn test_scissor() {
+        let mut canvas = Canvas::new(10, 10);
+        canvas.clear(Color32::RED);
+        canvas.set_scissor(Some(Rect::from_min_max(
+            Pos2::new(2.0, 2.0),
+            Pos2::new(5.0, 6.0),
+        )));
+        assert_eq!(canvas.scissor_bounds(), (2..5, 2..6));
+
+        canvas.clear(Color32::BLUE);
+        assert_eq!(canvas[(2, 2)], Color32::BLUE);
+        assert_eq!(canvas[(4, 5)], Color32::BLUE);
+        assert_eq!(
+            canvas[(5, 5)],
+            Color32::RED,
+            "очистка только внутри области"
+        );
+        assert_eq!(canvas[(1, 1)], Color32::RED);
+
+        assert!(canvas.test_and_set_z(3, 3, 0.5));
+        assert!(
+            !canvas.test_and_set_z(7, 7, 0.5),
+            "вне области глубина не проходит"
+        );
+        canvas.set_fragment(7, 7, Color32::GREEN);
+        assert_eq!(canvas[(7, 7)], Color32::RED);
+
+        canvas.draw_sharp_line(Pos2::new(0.0, 3.0), Pos2::new(9.0, 3.0), Color32::GREEN);
+        let row: Vec<bool> = (0..10).map(|x| canvas[(x, 3)] == Color32::GREEN).collect();
+        assert!(row[2..5].iter().all(|&green| green));
+        assert!(!row[..2].iter().any(|&green| green) && !row[5..].iter().any(|&green| green));
+
+        canvas.set_scissor(None);
+        assert_eq!(canvas.scissor_bounds(), (0..10, 0..10));
+        canvas.set_fragment(7, 7, Color32::GREEN);
+        assert_eq!(canvas[(7, 7)], Color32::GREEN);
+    }
+
+    #[test]
+    fn test_depth_func() {
+        let mut canvas = Canvas::new(4, 4);
+        canvas.set_depth_func(DepthFunc::LEqual);
+        assert!(canvas.test_and_set_z(0, 0, 0.5));
+        assert!(canvas.test_and_set_z(0, 0, 0.5), "равная глубина проходит");
+        assert!(!canvas.test_and_set_z(0, 0, 0.1));
+
+        canvas.set_depth_func(DepthFunc::Less);
+        assert!(
+            !canvas.test_and_set_z(0, 0, 0.5),
+            "равная глубина не проходит"
+        );
+
+        // дальние фрагменты перекрывают ближние, буфер очищается ближайшим значением
+        canvas.set_depth_func(DepthFunc::Greater);
+        canvas.clear_z_buffer();
+        assert!(canvas.test_and_set_z(0, 0, 0.5));
+        assert!(canvas.test_and_set_z(0, 0, 0.1));
+        assert!(!canvas.test_and_set_z(0, 0, 0.3));
+        assert!(canvas.test_z(0, 0, 0.1));
+        assert!(!canvas.test_z(0, 0, 0.3));
+    }
+
+    #[test]
+    fn test_id_buffer() {
+        let mut canvas = Canvas::new(10, 10);
+        canvas.set_current_id(Some(3));
+        canvas.set_fragment(1, 1, Color32::WHITE);
+        assert_eq!(canvas.id(1, 1), None, "буфер отключён по умолчанию");
+
+        canvas.set_id_buffer_enabled(true);
+        canvas.set_fragment(1, 1, Color32::WHITE);
+        canvas.draw_sharp_line(Pos2::new(0.0, 5.0), Pos2::new(9.0, 5.0), Color32::WHITE);
+        canvas.set_current_id(None);
+        canvas.set_fragment(2, 2, Color32::WHITE);
+        assert_eq!(canvas.id(1, 1), Some(3));
+        assert_eq!(canvas.id(7, 5), Some(3), "линии тоже помечаются");
+        assert_eq!(
+            canvas.id(2, 2),
+            None,
+            "без текущего идентификатора буфер не меняется"
+        );
+
+        canvas.invert_y();
+        assert_eq!(
+            canvas.id(1, 8),
+            Some(3),
+            "буфер переворачивается вместе с пикселями"
+        );
+
+        canvas.clear(Color32::BLACK);
+        assert_eq!(canvas.id(1, 8), None, "очистка сбрасывает буфер");
+    }
+
+    #[test]
+    fn test_hdr_fragments() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.set_fragment_hdr(0, 0, Vec3::new(3.0, 0.5, 0.0));
+        assert_eq!(
+            canvas[(0, 0)],
+            Color32::from_rgb(255, 127, 0),
+            "без HDR цвет обрезается"
+        );
+
+        let options = HdrOptions {
+            tone_mapping: crate::ToneMapping::Reinhard,
+            exposure: 1.0,
+            gamma: 1.0,
+        };
+        canvas.set_hdr(Some(options));
+        canvas.clear(Color32::BLACK);
+        canvas.set_fragment_hdr(0, 0, Vec3::new(3.0, 0.0, 0.0));
+        canvas.set_opacity(0.5);
+        canvas.set_fragment_hdr(0, 0, Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(
+            canvas.hdr_buffer().unwrap()[0],
+            Vec3::new(2.0, 0.0, 0.0),
+            "смешивание в HDR без обрезки"
+        );
+        assert_eq!(canvas[(0, 0)], options.map(Vec3::new(2.0, 0.0, 0.0)));
+        assert_eq!(canvas.fragment_count(0, 0), 2);
+    }
+
+    #[test]
+    fn test_overdraw_heatmap_saturates_at_max_count() {
+        let mut canvas = Canvas::new(3, 1);
+        canvas.clear(Color32::RED);
+        canvas.set_fragment(1, 0, Color32::RED);
+        for _ in 0..4 {
+            canvas.set_fragment(2, 0, Color32::RED);
+        }
+        canvas.apply_overdraw_heatmap(4);
+        assert_eq!(canvas[(0, 0)], Color32::BLACK);
+        assert_ne!(canvas[(1, 0)], Color32::WHITE);
+        assert_eq!(canvas[(2, 0)], Color32::WHITE, "ровно max_count фрагментов");
+    }
+
+    #[test]
+    fn test_depth_grayscale() {
+        let mut canvas = Canvas::new(3, 1);
+        canvas.clear(Color32::RED);
+        canvas.test_and_set_z(0, 0, 0.2);
+        canvas.test_and_set_z(1, 0, 0.8);
+        canvas.apply_depth_grayscale();
+
+        assert_eq!(
+            canvas[(0, 0)],
+            Color32::from_gray(DEPTH_GRAYSCALE_FAR as u8)
+        );
+        assert_eq!(canvas[(1, 0)], Color32::WHITE, "ближайшая точка белая");
+        assert_eq!(canvas[(2, 0)], Color32::BLACK, "глубина не записывалась");
+    }
+
+    #[test]
+    fn test_resize_preserves_content() {
+        let mut canvas = Canvas::new(4, 4);
+        canvas[(1, 2)] = Color32::RED;
+        canvas.test_and_set_z(1, 2, 0.5);
+
+        canvas.resize(6, 3);
+        assert_eq!(canvas.size(), [6, 3]);
+        assert_eq!(canvas[(1, 2)], Color32::RED);
+        assert!(!canvas.test_z(1, 2, 0.4), "z-буфер сохраняется");
+        assert_eq!(
+            canvas[(5, 0)],
+            Color32::GRAY,
+            "новые пиксели как у нового холста"
+        );
+
+        canvas.resize(1, 1);
+        assert_eq!(canvas.pixels(), &[Color32::GRAY]);
+    }
+
+    #[test]
+    fn test_blit_and_sub_canvas() {
+        let mut canvas = Canvas::new(10, 10);
+        canvas.clear(Color32::BLACK);
+        canvas.fill_rect(
+            Rect::from_min_max(Pos2::new(2.0, 3.0), Pos2::new(5.0, 4.0)),
+            Color32::RED,
+        );
+        assert_eq!(canvas[(2, 3)], Color32::RED);
+        assert_eq!(canvas[(4, 3)], Color32::RED);
+        assert_eq!(
+            canvas[(5, 3)],
+            Color32::BLACK,
+            "правая граница не включается"
+        );
+        assert_eq!(
+            canvas[(2, 4)],
+            Color32::BLACK,
+            "нижняя граница не включается"
+        );
+
+        let sub = canvas.sub_canvas(Rect::from_min_max(
+            Pos2::new(1.0, 2.0),
+            Pos2::new(20.0, 4.0),
+        ));
+        assert_eq!(sub.size(), [9, 2], "область обрезается по холсту");
+        assert_eq!(sub[(1, 1)], Color32::RED);
+        assert_eq!(sub[(0, 0)], Color32::BLACK);
+
+        let mut target = Canvas::new(5, 5);
+        target.clear(Color32::WHITE);
+        target.blit(&sub, (-1, 3));
+        assert_eq!(target[(0, 4)], Color32::RED, "смещение учитывается");
+        assert_eq!(target[(0, 3)], Color32::BLACK);
+        assert_eq!(target[(0, 2)], Color32::WHITE, "вне вставки без изменений");
+
+        // целиком за пределами холста - без паники
+        target.blit(&sub, (10, -10));
+        target.fill_rect(
+            Rect::from_min_max(Pos2::new(-5.0, -5.0), Pos2::new(-1.0, -1.0)),
+            Color32::RED,
+        );
+    }
+}
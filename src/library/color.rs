@@ -0,0 +1,341 @@
+//! Цвет с вещественными компонентами, цветовые модели HSV/HSL и палитры.
+
+use std::fmt::Display;
+
+use egui::Color32;
+use image::{DynamicImage, RgbImage};
+
+use crate::Texture;
+
+/// Цвет с компонентами в диапазоне `[0, 1]` без предумножения на прозрачность.
+///
+/// В отличие от [`Color32`], удобен для вычислений: переводов в HSV/HSL, интерполяции
+/// и палитр. Переводится в [`Color32`] и обратно через `From`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    /// Непрозрачность, 1 - полностью непрозрачный.
+    pub a: f32,
+}
+
+/// Цветовое пространство, в котором интерполируются цвета.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorSpace {
+    /// Покомпонентно по r, g, b.
+    #[default]
+    Rgb,
+    /// По тону, насыщенности и яркости, тон - по кратчайшей дуге цветового круга.
+    Hsv,
+    /// По тону, насыщенности и светлоте, тон - по кратчайшей дуге цветового круга.
+    Hsl,
+}
+
+impl Color {
+    pub const BLACK: Color = Color::new(0.0, 0.0, 0.0);
+    pub const WHITE: Color = Color::new(1.0, 1.0, 1.0);
+
+    /// Непрозрачный цвет.
+    pub const fn new(r: f32, g: f32, b: f32) -> Self {
+        Self { r, g, b, a: 1.0 }
+    }
+
+    /// Цвет с прозрачностью.
+    pub const fn rgba(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// Непрозрачный цвет по тону `hue` в градусах, насыщенности и яркости в `[0, 1]`.
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Self {
+        let chroma = value * saturation;
+        Self::from_hue_chroma(hue, chroma, value - chroma)
+    }
+
+    /// Тон в градусах `[0, 360)`, насыщенность и яркость в `[0, 1]`.
+    ///
+    /// У оттенков серого тон и насыщенность равны 0.
+    pub fn to_hsv(self) -> (f32, f32, f32) {
+        let max = self.r.max(self.g).max(self.b);
+        let min = self.r.min(self.g).min(self.b);
+        let saturation = if max > 0.0 { (max - min) / max } else { 0.0 };
+        (self.hue(max, min), saturation, max)
+    }
+
+    /// Непрозрачный цвет по тону `hue` в градусах, насыщенности и светлоте в `[0, 1]`.
+    pub fn from_hsl(hue: f32, saturation: f32, lightness: f32) -> Self {
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        Self::from_hue_chroma(hue, chroma, lightness - chroma / 2.0)
+    }
+
+    /// Тон в градусах `[0, 360)`, насыщенность и светлота в `[0, 1]`.
+    ///
+    /// У оттенков серого тон и насыщенность равны 0.
+    pub fn to_hsl(self) -> (f32, f32, f32) {
+        let max = self.r.max(self.g).max(self.b);
+        let min = self.r.min(self.g).min(self.b);
+        let lightness = (max + min) / 2.0;
+        let saturation = if max > min {
+            (max - min) / (1.0 - (2.0 * lightness - 1.0).abs())
+        } else {
+            0.0
+        };
+        (self.hue(max, min), saturation, lightness)
+    }
+
+    /// Интерполировать цвет от `self` (`t = 0`) до `other` (`t = 1`) в пространстве `space`.
+    ///
+    /// Прозрачность всегда интерполируется линейно.
+    pub fn lerp(self, other: Color, t: f32, space: ColorSpace) -> Color {
+        let lerp = |a: f32, b: f32| a + (b - a) * t;
+        let alpha = lerp(self.a, other.a);
+        let color = match space {
+            ColorSpace::Rgb => Color::new(
+                lerp(self.r, other.r),
+                lerp(self.g, other.g),
+                lerp(self.b, other.b),
+            ),
+            ColorSpace::Hsv => {
+                let ((h1, s1, v1), (h2, s2, v2)) = (self.to_hsv(), other.to_hsv());
+                let (h1, h2) = matching_hues(h1, s1, h2, s2);
+                Color::from_hsv(lerp_hue(h1, h2, t), lerp(s1, s2), lerp(v1, v2))
+            }
+            ColorSpace::Hsl => {
+                let ((h1, s1, l1), (h2, s2, l2)) = (self.to_hsl(), other.to_hsl());
+                let (h1, h2) = matching_hues(h1, s1, h2, s2);
+                Color::from_hsl(lerp_hue(h1, h2, t), lerp(s1, s2), lerp(l1, l2))
+            }
+        };
+        Color { a: alpha, ..color }
+    }
+
+    /// Цвет по тону, насыщенности цвета `chroma` и добавке `offset` ко всем каналам.
+    fn from_hue_chroma(hue: f32, chroma: f32, offset: f32) -> Self {
+        let sector = hue.rem_euclid(360.0) / 60.0;
+        let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+        let (r, g, b) = match sector as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        Self::new(r + offset, g + offset, b + offset)
+    }
+
+    /// Тон цвета в градусах по максимальной и минимальной компонентам.
+    fn hue(self, max: f32, min: f32) -> f32 {
+        let delta = max - min;
+        if delta <= 0.0 {
+            return 0.0;
+        }
+        let hue = if max == self.r {
+            (self.g - self.b) / delta
+        } else if max == self.g {
+            (self.b - self.r) / delta + 2.0
+        } else {
+            (self.r - self.g) / delta + 4.0
+        };
+        (hue * 60.0).rem_euclid(360.0)
+    }
+}
+
+/// У серого цвета тон не определён, поэтому берётся тон второго цвета,
+/// чтобы переход не проходил через чужие оттенки.
+fn matching_hues(h1: f32, s1: f32, h2: f32, s2: f32) -> (f32, f32) {
+    match (s1 <= 0.0, s2 <= 0.0) {
+        (true, false) => (h2, h2),
+        (false, true) => (h1, h1),
+        _ => (h1, h2),
+    }
+}
+
+/// Интерполяция тона в градусах по кратчайшей дуге цветового круга.
+fn lerp_hue(from: f32, to: f32, t: f32) -> f32 {
+    let delta = (to - from + 540.0).rem_euclid(360.0) - 180.0;
+    (from + delta * t).rem_euclid(360.0)
+}
+
+impl From<Color32> for Color {
+    fn from(color: Color32) -> Self {
+        let [r, g, b, a] = color.to_srgba_unmultiplied();
+        Self::rgba(
+            r as f32 / 255.0,
+            g as f32 / 255.0,
+            b as f32 / 255.0,
+            a as f32 / 255.0,
+        )
+    }
+}
+
+impl From<Color> for Color32 {
+    fn from(color: Color) -> Self {
+        let to_channel = |value: f32| (value * 255.0).round().clamp(0.0, 255.0) as u8;
+        Color32::from_rgba_unmultiplied(
+            to_channel(color.r),
+            to_channel(color.g),
+            to_channel(color.b),
+            to_channel(color.a),
+        )
+    }
+}
+
+// --------------------------------------------------
+// Палитры
+// --------------------------------------------------
+
+/// Опорные цвета палитры viridis (равномерно от 0 до 1).
+const VIRIDIS: [[u8; 3]; 9] = [
+    [68, 1, 84],
+    [71, 44, 122],
+    [59, 81, 139],
+    [44, 113, 142],
+    [33, 144, 141],
+    [39, 173, 129],
+    [92, 200, 99],
+    [170, 220, 50],
+    [253, 231, 37],
+];
+
+/// Опорные цвета палитры turbo (равномерно от 0 до 1).
+const TURBO: [[u8; 3]; 11] = [
+    [48, 18, 59],
+    [68, 84, 196],
+    [68, 144, 254],
+    [31, 200, 222],
+    [41, 239, 162],
+    [126, 255, 85],
+    [195, 241, 52],
+    [241, 202, 58],
+    [254, 146, 42],
+    [234, 79, 13],
+    [122, 4, 3],
+];
+
+/// Палитра для перевода скалярных значений из `[0, 1]` в цвета.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Palette {
+    /// От чёрного к белому.
+    Grayscale,
+    /// Перцептивно равномерная палитра от тёмно-фиолетового к жёлтому.
+    #[default]
+    Viridis,
+    /// Яркая радужная палитра от тёмно-синего к тёмно-красному.
+    Turbo,
+}
+
+impl Display for Palette {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Grayscale => f.write_str("Оттенки серого"),
+            Self::Viridis => f.write_str("Viridis"),
+            Self::Turbo => f.write_str("Turbo"),
+        }
+    }
+}
+
+impl Palette {
+    /// Цвет палитры для значения `t`, значения вне `[0, 1]` обрезаются.
+    pub fn sample(self, t: f32) -> Color {
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        let stops: &[[u8; 3]] = match self {
+            Self::Grayscale => return Color::new(t, t, t),
+            Self::Viridis => &VIRIDIS,
+            Self::Turbo => &TURBO,
+        };
+
+        let scaled = t * (stops.len() - 1) as f32;
+        let i = (scaled.floor() as usize).min(stops.len() - 2);
+        let to_color = |[r, g, b]: [u8; 3]| Color::from(Color32::from_rgb(r, g, b));
+        to_color(stops[i]).lerp(to_color(stops[i + 1]), scaled - i as f32, ColorSpace::Rgb)
+    }
+
+    /// Текстура-градиент шириной `width` пикселей: по `u` от 0 до 1 идёт вся палитра.
+    ///
+    /// Удобна для раскраски моделей по скалярному значению через текстурные координаты.
+    pub fn texture(self, width: usize) -> Texture {
+        debug_assert!(width >= 2, "ширина текстуры палитры должна быть хотя бы 2");
+        let width = width.max(2);
+        let image = RgbImage::from_fn(width as u32, 1, |x, _| {
+            let color = Color32::from(self.sample(x as f32 / (width - 1) as f32));
+            image::Rgb([color.r(), color.g(), color.b()])
+        });
+        Texture::new(DynamicImage::ImageRgb8(image))
+    }
+}
+
+#[cfg(test)]
+mod color_tests {
+    use super::*;
+
+    fn assert_close(a: Color, b: Color) {
+        for (x, y) in [(a.r, b.r), (a.g, b.g), (a.b, b.b), (a.a, b.a)] {
+            assert!((x - y).abs() < 1e-4, "{:?} != {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_hsv_and_hsl_conversions() {
+        assert_close(Color::from_hsv(0.0, 1.0, 1.0), Color::new(1.0, 0.0, 0.0));
+        assert_close(Color::from_hsv(120.0, 1.0, 0.5), Color::new(0.0, 0.5, 0.0));
+        assert_close(Color::from_hsl(240.0, 1.0, 0.5), Color::new(0.0, 0.0, 1.0));
+        assert_close(
+            Color::from_hsl(0.0, 0.0, 0.75),
+            Color::new(0.75, 0.75, 0.75),
+        );
+
+        let color = Color::new(0.2, 0.6, 0.9);
+        let (h, s, v) = color.to_hsv();
+        assert_close(Color::from_hsv(h, s, v), color);
+        let (h, s, l) = color.to_hsl();
+        assert_close(Color::from_hsl(h, s, l), color);
+        assert_eq!(
+            Color::new(0.3, 0.3, 0.3).to_hsv().1,
+            0.0,
+            "серый не насыщен"
+        );
+    }
+
+    #[test]
+    fn test_lerp_in_color_spaces() {
+        let red = Color::new(1.0, 0.0, 0.0);
+        let blue = Color::new(0.0, 0.0, 1.0);
+        assert_close(
+            red.lerp(blue, 0.5, ColorSpace::Rgb),
+            Color::new(0.5, 0.0, 0.5),
+        );
+        // по кругу тонов от красного (0°) к синему (240°) короче через пурпурный (300°)
+        assert_close(
+            red.lerp(blue, 0.5, ColorSpace::Hsv),
+            Color::from_hsv(300.0, 1.0, 1.0),
+        );
+        // от серого тон не проходит через красный
+        let gray = Color::new(0.5, 0.5, 0.5);
+        let (hue, _, _) = gray.lerp(blue, 0.5, ColorSpace::Hsl).to_hsl();
+        assert!((hue - 240.0).abs() < 1e-3, "тон {}", hue);
+    }
+
+    #[test]
+    fn test_palettes() {
+        assert_eq!(
+            Color32::from(Palette::Viridis.sample(0.0)),
+            Color32::from_rgb(68, 1, 84)
+        );
+        assert_eq!(
+            Color32::from(Palette::Turbo.sample(2.0)),
+            Color32::from_rgb(122, 4, 3),
+            "значения больше 1 обрезаются"
+        );
+        assert_close(
+            Palette::Grayscale.sample(0.25),
+            Color::new(0.25, 0.25, 0.25),
+        );
+
+        let texture = Palette::Viridis.texture(16);
+        assert_eq!(
+            texture.get_pixel_color(1.0, 0.5),
+            Color32::from_rgb(253, 231, 37)
+        );
+    }
+}
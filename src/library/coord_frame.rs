@@ -8,6 +8,7 @@ use crate::{Point3, Transform3D, UVec3, Vec3, library::utils};
 /// Эта структура представляет собой локальную коодринатную систему какого-либо объекта, в пределах которой объект записан.
 /// Через указанный базис системы можно получить части объекта в глобальных координатах.
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CoordFrame {
     /// Направление вперёд локальной системы координат. Сам базис указывается в **глобальных** координатах.
     forward: UVec3,
@@ -113,11 +114,7 @@ impl CoordFrame {
     /// Эта матрица преобразует вектор из локальной системы координат в глобальную.
     pub fn local_to_global_matrix(&self) -> Transform3D {
         let rotation = Transform3D::rotation_from_basis(self.forward(), self.right(), self.up());
-        let scale = Transform3D::scale(self.scale.x, self.scale.y, self.scale.z);
-        let translate = Transform3D::translation_vec(Vec3::from(self.origin));
-
-        // масштабирование -> поворот -> перемещение
-        scale.multiply(rotation).multiply(translate)
+        Transform3D::affine(Vec3::from(self.origin), rotation, self.scale)
     }
 
     /// Получить матрицу преобразования из глобальных координат в текущие локальные.
@@ -170,6 +167,18 @@ impl CoordFrame {
         self.up = self.up.apply_transform(transform).unwrap();
         self.right = self.right.apply_transform(transform).unwrap();
 
+        self.orthonormalize();
+    }
+
+    /// Восстановить ортонормированность базиса, накопившего ошибки округления.
+    ///
+    /// Направление `forward` сохраняется (только нормируется), `up` и `right` пересчитываются
+    /// перпендикулярно ему. Нужно после множества последовательных поворотов или после
+    /// ручного изменения осей, например при долгом управлении камерой.
+    pub fn orthonormalize(&mut self) {
+        if let Ok(forward) = Vec3::from(self.forward).normalize() {
+            self.forward = forward;
+        }
         (self.forward, self.right, self.up) =
             utils::ensure_orthonormal(self.forward, self.right, self.up);
 
@@ -177,6 +186,39 @@ impl CoordFrame {
         self.assert_orthonormal();
     }
 
+    /// Применить к координатной системе преобразование `transform` в **глобальных** координатах.
+    ///
+    /// Точка отсчёта преобразуется как точка, а оси с учётом масштаба - как векторы, поэтому
+    /// сдвиг, поворот и масштаб вдоль осей самой системы переносятся в `origin`, базис и `scale`.
+    /// Преобразования, перекашивающие оси, приводят базис к ближайшему ортонормированному.
+    pub fn apply_transform(&mut self, transform: Transform3D) {
+        let axes = [
+            (self.right, self.scale.x),
+            (self.up, self.scale.y),
+            (self.forward, self.scale.z),
+        ]
+        .map(|(axis, scale)| (axis * scale).apply_transform(transform).ok());
+        let (Ok(origin), [Some(right), Some(up), Some(forward)]) =
+            (self.origin.apply_transform(transform), axes)
+        else {
+            #[cfg(debug_assertions)]
+            eprintln!("Warning: не удалось применить преобразование к координатной системе");
+            return;
+        };
+        let (Ok(right_dir), Ok(up_dir), Ok(forward_dir)) =
+            (right.normalize(), up.normalize(), forward.normalize())
+        else {
+            #[cfg(debug_assertions)]
+            eprintln!("Warning: преобразование вырождает оси координатной системы");
+            return;
+        };
+
+        self.origin = origin;
+        self.scale = Vec3::new(right.length(), up.length(), forward.length());
+        (self.forward, self.right, self.up) =
+            utils::ensure_orthonormal(forward_dir, right_dir, up_dir);
+    }
+
     /// Отразить координатную систему в плоскости XY.
     pub fn reflect_xy(&mut self) {
         // отразить по xy это то же, что и поменять направление z
@@ -273,6 +315,35 @@ mod tests {
         assert_hvecs(back_to_global_vec, global_vec, TOLERANCE);
     }
 
+    #[test]
+    fn test_orthonormalize_removes_skew() {
+        let mut frame = CoordFrame::global();
+        // оси, "уплывшие" после множества поворотов
+        frame.forward = UVec3 {
+            x: 0.01,
+            y: 0.02,
+            z: 1.01,
+        };
+        frame.right = UVec3 {
+            x: 0.99,
+            y: 0.03,
+            z: 0.0,
+        };
+        frame.orthonormalize();
+
+        for (a, b) in [
+            (frame.forward(), frame.right()),
+            (frame.forward(), frame.up()),
+            (frame.right(), frame.up()),
+        ] {
+            assert!(a.dot(b).abs() < 2.0 * f32::EPSILON);
+        }
+        for axis in [frame.forward(), frame.right(), frame.up()] {
+            assert!((Vec3::from(axis).length() - 1.0).abs() < 1e-6);
+        }
+        assert!(frame.local_to_global_matrix().is_rigid());
+    }
+
     #[test]
     fn test_from_2_constructor_global() {
         // Создаётся локальная система идентичная глобальной, но по 2-м векторам
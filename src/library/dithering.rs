@@ -0,0 +1,341 @@
+//! Вывод кадра в ограниченной палитре с дизерингом.
+//!
+//! Нужен для стилизации под старые компьютеры и для экспорта на устройства и в форматы
+//! с малым количеством цветов (e-ink экраны, терминалы, GIF).
+
+use std::fmt::Display;
+
+use egui::Color32;
+
+use crate::{Canvas, Vec3};
+
+/// Матрица Байера 8x8 для упорядоченного дизеринга: пороги от 0 до 63.
+const BAYER_8X8: [[u8; 8]; 8] = [
+    [0, 32, 8, 40, 2, 34, 10, 42],
+    [48, 16, 56, 24, 50, 18, 58, 26],
+    [12, 44, 4, 36, 14, 46, 6, 38],
+    [60, 28, 52, 20, 62, 30, 54, 22],
+    [3, 35, 11, 43, 1, 33, 9, 41],
+    [51, 19, 59, 27, 49, 17, 57, 25],
+    [15, 47, 7, 39, 13, 45, 5, 37],
+    [63, 31, 55, 23, 61, 29, 53, 21],
+];
+
+/// Способ распределения ошибки округления до цвета палитры.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DitherMode {
+    /// Каждый пиксель заменяется ближайшим цветом палитры, плавные переходы становятся полосами.
+    None,
+    /// Упорядоченный дизеринг матрицей Байера: регулярный узор, который не "плывёт"
+    /// между кадрами анимации.
+    #[default]
+    Ordered,
+    /// Диффузия ошибки Флойда-Стейнберга: ошибка пикселя переносится на ещё не обработанных
+    /// соседей. Точнее передаёт тона, но узор меняется при малейшем изменении кадра.
+    FloydSteinberg,
+}
+
+impl Display for DitherMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::None => f.write_str("Без дизеринга"),
+            Self::Ordered => f.write_str("Упорядоченный (Байер)"),
+            Self::FloydSteinberg => f.write_str("Флойд-Стейнберг"),
+        }
+    }
+}
+
+/// Индексированная палитра: конечный набор цветов, которыми рисуется кадр.
+///
+/// В отличие от непрерывной [`crate::Palette`], цвета не интерполируются.
+///
+/// # Examples
+/// ```rust
+/// use egui::Color32;
+/// use g3d::IndexedPalette;
+///
+/// let palette = IndexedPalette::grayscale(2);
+/// assert_eq!(palette.colors(), &[Color32::BLACK, Color32::WHITE]);
+/// assert_eq!(palette.nearest(Color32::from_rgb(200, 180, 190)), Color32::WHITE);
+///
+/// assert_eq!(IndexedPalette::uniform_rgb(6).colors().len(), 216);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedPalette {
+    colors: Vec<Color32>,
+    /// Средняя наибольшая по каналам разница цвета палитры с ближайшим другим цветом
+    /// в долях от 255, от неё зависит размах упорядоченного дизеринга.
+    spread: f32,
+}
+
+impl IndexedPalette {
+    /// Палитра из цветов `colors`. Прозрачность цветов не учитывается.
+    ///
+    /// # Panics
+    /// Если `colors` пуст.
+    pub fn new(colors: Vec<Color32>) -> Self {
+        assert!(
+            !colors.is_empty(),
+            "палитра должна содержать хотя бы один цвет"
+        );
+        let channel_distance = |a: Color32, b: Color32| {
+            a.r()
+                .abs_diff(b.r())
+                .max(a.g().abs_diff(b.g()))
+                .max(a.b().abs_diff(b.b())) as f32
+        };
+        let spread = if colors.len() < 2 {
+            1.0
+        } else {
+            let total: f32 = colors
+                .iter()
+                .enumerate()
+                .map(|(i, &color)| {
+                    colors
+                        .iter()
+                        .enumerate()
+                        .filter(|&(j, _)| i != j)
+                        .map(|(_, &other)| channel_distance(color, other))
+                        .fold(f32::INFINITY, f32::min)
+                })
+                .sum();
+            total / colors.len() as f32 / 255.0
+        };
+        Self { colors, spread }
+    }
+
+    /// Палитра из `levels` (не меньше 2) оттенков серого от чёрного до белого.
+    pub fn grayscale(levels: usize) -> Self {
+        let levels = levels.max(2);
+        Self::new(
+            (0..levels)
+                .map(|i| Color32::from_gray(level_value(i, levels)))
+                .collect(),
+        )
+    }
+
+    /// Чёрно-белая палитра, например, для e-ink экранов.
+    pub fn black_and_white() -> Self {
+        Self::grayscale(2)
+    }
+
+    /// Палитра из `levels` (не меньше 2) равномерных уровней каждого канала RGB,
+    /// всего `levels³` цветов: 2 - 8 цветов терминала, 6 - 216 "безопасных" web-цветов.
+    pub fn uniform_rgb(levels: usize) -> Self {
+        let levels = levels.max(2);
+        let mut colors = Vec::with_capacity(levels * levels * levels);
+        for r in 0..levels {
+            for g in 0..levels {
+                for b in 0..levels {
+                    colors.push(Color32::from_rgb(
+                        level_value(r, levels),
+                        level_value(g, levels),
+                        level_value(b, levels),
+                    ));
+                }
+            }
+        }
+        Self::new(colors)
+    }
+
+    /// Цвета палитры.
+    pub fn colors(&self) -> &[Color32] {
+        &self.colors
+    }
+
+    /// Ближайший к `color` цвет палитры (по евклидову расстоянию в RGB).
+    pub fn nearest(&self, color: Color32) -> Color32 {
+        self.nearest_rgb(Vec3::new(
+            color.r() as f32,
+            color.g() as f32,
+            color.b() as f32,
+        ))
+    }
+
+    /// Ближайший цвет палитры к цвету `rgb` с каналами в [0, 255] (могут выходить за границы).
+    fn nearest_rgb(&self, rgb: Vec3) -> Color32 {
+        let distance = |color: &Color32| {
+            let (dr, dg, db) = (
+                color.r() as f32 - rgb.x,
+                color.g() as f32 - rgb.y,
+                color.b() as f32 - rgb.z,
+            );
+            dr * dr + dg * dg + db * db
+        };
+        *self
+            .colors
+            .iter()
+            .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+            .unwrap()
+    }
+}
+
+/// Значение канала `index`-го из `levels` равномерных уровней.
+fn level_value(index: usize, levels: usize) -> u8 {
+    (index as f32 * 255.0 / (levels - 1) as f32).round() as u8
+}
+
+/// Вывод кадра в ограниченной палитре, см. [`crate::SceneRenderer::palette_output`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaletteOutput {
+    /// Цвета, которыми рисуется кадр.
+    pub palette: IndexedPalette,
+    /// Способ дизеринга.
+    pub dither: DitherMode,
+}
+
+impl PaletteOutput {
+    /// Вывод в палитре `palette` с дизерингом `dither`.
+    pub fn new(palette: IndexedPalette, dither: DitherMode) -> Self {
+        Self { palette, dither }
+    }
+}
+
+impl Canvas {
+    /// Перевести пиксели холста внутри области отсечения в цвета палитры `output`.
+    ///
+    /// Прозрачность пикселей сохраняется. Меняются только 8-битные пиксели: HDR-буфер
+    /// остаётся прежним, поэтому преобразование нужно делать последним этапом кадра.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use egui::Color32;
+    /// use g3d::{Canvas, DitherMode, IndexedPalette, PaletteOutput};
+    ///
+    /// let mut canvas = Canvas::new(8, 8);
+    /// canvas.clear(Color32::from_gray(128));
+    /// canvas.quantize(&PaletteOutput::new(IndexedPalette::black_and_white(), DitherMode::Ordered));
+    ///
+    /// // серый передаётся узором из чёрных и белых пикселей поровну
+    /// let white = canvas.pixels().iter().filter(|&&color| color == Color32::WHITE).count();
+    /// assert_eq!(white, 32);
+    /// ```
+    pub fn quantize(&mut self, output: &PaletteOutput) {
+        let palette = &output.palette;
+        let (xs, ys) = self.scissor_bounds();
+        let rgb = |color: Color32| Vec3::new(color.r() as f32, color.g() as f32, color.b() as f32);
+        let with_alpha = |color: Color32, alpha: u8| {
+            Color32::from_rgba_unmultiplied(color.r(), color.g(), color.b(), alpha)
+        };
+
+        match output.dither {
+            DitherMode::None => {
+                for y in ys {
+                    for x in xs.clone() {
+                        let color = self[(x, y)];
+                        self[(x, y)] = with_alpha(palette.nearest(color), color.a());
+                    }
+                }
+            }
+            DitherMode::Ordered => {
+                let spread = palette.spread * 255.0;
+                for y in ys {
+                    for x in xs.clone() {
+                        // порог из [-0.5, 0.5) сдвигает все каналы одинаково
+                        let threshold = (BAYER_8X8[y % 8][x % 8] as f32 + 0.5) / 64.0 - 0.5;
+                        let color = self[(x, y)];
+                        let shifted = rgb(color) + Vec3::new(1.0, 1.0, 1.0) * (threshold * spread);
+                        self[(x, y)] = with_alpha(palette.nearest_rgb(shifted), color.a());
+                    }
+                }
+            }
+            DitherMode::FloydSteinberg => {
+                let width = xs.len();
+                // ошибки текущей и следующей строки
+                let mut current = vec![Vec3::zero(); width + 2];
+                let mut next = vec![Vec3::zero(); width + 2];
+                for y in ys {
+                    for (i, x) in xs.clone().enumerate() {
+                        let color = self[(x, y)];
+                        let wanted = rgb(color) + current[i + 1];
+                        let chosen = palette.nearest_rgb(wanted);
+                        let error = wanted - rgb(chosen);
+                        current[i + 2] += error * (7.0 / 16.0);
+                        next[i] += error * (3.0 / 16.0);
+                        next[i + 1] += error * (5.0 / 16.0);
+                        next[i + 2] += error * (1.0 / 16.0);
+                        self[(x, y)] = with_alpha(chosen, color.a());
+                    }
+                    std::mem::swap(&mut current, &mut next);
+                    next.fill(Vec3::zero());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod dithering_tests {
+    use super::*;
+
+    /// Холст с горизонтальным градиентом от чёрного к белому.
+    fn gradient(width: usize, height: usize) -> Canvas {
+        let mut canvas = Canvas::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                canvas[(x, y)] = Color32::from_gray((x * 255 / (width - 1)) as u8);
+            }
+        }
+        canvas
+    }
+
+    fn mean_gray(canvas: &Canvas) -> f32 {
+        canvas
+            .pixels()
+            .iter()
+            .map(|color| color.r() as f32)
+            .sum::<f32>()
+            / canvas.pixels().len() as f32
+    }
+
+    #[test]
+    fn test_quantize_uses_only_palette_colors() {
+        let palette = IndexedPalette::uniform_rgb(2);
+        for dither in [
+            DitherMode::None,
+            DitherMode::Ordered,
+            DitherMode::FloydSteinberg,
+        ] {
+            let mut canvas = gradient(32, 8);
+            canvas[(3, 3)] = Color32::from_rgb(250, 20, 30);
+            canvas.quantize(&PaletteOutput::new(palette.clone(), dither));
+            assert!(
+                canvas
+                    .pixels()
+                    .iter()
+                    .all(|color| palette.colors().contains(color)),
+                "{}: только цвета палитры",
+                dither
+            );
+        }
+    }
+
+    #[test]
+    fn test_dithering_preserves_mean_tone() {
+        let original = mean_gray(&gradient(64, 16));
+        let quantized = |dither| {
+            let mut canvas = gradient(64, 16);
+            canvas.quantize(&PaletteOutput::new(
+                IndexedPalette::black_and_white(),
+                dither,
+            ));
+            mean_gray(&canvas)
+        };
+
+        // с дизерингом средний тон градиента сохраняется, без него - только грубо
+        for dither in [DitherMode::Ordered, DitherMode::FloydSteinberg] {
+            assert!(
+                (quantized(dither) - original).abs() < 8.0,
+                "{}: средний тон",
+                dither
+            );
+        }
+        let mut canvas = Canvas::new(4, 4);
+        canvas.clear(Color32::from_gray(100));
+        canvas.quantize(&PaletteOutput::new(
+            IndexedPalette::black_and_white(),
+            DitherMode::None,
+        ));
+        assert!(canvas.pixels().iter().all(|&color| color == Color32::BLACK));
+    }
+}
@@ -0,0 +1,222 @@
+//! Освещение окружением (image-based lighting) по карте окружения.
+
+use std::{
+    f32::consts::PI,
+    hash::{Hash, Hasher},
+};
+
+use egui::Color32;
+
+use crate::library::state_hash::StateHash;
+use crate::{Background, UVec3, Vec3};
+
+/// Количество шагов по широте при проекции карты окружения в сферические гармоники.
+const PROJECTION_LATITUDE_STEPS: usize = 32;
+/// Количество шагов по долготе при проекции карты окружения в сферические гармоники.
+const PROJECTION_LONGITUDE_STEPS: usize = 64;
+
+/// Способ получения освещённости от карты окружения.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EnvironmentSampling {
+    /// Цвет карты берётся прямо по направлению нормали: быстро, но резко
+    /// повторяет детали карты.
+    Direct,
+    /// Освещённость восстанавливается по 9 коэффициентам сферических гармоник
+    /// (3 полосы), то есть карта окружения усредняется по полусфере вокруг нормали.
+    #[default]
+    SphericalHarmonics,
+}
+
+impl std::fmt::Display for EnvironmentSampling {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Direct => f.write_str("Напрямую"),
+            Self::SphericalHarmonics => f.write_str("Сферические гармоники"),
+        }
+    }
+}
+
+/// Освещение сцены картой окружения.
+///
+/// Добавляет рассеянный свет со всех направлений ко всем моделям освещения, так что
+/// нетекстурированные модели не выглядят плоскими. Картой может быть любой [`Background`],
+/// в том числе фон самой сцены. Коэффициенты сферических гармоник считаются один раз
+/// при задании карты.
+#[derive(Debug, Clone)]
+pub struct EnvironmentLight {
+    map: Background,
+    /// Коэффициенты сферических гармоник карты для каждого канала (r, g, b) в `[0, 1]`.
+    coefficients: [Vec3; 9],
+    /// Множитель яркости освещения.
+    pub intensity: f32,
+    /// Способ получения освещённости.
+    pub sampling: EnvironmentSampling,
+}
+
+impl EnvironmentLight {
+    pub fn new(map: Background, intensity: f32) -> Self {
+        let coefficients = project_to_harmonics(&map);
+        Self {
+            map,
+            coefficients,
+            intensity,
+            sampling: EnvironmentSampling::default(),
+        }
+    }
+
+    /// Карта окружения.
+    pub fn map(&self) -> &Background {
+        &self.map
+    }
+
+    /// Заменить карту окружения, коэффициенты сферических гармоник пересчитываются.
+    pub fn set_map(&mut self, map: Background) {
+        self.coefficients = project_to_harmonics(&map);
+        self.map = map;
+    }
+
+    /// Рассеянный свет, падающий на поверхность с нормалью `normal` в **глобальных** координатах.
+    ///
+    /// Для равномерной карты цвета `c` равен `c * intensity`, как и точечный источник,
+    /// результат умножается на цвет материала.
+    pub fn irradiance(&self, normal: UVec3) -> Color32 {
+        let color = match self.sampling {
+            EnvironmentSampling::Direct => {
+                let color = self.map.sample(normal.into());
+                Vec3::new(color.r() as f32, color.g() as f32, color.b() as f32) / 255.0
+            }
+            EnvironmentSampling::SphericalHarmonics => {
+                // свёртка с косинусом: коэффициенты полос A_l / π = 1, 2/3, 1/4
+                const BAND_WEIGHTS: [f32; 9] = [
+                    1.0,
+                    2.0 / 3.0,
+                    2.0 / 3.0,
+                    2.0 / 3.0,
+                    0.25,
+                    0.25,
+                    0.25,
+                    0.25,
+                    0.25,
+                ];
+                let basis = harmonics_basis(normal.into());
+                (0..9).fold(Vec3::zero(), |acc, i| {
+                    acc + self.coefficients[i] * (basis[i] * BAND_WEIGHTS[i])
+                })
+            }
+        };
+
+        let to_channel = |value: f32| (value * self.intensity * 255.0).clamp(0.0, 255.0) as u8;
+        Color32::from_rgb(
+            to_channel(color.x),
+            to_channel(color.y),
+            to_channel(color.z),
+        )
+    }
+}
+
+/// Значения 9 функций базиса сферических гармоник в направлении `direction` единичной длины.
+fn harmonics_basis(direction: Vec3) -> [f32; 9] {
+    let Vec3 { x, y, z } = direction;
+    [
+        0.282_095,
+        0.488_603 * y,
+        0.488_603 * z,
+        0.488_603 * x,
+        1.092_548 * x * y,
+        1.092_548 * y * z,
+        0.315_392 * (3.0 * z * z - 1.0),
+        1.092_548 * x * z,
+        0.546_274 * (x * x - y * y),
+    ]
+}
+
+/// Спроецировать карту окружения на базис сферических гармоник.
+///
+/// Сфера обходится по сетке широта-долгота, каждое направление учитывается с весом
+/// своего телесного угла.
+fn project_to_harmonics(map: &Background) -> [Vec3; 9] {
+    let mut coefficients = [Vec3::zero(); 9];
+    let d_theta = PI / PROJECTION_LATITUDE_STEPS as f32;
+    let d_phi = 2.0 * PI / PROJECTION_LONGITUDE_STEPS as f32;
+    for i in 0..PROJECTION_LATITUDE_STEPS {
+        let theta = (i as f32 + 0.5) * d_theta;
+        let solid_angle = theta.sin() * d_theta * d_phi;
+        for j in 0..PROJECTION_LONGITUDE_STEPS {
+            let phi = (j as f32 + 0.5) * d_phi;
+            let direction = Vec3::from(UVec3::from_spherical(theta, phi));
+            let color = map.sample(direction);
+            let color = Vec3::new(color.r() as f32, color.g() as f32, color.b() as f32) / 255.0;
+
+            for (coefficient, value) in coefficients.iter_mut().zip(harmonics_basis(direction)) {
+                *coefficient += color * (value * solid_angle);
+            }
+        }
+    }
+    coefficients
+}
+
+impl StateHash for EnvironmentLight {
+    fn hash_state<H: Hasher>(&self, state: &mut H) {
+        self.map.hash_state(state);
+        self.intensity.hash_state(state);
+        self.sampling.hash(state);
+    }
+}
+
+#[cfg(test)]
+mod environment_light_tests {
+    use super::*;
+
+    #[test]
+    fn test_uniform_map_gives_uniform_irradiance() {
+        let color = Color32::from_rgb(200, 100, 50);
+        let mut environment = EnvironmentLight::new(Background::Solid(color), 1.0);
+        for sampling in [
+            EnvironmentSampling::Direct,
+            EnvironmentSampling::SphericalHarmonics,
+        ] {
+            environment.sampling = sampling;
+            for normal in [UVec3::up(), UVec3::down(), UVec3::new(1.0, 1.0, -1.0)] {
+                let irradiance = environment.irradiance(normal);
+                for (got, expected) in [
+                    (irradiance.r(), color.r()),
+                    (irradiance.g(), color.g()),
+                    (irradiance.b(), color.b()),
+                ] {
+                    assert!(
+                        got.abs_diff(expected) <= 2,
+                        "{}: равномерная карта должна давать свой цвет, получено {:?}",
+                        sampling,
+                        irradiance
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_harmonics_follow_gradient() {
+        let environment = EnvironmentLight::new(
+            Background::VerticalGradient {
+                top: Color32::WHITE,
+                bottom: Color32::BLACK,
+            },
+            1.0,
+        );
+        let up = environment.irradiance(UVec3::up());
+        let side = environment.irradiance(UVec3::new(1.0, 0.0, 0.0));
+        let down = environment.irradiance(UVec3::down());
+        assert!(
+            up.r() > side.r() && side.r() > down.r(),
+            "снизу темнее, чем сверху: {:?} {:?} {:?}",
+            up,
+            side,
+            down
+        );
+        assert!(
+            side.r().abs_diff(128) <= 3,
+            "сбоку половина света, получено {:?}",
+            side
+        );
+    }
+}
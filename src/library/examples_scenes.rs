@@ -0,0 +1,347 @@
+//! Детерминированные демонстрационные сцены для примеров, бенчмарков и golden-тестов.
+//!
+//! Каждая сцена строится программно по `seed`: одинаковый `seed` всегда даёт одинаковую
+//! сцену вплоть до бита, поэтому туториалы, замеры производительности и эталонные
+//! изображения могут использовать одно и то же содержимое.
+
+use egui::Color32;
+use egui::ecolor::Hsva;
+use image::{DynamicImage, ImageBuffer, Luma};
+
+use crate::library::utils;
+use crate::{Camera, CullMode, LightSource, Mesh, Model, Point3, Scene, UVec3, Vec3};
+
+/// Половина стороны комнаты Корнелла.
+const ROOM_HALF_SIZE: f32 = 5.0;
+
+/// Количество разбиений стен комнаты Корнелла по каждой стороне.
+const ROOM_WALL_SUBDIVISIONS: usize = 8;
+
+/// Количество октав шума ландшафта.
+const TERRAIN_OCTAVES: usize = 4;
+
+/// Сторона ландшафта в глобальных координатах.
+const TERRAIN_SIZE: f32 = 20.0;
+
+/// Наибольшая высота ландшафта.
+const TERRAIN_HEIGHT: f32 = 3.0;
+
+/// Простой генератор псевдослучайных чисел (xorshift), одинаковый на всех платформах.
+struct SeedRng(u32);
+
+impl SeedRng {
+    fn new(seed: u32) -> Self {
+        // у xorshift нулевое состояние вырождено
+        Self(seed.wrapping_mul(0x9e37_79b9) | 1)
+    }
+
+    /// Следующее число из [0, 1).
+    fn next_f32(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        (self.0 >> 8) as f32 / (1 << 24) as f32
+    }
+
+    /// Следующее число из [min, max).
+    fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+}
+
+/// Камера с углом обзора 60° и соотношением сторон 16:9, смотрящая из `position` вдоль `look`.
+fn demo_camera(position: Point3, look: Vec3) -> Camera {
+    Camera::new(
+        position,
+        look.normalize().unwrap_or(UVec3::forward()),
+        UVec3::up(),
+        60_f32.to_radians(),
+        16.0 / 9.0,
+        1.0,
+        100.0,
+    )
+}
+
+// --------------------------------------------------
+// Комната Корнелла
+// --------------------------------------------------
+
+/// Комната наподобие коробки Корнелла: белые пол, потолок и задняя стена, красная левая
+/// и зелёная правая стены, два белых параллелепипеда внутри и источник света под потолком.
+///
+/// Передней стены нет, камера смотрит в комнату через неё. `seed` задаёт небольшие
+/// повороты параллелепипедов.
+///
+/// # Examples
+/// ```rust
+/// use g3d::examples_scenes;
+///
+/// let scene = examples_scenes::cornell_box(1);
+/// assert_eq!(scene.models.len(), 7);
+/// assert_eq!(scene.state_hash(), examples_scenes::cornell_box(1).state_hash());
+/// ```
+pub fn cornell_box(seed: u32) -> Scene {
+    let mut rng = SeedRng::new(seed);
+    let s = ROOM_HALF_SIZE;
+    let white = Color32::from_gray(220);
+
+    // стены: углы, нормаль внутрь комнаты и цвет
+    let red = Color32::from_rgb(190, 40, 40);
+    let green = Color32::from_rgb(40, 170, 60);
+    let walls = [
+        // пол
+        (
+            [(-s, -s, -s), (s, -s, -s), (s, -s, s), (-s, -s, s)],
+            UVec3::up(),
+            white,
+        ),
+        // потолок
+        (
+            [(-s, s, -s), (s, s, -s), (s, s, s), (-s, s, s)],
+            UVec3::down(),
+            white,
+        ),
+        // задняя стена
+        (
+            [(-s, -s, s), (s, -s, s), (s, s, s), (-s, s, s)],
+            UVec3::backward(),
+            white,
+        ),
+        // левая стена
+        (
+            [(-s, -s, -s), (-s, -s, s), (-s, s, s), (-s, s, -s)],
+            UVec3::right(),
+            red,
+        ),
+        // правая стена
+        (
+            [(s, -s, -s), (s, -s, s), (s, s, s), (s, s, -s)],
+            UVec3::left(),
+            green,
+        ),
+    ];
+    let mut models: Vec<Model> = walls
+        .into_iter()
+        .map(|(corners, normal, color)| {
+            let corners = corners.map(|(x, y, z)| Point3::new(x, y, z));
+            let mesh = Mesh::subdivided_quad(corners, normal, ROOM_WALL_SUBDIVISIONS);
+            let mut wall = Model::from_mesh(mesh);
+            wall.material.color = color;
+            wall.flags.cull_mode = CullMode::None;
+            wall
+        })
+        .collect();
+
+    // высокий и низкий параллелепипеды, стоящие на полу
+    for (position, size, angle) in [
+        (
+            Point3::new(-1.8, -s + 3.0, 1.5),
+            Vec3::new(3.0, 6.0, 3.0),
+            20.0,
+        ),
+        (
+            Point3::new(1.8, -s + 1.5, -1.2),
+            Vec3::new(3.0, 3.0, 3.0),
+            -18.0,
+        ),
+    ] {
+        let mut block = Model::from_mesh(Mesh::hexahedron());
+        block.material.color = white;
+        block.scale_vec(size);
+        block.rotate_local_y((angle + rng.range(-10.0, 10.0)).to_radians());
+        block.set_position(position);
+        models.push(block);
+    }
+
+    Scene {
+        models,
+        camera: demo_camera(Point3::new(0.0, 0.0, -s - 4.0), Vec3::new(0.0, 0.0, 1.0)),
+        lights: vec![
+            LightSource::new(Point3::new(0.0, s - 0.5, 0.0), Color32::WHITE, 1.0)
+                .with_name("ceiling"),
+        ],
+        ..Default::default()
+    }
+}
+
+// --------------------------------------------------
+// Галерея правильных многогранников
+// --------------------------------------------------
+
+/// Все пять правильных многогранников в ряд вдоль оси x со случайными по `seed`
+/// цветами и поворотами, источник света сверху спереди.
+///
+/// # Examples
+/// ```rust
+/// use g3d::examples_scenes;
+///
+/// let scene = examples_scenes::platonic_gallery(7);
+/// assert_eq!(scene.models.len(), 5);
+/// assert_ne!(scene.state_hash(), examples_scenes::platonic_gallery(8).state_hash());
+/// ```
+pub fn platonic_gallery(seed: u32) -> Scene {
+    let mut rng = SeedRng::new(seed);
+    let solids = [
+        Mesh::tetrahedron(),
+        Mesh::hexahedron(),
+        Mesh::octahedron(),
+        Mesh::dodecahedron(),
+        Mesh::icosahedron(),
+    ];
+    let count = solids.len();
+    let models: Vec<Model> = solids
+        .into_iter()
+        .enumerate()
+        .map(|(index, mesh)| {
+            let mut model = Model::from_mesh(mesh);
+            model.material.color = Hsva::new(rng.next_f32(), 0.6, 0.9, 1.0).into();
+            model.uniform_scale(1.5);
+            model.rotate_local_y(rng.range(0.0, 360.0).to_radians());
+            model.rotate_local_x(rng.range(-30.0, 30.0).to_radians());
+            model.set_position(Point3::new(
+                (index as f32 - (count - 1) as f32 / 2.0) * 3.0,
+                0.0,
+                0.0,
+            ));
+            model
+        })
+        .collect();
+
+    let mut scene = Scene {
+        models,
+        lights: vec![
+            LightSource::new(Point3::new(-4.0, 8.0, -8.0), Color32::WHITE, 1.0).with_name("key"),
+        ],
+        ..Default::default()
+    };
+    let mut camera = demo_camera(Point3::new(0.0, 2.0, -10.0), Vec3::new(0.0, -0.2, 1.0));
+    scene.frame_all(&mut camera);
+    scene.camera = camera;
+    scene
+}
+
+// --------------------------------------------------
+// Ландшафт из шума
+// --------------------------------------------------
+
+/// Ландшафт из фрактального шума в плоскости XZ: сетка `resolution` x `resolution` клеток
+/// стороной 20, центрированная в начале координат, с высотами от 0 до 3.
+///
+/// Вершины раскрашены по высоте: низины зелёные, склоны коричневые, вершины белые.
+pub fn noise_terrain_mesh(seed: u32, resolution: usize) -> Mesh {
+    let step = TERRAIN_SIZE / resolution.max(1) as f32;
+    let height = |i: u32, j: u32| {
+        let (x, z) = (i as f32 * step, j as f32 * step);
+        let (mut amplitude, mut frequency, mut sum) = (1.0, 0.15, 0.0);
+        for octave in 0..TERRAIN_OCTAVES {
+            let p = Vec3::new(x * frequency, 0.0, z * frequency);
+            sum += amplitude * utils::value_noise(p, seed.wrapping_add(octave as u32));
+            amplitude *= 0.5;
+            frequency *= 2.0;
+        }
+        // сумма октав из [-(2 - 2^-3), 2 - 2^-3], переводится в [0, 1]
+        (sum / 1.875 + 1.0) / 2.0
+    };
+    let size = resolution.max(1) as u32 + 1;
+    let heightmap = ImageBuffer::from_fn(size, size, |i, j| {
+        Luma([(height(i, j).clamp(0.0, 1.0) * u16::MAX as f32) as u16])
+    });
+    let mut mesh = Mesh::from_heightmap(
+        &DynamicImage::ImageLuma16(heightmap),
+        (TERRAIN_SIZE, TERRAIN_SIZE),
+        TERRAIN_HEIGHT,
+    );
+
+    let colors = mesh
+        .get_local_vertex_iter()
+        .map(|vertex| {
+            let t = (vertex.y / TERRAIN_HEIGHT).clamp(0.0, 1.0);
+            if t < 0.5 {
+                utils::lerp_color(
+                    Color32::from_rgb(60, 140, 50),
+                    Color32::from_rgb(130, 100, 60),
+                    t * 2.0,
+                )
+            } else {
+                utils::lerp_color(
+                    Color32::from_rgb(130, 100, 60),
+                    Color32::WHITE,
+                    t * 2.0 - 1.0,
+                )
+            }
+        })
+        .collect();
+    mesh.set_vertex_colors(colors);
+    mesh
+}
+
+/// Сцена с ландшафтом [`noise_terrain_mesh`] из 64 x 64 клеток, солнцем высоко над ним
+/// и камерой, смотрящей на ландшафт сверху под углом.
+///
+/// # Examples
+/// ```rust
+/// use g3d::examples_scenes;
+///
+/// let a = examples_scenes::noise_terrain(3);
+/// let b = examples_scenes::noise_terrain(3);
+/// assert_eq!(a.state_hash(), b.state_hash());
+/// ```
+pub fn noise_terrain(seed: u32) -> Scene {
+    let mut scene = Scene {
+        models: vec![Model::from_mesh(noise_terrain_mesh(seed, 64))],
+        lights: vec![
+            LightSource::new(Point3::new(10.0, 30.0, -10.0), Color32::WHITE, 1.0).with_name("sun"),
+        ],
+        ..Default::default()
+    };
+    let mut camera = demo_camera(Point3::new(0.0, 10.0, -20.0), Vec3::new(0.0, -0.6, 1.0));
+    scene.frame_all(&mut camera);
+    scene.camera = camera;
+    scene
+}
+
+#[cfg(test)]
+mod examples_scenes_tests {
+    use super::*;
+
+    #[test]
+    fn test_scenes_are_deterministic() {
+        for build in [cornell_box, platonic_gallery, noise_terrain] {
+            assert_eq!(build(5).state_hash(), build(5).state_hash());
+            assert_ne!(build(5).state_hash(), build(6).state_hash());
+        }
+    }
+
+    #[test]
+    fn test_cornell_box_walls_face_camera() {
+        let renderer = crate::SceneRenderer {
+            render_wireframe: false,
+            render_solid: true,
+            backface_culling: true,
+            projection_type: crate::ProjectionType::Perspective,
+            ..Default::default()
+        };
+        let mut canvas = crate::Canvas::new(300, 200);
+        renderer.render(
+            &cornell_box(0),
+            &mut canvas,
+            false,
+            Point3::zero(),
+            Point3::zero(),
+        );
+        assert_eq!(canvas[(5, 100)], Color32::from_rgb(190, 40, 40));
+        assert_eq!(canvas[(295, 100)], Color32::from_rgb(40, 170, 60));
+    }
+
+    #[test]
+    fn test_terrain_height_range() {
+        let mesh = noise_terrain_mesh(11, 16);
+        assert_eq!(mesh.vertex_count(), 17 * 17);
+        let heights: Vec<f32> = mesh.get_local_vertex_iter().map(|v| v.y).collect();
+        assert!(heights.iter().all(|&h| (0.0..=TERRAIN_HEIGHT).contains(&h)));
+        let (min, max) = heights.iter().fold((f32::MAX, f32::MIN), |(min, max), &h| {
+            (min.min(h), max.max(h))
+        });
+        assert!(max - min > 0.1, "ландшафт плоский");
+    }
+}
@@ -0,0 +1,446 @@
+//! Ручки для интерактивного перемещения, поворота и масштабирования моделей.
+
+use std::fmt::Display;
+
+use egui::{Color32, Pos2};
+
+use crate::{Canvas, CoordFrame, Line3, Plane, Point3, Transform3D, UVec3, Vec3};
+
+/// Количество отрезков, которыми рисуется кольцо поворота.
+const RING_SEGMENTS: usize = 48;
+/// Длина наконечника стрелки и половина стороны квадрата масштаба в пикселях.
+const HANDLE_TIP_SIZE: f32 = 6.0;
+/// Точность, с которой луч считается параллельным оси или плоскости ручки.
+const PARALLEL_EPSILON: f32 = 1e-6;
+
+/// Вид манипулятора.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GizmoMode {
+    /// Стрелки вдоль осей для перемещения.
+    #[default]
+    Translate,
+    /// Кольца вокруг осей для поворота.
+    Rotate,
+    /// Оси с квадратами на концах для масштабирования.
+    Scale,
+}
+
+impl Display for GizmoMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Translate => write!(f, "Перемещение"),
+            Self::Rotate => write!(f, "Поворот"),
+            Self::Scale => write!(f, "Масштаб"),
+        }
+    }
+}
+
+/// Локальная ось координатной системы, за которую взята ручка.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GizmoAxis {
+    /// Ось `right` (локальная x).
+    X,
+    /// Ось `up` (локальная y).
+    Y,
+    /// Ось `forward` (локальная z).
+    Z,
+}
+
+impl GizmoAxis {
+    /// Все оси в порядке x, y, z.
+    pub const ALL: [Self; 3] = [Self::X, Self::Y, Self::Z];
+
+    /// Направление оси системы `frame` в **глобальных** координатах.
+    pub fn direction(self, frame: &CoordFrame) -> UVec3 {
+        match self {
+            Self::X => frame.right(),
+            Self::Y => frame.up(),
+            Self::Z => frame.forward(),
+        }
+    }
+
+    /// Цвет ручки этой оси.
+    pub fn color(self) -> Color32 {
+        match self {
+            Self::X => Color32::RED,
+            Self::Y => Color32::GREEN,
+            Self::Z => Color32::BLUE,
+        }
+    }
+}
+
+/// Манипулятор (gizmo) для координатной системы модели.
+///
+/// Ручки строятся от точки отсчёта системы вдоль её осей и не зависят от её масштаба.
+/// Выбор ручки производится лучом (см. [`crate::Camera::screen_point_to_ray`]), а перетаскивание
+/// переводится в преобразование по двум лучам: в начале и в конце перемещения мыши.
+///
+/// # Examples
+/// ```rust
+/// use g3d::{CoordFrame, Gizmo, GizmoAxis, Line3, Point3, UVec3};
+///
+/// let mut frame = CoordFrame::global();
+/// let gizmo = Gizmo::default();
+///
+/// // луч сверху на середину стрелки оси x
+/// let from = Line3::new(Point3::new(0.5, 5.0, 0.0), UVec3::down());
+/// assert_eq!(gizmo.pick(&frame, &from), Some(GizmoAxis::X));
+///
+/// // перетащить стрелку на 2 единицы вдоль x
+/// let to = Line3::new(Point3::new(2.5, 5.0, 0.0), UVec3::down());
+/// let transform = gizmo.drag_transform(&frame, GizmoAxis::X, &from, &to).unwrap();
+/// frame.apply_transform(transform);
+/// assert!(frame.origin.approx_equal(Point3::new(2.0, 0.0, 0.0), 1.0e-4));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gizmo {
+    /// Вид манипулятора.
+    pub mode: GizmoMode,
+    /// Длина ручек (радиус колец) в **глобальных** координатах.
+    pub size: f32,
+    /// Расстояние до ручки, на котором луч её захватывает, в долях `size`.
+    pub pick_tolerance: f32,
+    /// Подсвеченная ручка, например та, над которой находится мышь.
+    pub highlighted: Option<GizmoAxis>,
+}
+
+impl Default for Gizmo {
+    fn default() -> Self {
+        Self {
+            mode: GizmoMode::default(),
+            size: 1.5,
+            pick_tolerance: 0.1,
+            highlighted: None,
+        }
+    }
+}
+
+impl Gizmo {
+    /// Создать манипулятор вида `mode` с параметрами по умолчанию.
+    pub fn new(mode: GizmoMode) -> Self {
+        Self {
+            mode,
+            ..Default::default()
+        }
+    }
+
+    // --------------------------------------------------
+    // Отрисовка
+    // --------------------------------------------------
+
+    /// Нарисовать ручки для системы `frame` поверх холста без проверки глубины.
+    pub fn draw(&self, frame: &CoordFrame, global_to_screen: Transform3D, canvas: &mut Canvas) {
+        let project = |point: Point3| -> Option<Pos2> {
+            let point = point.apply_transform(global_to_screen).ok()?;
+            Some(Pos2::new(point.x, point.y))
+        };
+        let Some(origin) = project(frame.origin) else {
+            return;
+        };
+
+        for axis in GizmoAxis::ALL {
+            let color = if self.highlighted == Some(axis) {
+                Color32::YELLOW
+            } else {
+                axis.color()
+            };
+            let direction = axis.direction(frame);
+
+            match self.mode {
+                GizmoMode::Translate | GizmoMode::Scale => {
+                    let Some(end) = project(frame.origin + direction * self.size) else {
+                        continue;
+                    };
+                    canvas.draw_sharp_line(origin, end, color);
+                    if self.mode == GizmoMode::Translate {
+                        draw_arrow_tip(canvas, origin, end, color);
+                    } else {
+                        draw_square(canvas, end, color);
+                    }
+                }
+                GizmoMode::Rotate => {
+                    let ring: Vec<Option<Pos2>> = ring_points(frame, axis, self.size)
+                        .into_iter()
+                        .map(project)
+                        .collect();
+                    for i in 0..RING_SEGMENTS {
+                        if let (Some(a), Some(b)) = (ring[i], ring[(i + 1) % RING_SEGMENTS]) {
+                            canvas.draw_sharp_line(a, b, color);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // --------------------------------------------------
+    // Выбор ручки
+    // --------------------------------------------------
+
+    /// Ручка, которую захватывает луч `ray` в **глобальных** координатах.
+    ///
+    /// Если луч проходит рядом с несколькими ручками, то выбирается ближайшая к ним.
+    pub fn pick(&self, frame: &CoordFrame, ray: &Line3) -> Option<GizmoAxis> {
+        let tolerance = self.pick_tolerance * self.size;
+        // луч ограничивается отрезком, заведомо проходящим мимо манипулятора
+        let ray_length = (frame.origin - ray.origin).length() + 2.0 * self.size;
+        let ray_end = ray.origin + ray.direction * ray_length;
+
+        let mut nearest: Option<(f32, GizmoAxis)> = None;
+        for axis in GizmoAxis::ALL {
+            let distance = match self.mode {
+                GizmoMode::Translate | GizmoMode::Scale => {
+                    let end = frame.origin + axis.direction(frame) * self.size;
+                    Line3::segment_distance(frame.origin, end, ray.origin, ray_end)
+                }
+                GizmoMode::Rotate => {
+                    let Some(point) = ray_plane_hit(ray, &axis_plane(frame, axis)) else {
+                        continue;
+                    };
+                    ((point - frame.origin).length() - self.size).abs()
+                }
+            };
+            if distance <= tolerance && nearest.is_none_or(|(nearest, _)| distance < nearest) {
+                nearest = Some((distance, axis));
+            }
+        }
+        nearest.map(|(_, axis)| axis)
+    }
+
+    // --------------------------------------------------
+    // Перетаскивание
+    // --------------------------------------------------
+
+    /// Преобразование в **глобальных** координатах для перетаскивания ручки `axis` от луча
+    /// `from` к лучу `to`.
+    ///
+    /// Перемещение сдвигает систему вдоль оси, поворот вращает её вокруг оси, проходящей через
+    /// точку отсчёта, масштаб растягивает вдоль оси относительно точки отсчёта. Результат
+    /// применяется к системе через [`CoordFrame::apply_transform`]. Возвращает `None`, если
+    /// луч параллелен оси (или плоскости кольца) и перемещение не определено.
+    pub fn drag_transform(
+        &self,
+        frame: &CoordFrame,
+        axis: GizmoAxis,
+        from: &Line3,
+        to: &Line3,
+    ) -> Option<Transform3D> {
+        let direction = axis.direction(frame);
+        match self.mode {
+            GizmoMode::Translate => {
+                let axis_line = Line3::new(frame.origin, direction);
+                let delta =
+                    closest_axis_param(&axis_line, to)? - closest_axis_param(&axis_line, from)?;
+                Some(Transform3D::translation_vec(direction * delta))
+            }
+            GizmoMode::Rotate => {
+                let plane = axis_plane(frame, axis);
+                let start = ray_plane_hit(from, &plane)? - frame.origin;
+                let end = ray_plane_hit(to, &plane)? - frame.origin;
+                let angle = Vec3::from(direction)
+                    .dot(start.cross(end))
+                    .atan2(start.dot(end));
+                Some(Transform3D::rotation_around_line(
+                    Line3::new(frame.origin, direction),
+                    angle,
+                ))
+            }
+            GizmoMode::Scale => {
+                let axis_line = Line3::new(frame.origin, direction);
+                let start = closest_axis_param(&axis_line, from)?;
+                let end = closest_axis_param(&axis_line, to)?;
+                if start.abs() < PARALLEL_EPSILON {
+                    return None;
+                }
+                Some(axis_scale(frame, axis, end / start))
+            }
+        }
+    }
+}
+
+// --------------------------------------------------
+// Вспомогательные методы
+// --------------------------------------------------
+
+/// Плоскость кольца поворота вокруг оси `axis`.
+fn axis_plane(frame: &CoordFrame, axis: GizmoAxis) -> Plane {
+    Plane::new(frame.origin, axis.direction(frame))
+}
+
+/// Точки кольца поворота вокруг оси `axis` в **глобальных** координатах.
+fn ring_points(frame: &CoordFrame, axis: GizmoAxis, radius: f32) -> Vec<Point3> {
+    // две другие оси задают плоскость кольца
+    let (u, v) = match axis {
+        GizmoAxis::X => (frame.up(), frame.forward()),
+        GizmoAxis::Y => (frame.forward(), frame.right()),
+        GizmoAxis::Z => (frame.right(), frame.up()),
+    };
+    (0..RING_SEGMENTS)
+        .map(|i| {
+            let angle = i as f32 / RING_SEGMENTS as f32 * std::f32::consts::TAU;
+            frame.origin + u * (radius * angle.cos()) + v * (radius * angle.sin())
+        })
+        .collect()
+}
+
+/// Пересечение луча (а не прямой) с плоскостью.
+fn ray_plane_hit(ray: &Line3, plane: &Plane) -> Option<Point3> {
+    let point = ray.intersect_plane(plane)?;
+    ((point - ray.origin).dot(Vec3::from(ray.direction)) >= 0.0).then_some(point)
+}
+
+/// Параметр ближайшей к лучу `ray` точки на оси `axis_line`.
+///
+/// `None`, если луч параллелен оси.
+fn closest_axis_param(axis_line: &Line3, ray: &Line3) -> Option<f32> {
+    let u = Vec3::from(axis_line.direction);
+    let v = Vec3::from(ray.direction);
+    let w = axis_line.origin - ray.origin;
+    let uv = u.dot(v);
+    let denominator = 1.0 - uv * uv;
+    if denominator.abs() < PARALLEL_EPSILON {
+        return None;
+    }
+    Some((uv * v.dot(w) - u.dot(w)) / denominator)
+}
+
+/// Масштабирование в `factor` раз вдоль локальной оси `axis` относительно точки отсчёта.
+fn axis_scale(frame: &CoordFrame, axis: GizmoAxis, factor: f32) -> Transform3D {
+    let scale = match axis {
+        GizmoAxis::X => Transform3D::scale(factor, 1.0, 1.0),
+        GizmoAxis::Y => Transform3D::scale(1.0, factor, 1.0),
+        GizmoAxis::Z => Transform3D::scale(1.0, 1.0, factor),
+    };
+    let (forward, right, up) = (frame.forward(), frame.right(), frame.up());
+    Transform3D::translation_vec(-Vec3::from(frame.origin))
+        .multiply(Transform3D::rotation_to_basis(forward, right, up))
+        .multiply(scale)
+        .multiply(Transform3D::rotation_from_basis(forward, right, up))
+        .multiply(Transform3D::translation_vec(Vec3::from(frame.origin)))
+}
+
+/// Наконечник стрелки на конце отрезка `[start, end]` в экранных координатах.
+fn draw_arrow_tip(canvas: &mut Canvas, start: Pos2, end: Pos2, color: Color32) {
+    let direction = (end - start).normalized();
+    if !direction.is_finite() {
+        return;
+    }
+    let back = end - direction * HANDLE_TIP_SIZE;
+    let side = direction.rot90() * (HANDLE_TIP_SIZE / 2.0);
+    canvas.draw_sharp_line(end, back + side, color);
+    canvas.draw_sharp_line(end, back - side, color);
+    canvas.draw_sharp_line(back + side, back - side, color);
+}
+
+/// Квадрат с центром в `center` в экранных координатах.
+fn draw_square(canvas: &mut Canvas, center: Pos2, color: Color32) {
+    let d = HANDLE_TIP_SIZE / 2.0;
+    let corners = [
+        Pos2::new(center.x - d, center.y - d),
+        Pos2::new(center.x + d, center.y - d),
+        Pos2::new(center.x + d, center.y + d),
+        Pos2::new(center.x - d, center.y + d),
+    ];
+    for i in 0..corners.len() {
+        canvas.draw_sharp_line(corners[i], corners[(i + 1) % corners.len()], color);
+    }
+}
+
+#[cfg(test)]
+mod gizmo_tests {
+    use super::*;
+
+    fn down_ray(x: f32, z: f32) -> Line3 {
+        Line3::new(Point3::new(x, 5.0, z), UVec3::down())
+    }
+
+    #[test]
+    fn test_pick_handles() {
+        let frame = CoordFrame::global();
+        let gizmo = Gizmo::default();
+
+        assert_eq!(gizmo.pick(&frame, &down_ray(1.0, 0.0)), Some(GizmoAxis::X));
+        assert_eq!(gizmo.pick(&frame, &down_ray(0.0, 1.0)), Some(GizmoAxis::Z));
+        assert_eq!(gizmo.pick(&frame, &down_ray(1.0, 1.0)), None, "мимо ручек");
+
+        let rotate = Gizmo::new(GizmoMode::Rotate);
+        // луч сверху на кольцо вокруг оси y
+        assert_eq!(rotate.pick(&frame, &down_ray(1.5, 0.0)), Some(GizmoAxis::Y));
+        assert_eq!(rotate.pick(&frame, &down_ray(0.5, 0.5)), None);
+    }
+
+    #[test]
+    fn test_drag_rotates_and_scales_frame() {
+        let mut frame = CoordFrame::global();
+        frame.origin = Point3::new(1.0, 0.0, 0.0);
+
+        // поворот кольца y на четверть оборота: x переходит в -z (левая система)
+        let rotate = Gizmo::new(GizmoMode::Rotate);
+        let transform = rotate
+            .drag_transform(
+                &frame,
+                GizmoAxis::Y,
+                &down_ray(2.0, 0.0),
+                &down_ray(1.0, 1.0),
+            )
+            .unwrap();
+        let mut rotated = frame;
+        rotated.apply_transform(transform);
+        assert!(
+            rotated.origin.approx_equal(frame.origin, 1e-4),
+            "точка отсчёта на месте"
+        );
+        let expected = Point3::new(2.0, 0.0, 0.0)
+            .apply_transform(transform)
+            .unwrap();
+        assert!(
+            expected.approx_equal(Point3::new(1.0, 0.0, 1.0), 1e-4),
+            "точка на кольце переходит в конец перетаскивания, получено {}",
+            expected
+        );
+
+        // растяжение оси x вдвое
+        let scale = Gizmo::new(GizmoMode::Scale);
+        let transform = scale
+            .drag_transform(
+                &frame,
+                GizmoAxis::X,
+                &down_ray(2.0, 0.0),
+                &down_ray(3.0, 0.0),
+            )
+            .unwrap();
+        frame.apply_transform(transform);
+        assert!(frame.origin.approx_equal(Point3::new(1.0, 0.0, 0.0), 1e-4));
+        assert!(
+            (frame.scale.x - 2.0).abs() < 1e-4,
+            "масштаб {:?}",
+            frame.scale
+        );
+        assert!((frame.scale.y - 1.0).abs() < 1e-4);
+
+        // луч вдоль оси не задаёт перемещение
+        let along = Line3::new(Point3::new(5.0, 0.0, 0.0), UVec3::new(-1.0, 0.0, 0.0));
+        assert!(
+            Gizmo::default()
+                .drag_transform(&frame, GizmoAxis::X, &along, &along)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_draw_highlighted_handle() {
+        let mut canvas = Canvas::new(100, 100);
+        let gizmo = Gizmo {
+            highlighted: Some(GizmoAxis::X),
+            size: 40.0,
+            ..Default::default()
+        };
+        // экранные координаты совпадают с глобальными
+        let mut frame = CoordFrame::global();
+        frame.origin = Point3::new(50.0, 50.0, 0.0);
+        gizmo.draw(&frame, Transform3D::identity(), &mut canvas);
+
+        assert_eq!(canvas[(70, 50)], Color32::YELLOW, "подсвеченная ось x");
+        assert_eq!(canvas[(50, 70)], Color32::GREEN, "ось y своего цвета");
+    }
+}
@@ -0,0 +1,95 @@
+//! Слои моделей и источников света для выборочной отрисовки.
+
+use std::ops::{BitAnd, BitOr, Not};
+
+/// Набор слоёв (битовая маска из 32 слоёв).
+///
+/// Модели и источники света лежат в одном или нескольких слоях, а рендер рисует только те,
+/// чьи слои пересекаются с [`crate::SceneRenderer::layer_mask`]. Так можно, например,
+/// держать коллизионные Mesh'и и вспомогательную геометрию в отдельных слоях и показывать
+/// их только в нужных окнах просмотра.
+///
+/// # Examples
+/// ```rust
+/// use g3d::LayerMask;
+///
+/// const HELPERS: LayerMask = LayerMask::layer(1);
+/// const COLLISION: LayerMask = LayerMask::layer(2);
+///
+/// let model_layers = LayerMask::DEFAULT | HELPERS;
+/// assert!(model_layers.intersects(HELPERS));
+/// assert!(!model_layers.intersects(COLLISION));
+///
+/// // всё, кроме коллизий
+/// let filter = !COLLISION;
+/// assert!(filter.intersects(model_layers));
+/// assert!(!filter.intersects(COLLISION));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LayerMask(pub u32);
+
+impl Default for LayerMask {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl LayerMask {
+    /// Все слои.
+    pub const ALL: Self = Self(u32::MAX);
+    /// Ни одного слоя. Объект без слоёв никогда не рисуется.
+    pub const NONE: Self = Self(0);
+    /// Слой 0, в котором по умолчанию лежат модели и источники света.
+    pub const DEFAULT: Self = Self(1);
+
+    /// Маска из одного слоя с номером `index` из [0, 32).
+    pub const fn layer(index: u32) -> Self {
+        assert!(index < u32::BITS, "номер слоя должен быть меньше 32");
+        Self(1 << index)
+    }
+
+    /// Есть ли у масок общий слой.
+    pub fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    /// Содержит ли маска все слои `other`.
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Маска с добавленными слоями `other`.
+    pub fn with(self, other: Self) -> Self {
+        self | other
+    }
+
+    /// Маска без слоёв `other`.
+    pub fn without(self, other: Self) -> Self {
+        self & !other
+    }
+}
+
+impl BitOr for LayerMask {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitAnd for LayerMask {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl Not for LayerMask {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        Self(!self.0)
+    }
+}
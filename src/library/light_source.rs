@@ -1,13 +1,24 @@
-use crate::Point3;
+use crate::{LayerMask, Point3};
 
 /// Точечный источник света.
 ///
 /// Свет от этого источника направлен по все стороны.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LightSource {
     pub position: Point3,
     pub color: egui::Color32,
     pub intensity: f32,
+    /// Имя источника, например для поиска через [`crate::Scene::light`].
+    pub name: String,
+    /// Группа источника, которую можно включать и выключать целиком
+    /// через [`crate::Scene::set_light_group_enabled`].
+    pub group: Option<String>,
+    /// Учитывается ли источник при рендере. Выключенный источник сохраняет свои настройки.
+    pub enabled: bool,
+    /// Слои источника. Источник освещает сцену, только если они пересекаются с
+    /// [`crate::SceneRenderer::layer_mask`].
+    pub layers: LayerMask,
 }
 
 impl LightSource {
@@ -16,6 +27,28 @@ impl LightSource {
             position,
             color,
             intensity,
+            name: String::new(),
+            group: None,
+            enabled: true,
+            layers: LayerMask::default(),
         }
     }
+
+    /// Задать имя источника.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Задать слои источника.
+    pub fn with_layers(mut self, layers: LayerMask) -> Self {
+        self.layers = layers;
+        self
+    }
+
+    /// Добавить источник в группу `group`.
+    pub fn with_group(mut self, group: impl Into<String>) -> Self {
+        self.group = Some(group.into());
+        self
+    }
 }
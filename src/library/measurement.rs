@@ -0,0 +1,248 @@
+//! Измерения на сцене: расстояния между точками, углы и габариты моделей.
+//!
+//! Точки для измерений обычно выбираются курсором через [`Scene::pick_point`], а результат
+//! можно показать на сцене аннотациями - ломаными, которые добавляются в [`Scene::polylines`].
+
+use crate::{Aabb, LineStyle, Model, Point3, Polyline3, Scene, SnapQuery, UVec3, Vec3};
+
+/// Количество отрезков дуги в аннотации угла.
+const ANGLE_ARC_SEGMENTS: usize = 16;
+
+/// Радиус дуги в аннотации угла в долях более короткой стороны угла.
+const ANGLE_ARC_RADIUS: f32 = 0.3;
+
+/// Расстояние между двумя точками.
+///
+/// # Examples
+/// ```rust
+/// use egui::Color32;
+/// use g3d::{DistanceMeasurement, LineStyle, Point3};
+///
+/// let measurement = DistanceMeasurement::new(Point3::zero(), Point3::new(3.0, 4.0, 0.0));
+/// assert_eq!(measurement.distance(), 5.0);
+///
+/// let annotation = measurement.annotation(LineStyle::new(Color32::YELLOW, 2.0));
+/// assert_eq!(annotation.len(), 1);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DistanceMeasurement {
+    /// Первая точка в **глобальных** координатах.
+    pub start: Point3,
+    /// Вторая точка в **глобальных** координатах.
+    pub end: Point3,
+}
+
+impl DistanceMeasurement {
+    /// Измерить расстояние от `start` до `end`.
+    pub fn new(start: Point3, end: Point3) -> Self {
+        Self { start, end }
+    }
+
+    /// Расстояние между точками.
+    pub fn distance(&self) -> f32 {
+        self.delta().length()
+    }
+
+    /// Смещение от первой точки ко второй, то есть расстояния по каждой оси.
+    pub fn delta(&self) -> Vec3 {
+        self.end - self.start
+    }
+
+    /// Аннотация для отрисовки: отрезок между точками.
+    pub fn annotation(&self, style: LineStyle) -> Vec<Polyline3> {
+        vec![Polyline3::from_points(vec![self.start, self.end], style)]
+    }
+}
+
+/// Угол между направлениями из вершины `vertex` на точки `a` и `b`.
+///
+/// # Examples
+/// ```rust
+/// use g3d::{AngleMeasurement, Point3};
+///
+/// let measurement = AngleMeasurement::new(
+///     Point3::new(1.0, 0.0, 0.0),
+///     Point3::zero(),
+///     Point3::new(0.0, 2.0, 0.0),
+/// );
+/// assert!((measurement.angle_deg().unwrap() - 90.0).abs() < 1e-4);
+///
+/// // совпадающие точки не задают угол
+/// assert_eq!(AngleMeasurement::new(Point3::zero(), Point3::zero(), Point3::zero()).angle(), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AngleMeasurement {
+    /// Конец первой стороны угла в **глобальных** координатах.
+    pub a: Point3,
+    /// Вершина угла в **глобальных** координатах.
+    pub vertex: Point3,
+    /// Конец второй стороны угла в **глобальных** координатах.
+    pub b: Point3,
+}
+
+impl AngleMeasurement {
+    /// Измерить угол `a - vertex - b`.
+    pub fn new(a: Point3, vertex: Point3, b: Point3) -> Self {
+        Self { a, vertex, b }
+    }
+
+    /// Направления сторон угла, если обе стороны ненулевой длины.
+    fn sides(&self) -> Option<(UVec3, UVec3)> {
+        let a = (self.a - self.vertex).normalize().ok()?;
+        let b = (self.b - self.vertex).normalize().ok()?;
+        Some((a, b))
+    }
+
+    /// Угол в радианах из [0, π], или `None`, если одна из точек совпадает с вершиной.
+    pub fn angle(&self) -> Option<f32> {
+        let (a, b) = self.sides()?;
+        Some(a.dot(b).clamp(-1.0, 1.0).acos())
+    }
+
+    /// Угол в градусах из [0, 180], или `None`, если одна из точек совпадает с вершиной.
+    pub fn angle_deg(&self) -> Option<f32> {
+        self.angle().map(f32::to_degrees)
+    }
+
+    /// Аннотация для отрисовки: обе стороны угла одной ломаной и дуга между ними.
+    ///
+    /// Для вырожденного угла возвращаются только стороны.
+    pub fn annotation(&self, style: LineStyle) -> Vec<Polyline3> {
+        let mut annotation = vec![Polyline3::from_points(
+            vec![self.a, self.vertex, self.b],
+            style,
+        )];
+        let Some((a, b)) = self.sides() else {
+            return annotation;
+        };
+
+        let radius = ANGLE_ARC_RADIUS
+            * (self.a - self.vertex)
+                .length()
+                .min((self.b - self.vertex).length());
+        let arc = (0..=ANGLE_ARC_SEGMENTS)
+            .map(|i| self.vertex + a.slerp(b, i as f32 / ANGLE_ARC_SEGMENTS as f32) * radius)
+            .collect();
+        annotation.push(Polyline3::from_points(arc, style));
+        annotation
+    }
+}
+
+/// Габариты модели: размеры её ограничивающего параллелепипеда в **глобальных** координатах.
+///
+/// # Examples
+/// ```rust
+/// use egui::Color32;
+/// use g3d::{BoundsMeasurement, LineStyle, Mesh, Model, Vec3};
+///
+/// let mut model = Model::from_mesh(Mesh::hexahedron());
+/// model.uniform_scale(2.0);
+/// let measurement = BoundsMeasurement::of_model(&model).unwrap();
+///
+/// assert!(measurement.size().approx_equal(Vec3::new(2.0, 2.0, 2.0), 1e-4));
+/// assert_eq!(measurement.annotation(LineStyle::default()).len(), 3);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundsMeasurement {
+    /// Ограничивающий параллелепипед.
+    pub aabb: Aabb,
+}
+
+impl BoundsMeasurement {
+    /// Габариты модели `model`, или `None`, если у неё нет вершин.
+    pub fn of_model(model: &Model) -> Option<Self> {
+        Some(Self {
+            aabb: model.mesh.global_aabb()?,
+        })
+    }
+
+    /// Размеры по осям x, y и z.
+    pub fn size(&self) -> Vec3 {
+        self.aabb.max - self.aabb.min
+    }
+
+    /// Аннотация для отрисовки: три размерные линии вдоль рёбер параллелепипеда,
+    /// выходящих из минимального угла, по осям x, y и z.
+    pub fn annotation(&self, style: LineStyle) -> Vec<Polyline3> {
+        let min = self.aabb.min;
+        let size = self.size();
+        [
+            Vec3::new(size.x, 0.0, 0.0),
+            Vec3::new(0.0, size.y, 0.0),
+            Vec3::new(0.0, 0.0, size.z),
+        ]
+        .into_iter()
+        .map(|edge| Polyline3::from_points(vec![min, min + edge], style))
+        .collect()
+    }
+}
+
+impl Scene {
+    /// Точка видимой модели под курсором запроса `query` для измерений,
+    /// или `None`, если луч не попадает ни в одну модель.
+    ///
+    /// Запрос строится по кадру так же, как для привязки, см. [`SnapQuery::from_screen`].
+    pub fn pick_point(&self, query: &SnapQuery) -> Option<Point3> {
+        self.raycast(&query.ray, None).map(|(_, hit)| hit.point)
+    }
+}
+
+#[cfg(test)]
+mod measurement_tests {
+    use super::*;
+    use crate::{Canvas, Mesh, ProjectionType};
+    use egui::Pos2;
+
+    #[test]
+    fn test_angle_annotation_arc() {
+        let measurement = AngleMeasurement::new(
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::zero(),
+            Point3::new(0.0, 0.0, 4.0),
+        );
+        let annotation = measurement.annotation(LineStyle::default());
+        assert_eq!(annotation.len(), 2);
+        assert_eq!(annotation[0].points()[1], Point3::zero());
+
+        // дуга радиусом в долю короткой стороны соединяет обе стороны
+        let arc = annotation[1].points();
+        assert_eq!(arc.len(), ANGLE_ARC_SEGMENTS + 1);
+        assert!(arc[0].approx_equal(Point3::new(0.6, 0.0, 0.0), 1e-5));
+        assert!(arc[ANGLE_ARC_SEGMENTS].approx_equal(Point3::new(0.0, 0.0, 0.6), 1e-5));
+        assert!(
+            arc.iter()
+                .all(|point| ((*point - Point3::zero()).length() - 0.6).abs() < 1e-5)
+        );
+    }
+
+    #[test]
+    fn test_pick_points_and_measure() {
+        let mut cube = Model::from_mesh(Mesh::hexahedron());
+        cube.uniform_scale(2.0);
+        let scene = Scene {
+            models: vec![cube],
+            ..Default::default()
+        };
+        let canvas = Canvas::new(300, 200);
+        let pick = |x: f32, y: f32| {
+            let query = SnapQuery::from_screen(
+                &scene.camera,
+                ProjectionType::Perspective,
+                &canvas,
+                Pos2::new(x, y),
+            )
+            .unwrap();
+            scene.pick_point(&query)
+        };
+
+        // обе точки на передней грани куба, обращённой к камере
+        let start = pick(150.0, 100.0).unwrap();
+        let end = pick(160.0, 100.0).unwrap();
+        assert!((start.z + 1.0).abs() < 1e-3 && (end.z + 1.0).abs() < 1e-3);
+        let measurement = DistanceMeasurement::new(start, end);
+        assert!(measurement.distance() > 0.0);
+        assert!(measurement.delta().z.abs() < 1e-3);
+
+        assert_eq!(pick(5.0, 5.0), None, "мимо модели");
+    }
+}
@@ -9,12 +9,35 @@ pub mod primitives;
 pub mod model;
 
 // прочие структуры
+pub mod background;
+pub(crate) mod bvh;
 pub mod camera;
+pub mod camera_track;
 pub mod canvas;
+pub mod color;
 pub mod coord_frame;
+pub mod dithering;
+pub mod environment_light;
+pub mod examples_scenes;
+pub mod gizmo;
+pub mod layers;
 pub mod light_source;
+pub mod measurement;
+pub mod physics;
+pub mod polyline;
+pub mod raytrace_renderer;
+pub mod render_job;
+pub mod render_target;
 pub mod scene;
+pub mod scene_command;
 pub mod scene_renderer;
+pub mod snapping;
+pub mod swap_canvas;
+pub mod testing;
+pub mod texture_projector;
+pub mod tone_mapping;
+pub mod turntable;
 
 // вспомогательные методы
+pub(crate) mod state_hash;
 pub mod utils;
@@ -1,32 +1,151 @@
-use crate::{CoordFrame, UVec3};
+use crate::library::state_hash::StateHash;
+use crate::{CoordFrame, LayerMask, SectionBox, UVec3, library::utils};
 
 use super::primitives::{Point3, Transform3D, Vec3};
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::{BufRead, BufReader, Write};
-use std::path::Path;
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 // составные части модели
+mod billboard;
+mod lod_group;
 mod material;
 mod mesh;
+mod obj_stream;
+mod scene_helpers;
+mod skeleton;
 mod surface_generator;
 mod texture;
+mod voxel_grid;
 
 // re-export в модуль `model`
+pub use billboard::*;
+pub use lod_group::*;
 pub use material::*;
 pub use mesh::*;
+pub use obj_stream::*;
+pub use scene_helpers::*;
+pub use skeleton::*;
 pub use surface_generator::*;
 pub use texture::*;
+pub use voxel_grid::*;
 
 /// Модель (объект) в 3D пространстве.
 ///
 /// По сути просто контейнер для Mesh'а и его материала, где Mesh задаёт форму модели, а материал отображение (цвет).
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Model {
     /// Mesh модели.
     pub mesh: Mesh,
     /// Материал модели.
     pub material: Material,
+    /// Секущий параллелепипед. Если задан, то отрисовывается только часть модели внутри него.
+    pub section_box: Option<SectionBox>,
+    /// Флаги отрисовки модели.
+    pub flags: ModelFlags,
+    /// Скелет модели. Если задан, то Mesh перед отрисовкой деформируется текущей позой скелета.
+    pub skeleton: Option<Skeleton>,
+    /// Веса форм Mesh'а по их именам. Формы без веса не применяются.
+    morph_weights: HashMap<String, f32>,
+    /// Упрощённые уровни детализации Mesh'а. Уровень 0 - сам `mesh`.
+    pub lod_group: LodGroup,
+    /// Если задан, то при отрисовке модель поворачивается к камере (см. [`BillboardMode`]).
+    pub billboard: Option<BillboardMode>,
+    /// Имя модели для поиска на сцене (см. [`crate::Scene::model_by_name`]), если задано.
+    pub name: Option<String>,
+    /// Идентификатор, выданный сценой при добавлении модели. `None` у моделей,
+    /// добавленных в `scene.models` напрямую.
+    ///
+    /// Идентификаторы уникальны только в пределах запуска программы, поэтому
+    /// не сохраняются: загруженная модель получает новый при добавлении на сцену.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    id: Option<ModelId>,
+}
+
+/// Стабильный идентификатор модели на сцене.
+///
+/// В отличие от индекса в `scene.models`, не меняется при удалении других моделей.
+/// Выдаётся [`crate::Scene::add_model`] и уникален в пределах всей программы.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ModelId(u64);
+
+impl ModelId {
+    /// Выдать новый, ещё не использованный идентификатор.
+    pub(crate) fn next() -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        Self(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl std::fmt::Display for ModelId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// Флаги, определяющие, как модель отрисовывается на сцене.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ModelFlags {
+    /// Отрисовывать ли модель вообще.
+    pub visible: bool,
+    /// Отбрасывает ли модель тени на другие модели.
+    pub casts_shadows: bool,
+    /// Отрисовывать ли модель только каркасом, даже если рендер граней включён.
+    pub wireframe_only: bool,
+    /// Выделена ли модель. Выделенные модели обводятся цветом выделения.
+    pub selected: bool,
+    /// Какие грани модели отсекаются, если отсечение граней включено в рендерере.
+    pub cull_mode: CullMode,
+    /// Записывает ли модель глубину в z-буфер. Модели без записи глубины (например, небо)
+    /// рисуются раньше остальных и не перекрывают их.
+    pub depth_write: bool,
+    /// Слои модели. Модель рисуется, только если они пересекаются с
+    /// [`crate::SceneRenderer::layer_mask`].
+    pub layers: LayerMask,
+}
+
+/// Какие грани модели отсекаются при отсечении нелицевых граней.
+///
+/// Лицевая грань - та, нормаль которой направлена к камере.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CullMode {
+    /// Отсекаются лицевые грани, видна только внутренняя сторона модели.
+    Front,
+    /// Отсекаются нелицевые грани.
+    #[default]
+    Back,
+    /// Грани не отсекаются.
+    None,
+}
+
+impl std::fmt::Display for CullMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Front => f.write_str("Лицевые"),
+            Self::Back => f.write_str("Нелицевые"),
+            Self::None => f.write_str("Без отсечения"),
+        }
+    }
+}
+
+impl Default for ModelFlags {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            casts_shadows: true,
+            wireframe_only: false,
+            selected: false,
+            cull_mode: CullMode::default(),
+            depth_write: true,
+            layers: LayerMask::default(),
+        }
+    }
 }
 
 impl Model {
@@ -39,6 +158,14 @@ impl Model {
         Self {
             mesh,
             material: Material::default(),
+            section_box: None,
+            flags: ModelFlags::default(),
+            skeleton: None,
+            morph_weights: HashMap::new(),
+            lod_group: LodGroup::new(),
+            billboard: None,
+            name: None,
+            id: None,
         }
     }
 
@@ -47,109 +174,44 @@ impl Model {
     /// По идее, .obj файла должно хватить для всей информации о Mesh модели,
     /// но при этом материал и текстура там вроде не хранятся.
     pub fn load_from_obj(file_path: &str) -> Result<Self, ObjLoadError> {
-        let path = Path::new(file_path);
-        if !path.exists() {
-            return Err(ObjLoadError::FileNotFound);
-        }
-
-        let file = File::open(file_path).map_err(|_| ObjLoadError::FileNotFound)?;
-        let reader = BufReader::new(file);
-
-        let mut vertexes = Vec::new();
-        let mut polygons = Vec::new();
-        // let mut current_line = 0;
-
-        for line in reader.lines() {
-            // current_line += 1;
-            let line = line.map_err(|_| ObjLoadError::InvalidFormat)?;
-            let trimmed = line.trim();
-
-            // Пропускаем комментарии и пустые строки
-            if trimmed.starts_with('#') || trimmed.is_empty() {
-                continue;
-            }
+        ObjStreamLoader::open(file_path)?.finish()
+    }
 
-            let parts: Vec<&str> = trimmed.split_whitespace().collect();
-            if parts.len() < 2 {
-                continue;
-            }
+    /// Загрузить модель из .obj файла, заданного в системе координат с ориентацией `handedness`.
+    ///
+    /// Mesh переводится в левую систему координат библиотеки.
+    pub fn load_from_obj_with_handedness(
+        file_path: &str,
+        handedness: Handedness,
+    ) -> Result<Self, ObjLoadError> {
+        ObjStreamLoader::open(file_path)?
+            .with_handedness(handedness)
+            .finish()
+    }
 
-            match parts[0] {
-                "v" => {
-                    // Vertex: v x y z [w]
-                    if parts.len() >= 4 {
-                        let x = parts[1]
-                            .parse::<f32>()
-                            .map_err(|_| ObjLoadError::InvalidFormat)?;
-                        let y = parts[2]
-                            .parse::<f32>()
-                            .map_err(|_| ObjLoadError::InvalidFormat)?;
-                        let z = parts[3]
-                            .parse::<f32>()
-                            .map_err(|_| ObjLoadError::InvalidFormat)?;
-
-                        vertexes.push(Point3::new(x, y, z));
-                    }
-                }
-                "f" => {
-                    // Face: f v1 v2 v3 ...
-                    if parts.len() >= 4 {
-                        let mut face_vertex_indices = Vec::new();
-
-                        for i in 1..parts.len() {
-                            // OBJ формат может быть: "v", "v/vt", или "v/vt/vn"
-                            // Нас интересует только индекс вершины
-                            let vertex_part = parts[i].split('/').next().unwrap();
-                            let vertex_index = vertex_part
-                                .parse::<i32>()
-                                .map_err(|_| ObjLoadError::InvalidFormat)?;
-
-                            // OBJ индексы начинаются с 1, наши с 0
-                            if vertex_index > 0 {
-                                if (vertex_index as usize) <= vertexes.len() {
-                                    face_vertex_indices.push((vertex_index - 1) as usize);
-                                } else {
-                                    return Err(ObjLoadError::InvalidFormat);
-                                }
-                            } else if vertex_index < 0 {
-                                // Отрицательные индексы (относительные)
-                                let actual_index = (vertexes.len() as i32 + vertex_index) as usize;
-                                if actual_index < vertexes.len() {
-                                    face_vertex_indices.push(actual_index);
-                                } else {
-                                    return Err(ObjLoadError::InvalidFormat);
-                                }
-                            }
-                        }
-
-                        if face_vertex_indices.len() >= 3 {
-                            polygons.push(Polygon::from_list(&face_vertex_indices));
-                        }
-                    }
-                }
-                "vt" | "vn" | "vp" => {
-                    // Пока игнорируем текстурные координаты, нормали и параметрические вершины
-                    continue;
-                }
-                _ => {
-                    // Игнорируем неизвестные типы
-                    continue;
-                }
-            }
-        }
+    /// Сохранить текущую модель в .obj файл
+    pub fn save_to_obj(&self, file_path: &str) -> Result<(), ObjSaveError> {
+        self.save_to_obj_with_handedness(file_path, Handedness::LeftHanded)
+    }
 
-        if vertexes.is_empty() || polygons.is_empty() {
-            return Err(ObjLoadError::InvalidFormat);
+    /// Сохранить текущую модель в .obj файл в системе координат с ориентацией `handedness`.
+    pub fn save_to_obj_with_handedness(
+        &self,
+        file_path: &str,
+        handedness: Handedness,
+    ) -> Result<(), ObjSaveError> {
+        if handedness != Handedness::LeftHanded {
+            let mut converted = self.clone();
+            converted
+                .mesh
+                .convert_handedness(Handedness::LeftHanded, handedness);
+            return converted.write_obj(file_path);
         }
-
-        // Создаем Mesh из вершин и полигонов
-        let mesh = Mesh::from_polygons(vertexes, polygons);
-
-        Ok(Self::from_mesh(mesh))
+        self.write_obj(file_path)
     }
 
-    /// Сохранить текущую модель в .obj файл
-    pub fn save_to_obj(&self, file_path: &str) -> Result<(), ObjSaveError> {
+    /// Записать Mesh модели в .obj файл без перевода координат.
+    fn write_obj(&self, file_path: &str) -> Result<(), ObjSaveError> {
         let mut file = File::create(file_path).map_err(|_| ObjSaveError::WriteError)?;
 
         // Записываем заголовок
@@ -308,6 +370,48 @@ impl Model {
         self.mesh.local_frame.reflect_yz();
     }
 
+    /// Идентификатор модели на сцене, если она добавлена через [`crate::Scene::add_model`].
+    ///
+    /// Копия модели (`clone`) сохраняет идентификатор оригинала, пока её саму
+    /// не добавят через [`crate::Scene::add_model`], который всегда выдаёт новый.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::{Mesh, Model, Scene};
+    ///
+    /// let mut scene = Scene::default();
+    /// let id = scene.add_model(Model::from_mesh(Mesh::hexahedron()));
+    /// let copy = scene.models[0].clone();
+    /// assert_eq!(copy.id(), Some(id));
+    ///
+    /// let copy_id = scene.add_model(copy);
+    /// assert_ne!(copy_id, id);
+    /// assert_eq!(scene.model_index(id), Some(0));
+    /// ```
+    pub fn id(&self) -> Option<ModelId> {
+        self.id
+    }
+
+    /// Выдать модели новый идентификатор.
+    pub(crate) fn assign_id(&mut self) -> ModelId {
+        let id = ModelId::next();
+        self.id = Some(id);
+        id
+    }
+
+    /// Ограничивающий параллелепипед модели `(min, max)` в **глобальных** координатах.
+    pub fn global_bounds(&self) -> (Point3, Point3) {
+        let vertexes: Vec<Point3> = self.mesh.get_global_vertex_iter().collect();
+        utils::calculate_bounds(&vertexes)
+    }
+
+    /// Пересекается ли модель с моделью `other` в текущем положении.
+    ///
+    /// Сравниваются Mesh'и моделей, см. [`Mesh::intersects`].
+    pub fn intersects(&self, other: &Model) -> bool {
+        self.mesh.intersects(&other.mesh)
+    }
+
     /// Текущая позиция модели
     pub fn get_position(&self) -> Point3 {
         self.mesh.local_frame.origin
@@ -316,15 +420,284 @@ impl Model {
     /// Поставить модель в новую позицию.
     ///
     /// Просто синтаксический сахар для более удобных операций над моделькой.
+    /// Масштаб и поворот модели при этом сохраняются.
     pub fn set_position(&mut self, position: Point3) {
-        let current_frame = self.mesh.local_frame;
-        let new_frame = CoordFrame::new(
-            current_frame.forward(),
-            current_frame.right(),
-            current_frame.up(),
-            position,
+        self.mesh.local_frame.origin = position;
+    }
+
+    /// "Запечь" положение, поворот и масштаб модели в вершины Mesh'а.
+    ///
+    /// Вершины и нормали Mesh'а (и уровней детализации) переводятся в глобальные координаты,
+    /// а локальная система координат становится глобальной. Изображение модели при этом
+    /// не меняется. Нужно перед экспортом, CSG и физикой, где геометрия должна совпадать
+    /// с отображаемой. Позы скелета не пересчитываются.
+    pub fn bake_transform(&mut self) {
+        let transform = self.mesh.local_frame.local_to_global_matrix();
+        self.mesh.apply_transform(transform);
+        self.mesh.local_frame = CoordFrame::global();
+        for (_, lod_mesh) in &mut self.lod_group.levels {
+            lod_mesh.apply_transform(transform);
+        }
+    }
+
+    // --------------------------------------------------
+    // Отсечение
+    // --------------------------------------------------
+
+    /// Какие грани модели отсекаются с учётом материала.
+    ///
+    /// У двустороннего материала грани не отсекаются независимо от [`ModelFlags::cull_mode`].
+    pub fn cull_mode(&self) -> CullMode {
+        if self.material.double_sided {
+            CullMode::None
+        } else {
+            self.flags.cull_mode
+        }
+    }
+
+    /// Разворачиваются ли нормали обратных граней модели при освещении.
+    ///
+    /// Нормали разворачиваются, если обратные грани модели должны быть видны: у двустороннего
+    /// материала или если нелицевые грани модели не отсекаются.
+    pub fn flips_back_face_normals(&self) -> bool {
+        self.cull_mode() != CullMode::Back
+    }
+
+    /// Получить модель, отсечённую секущим параллелепипедом.
+    ///
+    /// Если задан секущий параллелепипед, то возвращается копия модели с отсечённой геометрией.
+    pub fn clipped_by_section_box(&self) -> std::borrow::Cow<'_, Model> {
+        match &self.section_box {
+            Some(section_box) => {
+                std::borrow::Cow::Owned(self.with_mesh(self.mesh.clip_by_section_box(section_box)))
+            }
+            None => std::borrow::Cow::Borrowed(self),
+        }
+    }
+
+    /// Получить модель в том виде, в котором она отрисовывается.
+    ///
+    /// Сначала смешиваются формы Mesh'а с заданными весами, затем Mesh деформируется позой
+    /// скелета (если он задан и вершины к нему привязаны), и наконец отсекается секущим
+    /// параллелепипедом. Если ничего из этого не задано, то модель не копируется.
+    pub fn render_geometry(&self) -> std::borrow::Cow<'_, Model> {
+        let morphed = self.mesh.has_morph_targets() && !self.morph_weights.is_empty();
+        let skinned = self.skeleton.is_some() && self.mesh.has_bone_weights();
+        if !morphed && !skinned {
+            return self.clipped_by_section_box();
+        }
+
+        let mut mesh = if morphed {
+            self.mesh.morphed(&self.morph_weights)
+        } else {
+            self.mesh.clone()
+        };
+        if let Some(skeleton) = &self.skeleton
+            && skinned
+        {
+            mesh = mesh.skinned(skeleton);
+        }
+        if let Some(section_box) = &self.section_box {
+            mesh = mesh.clip_by_section_box(section_box);
+        }
+        std::borrow::Cow::Owned(self.with_mesh(mesh))
+    }
+
+    /// Получить модель в том виде, в котором она отрисовывается на уровне детализации `lod`.
+    ///
+    /// Уровень 0 совпадает с `render_geometry`. Упрощённые уровни не деформируются скелетом
+    /// и формами, но следуют за положением модели и отсекаются секущим параллелепипедом.
+    /// Уровень больше доступного заменяется самым грубым.
+    pub fn render_geometry_at_lod(&self, lod: usize) -> std::borrow::Cow<'_, Model> {
+        let lod = lod.min(self.lod_group.len());
+        let Some(lod_mesh) = self.lod_group.get_mesh(lod) else {
+            return self.render_geometry();
+        };
+        let mut mesh = lod_mesh.clone();
+        mesh.local_frame = self.mesh.local_frame;
+        if let Some(section_box) = &self.section_box {
+            mesh = mesh.clip_by_section_box(section_box);
+        }
+        std::borrow::Cow::Owned(self.with_mesh(mesh))
+    }
+
+    /// Копия модели с другим Mesh'ем, в которой вся деформация и отсечение уже применены.
+    fn with_mesh(&self, mesh: Mesh) -> Model {
+        Model {
+            mesh,
+            material: self.material.clone(),
+            section_box: None,
+            flags: self.flags,
+            skeleton: None,
+            morph_weights: HashMap::new(),
+            lod_group: LodGroup::new(),
+            billboard: self.billboard,
+            name: self.name.clone(),
+            id: self.id,
+        }
+    }
+
+    // --------------------------------------------------
+    // Уровни детализации
+    // --------------------------------------------------
+
+    /// Сгенерировать `level_count` упрощённых уровней детализации.
+    ///
+    /// Каждый следующий уровень содержит долю `ratio` треугольников предыдущего
+    /// (см. `Mesh::simplify`). Первый уровень включается с расстояния `switch_distance`,
+    /// каждый следующий - с вдвое большего. Прошлые уровни заменяются.
+    pub fn generate_lods(&mut self, level_count: usize, ratio: f32, switch_distance: f32) {
+        debug_assert!(
+            0.0 < ratio && ratio < 1.0,
+            "доля треугольников {} должна быть в (0, 1)",
+            ratio
         );
-        self.mesh.local_frame = new_frame;
+
+        let mut group = LodGroup::new();
+        let mut distance = switch_distance;
+        for _ in 0..level_count {
+            let previous = group.levels.last().map_or(&self.mesh, |(_, mesh)| mesh);
+            let simplified = previous.simplify(ratio);
+            // дальше упрощать некуда
+            if simplified.polygon_count() >= previous.polygon_count() {
+                break;
+            }
+            group.add_level(distance, simplified);
+            distance *= 2.0;
+        }
+        self.lod_group = group;
+    }
+
+    /// Количество уровней детализации вместе с исходным Mesh'ем.
+    pub fn lod_count(&self) -> usize {
+        self.lod_group.len() + 1
+    }
+
+    /// Получить Mesh уровня детализации `lod`. Уровень 0 - исходный Mesh.
+    pub fn get_lod_mesh(&self, lod: usize) -> Option<&Mesh> {
+        match lod {
+            0 => Some(&self.mesh),
+            _ => self.lod_group.get_mesh(lod),
+        }
+    }
+
+    /// Выбрать уровень детализации по расстоянию от точки `eye` до центра модели.
+    pub fn select_lod_by_distance(&self, eye: Point3) -> usize {
+        if self.lod_group.is_empty() {
+            return 0;
+        }
+        let (min, max) = self.global_bounds();
+        let center = Point3::from((Vec3::from(min) + Vec3::from(max)) * 0.5);
+        self.lod_group.select((center - eye).length())
+    }
+
+    // --------------------------------------------------
+    // Морф-таргеты
+    // --------------------------------------------------
+
+    /// Задать вес формы Mesh'а с именем `name`.
+    ///
+    /// Нулевой вес убирает форму из смешивания. Вес для несуществующей формы игнорируется.
+    pub fn set_morph_weight(&mut self, name: &str, weight: f32) {
+        if self.mesh.find_morph_target(name).is_none() {
+            #[cfg(debug_assertions)]
+            eprintln!("Warning: у Mesh'а нет формы с именем '{}'", name);
+            return;
+        }
+
+        if weight == 0.0 {
+            self.morph_weights.remove(name);
+        } else {
+            self.morph_weights.insert(name.to_owned(), weight);
+        }
+    }
+
+    /// Получить вес формы Mesh'а с именем `name`.
+    pub fn get_morph_weight(&self, name: &str) -> f32 {
+        self.morph_weights.get(name).copied().unwrap_or(0.0)
+    }
+
+    /// Обнулить веса всех форм.
+    pub fn reset_morph_weights(&mut self) {
+        self.morph_weights.clear();
+    }
+}
+
+impl StateHash for Model {
+    fn hash_state<H: Hasher>(&self, state: &mut H) {
+        self.mesh.hash_state(state);
+        self.material.hash_state(state);
+        self.section_box.hash_state(state);
+        self.flags.hash_state(state);
+        self.skeleton.hash_state(state);
+        // порядок обхода HashMap не определён, поэтому веса сортируются по имени
+        let mut weights: Vec<(&String, &f32)> = self.morph_weights.iter().collect();
+        weights.sort_by(|a, b| a.0.cmp(b.0));
+        state.write_usize(weights.len());
+        for (name, weight) in weights {
+            name.hash(state);
+            weight.hash_state(state);
+        }
+        self.lod_group.hash_state(state);
+        self.billboard.hash(state);
+    }
+}
+
+/// Ориентация (handedness) системы координат файла модели.
+///
+/// Сама библиотека использует левую систему координат, а модели в .obj файлах чаще всего
+/// заданы в правой, поэтому без перевода они отображаются зеркально. Перевод между
+/// ориентациями отражает ось z (см. [`Mesh::convert_handedness`]).
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Handedness {
+    /// Левая система координат, как в библиотеке: перевод не нужен.
+    #[default]
+    LeftHanded,
+    /// Правая система координат.
+    RightHanded,
+}
+
+impl std::fmt::Display for Handedness {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::LeftHanded => f.write_str("Левая"),
+            Self::RightHanded => f.write_str("Правая"),
+        }
+    }
+}
+
+/// Координатная ось, например направленная вверх в файле модели.
+///
+/// В библиотеке вверх направлена ось y (см. [`Mesh::convert_up_axis`]).
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    /// Ось x.
+    X,
+    /// Ось y.
+    #[default]
+    Y,
+    /// Ось z.
+    Z,
+}
+
+impl Axis {
+    /// Единичный вектор положительного направления оси.
+    pub fn unit_vector(self) -> UVec3 {
+        match self {
+            Self::X => UVec3::plus_x(),
+            Self::Y => UVec3::plus_y(),
+            Self::Z => UVec3::plus_z(),
+        }
+    }
+}
+
+impl std::fmt::Display for Axis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::X => f.write_str("X"),
+            Self::Y => f.write_str("Y"),
+            Self::Z => f.write_str("Z"),
+        }
     }
 }
 
@@ -484,4 +857,126 @@ mod model_tests {
             );
         }
     }
+
+    #[test]
+    fn test_render_geometry_applies_skeleton() {
+        let mut cube = Model::from_mesh(Mesh::hexahedron());
+        assert!(matches!(
+            cube.render_geometry(),
+            std::borrow::Cow::Borrowed(_)
+        ));
+
+        cube.mesh
+            .set_bone_weights(vec![BoneWeights::single(0); cube.mesh.vertex_count()]);
+        let mut skeleton = Skeleton::new();
+        skeleton.add_joint("root", None, Transform3D::identity());
+        skeleton.set_pose(0, Transform3D::translation(0.0, 2.0, 0.0));
+        cube.skeleton = Some(skeleton);
+
+        let rendered = cube.render_geometry();
+        for i in 0..cube.mesh.vertex_count() {
+            assert_points(
+                rendered.mesh.get_global_vertex(i),
+                cube.mesh.get_global_vertex(i) + Vec3::new(0.0, 2.0, 0.0),
+                TOLERANCE,
+            );
+        }
+    }
+
+    #[test]
+    fn test_render_geometry_applies_morph_weights() {
+        let mut cube = Model::from_mesh(Mesh::hexahedron());
+        let shifted = cube
+            .mesh
+            .get_local_vertex_iter()
+            .map(|v| v + Vec3::new(1.0, 0.0, 0.0))
+            .collect();
+        cube.mesh.add_morph_target("shift", shifted, None);
+
+        cube.set_morph_weight("missing", 1.0);
+        assert_eq!(cube.get_morph_weight("missing"), 0.0);
+        assert!(matches!(
+            cube.render_geometry(),
+            std::borrow::Cow::Borrowed(_)
+        ));
+
+        cube.set_morph_weight("shift", 0.25);
+        assert_eq!(cube.get_morph_weight("shift"), 0.25);
+        let rendered = cube.render_geometry();
+        for i in 0..cube.mesh.vertex_count() {
+            assert_points(
+                rendered.mesh.get_global_vertex(i),
+                cube.mesh.get_global_vertex(i) + Vec3::new(0.25, 0.0, 0.0),
+                TOLERANCE,
+            );
+        }
+
+        cube.reset_morph_weights();
+        assert!(matches!(
+            cube.render_geometry(),
+            std::borrow::Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn test_bake_transform_keeps_global_geometry() {
+        let mut cube = Model::from_mesh(Mesh::hexahedron());
+        cube.uniform_scale(2.0);
+        cube.rotate_local_y(0.7);
+        cube.set_position(Point3::new(1.0, -2.0, 3.0));
+        cube.generate_lods(1, 0.5, 10.0);
+
+        let vertexes: Vec<Point3> = cube.mesh.get_global_vertex_iter().collect();
+        let normals: Vec<UVec3> = cube.mesh.get_global_normals_iter().unwrap().collect();
+        cube.bake_transform();
+
+        assert_eq!(cube.mesh.local_frame, CoordFrame::global());
+        for (i, (vertex, normal)) in vertexes.into_iter().zip(normals).enumerate() {
+            assert_points(cube.mesh.get_global_vertex(i), vertex, 1e-4);
+            assert_uvecs(cube.mesh.get_global_normal(i).unwrap(), normal, 1e-4);
+        }
+        if let Some((_, lod_mesh)) = cube.lod_group.levels.first() {
+            let (min, max) = cube.global_bounds();
+            for vertex in lod_mesh.get_local_vertex_iter() {
+                assert!(min.x - 1e-3 <= vertex.x && vertex.x <= max.x + 1e-3);
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let mut model = Model::from_mesh(Mesh::hexahedron());
+        model.set_position(Point3::new(1.0, -2.0, 3.0));
+        model.rotate_local_y(0.5);
+        model.material.color = egui::Color32::from_rgb(10, 20, 30);
+        model.material.texture = Some(Texture::new(image::DynamicImage::ImageRgb8(
+            image::RgbImage::from_pixel(2, 3, image::Rgb([1, 2, 3])),
+        )));
+        model.flags.cull_mode = CullMode::Front;
+
+        let json = serde_json::to_string(&model).unwrap();
+        let restored: Model = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored.mesh.vertex_count(), model.mesh.vertex_count());
+        assert_eq!(restored.mesh.polygon_count(), model.mesh.polygon_count());
+        for i in 0..model.mesh.vertex_count() {
+            assert_points(
+                restored.mesh.get_global_vertex(i),
+                model.mesh.get_global_vertex(i),
+                TOLERANCE,
+            );
+        }
+        assert_eq!(restored.flags, model.flags);
+        assert_eq!(restored.material.color, model.material.color);
+        let texture = restored.material.texture.as_ref().unwrap();
+        assert_eq!(
+            texture.get_pixel_color(1.0, 1.0),
+            egui::Color32::from_rgb(1, 2, 3)
+        );
+
+        // повреждённая текстура не загружается
+        let broken = json.replacen("\"width\":2", "\"width\":5", 1);
+        assert!(serde_json::from_str::<Model>(&broken).is_err());
+    }
 }
@@ -0,0 +1,144 @@
+//! Billboard'ы - плоские модели, всегда повёрнутые к камере.
+
+use super::{Material, Mesh, Model, Polygon, Texture};
+use crate::{CoordFrame, Point3, UVec3, Vec3};
+
+/// Способ поворота billboard'а к камере.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BillboardMode {
+    /// Модель полностью поворачивается к камере, верх модели направлен к глобальному верху.
+    Spherical,
+    /// Модель поворачивается к камере только вокруг своей оси `up`.
+    Cylindrical,
+}
+
+impl BillboardMode {
+    /// Локальная система координат, повёрнутая к камере.
+    ///
+    /// `to_camera` - направление от модели к камере в **глобальных** координатах. Ось `forward`
+    /// результата смотрит на камеру, положение и масштаб `frame` сохраняются. Если повернуть
+    /// модель нельзя (камера на оси цилиндрического billboard'а), то `frame` не меняется.
+    pub fn face_camera(self, frame: &CoordFrame, to_camera: Vec3) -> CoordFrame {
+        let (forward, up) = match self {
+            Self::Spherical => {
+                let Ok(forward) = to_camera.normalize() else {
+                    return *frame;
+                };
+                // если камера прямо сверху или снизу, верх берётся от текущей ориентации
+                let up = orthogonal_part(UVec3::up(), forward)
+                    .or_else(|| orthogonal_part(frame.up(), forward))
+                    .or_else(|| orthogonal_part(frame.forward(), forward));
+                (forward, up)
+            }
+            Self::Cylindrical => {
+                let up = frame.up();
+                let forward = UVec3::try_from(to_camera)
+                    .ok()
+                    .and_then(|direction| orthogonal_part(direction, up));
+                let Some(forward) = forward else {
+                    return *frame;
+                };
+                (forward, Some(up))
+            }
+        };
+        let Some(up) = up else {
+            return *frame;
+        };
+
+        let mut faced = CoordFrame::from_2(forward, up, frame.origin);
+        faced.scale = frame.scale;
+        faced
+    }
+}
+
+/// Составляющая `vector`, перпендикулярная `axis`, или `None`, если они параллельны.
+fn orthogonal_part(vector: UVec3, axis: UVec3) -> Option<UVec3> {
+    let vector = Vec3::from(vector);
+    let axis = Vec3::from(axis);
+    let part = vector - axis * vector.dot(axis);
+    if part.length() < 1e-4 {
+        return None;
+    }
+    part.normalize().ok()
+}
+
+impl Model {
+    /// Создать billboard: квадрат со стороной `size` и текстурой `texture`, который при
+    /// отрисовке всегда повёрнут к камере (сферически).
+    ///
+    /// Квадрат лежит в локальной плоскости XY, лицевая сторона смотрит вдоль `forward`.
+    pub fn billboard(texture: Texture, size: f32) -> Self {
+        let half = size / 2.0;
+        let vertexes = vec![
+            Point3::new(-half, -half, 0.0),
+            Point3::new(half, -half, 0.0),
+            Point3::new(half, half, 0.0),
+            Point3::new(-half, half, 0.0),
+        ];
+        let polygons = vec![Polygon::from_list(&[0, 1, 2, 3])];
+        let normals = vec![UVec3::forward(); 4];
+        // верх текстуры соответствует верху квадрата
+        let texture_coords = vec![(0.0, 1.0), (1.0, 1.0), (1.0, 0.0), (0.0, 0.0)];
+
+        let mut model = Self::from_mesh(Mesh::new(
+            vertexes,
+            polygons,
+            Some(normals),
+            Some(texture_coords),
+        ));
+        model.material = Material {
+            texture: Some(texture),
+            ..Default::default()
+        };
+        model.billboard = Some(BillboardMode::Spherical);
+        model
+    }
+}
+
+#[cfg(test)]
+mod billboard_tests {
+    use super::*;
+
+    #[test]
+    fn test_spherical_billboard_faces_camera() {
+        let mut frame = CoordFrame::global();
+        frame.origin = Point3::new(1.0, 2.0, 3.0);
+        frame.scale = Vec3::new(2.0, 2.0, 2.0);
+
+        let to_camera = Vec3::new(1.0, 1.0, -1.0);
+        let faced = BillboardMode::Spherical.face_camera(&frame, to_camera);
+        let expected = UVec3::try_from(to_camera).unwrap();
+        assert!(
+            Vec3::from(faced.forward()).dot(Vec3::from(expected)) > 1.0 - 1e-4,
+            "forward смотрит на камеру, получено {:?}",
+            faced.forward()
+        );
+        assert!(
+            faced.right().y.abs() < 1e-4,
+            "billboard не заваливается набок"
+        );
+        assert_eq!(faced.origin, frame.origin);
+        assert_eq!(faced.scale, frame.scale);
+
+        // камера прямо сверху
+        let faced = BillboardMode::Spherical.face_camera(&frame, Vec3::new(0.0, 5.0, 0.0));
+        assert!(faced.forward().y > 1.0 - 1e-4);
+    }
+
+    #[test]
+    fn test_cylindrical_billboard_keeps_up_axis() {
+        let frame = CoordFrame::global();
+        let faced = BillboardMode::Cylindrical.face_camera(&frame, Vec3::new(-3.0, 4.0, 0.0));
+
+        assert!(faced.up().y > 1.0 - 1e-4, "ось up не меняется");
+        assert!(
+            faced.forward().x < -1.0 + 1e-4,
+            "forward в сторону камеры по горизонтали"
+        );
+
+        // камера на оси billboard'а
+        let faced = BillboardMode::Cylindrical.face_camera(&frame, Vec3::new(0.0, 4.0, 0.0));
+        assert_eq!(faced, frame);
+    }
+}
@@ -0,0 +1,81 @@
+//! Группа уровней детализации модели.
+
+use crate::Mesh;
+
+/// Упрощённые версии Mesh'а модели с расстояниями переключения.
+///
+/// Уровень `(distance, mesh)` рисуется, когда камера находится от модели не ближе `distance`.
+/// Ближе первого расстояния рисуется исходный Mesh модели (уровень 0). Уровни хранятся
+/// по возрастанию расстояния, от подробных к грубым.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LodGroup {
+    /// Расстояния переключения и Mesh'и уровней.
+    pub levels: Vec<(f32, Mesh)>,
+}
+
+impl LodGroup {
+    /// Создать пустую группу.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Добавить уровень, который рисуется начиная с расстояния `distance`.
+    pub fn add_level(&mut self, distance: f32, mesh: Mesh) {
+        debug_assert!(
+            distance >= 0.0,
+            "расстояние переключения {} не может быть отрицательным",
+            distance
+        );
+
+        let index = self.levels.partition_point(|(other, _)| *other <= distance);
+        self.levels.insert(index, (distance, mesh));
+    }
+
+    /// Количество упрощённых уровней (без исходного Mesh'а).
+    pub fn len(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Нет ни одного упрощённого уровня?
+    pub fn is_empty(&self) -> bool {
+        self.levels.is_empty()
+    }
+
+    /// Mesh упрощённого уровня `level`, где уровень 1 - первый из `levels`.
+    pub fn get_mesh(&self, level: usize) -> Option<&Mesh> {
+        let index = level.checked_sub(1)?;
+        self.levels.get(index).map(|(_, mesh)| mesh)
+    }
+
+    /// Выбрать уровень по расстоянию от камеры до модели. Уровень 0 - исходный Mesh.
+    pub fn select(&self, distance: f32) -> usize {
+        self.levels
+            .iter()
+            .rposition(|(switch_distance, _)| *switch_distance <= distance)
+            .map_or(0, |index| index + 1)
+    }
+}
+
+#[cfg(test)]
+mod lod_group_tests {
+    use super::*;
+
+    #[test]
+    fn test_select_by_distance() {
+        let mut group = LodGroup::new();
+        assert_eq!(group.select(100.0), 0, "без уровней всегда исходный Mesh");
+
+        group.add_level(20.0, Mesh::tetrahedron());
+        group.add_level(10.0, Mesh::octahedron());
+        assert_eq!(group.len(), 2);
+        assert_eq!(group.levels[0].0, 10.0, "уровни сортируются по расстоянию");
+
+        assert_eq!(group.select(5.0), 0);
+        assert_eq!(group.select(10.0), 1);
+        assert_eq!(group.select(15.0), 1);
+        assert_eq!(group.select(25.0), 2);
+        assert_eq!(group.get_mesh(2).unwrap().vertex_count(), 4);
+        assert!(group.get_mesh(0).is_none() && group.get_mesh(3).is_none());
+    }
+}
@@ -6,13 +6,35 @@ use std::fmt::Display;
 ///
 /// Материал задаёт сплошной цвет модели и его поведение при освещении.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Material {
     /// Цвет всего объекта
     pub color: egui::Color32,
-    /// Текстура объекта, если имеется
+    /// Текстура объекта, если имеется. Может быть областью общего атласа, см. [`Texture::sub_region`].
     pub texture: Option<Texture>,
     /// Как совмещать текстуру с цветом материала
     pub blend_mode: TextureBlendMode,
+    /// Непрозрачность объекта в диапазоне [0, 1], где 1 - полностью непрозрачный.
+    pub opacity: f32,
+    /// Отражающая способность в диапазоне [0, 1], где 0 - поверхность не отражает окружение.
+    ///
+    /// При трассировке лучей (см. [`crate::RaytraceRenderer`]) отражается сама сцена,
+    /// при растеризации - карта отражений сцены [`crate::Scene::reflection_map`]
+    /// или карта окружения при шейдинге с освещением.
+    pub reflectivity: f32,
+    /// Двусторонний ли материал.
+    ///
+    /// Грани двустороннего материала никогда не отсекаются как нелицевые, а у обратных
+    /// граней при освещении разворачиваются нормали. Нужно для незамкнутых поверхностей,
+    /// например, графиков функций, чтобы они не пропадали при взгляде снизу.
+    pub double_sided: bool,
+    /// Цвет собственного свечения, которое не зависит от освещения. Чёрный - без свечения.
+    ///
+    /// Свечение прибавляется к освещённому цвету в шейдерах с освещением, а при включённом
+    /// [`crate::BloomOptions`] ещё и расплывается ореолом вокруг модели.
+    pub emission: Color32,
+    /// Яркость свечения: множитель цвета [`Material::emission`], может быть больше 1 в HDR-режиме.
+    pub emission_strength: f32,
 }
 
 impl Default for Material {
@@ -21,22 +43,48 @@ impl Default for Material {
             color: Color32::WHITE,
             texture: None,
             blend_mode: TextureBlendMode::default(),
+            opacity: 1.0,
+            reflectivity: 0.0,
+            double_sided: false,
+            emission: Color32::BLACK,
+            emission_strength: 1.0,
         }
     }
 }
 
 impl Material {
+    /// Является ли материал полупрозрачным.
+    pub fn is_transparent(&self) -> bool {
+        self.opacity < 1.0
+    }
+
+    /// Светится ли материал сам.
+    pub fn is_emissive(&self) -> bool {
+        self.emission != Color32::BLACK && self.emission_strength > 0.0
+    }
+
+    /// Линейный цвет свечения с учётом яркости (1 соответствует 255).
+    pub fn emitted_light(&self) -> crate::Vec3 {
+        crate::library::utils::color_to_vec(self.emission) * self.emission_strength.max(0.0)
+    }
+
     /// Получить цвет пикселя модели по UV-координатам с учётом материала.
     ///
     /// Обращаю внимание, что тут происходит только смешивание текстуры и материала.
     /// Освещение и шейдинг тут никак не учитываются.
     pub fn get_uv_color(&self, u: f32, v: f32) -> Color32 {
+        self.get_vertex_uv_color(u, v, None)
+    }
+
+    /// То же, что и [`Self::get_uv_color`], но цвет вершины `vertex_color`, если он задан,
+    /// заменяет цвет материала.
+    pub fn get_vertex_uv_color(&self, u: f32, v: f32, vertex_color: Option<Color32>) -> Color32 {
+        let color = vertex_color.unwrap_or(self.color);
         let (u, v) = self.cycle_texture(u, v);
         if let Some(texture) = &self.texture {
-            self.blend_mode
-                .blend(texture.get_pixel_color(u, v), self.color)
+            self.blend_mode.blend(texture.get_pixel_color(u, v), color)
         } else {
-            self.color
+            color
         }
     }
 
@@ -51,6 +99,7 @@ impl Material {
 
 /// Тип взаимодействия между текстурой и цветом материала.
 #[derive(Default, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TextureBlendMode {
     /// Текстура полностью заменяет цвет материала.
     Replace,
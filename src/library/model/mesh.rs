@@ -3,11 +3,34 @@
 //! По сути, это является каркасом модели, которого достаточно только
 //! для рендера в формате wireframe.
 
-use crate::{CoordFrame, Line3, Point3, Transform3D, UVec3, Vec3, library::utils};
-
+use crate::library::state_hash::StateHash;
+use crate::{
+    Camera, CoordFrame, Curve3, Line3, Point3, ProjectionType, Transform3D, UVec3, Vec3,
+    library::utils,
+};
+use std::hash::{Hash, Hasher};
+
+mod ambient_occlusion;
+mod analysis;
+mod clipping;
+mod collision;
+mod half_edge;
+mod implicit;
+mod morphing;
 mod polygon;
+mod processing;
+mod raycast;
+mod simplify;
+mod skinning;
+mod texture_seams;
+mod vertex_colors;
 // re-export в модель
+pub use half_edge::{HalfEdge, HalfEdgeMesh};
+pub use morphing::MorphTarget;
 pub use polygon::Polygon;
+pub use raycast::RayHit;
+pub(crate) use raycast::intersect_triangle;
+pub use skinning::{BoneWeights, MAX_BONE_INFLUENCES};
 
 /// Mesh модели.
 ///
@@ -15,6 +38,7 @@ pub use polygon::Polygon;
 /// вершины в сетку, а также набор нормалей и текстурных координат для дальнейшей отрисовки модели.
 /// Все векторы и точки хранятся в локальных координатах модели.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Mesh {
     /// Все вершины Mesh'а модели.
     ///
@@ -35,6 +59,24 @@ pub struct Mesh {
 
     /// Соответствие между UV-координатами текстуры и вершинами.
     texture_coords: Option<Vec<(f32, f32)>>,
+
+    /// Таблица UV-координат углов полигонов, на которую ссылаются текстурные индексы полигонов.
+    ///
+    /// Если задана, то `texture_coords` тоже заданы и содержат средние UV-координаты углов вершины.
+    corner_texture_coords: Option<Vec<(f32, f32)>>,
+
+    /// Привязка вершин к суставам скелета. Индексируется в том же порядке, что и вершины Mesh'а.
+    bone_weights: Option<Vec<BoneWeights>>,
+
+    /// Альтернативные формы Mesh'а для смешивания.
+    morph_targets: Vec<MorphTarget>,
+
+    /// Запечённое затенение окружением вершин: 0 - вершина открыта, 1 - полностью закрыта.
+    /// Индексируется в том же порядке, что и вершины Mesh'а.
+    ambient_occlusion: Option<Vec<f32>>,
+
+    /// Цвета вершин. Индексируются в том же порядке, что и вершины Mesh'а.
+    vertex_colors: Option<Vec<egui::Color32>>,
 }
 
 impl Mesh {
@@ -212,7 +254,7 @@ impl Mesh {
     /// Создать новый Mesh из уже известных данных.
     ///
     /// Локальная система координат этого Mesh'а будет совпадать с глобальной.
-    fn new(
+    pub(super) fn new(
         vertexes: Vec<Point3>,
         polygons: Vec<Polygon>,
         normals: Option<Vec<UVec3>>,
@@ -235,6 +277,11 @@ impl Mesh {
             local_frame: CoordFrame::global(),
             normals,
             texture_coords,
+            corner_texture_coords: None,
+            bone_weights: None,
+            morph_targets: Vec::new(),
+            ambient_occlusion: None,
+            vertex_colors: None,
         }
     }
 
@@ -252,6 +299,101 @@ impl Mesh {
         mesh
     }
 
+    /// Объединить несколько Mesh'ей в один.
+    ///
+    /// Вершины, полигоны, нормали и текстурные координаты склеиваются по порядку, индексы
+    /// полигонов сдвигаются на количество вершин предыдущих Mesh'ей. Если `bake_transforms`,
+    /// то геометрия каждого Mesh'а переводится в глобальные координаты (см. `apply_transform`),
+    /// а результат получает глобальную систему координат. Иначе вершины берутся в локальных
+    /// координатах как есть, и результат получает систему координат первого Mesh'а.
+    ///
+    /// Текстурные координаты (вместе с UV-координатами углов) сохраняются, только если они есть
+    /// у всех Mesh'ей. Если нормали
+    /// есть не у всех, то они генерируются заново. Затенение окружением и цвета вершин
+    /// сохраняются, только если они есть у всех Mesh'ей. Скелет и формы не переносятся.
+    pub fn merge(meshes: &[&Mesh], bake_transforms: bool) -> Mesh {
+        let with_normals = meshes.iter().all(|mesh| mesh.has_normals());
+        let any_normals = meshes.iter().any(|mesh| mesh.has_normals());
+        let with_texture_coords = meshes.iter().all(|mesh| mesh.has_texture_coords());
+        let with_occlusion = meshes.iter().all(|mesh| mesh.has_ambient_occlusion());
+        let with_colors = meshes.iter().all(|mesh| mesh.has_vertex_colors());
+
+        let mut vertexes = Vec::new();
+        let mut polygons = Vec::new();
+        let mut normals = Vec::new();
+        let mut texture_coords = Vec::new();
+        let mut corner_texture_coords = Vec::new();
+        let mut occlusion = Vec::new();
+        let mut colors = Vec::new();
+        for &mesh in meshes {
+            let baked;
+            let mesh = if bake_transforms {
+                let mut copy = mesh.clone();
+                copy.apply_transform(mesh.local_frame.local_to_global_matrix());
+                baked = copy;
+                &baked
+            } else {
+                mesh
+            };
+
+            let offset = vertexes.len();
+            let corner_offset = corner_texture_coords.len();
+            vertexes.extend_from_slice(&mesh.vertexes);
+            polygons.extend(mesh.polygons.iter().map(|polygon| {
+                let mut merged = Polygon::from_vec(
+                    polygon
+                        .get_mesh_vertex_index_iter()
+                        .map(|index| index + offset)
+                        .collect(),
+                );
+                if with_texture_coords && mesh.has_corner_texture_coords() {
+                    merged.set_texture_indexes(polygon.texture_indexes().map(|indexes| {
+                        indexes.iter().map(|index| index + corner_offset).collect()
+                    }));
+                }
+                merged
+            }));
+            if let (true, Some(mesh_normals)) = (with_normals, &mesh.normals) {
+                normals.extend_from_slice(mesh_normals);
+            }
+            if let (true, Some(mesh_texture_coords)) = (with_texture_coords, &mesh.texture_coords) {
+                texture_coords.extend_from_slice(mesh_texture_coords);
+            }
+            if let (true, Some(mesh_corners)) = (with_texture_coords, &mesh.corner_texture_coords) {
+                corner_texture_coords.extend_from_slice(mesh_corners);
+            }
+            if let (true, Some(mesh_occlusion)) = (with_occlusion, &mesh.ambient_occlusion) {
+                occlusion.extend_from_slice(mesh_occlusion);
+            }
+            if let (true, Some(mesh_colors)) = (with_colors, &mesh.vertex_colors) {
+                colors.extend_from_slice(mesh_colors);
+            }
+        }
+
+        let mut merged = Self::new(
+            vertexes,
+            polygons,
+            (with_normals && !meshes.is_empty()).then_some(normals),
+            (with_texture_coords && !meshes.is_empty()).then_some(texture_coords),
+        );
+        if any_normals && !with_normals {
+            merged.generate_normals();
+        }
+        if !corner_texture_coords.is_empty() {
+            merged.corner_texture_coords = Some(corner_texture_coords);
+        }
+        if with_occlusion && !meshes.is_empty() {
+            merged.ambient_occlusion = Some(occlusion);
+        }
+        if with_colors && !meshes.is_empty() {
+            merged.vertex_colors = Some(colors);
+        }
+        if !bake_transforms && let Some(first) = meshes.first() {
+            merged.local_frame = first.local_frame;
+        }
+        merged
+    }
+
     /// Создать Mesh как модель вращения.
     ///
     /// `profile_points` - изначальные точки, на основе которых строится модель
@@ -303,7 +445,49 @@ impl Mesh {
         // Создаем крышки (если нужно)
         Self::create_rotation_caps(&mut polygons, profile_count, vertices_per_profile);
 
-        Self::from_polygons(vertexes, polygons)
+        let mut mesh = Self::from_polygons(vertexes, polygons);
+        mesh.generate_rotation_texture_coords(profile_count, parts);
+        mesh
+    }
+
+    /// Цилиндрическая развёртка модели вращения с кольцами из `parts` вершин.
+    ///
+    /// Боковая поверхность разворачивается по углу (u) и номеру точки профиля (v), причём
+    /// на стыке первого и последнего сегмента u = 1, а не 0, так что шов не усредняется.
+    /// Крышки получают круглую развёртку в центре текстуры.
+    fn generate_rotation_texture_coords(&mut self, profile_count: usize, parts: usize) {
+        let mut texture_coords = Vec::with_capacity((parts + 1) * (profile_count + 1));
+        for profile_idx in 0..profile_count {
+            for segment_idx in 0..=parts {
+                texture_coords.push((
+                    segment_idx as f32 / parts as f32,
+                    profile_idx as f32 / (profile_count - 1) as f32,
+                ));
+            }
+        }
+        let cap_start = texture_coords.len();
+        for i in 0..parts {
+            let angle = 2.0 * std::f32::consts::PI * i as f32 / parts as f32;
+            texture_coords.push((0.5 + 0.5 * angle.cos(), 0.5 + 0.5 * angle.sin()));
+        }
+
+        let side = |segment_idx: usize, profile_idx: usize| profile_idx * (parts + 1) + segment_idx;
+        let mut texture_indexes = Vec::with_capacity(self.polygons.len());
+        for profile_idx in 0..profile_count - 1 {
+            for segment_idx in 0..parts {
+                texture_indexes.push(vec![
+                    side(segment_idx, profile_idx),
+                    side(segment_idx + 1, profile_idx),
+                    side(segment_idx + 1, profile_idx + 1),
+                    side(segment_idx, profile_idx + 1),
+                ]);
+            }
+        }
+        // крышки в том же порядке, что и в create_rotation_caps
+        texture_indexes.push((0..parts).map(|i| cap_start + i).collect());
+        texture_indexes.push((0..parts).rev().map(|i| cap_start + i).collect());
+
+        self.set_corner_texture_coords(texture_coords, texture_indexes);
     }
 
     /// Создает верхнюю и нижнюю крышки для модели вращения
@@ -338,6 +522,19 @@ impl Mesh {
         }
     }
 
+    /// Создать Mesh как модель вращения гладкого профиля.
+    ///
+    /// Профиль `profile` разбивается на `segments` отрезков, после чего
+    /// строится обычная модель вращения, как в [`Mesh::create_rotation_model`].
+    pub fn create_rotation_model_from_curve(
+        profile: &Curve3,
+        segments: usize,
+        axis: Line3,
+        parts: usize,
+    ) -> Self {
+        Self::create_rotation_model(&profile.sample(segments), axis, parts)
+    }
+
     /// Создать Mesh как график функции от 2-х переменных
     ///
     /// `func` - функция от двух переменных `f(x, y) = z`
@@ -403,6 +600,94 @@ impl Mesh {
         Self::from_polygons(vertexes, polygons)
     }
 
+    /// Создать Mesh как параметрическую поверхность `(u, v) -> Point3`.
+    ///
+    /// `func` - функция, переводящая параметры `(u, v)` в точку поверхности
+    /// `u_range`, `v_range` - границы параметров
+    /// `u_steps`, `v_steps` - количество разбиений по каждому параметру
+    /// `close_u`, `close_v` - замкнуть поверхность по параметру: последний ряд вершин
+    /// соединяется с первым вместо дублирования. Подходит для поверхностей, у которых
+    /// `func(u_end, v) == func(u_start, v)` (сфера, тор), но не для ленты Мёбиуса.
+    ///
+    /// Текстура натягивается по параметрам: углы полигонов получают UV-координаты
+    /// `((u - u_start) / (u_end - u_start), (v - v_start) / (v_end - v_start))`.
+    ///
+    /// Точки, в которых функция не определена (NaN или бесконечность), заменяются на начало координат.
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_parametric<F>(
+        func: F,
+        u_range: (f32, f32),
+        v_range: (f32, f32),
+        u_steps: usize,
+        v_steps: usize,
+        close_u: bool,
+        close_v: bool,
+    ) -> Self
+    where
+        F: Fn(f32, f32) -> Point3,
+    {
+        assert!(
+            u_steps >= 1 && v_steps >= 1,
+            "количество разбиений должно быть не менее 1"
+        );
+        assert!(
+            (!close_u || u_steps >= 3) && (!close_v || v_steps >= 3),
+            "для замыкания поверхности нужно не менее 3 разбиений"
+        );
+
+        let (u0, u1) = u_range;
+        let (v0, v1) = v_range;
+        let du = (u1 - u0) / u_steps as f32;
+        let dv = (v1 - v0) / v_steps as f32;
+
+        // при замыкании последний ряд совпадает с первым и не дублируется
+        let u_count = if close_u { u_steps } else { u_steps + 1 };
+        let v_count = if close_v { v_steps } else { v_steps + 1 };
+
+        let mut vertexes = Vec::with_capacity(u_count * v_count);
+        for j in 0..v_count {
+            for i in 0..u_count {
+                let point = func(u0 + i as f32 * du, v0 + j as f32 * dv);
+                if point.x.is_finite() && point.y.is_finite() && point.z.is_finite() {
+                    vertexes.push(point);
+                } else {
+                    vertexes.push(Point3::zero());
+                }
+            }
+        }
+
+        let idx = |i: usize, j: usize| -> usize { (j % v_count) * u_count + i % u_count };
+        // UV-координаты углов не замыкаются, поэтому на швах замкнутой поверхности не усредняются
+        let uv_idx = |i: usize, j: usize| -> usize { j * (u_steps + 1) + i };
+        let mut polygons = Vec::with_capacity(u_steps * v_steps * 2);
+        let mut texture_indexes = Vec::with_capacity(u_steps * v_steps * 2);
+        for j in 0..v_steps {
+            for i in 0..u_steps {
+                polygons.push(Polygon::triangle(
+                    idx(i, j),
+                    idx(i + 1, j),
+                    idx(i + 1, j + 1),
+                ));
+                polygons.push(Polygon::triangle(
+                    idx(i, j),
+                    idx(i + 1, j + 1),
+                    idx(i, j + 1),
+                ));
+                texture_indexes.push(vec![uv_idx(i, j), uv_idx(i + 1, j), uv_idx(i + 1, j + 1)]);
+                texture_indexes.push(vec![uv_idx(i, j), uv_idx(i + 1, j + 1), uv_idx(i, j + 1)]);
+            }
+        }
+        let texture_coords = (0..=v_steps)
+            .flat_map(|j| {
+                (0..=u_steps).map(move |i| (i as f32 / u_steps as f32, j as f32 / v_steps as f32))
+            })
+            .collect();
+
+        let mut mesh = Self::from_polygons(vertexes, polygons);
+        mesh.set_corner_texture_coords(texture_coords, texture_indexes);
+        mesh
+    }
+
     /// Создание тетраэдра со сторонами единичной длины.
     pub fn tetrahedron() -> Self {
         // Координаты правильного тетраэдра с длиной ребра = 1
@@ -425,10 +710,14 @@ impl Mesh {
             Polygon::triangle(1, 3, 2),
         ];
 
-        Self::from_polygons(vertexes, polygons)
+        let mut mesh = Self::from_polygons(vertexes, polygons);
+        mesh.generate_face_texture_coords();
+        mesh
     }
 
     /// Создание гексаэдра со сторонами единичной длины.
+    ///
+    /// Каждая грань покрывается текстурой целиком.
     pub fn hexahedron() -> Self {
         // Куб с длиной ребра = 1, центрированный в начале координат
         let half = 0.5;
@@ -455,7 +744,9 @@ impl Mesh {
             Polygon::from_list(&[1, 2, 6, 5]),
         ];
 
-        Self::from_polygons(vertexes, polygons)
+        let mut mesh = Self::from_polygons(vertexes, polygons);
+        mesh.generate_face_texture_coords();
+        mesh
     }
 
     /// Создание октаэдра со сторонами единичной длины.
@@ -487,7 +778,9 @@ impl Mesh {
             Polygon::triangle(1, 2, 5), // низ-право-зад
         ];
 
-        Self::from_polygons(vertexes, polygons)
+        let mut mesh = Self::from_polygons(vertexes, polygons);
+        mesh.generate_face_texture_coords();
+        mesh
     }
 
     /// Создание икосаэдра со сторонами единичной длины.
@@ -546,7 +839,9 @@ impl Mesh {
             Polygon::triangle(7, 11, 3),
         ];
 
-        Self::from_polygons(vertexes, polygons)
+        let mut mesh = Self::from_polygons(vertexes, polygons);
+        mesh.generate_face_texture_coords();
+        mesh
     }
 
     /// Создание додекаэдра со сторонами единичной длины.
@@ -604,7 +899,45 @@ impl Mesh {
             Polygon::from_list(&[4, 17, 19, 6, 13]),
         ];
 
-        Self::from_polygons(vertexes, polygons)
+        let mut mesh = Self::from_polygons(vertexes, polygons);
+        mesh.generate_face_texture_coords();
+        mesh
+    }
+
+    /// Создание области видимости (frustum) камеры для отладки проекций и отсечения.
+    ///
+    /// Вершины - 8 углов из [`Camera::frustum_corners`] в **глобальных** координатах, полигоны -
+    /// ближняя и дальняя плоскости отсечения и 4 боковые грани. Mesh предназначен для
+    /// отрисовки каркасом (например, с флагом `wireframe_only`), поэтому нормалей и текстурных
+    /// координат у него нет.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::{Camera, Mesh, Model, ProjectionType};
+    ///
+    /// let mut frustum = Model::from_mesh(Mesh::from_camera_frustum(
+    ///     &Camera::default(),
+    ///     ProjectionType::Perspective,
+    /// ));
+    /// frustum.flags.wireframe_only = true;
+    /// assert_eq!(frustum.mesh.vertex_count(), 8);
+    /// assert_eq!(frustum.mesh.edge_list().len(), 12);
+    /// ```
+    pub fn from_camera_frustum(camera: &Camera, projection_type: ProjectionType) -> Self {
+        let polygons = vec![
+            Polygon::from_list(&[0, 1, 2, 3]),
+            Polygon::from_list(&[4, 7, 6, 5]),
+            Polygon::from_list(&[0, 4, 5, 1]),
+            Polygon::from_list(&[1, 5, 6, 2]),
+            Polygon::from_list(&[2, 6, 7, 3]),
+            Polygon::from_list(&[3, 7, 4, 0]),
+        ];
+        Self::new(
+            camera.frustum_corners(projection_type).to_vec(),
+            polygons,
+            None,
+            None,
+        )
     }
 
     // --------------------------------------------------
@@ -710,6 +1043,84 @@ impl Mesh {
         Some(texture_coords.iter().copied())
     }
 
+    // --------------------------------------------------
+    // Треугольники и рёбра
+    // --------------------------------------------------
+
+    /// Получить индексированный список треугольников всего Mesh'а.
+    ///
+    /// Каждый полигон триангулируется через `Polygon::triangles`, индексы указывают на вершины Mesh'а.
+    pub fn triangle_list(&self) -> Vec<[usize; 3]> {
+        self.polygons
+            .iter()
+            .flat_map(|polygon| polygon.triangles())
+            .collect()
+    }
+
+    /// Получить количество треугольников, на которые разбиваются полигоны Mesh'а.
+    pub fn triangle_count(&self) -> usize {
+        self.polygons
+            .iter()
+            .map(|polygon| polygon.vertex_count().saturating_sub(2))
+            .sum()
+    }
+
+    /// Получить список уникальных рёбер Mesh'а.
+    ///
+    /// Ребро, общее для нескольких полигонов, встречается один раз. Каждое ребро задаётся
+    /// парой индексов вершин `(a, b)`, где `a < b`; список отсортирован.
+    pub fn edge_list(&self) -> Vec<(usize, usize)> {
+        let mut edges: Vec<(usize, usize)> = self
+            .polygons
+            .iter()
+            .flat_map(|polygon| polygon.edges())
+            .filter(|(a, b)| a != b)
+            .map(|(a, b)| (a.min(b), a.max(b)))
+            .collect();
+        edges.sort_unstable();
+        edges.dedup();
+        edges
+    }
+
+    /// Получить копию Mesh'а, в которой все полигоны разбиты на треугольники.
+    ///
+    /// UV-координаты углов переносятся на углы треугольников.
+    pub fn triangulated(&self) -> Mesh {
+        let polygons = self
+            .polygons
+            .iter()
+            .flat_map(|polygon| {
+                polygon.corner_triangles().map(|corners| {
+                    let [a, b, c] = corners.map(|i| polygon.get_mesh_vertex_index(i));
+                    let mut triangle = Polygon::triangle(a, b, c);
+                    if polygon.has_texture_indexes() {
+                        triangle.set_texture_indexes(
+                            corners
+                                .iter()
+                                .map(|&i| polygon.get_texture_index(i))
+                                .collect(),
+                        );
+                    }
+                    triangle
+                })
+            })
+            .collect();
+
+        let mut mesh = Self::new(
+            self.vertexes.clone(),
+            polygons,
+            self.normals.clone(),
+            self.texture_coords.clone(),
+        );
+        mesh.local_frame = self.local_frame;
+        mesh.corner_texture_coords = self.corner_texture_coords.clone();
+        mesh.bone_weights = self.bone_weights.clone();
+        mesh.morph_targets = self.morph_targets.clone();
+        mesh.ambient_occlusion = self.ambient_occlusion.clone();
+        mesh.vertex_colors = self.vertex_colors.clone();
+        mesh
+    }
+
     // --------------------------------------------------
     // Вспомогательные методы
     // --------------------------------------------------
@@ -766,6 +1177,36 @@ impl Mesh {
     }
 }
 
+impl StateHash for Mesh {
+    fn hash_state<H: Hasher>(&self, state: &mut H) {
+        self.vertexes.hash_state(state);
+        state.write_usize(self.polygons.len());
+        for polygon in &self.polygons {
+            state.write_usize(polygon.vertex_count());
+            for index in polygon.get_mesh_vertex_index_iter() {
+                state.write_usize(index);
+            }
+            if let Some(indexes) = polygon.texture_indexes() {
+                indexes.hash(state);
+            }
+        }
+        self.local_frame.hash_state(state);
+        self.normals.hash_state(state);
+        self.texture_coords.hash_state(state);
+        self.corner_texture_coords.hash_state(state);
+        self.bone_weights.hash_state(state);
+        self.ambient_occlusion.hash_state(state);
+        self.vertex_colors.hash_state(state);
+        state.write_usize(self.morph_targets.len());
+        for target in &self.morph_targets {
+            target.name.hash(state);
+            for i in 0..self.vertexes.len() {
+                target.get_position_delta(i).hash_state(state);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod mesh_tests {
     use crate::HVec3;
@@ -964,4 +1405,93 @@ mod mesh_tests {
             );
         }
     }
+
+    #[test]
+    fn test_triangle_list() {
+        let cube = generate_cube();
+        let triangles = cube.triangle_list();
+        // 6 квадратных граней по 2 треугольника
+        assert_eq!(triangles.len(), 12);
+        assert_eq!(cube.triangulated().polygon_count(), 12);
+    }
+
+    #[test]
+    fn test_edge_list_unique() {
+        let cube = Mesh::hexahedron();
+        assert_eq!(cube.edge_list().len(), 12, "у куба 12 рёбер");
+    }
+
+    #[test]
+    fn test_from_parametric_closed_sphere() {
+        use std::f32::consts::PI;
+        let sphere = |u: f32, v: f32| Point3::new(v.sin() * u.cos(), v.cos(), v.sin() * u.sin());
+
+        // замкнута только по долготе, полюса остаются отдельными рядами
+        let mesh = Mesh::from_parametric(sphere, (0.0, 2.0 * PI), (0.0, PI), 16, 8, true, false);
+        assert_eq!(mesh.vertex_count(), 16 * 9);
+        assert_eq!(mesh.polygon_count(), 16 * 8 * 2);
+        for vertex in mesh.get_local_vertex_iter() {
+            assert!(
+                (Vec3::from(vertex).length() - 1.0).abs() < 1e-5,
+                "вершина {} должна лежать на единичной сфере",
+                vertex
+            );
+        }
+        // на шве по долготе UV-координаты углов не усредняются
+        assert_eq!(mesh.split_texture_seams().vertex_count(), 17 * 9);
+
+        // тор замкнут по обоим параметрам
+        let torus = |u: f32, v: f32| {
+            let r = 2.0 + 0.5 * v.cos();
+            Point3::new(r * u.cos(), 0.5 * v.sin(), r * u.sin())
+        };
+        let mesh =
+            Mesh::from_parametric(torus, (0.0, 2.0 * PI), (0.0, 2.0 * PI), 12, 6, true, true);
+        assert_eq!(mesh.vertex_count(), 12 * 6);
+        // у замкнутой поверхности каждое ребро принадлежит ровно двум треугольникам
+        let edges = mesh.edge_list().len();
+        assert_eq!(edges * 2, mesh.polygon_count() * 3);
+    }
+
+    #[test]
+    fn test_merge_remaps_indexes() {
+        let mut cube = Mesh::hexahedron();
+        cube.local_frame.translate_vec(Vec3::new(5.0, 0.0, 0.0));
+        let tetrahedron = Mesh::tetrahedron();
+
+        let merged = Mesh::merge(&[&tetrahedron, &cube], true);
+        assert_eq!(
+            merged.vertex_count(),
+            tetrahedron.vertex_count() + cube.vertex_count()
+        );
+        assert_eq!(
+            merged.polygon_count(),
+            tetrahedron.polygon_count() + cube.polygon_count()
+        );
+        assert!(merged.has_normals() && merged.has_texture_coords());
+
+        // полигон куба ссылается на вершины куба, перенесённые в глобальные координаты
+        let polygon = merged.get_polygon(tetrahedron.polygon_count());
+        let offset = tetrahedron.vertex_count();
+        for (merged_index, cube_index) in polygon
+            .get_mesh_vertex_index_iter()
+            .zip(cube.get_polygon(0).get_mesh_vertex_index_iter())
+        {
+            assert_eq!(merged_index, cube_index + offset);
+            assert_points(
+                merged.get_global_vertex(merged_index),
+                cube.get_global_vertex(cube_index),
+                1e-5,
+            );
+        }
+
+        // без запекания вершины остаются локальными
+        let merged = Mesh::merge(&[&cube, &tetrahedron], false);
+        assert_eq!(merged.local_frame, cube.local_frame);
+        assert_points(
+            merged.get_local_vertex(0),
+            cube.get_local_vertex(0),
+            TOLERANCE,
+        );
+    }
 }
@@ -0,0 +1,45 @@
+//! Запечённое затенение окружением (ambient occlusion) вершин Mesh'а.
+//!
+//! Само запекание выполняется сценой, см. [`crate::Scene::bake_ambient_occlusion`],
+//! так как для него нужна геометрия всех моделей.
+
+use super::Mesh;
+
+impl Mesh {
+    // --------------------------------------------------
+    // Затенение окружением
+    // --------------------------------------------------
+
+    /// Задать затенение окружением вершин.
+    ///
+    /// Значения индексируются в том же порядке, что и вершины Mesh'а, и лежат в `[0, 1]`:
+    /// 0 - вершина полностью открыта, 1 - полностью закрыта окружающей геометрией.
+    pub fn set_ambient_occlusion(&mut self, occlusion: Vec<f32>) {
+        assert_eq!(
+            self.vertexes.len(),
+            occlusion.len(),
+            "Количество значений затенения должно совпадать с количеством вершин Mesh'а"
+        );
+        debug_assert!(
+            occlusion.iter().all(|value| (0.0..=1.0).contains(value)),
+            "значения затенения должны быть в [0, 1]"
+        );
+        self.ambient_occlusion = Some(occlusion);
+    }
+
+    /// Убрать затенение окружением.
+    pub fn clear_ambient_occlusion(&mut self) {
+        self.ambient_occlusion = None;
+    }
+
+    /// Получить затенение окружением i-ой вершины.
+    pub fn get_ambient_occlusion(&self, i: usize) -> Option<f32> {
+        let occlusion = self.ambient_occlusion.as_ref()?;
+        occlusion.get(i).copied()
+    }
+
+    /// Запечено ли затенение окружением?
+    pub fn has_ambient_occlusion(&self) -> bool {
+        self.ambient_occlusion.is_some()
+    }
+}
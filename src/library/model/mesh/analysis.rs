@@ -0,0 +1,240 @@
+//! Геометрические характеристики Mesh'а: площадь, объём, центр масс.
+
+use std::collections::HashMap;
+
+use super::Mesh;
+use crate::{Point3, Vec3};
+
+impl Mesh {
+    // --------------------------------------------------
+    // Геометрические характеристики
+    // --------------------------------------------------
+
+    /// Площадь поверхности Mesh'а в **локальных** координатах.
+    pub fn surface_area(&self) -> f32 {
+        self.triangle_list()
+            .into_iter()
+            .map(|[a, b, c]| self.triangle_area(a, b, c))
+            .sum()
+    }
+
+    /// Объём, ограниченный Mesh'ем, в **локальных** координатах.
+    ///
+    /// Считается по теореме о дивергенции как сумма знаковых объёмов тетраэдров из начала
+    /// координат на каждый треугольник. Имеет смысл только для замкнутого Mesh'а
+    /// (см. [`Mesh::is_watertight`]) с согласованной ориентацией граней. Знак зависит от обхода
+    /// вершин: объём положительный, если нормали граней по обходу `(b - a) x (c - a)`
+    /// направлены наружу.
+    pub fn signed_volume(&self) -> f32 {
+        self.triangle_list()
+            .into_iter()
+            .map(|[a, b, c]| self.tetrahedron_volume(a, b, c))
+            .sum()
+    }
+
+    /// Центр масс Mesh'а в **локальных** координатах.
+    ///
+    /// Для замкнутого Mesh'а с ненулевым объёмом - центр масс однородного тела, иначе - центр
+    /// масс однородной поверхности. Для Mesh'а без граней - среднее вершин.
+    pub fn centroid(&self) -> Point3 {
+        let triangles = self.triangle_list();
+
+        // центр однородного тела как взвешенное среднее центров тетраэдров
+        let volume = self.signed_volume();
+        if volume.abs() > f32::EPSILON && self.is_watertight() {
+            let sum = triangles.iter().fold(Vec3::zero(), |acc, &[a, b, c]| {
+                let center = (Vec3::from(self.vertexes[a])
+                    + Vec3::from(self.vertexes[b])
+                    + Vec3::from(self.vertexes[c]))
+                    / 4.0;
+                acc + center * self.tetrahedron_volume(a, b, c)
+            });
+            return Point3::from(sum / volume);
+        }
+
+        // центр однородной поверхности как взвешенное среднее центров треугольников
+        let area = self.surface_area();
+        if area > f32::EPSILON {
+            let sum = triangles.iter().fold(Vec3::zero(), |acc, &[a, b, c]| {
+                let center = (Vec3::from(self.vertexes[a])
+                    + Vec3::from(self.vertexes[b])
+                    + Vec3::from(self.vertexes[c]))
+                    / 3.0;
+                acc + center * self.triangle_area(a, b, c)
+            });
+            return Point3::from(sum / area);
+        }
+
+        crate::library::utils::calculate_center(&self.vertexes)
+    }
+
+    /// Замкнут ли Mesh: каждое ребро принадлежит ровно двум полигонам.
+    ///
+    /// Вершины с совпадающими координатами (например, на швах текстуры) считаются одной
+    /// вершиной. Mesh без полигонов не считается замкнутым.
+    pub fn is_watertight(&self) -> bool {
+        if self.polygons.is_empty() {
+            return false;
+        }
+
+        let (groups, _) = self.position_groups();
+        let mut edge_counts: HashMap<(usize, usize), usize> = HashMap::new();
+        for polygon in &self.polygons {
+            for (a, b) in polygon.edges() {
+                let (a, b) = (groups[a], groups[b]);
+                if a != b {
+                    *edge_counts.entry((a.min(b), a.max(b))).or_default() += 1;
+                }
+            }
+        }
+        edge_counts.values().all(|&count| count == 2)
+    }
+
+    /// Разбить вершины Mesh'а на связные компоненты: куски, не соединённые общими рёбрами.
+    ///
+    /// Вершины с совпадающими координатами (например, на швах текстуры) считаются одной
+    /// вершиной. Возвращает номер компоненты каждой вершины и количество компонент; номера идут
+    /// по порядку первых вершин компонент. Вершина без полигонов образует свою компоненту.
+    pub fn connected_components(&self) -> (Vec<usize>, usize) {
+        let (groups, positions) = self.position_groups();
+
+        // система непересекающихся множеств по группам вершин
+        let mut parents: Vec<usize> = (0..positions.len()).collect();
+        fn find(parents: &mut [usize], mut i: usize) -> usize {
+            while parents[i] != i {
+                parents[i] = parents[parents[i]];
+                i = parents[i];
+            }
+            i
+        }
+        for (a, b) in self.edge_list() {
+            let (a, b) = (find(&mut parents, groups[a]), find(&mut parents, groups[b]));
+            if a != b {
+                parents[b] = a;
+            }
+        }
+
+        let mut numbers: HashMap<usize, usize> = HashMap::new();
+        let components = groups
+            .iter()
+            .map(|&group| {
+                let root = find(&mut parents, group);
+                let count = numbers.len();
+                *numbers.entry(root).or_insert(count)
+            })
+            .collect();
+        (components, numbers.len())
+    }
+
+    /// Площадь треугольника с вершинами `a`, `b`, `c`.
+    fn triangle_area(&self, a: usize, b: usize, c: usize) -> f32 {
+        let (a, b, c) = (self.vertexes[a], self.vertexes[b], self.vertexes[c]);
+        (b - a).cross(c - a).length() / 2.0
+    }
+
+    /// Знаковый объём тетраэдра из начала координат на треугольник `a`, `b`, `c`.
+    fn tetrahedron_volume(&self, a: usize, b: usize, c: usize) -> f32 {
+        let (a, b, c) = (
+            Vec3::from(self.vertexes[a]),
+            Vec3::from(self.vertexes[b]),
+            Vec3::from(self.vertexes[c]),
+        );
+        a.dot(b.cross(c)) / 6.0
+    }
+}
+
+#[cfg(test)]
+mod analysis_tests {
+    use super::*;
+    use crate::Polygon;
+
+    const TOLERANCE: f32 = 1e-4;
+
+    /// Куб со стороной 2 и согласованной ориентацией граней.
+    fn oriented_cube(offset: Vec3) -> Mesh {
+        let mut cube = Mesh::hexahedron();
+        cube.apply_transform(crate::Transform3D::scale_uniform(2.0));
+        // согласовать обход граней: нормаль по обходу должна смотреть наружу
+        let center = Point3::zero();
+        let polygons: Vec<Polygon> = cube
+            .get_polygon_iter()
+            .map(|polygon| {
+                let indexes: Vec<usize> = polygon.get_mesh_vertex_index_iter().collect();
+                let (a, b, c) = (
+                    cube.get_local_vertex(indexes[0]),
+                    cube.get_local_vertex(indexes[1]),
+                    cube.get_local_vertex(indexes[2]),
+                );
+                let orientation = (a - center).dot((b - a).cross(c - a));
+                if orientation < 0.0 {
+                    Polygon::from_vec(indexes.into_iter().rev().collect())
+                } else {
+                    Polygon::from_vec(indexes)
+                }
+            })
+            .collect();
+        let vertexes = cube
+            .get_local_vertex_iter()
+            .map(|vertex| vertex + offset)
+            .collect();
+        Mesh::from_polygons(vertexes, polygons)
+    }
+
+    #[test]
+    fn test_cube_measurements() {
+        let cube = oriented_cube(Vec3::new(3.0, -1.0, 2.0));
+
+        assert!((cube.surface_area() - 24.0).abs() < TOLERANCE);
+        assert!(
+            (cube.signed_volume() - 8.0).abs() < TOLERANCE,
+            "объём {}",
+            cube.signed_volume()
+        );
+        assert!(cube.is_watertight());
+        assert!(
+            cube.centroid()
+                .approx_equal(Point3::new(3.0, -1.0, 2.0), TOLERANCE)
+        );
+    }
+
+    #[test]
+    fn test_connected_components() {
+        let merged = Mesh::merge(
+            &[
+                &oriented_cube(Vec3::zero()),
+                &oriented_cube(Vec3::new(5.0, 0.0, 0.0)),
+            ],
+            false,
+        );
+        let (components, count) = merged.connected_components();
+        assert_eq!(count, 2);
+        assert_eq!(components.len(), merged.vertex_count());
+        for (i, vertex) in merged.get_local_vertex_iter().enumerate() {
+            assert_eq!(components[i], usize::from(vertex.x > 2.5));
+        }
+
+        // совпадающие вершины склеивают компоненты
+        let cube = oriented_cube(Vec3::zero());
+        let (_, count) = Mesh::merge(&[&cube, &cube], false).connected_components();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_open_mesh_is_not_watertight() {
+        let vertexes = vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(2.0, 2.0, 0.0),
+            Point3::new(0.0, 2.0, 0.0),
+        ];
+        let quad = Mesh::from_polygons(vertexes, vec![Polygon::from_list(&[0, 1, 2, 3])]);
+
+        assert!(!quad.is_watertight());
+        assert!((quad.surface_area() - 4.0).abs() < TOLERANCE);
+        assert!(
+            quad.centroid()
+                .approx_equal(Point3::new(1.0, 1.0, 0.0), TOLERANCE),
+            "центр поверхности"
+        );
+    }
+}
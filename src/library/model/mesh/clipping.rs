@@ -0,0 +1,414 @@
+//! Отсечение геометрии Mesh'а плоскостями и секущими параллелепипедами.
+
+use std::collections::HashMap;
+
+use egui::Color32;
+
+use super::{Mesh, Polygon};
+use crate::{Plane, Point3, SectionBox, UVec3, Vec3, library::utils};
+
+/// Вспомогательная структура для сборки нового Mesh'а из частей исходного.
+struct ClippedMeshBuilder<'a> {
+    source: &'a Mesh,
+    vertexes: Vec<Point3>,
+    normals: Option<Vec<UVec3>>,
+    texture_coords: Option<Vec<(f32, f32)>>,
+    ambient_occlusion: Option<Vec<f32>>,
+    vertex_colors: Option<Vec<Color32>>,
+    /// Новая таблица UV-координат углов, если они есть у исходного Mesh'а.
+    corner_texture_coords: Option<Vec<(f32, f32)>>,
+    /// Соответствие индексов исходных вершин новым.
+    kept: HashMap<usize, usize>,
+    /// Новые вершины на пересечении рёбер `(a, b)` с плоскостью, где `a < b`.
+    intersections: HashMap<(usize, usize), usize>,
+}
+
+impl<'a> ClippedMeshBuilder<'a> {
+    fn new(source: &'a Mesh) -> Self {
+        Self {
+            source,
+            vertexes: Vec::new(),
+            normals: source.normals.as_ref().map(|_| Vec::new()),
+            texture_coords: source.texture_coords.as_ref().map(|_| Vec::new()),
+            ambient_occlusion: source.ambient_occlusion.as_ref().map(|_| Vec::new()),
+            vertex_colors: source.vertex_colors.as_ref().map(|_| Vec::new()),
+            corner_texture_coords: source.corner_texture_coords.as_ref().map(|_| Vec::new()),
+            kept: HashMap::new(),
+            intersections: HashMap::new(),
+        }
+    }
+
+    /// Добавить вершину исходного Mesh'а (однократно).
+    fn keep(&mut self, index: usize) -> usize {
+        if let Some(&new_index) = self.kept.get(&index) {
+            return new_index;
+        }
+
+        let new_index = self.vertexes.len();
+        self.vertexes.push(self.source.vertexes[index]);
+        if let (Some(normals), Some(source)) = (&mut self.normals, &self.source.normals) {
+            normals.push(source[index]);
+        }
+        if let (Some(coords), Some(source)) =
+            (&mut self.texture_coords, &self.source.texture_coords)
+        {
+            coords.push(source[index]);
+        }
+        if let (Some(occlusion), Some(source)) =
+            (&mut self.ambient_occlusion, &self.source.ambient_occlusion)
+        {
+            occlusion.push(source[index]);
+        }
+        if let (Some(colors), Some(source)) = (&mut self.vertex_colors, &self.source.vertex_colors)
+        {
+            colors.push(source[index]);
+        }
+        self.kept.insert(index, new_index);
+        new_index
+    }
+
+    /// Добавить вершину на ребре `(a, b)` исходного Mesh'а с параметром `t` от вершины `a`.
+    fn intersect(&mut self, a: usize, b: usize, t: f32) -> usize {
+        // одно и то же ребро у соседних полигонов должно давать одну и ту же вершину
+        let (key, t) = if a < b {
+            ((a, b), t)
+        } else {
+            ((b, a), 1.0 - t)
+        };
+        if let Some(&new_index) = self.intersections.get(&key) {
+            return new_index;
+        }
+        let (a, b) = key;
+
+        let new_index = self.vertexes.len();
+        let va = self.source.vertexes[a];
+        let vb = self.source.vertexes[b];
+        self.vertexes.push(va + (vb - va) * t);
+        if let (Some(normals), Some(source)) = (&mut self.normals, &self.source.normals) {
+            let normal = Vec3::from(source[a]) * (1.0 - t) + Vec3::from(source[b]) * t;
+            normals.push(normal.normalize().unwrap_or(source[a]));
+        }
+        if let (Some(coords), Some(source)) =
+            (&mut self.texture_coords, &self.source.texture_coords)
+        {
+            let (ua, va) = source[a];
+            let (ub, vb) = source[b];
+            coords.push((ua + (ub - ua) * t, va + (vb - va) * t));
+        }
+        if let (Some(occlusion), Some(source)) =
+            (&mut self.ambient_occlusion, &self.source.ambient_occlusion)
+        {
+            occlusion.push(source[a] + (source[b] - source[a]) * t);
+        }
+        if let (Some(colors), Some(source)) = (&mut self.vertex_colors, &self.source.vertex_colors)
+        {
+            colors.push(utils::lerp_color(source[a], source[b], t));
+        }
+        self.intersections.insert(key, new_index);
+        new_index
+    }
+
+    /// Добавить UV-координаты угла нового полигона, если у исходного Mesh'а есть таблица углов.
+    fn corner(&mut self, uv: Option<(f32, f32)>) -> Option<usize> {
+        let coords = self.corner_texture_coords.as_mut()?;
+        coords.push(uv?);
+        Some(coords.len() - 1)
+    }
+
+    fn build(self, polygons: Vec<Polygon>) -> Mesh {
+        let mut mesh = Mesh::new(self.vertexes, polygons, self.normals, self.texture_coords);
+        mesh.corner_texture_coords = self.corner_texture_coords;
+        mesh.local_frame = self.source.local_frame;
+        mesh.ambient_occlusion = self.ambient_occlusion;
+        mesh.vertex_colors = self.vertex_colors;
+        mesh
+    }
+}
+
+impl Mesh {
+    // --------------------------------------------------
+    // Отсечение геометрии
+    // --------------------------------------------------
+
+    /// Отсечь часть Mesh'а плоскостью, заданной в **глобальных** координатах.
+    ///
+    /// Остаётся только часть со стороны нормали плоскости. Полигоны, пересекающие плоскость,
+    /// обрезаются алгоритмом Сазерленда-Ходжмана; нормали и текстурные координаты новых
+    /// вершин (и UV-координаты углов) интерполируются. Срез не закрывается новыми гранями.
+    pub fn clip_by_plane(&self, plane: &Plane) -> Mesh {
+        let normal = Vec3::from(plane.normal);
+        let distances: Vec<f32> = self
+            .get_global_vertex_iter()
+            .map(|p| (p - plane.origin).dot(normal))
+            .collect();
+
+        let mut builder = ClippedMeshBuilder::new(self);
+        let mut polygons = Vec::new();
+        for polygon in &self.polygons {
+            let indexes: Vec<usize> = polygon.get_mesh_vertex_index_iter().collect();
+            let with_corners = polygon.has_texture_indexes() && self.has_corner_texture_coords();
+            let mut clipped = Vec::with_capacity(indexes.len() + 1);
+            let mut corners = Vec::with_capacity(indexes.len() + 1);
+            for i in 0..indexes.len() {
+                let j = (i + 1) % indexes.len();
+                let a = indexes[i];
+                let b = indexes[j];
+                let (da, db) = (distances[a], distances[b]);
+
+                if da >= 0.0 {
+                    clipped.push(builder.keep(a));
+                    if with_corners {
+                        corners.push(builder.corner(polygon.get_texture_coord(self, i)));
+                    }
+                }
+                if (da >= 0.0) != (db >= 0.0) {
+                    let t = da / (da - db);
+                    clipped.push(builder.intersect(a, b, t));
+                    if with_corners {
+                        let uv = polygon
+                            .get_texture_coord(self, i)
+                            .zip(polygon.get_texture_coord(self, j));
+                        corners.push(builder.corner(
+                            uv.map(|((ua, va), (ub, vb))| (ua + (ub - ua) * t, va + (vb - va) * t)),
+                        ));
+                    }
+                }
+            }
+
+            // вырожденные остатки не нужны
+            if clipped.len() >= 3 {
+                let mut clipped = Polygon::from_vec(clipped);
+                if with_corners {
+                    clipped.set_texture_indexes(corners.into_iter().collect());
+                }
+                polygons.push(clipped);
+            }
+        }
+
+        builder.build(polygons)
+    }
+
+    /// Отсечь часть Mesh'а несколькими плоскостями, заданными в **глобальных** координатах.
+    ///
+    /// Остаётся только часть, находящаяся со стороны нормали у всех плоскостей.
+    pub fn clip_by_planes(&self, planes: &[Plane]) -> Mesh {
+        let mut mesh = self.clone();
+        for plane in planes {
+            mesh = mesh.clip_by_plane(plane);
+        }
+        mesh
+    }
+
+    /// Оставить только ту часть Mesh'а, которая находится внутри секущего параллелепипеда.
+    pub fn clip_by_section_box(&self, section_box: &SectionBox) -> Mesh {
+        self.clip_by_planes(&section_box.planes())
+    }
+
+    /// Разрезать Mesh плоскостью, заданной в **глобальных** координатах.
+    ///
+    /// Возвращает части со стороны нормали плоскости и с обратной стороны.
+    /// Как и в [`Mesh::clip_by_plane`], срез не закрывается новыми гранями.
+    pub fn slice(&self, plane: &Plane) -> (Mesh, Mesh) {
+        let back = Plane::new(plane.origin, -plane.normal);
+        (self.clip_by_plane(plane), self.clip_by_plane(&back))
+    }
+
+    /// Контуры сечения Mesh'а плоскостью, заданной в **глобальных** координатах.
+    ///
+    /// Каждый контур - ломаная в **глобальных** координатах. У замкнутых контуров последняя
+    /// точка повторяет первую, незамкнутые получаются на краях незамкнутого Mesh'а.
+    /// Вершины с совпадающими координатами (например, на швах текстуры) считаются одной
+    /// вершиной, поэтому контур не рвётся на швах.
+    pub fn cross_section(&self, plane: &Plane) -> Vec<Vec<Point3>> {
+        let normal = Vec3::from(plane.normal);
+        let global: Vec<Point3> = self.get_global_vertex_iter().collect();
+        let distances: Vec<f32> = global
+            .iter()
+            .map(|&p| (p - plane.origin).dot(normal))
+            .collect();
+        let (groups, _) = self.position_groups();
+
+        // точки сечения определяются ребром (парой групп вершин), на котором они лежат;
+        // точка в самой вершине определяется парой из одной группы
+        let mut points: HashMap<(usize, usize), Point3> = HashMap::new();
+        let mut segments: Vec<[(usize, usize); 2]> = Vec::new();
+        for polygon in &self.polygons {
+            let indexes: Vec<usize> = polygon.get_mesh_vertex_index_iter().collect();
+            let mut crossings = Vec::with_capacity(2);
+            for i in 0..indexes.len() {
+                let a = indexes[i];
+                let b = indexes[(i + 1) % indexes.len()];
+                let (da, db) = (distances[a], distances[b]);
+                if (da >= 0.0) == (db >= 0.0) {
+                    continue;
+                }
+
+                let t = da / (da - db);
+                let (ga, gb) = (groups[a], groups[b]);
+                let key = if t <= 0.0 {
+                    (ga, ga)
+                } else if t >= 1.0 {
+                    (gb, gb)
+                } else {
+                    (ga.min(gb), ga.max(gb))
+                };
+                points
+                    .entry(key)
+                    .or_insert_with(|| global[a] + (global[b] - global[a]) * t);
+                crossings.push(key);
+            }
+
+            // у невыпуклого полигона пересечений может быть больше двух
+            for pair in crossings.chunks_exact(2) {
+                if pair[0] != pair[1] {
+                    segments.push([pair[0], pair[1]]);
+                }
+            }
+        }
+
+        let mut adjacency: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+        for (index, segment) in segments.iter().enumerate() {
+            for key in segment {
+                adjacency.entry(*key).or_default().push(index);
+            }
+        }
+
+        // сначала обходятся незамкнутые контуры от их концов, затем оставшиеся замкнутые
+        let mut used = vec![false; segments.len()];
+        let mut starts: Vec<(usize, usize)> = adjacency
+            .iter()
+            .filter(|(_, list)| list.len() == 1)
+            .map(|(&key, _)| key)
+            .collect();
+        starts.sort_unstable();
+        let mut contours = Vec::new();
+        let walk = |start_segment: usize, start: (usize, usize), used: &mut Vec<bool>| {
+            let mut contour = vec![points[&start]];
+            let (mut segment, mut current) = (start_segment, start);
+            loop {
+                used[segment] = true;
+                let [a, b] = segments[segment];
+                current = if a == current { b } else { a };
+                contour.push(points[&current]);
+                let next = adjacency[&current].iter().find(|&&next| !used[next]);
+                match next {
+                    Some(&next) => segment = next,
+                    None => break,
+                }
+            }
+            contour
+        };
+        for start in starts {
+            let segment = adjacency[&start][0];
+            if !used[segment] {
+                contours.push(walk(segment, start, &mut used));
+            }
+        }
+        for segment in 0..segments.len() {
+            if !used[segment] {
+                contours.push(walk(segment, segments[segment][0], &mut used));
+            }
+        }
+        contours
+    }
+}
+
+#[cfg(test)]
+mod clipping_tests {
+    use super::*;
+
+    #[test]
+    fn test_clip_cube_in_half() {
+        let cube = Mesh::hexahedron();
+        let plane = Plane::new(Point3::zero(), UVec3::plus_x());
+        let half = cube.clip_by_plane(&plane);
+
+        // все 6 граней пересекают или касаются плоскости, но только 5 остаются невырожденными
+        assert_eq!(half.polygon_count(), 5);
+        // 4 исходные вершины и 4 новые на пересечении рёбер
+        assert_eq!(half.vertex_count(), 8);
+        for vertex in half.get_global_vertex_iter() {
+            assert!(
+                vertex.x >= -1e-6,
+                "вершина {:?} должна быть отсечена",
+                vertex
+            );
+        }
+        assert!(half.has_normals() && half.has_texture_coords());
+
+        // у разрезанных граней UV-координаты углов интерполируются до середины текстуры
+        assert!(half.has_corner_texture_coords());
+        assert!(half.get_polygon_iter().any(|polygon| {
+            polygon
+                .get_texture_coord_iter(&half)
+                .unwrap()
+                .any(|(u, v)| (u - 0.5).abs() < 1e-6 || (v - 0.5).abs() < 1e-6)
+        }));
+    }
+
+    #[test]
+    fn test_clip_keeps_mesh_inside_box() {
+        let cube = Mesh::hexahedron();
+        let section_box =
+            SectionBox::axis_aligned(Point3::new(-5.0, -5.0, -5.0), Point3::new(5.0, 5.0, 5.0));
+        let clipped = cube.clip_by_section_box(&section_box);
+
+        assert_eq!(clipped.polygon_count(), cube.polygon_count());
+        assert_eq!(clipped.vertex_count(), cube.vertex_count());
+    }
+
+    #[test]
+    fn test_slice_splits_mesh() {
+        let cube = Mesh::hexahedron();
+        let plane = Plane::new(Point3::new(0.0, 0.2, 0.0), UVec3::plus_y());
+        let (front, back) = cube.slice(&plane);
+
+        assert_eq!(front.polygon_count(), 5);
+        assert_eq!(back.polygon_count(), 5);
+        assert!(front.get_global_vertex_iter().all(|v| v.y >= 0.2 - 1e-6));
+        assert!(back.get_global_vertex_iter().all(|v| v.y <= 0.2 + 1e-6));
+    }
+
+    #[test]
+    fn test_cross_section_contours() {
+        let mut cube = Mesh::hexahedron();
+        cube.local_frame.translate_vec(Vec3::new(0.0, 0.0, 3.0));
+        let plane = Plane::new(Point3::new(0.1, 0.0, 0.0), UVec3::plus_x());
+
+        // куб режется на швах текстуры, но контур всё равно один и замкнутый
+        let contours = cube.cross_section(&plane);
+        assert_eq!(contours.len(), 1);
+        let contour = &contours[0];
+        assert_eq!(contour.len(), 5, "квадрат из 4-х точек и повтор первой");
+        assert!(contour[0].approx_equal(contour[4], 1e-6));
+        for point in contour {
+            assert!((point.x - 0.1).abs() < 1e-5);
+            assert!((point.z - 3.0).abs() <= 0.5 + 1e-5);
+        }
+
+        // плоскость мимо Mesh'а
+        let plane = Plane::new(Point3::new(5.0, 0.0, 0.0), UVec3::plus_x());
+        assert!(cube.cross_section(&plane).is_empty());
+
+        // у незамкнутого Mesh'а контур незамкнутый
+        let half = cube.clip_by_plane(&Plane::new(Point3::zero(), UVec3::plus_y()));
+        let plane = Plane::new(Point3::new(0.0, 0.0, 3.0), UVec3::plus_z());
+        let contours = half.cross_section(&plane);
+        assert_eq!(contours.len(), 1);
+        assert_eq!(contours[0].len(), 4, "три стороны квадрата");
+        assert!(!contours[0][0].approx_equal(contours[0][3], 1e-6));
+    }
+
+    #[test]
+    fn test_clip_respects_local_frame() {
+        let mut cube = Mesh::hexahedron();
+        cube.local_frame.translate_vec(Vec3::new(10.0, 0.0, 0.0));
+        let plane = Plane::new(Point3::zero(), UVec3::plus_x());
+
+        // куб целиком со стороны нормали
+        assert_eq!(cube.clip_by_plane(&plane).polygon_count(), 6);
+        // куб целиком с обратной стороны
+        let plane = Plane::new(Point3::zero(), -UVec3::plus_x());
+        assert_eq!(cube.clip_by_plane(&plane).polygon_count(), 0);
+    }
+}
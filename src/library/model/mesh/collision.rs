@@ -0,0 +1,331 @@
+//! Проверки пересечения Mesh'а с другими Mesh'ами и примитивами.
+
+use super::Mesh;
+use super::raycast::intersect_triangle;
+use crate::{Aabb, Line3, Plane, Point3, UVec3};
+
+/// Треугольник в **глобальных** координатах вместе с ограничивающим его параллелепипедом.
+struct GlobalTriangle {
+    vertexes: [Point3; 3],
+    aabb: Aabb,
+}
+
+impl Mesh {
+    // --------------------------------------------------
+    // Проверки пересечений
+    // --------------------------------------------------
+
+    /// Ограничивающий параллелепипед Mesh'а в **глобальных** координатах.
+    ///
+    /// Для Mesh'а без вершин возвращает `None`.
+    pub fn global_aabb(&self) -> Option<Aabb> {
+        Aabb::from_points(self.get_global_vertex_iter())
+    }
+
+    /// Находится ли точка `point`, заданная в **глобальных** координатах, внутри Mesh'а.
+    ///
+    /// Считается по чётности числа пересечений луча из точки с гранями, поэтому имеет смысл
+    /// только для замкнутого Mesh'а (см. [`Mesh::is_watertight`]).
+    pub fn contains_point(&self, point: Point3) -> bool {
+        let Some(aabb) = self.global_aabb() else {
+            return false;
+        };
+        if !aabb.contains(point) {
+            return false;
+        }
+
+        // направление не вдоль осей, чтобы луч реже попадал точно в рёбра граней
+        let ray = Line3::new(point, UVec3::new(0.5773, 0.5774, 0.5775));
+        let hits = self
+            .global_triangles()
+            .iter()
+            .filter(|triangle| {
+                let [a, b, c] = triangle.vertexes;
+                intersect_triangle(&ray, a, b, c).is_some()
+            })
+            .count();
+        hits % 2 == 1
+    }
+
+    /// Пересекается ли Mesh с Mesh'ем `other` (оба в **глобальных** координатах).
+    ///
+    /// Сначала сравниваются ограничивающие параллелепипеды, затем ищется пересечение рёбер
+    /// треугольников одного Mesh'а с треугольниками другого. Если поверхности не пересекаются,
+    /// то проверяется, не вложен ли один замкнутый Mesh в другой. Касание копланарными гранями
+    /// без пересечения рёбер не считается пересечением.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::{Mesh, Vec3};
+    ///
+    /// let a = Mesh::hexahedron();
+    /// let mut b = Mesh::hexahedron();
+    /// b.local_frame.translate_vec(Vec3::new(0.8, 0.3, 0.0));
+    /// assert!(a.intersects(&b));
+    ///
+    /// b.local_frame.translate_vec(Vec3::new(2.0, 0.0, 0.0));
+    /// assert!(!a.intersects(&b));
+    /// ```
+    pub fn intersects(&self, other: &Mesh) -> bool {
+        let (Some(aabb), Some(other_aabb)) = (self.global_aabb(), other.global_aabb()) else {
+            return false;
+        };
+        if !aabb.intersects(&other_aabb) {
+            return false;
+        }
+
+        // только треугольники, попадающие в общую область
+        let triangles: Vec<GlobalTriangle> = self
+            .global_triangles()
+            .into_iter()
+            .filter(|triangle| triangle.aabb.intersects(&other_aabb))
+            .collect();
+        let other_triangles: Vec<GlobalTriangle> = other
+            .global_triangles()
+            .into_iter()
+            .filter(|triangle| triangle.aabb.intersects(&aabb))
+            .collect();
+
+        for triangle in &triangles {
+            for other_triangle in &other_triangles {
+                if triangle.aabb.intersects(&other_triangle.aabb)
+                    && triangles_intersect(&triangle.vertexes, &other_triangle.vertexes)
+                {
+                    return true;
+                }
+            }
+        }
+
+        // один Mesh целиком внутри другого
+        let inside = |inner: &Mesh, outer: &Mesh| {
+            !inner.vertexes.is_empty()
+                && outer.is_watertight()
+                && outer.contains_point(inner.get_global_vertex(0))
+        };
+        inside(self, other) || inside(other, self)
+    }
+
+    /// Пересекается ли Mesh с плоскостью `plane`, то есть лежат ли его вершины по разные
+    /// стороны от неё или на ней.
+    pub fn intersects_plane(&self, plane: &Plane) -> bool {
+        let mut has_front = false;
+        let mut has_back = false;
+        for vertex in self.get_global_vertex_iter() {
+            let distance = plane.distance_to_point(vertex);
+            if distance == 0.0 {
+                return true;
+            }
+            has_front |= distance > 0.0;
+            has_back |= distance < 0.0;
+            if has_front && has_back {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Пересекается ли поверхность Mesh'а (или его объём, если Mesh замкнут) с
+    /// параллелепипедом `aabb`, заданным в **глобальных** координатах.
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        let Some(mesh_aabb) = self.global_aabb() else {
+            return false;
+        };
+        if !mesh_aabb.intersects(aabb) {
+            return false;
+        }
+
+        for triangle in self.global_triangles() {
+            if !triangle.aabb.intersects(aabb) {
+                continue;
+            }
+            let [a, b, c] = triangle.vertexes;
+            // ребро треугольника проходит через параллелепипед
+            if aabb.intersects_segment(a, b)
+                || aabb.intersects_segment(b, c)
+                || aabb.intersects_segment(c, a)
+            {
+                return true;
+            }
+            // треугольник пересекает параллелепипед, не задевая его рёбрами
+            if aabb
+                .edges()
+                .iter()
+                .any(|&(start, end)| segment_intersects_triangle(start, end, &triangle.vertexes))
+            {
+                return true;
+            }
+        }
+
+        // параллелепипед целиком внутри Mesh'а
+        self.is_watertight() && self.contains_point(aabb.center())
+    }
+
+    /// Пересекается ли поверхность Mesh'а (или его объём, если Mesh замкнут) с шаром с
+    /// центром `center` и радиусом `radius`, заданными в **глобальных** координатах.
+    pub fn intersects_sphere(&self, center: Point3, radius: f32) -> bool {
+        let Some(mesh_aabb) = self.global_aabb() else {
+            return false;
+        };
+        if !mesh_aabb.intersects_sphere(center, radius) {
+            return false;
+        }
+
+        let touches_surface = self.global_triangles().iter().any(|triangle| {
+            triangle.aabb.intersects_sphere(center, radius)
+                && (closest_point_on_triangle(&triangle.vertexes, center) - center).length()
+                    <= radius
+        });
+        touches_surface || (self.is_watertight() && self.contains_point(center))
+    }
+
+    /// Все треугольники Mesh'а в **глобальных** координатах.
+    fn global_triangles(&self) -> Vec<GlobalTriangle> {
+        let vertexes: Vec<Point3> = self.get_global_vertex_iter().collect();
+        self.triangle_list()
+            .into_iter()
+            .map(|[a, b, c]| {
+                let vertexes = [vertexes[a], vertexes[b], vertexes[c]];
+                GlobalTriangle {
+                    vertexes,
+                    aabb: Aabb::from_points(vertexes).unwrap(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Пересекаются ли треугольники: хотя бы одно ребро одного пересекает другой.
+fn triangles_intersect(first: &[Point3; 3], second: &[Point3; 3]) -> bool {
+    let edges_cross = |edges: &[Point3; 3], triangle: &[Point3; 3]| {
+        (0..3).any(|i| segment_intersects_triangle(edges[i], edges[(i + 1) % 3], triangle))
+    };
+    edges_cross(first, second) || edges_cross(second, first)
+}
+
+/// Пересекается ли отрезок `[start, end]` с треугольником.
+fn segment_intersects_triangle(start: Point3, end: Point3, triangle: &[Point3; 3]) -> bool {
+    let offset = end - start;
+    let length = offset.length();
+    let Ok(direction) = offset.normalize() else {
+        return false;
+    };
+    let ray = Line3::new(start, direction);
+    let [a, b, c] = *triangle;
+    intersect_triangle(&ray, a, b, c).is_some_and(|(distance, _, _)| distance <= length)
+}
+
+/// Ближайшая к `point` точка треугольника.
+fn closest_point_on_triangle(triangle: &[Point3; 3], point: Point3) -> Point3 {
+    let [a, b, c] = *triangle;
+    let ab = b - a;
+    let ac = c - a;
+
+    // область вершины a
+    let ap = point - a;
+    let (d1, d2) = (ab.dot(ap), ac.dot(ap));
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return a;
+    }
+
+    // область вершины b
+    let bp = point - b;
+    let (d3, d4) = (ab.dot(bp), ac.dot(bp));
+    if d3 >= 0.0 && d4 <= d3 {
+        return b;
+    }
+
+    // область ребра ab
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + ab * (d1 / (d1 - d3));
+    }
+
+    // область вершины c
+    let cp = point - c;
+    let (d5, d6) = (ab.dot(cp), ac.dot(cp));
+    if d6 >= 0.0 && d5 <= d6 {
+        return c;
+    }
+
+    // область ребра ac
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + ac * (d2 / (d2 - d6));
+    }
+
+    // область ребра bc
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+
+    // внутри треугольника
+    let denominator = 1.0 / (va + vb + vc);
+    a + ab * (vb * denominator) + ac * (vc * denominator)
+}
+
+#[cfg(test)]
+mod collision_tests {
+    use super::*;
+    use crate::Vec3;
+
+    fn cube_at(offset: Vec3, scale: f32) -> Mesh {
+        let mut cube = Mesh::hexahedron();
+        cube.local_frame.scale = Vec3::new(scale, scale, scale);
+        cube.local_frame.translate_vec(offset);
+        cube
+    }
+
+    #[test]
+    fn test_mesh_intersections() {
+        let cube = cube_at(Vec3::zero(), 1.0);
+
+        assert!(cube.intersects(&cube_at(Vec3::new(0.7, 0.2, -0.1), 1.0)));
+        assert!(!cube.intersects(&cube_at(Vec3::new(1.5, 0.0, 0.0), 1.0)));
+
+        // маленький куб внутри большого: поверхности не пересекаются
+        let inner = cube_at(Vec3::zero(), 0.2);
+        assert!(cube.intersects(&inner), "вложенный Mesh пересекается");
+        assert!(inner.intersects(&cube), "проверка симметрична");
+    }
+
+    #[test]
+    fn test_primitive_intersections() {
+        let cube = cube_at(Vec3::zero(), 1.0);
+
+        let plane = Plane::new(Point3::new(0.0, 0.3, 0.0), UVec3::up());
+        assert!(cube.intersects_plane(&plane));
+        let plane = Plane::new(Point3::new(0.0, 0.6, 0.0), UVec3::up());
+        assert!(!cube.intersects_plane(&plane));
+
+        assert!(cube.intersects_sphere(Point3::new(0.9, 0.0, 0.0), 0.5));
+        assert!(!cube.intersects_sphere(Point3::new(0.9, 0.9, 0.9), 0.5));
+        assert!(
+            cube.intersects_sphere(Point3::zero(), 0.1),
+            "шар внутри замкнутого Mesh'а"
+        );
+
+        let aabb = Aabb::new(Point3::new(0.4, -2.0, -2.0), Point3::new(2.0, 2.0, 2.0));
+        assert!(
+            cube.intersects_aabb(&aabb),
+            "грань куба внутри параллелепипеда"
+        );
+        let aabb = Aabb::new(Point3::new(0.6, -2.0, -2.0), Point3::new(2.0, 2.0, 2.0));
+        assert!(!cube.intersects_aabb(&aabb));
+    }
+
+    #[test]
+    fn test_closest_point_on_triangle() {
+        let triangle = [
+            Point3::zero(),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(0.0, 2.0, 0.0),
+        ];
+        let inside = closest_point_on_triangle(&triangle, Point3::new(0.5, 0.5, 3.0));
+        assert!(inside.approx_equal(Point3::new(0.5, 0.5, 0.0), 1e-5));
+        let edge = closest_point_on_triangle(&triangle, Point3::new(2.0, 2.0, 0.0));
+        assert!(edge.approx_equal(Point3::new(1.0, 1.0, 0.0), 1e-5));
+        let vertex = closest_point_on_triangle(&triangle, Point3::new(-1.0, -1.0, 0.0));
+        assert!(vertex.approx_equal(Point3::zero(), 1e-5));
+    }
+}
@@ -0,0 +1,467 @@
+//! Полурёберная структура (half-edge) для запросов смежности Mesh'а.
+
+use std::collections::HashMap;
+
+use super::Mesh;
+use crate::{Point3, Vec3};
+
+/// Полуребро: направленное ребро одного полигона.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HalfEdge {
+    /// Индекс вершины Mesh'а, из которой выходит полуребро.
+    pub origin: usize,
+    /// Индекс вершины Mesh'а, в которую входит полуребро.
+    pub target: usize,
+    /// Индекс полигона, которому принадлежит полуребро.
+    pub face: usize,
+    /// Следующее полуребро того же полигона по обходу.
+    pub next: usize,
+    /// Предыдущее полуребро того же полигона по обходу.
+    pub prev: usize,
+    /// Полуребро соседнего полигона по тому же ребру.
+    ///
+    /// `None` на границе Mesh'а и на неманифолдных рёбрах, у которых больше двух полигонов.
+    pub twin: Option<usize>,
+}
+
+/// Полурёберное представление Mesh'а для запросов смежности: рёбра и полигоны вокруг вершины,
+/// полигоны вокруг ребра, граничные контуры и силуэтные рёбра.
+///
+/// Вершины с совпадающими координатами (например, на швах текстуры) считаются одной вершиной,
+/// поэтому швы не становятся границей. Вершины в запросах и результатах - индексы вершин
+/// исходного Mesh'а. Представление не следит за изменениями Mesh'а и строится заново после них.
+///
+/// Двойники полурёбер ищутся по неориентированным рёбрам, поэтому структура строится и для
+/// Mesh'ей с несогласованным обходом полигонов (в согласованном двойник направлен обратно).
+///
+/// # Examples
+/// ```rust
+/// use g3d::{HalfEdgeMesh, Mesh};
+///
+/// let cube = Mesh::hexahedron();
+/// let half_edges = HalfEdgeMesh::new(&cube);
+///
+/// assert_eq!(half_edges.half_edge_count(), 24);
+/// assert_eq!(half_edges.edges_around_vertex(0).len(), 3);
+/// assert!(half_edges.boundary_loops().is_empty());
+/// ```
+#[derive(Debug, Clone)]
+pub struct HalfEdgeMesh {
+    /// Все полуребра по порядку полигонов и их рёбер.
+    half_edges: Vec<HalfEdge>,
+    /// Группа совпадающих вершин для каждой вершины Mesh'а.
+    groups: Vec<usize>,
+    /// Полуребра, выходящие из каждой группы вершин.
+    outgoing: Vec<Vec<usize>>,
+    /// Полуребра каждого неориентированного ребра по порядку появления рёбер.
+    edges: Vec<Vec<usize>>,
+    /// Номер ребра по упорядоченной паре групп его концов.
+    edge_index: HashMap<(usize, usize), usize>,
+}
+
+impl HalfEdgeMesh {
+    // --------------------------------------------------
+    // Построение
+    // --------------------------------------------------
+
+    /// Построить полурёберное представление Mesh'а.
+    pub fn new(mesh: &Mesh) -> Self {
+        let (groups, positions) = mesh.position_groups();
+        let mut half_edges = Vec::new();
+        let mut outgoing = vec![Vec::new(); positions.len()];
+        let mut edges: Vec<Vec<usize>> = Vec::new();
+        let mut edge_index = HashMap::new();
+
+        for (face, polygon) in mesh.get_polygon_iter().enumerate() {
+            let first = half_edges.len();
+            let polygon_edges: Vec<(usize, usize)> = polygon.edges().collect();
+            let n = polygon_edges.len();
+            for (i, &(origin, target)) in polygon_edges.iter().enumerate() {
+                let index = first + i;
+                half_edges.push(HalfEdge {
+                    origin,
+                    target,
+                    face,
+                    next: first + (i + 1) % n,
+                    prev: first + (i + n - 1) % n,
+                    twin: None,
+                });
+                outgoing[groups[origin]].push(index);
+
+                let (a, b) = (groups[origin], groups[target]);
+                // ребро, стянутое в точку, соседей не имеет
+                if a == b {
+                    continue;
+                }
+                let edge = *edge_index.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    edges.push(Vec::new());
+                    edges.len() - 1
+                });
+                edges[edge].push(index);
+            }
+        }
+
+        for edge in &edges {
+            if let &[a, b] = edge.as_slice() {
+                half_edges[a].twin = Some(b);
+                half_edges[b].twin = Some(a);
+            }
+        }
+
+        Self {
+            half_edges,
+            groups,
+            outgoing,
+            edges,
+            edge_index,
+        }
+    }
+
+    // --------------------------------------------------
+    // Полуребра
+    // --------------------------------------------------
+
+    /// Количество полурёбер.
+    pub fn half_edge_count(&self) -> usize {
+        self.half_edges.len()
+    }
+
+    /// Получить `i`-е полуребро.
+    pub fn half_edge(&self, i: usize) -> &HalfEdge {
+        &self.half_edges[i]
+    }
+
+    /// Итератор по всем полурёбрам.
+    pub fn half_edge_iter(&self) -> impl Iterator<Item = &HalfEdge> {
+        self.half_edges.iter()
+    }
+
+    /// Является ли каждое ребро общим не более чем для двух полигонов.
+    pub fn is_manifold(&self) -> bool {
+        self.edges.iter().all(|edge| edge.len() <= 2)
+    }
+
+    // --------------------------------------------------
+    // Смежность
+    // --------------------------------------------------
+
+    /// Рёбра, выходящие из вершины `vertex`, в виде пар вершин, первая из которых совпадает
+    /// с `vertex` (или находится в той же точке). Каждое ребро перечисляется один раз.
+    pub fn edges_around_vertex(&self, vertex: usize) -> Vec<(usize, usize)> {
+        let group = self.groups[vertex];
+        let mut seen = Vec::new();
+        let mut result = Vec::new();
+        for &index in &self.outgoing[group] {
+            let half_edge = &self.half_edges[index];
+            // входящее полуребро того же полигона задаёт второе ребро при вершине
+            let incoming = &self.half_edges[half_edge.prev];
+            for (other, endpoint) in [
+                (half_edge.target, half_edge.origin),
+                (incoming.origin, incoming.target),
+            ] {
+                let other_group = self.groups[other];
+                if other_group != group && !seen.contains(&other_group) {
+                    seen.push(other_group);
+                    result.push((endpoint, other));
+                }
+            }
+        }
+        result
+    }
+
+    /// Полигоны, в которые входит вершина `vertex`, в порядке возрастания индексов.
+    pub fn faces_around_vertex(&self, vertex: usize) -> Vec<usize> {
+        let mut faces: Vec<usize> = self.outgoing[self.groups[vertex]]
+            .iter()
+            .map(|&index| self.half_edges[index].face)
+            .collect();
+        faces.sort_unstable();
+        faces.dedup();
+        faces
+    }
+
+    /// Полигоны, которым принадлежит ребро между вершинами `a` и `b`.
+    ///
+    /// Если такого ребра нет, то возвращается пустой вектор.
+    pub fn faces_around_edge(&self, a: usize, b: usize) -> Vec<usize> {
+        let (a, b) = (self.groups[a], self.groups[b]);
+        self.edge_index
+            .get(&(a.min(b), a.max(b)))
+            .map(|&edge| self.edge_faces(edge).collect())
+            .unwrap_or_default()
+    }
+
+    // --------------------------------------------------
+    // Граница и силуэт
+    // --------------------------------------------------
+
+    /// Граничные рёбра, принадлежащие ровно одному полигону, в виде пар вершин по обходу
+    /// этого полигона.
+    pub fn boundary_edges(&self) -> Vec<(usize, usize)> {
+        self.edges
+            .iter()
+            .filter(|edge| edge.len() == 1)
+            .map(|edge| self.endpoints(edge[0]))
+            .collect()
+    }
+
+    /// Замкнутые контуры граничных рёбер (отверстия в Mesh'е).
+    ///
+    /// Каждый контур - вершины по порядку, первая вершина не повторяется в конце. Направление
+    /// контура задаётся обходом полигона его первого ребра. Если граница разветвляется
+    /// (неманифолдная вершина), то контур прерывается в точке ветвления.
+    pub fn boundary_loops(&self) -> Vec<Vec<usize>> {
+        let boundary: Vec<usize> = (0..self.edges.len())
+            .filter(|&edge| self.edges[edge].len() == 1)
+            .collect();
+        let mut around_group: HashMap<usize, Vec<usize>> = HashMap::new();
+        for &edge in &boundary {
+            let (a, b) = self.endpoints(self.edges[edge][0]);
+            around_group.entry(self.groups[a]).or_default().push(edge);
+            around_group.entry(self.groups[b]).or_default().push(edge);
+        }
+
+        let mut visited = vec![false; self.edges.len()];
+        let mut loops = Vec::new();
+        for &start in &boundary {
+            if visited[start] {
+                continue;
+            }
+            visited[start] = true;
+
+            let (first, mut current) = self.endpoints(self.edges[start][0]);
+            let mut contour = vec![first];
+            while self.groups[current] != self.groups[first] {
+                contour.push(current);
+                let next = around_group[&self.groups[current]]
+                    .iter()
+                    .copied()
+                    .find(|&edge| !visited[edge]);
+                let Some(next) = next else {
+                    break;
+                };
+                visited[next] = true;
+                let (a, b) = self.endpoints(self.edges[next][0]);
+                current = if self.groups[a] == self.groups[current] {
+                    b
+                } else {
+                    a
+                };
+            }
+            loops.push(contour);
+        }
+        loops
+    }
+
+    /// Силуэтные рёбра при взгляде в направлении `view_direction` в **глобальных** координатах.
+    ///
+    /// Ребро силуэтное, если один его полигон повёрнут к наблюдателю, а другой нет, или если
+    /// это граничное ребро повёрнутого к наблюдателю полигона. Направление полигонов
+    /// определяется по [`Mesh::get_global_polygon_normal`], поэтому `mesh` должен быть тем же
+    /// Mesh'ем, из которого построено представление. Вырожденные полигоны считаются
+    /// отвёрнутыми.
+    pub fn silhouette_edges(&self, mesh: &Mesh, view_direction: Vec3) -> Vec<(usize, usize)> {
+        self.silhouette_edges_by(mesh, |_| view_direction)
+    }
+
+    /// Силуэтные рёбра при взгляде из точки `eye` в **глобальных** координатах
+    /// (при перспективной проекции).
+    ///
+    /// То же, что [`HalfEdgeMesh::silhouette_edges`], но направление взгляда на каждый полигон
+    /// своё: из `eye` в его центр.
+    pub fn silhouette_edges_from(&self, mesh: &Mesh, eye: Point3) -> Vec<(usize, usize)> {
+        self.silhouette_edges_by(mesh, |face| mesh.get_global_polygon_center(face) - eye)
+    }
+
+    /// Силуэтные рёбра при направлении взгляда на каждый полигон `view_direction(face)`.
+    fn silhouette_edges_by<F>(&self, mesh: &Mesh, view_direction: F) -> Vec<(usize, usize)>
+    where
+        F: Fn(usize) -> Vec3,
+    {
+        let front_facing: Vec<bool> = (0..mesh.polygon_count())
+            .map(|face| {
+                mesh.get_global_polygon_normal(face)
+                    .is_some_and(|normal| Vec3::from(normal).dot(view_direction(face)) < 0.0)
+            })
+            .collect();
+
+        self.edges
+            .iter()
+            .enumerate()
+            .filter(|(edge, half_edges)| {
+                let mut facing = self.edge_faces(*edge).map(|face| front_facing[face]);
+                match half_edges.len() {
+                    1 => facing.any(|is_front| is_front),
+                    _ => {
+                        let first = facing.next();
+                        facing.any(|is_front| Some(is_front) != first)
+                    }
+                }
+            })
+            .map(|(_, half_edges)| self.endpoints(half_edges[0]))
+            .collect()
+    }
+
+    /// Полигоны ребра `edge`.
+    fn edge_faces(&self, edge: usize) -> impl Iterator<Item = usize> + '_ {
+        self.edges[edge]
+            .iter()
+            .map(|&index| self.half_edges[index].face)
+    }
+
+    /// Концы полуребра `index`.
+    fn endpoints(&self, index: usize) -> (usize, usize) {
+        let half_edge = &self.half_edges[index];
+        (half_edge.origin, half_edge.target)
+    }
+}
+
+impl Mesh {
+    // --------------------------------------------------
+    // Силуэт
+    // --------------------------------------------------
+
+    /// Силуэтные рёбра Mesh'а при взгляде в направлении `view_direction` отрезками
+    /// в **глобальных** координатах.
+    ///
+    /// Ребро силуэтное, если один из его полигонов повёрнут к наблюдателю, а другой нет
+    /// (см. [`HalfEdgeMesh::silhouette_edges`]). Для обводки нескольких кадров одного Mesh'а
+    /// выгоднее один раз построить [`HalfEdgeMesh`].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::{Mesh, Vec3};
+    ///
+    /// let mut cube = Mesh::hexahedron();
+    /// cube.generate_normals();
+    ///
+    /// // по диагонали силуэт куба - шестиугольник
+    /// assert_eq!(cube.silhouette_edges(Vec3::new(1.0, 1.0, 1.0)).len(), 6);
+    /// ```
+    pub fn silhouette_edges(&self, view_direction: Vec3) -> Vec<(Point3, Point3)> {
+        let half_edges = HalfEdgeMesh::new(self);
+        self.edge_segments(half_edges.silhouette_edges(self, view_direction))
+    }
+
+    /// Силуэтные рёбра Mesh'а при взгляде из точки `eye` (при перспективной проекции)
+    /// отрезками в **глобальных** координатах.
+    pub fn silhouette_edges_from(&self, eye: Point3) -> Vec<(Point3, Point3)> {
+        let half_edges = HalfEdgeMesh::new(self);
+        self.edge_segments(half_edges.silhouette_edges_from(self, eye))
+    }
+
+    /// Рёбра, заданные индексами вершин, отрезками в **глобальных** координатах.
+    fn edge_segments(&self, edges: Vec<(usize, usize)>) -> Vec<(Point3, Point3)> {
+        edges
+            .into_iter()
+            .map(|(a, b)| (self.get_global_vertex(a), self.get_global_vertex(b)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod half_edge_tests {
+    use super::*;
+    use crate::Polygon;
+
+    /// Куб без верхней грани.
+    fn open_box() -> Mesh {
+        let cube = Mesh::hexahedron();
+        let vertexes: Vec<Point3> = cube.get_local_vertex_iter().collect();
+        let polygons: Vec<Polygon> = cube
+            .get_polygon_iter()
+            .enumerate()
+            .filter(|(i, _)| *i != 1)
+            .map(|(_, polygon)| polygon.clone())
+            .collect();
+        Mesh::from_polygons(vertexes, polygons)
+    }
+
+    #[test]
+    fn test_closed_cube_adjacency() {
+        let cube = Mesh::hexahedron();
+        let half_edges = HalfEdgeMesh::new(&cube);
+
+        assert!(half_edges.is_manifold());
+        assert!(half_edges.half_edge_iter().all(|h| h.twin.is_some()));
+        assert!(half_edges.boundary_edges().is_empty());
+        for vertex in 0..cube.vertex_count() {
+            let edges = half_edges.edges_around_vertex(vertex);
+            assert_eq!(edges.len(), 3, "вершина {}: {:?}", vertex, edges);
+            assert!(edges.iter().all(|&(a, _)| a == vertex));
+            assert_eq!(half_edges.faces_around_vertex(vertex).len(), 3);
+        }
+        for (a, b) in cube.edge_list() {
+            assert_eq!(half_edges.faces_around_edge(a, b).len(), 2);
+        }
+        // диагональ грани не является ребром
+        assert!(half_edges.faces_around_edge(0, 2).is_empty());
+
+        let h = half_edges.half_edge(0);
+        assert_eq!(half_edges.half_edge(h.next).origin, h.target);
+        assert_eq!(half_edges.half_edge(h.prev).target, h.origin);
+    }
+
+    #[test]
+    fn test_boundary_loops() {
+        let open = open_box();
+        let half_edges = HalfEdgeMesh::new(&open);
+
+        assert_eq!(half_edges.boundary_edges().len(), 4);
+        let loops = half_edges.boundary_loops();
+        assert_eq!(loops.len(), 1);
+        let mut contour = loops[0].clone();
+        contour.sort_unstable();
+        assert_eq!(contour, vec![4, 5, 6, 7], "контур по краю верхней грани");
+
+        // вершины на швах не размыкают Mesh
+        let seams = Mesh::hexahedron().split_texture_seams();
+        assert!(HalfEdgeMesh::new(&seams).boundary_loops().is_empty());
+    }
+
+    #[test]
+    fn test_silhouette_edges() {
+        let mut cube = Mesh::hexahedron();
+        cube.generate_normals();
+        let half_edges = HalfEdgeMesh::new(&cube);
+
+        // по диагонали видны три грани, силуэт - шестиугольник
+        let silhouette = half_edges.silhouette_edges(&cube, Vec3::new(1.0, 1.0, 1.0));
+        assert_eq!(silhouette.len(), 6, "{:?}", silhouette);
+        let corner_touching = |vertex: usize| {
+            silhouette
+                .iter()
+                .filter(|&&(a, b)| a == vertex || b == vertex)
+                .count()
+        };
+        // ближний и дальний углы в силуэт не входят
+        assert_eq!(corner_touching(0), 0);
+        assert_eq!(corner_touching(6), 0);
+
+        // у незамкнутого Mesh'а в силуэт входит граница повёрнутых к наблюдателю граней
+        let quad = Mesh::from_polygons(
+            vec![
+                Point3::new(0.0, 0.0, 0.0),
+                Point3::new(1.0, 0.0, 0.0),
+                Point3::new(1.0, 1.0, 0.0),
+                Point3::new(0.0, 1.0, 0.0),
+            ],
+            vec![Polygon::from_list(&[0, 1, 2, 3])],
+        );
+        let half_edges = HalfEdgeMesh::new(&quad);
+        let normal = Vec3::from(quad.get_global_polygon_normal(0).unwrap());
+        assert_eq!(half_edges.silhouette_edges(&quad, -normal).len(), 4);
+        assert!(half_edges.silhouette_edges(&quad, normal).is_empty());
+        let eye = Point3::new(0.5, 0.5, 0.0) + normal * 3.0;
+        assert_eq!(half_edges.silhouette_edges_from(&quad, eye).len(), 4);
+
+        // отрезки в глобальных координатах
+        let mut moved = cube.clone();
+        moved.apply_transform(crate::Transform3D::translation(0.0, 10.0, 0.0));
+        moved.local_frame.origin = Point3::new(5.0, 0.0, 0.0);
+        for (start, end) in moved.silhouette_edges(Vec3::new(1.0, 1.0, 1.0)) {
+            assert!(start.x > 4.0 && end.x > 4.0 && start.y > 9.0 && end.y > 9.0);
+            assert!(((end - start).length() - 1.0).abs() < 1e-5);
+        }
+    }
+}
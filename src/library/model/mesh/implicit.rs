@@ -0,0 +1,224 @@
+//! Построение Mesh'а по неявной функции (скалярному полю) методом marching cubes.
+
+use std::collections::HashMap;
+
+use super::{Mesh, Polygon};
+use crate::{Aabb, Point3, UVec3, Vec3};
+
+/// Разбиение куба сетки на 6 тетраэдров вдоль главной диагонали.
+///
+/// Углы куба задаются смещениями `(dx, dy, dz)`. Соседние кубы делят общие грани
+/// по одинаковым диагоналям, поэтому поверхность получается без щелей.
+const CUBE_TETRAHEDRA: [[(usize, usize, usize); 4]; 6] = [
+    [(0, 0, 0), (1, 0, 0), (1, 1, 0), (1, 1, 1)],
+    [(0, 0, 0), (1, 0, 0), (1, 0, 1), (1, 1, 1)],
+    [(0, 0, 0), (0, 1, 0), (1, 1, 0), (1, 1, 1)],
+    [(0, 0, 0), (0, 1, 0), (0, 1, 1), (1, 1, 1)],
+    [(0, 0, 0), (0, 0, 1), (1, 0, 1), (1, 1, 1)],
+    [(0, 0, 0), (0, 0, 1), (0, 1, 1), (1, 1, 1)],
+];
+
+impl Mesh {
+    /// Создать Mesh поверхности `f(p) = 0` неявной функции `f` в пределах `bounds`.
+    ///
+    /// Область разбивается на `resolution` кубов по каждой оси, поверхность строится
+    /// методом marching cubes, где каждый куб делится на тетраэдры (marching tetrahedra):
+    /// так нет неоднозначных случаев и дыр в поверхности. Точки с `f(p) < 0` считаются
+    /// внутренними, как у функций расстояния со знаком (SDF), грани смотрят наружу.
+    /// Нормали вершин - нормированный градиент поля, текстурные координаты - проекция на
+    /// плоскость XY в пределах `bounds`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::{Aabb, Mesh, Point3, Vec3};
+    ///
+    /// // сфера радиуса 1
+    /// let sphere = Mesh::from_implicit(
+    ///     |p| Vec3::from(p).length() - 1.0,
+    ///     Aabb::new(Point3::new(-1.5, -1.5, -1.5), Point3::new(1.5, 1.5, 1.5)),
+    ///     16,
+    /// );
+    /// assert!(sphere.polygon_count() > 0);
+    /// ```
+    pub fn from_implicit(f: impl Fn(Point3) -> f32, bounds: Aabb, resolution: usize) -> Mesh {
+        assert!(
+            resolution >= 1,
+            "количество разбиений должно быть не менее 1"
+        );
+
+        let n = resolution;
+        let size = bounds.max - bounds.min;
+        let cell = Vec3::new(size.x / n as f32, size.y / n as f32, size.z / n as f32);
+        let node_index = |(i, j, k): (usize, usize, usize)| (k * (n + 1) + j) * (n + 1) + i;
+        let node_position = |(i, j, k): (usize, usize, usize)| {
+            bounds.min + Vec3::new(i as f32 * cell.x, j as f32 * cell.y, k as f32 * cell.z)
+        };
+
+        // значения поля в узлах сетки, неопределённые точки считаются внешними
+        let mut values = vec![0.0; (n + 1) * (n + 1) * (n + 1)];
+        for k in 0..=n {
+            for j in 0..=n {
+                for i in 0..=n {
+                    let value = f(node_position((i, j, k)));
+                    values[node_index((i, j, k))] = if value.is_nan() { f32::MAX } else { value };
+                }
+            }
+        }
+
+        // градиент центральными разностями
+        let step = cell.x.min(cell.y).min(cell.z) * 0.25;
+        let gradient = |p: Point3| {
+            let axis = |offset: Vec3| (f(p + offset) - f(p + offset * -1.0)) / (2.0 * step);
+            Vec3::new(
+                axis(Vec3::new(step, 0.0, 0.0)),
+                axis(Vec3::new(0.0, step, 0.0)),
+                axis(Vec3::new(0.0, 0.0, step)),
+            )
+        };
+
+        let mut vertexes = Vec::new();
+        // вершина на ребре сетки между узлами (a, b), a < b
+        let mut edge_vertexes: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut polygons = Vec::new();
+
+        for k in 0..n {
+            for j in 0..n {
+                for i in 0..n {
+                    for tetrahedron in CUBE_TETRAHEDRA {
+                        let nodes = tetrahedron.map(|(dx, dy, dz)| (i + dx, j + dy, k + dz));
+                        let indexes = nodes.map(node_index);
+                        let inside: Vec<usize> =
+                            (0..4).filter(|&t| values[indexes[t]] < 0.0).collect();
+                        let outside: Vec<usize> =
+                            (0..4).filter(|&t| values[indexes[t]] >= 0.0).collect();
+
+                        let mut crossing = |a: usize, b: usize| -> usize {
+                            let (a, b) = (indexes[a].min(indexes[b]), indexes[a].max(indexes[b]));
+                            *edge_vertexes.entry((a, b)).or_insert_with(|| {
+                                let position = |index: usize| {
+                                    let i = index % (n + 1);
+                                    let j = index / (n + 1) % (n + 1);
+                                    let k = index / ((n + 1) * (n + 1));
+                                    node_position((i, j, k))
+                                };
+                                let (va, vb) = (values[a], values[b]);
+                                let t = (va / (va - vb)).clamp(0.0, 1.0);
+                                let (pa, pb) = (position(a), position(b));
+                                vertexes.push(pa + (pb - pa) * t);
+                                vertexes.len() - 1
+                            })
+                        };
+
+                        let triangles: Vec<[usize; 3]> = match (inside.len(), outside.len()) {
+                            // одна вершина отделена от трёх остальных
+                            (1, 3) | (3, 1) => {
+                                let (lone, rest) = if inside.len() == 1 {
+                                    (inside[0], &outside)
+                                } else {
+                                    (outside[0], &inside)
+                                };
+                                vec![[
+                                    crossing(lone, rest[0]),
+                                    crossing(lone, rest[1]),
+                                    crossing(lone, rest[2]),
+                                ]]
+                            }
+                            // сечение - четырёхугольник
+                            (2, 2) => {
+                                let (a, b) = (inside[0], inside[1]);
+                                let (c, d) = (outside[0], outside[1]);
+                                let quad = [
+                                    crossing(a, c),
+                                    crossing(a, d),
+                                    crossing(b, d),
+                                    crossing(b, c),
+                                ];
+                                vec![[quad[0], quad[1], quad[2]], [quad[0], quad[2], quad[3]]]
+                            }
+                            _ => Vec::new(),
+                        };
+
+                        for [a, b, c] in triangles {
+                            let (pa, pb, pc) = (vertexes[a], vertexes[b], vertexes[c]);
+                            let normal = (pb - pa).cross(pc - pa);
+                            if normal.length() <= f32::EPSILON {
+                                // вырожденный треугольник при значениях поля ровно 0 в узлах
+                                continue;
+                            }
+                            // против часовой стрелки, если смотреть снаружи
+                            let center = pa + ((pb - pa) + (pc - pa)) * (1.0 / 3.0);
+                            if normal.dot(gradient(center)) >= 0.0 {
+                                polygons.push(Polygon::triangle(a, b, c));
+                            } else {
+                                polygons.push(Polygon::triangle(a, c, b));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        let normals = vertexes
+            .iter()
+            .map(|&vertex| gradient(vertex).normalize().unwrap_or(UVec3::up()))
+            .collect();
+        let texture_coords = vertexes
+            .iter()
+            .map(|vertex| {
+                let relative = *vertex - bounds.min;
+                let coord = |value: f32, size: f32| {
+                    if size > 0.0 {
+                        (value / size).clamp(0.0, 1.0)
+                    } else {
+                        0.0
+                    }
+                };
+                (coord(relative.x, size.x), coord(relative.y, size.y))
+            })
+            .collect();
+
+        Mesh::new(vertexes, polygons, Some(normals), Some(texture_coords))
+    }
+}
+
+#[cfg(test)]
+mod implicit_tests {
+    use super::*;
+
+    #[test]
+    fn test_sphere_from_implicit() {
+        let bounds = Aabb::new(Point3::new(-1.5, -1.5, -1.5), Point3::new(1.5, 1.5, 1.5));
+        let sphere = Mesh::from_implicit(|p| Vec3::from(p).length() - 1.0, bounds, 12);
+
+        assert!(sphere.polygon_count() > 100);
+        for i in 0..sphere.vertex_count() {
+            let vertex = sphere.get_local_vertex(i);
+            let radius = Vec3::from(vertex).length();
+            assert!(
+                (radius - 1.0).abs() < 0.05,
+                "вершина {} не на сфере",
+                vertex
+            );
+            // нормаль по градиенту смотрит от центра
+            let normal = sphere.get_local_normal(i).unwrap();
+            assert!(normal.approx_equal(Vec3::from(vertex).normalize().unwrap(), 0.05));
+        }
+        for i in 0..sphere.polygon_count() {
+            let normal = sphere.get_global_polygon_normal(i).unwrap();
+            let vertex = sphere.get_global_vertex(sphere.get_polygon(i).get_mesh_vertex_index(0));
+            assert!(
+                Vec3::from(normal).dot(Vec3::from(vertex)) > 0.0,
+                "грань {} должна смотреть наружу",
+                i
+            );
+        }
+    }
+
+    #[test]
+    fn test_empty_field_gives_empty_mesh() {
+        let bounds = Aabb::new(Point3::zero(), Point3::new(1.0, 1.0, 1.0));
+        let mesh = Mesh::from_implicit(|_| 1.0, bounds, 4);
+        assert_eq!(mesh.vertex_count(), 0);
+        assert_eq!(mesh.polygon_count(), 0);
+    }
+}
@@ -0,0 +1,263 @@
+//! Морф-таргеты (blend shapes) Mesh'а.
+
+use super::Mesh;
+use crate::{Point3, Transform3D, UVec3, Vec3};
+use std::collections::HashMap;
+
+/// Альтернативная форма Mesh'а.
+///
+/// Хранит смещения вершин (и, если заданы, нормалей) относительно исходной формы в
+/// **локальных** координатах Mesh'а, поэтому несколько форм можно смешивать одновременно.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MorphTarget {
+    /// Имя формы, по нему задаётся её вес.
+    pub name: String,
+    /// Смещения вершин относительно исходной формы.
+    position_deltas: Vec<Vec3>,
+    /// Смещения нормалей относительно исходной формы.
+    normal_deltas: Option<Vec<Vec3>>,
+}
+
+impl MorphTarget {
+    /// Смещение i-ой вершины.
+    pub fn get_position_delta(&self, i: usize) -> Vec3 {
+        self.position_deltas[i]
+    }
+
+    /// Содержит ли форма нормали?
+    pub fn has_normals(&self) -> bool {
+        self.normal_deltas.is_some()
+    }
+
+    /// Преобразовать смещения формы вместе с Mesh'ем.
+    ///
+    /// Смещения вершин преобразуются как векторы матрицей `transform`, смещения нормалей -
+    /// матрицей нормалей `normal_transform`.
+    pub(super) fn apply_transform(
+        &mut self,
+        transform: Transform3D,
+        normal_transform: Transform3D,
+    ) {
+        for delta in &mut self.position_deltas {
+            *delta = delta.apply_transform(transform).unwrap_or(*delta);
+        }
+        if let Some(deltas) = &mut self.normal_deltas {
+            for delta in deltas {
+                *delta = delta.apply_transform(normal_transform).unwrap_or(*delta);
+            }
+        }
+    }
+
+    /// Форма для Mesh'а, в котором i-ая вершина - копия вершины `sources[i]` исходного.
+    pub(super) fn remapped(&self, sources: &[usize]) -> Self {
+        Self {
+            name: self.name.clone(),
+            position_deltas: sources
+                .iter()
+                .map(|&source| self.position_deltas[source])
+                .collect(),
+            normal_deltas: self
+                .normal_deltas
+                .as_ref()
+                .map(|deltas| sources.iter().map(|&source| deltas[source]).collect()),
+        }
+    }
+}
+
+impl Mesh {
+    // --------------------------------------------------
+    // Морф-таргеты
+    // --------------------------------------------------
+
+    /// Добавить альтернативную форму Mesh'а.
+    ///
+    /// `positions` - положения всех вершин в этой форме, `normals` - нормали вершин в ней
+    /// (если не заданы, то нормали при смешивании не меняются). Форма с уже существующим
+    /// именем заменяется.
+    pub fn add_morph_target(
+        &mut self,
+        name: impl Into<String>,
+        positions: Vec<Point3>,
+        normals: Option<Vec<UVec3>>,
+    ) {
+        assert_eq!(
+            self.vertexes.len(),
+            positions.len(),
+            "Количество вершин формы должно совпадать с количеством вершин Mesh'а"
+        );
+
+        let position_deltas = positions
+            .into_iter()
+            .zip(&self.vertexes)
+            .map(|(target, &base)| target - base)
+            .collect();
+        let normal_deltas = match (normals, &self.normals) {
+            (Some(normals), Some(base_normals)) => {
+                Self::assert_normals(&self.vertexes, &normals);
+                Some(
+                    normals
+                        .into_iter()
+                        .zip(base_normals)
+                        .map(|(target, &base)| Vec3::from(target) - Vec3::from(base))
+                        .collect(),
+                )
+            }
+            _ => None,
+        };
+
+        let target = MorphTarget {
+            name: name.into(),
+            position_deltas,
+            normal_deltas,
+        };
+        match self.find_morph_target(&target.name) {
+            Some(index) => self.morph_targets[index] = target,
+            None => self.morph_targets.push(target),
+        }
+    }
+
+    /// Удалить форму по имени. Возвращает удалённую форму, если она была.
+    pub fn remove_morph_target(&mut self, name: &str) -> Option<MorphTarget> {
+        let index = self.find_morph_target(name)?;
+        Some(self.morph_targets.remove(index))
+    }
+
+    /// Найти индекс формы по имени.
+    pub fn find_morph_target(&self, name: &str) -> Option<usize> {
+        self.morph_targets
+            .iter()
+            .position(|target| target.name == name)
+    }
+
+    /// Все альтернативные формы Mesh'а.
+    pub fn morph_targets(&self) -> &[MorphTarget] {
+        &self.morph_targets
+    }
+
+    /// Есть ли у Mesh'а альтернативные формы?
+    pub fn has_morph_targets(&self) -> bool {
+        !self.morph_targets.is_empty()
+    }
+
+    /// Получить копию Mesh'а, в которой формы смешаны с весами `weights` (по именам форм).
+    ///
+    /// Вершина смещается на сумму смещений форм, умноженных на их веса. Веса не ограничиваются
+    /// диапазоном `[0, 1]`, так что форму можно "пересилить" или применить в обратную сторону.
+    /// Неизвестные имена игнорируются.
+    pub fn morphed(&self, weights: &HashMap<String, f32>) -> Mesh {
+        let mut mesh = self.clone();
+        let active: Vec<(&MorphTarget, f32)> = self
+            .morph_targets
+            .iter()
+            .filter_map(|target| Some((target, *weights.get(&target.name)?)))
+            .filter(|(_, weight)| *weight != 0.0)
+            .collect();
+        if active.is_empty() {
+            return mesh;
+        }
+
+        for (i, vertex) in mesh.vertexes.iter_mut().enumerate() {
+            for (target, weight) in &active {
+                *vertex += target.position_deltas[i] * *weight;
+            }
+        }
+
+        if let Some(normals) = &mut mesh.normals {
+            for (i, normal) in normals.iter_mut().enumerate() {
+                let mut blended = Vec3::from(*normal);
+                for (target, weight) in &active {
+                    if let Some(deltas) = &target.normal_deltas {
+                        blended += deltas[i] * *weight;
+                    }
+                }
+                if let Ok(blended) = blended.normalize() {
+                    *normal = blended;
+                }
+            }
+        }
+
+        mesh
+    }
+}
+
+#[cfg(test)]
+mod morphing_tests {
+    use super::*;
+
+    const TOLERANCE: f32 = 1e-5;
+
+    /// Куб и его форма, растянутая вдвое по y.
+    fn cube_with_stretch() -> Mesh {
+        let mut mesh = Mesh::hexahedron();
+        let stretched = mesh
+            .get_local_vertex_iter()
+            .map(|v| Point3::new(v.x, v.y * 2.0, v.z))
+            .collect();
+        mesh.add_morph_target("stretch", stretched, None);
+        mesh
+    }
+
+    #[test]
+    fn test_morph_weights() {
+        let mesh = cube_with_stretch();
+        assert!(mesh.has_morph_targets());
+        assert_eq!(mesh.find_morph_target("stretch"), Some(0));
+
+        let mut weights = HashMap::new();
+        for (weight, scale) in [(0.0, 1.0), (0.5, 1.5), (1.0, 2.0)] {
+            weights.insert("stretch".to_owned(), weight);
+            let morphed = mesh.morphed(&weights);
+            for i in 0..mesh.vertex_count() {
+                let base = mesh.get_local_vertex(i);
+                let expected = Point3::new(base.x, base.y * scale, base.z);
+                assert!(
+                    morphed
+                        .get_local_vertex(i)
+                        .approx_equal(expected, TOLERANCE),
+                    "при весе {} ожидалась вершина {}, но получено {}",
+                    weight,
+                    expected,
+                    morphed.get_local_vertex(i)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_morph_target_replace_and_remove() {
+        let mut mesh = cube_with_stretch();
+        let positions = mesh.get_local_vertex_iter().collect();
+        mesh.add_morph_target("stretch", positions, None);
+        assert_eq!(
+            mesh.morph_targets().len(),
+            1,
+            "форма с тем же именем заменяется"
+        );
+        assert_eq!(mesh.morph_targets()[0].get_position_delta(0).length(), 0.0);
+
+        assert!(mesh.remove_morph_target("stretch").is_some());
+        assert!(mesh.remove_morph_target("stretch").is_none());
+        assert!(!mesh.has_morph_targets());
+    }
+
+    #[test]
+    fn test_morph_normals() {
+        let mut mesh = Mesh::hexahedron();
+        let positions = mesh.get_local_vertex_iter().collect();
+        let normals = vec![UVec3::up(); mesh.vertex_count()];
+        mesh.add_morph_target("up", positions, Some(normals));
+        assert!(mesh.morph_targets()[0].has_normals());
+
+        let weights = HashMap::from([("up".to_owned(), 1.0)]);
+        let morphed = mesh.morphed(&weights);
+        for i in 0..mesh.vertex_count() {
+            let normal = morphed.get_local_normal(i).unwrap();
+            assert!(
+                normal.approx_equal(UVec3::up(), TOLERANCE),
+                "при полном весе нормаль должна совпасть с нормалью формы, но получено {:?}",
+                normal
+            );
+        }
+    }
+}
@@ -1,13 +1,28 @@
 //! Объявление и реализация `Polygon` для `Mesh`.
 
+use std::sync::OnceLock;
+
 use crate::{Mesh, Point3, UVec3, Vec3};
 
+/// Допустимое отклонение вершин от плоскости полигона, доля длины его наибольшего ребра.
+const POLYGON_PLANE_TOLERANCE: f32 = 1e-4;
+
 /// Представление одного полигона модели. Дабы избежать копирования вершин,
 /// полигоны только хранят индексы вершин из Mesh'а.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Polygon {
     /// Индексы вершин, которые соединяет этот полигон.
     vertex_indexes: Vec<usize>,
+    /// Индексы текстурных координат углов полигона в таблице UV-координат углов Mesh'а.
+    ///
+    /// Если заданы, то текстурные координаты берутся не из вершин, а из углов полигона,
+    /// что позволяет задавать швы развёртки (как `f v/vt` в OBJ).
+    #[cfg_attr(feature = "serde", serde(default))]
+    texture_indexes: Option<Vec<usize>>,
+    /// Закэшированная триангуляция полигона, вычисляется при первом обращении.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    triangles: OnceLock<Vec<[usize; 3]>>,
 }
 
 impl Polygon {
@@ -17,21 +32,21 @@ impl Polygon {
 
     /// Создать треугольник.
     pub fn triangle(p1: usize, p2: usize, p3: usize) -> Self {
-        Self {
-            vertex_indexes: vec![p1, p2, p3],
-        }
+        Self::from_vec(vec![p1, p2, p3])
     }
 
     /// Создать полигон из списка индексов вершин.
     pub fn from_list(vertex_indexes: &[usize]) -> Self {
-        Self {
-            vertex_indexes: vertex_indexes.into(),
-        }
+        Self::from_vec(vertex_indexes.into())
     }
 
     /// Создать полигон из вектора индексов.
     pub fn from_vec(vertex_indexes: Vec<usize>) -> Self {
-        Self { vertex_indexes }
+        Self {
+            vertex_indexes,
+            texture_indexes: None,
+            triangles: OnceLock::new(),
+        }
     }
 
     // --------------------------------------------------
@@ -99,7 +114,47 @@ impl Polygon {
         parent_mesh.get_global_normal(self.vertex_indexes[i])
     }
 
+    /// Индекс текстурных координат i-ого угла полигона в таблице UV-координат углов Mesh'а.
+    pub fn get_texture_index(&self, i: usize) -> Option<usize> {
+        self.texture_indexes.as_ref()?.get(i).copied()
+    }
+
+    /// Индексы текстурных координат всех углов полигона, если они заданы.
+    pub(super) fn texture_indexes(&self) -> Option<&[usize]> {
+        self.texture_indexes.as_deref()
+    }
+
+    /// Заданы ли у полигона индексы текстурных координат углов?
+    pub fn has_texture_indexes(&self) -> bool {
+        self.texture_indexes.is_some()
+    }
+
+    /// Задать или убрать индексы текстурных координат углов.
+    pub(super) fn set_texture_indexes(&mut self, texture_indexes: Option<Vec<usize>>) {
+        debug_assert!(
+            texture_indexes
+                .as_ref()
+                .is_none_or(|indexes| indexes.len() == self.vertex_count()),
+            "Количество текстурных индексов должно совпадать с количеством вершин полигона"
+        );
+        self.texture_indexes = texture_indexes;
+    }
+
+    /// Изменить порядок обхода вершин (и индексов текстурных координат) на обратный.
+    ///
+    /// Лицевая и нелицевая стороны полигона меняются местами.
+    pub fn flip(&mut self) {
+        self.vertex_indexes.reverse();
+        if let Some(texture_indexes) = &mut self.texture_indexes {
+            texture_indexes.reverse();
+        }
+        self.triangles = OnceLock::new();
+    }
+
     /// Получить текстурные координаты i-ой вершины полигона.
+    ///
+    /// Если у полигона и Mesh'а заданы UV-координаты углов, то возвращаются они,
+    /// иначе - текстурные координаты самой вершины.
     pub fn get_texture_coord(&self, parent_mesh: &Mesh, i: usize) -> Option<(f32, f32)> {
         debug_assert!(
             i < self.vertex_count(),
@@ -107,6 +162,11 @@ impl Polygon {
             i,
             self.vertex_count()
         );
+        if let Some(texture_index) = self.get_texture_index(i)
+            && let Some(uv) = parent_mesh.get_corner_texture_coord(texture_index)
+        {
+            return Some(uv);
+        }
         parent_mesh.get_texture_coord(self.vertex_indexes[i])
     }
 
@@ -175,12 +235,63 @@ impl Polygon {
         if !parent_mesh.has_texture_coords() {
             None
         } else {
-            Some(
-                self.vertex_indexes
-                    .iter()
-                    .map(|&i| parent_mesh.get_texture_coord(i).unwrap()),
-            )
+            Some((0..self.vertex_count()).map(|i| self.get_texture_coord(parent_mesh, i).unwrap()))
+        }
+    }
+
+    // --------------------------------------------------
+    // Триангуляция и рёбра
+    // --------------------------------------------------
+
+    /// Получить итератор по треугольникам полигона в нумерации вершин из всего Mesh'а.
+    ///
+    /// Пока что используется веерная триангуляция, подходящая для выпуклых полигонов.
+    /// Триангуляция вычисляется один раз при первом обращении.
+    pub fn triangles(&self) -> impl Iterator<Item = [usize; 3]> + '_ {
+        self.triangles
+            .get_or_init(|| Self::fan_triangulation(&self.vertex_indexes))
+            .iter()
+            .copied()
+    }
+
+    /// Получить итератор по треугольникам полигона в нумерации углов самого полигона.
+    ///
+    /// Треугольники идут в том же порядке, что и в [`Polygon::triangles`], поэтому
+    /// по номерам углов можно получить и вершины, и текстурные координаты углов.
+    pub fn corner_triangles(&self) -> impl Iterator<Item = [usize; 3]> + use<> {
+        (1..self.vertex_indexes.len().saturating_sub(1)).map(|i| [0, i, i + 1])
+    }
+
+    /// Количество треугольников в триангуляции полигона.
+    pub fn triangle_count(&self) -> usize {
+        self.vertex_indexes.len().saturating_sub(2)
+    }
+
+    /// Получить итератор по рёбрам полигона в нумерации вершин из всего Mesh'а.
+    ///
+    /// Рёбра идут по порядку обхода, последнее ребро замыкает полигон.
+    pub fn edges(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let n = self.vertex_indexes.len();
+        // у вырожденного полигона из одной вершины рёбер нет
+        let count = if n < 2 { 0 } else { n };
+        (0..count).map(move |i| (self.vertex_indexes[i], self.vertex_indexes[(i + 1) % n]))
+    }
+
+    /// Веерная триангуляция полигона, заданного индексами вершин.
+    fn fan_triangulation(vertex_indexes: &[usize]) -> Vec<[usize; 3]> {
+        #[cfg(debug_assertions)]
+        {
+            if vertex_indexes.len() < 3 {
+                eprintln!(
+                    "Warning: триангуляция полигона с {} вершинами",
+                    vertex_indexes.len()
+                );
+            }
         }
+
+        (1..vertex_indexes.len().saturating_sub(1))
+            .map(|i| [vertex_indexes[0], vertex_indexes[i], vertex_indexes[i + 1]])
+            .collect()
     }
 
     // --------------------------------------------------
@@ -204,7 +315,15 @@ impl Polygon {
 
     /// Полигон является четырёхугольником?
     pub fn is_quad(&self) -> bool {
-        self.vertex_indexes.len() == 0
+        self.vertex_indexes.len() == 4
+    }
+
+    /// Полигон является плоским выпуклым четырёхугольником?
+    ///
+    /// Только такие четырёхугольники можно заполнять билинейной интерполяцией,
+    /// остальные нужно разбивать на треугольники. Плоскостность проверяет [`Polygon::is_convex`].
+    pub fn is_convex_quad(&self, parent_mesh: &Mesh) -> bool {
+        self.is_quad() && self.is_convex(parent_mesh)
     }
 
     /// Полигон является хотя бы треугольником.
@@ -214,6 +333,151 @@ impl Polygon {
         self.vertex_indexes.len() >= 3
     }
 
+    /// Площадь полигона в **локальных** координатах.
+    ///
+    /// Считается по формуле Ньюэлла, поэтому подходит и для невыпуклых плоских полигонов.
+    /// У полигонов меньше чем из 3-х вершин площадь нулевая.
+    pub fn area(&self, parent_mesh: &Mesh) -> f32 {
+        self.newell_normal(parent_mesh).length() / 2.0
+    }
+
+    /// Центр масс плоского полигона в **локальных** координатах.
+    ///
+    /// В отличие от среднего вершин, не смещается к участкам с частыми вершинами.
+    /// Для вырожденного (нулевой площади) полигона возвращает среднее вершин.
+    pub fn centroid(&self, parent_mesh: &Mesh) -> Point3 {
+        let normal = self.newell_normal(parent_mesh);
+        let p0 = Vec3::from(self.get_local_vertex(parent_mesh, 0));
+        let mut weighted = Vec3::zero();
+        let mut total = 0.0;
+        for [_, i, j] in self.corner_triangles() {
+            let a = Vec3::from(self.get_local_vertex(parent_mesh, i));
+            let b = Vec3::from(self.get_local_vertex(parent_mesh, j));
+            // площадь со знаком относительно нормали полигона для невыпуклого случая
+            let area = (a - p0).cross(b - p0).dot(normal);
+            weighted += (p0 + a + b) / 3.0 * area;
+            total += area;
+        }
+        // сумма площадей равна квадрату длины нормали Ньюэлла, ноль - вырожденный полигон
+        if total <= f32::MIN_POSITIVE {
+            let sum = self
+                .get_local_vertex_iter(parent_mesh)
+                .fold(Vec3::zero(), |acc, vertex| acc + Vec3::from(vertex));
+            return (sum / self.vertex_count().max(1) as f32).into();
+        }
+        (weighted / total).into()
+    }
+
+    /// Выпуклый ли полигон в **локальных** координатах.
+    ///
+    /// Полигон должен быть плоским: неплоские и вырожденные (меньше 3-х вершин или нулевой
+    /// площади) полигоны считаются невыпуклыми. Только для выпуклых полигонов верна веерная
+    /// триангуляция [`Polygon::triangles`].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::{Mesh, Point3, Polygon};
+    ///
+    /// let vertexes = vec![
+    ///     Point3::new(0.0, 0.0, 0.0),
+    ///     Point3::new(2.0, 0.0, 0.0),
+    ///     Point3::new(2.0, 2.0, 0.0),
+    ///     Point3::new(1.0, 0.5, 0.0),
+    ///     Point3::new(0.0, 2.0, 0.0),
+    /// ];
+    /// let mesh = Mesh::from_polygons(vertexes, vec![Polygon::from_list(&[0, 1, 2, 3, 4])]);
+    /// let polygon = mesh.get_polygon(0);
+    ///
+    /// // "наконечник стрелы" с вершиной, вдавленной внутрь
+    /// assert!(!polygon.is_convex(&mesh));
+    /// assert!((polygon.area(&mesh) - 2.5).abs() < 1.0e-6);
+    /// ```
+    pub fn is_convex(&self, parent_mesh: &Mesh) -> bool {
+        if !self.is_planar(parent_mesh) {
+            return false;
+        }
+        let n = self.vertex_count();
+        let vertexes: Vec<Vec3> = self
+            .get_local_vertex_iter(parent_mesh)
+            .map(Vec3::from)
+            .collect();
+        let size = Self::max_edge_length(&vertexes);
+        let normal = self.newell_normal(parent_mesh);
+        let unit_normal = normal / normal.length();
+        let tolerance = POLYGON_PLANE_TOLERANCE * size;
+
+        // все повороты между соседними рёбрами в одну сторону - по нормали полигона
+        (0..n).all(|i| {
+            let edge = vertexes[(i + 1) % n] - vertexes[i];
+            let next = vertexes[(i + 2) % n] - vertexes[(i + 1) % n];
+            edge.cross(next).dot(unit_normal) >= -tolerance * size
+        })
+    }
+
+    /// Лежат ли все вершины полигона в одной плоскости (в **локальных** координатах).
+    ///
+    /// Отклонение от плоскости допускается в пределах `1e-4` длины наибольшего ребра.
+    /// Вырожденные полигоны (меньше 3-х вершин или нулевой площади) плоскими не считаются.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::{Mesh, Point3, Polygon};
+    ///
+    /// let vertexes = vec![
+    ///     Point3::new(0.0, 0.0, 0.0),
+    ///     Point3::new(1.0, 0.0, 0.0),
+    ///     Point3::new(1.0, 1.0, 0.0),
+    ///     Point3::new(0.0, 1.0, 0.0),
+    ///     Point3::new(0.0, 1.0, 0.5),
+    /// ];
+    /// let mesh = Mesh::from_polygons(
+    ///     vertexes,
+    ///     vec![Polygon::from_list(&[0, 1, 2, 3]), Polygon::from_list(&[0, 1, 2, 4])],
+    /// );
+    /// assert!(mesh.get_polygon(0).is_planar(&mesh));
+    /// assert!(!mesh.get_polygon(1).is_planar(&mesh));
+    /// ```
+    pub fn is_planar(&self, parent_mesh: &Mesh) -> bool {
+        let normal = self.newell_normal(parent_mesh);
+        if self.vertex_count() < 3 || normal.length_squared() == 0.0 {
+            return false;
+        }
+        let vertexes: Vec<Vec3> = self
+            .get_local_vertex_iter(parent_mesh)
+            .map(Vec3::from)
+            .collect();
+        let unit_normal = normal / normal.length();
+        let tolerance = POLYGON_PLANE_TOLERANCE * Self::max_edge_length(&vertexes);
+        vertexes
+            .iter()
+            .all(|&vertex| (vertex - vertexes[0]).dot(unit_normal).abs() <= tolerance)
+    }
+
+    /// Длина наибольшего ребра замкнутой ломаной `vertexes`.
+    fn max_edge_length(vertexes: &[Vec3]) -> f32 {
+        let n = vertexes.len();
+        (0..n)
+            .map(|i| (vertexes[(i + 1) % n] - vertexes[i]).length())
+            .fold(0.0, f32::max)
+    }
+
+    /// Ненормированная нормаль полигона по формуле Ньюэлла в **локальных** координатах.
+    ///
+    /// Длина вектора равна удвоенной площади полигона, направление - по правилу обхода вершин.
+    fn newell_normal(&self, parent_mesh: &Mesh) -> Vec3 {
+        let n = self.vertex_count();
+        if n < 3 {
+            return Vec3::zero();
+        }
+        let mut normal = Vec3::zero();
+        for i in 0..n {
+            let a = Vec3::from(self.get_local_vertex(parent_mesh, i));
+            let b = Vec3::from(self.get_local_vertex(parent_mesh, (i + 1) % n));
+            normal += a.cross(b);
+        }
+        normal
+    }
+
     /// Находится ли точка внутри полигона?
     pub fn is_point_in_convex_polygon(&self, parent_mesh: &Mesh, point: Point3) -> bool {
         let n = self.vertex_indexes.len();
@@ -302,3 +566,104 @@ impl Polygon {
         )
     }
 }
+
+#[cfg(test)]
+mod polygon_tests {
+    use super::*;
+
+    #[test]
+    fn test_triangles_fan() {
+        let polygon = Polygon::from_list(&[4, 5, 6, 7, 8]);
+        let triangles: Vec<[usize; 3]> = polygon.triangles().collect();
+        assert_eq!(triangles, vec![[4, 5, 6], [4, 6, 7], [4, 7, 8]]);
+        assert_eq!(polygon.triangle_count(), 3);
+        let corners: Vec<[usize; 3]> = polygon.corner_triangles().collect();
+        assert_eq!(corners, vec![[0, 1, 2], [0, 2, 3], [0, 3, 4]]);
+
+        // повторное обращение берёт результат из кэша
+        assert_eq!(polygon.triangles().count(), 3);
+    }
+
+    #[test]
+    fn test_edges_closed() {
+        let polygon = Polygon::triangle(0, 1, 2);
+        let edges: Vec<(usize, usize)> = polygon.edges().collect();
+        assert_eq!(edges, vec![(0, 1), (1, 2), (2, 0)]);
+    }
+
+    #[test]
+    fn test_area_centroid_and_flip() {
+        // L-образный шестиугольник из трёх единичных квадратов
+        let vertexes = vec![
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(2.0, 0.0, 1.0),
+            Point3::new(2.0, 1.0, 1.0),
+            Point3::new(1.0, 1.0, 1.0),
+            Point3::new(1.0, 2.0, 1.0),
+            Point3::new(0.0, 2.0, 1.0),
+        ];
+        let mesh = Mesh::from_polygons(vertexes, vec![Polygon::from_list(&[0, 1, 2, 3, 4, 5])]);
+        let mut polygon = mesh.get_polygon(0).clone();
+
+        assert!((polygon.area(&mesh) - 3.0).abs() < 1e-6);
+        let centroid = polygon.centroid(&mesh);
+        assert!(centroid.approx_equal(Point3::new(5.0 / 6.0, 5.0 / 6.0, 1.0), 1e-5));
+        assert!(!polygon.is_convex(&mesh));
+
+        // после смены обхода площадь и центр не меняются, а нормаль разворачивается
+        let normal = polygon.plane_normal(&mesh, None);
+        polygon.flip();
+        assert_eq!(polygon.get_mesh_vertex_index(0), 5);
+        assert!((polygon.area(&mesh) - 3.0).abs() < 1e-6);
+        assert!(polygon.centroid(&mesh).approx_equal(centroid, 1e-5));
+        assert!(
+            polygon
+                .plane_normal(&mesh, None)
+                .approx_equal(-normal, 1e-6)
+        );
+    }
+
+    #[test]
+    fn test_is_convex() {
+        let vertexes = vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            // выведенная из плоскости вершина
+            Point3::new(0.0, 1.0, 0.5),
+            // вдавленная внутрь квадрата вершина
+            Point3::new(0.3, 0.3, 0.0),
+        ];
+        let mesh = Mesh::from_polygons(
+            vertexes,
+            vec![
+                Polygon::from_list(&[0, 1, 2, 3]),
+                Polygon::from_list(&[3, 2, 1, 0]),
+                Polygon::from_list(&[0, 1, 2, 4]),
+                Polygon::triangle(0, 1, 2),
+                Polygon::from_list(&[0, 1, 5, 3]),
+            ],
+        );
+        assert!(mesh.get_polygon(0).is_convex(&mesh));
+        assert!(mesh.get_polygon(1).is_convex(&mesh));
+        assert!(!mesh.get_polygon(2).is_convex(&mesh));
+        assert!(mesh.get_polygon(3).is_convex(&mesh));
+        // заполняются билинейно только плоские выпуклые четырёхугольники
+        assert!(mesh.get_polygon(0).is_convex_quad(&mesh));
+        assert!(mesh.get_polygon(1).is_convex_quad(&mesh));
+        assert!(!mesh.get_polygon(2).is_planar(&mesh));
+        assert!(!mesh.get_polygon(2).is_convex_quad(&mesh));
+        assert!(!mesh.get_polygon(3).is_convex_quad(&mesh));
+        assert!(mesh.get_polygon(4).is_planar(&mesh));
+        assert!(!mesh.get_polygon(4).is_convex_quad(&mesh));
+        assert!(!Polygon::from_list(&[0, 1]).is_convex(&mesh));
+    }
+
+    #[test]
+    fn test_degenerate_polygon() {
+        let polygon = Polygon::from_list(&[3]);
+        assert_eq!(polygon.triangles().count(), 0);
+        assert_eq!(polygon.edges().count(), 0);
+    }
+}
@@ -0,0 +1,463 @@
+//! Обработка геометрии Mesh'а: сглаживание и шум.
+
+use std::collections::HashMap;
+
+use super::Mesh;
+use crate::{Axis, Handedness, Point3, Transform3D, UVec3, Vec3};
+
+impl Mesh {
+    // --------------------------------------------------
+    // Обработка геометрии
+    // --------------------------------------------------
+
+    /// Сгладить Mesh лапласовским сглаживанием.
+    ///
+    /// За одну итерацию каждая вершина сдвигается на долю `lambda` к среднему своих соседей
+    /// по рёбрам. `lambda` из `(0, 1]`, большие значения сглаживают быстрее, но сильнее сжимают
+    /// модель. Вершины с совпадающими координатами (например, на швах текстуры) двигаются
+    /// вместе, поэтому Mesh не расходится по швам. Если у Mesh'а были нормали, то они
+    /// пересчитываются.
+    pub fn smooth_laplacian(&mut self, iterations: usize, lambda: f32) {
+        debug_assert!(
+            0.0 < lambda && lambda <= 1.0,
+            "коэффициент сглаживания {} должен быть в (0, 1]",
+            lambda
+        );
+
+        let (groups, mut positions) = self.position_groups();
+        let mut neighbors: Vec<Vec<usize>> = vec![Vec::new(); positions.len()];
+        for (a, b) in self.edge_list() {
+            let (a, b) = (groups[a], groups[b]);
+            if a != b {
+                neighbors[a].push(b);
+                neighbors[b].push(a);
+            }
+        }
+        for list in &mut neighbors {
+            list.sort_unstable();
+            list.dedup();
+        }
+
+        for _ in 0..iterations {
+            let previous = positions.clone();
+            for (group, list) in neighbors.iter().enumerate() {
+                if list.is_empty() {
+                    continue;
+                }
+                let sum = list.iter().fold(Vec3::zero(), |acc, &neighbor| {
+                    acc + Vec3::from(previous[neighbor])
+                });
+                let average = Point3::from(sum / list.len() as f32);
+                positions[group] = previous[group] + (average - previous[group]) * lambda;
+            }
+        }
+
+        for (vertex, &group) in self.vertexes.iter_mut().zip(&groups) {
+            *vertex = positions[group];
+        }
+        if self.has_normals() {
+            self.generate_normals();
+        }
+    }
+
+    /// Сместить вершины по гладкому шуму.
+    ///
+    /// Вершины сдвигаются вдоль нормали (если нормалей нет, то вдоль направления от центра
+    /// Mesh'а) на величину из `[-amplitude, amplitude]`. `frequency` задаёт, сколько "волн" шума
+    /// приходится на единицу длины, `seed` - конкретную реализацию шума: одинаковый `seed`
+    /// даёт одинаковый результат. Вершины с совпадающими координатами смещаются одинаково.
+    /// Если у Mesh'а были нормали, то они пересчитываются.
+    pub fn displace_noise(&mut self, amplitude: f32, frequency: f32, seed: u32) {
+        let (groups, positions) = self.position_groups();
+
+        // направление смещения каждой группы - среднее нормалей её вершин
+        let mut directions = vec![Vec3::zero(); positions.len()];
+        match &self.normals {
+            Some(normals) => {
+                for (normal, &group) in normals.iter().zip(&groups) {
+                    directions[group] += *normal;
+                }
+            }
+            None => {
+                let center = crate::library::utils::calculate_center(&self.vertexes);
+                for (direction, &position) in directions.iter_mut().zip(&positions) {
+                    *direction = position - center;
+                }
+            }
+        }
+
+        let offsets: Vec<Vec3> = positions
+            .iter()
+            .zip(&directions)
+            .map(|(&position, &direction)| {
+                let Ok(direction) = direction.normalize() else {
+                    return Vec3::zero();
+                };
+                let noise =
+                    crate::library::utils::value_noise(Vec3::from(position) * frequency, seed);
+                Vec3::from(direction) * (noise * amplitude)
+            })
+            .collect();
+
+        for (vertex, &group) in self.vertexes.iter_mut().zip(&groups) {
+            *vertex += offsets[group];
+        }
+        if self.has_normals() {
+            self.generate_normals();
+        }
+    }
+
+    /// Применить преобразование `transform` к вершинам Mesh'а в **локальных** координатах.
+    ///
+    /// Нормали преобразуются обратной транспонированной матрицей, поэтому остаются
+    /// перпендикулярными поверхности и при неравномерном масштабе. Смещения форм
+    /// преобразуются вместе с Mesh'ем. Локальная система координат не меняется.
+    pub fn apply_transform(&mut self, transform: Transform3D) {
+        // нормали - направления, поэтому матрица нормалей строится без перемещения
+        let mut linear = transform;
+        linear.m[12..15].fill(0.0);
+        let inverse = linear.inverse();
+        // вырожденное преобразование не переводит нормали, они пересчитываются по граням
+        let normal_transform = inverse.map_or(linear, Transform3D::transpose);
+
+        for vertex in &mut self.vertexes {
+            *vertex = vertex.apply_transform(transform).unwrap_or(*vertex);
+        }
+        if let Some(normals) = &mut self.normals {
+            if inverse.is_some() {
+                for normal in normals.iter_mut() {
+                    *normal = normal.apply_transform(normal_transform).unwrap_or(*normal);
+                }
+            } else {
+                #[cfg(debug_assertions)]
+                eprintln!("Warning: вырожденное преобразование, нормали пересчитаны по граням");
+                self.generate_normals();
+            }
+        }
+        for target in &mut self.morph_targets {
+            target.apply_transform(transform, normal_transform);
+        }
+    }
+
+    /// Перевести Mesh из системы координат с ориентацией `from` в систему с ориентацией `to`.
+    ///
+    /// При смене ориентации ось z **локальных** координат отражается: вершины, нормали и
+    /// смещения форм меняют знак z, а порядок обхода вершин полигонов меняется на обратный,
+    /// чтобы лицевые грани остались лицевыми. Если ориентации совпадают, Mesh не меняется.
+    pub fn convert_handedness(&mut self, from: Handedness, to: Handedness) {
+        if from == to {
+            return;
+        }
+        self.apply_transform(Transform3D::scale(1.0, 1.0, -1.0));
+        for polygon in &mut self.polygons {
+            polygon.flip();
+        }
+    }
+
+    /// Перевести Mesh в другие единицы измерения, умножив **локальные** координаты на `scale`.
+    ///
+    /// Например, `0.001` переводит модель в миллиметрах в метры. Нормали не меняются,
+    /// смещения форм масштабируются вместе с вершинами.
+    pub fn convert_units(&mut self, scale: f32) {
+        debug_assert!(
+            scale > 0.0 && scale.is_finite(),
+            "масштаб единиц {} должен быть положительным",
+            scale
+        );
+        self.apply_transform(Transform3D::scale(scale, scale, scale));
+    }
+
+    /// Повернуть Mesh так, чтобы ось `from`, направленная вверх, совпала с осью `to`.
+    ///
+    /// Например, `convert_up_axis(Axis::Z, Axis::Y)` переводит модель с осью z вверх в систему
+    /// библиотеки, где вверх направлена ось y. Это поворот на 90° вокруг третьей оси, поэтому
+    /// ориентация системы координат (handedness) и порядок обхода полигонов сохраняются.
+    pub fn convert_up_axis(&mut self, from: Axis, to: Axis) {
+        if from == to {
+            return;
+        }
+        self.apply_transform(Transform3D::rotation_aligning(
+            from.unit_vector(),
+            to.unit_vector(),
+        ));
+    }
+
+    /// Копия Mesh'а с другими атрибутами вершин.
+    ///
+    /// Полигоны, локальная система координат и привязка к скелету сохраняются. Количество
+    /// вершин должно совпадать с исходным. Если `normals` или `texture_coords` равны `None`,
+    /// то у копии этих атрибутов не будет (без `texture_coords` пропадают и UV-координаты углов).
+    pub fn with_vertex_attributes(
+        &self,
+        vertexes: Vec<Point3>,
+        normals: Option<Vec<UVec3>>,
+        texture_coords: Option<Vec<(f32, f32)>>,
+    ) -> Mesh {
+        assert_eq!(
+            self.vertexes.len(),
+            vertexes.len(),
+            "Количество вершин должно совпадать с количеством вершин Mesh'а"
+        );
+        #[cfg(debug_assertions)]
+        {
+            if let Some(normals) = &normals {
+                Self::assert_normals(&vertexes, normals);
+            }
+            if let Some(texture_coords) = &texture_coords {
+                Self::assert_texture(&vertexes, texture_coords);
+            }
+        }
+
+        let mut mesh = self.clone();
+        mesh.vertexes = vertexes;
+        mesh.normals = normals;
+        if texture_coords.is_none() {
+            mesh.clear_corner_texture_coords();
+        }
+        mesh.texture_coords = texture_coords;
+        mesh
+    }
+
+    /// Раздвинуть связные компоненты Mesh'а (см. [`Mesh::connected_components`]) от его центра.
+    ///
+    /// Каждая компонента сдвигается целиком на `factor`, умноженный на смещение её центра
+    /// от центра всего Mesh'а (центры - средние вершин в **локальных** координатах).
+    /// При `factor = 0` Mesh не меняется. Удобно для разнесённого вида сборок,
+    /// объединённых в один Mesh.
+    pub fn explode_components(&mut self, factor: f32) {
+        let (components, count) = self.connected_components();
+        if count < 2 {
+            return;
+        }
+
+        let mut sums = vec![(Vec3::zero(), 0usize); count];
+        for (vertex, &component) in self.vertexes.iter().zip(&components) {
+            sums[component].0 += Vec3::from(*vertex);
+            sums[component].1 += 1;
+        }
+        let center = crate::library::utils::calculate_center(&self.vertexes);
+        let offsets: Vec<Vec3> = sums
+            .into_iter()
+            .map(|(sum, n)| (Point3::from(sum / n as f32) - center) * factor)
+            .collect();
+        for (vertex, &component) in self.vertexes.iter_mut().zip(&components) {
+            *vertex += offsets[component];
+        }
+    }
+
+    /// Сгруппировать вершины с одинаковыми координатами.
+    ///
+    /// Возвращает номер группы каждой вершины и координаты групп.
+    pub(super) fn position_groups(&self) -> (Vec<usize>, Vec<Point3>) {
+        let mut positions = Vec::new();
+        let mut index: HashMap<[u32; 3], usize> = HashMap::new();
+        let groups = self
+            .vertexes
+            .iter()
+            .map(|vertex| {
+                let key = [vertex.x.to_bits(), vertex.y.to_bits(), vertex.z.to_bits()];
+                *index.entry(key).or_insert_with(|| {
+                    positions.push(*vertex);
+                    positions.len() - 1
+                })
+            })
+            .collect();
+        (groups, positions)
+    }
+}
+
+#[cfg(test)]
+mod processing_tests {
+    use super::*;
+    use crate::{Polygon, SurfaceFunction};
+
+    #[test]
+    fn test_smooth_laplacian_flattens_noise() {
+        let mut mesh =
+            SurfaceFunction::Wave.generate_surface_mesh((-3.0, 3.0), (-3.0, 3.0), (30, 30));
+        let roughness = |mesh: &Mesh| -> f32 {
+            // сумма квадратов разностей высот соседних вершин
+            mesh.edge_list()
+                .into_iter()
+                .map(|(a, b)| (mesh.get_local_vertex(a).z - mesh.get_local_vertex(b).z).powi(2))
+                .sum()
+        };
+
+        mesh.displace_noise(0.3, 4.0, 7);
+        let noisy = roughness(&mesh);
+        mesh.smooth_laplacian(5, 0.5);
+        let smoothed = roughness(&mesh);
+        assert!(
+            smoothed < noisy * 0.5,
+            "сглаживание должно уменьшить шероховатость: было {}, стало {}",
+            noisy,
+            smoothed
+        );
+        assert!(mesh.has_normals());
+    }
+
+    #[test]
+    fn test_displace_noise_deterministic_and_bounded() {
+        let original = Mesh::icosahedron();
+        let mut a = original.clone();
+        let mut b = original.clone();
+        a.displace_noise(0.1, 3.0, 42);
+        b.displace_noise(0.1, 3.0, 42);
+
+        let mut moved = false;
+        for i in 0..original.vertex_count() {
+            assert!(
+                a.get_local_vertex(i)
+                    .approx_equal(b.get_local_vertex(i), 1e-6)
+            );
+            let offset = (a.get_local_vertex(i) - original.get_local_vertex(i)).length();
+            assert!(offset <= 0.1 + 1e-5, "смещение {} больше амплитуды", offset);
+            moved |= offset > 1e-4;
+        }
+        assert!(moved, "шум должен сдвинуть хотя бы одну вершину");
+    }
+
+    #[test]
+    fn test_processing_keeps_seams_closed() {
+        // у куба вершины дублируются по граням, после обработки они должны совпадать
+        let mut mesh = Mesh::hexahedron();
+        mesh.smooth_laplacian(2, 0.5);
+        mesh.displace_noise(0.2, 2.0, 1);
+
+        let (groups, _) = Mesh::hexahedron().position_groups();
+        for i in 0..mesh.vertex_count() {
+            for j in 0..mesh.vertex_count() {
+                if groups[i] == groups[j] {
+                    assert!(
+                        mesh.get_local_vertex(i)
+                            .approx_equal(mesh.get_local_vertex(j), 1e-6)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_convert_units_and_up_axis() {
+        let mut mesh = Mesh::from_polygons(
+            vec![
+                Point3::new(0.0, 0.0, 0.0),
+                Point3::new(1000.0, 0.0, 0.0),
+                Point3::new(0.0, 0.0, 2000.0),
+            ],
+            vec![Polygon::triangle(0, 1, 2)],
+        );
+        let normal = mesh.get_local_normal(0).unwrap();
+
+        // модель с осью z вверх в миллиметрах
+        mesh.convert_up_axis(Axis::Z, Axis::Y);
+        mesh.convert_units(0.001);
+        assert!(
+            mesh.get_local_vertex(1)
+                .approx_equal(Point3::new(1.0, 0.0, 0.0), 1e-5)
+        );
+        assert!(
+            mesh.get_local_vertex(2)
+                .approx_equal(Point3::new(0.0, 2.0, 0.0), 1e-5)
+        );
+        // нормаль поворачивается вместе с Mesh'ем и остаётся единичной
+        let expected = UVec3::new(normal.x, normal.z, -normal.y);
+        assert!(
+            mesh.get_local_normal(0)
+                .unwrap()
+                .approx_equal(expected, 1e-5)
+        );
+
+        let before = mesh.clone();
+        mesh.convert_up_axis(Axis::X, Axis::X);
+        assert_eq!(mesh.get_local_vertex(2), before.get_local_vertex(2));
+    }
+
+    #[test]
+    fn test_explode_components() {
+        let mut right = Mesh::hexahedron();
+        right.apply_transform(Transform3D::translation(2.0, 0.0, 0.0));
+        let mut mesh = Mesh::merge(&[&Mesh::hexahedron(), &right], false);
+        let original: Vec<Point3> = mesh.get_local_vertex_iter().collect();
+
+        mesh.explode_components(0.5);
+        for (vertex, original) in mesh.get_local_vertex_iter().zip(original) {
+            // центры кубов в 0 и 2, центр Mesh'а в 1
+            let shift = if original.x > 1.0 { 0.5 } else { -0.5 };
+            assert!(
+                vertex.approx_equal(original + Vec3::new(shift, 0.0, 0.0), 1e-5),
+                "{:?} -> {:?}",
+                original,
+                vertex
+            );
+        }
+    }
+
+    #[test]
+    fn test_convert_handedness() {
+        let original = Mesh::hexahedron();
+        let mut mesh = original.clone();
+        mesh.convert_handedness(Handedness::LeftHanded, Handedness::LeftHanded);
+        assert_eq!(mesh.get_local_vertex(1), original.get_local_vertex(1));
+
+        mesh.convert_handedness(Handedness::RightHanded, Handedness::LeftHanded);
+        let vertex = original.get_local_vertex(1);
+        assert_eq!(
+            mesh.get_local_vertex(1),
+            Point3::new(vertex.x, vertex.y, -vertex.z)
+        );
+        let normal = original.get_local_normal(1).unwrap();
+        assert!(
+            mesh.get_local_normal(1)
+                .unwrap()
+                .approx_equal(UVec3::new(normal.x, normal.y, -normal.z), 1e-6)
+        );
+
+        let center = Point3::zero();
+        for (polygon, original_polygon) in mesh.get_polygon_iter().zip(original.get_polygon_iter())
+        {
+            // обход обратный, а UV-координаты остаются у тех же углов
+            let count = polygon.vertex_count();
+            for i in 0..count {
+                assert_eq!(
+                    polygon.get_mesh_vertex_index(i),
+                    original_polygon.get_mesh_vertex_index(count - 1 - i)
+                );
+                assert_eq!(
+                    polygon.get_texture_coord(&mesh, i),
+                    original_polygon.get_texture_coord(&original, count - 1 - i)
+                );
+            }
+
+            // лицевая сторона по обходу остаётся снаружи
+            let winding_side = |mesh: &Mesh, polygon: &Polygon| {
+                let [a, b, c] = [0, 1, 2].map(|i| polygon.get_local_vertex(mesh, i));
+                let outward = polygon
+                    .get_local_vertex_iter(mesh)
+                    .fold(Vec3::zero(), |sum, p| sum + (p - center));
+                (b - a).cross(c - a).dot(outward).signum()
+            };
+            assert_eq!(
+                winding_side(&mesh, polygon),
+                winding_side(&original, original_polygon)
+            );
+        }
+
+        // обратный перевод возвращает исходный Mesh
+        mesh.convert_handedness(Handedness::LeftHanded, Handedness::RightHanded);
+        for i in 0..mesh.vertex_count() {
+            assert!(
+                mesh.get_local_vertex(i)
+                    .approx_equal(original.get_local_vertex(i), 1e-6)
+            );
+        }
+        for (polygon, original_polygon) in mesh.get_polygon_iter().zip(original.get_polygon_iter())
+        {
+            assert!(
+                polygon
+                    .get_mesh_vertex_index_iter()
+                    .eq(original_polygon.get_mesh_vertex_index_iter())
+            );
+        }
+    }
+}
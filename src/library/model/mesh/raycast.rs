@@ -0,0 +1,193 @@
+//! Пересечение лучей с Mesh'ем.
+
+use super::Mesh;
+use crate::{Line3, Point3, UVec3, Vec3, library::utils};
+
+/// Точность, с которой луч считается параллельным треугольнику.
+const PARALLEL_EPSILON: f32 = 1e-7;
+/// Допуск барицентрических координат, чтобы луч не проскакивал по общему ребру треугольников.
+const EDGE_EPSILON: f32 = 1e-6;
+
+/// Результат пересечения луча с Mesh'ем.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit {
+    /// Расстояние от начала луча до точки пересечения.
+    pub distance: f32,
+    /// Точка пересечения в **глобальных** координатах.
+    pub point: Point3,
+    /// Нормаль полигона в точке пересечения в **глобальных** координатах.
+    pub normal: UVec3,
+    /// Индекс пересечённого полигона в Mesh'е.
+    pub polygon_index: usize,
+}
+
+impl Mesh {
+    // --------------------------------------------------
+    // Пересечение с лучами
+    // --------------------------------------------------
+
+    /// Найти ближайшее пересечение луча с Mesh'ем.
+    ///
+    /// Луч задаётся прямой `ray`, заданной в **глобальных** координатах, и идёт от `ray.origin`
+    /// в направлении `ray.direction`. Учитываются обе стороны полигонов.
+    pub fn raycast(&self, ray: &Line3) -> Option<RayHit> {
+        let vertexes: Vec<Point3> = self.get_global_vertex_iter().collect();
+
+        let mut nearest: Option<(f32, usize)> = None;
+        for (polygon_index, polygon) in self.polygons.iter().enumerate() {
+            for [a, b, c] in polygon.triangles() {
+                let Some((distance, _, _)) =
+                    intersect_triangle(ray, vertexes[a], vertexes[b], vertexes[c])
+                else {
+                    continue;
+                };
+                if nearest.is_none_or(|(nearest_distance, _)| distance < nearest_distance) {
+                    nearest = Some((distance, polygon_index));
+                }
+            }
+        }
+
+        let (distance, polygon_index) = nearest?;
+        Some(RayHit {
+            distance,
+            point: ray.origin + ray.direction * distance,
+            normal: self.get_global_polygon_normal(polygon_index)?,
+            polygon_index,
+        })
+    }
+
+    /// Нормаль `i`-го полигона в **глобальных** координатах.
+    ///
+    /// Направление нормали согласуется с нормалями вершин, если они есть, иначе
+    /// определяется порядком обхода вершин. Для вырожденного полигона возвращает `None`.
+    pub fn get_global_polygon_normal(&self, i: usize) -> Option<UVec3> {
+        let indexes: Vec<usize> = self.polygons[i].get_mesh_vertex_index_iter().collect();
+        if indexes.len() < 3 {
+            return None;
+        }
+
+        // нормаль Ньюэлла устойчива и для невыпуклых многоугольников
+        let vertexes: Vec<Point3> = indexes
+            .iter()
+            .map(|&index| self.get_global_vertex(index))
+            .collect();
+        let mut normal = utils::newell_normal(&vertexes)?;
+
+        // согласовать с нормалями вершин
+        if self.has_normals() {
+            let vertex_normals = indexes
+                .iter()
+                .filter_map(|&index| self.get_global_normal(index))
+                .fold(Vec3::zero(), |acc, n| acc + n);
+            if Vec3::from(normal).dot(vertex_normals) < 0.0 {
+                normal = -normal;
+            }
+        }
+
+        Some(normal)
+    }
+
+    /// Центр `i`-го полигона (среднее его вершин) в **глобальных** координатах.
+    pub fn get_global_polygon_center(&self, i: usize) -> Point3 {
+        let polygon = &self.polygons[i];
+        let sum = polygon
+            .get_mesh_vertex_index_iter()
+            .map(|index| Vec3::from(self.get_global_vertex(index)))
+            .fold(Vec3::zero(), |acc, v| acc + v);
+        Point3::from(sum / polygon.vertex_count().max(1) as f32)
+    }
+}
+
+/// Пересечение луча с треугольником алгоритмом Моллера-Трумбора.
+///
+/// Возвращает расстояние от начала луча до точки пересечения, если она не позади луча,
+/// и барицентрические координаты `(u, v)` точки относительно вершин `b` и `c`.
+pub(crate) fn intersect_triangle(
+    ray: &Line3,
+    a: Point3,
+    b: Point3,
+    c: Point3,
+) -> Option<(f32, f32, f32)> {
+    let direction = Vec3::from(ray.direction);
+    let edge1 = b - a;
+    let edge2 = c - a;
+
+    let p = direction.cross(edge2);
+    let det = edge1.dot(p);
+    if det.abs() < PARALLEL_EPSILON {
+        return None;
+    }
+    let inv_det = 1.0 / det;
+
+    let s = ray.origin - a;
+    let u = s.dot(p) * inv_det;
+    if !(-EDGE_EPSILON..=1.0 + EDGE_EPSILON).contains(&u) {
+        return None;
+    }
+
+    let q = s.cross(edge1);
+    let v = direction.dot(q) * inv_det;
+    if v < -EDGE_EPSILON || u + v > 1.0 + EDGE_EPSILON {
+        return None;
+    }
+
+    let distance = edge2.dot(q) * inv_det;
+    (distance >= 0.0).then_some((distance, u, v))
+}
+
+#[cfg(test)]
+mod raycast_tests {
+    use super::*;
+
+    const TOLERANCE: f32 = 1e-5;
+
+    #[test]
+    fn test_raycast_hexahedron() {
+        let mesh = Mesh::hexahedron();
+        let ray = Line3::new(Point3::new(0.0, 10.0, 0.0), UVec3::down());
+
+        let hit = mesh.raycast(&ray).expect("луч должен пересечь куб");
+        assert!(
+            (hit.distance - 9.5).abs() < TOLERANCE,
+            "ожидалось расстояние 9.5, но получено {}",
+            hit.distance
+        );
+        assert!(
+            hit.point
+                .approx_equal(Point3::new(0.0, 0.5, 0.0), TOLERANCE)
+        );
+        assert!(
+            hit.normal.approx_equal(UVec3::up(), TOLERANCE),
+            "нормаль верхней грани должна быть направлена вверх, но получено {:?}",
+            hit.normal
+        );
+    }
+
+    #[test]
+    fn test_raycast_miss_and_behind() {
+        let mesh = Mesh::hexahedron();
+
+        // мимо
+        let ray = Line3::new(Point3::new(5.0, 10.0, 0.0), UVec3::down());
+        assert!(mesh.raycast(&ray).is_none());
+
+        // куб позади луча
+        let ray = Line3::new(Point3::new(0.0, 10.0, 0.0), UVec3::up());
+        assert!(mesh.raycast(&ray).is_none());
+    }
+
+    #[test]
+    fn test_polygon_center() {
+        let mesh = Mesh::hexahedron();
+        for i in 0..mesh.polygon_count() {
+            let center = mesh.get_global_polygon_center(i);
+            let normal = mesh.get_global_polygon_normal(i).unwrap();
+            // центр грани куба лежит на расстоянии 0.5 от центра вдоль нормали
+            assert!(
+                (Vec3::from(center).dot(Vec3::from(normal)) - 0.5).abs() < TOLERANCE,
+                "нормаль грани {} должна быть направлена наружу",
+                i
+            );
+        }
+    }
+}
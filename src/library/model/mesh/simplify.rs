@@ -0,0 +1,459 @@
+//! Упрощение Mesh'а стягиванием рёбер по квадрикам ошибки (Garland-Heckbert).
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+use super::{Mesh, Polygon};
+use crate::{Point3, UVec3, Vec3};
+
+/// Вес квадрик граничных рёбер: чем больше, тем сильнее сохраняется контур открытых поверхностей.
+const BOUNDARY_WEIGHT: f64 = 1000.0;
+
+/// Квадрика ошибки - симметричная матрица 4x4 суммы квадратов расстояний до плоскостей.
+///
+/// Хранится только верхний треугольник: `a², ab, ac, ad, b², bc, bd, c², cd, d²`.
+#[derive(Debug, Clone, Copy, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    /// Квадрика плоскости `ax + by + cz + d = 0` с единичной нормалью `(a, b, c)`.
+    fn from_plane(normal: UVec3, point: Point3, weight: f64) -> Self {
+        let (a, b, c) = (normal.x as f64, normal.y as f64, normal.z as f64);
+        let d = -(a * point.x as f64 + b * point.y as f64 + c * point.z as f64);
+        Self(
+            [
+                a * a,
+                a * b,
+                a * c,
+                a * d,
+                b * b,
+                b * c,
+                b * d,
+                c * c,
+                c * d,
+                d * d,
+            ]
+            .map(|value| value * weight),
+        )
+    }
+
+    fn add(&mut self, other: &Quadric) {
+        for (value, other) in self.0.iter_mut().zip(other.0) {
+            *value += other;
+        }
+    }
+
+    /// Сумма квадратов расстояний от точки до плоскостей квадрики.
+    fn error(&self, p: Point3) -> f64 {
+        let [a2, ab, ac, ad, b2, bc, bd, c2, cd, d2] = self.0;
+        let (x, y, z) = (p.x as f64, p.y as f64, p.z as f64);
+        a2 * x * x
+            + 2.0 * ab * x * y
+            + 2.0 * ac * x * z
+            + 2.0 * ad * x
+            + b2 * y * y
+            + 2.0 * bc * y * z
+            + 2.0 * bd * y
+            + c2 * z * z
+            + 2.0 * cd * z
+            + d2
+    }
+}
+
+/// Кандидат на стягивание ребра `(a, b)` в вершину `a`.
+#[derive(Debug, Clone, Copy)]
+struct Collapse {
+    cost: f64,
+    a: usize,
+    b: usize,
+    /// Версии вершин на момент вычисления, устаревшие кандидаты пропускаются.
+    versions: (u32, u32),
+    /// Новое положение вершины.
+    position: Point3,
+    /// Параметр интерполяции атрибутов от вершины `a` к `b`.
+    t: f32,
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Collapse {
+    /// Обратный порядок по стоимости, чтобы `BinaryHeap` отдавал самое дешёвое ребро.
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+/// Состояние упрощения: треугольники, вершины и их квадрики.
+struct Simplifier {
+    positions: Vec<Point3>,
+    normals: Option<Vec<UVec3>>,
+    texture_coords: Option<Vec<(f32, f32)>>,
+    quadrics: Vec<Quadric>,
+    versions: Vec<u32>,
+    removed: Vec<bool>,
+    triangles: Vec<[usize; 3]>,
+    alive: Vec<bool>,
+    alive_count: usize,
+    /// Треугольники, в которые входит каждая вершина.
+    vertex_triangles: Vec<Vec<usize>>,
+    heap: BinaryHeap<Collapse>,
+}
+
+impl Simplifier {
+    fn new(mesh: &Mesh) -> Self {
+        let vertex_count = mesh.vertexes.len();
+        let triangles = mesh.triangle_list();
+
+        let mut vertex_triangles = vec![Vec::new(); vertex_count];
+        for (t, triangle) in triangles.iter().enumerate() {
+            for &index in triangle {
+                vertex_triangles[index].push(t);
+            }
+        }
+
+        let mut simplifier = Self {
+            positions: mesh.vertexes.clone(),
+            normals: mesh.normals.clone(),
+            texture_coords: mesh.texture_coords.clone(),
+            quadrics: vec![Quadric::default(); vertex_count],
+            versions: vec![0; vertex_count],
+            removed: vec![false; vertex_count],
+            alive: vec![true; triangles.len()],
+            alive_count: triangles.len(),
+            triangles,
+            vertex_triangles,
+            heap: BinaryHeap::new(),
+        };
+        simplifier.init_quadrics();
+        simplifier.init_heap();
+        simplifier
+    }
+
+    /// Нормаль треугольника, `None` для вырожденного.
+    fn triangle_normal(&self, [a, b, c]: [usize; 3]) -> Option<UVec3> {
+        let (a, b, c) = (self.positions[a], self.positions[b], self.positions[c]);
+        (b - a).cross(c - a).normalize().ok()
+    }
+
+    fn init_quadrics(&mut self) {
+        // (ребро, количество треугольников с ним, нормаль последнего такого треугольника)
+        let mut edges: HashMap<(usize, usize), (usize, UVec3)> = HashMap::new();
+        for t in 0..self.triangles.len() {
+            let triangle = self.triangles[t];
+            let Some(normal) = self.triangle_normal(triangle) else {
+                continue;
+            };
+
+            let plane = Quadric::from_plane(normal, self.positions[triangle[0]], 1.0);
+            for k in 0..3 {
+                self.quadrics[triangle[k]].add(&plane);
+
+                let (a, b) = (triangle[k], triangle[(k + 1) % 3]);
+                let entry = edges.entry((a.min(b), a.max(b))).or_insert((0, normal));
+                entry.0 += 1;
+            }
+        }
+
+        // граничные рёбра удерживаются плоскостями, перпендикулярными граням
+        for ((a, b), (count, normal)) in edges {
+            if count != 1 {
+                continue;
+            }
+            let edge = self.positions[b] - self.positions[a];
+            let Ok(side) = edge.cross(Vec3::from(normal)).normalize() else {
+                continue;
+            };
+            let plane = Quadric::from_plane(side, self.positions[a], BOUNDARY_WEIGHT);
+            self.quadrics[a].add(&plane);
+            self.quadrics[b].add(&plane);
+        }
+    }
+
+    fn init_heap(&mut self) {
+        let mut edges: Vec<(usize, usize)> = self
+            .triangles
+            .iter()
+            .flat_map(|&[a, b, c]| [(a, b), (b, c), (c, a)])
+            .filter(|(a, b)| a != b)
+            .map(|(a, b)| (a.min(b), a.max(b)))
+            .collect();
+        edges.sort_unstable();
+        edges.dedup();
+
+        for (a, b) in edges {
+            self.push_candidate(a, b);
+        }
+    }
+
+    /// Вычислить оптимальное стягивание ребра `(a, b)` среди концов и середины ребра.
+    fn push_candidate(&mut self, a: usize, b: usize) {
+        let mut quadric = self.quadrics[a];
+        quadric.add(&self.quadrics[b]);
+
+        let (pa, pb) = (self.positions[a], self.positions[b]);
+        let middle = Point3::from((Vec3::from(pa) + Vec3::from(pb)) * 0.5);
+        let (position, t) = [(pa, 0.0), (pb, 1.0), (middle, 0.5)]
+            .into_iter()
+            .min_by(|(p1, _), (p2, _)| quadric.error(*p1).total_cmp(&quadric.error(*p2)))
+            .unwrap();
+
+        self.heap.push(Collapse {
+            cost: quadric.error(position),
+            a,
+            b,
+            versions: (self.versions[a], self.versions[b]),
+            position,
+            t,
+        });
+    }
+
+    /// Перевернётся ли какой-нибудь из оставшихся треугольников при стягивании.
+    fn flips(&self, collapse: &Collapse) -> bool {
+        for &vertex in &[collapse.a, collapse.b] {
+            for &t in &self.vertex_triangles[vertex] {
+                let triangle = self.triangles[t];
+                if !self.alive[t]
+                    || (triangle.contains(&collapse.a) && triangle.contains(&collapse.b))
+                {
+                    continue;
+                }
+                let Some(before) = self.triangle_normal(triangle) else {
+                    continue;
+                };
+
+                let moved = triangle.map(|index| {
+                    if index == vertex {
+                        collapse.position
+                    } else {
+                        self.positions[index]
+                    }
+                });
+                let after = (moved[1] - moved[0]).cross(moved[2] - moved[0]);
+                if Vec3::from(before).dot(after) <= 0.0 {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// Стянуть ребро: вершина `b` удаляется, вершина `a` переносится в новое положение.
+    fn collapse(&mut self, collapse: &Collapse) {
+        let (a, b, t) = (collapse.a, collapse.b, collapse.t);
+
+        self.positions[a] = collapse.position;
+        if let Some(normals) = &mut self.normals {
+            let blended = Vec3::from(normals[a]) * (1.0 - t) + Vec3::from(normals[b]) * t;
+            normals[a] = blended.normalize().unwrap_or(normals[a]);
+        }
+        if let Some(coords) = &mut self.texture_coords {
+            let (ua, va) = coords[a];
+            let (ub, vb) = coords[b];
+            coords[a] = (ua + (ub - ua) * t, va + (vb - va) * t);
+        }
+        let quadric = self.quadrics[b];
+        self.quadrics[a].add(&quadric);
+        self.removed[b] = true;
+        self.versions[a] += 1;
+
+        for triangle_index in std::mem::take(&mut self.vertex_triangles[b]) {
+            if !self.alive[triangle_index] {
+                continue;
+            }
+            let triangle = &mut self.triangles[triangle_index];
+            for index in triangle.iter_mut() {
+                if *index == b {
+                    *index = a;
+                }
+            }
+            let [i, j, k] = *triangle;
+            if i == j || j == k || k == i {
+                self.alive[triangle_index] = false;
+                self.alive_count -= 1;
+            } else {
+                self.vertex_triangles[a].push(triangle_index);
+            }
+        }
+        let alive = &self.alive;
+        self.vertex_triangles[a].retain(|&t| alive[t]);
+
+        // пересчитать стоимость всех рёбер, выходящих из a
+        let mut neighbors: Vec<usize> = self.vertex_triangles[a]
+            .iter()
+            .flat_map(|&t| self.triangles[t])
+            .filter(|&index| index != a)
+            .collect();
+        neighbors.sort_unstable();
+        neighbors.dedup();
+        for neighbor in neighbors {
+            self.push_candidate(a, neighbor);
+        }
+    }
+
+    fn run(&mut self, target_triangles: usize) {
+        while self.alive_count > target_triangles {
+            let Some(collapse) = self.heap.pop() else {
+                break;
+            };
+            let (a, b) = (collapse.a, collapse.b);
+            if self.removed[a]
+                || self.removed[b]
+                || (self.versions[a], self.versions[b]) != collapse.versions
+                || self.flips(&collapse)
+            {
+                continue;
+            }
+            self.collapse(&collapse);
+        }
+    }
+
+    /// Собрать Mesh из оставшихся треугольников и используемых ими вершин.
+    fn build(self, source: &Mesh) -> Mesh {
+        let mut remap: Vec<Option<usize>> = vec![None; self.positions.len()];
+        let mut vertexes = Vec::new();
+        let mut normals = self.normals.as_ref().map(|_| Vec::new());
+        let mut texture_coords = self.texture_coords.as_ref().map(|_| Vec::new());
+
+        let mut polygons = Vec::with_capacity(self.alive_count);
+        for (triangle, _) in self
+            .triangles
+            .iter()
+            .zip(&self.alive)
+            .filter(|(_, alive)| **alive)
+        {
+            let [a, b, c] = triangle.map(|index| {
+                *remap[index].get_or_insert_with(|| {
+                    vertexes.push(self.positions[index]);
+                    if let (Some(normals), Some(source)) = (&mut normals, &self.normals) {
+                        normals.push(source[index]);
+                    }
+                    if let (Some(coords), Some(source)) =
+                        (&mut texture_coords, &self.texture_coords)
+                    {
+                        coords.push(source[index]);
+                    }
+                    vertexes.len() - 1
+                })
+            });
+            polygons.push(Polygon::triangle(a, b, c));
+        }
+
+        let mut mesh = Mesh::new(vertexes, polygons, normals, texture_coords);
+        mesh.local_frame = source.local_frame;
+        mesh
+    }
+}
+
+impl Mesh {
+    // --------------------------------------------------
+    // Упрощение
+    // --------------------------------------------------
+
+    /// Получить упрощённую копию Mesh'а, в которой осталась доля `target_ratio` треугольников.
+    ///
+    /// Рёбра стягиваются по возрастанию квадрики ошибки (сумма квадратов расстояний до плоскостей
+    /// исходных граней), поэтому плоские участки упрощаются в первую очередь, а острые рёбра и
+    /// контур открытых поверхностей сохраняются дольше. Стягивания, переворачивающие грани,
+    /// пропускаются, так что треугольников может остаться больше, чем запрошено.
+    ///
+    /// Все полигоны результата - треугольники. Нормали и текстурные координаты интерполируются,
+    /// привязка к скелету и морф-таргеты не переносятся. UV-координаты углов тоже не переносятся:
+    /// используются усреднённые UV-координаты вершин, так что перед упрощением текстурированного
+    /// Mesh'а со швами стоит разрезать швы через [`Mesh::split_texture_seams`].
+    pub fn simplify(&self, target_ratio: f32) -> Mesh {
+        let target_ratio = target_ratio.clamp(0.0, 1.0);
+        let mut simplifier = Simplifier::new(self);
+        let target_triangles = (simplifier.triangles.len() as f32 * target_ratio).round() as usize;
+        simplifier.run(target_triangles);
+        simplifier.build(self)
+    }
+}
+
+#[cfg(test)]
+mod simplify_tests {
+    use super::*;
+    use crate::SurfaceFunction;
+
+    fn plane_grid(divisions: usize) -> Mesh {
+        let mut vertexes = Vec::new();
+        for i in 0..=divisions {
+            for j in 0..=divisions {
+                vertexes.push(Point3::new(i as f32, 0.0, j as f32));
+            }
+        }
+        let row = divisions + 1;
+        let mut polygons = Vec::new();
+        for i in 0..divisions {
+            for j in 0..divisions {
+                let a = i * row + j;
+                polygons.push(Polygon::from_list(&[a, a + 1, a + row + 1, a + row]));
+            }
+        }
+        Mesh::from_polygons(vertexes, polygons)
+    }
+
+    #[test]
+    fn test_simplify_plane_keeps_shape() {
+        let mesh = plane_grid(10);
+        let simplified = mesh.simplify(0.1);
+
+        let original = mesh.triangle_list().len();
+        let triangles = simplified.triangle_list().len();
+        assert!(
+            triangles <= original / 5,
+            "ожидалось сильное упрощение плоскости, но осталось {} из {} треугольников",
+            triangles,
+            original
+        );
+
+        // все вершины остаются в плоскости и внутри исходного квадрата
+        for vertex in simplified.get_local_vertex_iter() {
+            assert!(
+                vertex.y.abs() < 1e-4,
+                "вершина {} вышла из плоскости",
+                vertex
+            );
+            assert!((-1e-4..=10.0001).contains(&vertex.x) && (-1e-4..=10.0001).contains(&vertex.z));
+        }
+        // углы квадрата сохраняются благодаря граничным квадрикам
+        let (min, max) =
+            crate::library::utils::calculate_bounds(&simplified.get_local_vertex_iter().collect());
+        assert!(min.approx_equal(Point3::new(0.0, 0.0, 0.0), 1e-3));
+        assert!(max.approx_equal(Point3::new(10.0, 0.0, 10.0), 1e-3));
+    }
+
+    #[test]
+    fn test_simplify_ratio_bounds() {
+        let mesh = Mesh::hexahedron();
+        let same = mesh.simplify(1.0);
+        assert_eq!(same.triangle_list().len(), mesh.triangle_list().len());
+        assert!(same.has_normals() && same.has_texture_coords());
+
+        let surface =
+            SurfaceFunction::Paraboloid.generate_surface_mesh((-1.0, 1.0), (-1.0, 1.0), (30, 30));
+        let simplified = surface.simplify(0.25);
+        let (original, triangles) = (
+            surface.triangle_list().len(),
+            simplified.triangle_list().len(),
+        );
+        assert!(
+            triangles < original / 2,
+            "осталось {} из {} треугольников",
+            triangles,
+            original
+        );
+        assert!(simplified.vertex_count() < surface.vertex_count());
+    }
+}
@@ -0,0 +1,239 @@
+//! Привязка вершин Mesh'а к суставам скелета и линейный скиннинг.
+
+use super::Mesh;
+use crate::{Point3, Skeleton, Vec3};
+
+/// Максимальное количество суставов, влияющих на одну вершину.
+pub const MAX_BONE_INFLUENCES: usize = 4;
+
+/// Влияние суставов скелета на одну вершину.
+///
+/// Неиспользуемые слоты задаются нулевым весом. Веса не обязаны быть нормированы,
+/// при скиннинге они делятся на свою сумму.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoneWeights {
+    /// Индексы суставов в скелете.
+    pub bones: [usize; MAX_BONE_INFLUENCES],
+    /// Веса соответствующих суставов.
+    pub weights: [f32; MAX_BONE_INFLUENCES],
+}
+
+impl BoneWeights {
+    pub fn new(bones: [usize; MAX_BONE_INFLUENCES], weights: [f32; MAX_BONE_INFLUENCES]) -> Self {
+        Self { bones, weights }
+    }
+
+    /// Вершина, полностью привязанная к одному суставу.
+    pub fn single(bone: usize) -> Self {
+        Self::new([bone, 0, 0, 0], [1.0, 0.0, 0.0, 0.0])
+    }
+
+    /// Итератор по парам (сустав, вес) с ненулевым весом.
+    pub fn influences(&self) -> impl Iterator<Item = (usize, f32)> {
+        self.bones
+            .into_iter()
+            .zip(self.weights)
+            .filter(|(_, weight)| *weight != 0.0)
+    }
+}
+
+impl Mesh {
+    // --------------------------------------------------
+    // Скелетная анимация
+    // --------------------------------------------------
+
+    /// Привязать вершины к суставам скелета.
+    ///
+    /// Веса индексируются в том же порядке, что и вершины Mesh'а.
+    pub fn set_bone_weights(&mut self, bone_weights: Vec<BoneWeights>) {
+        assert_eq!(
+            self.vertexes.len(),
+            bone_weights.len(),
+            "Количество весов суставов должно совпадать с количеством вершин Mesh'а"
+        );
+        self.bone_weights = Some(bone_weights);
+    }
+
+    /// Убрать привязку вершин к суставам.
+    pub fn clear_bone_weights(&mut self) {
+        self.bone_weights = None;
+    }
+
+    /// Получить веса суставов i-ой вершины.
+    pub fn get_bone_weights(&self, i: usize) -> Option<BoneWeights> {
+        let bone_weights = self.bone_weights.as_ref()?;
+        bone_weights.get(i).copied()
+    }
+
+    /// Привязаны ли вершины к суставам?
+    pub fn has_bone_weights(&self) -> bool {
+        self.bone_weights.is_some()
+    }
+
+    /// Получить копию Mesh'а, деформированную текущей позой скелета.
+    ///
+    /// Используется линейный скиннинг (linear blend skinning) в **локальных** координатах Mesh'а:
+    /// вершина и нормаль преобразуются матрицей каждого влияющего сустава, результаты
+    /// смешиваются по весам. Вершины без привязки (или с нулевой суммой весов) не меняются.
+    /// В копии привязка к суставам сохраняется, так что её можно деформировать повторно.
+    pub fn skinned(&self, skeleton: &Skeleton) -> Mesh {
+        let Some(bone_weights) = &self.bone_weights else {
+            return self.clone();
+        };
+        let matrices = skeleton.skinning_matrices();
+
+        let mut mesh = self.clone();
+        for (i, weights) in bone_weights.iter().enumerate() {
+            let total: f32 = weights.influences().map(|(_, weight)| weight).sum();
+            if total.abs() < f32::EPSILON {
+                continue;
+            }
+
+            let vertex = self.vertexes[i];
+            let normal = self.get_local_normal(i);
+            let mut blended_vertex = Vec3::zero();
+            let mut blended_normal = Vec3::zero();
+            for (bone, weight) in weights.influences() {
+                let Some(&matrix) = matrices.get(bone) else {
+                    #[cfg(debug_assertions)]
+                    eprintln!(
+                        "Warning: вершина {} ссылается на несуществующий сустав {}",
+                        i, bone
+                    );
+                    continue;
+                };
+                let weight = weight / total;
+                if let Ok(point) = vertex.apply_transform(matrix) {
+                    blended_vertex += Vec3::from(point) * weight;
+                }
+                if let Some(Ok(n)) = normal.map(|n| Vec3::from(n).apply_transform(matrix)) {
+                    blended_normal += n * weight;
+                }
+            }
+
+            mesh.vertexes[i] = Point3::from(blended_vertex);
+            if let (Some(normals), Ok(n)) = (&mut mesh.normals, blended_normal.normalize()) {
+                normals[i] = n;
+            }
+        }
+        mesh
+    }
+}
+
+#[cfg(test)]
+mod skinning_tests {
+    use super::*;
+    use crate::Transform3D;
+
+    const TOLERANCE: f32 = 1e-5;
+
+    /// Куб, верхние вершины которого привязаны ко второму суставу, а нижние к первому.
+    fn rigged_cube() -> (Mesh, Skeleton) {
+        let mut mesh = Mesh::hexahedron();
+        let weights = mesh
+            .get_local_vertex_iter()
+            .map(|v| BoneWeights::single(if v.y > 0.0 { 1 } else { 0 }))
+            .collect();
+        mesh.set_bone_weights(weights);
+
+        let mut skeleton = Skeleton::new();
+        let root = skeleton.add_joint("root", None, Transform3D::identity());
+        skeleton.add_joint("top", Some(root), Transform3D::translation(0.0, 0.5, 0.0));
+        (mesh, skeleton)
+    }
+
+    #[test]
+    fn test_bind_pose_keeps_mesh() {
+        let (mesh, skeleton) = rigged_cube();
+        let skinned = mesh.skinned(&skeleton);
+        for i in 0..mesh.vertex_count() {
+            assert!(
+                skinned
+                    .get_local_vertex(i)
+                    .approx_equal(mesh.get_local_vertex(i), TOLERANCE),
+                "в исходной позе вершина {} не должна сдвигаться",
+                i
+            );
+        }
+    }
+
+    #[test]
+    fn test_pose_moves_bound_vertexes() {
+        let (mesh, mut skeleton) = rigged_cube();
+        skeleton.set_pose(1, Transform3D::translation(0.0, 1.5, 0.0));
+        let skinned = mesh.skinned(&skeleton);
+
+        for i in 0..mesh.vertex_count() {
+            let original = mesh.get_local_vertex(i);
+            let expected = if original.y > 0.0 {
+                Point3::new(original.x, original.y + 1.0, original.z)
+            } else {
+                original
+            };
+            assert!(
+                skinned
+                    .get_local_vertex(i)
+                    .approx_equal(expected, TOLERANCE),
+                "ожидалась вершина {}, но получено {}",
+                expected,
+                skinned.get_local_vertex(i)
+            );
+        }
+        assert!(skinned.has_bone_weights());
+    }
+
+    #[test]
+    fn test_blended_weights() {
+        let (mut mesh, mut skeleton) = rigged_cube();
+        let weights =
+            vec![BoneWeights::new([0, 1, 0, 0], [1.0, 1.0, 0.0, 0.0]); mesh.vertex_count()];
+        mesh.set_bone_weights(weights);
+        skeleton.set_pose(
+            1,
+            Transform3D::rotation_y_deg(180.0).multiply(Transform3D::translation(0.0, 0.5, 0.0)),
+        );
+
+        let skinned = mesh.skinned(&skeleton);
+        for i in 0..mesh.vertex_count() {
+            // половина веса у неподвижного сустава, половина у повёрнутого на 180 градусов
+            let original = mesh.get_local_vertex(i);
+            let expected = Point3::new(0.0, original.y, 0.0);
+            assert!(
+                skinned
+                    .get_local_vertex(i)
+                    .approx_equal(expected, TOLERANCE),
+                "ожидалась вершина {}, но получено {}",
+                expected,
+                skinned.get_local_vertex(i)
+            );
+        }
+    }
+
+    #[test]
+    fn test_normals_follow_rotation() {
+        let (mut mesh, mut skeleton) = rigged_cube();
+        mesh.set_bone_weights(vec![BoneWeights::single(1); mesh.vertex_count()]);
+        let rotation = Transform3D::rotation_y_deg(90.0);
+        skeleton.set_pose(
+            1,
+            rotation.multiply(Transform3D::translation(0.0, 0.5, 0.0)),
+        );
+
+        let skinned = mesh.skinned(&skeleton);
+        for i in 0..mesh.vertex_count() {
+            let expected = mesh
+                .get_local_normal(i)
+                .unwrap()
+                .apply_transform(rotation)
+                .unwrap();
+            let normal = skinned.get_local_normal(i).unwrap();
+            assert!(
+                normal.approx_equal(expected, TOLERANCE),
+                "ожидалась нормаль {:?}, но получено {:?}",
+                expected,
+                normal
+            );
+        }
+    }
+}
@@ -0,0 +1,312 @@
+//! UV-координаты углов полигонов и швы текстурной развёртки.
+//!
+//! Обычно UV-координаты задаются для вершин, поэтому вершина на шве развёртки (ребро куба,
+//! стык боковой поверхности цилиндра) получает среднее значение соседних граней. UV-координаты
+//! углов задаются для каждого угла каждого полигона отдельно, как `f v/vt` в OBJ.
+
+use std::collections::HashMap;
+
+use super::Mesh;
+use crate::{Polygon, Vec3};
+
+impl Mesh {
+    // --------------------------------------------------
+    // UV-координаты углов
+    // --------------------------------------------------
+
+    /// Задать UV-координаты углов полигонов.
+    ///
+    /// `texture_coords` - таблица UV-координат, `texture_indexes[p][i]` - индекс в ней для
+    /// i-ого угла p-ого полигона. Текстурные координаты вершин заменяются средним по их углам,
+    /// они используются там, где углы не поддерживаются (например, в шейдере вершин).
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::{Mesh, Point3, Polygon};
+    ///
+    /// // два треугольника с общей вершиной 1, но разными UV в ней
+    /// let vertexes = vec![
+    ///     Point3::new(0.0, 0.0, 0.0),
+    ///     Point3::new(1.0, 0.0, 0.0),
+    ///     Point3::new(0.0, 1.0, 0.0),
+    ///     Point3::new(2.0, 0.0, 0.0),
+    ///     Point3::new(2.0, 1.0, 0.0),
+    /// ];
+    /// let polygons = vec![Polygon::triangle(0, 1, 2), Polygon::triangle(1, 3, 4)];
+    /// let mut mesh = Mesh::from_polygons(vertexes, polygons);
+    /// mesh.set_corner_texture_coords(
+    ///     vec![(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)],
+    ///     vec![vec![0, 1, 2], vec![0, 1, 3]],
+    /// );
+    ///
+    /// let first = mesh.get_polygon(0);
+    /// let second = mesh.get_polygon(1);
+    /// assert_eq!(first.get_texture_coord(&mesh, 1), Some((1.0, 0.0)));
+    /// assert_eq!(second.get_texture_coord(&mesh, 0), Some((0.0, 0.0)));
+    /// assert_eq!(mesh.get_texture_coord(1), Some((0.5, 0.0)));
+    /// ```
+    pub fn set_corner_texture_coords(
+        &mut self,
+        texture_coords: Vec<(f32, f32)>,
+        texture_indexes: Vec<Vec<usize>>,
+    ) {
+        assert_eq!(
+            self.polygons.len(),
+            texture_indexes.len(),
+            "Количество наборов текстурных индексов должно совпадать с количеством полигонов"
+        );
+        for (polygon, indexes) in self.polygons.iter().zip(&texture_indexes) {
+            assert_eq!(
+                polygon.vertex_count(),
+                indexes.len(),
+                "Количество текстурных индексов должно совпадать с количеством вершин полигона"
+            );
+            assert!(
+                indexes.iter().all(|&index| index < texture_coords.len()),
+                "Текстурный индекс указывает на несуществующие UV-координаты"
+            );
+        }
+        for &(u, v) in &texture_coords {
+            assert!(
+                (0.0..=1.0).contains(&u) && (0.0..=1.0).contains(&v),
+                "UV-координаты ({}, {}) должны быть в диапазоне [0, 1]",
+                u,
+                v
+            );
+        }
+
+        // средние UV-координаты углов каждой вершины
+        let mut sums = vec![(0.0, 0.0); self.vertexes.len()];
+        let mut counts = vec![0; self.vertexes.len()];
+        for (polygon, indexes) in self.polygons.iter().zip(&texture_indexes) {
+            for (vertex, &index) in polygon.get_mesh_vertex_index_iter().zip(indexes) {
+                let (u, v) = texture_coords[index];
+                sums[vertex].0 += u;
+                sums[vertex].1 += v;
+                counts[vertex] += 1;
+            }
+        }
+        let vertex_coords = (0..self.vertexes.len())
+            .map(|vertex| match counts[vertex] {
+                0 => self.get_texture_coord(vertex).unwrap_or((0.0, 0.0)),
+                count => {
+                    let count = count as f32;
+                    (
+                        (sums[vertex].0 / count).clamp(0.0, 1.0),
+                        (sums[vertex].1 / count).clamp(0.0, 1.0),
+                    )
+                }
+            })
+            .collect();
+
+        for (polygon, indexes) in self.polygons.iter_mut().zip(texture_indexes) {
+            polygon.set_texture_indexes(Some(indexes));
+        }
+        self.texture_coords = Some(vertex_coords);
+        self.corner_texture_coords = Some(texture_coords);
+    }
+
+    /// Убрать UV-координаты углов, оставив только UV-координаты вершин.
+    pub fn clear_corner_texture_coords(&mut self) {
+        self.corner_texture_coords = None;
+        for polygon in &mut self.polygons {
+            polygon.set_texture_indexes(None);
+        }
+    }
+
+    /// Заданы ли UV-координаты углов полигонов?
+    pub fn has_corner_texture_coords(&self) -> bool {
+        self.corner_texture_coords.is_some()
+    }
+
+    /// Получить UV-координаты из таблицы углов по текстурному индексу.
+    pub fn get_corner_texture_coord(&self, texture_index: usize) -> Option<(f32, f32)> {
+        let coords = self.corner_texture_coords.as_ref()?;
+        coords.get(texture_index).copied()
+    }
+
+    /// Сгенерировать UV-координаты углов планарной развёрткой каждого полигона по отдельности.
+    ///
+    /// Каждый полигон проецируется на ближайшую к нему координатную плоскость и растягивается
+    /// на всю текстуру, так что у многогранников каждая грань получает текстуру целиком.
+    pub fn generate_face_texture_coords(&mut self) {
+        let mut texture_coords = Vec::new();
+        let mut texture_indexes = Vec::with_capacity(self.polygons.len());
+        for polygon in &self.polygons {
+            let vertex_indices: Vec<usize> = polygon.get_mesh_vertex_index_iter().collect();
+            let normal = polygon.plane_normal(self, None);
+            let (u_axis, v_axis) = Self::get_projection_axes(normal);
+            let (min_u, min_v, max_u, max_v) =
+                Self::get_polygon_bounds(&self.vertexes, &vertex_indices, u_axis, v_axis);
+
+            let mut indexes = Vec::with_capacity(vertex_indices.len());
+            for vertex_index in vertex_indices {
+                let vertex = Vec3::from(self.vertexes[vertex_index]);
+                let u = (vertex.dot(u_axis) - min_u) / (max_u - min_u);
+                let v = (vertex.dot(v_axis) - min_v) / (max_v - min_v);
+                indexes.push(texture_coords.len());
+                texture_coords.push((u.clamp(0.0, 1.0), v.clamp(0.0, 1.0)));
+            }
+            texture_indexes.push(indexes);
+        }
+        self.set_corner_texture_coords(texture_coords, texture_indexes);
+    }
+
+    // --------------------------------------------------
+    // Разрезание швов
+    // --------------------------------------------------
+
+    /// Получить копию Mesh'а, в которой UV-координаты углов перенесены на вершины.
+    ///
+    /// Вершина дублируется для каждой своей различной UV-координаты, так что на швах
+    /// развёртки появляются вершины с одинаковыми координатами. Нормали, привязка к скелету,
+    /// морф-таргеты, затенение окружением и цвета вершин копируются вместе с вершинами.
+    /// Если UV-координаты углов не заданы, то возвращается простая копия.
+    pub fn split_texture_seams(&self) -> Mesh {
+        if !self.has_corner_texture_coords() {
+            return self.clone();
+        }
+
+        // исходная вершина для каждой новой
+        let mut sources = Vec::with_capacity(self.vertexes.len());
+        let mut texture_coords = Vec::with_capacity(self.vertexes.len());
+        let mut new_indexes: HashMap<(usize, Option<usize>), usize> = HashMap::new();
+        let mut polygons = Vec::with_capacity(self.polygons.len());
+        for polygon in &self.polygons {
+            let mut indexes = Vec::with_capacity(polygon.vertex_count());
+            for i in 0..polygon.vertex_count() {
+                let vertex = polygon.get_mesh_vertex_index(i);
+                let key = (vertex, polygon.get_texture_index(i));
+                let index = *new_indexes.entry(key).or_insert_with(|| {
+                    sources.push(vertex);
+                    texture_coords.push(polygon.get_texture_coord(self, i).unwrap_or((0.0, 0.0)));
+                    sources.len() - 1
+                });
+                indexes.push(index);
+            }
+            polygons.push(Polygon::from_vec(indexes));
+        }
+        // вершины вне полигонов тоже сохраняются
+        let mut used = vec![false; self.vertexes.len()];
+        for &source in &sources {
+            used[source] = true;
+        }
+        for (vertex, _) in used.iter().enumerate().filter(|(_, used)| !**used) {
+            sources.push(vertex);
+            texture_coords.push(self.get_texture_coord(vertex).unwrap_or((0.0, 0.0)));
+        }
+
+        let normals = self
+            .normals
+            .as_ref()
+            .map(|normals| sources.iter().map(|&source| normals[source]).collect());
+        let mut mesh = Mesh::new(
+            sources
+                .iter()
+                .map(|&source| self.vertexes[source])
+                .collect(),
+            polygons,
+            normals,
+            Some(texture_coords),
+        );
+        mesh.local_frame = self.local_frame;
+        mesh.bone_weights = self
+            .bone_weights
+            .as_ref()
+            .map(|weights| sources.iter().map(|&source| weights[source]).collect());
+        mesh.ambient_occlusion = self
+            .ambient_occlusion
+            .as_ref()
+            .map(|occlusion| sources.iter().map(|&source| occlusion[source]).collect());
+        mesh.vertex_colors = self
+            .vertex_colors
+            .as_ref()
+            .map(|colors| sources.iter().map(|&source| colors[source]).collect());
+        mesh.morph_targets = self
+            .morph_targets
+            .iter()
+            .map(|target| target.remapped(&sources))
+            .collect();
+        mesh
+    }
+}
+
+#[cfg(test)]
+mod texture_seams_tests {
+    use super::*;
+    use crate::{Line3, Point3, UVec3};
+
+    #[test]
+    fn test_cube_faces_have_own_texture_coords() {
+        let cube = Mesh::hexahedron();
+        assert!(cube.has_corner_texture_coords());
+
+        // каждая грань покрывает всю текстуру, хотя вершины общие
+        for polygon in cube.get_polygon_iter() {
+            let coords: Vec<(f32, f32)> = polygon.get_texture_coord_iter(&cube).unwrap().collect();
+            for corner in [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)] {
+                assert!(coords.contains(&corner), "{:?} нет в {:?}", corner, coords);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rotation_model_seam() {
+        let profile = [Point3::new(1.0, 0.0, 0.0), Point3::new(1.0, 2.0, 0.0)];
+        let cylinder =
+            Mesh::create_rotation_model(&profile, Line3::new(Point3::zero(), UVec3::up()), 8);
+
+        // последний сегмент боковой поверхности замыкается на u = 1, а не на 0
+        let last = cylinder.get_polygon(7);
+        let coords: Vec<(f32, f32)> = last.get_texture_coord_iter(&cylinder).unwrap().collect();
+        assert_eq!(
+            coords,
+            vec![(0.875, 0.0), (1.0, 0.0), (1.0, 1.0), (0.875, 1.0)]
+        );
+        assert_eq!(
+            cylinder.get_polygon(0).get_texture_coord(&cylinder, 0),
+            Some((0.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn test_split_texture_seams() {
+        let mut cube = Mesh::hexahedron();
+        cube.add_morph_target("same", cube.get_local_vertex_iter().collect(), None);
+        let split = cube.split_texture_seams();
+
+        // у каждой из 8 вершин 3 разных UV-координаты
+        assert_eq!(split.vertex_count(), 24);
+        assert!(!split.has_corner_texture_coords());
+        assert!(split.has_normals());
+        assert_eq!(split.morph_targets().len(), 1);
+        for (polygon, original) in split.get_polygon_iter().zip(cube.get_polygon_iter()) {
+            for i in 0..polygon.vertex_count() {
+                assert_eq!(
+                    polygon.get_local_vertex(&split, i),
+                    original.get_local_vertex(&cube, i)
+                );
+                assert_eq!(
+                    polygon.get_texture_coord(&split, i),
+                    original.get_texture_coord(&cube, i)
+                );
+            }
+        }
+
+        // после разрезания новых швов нет
+        assert_eq!(split.split_texture_seams().vertex_count(), 24);
+    }
+
+    #[test]
+    fn test_clear_corner_texture_coords() {
+        let mut cube = Mesh::hexahedron();
+        cube.clear_corner_texture_coords();
+        assert!(!cube.has_corner_texture_coords());
+        assert!(cube.has_texture_coords());
+        let polygon = cube.get_polygon(0);
+        assert_eq!(
+            polygon.get_texture_coord(&cube, 0),
+            cube.get_texture_coord(polygon.get_mesh_vertex_index(0))
+        );
+    }
+}
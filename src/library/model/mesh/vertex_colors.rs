@@ -0,0 +1,97 @@
+//! Цвета вершин Mesh'а и раскраска по скалярному полю.
+
+use egui::Color32;
+
+use super::Mesh;
+use crate::{Palette, Point3};
+
+impl Mesh {
+    // --------------------------------------------------
+    // Цвета вершин
+    // --------------------------------------------------
+
+    /// Задать цвета вершин.
+    ///
+    /// Цвета индексируются в том же порядке, что и вершины Mesh'а. При отрисовке они
+    /// интерполируются по полигонам и заменяют цвет материала (текстура, если она есть,
+    /// смешивается уже с цветом вершин).
+    pub fn set_vertex_colors(&mut self, colors: Vec<Color32>) {
+        assert_eq!(
+            self.vertexes.len(),
+            colors.len(),
+            "Количество цветов должно совпадать с количеством вершин Mesh'а"
+        );
+        self.vertex_colors = Some(colors);
+    }
+
+    /// Убрать цвета вершин.
+    pub fn clear_vertex_colors(&mut self) {
+        self.vertex_colors = None;
+    }
+
+    /// Получить цвет i-ой вершины.
+    pub fn get_vertex_color(&self, i: usize) -> Option<Color32> {
+        let colors = self.vertex_colors.as_ref()?;
+        colors.get(i).copied()
+    }
+
+    /// Заданы ли цвета вершин?
+    pub fn has_vertex_colors(&self) -> bool {
+        self.vertex_colors.is_some()
+    }
+
+    /// Раскрасить вершины палитрой `palette` по скалярному полю `field`.
+    ///
+    /// `field` вычисляется для каждой вершины в **локальных** координатах, значения
+    /// нормируются от минимального (начало палитры) до максимального (конец палитры).
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::{Palette, SurfaceFunction};
+    ///
+    /// // график, раскрашенный по высоте
+    /// let mut mesh = SurfaceFunction::Saddle.generate_surface_mesh((-1.0, 1.0), (-1.0, 1.0), (8, 8));
+    /// mesh.color_by(Palette::Turbo, |vertex| vertex.z);
+    /// assert!(mesh.has_vertex_colors());
+    /// ```
+    pub fn color_by(&mut self, palette: Palette, field: impl Fn(Point3) -> f32) {
+        let values: Vec<f32> = self.vertexes.iter().map(|&vertex| field(vertex)).collect();
+        let (min, max) = values
+            .iter()
+            .filter(|value| value.is_finite())
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), &value| {
+                (min.min(value), max.max(value))
+            });
+        let range = (max - min).max(f32::EPSILON);
+        self.vertex_colors = Some(
+            values
+                .into_iter()
+                .map(|value| Color32::from(palette.sample((value - min) / range)))
+                .collect(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod vertex_colors_tests {
+    use super::*;
+
+    #[test]
+    fn test_color_by_normalizes_field() {
+        let mut mesh = Mesh::hexahedron();
+        mesh.color_by(Palette::Grayscale, |vertex| vertex.x);
+
+        for i in 0..mesh.vertex_count() {
+            let expected = if mesh.get_local_vertex(i).x < 0.0 {
+                Color32::BLACK
+            } else {
+                Color32::WHITE
+            };
+            assert_eq!(mesh.get_vertex_color(i), Some(expected), "вершина {}", i);
+        }
+
+        // отсечение сохраняет и интерполирует цвета
+        let clipped = mesh.clip_by_plane(&crate::Plane::new(Point3::zero(), crate::UVec3::right()));
+        assert!(clipped.has_vertex_colors());
+    }
+}
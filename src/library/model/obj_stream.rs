@@ -0,0 +1,534 @@
+//! Потоковая (постепенная) загрузка моделей из .obj файлов.
+//!
+//! Позволяет читать большой файл по частям и показывать уже прочитанную часть модели,
+//! не дожидаясь окончания загрузки.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Lines};
+use std::path::Path;
+
+use super::{Axis, Handedness, Mesh, Model, ObjLoadError, Polygon};
+use crate::Point3;
+
+/// Разобрать одну строку .obj файла, добавив прочитанную вершину, текстурную координату
+/// или полигон.
+///
+/// Для каждого полигона в `texture_indexes` добавляются индексы текстурных координат его
+/// углов (`f v/vt`), если они заданы для всех углов. Неподдерживаемые и неизвестные команды
+/// пропускаются.
+pub(super) fn parse_obj_line(
+    line: &str,
+    vertexes: &mut Vec<Point3>,
+    texture_coords: &mut Vec<(f32, f32)>,
+    polygons: &mut Vec<Polygon>,
+    texture_indexes: &mut Vec<Option<Vec<usize>>>,
+) -> Result<(), ObjLoadError> {
+    let trimmed = line.trim();
+
+    // Пропускаем комментарии и пустые строки
+    if trimmed.starts_with('#') || trimmed.is_empty() {
+        return Ok(());
+    }
+
+    let parts: Vec<&str> = trimmed.split_whitespace().collect();
+    if parts.len() < 2 {
+        return Ok(());
+    }
+
+    match parts[0] {
+        // Vertex: v x y z [w]
+        "v" if parts.len() >= 4 => {
+            let x = parts[1]
+                .parse::<f32>()
+                .map_err(|_| ObjLoadError::InvalidFormat)?;
+            let y = parts[2]
+                .parse::<f32>()
+                .map_err(|_| ObjLoadError::InvalidFormat)?;
+            let z = parts[3]
+                .parse::<f32>()
+                .map_err(|_| ObjLoadError::InvalidFormat)?;
+
+            vertexes.push(Point3::new(x, y, z));
+        }
+        // Texture coordinate: vt u [v [w]]
+        "vt" => {
+            let u = parts[1]
+                .parse::<f32>()
+                .map_err(|_| ObjLoadError::InvalidFormat)?;
+            let v = match parts.get(2) {
+                Some(v) => v.parse::<f32>().map_err(|_| ObjLoadError::InvalidFormat)?,
+                None => 0.0,
+            };
+
+            // в OBJ ось v направлена вверх, а у текстур v = 0 - верхняя строка;
+            // повторение текстуры не поддерживается
+            texture_coords.push((u.clamp(0.0, 1.0), 1.0 - v.clamp(0.0, 1.0)));
+        }
+        // Face: f v1 v2 v3 ...
+        "f" if parts.len() >= 4 => {
+            let mut face_vertex_indices = Vec::new();
+            let mut face_texture_indices = Some(Vec::new());
+
+            for part in &parts[1..] {
+                // OBJ формат может быть: "v", "v/vt", "v//vn" или "v/vt/vn"
+                // Нас интересуют индексы вершины и текстурной координаты
+                let mut indices = part.split('/');
+                if let Some(index) = parse_obj_index(indices.next().unwrap(), vertexes.len())? {
+                    face_vertex_indices.push(index);
+                } else {
+                    continue;
+                }
+
+                let texture_index = match indices.next() {
+                    Some(texture_part) if !texture_part.is_empty() => {
+                        parse_obj_index(texture_part, texture_coords.len())?
+                    }
+                    _ => None,
+                };
+                face_texture_indices =
+                    face_texture_indices
+                        .zip(texture_index)
+                        .map(|(mut indices, index)| {
+                            indices.push(index);
+                            indices
+                        });
+            }
+
+            if face_vertex_indices.len() >= 3 {
+                polygons.push(Polygon::from_list(&face_vertex_indices));
+                texture_indexes.push(face_texture_indices);
+            }
+        }
+        // Пока игнорируем нормали, параметрические вершины и неизвестные типы
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Перевести индекс из .obj файла (с 1, отрицательные - с конца) в индекс с 0 среди `count`
+/// уже прочитанных элементов. Нулевой индекс пропускается.
+fn parse_obj_index(part: &str, count: usize) -> Result<Option<usize>, ObjLoadError> {
+    let index = part
+        .parse::<i32>()
+        .map_err(|_| ObjLoadError::InvalidFormat)?;
+
+    // OBJ индексы начинаются с 1, наши с 0
+    if index > 0 {
+        if (index as usize) <= count {
+            Ok(Some((index - 1) as usize))
+        } else {
+            Err(ObjLoadError::InvalidFormat)
+        }
+    } else if index < 0 {
+        // Отрицательные индексы (относительные)
+        let actual_index = count as i32 + index;
+        if actual_index >= 0 {
+            Ok(Some(actual_index as usize))
+        } else {
+            Err(ObjLoadError::InvalidFormat)
+        }
+    } else {
+        Ok(None)
+    }
+}
+
+/// Собрать Mesh из прочитанных данных.
+///
+/// Текстурные координаты углов переносятся, только если они заданы у всех полигонов.
+/// Затем Mesh переводится в систему координат библиотеки по настройкам `options`.
+fn build_mesh(
+    vertexes: Vec<Point3>,
+    texture_coords: &[(f32, f32)],
+    polygons: Vec<Polygon>,
+    texture_indexes: &[Option<Vec<usize>>],
+    options: &ObjImportOptions,
+) -> Mesh {
+    let mut mesh = Mesh::from_polygons(vertexes, polygons);
+    if let Some(texture_indexes) = texture_indexes.iter().cloned().collect::<Option<Vec<_>>>()
+        && !texture_coords.is_empty()
+    {
+        mesh.set_corner_texture_coords(texture_coords.to_vec(), texture_indexes);
+    }
+    options.apply(&mut mesh);
+    mesh
+}
+
+/// Настройки перевода загружаемой модели в систему координат библиотеки.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ObjImportOptions {
+    /// Ориентация системы координат файла.
+    handedness: Handedness,
+    /// Ось, направленная вверх в файле.
+    up_axis: Axis,
+    /// Множитель перевода единиц файла в единицы сцены.
+    unit_scale: f32,
+}
+
+impl Default for ObjImportOptions {
+    fn default() -> Self {
+        Self {
+            handedness: Handedness::LeftHanded,
+            up_axis: Axis::Y,
+            unit_scale: 1.0,
+        }
+    }
+}
+
+impl ObjImportOptions {
+    /// Перевести Mesh: сначала поворот оси вверх (в ориентации файла), затем смена
+    /// ориентации и единиц измерения.
+    fn apply(&self, mesh: &mut Mesh) {
+        mesh.convert_up_axis(self.up_axis, Axis::Y);
+        mesh.convert_handedness(self.handedness, Handedness::LeftHanded);
+        if self.unit_scale != 1.0 {
+            mesh.convert_units(self.unit_scale);
+        }
+    }
+}
+
+/// Потоковый загрузчик .obj файла.
+///
+/// Файл читается порциями строк через `load_chunk`, а между порциями можно получить
+/// модель из уже прочитанных вершин и полигонов через `current_model` или, чтобы
+/// не пересобирать её после каждой порции, обновлённую геометрию через `updated_preview`.
+///
+/// # Examples
+/// ```no_run
+/// use g3d::ObjStreamLoader;
+///
+/// let mut loader = ObjStreamLoader::open("model.obj").unwrap();
+/// while !loader.load_chunk(10_000).unwrap() {
+///     if let Some(partial) = loader.updated_preview() {
+///         // отрисовать уже загруженную часть модели
+///     }
+/// }
+/// let model = loader.finish().unwrap();
+/// ```
+pub struct ObjStreamLoader {
+    lines: Lines<BufReader<File>>,
+    vertexes: Vec<Point3>,
+    texture_coords: Vec<(f32, f32)>,
+    polygons: Vec<Polygon>,
+    texture_indexes: Vec<Option<Vec<usize>>>,
+    options: ObjImportOptions,
+    finished: bool,
+    /// Количество полигонов в последней выданной `updated_preview` геометрии.
+    preview_polygon_count: usize,
+}
+
+impl ObjStreamLoader {
+    /// Открыть .obj файл для постепенной загрузки.
+    pub fn open(file_path: &str) -> Result<Self, ObjLoadError> {
+        let path = Path::new(file_path);
+        if !path.exists() {
+            return Err(ObjLoadError::FileNotFound);
+        }
+
+        let file = File::open(path).map_err(|_| ObjLoadError::FileNotFound)?;
+        Ok(Self {
+            lines: BufReader::new(file).lines(),
+            vertexes: Vec::new(),
+            texture_coords: Vec::new(),
+            polygons: Vec::new(),
+            texture_indexes: Vec::new(),
+            options: ObjImportOptions::default(),
+            finished: false,
+            preview_polygon_count: 0,
+        })
+    }
+
+    /// Задать ориентацию системы координат файла, по умолчанию левая (как в библиотеке).
+    ///
+    /// Загруженный Mesh переводится в левую систему координат.
+    pub fn with_handedness(mut self, handedness: Handedness) -> Self {
+        self.options.handedness = handedness;
+        self
+    }
+
+    /// Задать ось, направленную вверх в файле, по умолчанию y (как в библиотеке).
+    ///
+    /// Загруженный Mesh поворачивается так, чтобы эта ось стала осью y.
+    pub fn with_up_axis(mut self, up_axis: Axis) -> Self {
+        self.options.up_axis = up_axis;
+        self
+    }
+
+    /// Задать множитель перевода единиц файла в единицы сцены, по умолчанию 1.
+    ///
+    /// Например, `0.001` для модели в миллиметрах, если сцена в метрах.
+    pub fn with_unit_scale(mut self, unit_scale: f32) -> Self {
+        debug_assert!(
+            unit_scale > 0.0 && unit_scale.is_finite(),
+            "масштаб единиц {} должен быть положительным",
+            unit_scale
+        );
+        self.options.unit_scale = unit_scale;
+        self
+    }
+
+    /// Прочитать не более `max_lines` строк файла.
+    ///
+    /// Возвращает `true`, если файл прочитан полностью.
+    pub fn load_chunk(&mut self, max_lines: usize) -> Result<bool, ObjLoadError> {
+        for _ in 0..max_lines {
+            match self.lines.next() {
+                Some(line) => {
+                    let line = line.map_err(|_| ObjLoadError::InvalidFormat)?;
+                    parse_obj_line(
+                        &line,
+                        &mut self.vertexes,
+                        &mut self.texture_coords,
+                        &mut self.polygons,
+                        &mut self.texture_indexes,
+                    )?;
+                }
+                None => {
+                    self.finished = true;
+                    break;
+                }
+            }
+        }
+
+        Ok(self.finished)
+    }
+
+    /// Прочитан ли файл полностью.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Количество прочитанных на данный момент вершин.
+    pub fn vertex_count(&self) -> usize {
+        self.vertexes.len()
+    }
+
+    /// Количество прочитанных на данный момент полигонов.
+    pub fn polygon_count(&self) -> usize {
+        self.polygons.len()
+    }
+
+    /// Модель из уже прочитанной части файла.
+    ///
+    /// Mesh каждый раз собирается заново из всего прочитанного, поэтому вызов после каждой
+    /// порции делает загрузку квадратичной, для показа загрузки лучше `updated_preview`.
+    ///
+    /// Возвращает `None`, если ещё не прочитано ни одного полигона.
+    pub fn current_model(&self) -> Option<Model> {
+        if self.polygons.is_empty() {
+            return None;
+        }
+
+        let mesh = build_mesh(
+            self.vertexes.clone(),
+            &self.texture_coords,
+            self.polygons.clone(),
+            &self.texture_indexes,
+            &self.options,
+        );
+        Some(Model::from_mesh(mesh))
+    }
+
+    /// Геометрия уже прочитанной части файла, если с прошлого вызова количество полигонов
+    /// хотя бы удвоилось или файл дочитан до конца.
+    ///
+    /// Mesh пересобирается только при удвоении, поэтому суммарное время сборок за всю
+    /// загрузку не больше двух сборок итогового Mesh.
+    pub fn updated_preview(&mut self) -> Option<Mesh> {
+        let count = self.polygons.len();
+        let doubled = count >= (2 * self.preview_polygon_count).max(1);
+        if count == self.preview_polygon_count || !(doubled || self.finished) {
+            return None;
+        }
+
+        self.preview_polygon_count = count;
+        self.current_model().map(|model| model.mesh)
+    }
+
+    /// Дочитать файл до конца и получить итоговую модель.
+    pub fn finish(mut self) -> Result<Model, ObjLoadError> {
+        while !self.load_chunk(usize::MAX)? {}
+
+        if self.vertexes.is_empty() || self.polygons.is_empty() {
+            return Err(ObjLoadError::InvalidFormat);
+        }
+
+        let mesh = build_mesh(
+            self.vertexes,
+            &self.texture_coords,
+            self.polygons,
+            &self.texture_indexes,
+            &self.options,
+        );
+        Ok(Model::from_mesh(mesh))
+    }
+}
+
+#[cfg(test)]
+mod obj_stream_tests {
+    use super::*;
+    use std::io::Write;
+
+    /// Записать .obj файл с `count` отдельными треугольниками во временную директорию.
+    fn write_triangles_obj(name: &str, count: usize) -> String {
+        let path = std::env::temp_dir().join(name);
+        let mut file = File::create(&path).unwrap();
+        for i in 0..count {
+            let x = i as f32;
+            writeln!(file, "v {} 0 0", x).unwrap();
+            writeln!(file, "v {} 1 0", x).unwrap();
+            writeln!(file, "v {} 0 1", x + 0.5).unwrap();
+            writeln!(file, "f -3 -2 -1").unwrap();
+        }
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_stream_loads_in_chunks() {
+        let path = write_triangles_obj("g3d_obj_stream_chunks.obj", 5);
+        let mut loader = ObjStreamLoader::open(&path).unwrap();
+        assert!(loader.current_model().is_none());
+
+        // 2 треугольника по 4 строки
+        assert!(!loader.load_chunk(8).unwrap());
+        assert_eq!(loader.polygon_count(), 2);
+        assert_eq!(loader.current_model().unwrap().mesh.polygon_count(), 2);
+
+        let model = loader.finish().unwrap();
+        assert_eq!(model.mesh.polygon_count(), 5);
+        assert_eq!(model.mesh.vertex_count(), 15);
+    }
+
+    #[test]
+    fn test_preview_is_rebuilt_on_doubling() {
+        let path = write_triangles_obj("g3d_obj_stream_preview.obj", 10);
+        let mut loader = ObjStreamLoader::open(&path).unwrap();
+        assert!(loader.updated_preview().is_none());
+
+        // по одному треугольнику за порцию: сборки на 1, 2, 4, 8 и в конце на 10 полигонах
+        let mut rebuilt = Vec::new();
+        while !loader.load_chunk(4).unwrap() {
+            if let Some(mesh) = loader.updated_preview() {
+                rebuilt.push(mesh.polygon_count());
+            }
+        }
+        if let Some(mesh) = loader.updated_preview() {
+            rebuilt.push(mesh.polygon_count());
+        }
+        assert_eq!(rebuilt, vec![1, 2, 4, 8, 10]);
+        assert!(loader.updated_preview().is_none());
+    }
+
+    #[test]
+    fn test_stream_matches_full_load() {
+        let path = write_triangles_obj("g3d_obj_stream_full.obj", 3);
+        let streamed = ObjStreamLoader::open(&path).unwrap().finish().unwrap();
+        let loaded = Model::load_from_obj(&path).unwrap();
+        assert_eq!(streamed.mesh.polygon_count(), loaded.mesh.polygon_count());
+        assert_eq!(streamed.mesh.vertex_count(), loaded.mesh.vertex_count());
+    }
+
+    #[test]
+    fn test_stream_reads_corner_texture_coords() {
+        let path = std::env::temp_dir().join("g3d_obj_stream_uv.obj");
+        std::fs::write(
+            &path,
+            "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nv 2 0 0\n\
+             vt 0 0\nvt 1 0\nvt 1 1\nvt 0 1\n\
+             f 1/1 2/2 3/3 4/4\nf 2/-4/1 5/-3/1 3/-1/1\n",
+        )
+        .unwrap();
+        let model = Model::load_from_obj(path.to_str().unwrap()).unwrap();
+        let mesh = &model.mesh;
+        assert!(mesh.has_corner_texture_coords());
+
+        // у вершины 2 разные UV-координаты в разных полигонах
+        assert_eq!(
+            mesh.get_polygon(0).get_texture_coord(mesh, 1),
+            Some((1.0, 1.0))
+        );
+        assert_eq!(
+            mesh.get_polygon(1).get_texture_coord(mesh, 0),
+            Some((0.0, 1.0))
+        );
+        assert_eq!(
+            mesh.get_polygon(1).get_texture_coord(mesh, 2),
+            Some((0.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn test_obj_handedness_round_trip() {
+        let path = std::env::temp_dir().join("g3d_obj_handedness.obj");
+        let path = path.to_str().unwrap();
+        let model = Model::from_mesh(Mesh::tetrahedron());
+        model
+            .save_to_obj_with_handedness(path, Handedness::RightHanded)
+            .unwrap();
+
+        // в файле ось z отражена
+        let raw = Model::load_from_obj(path).unwrap();
+        let apex = model.mesh.get_local_vertex(0);
+        assert!(
+            raw.mesh
+                .get_local_vertex(0)
+                .approx_equal(Point3::new(apex.x, apex.y, -apex.z), 1e-5)
+        );
+
+        // при загрузке с той же ориентацией Mesh совпадает с исходным
+        let loaded = Model::load_from_obj_with_handedness(path, Handedness::RightHanded).unwrap();
+        for i in 0..model.mesh.vertex_count() {
+            assert!(
+                loaded
+                    .mesh
+                    .get_local_vertex(i)
+                    .approx_equal(model.mesh.get_local_vertex(i), 1e-5)
+            );
+        }
+        for (loaded, original) in loaded
+            .mesh
+            .get_polygon_iter()
+            .zip(model.mesh.get_polygon_iter())
+        {
+            assert!(
+                loaded
+                    .get_mesh_vertex_index_iter()
+                    .eq(original.get_mesh_vertex_index_iter())
+            );
+        }
+    }
+
+    #[test]
+    fn test_stream_import_options() {
+        let path = write_triangles_obj("g3d_obj_import_options.obj", 1);
+        let model = ObjStreamLoader::open(&path)
+            .unwrap()
+            .with_up_axis(Axis::Z)
+            .with_unit_scale(0.5)
+            .finish()
+            .unwrap();
+
+        // вершина (0.5, 0, 1) с осью z вверх в половинных единицах
+        assert!(
+            model
+                .mesh
+                .get_local_vertex(2)
+                .approx_equal(Point3::new(0.25, 0.5, 0.0), 1e-5)
+        );
+        // вершина (0, 1, 0) уходит вглубь
+        assert!(
+            model
+                .mesh
+                .get_local_vertex(1)
+                .approx_equal(Point3::new(0.0, 0.0, -0.5), 1e-5)
+        );
+    }
+
+    #[test]
+    fn test_stream_missing_file() {
+        assert!(matches!(
+            ObjStreamLoader::open("/definitely/not/here.obj"),
+            Err(ObjLoadError::FileNotFound)
+        ));
+    }
+}
@@ -0,0 +1,298 @@
+//! Готовые вспомогательные модели сцены: опорная плоскость земли и небесная сфера.
+
+use std::f32::consts::PI;
+
+use egui::Color32;
+
+use super::{CullMode, Material, Mesh, Model, Polygon};
+use crate::{Camera, Point3, UVec3, library::utils};
+
+/// Количество разбиений небесной сферы по долготе (по широте вдвое меньше).
+const SKYSPHERE_SEGMENTS: usize = 32;
+
+/// Параметры опорной плоскости земли, см. [`Model::ground_plane`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GroundPlaneOptions {
+    /// Сторона клетки шахматной доски.
+    pub cell_size: f32,
+    /// Радиус плоскости: на этом расстоянии от центра клетки полностью сливаются с `fade_color`.
+    pub extent: f32,
+    /// Доля радиуса из [0, 1], с которой начинается затухание клеток.
+    pub fade_start: f32,
+    /// Цвет светлых клеток.
+    pub light_color: Color32,
+    /// Цвет тёмных клеток.
+    pub dark_color: Color32,
+    /// Цвет, в который клетки затухают с расстоянием. Обычно совпадает с фоном сцены.
+    pub fade_color: Color32,
+}
+
+impl Default for GroundPlaneOptions {
+    fn default() -> Self {
+        Self {
+            cell_size: 1.0,
+            extent: 30.0,
+            fade_start: 0.3,
+            light_color: Color32::from_gray(200),
+            dark_color: Color32::from_gray(150),
+            fade_color: Color32::GRAY,
+        }
+    }
+}
+
+impl GroundPlaneOptions {
+    /// Цвет клетки `(i, j)` в точке на расстоянии `distance` от центра плоскости.
+    fn cell_color(&self, i: i32, j: i32, distance: f32) -> Color32 {
+        let color = if (i + j).rem_euclid(2) == 0 {
+            self.light_color
+        } else {
+            self.dark_color
+        };
+        let fade_start = self.fade_start.clamp(0.0, 1.0);
+        let t = (distance / self.extent - fade_start) / (1.0 - fade_start).max(f32::EPSILON);
+        let t = t.clamp(0.0, 1.0);
+        // плавное затухание без резкой границы
+        utils::lerp_color(color, self.fade_color, t * t * (3.0 - 2.0 * t))
+    }
+}
+
+impl Mesh {
+    /// Плоский четырёхугольник с углами `corners` по порядку обхода, разбитый на
+    /// `subdivisions` x `subdivisions` клеток, чтобы освещение по вершинам было плавным.
+    ///
+    /// Все вершины получают нормаль `normal`, от порядка обхода она не зависит,
+    /// текстурные координаты растягивают текстуру на весь четырёхугольник.
+    pub(crate) fn subdivided_quad(
+        corners: [Point3; 4],
+        normal: UVec3,
+        subdivisions: usize,
+    ) -> Self {
+        let n = subdivisions.max(1);
+        let [a, b, c, d] = corners;
+        let mut vertexes = Vec::with_capacity((n + 1) * (n + 1));
+        let mut texture_coords = Vec::with_capacity((n + 1) * (n + 1));
+        for j in 0..=n {
+            let v = j as f32 / n as f32;
+            for i in 0..=n {
+                let u = i as f32 / n as f32;
+                let near = a + (b - a) * u;
+                let far = d + (c - d) * u;
+                vertexes.push(near + (far - near) * v);
+                texture_coords.push((u, v));
+            }
+        }
+        let index = |i: usize, j: usize| j * (n + 1) + i;
+        let mut polygons = Vec::with_capacity(n * n);
+        for j in 0..n {
+            for i in 0..n {
+                polygons.push(Polygon::from_list(&[
+                    index(i, j),
+                    index(i + 1, j),
+                    index(i + 1, j + 1),
+                    index(i, j + 1),
+                ]));
+            }
+        }
+        let normals = vec![normal; vertexes.len()];
+        Self::new(vertexes, polygons, Some(normals), Some(texture_coords))
+    }
+}
+
+impl Model {
+    /// Создать опорную плоскость земли: шахматную доску в локальной плоскости XZ,
+    /// клетки которой плавно затухают в `fade_color` с расстоянием от центра.
+    ///
+    /// Плоскость видна с обеих сторон и не отбрасывает теней. Чтобы она казалась бесконечной,
+    /// перед каждым кадром вызывайте [`Model::recenter_ground_plane`].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::{GroundPlaneOptions, Model, Scene};
+    ///
+    /// let options = GroundPlaneOptions::default();
+    /// let mut ground = Model::ground_plane(&options);
+    /// ground.set_position(g3d::Point3::new(0.0, -1.0, 0.0));
+    ///
+    /// let mut scene = Scene::default();
+    /// ground.recenter_ground_plane(&scene.camera, options.cell_size);
+    /// scene.models.push(ground);
+    /// ```
+    pub fn ground_plane(options: &GroundPlaneOptions) -> Self {
+        assert!(
+            options.cell_size > 0.0 && options.extent > 0.0,
+            "размер клетки и радиус плоскости должны быть больше 0"
+        );
+
+        // у каждой клетки свои вершины, чтобы цвета соседних клеток не смешивались
+        let half_cells = (options.extent / options.cell_size).ceil() as i32;
+        let mut vertexes = Vec::new();
+        let mut colors = Vec::new();
+        let mut polygons = Vec::new();
+        for i in -half_cells..half_cells {
+            for j in -half_cells..half_cells {
+                let (x0, z0) = (i as f32 * options.cell_size, j as f32 * options.cell_size);
+                let (x1, z1) = (x0 + options.cell_size, z0 + options.cell_size);
+                let start = vertexes.len();
+                // обход, при котором нормаль направлена вверх
+                for (x, z) in [(x0, z0), (x0, z1), (x1, z1), (x1, z0)] {
+                    vertexes.push(Point3::new(x, 0.0, z));
+                    colors.push(options.cell_color(i, j, x.hypot(z)));
+                }
+                polygons.push(Polygon::from_list(&[
+                    start,
+                    start + 1,
+                    start + 2,
+                    start + 3,
+                ]));
+            }
+        }
+        let normals = vec![UVec3::up(); vertexes.len()];
+
+        let mut mesh = Mesh::new(vertexes, polygons, Some(normals), None);
+        mesh.set_vertex_colors(colors);
+        let mut model = Self::from_mesh(mesh);
+        model.flags.cull_mode = CullMode::None;
+        model.flags.casts_shadows = false;
+        model
+    }
+
+    /// Передвинуть опорную плоскость земли под камеру, сохранив её высоту.
+    ///
+    /// Плоскость сдвигается с шагом в две клетки `cell_size`, поэтому шахматный узор
+    /// остаётся на месте, а края плоскости никогда не приближаются к камере.
+    pub fn recenter_ground_plane(&mut self, camera: &Camera, cell_size: f32) {
+        let step = 2.0 * cell_size;
+        let eye = camera.get_position();
+        let origin = &mut self.mesh.local_frame.origin;
+        origin.x = (eye.x / step).round() * step;
+        origin.z = (eye.z / step).round() * step;
+    }
+
+    /// Создать небесную сферу радиуса `radius` с материалом `material`.
+    ///
+    /// Текстура материала натягивается как равнопромежуточная панорама (верх картинки - зенит).
+    /// Сфера видна изнутри, не отбрасывает теней и не записывает глубину, поэтому рисуется
+    /// позади всех моделей. Радиус должен быть меньше дальней плоскости камеры, а сама сфера
+    /// перед каждым кадром ставится в положение камеры (см. [`Model::center_on_camera`]).
+    ///
+    /// # Examples
+    /// ```rust
+    /// use egui::Color32;
+    /// use g3d::{Material, Model, Scene};
+    ///
+    /// let material = Material {
+    ///     color: Color32::LIGHT_BLUE,
+    ///     ..Default::default()
+    /// };
+    /// let mut sky = Model::skysphere(material, 50.0);
+    /// assert!(!sky.flags.depth_write);
+    ///
+    /// let mut scene = Scene::default();
+    /// sky.center_on_camera(&scene.camera);
+    /// scene.models.push(sky);
+    /// ```
+    pub fn skysphere(material: Material, radius: f32) -> Self {
+        let sphere = |u: f32, v: f32| {
+            Point3::new(
+                radius * v.sin() * u.cos(),
+                radius * v.cos(),
+                radius * v.sin() * u.sin(),
+            )
+        };
+        let mut mesh = Mesh::from_parametric(
+            sphere,
+            (0.0, 2.0 * PI),
+            (0.0, PI),
+            SKYSPHERE_SEGMENTS,
+            SKYSPHERE_SEGMENTS / 2,
+            true,
+            false,
+        );
+        mesh.generate_normals();
+
+        let mut model = Self::from_mesh(mesh);
+        model.material = material;
+        model.flags.cull_mode = CullMode::Front;
+        model.flags.casts_shadows = false;
+        model.flags.depth_write = false;
+        model
+    }
+
+    /// Поставить модель в положение камеры (например, небесную сферу).
+    pub fn center_on_camera(&mut self, camera: &Camera) {
+        self.set_position(camera.get_position());
+    }
+}
+
+#[cfg(test)]
+mod scene_helpers_tests {
+    use super::*;
+    use crate::{Canvas, ProjectionType, Scene, SceneRenderer};
+
+    #[test]
+    fn test_ground_plane_fades_with_distance() {
+        let options = GroundPlaneOptions {
+            cell_size: 1.0,
+            extent: 4.0,
+            fade_start: 0.5,
+            light_color: Color32::WHITE,
+            dark_color: Color32::BLACK,
+            fade_color: Color32::RED,
+        };
+        let ground = Model::ground_plane(&options);
+        assert_eq!(ground.mesh.polygon_count(), 8 * 8);
+
+        // в центре шахматный узор, у края только цвет затухания
+        let color_at = |x: f32, z: f32| {
+            let index = ground
+                .mesh
+                .get_local_vertex_iter()
+                .position(|vertex| vertex == Point3::new(x, 0.0, z))
+                .unwrap();
+            ground.mesh.get_vertex_color(index).unwrap()
+        };
+        assert_eq!(color_at(0.0, 0.0), Color32::WHITE);
+        assert_eq!(color_at(-1.0, 0.0), Color32::BLACK);
+        assert_eq!(color_at(4.0, 0.0), Color32::RED);
+        assert_eq!(color_at(4.0, 4.0), Color32::RED);
+    }
+
+    #[test]
+    fn test_recenter_ground_plane_keeps_pattern() {
+        let mut ground = Model::ground_plane(&GroundPlaneOptions::default());
+        ground.set_position(Point3::new(0.0, -2.0, 0.0));
+        let mut camera = Camera::default();
+        camera.local_frame.origin = Point3::new(2.9, 5.0, -7.2);
+
+        ground.recenter_ground_plane(&camera, 1.5);
+        assert_eq!(ground.get_position(), Point3::new(3.0, -2.0, -6.0));
+    }
+
+    #[test]
+    fn test_skysphere_stays_behind_models() {
+        let mut cube = Model::from_mesh(Mesh::hexahedron());
+        cube.material.color = Color32::WHITE;
+        let mut sky = Model::skysphere(
+            Material {
+                color: Color32::BLUE,
+                ..Default::default()
+            },
+            50.0,
+        );
+        let mut scene = Scene::default();
+        sky.center_on_camera(&scene.camera);
+        // небо позже куба в списке моделей, но всё равно рисуется позади него
+        scene.models = vec![cube, sky];
+
+        let renderer = SceneRenderer {
+            render_wireframe: false,
+            render_solid: true,
+            projection_type: ProjectionType::Perspective,
+            ..Default::default()
+        };
+        let mut canvas = Canvas::new(300, 200);
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        assert_eq!(canvas[(150, 100)], Color32::WHITE);
+        assert_eq!(canvas[(10, 10)], Color32::BLUE);
+    }
+}
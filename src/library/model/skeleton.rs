@@ -0,0 +1,204 @@
+//! Скелет модели для скелетной анимации.
+
+use crate::Transform3D;
+
+/// Сустав (кость) скелета.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Joint {
+    /// Имя сустава, по нему сустав можно найти в скелете.
+    pub name: String,
+    /// Индекс родительского сустава. `None` для корневых суставов.
+    pub parent: Option<usize>,
+    /// Преобразование сустава относительно родителя в исходной позе (bind pose).
+    pub bind_transform: Transform3D,
+    /// Текущее преобразование сустава относительно родителя.
+    pub pose_transform: Transform3D,
+}
+
+/// Скелет - иерархия суставов с исходной и текущей позами.
+///
+/// Все преобразования суставов задаются относительно родителя, для корневых суставов -
+/// в **локальных** координатах Mesh'а. Родитель всегда идёт в списке суставов раньше потомков,
+/// поэтому глобальные преобразования вычисляются одним проходом.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Skeleton {
+    joints: Vec<Joint>,
+}
+
+impl Skeleton {
+    // --------------------------------------------------
+    // Конструкторы
+    // --------------------------------------------------
+
+    /// Создать пустой скелет.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Добавить сустав и получить его индекс.
+    ///
+    /// `parent` должен быть индексом уже добавленного сустава. Текущая поза нового сустава
+    /// совпадает с исходной.
+    pub fn add_joint(
+        &mut self,
+        name: impl Into<String>,
+        parent: Option<usize>,
+        bind_transform: Transform3D,
+    ) -> usize {
+        assert!(
+            parent.is_none_or(|parent| parent < self.joints.len()),
+            "родительский сустав {:?} должен быть добавлен раньше потомка",
+            parent
+        );
+
+        self.joints.push(Joint {
+            name: name.into(),
+            parent,
+            bind_transform,
+            pose_transform: bind_transform,
+        });
+        self.joints.len() - 1
+    }
+
+    // --------------------------------------------------
+    // Доступ к суставам
+    // --------------------------------------------------
+
+    /// Количество суставов.
+    pub fn joint_count(&self) -> usize {
+        self.joints.len()
+    }
+
+    /// Получить i-ый сустав.
+    pub fn get_joint(&self, i: usize) -> &Joint {
+        &self.joints[i]
+    }
+
+    /// Итератор по всем суставам.
+    pub fn get_joint_iter(&self) -> impl Iterator<Item = &Joint> {
+        self.joints.iter()
+    }
+
+    /// Найти индекс сустава по имени.
+    pub fn find_joint(&self, name: &str) -> Option<usize> {
+        self.joints.iter().position(|joint| joint.name == name)
+    }
+
+    // --------------------------------------------------
+    // Поза
+    // --------------------------------------------------
+
+    /// Задать текущее преобразование i-го сустава относительно родителя.
+    pub fn set_pose(&mut self, i: usize, pose_transform: Transform3D) {
+        self.joints[i].pose_transform = pose_transform;
+    }
+
+    /// Вернуть все суставы в исходную позу.
+    pub fn reset_pose(&mut self) {
+        for joint in &mut self.joints {
+            joint.pose_transform = joint.bind_transform;
+        }
+    }
+
+    /// Глобальные (в координатах Mesh'а) преобразования суставов в исходной позе.
+    pub fn bind_global_transforms(&self) -> Vec<Transform3D> {
+        self.global_transforms(|joint| joint.bind_transform)
+    }
+
+    /// Глобальные (в координатах Mesh'а) преобразования суставов в текущей позе.
+    pub fn pose_global_transforms(&self) -> Vec<Transform3D> {
+        self.global_transforms(|joint| joint.pose_transform)
+    }
+
+    /// Матрицы скиннинга для каждого сустава.
+    ///
+    /// Матрица переводит вершину из исходной позы в текущую: сначала в систему координат сустава
+    /// в исходной позе, затем обратно по текущей позе. Для сустава с вырожденной исходной
+    /// матрицей используется единичная матрица.
+    pub fn skinning_matrices(&self) -> Vec<Transform3D> {
+        self.bind_global_transforms()
+            .into_iter()
+            .zip(self.pose_global_transforms())
+            .map(|(bind, pose)| match bind.inverse() {
+                Some(inverse_bind) => inverse_bind.multiply(pose),
+                None => {
+                    #[cfg(debug_assertions)]
+                    eprintln!("Warning: вырожденная исходная матрица сустава, скиннинг пропущен");
+                    Transform3D::identity()
+                }
+            })
+            .collect()
+    }
+
+    /// Глобальные преобразования суставов, локальное преобразование выбирается `local`.
+    fn global_transforms(&self, local: impl Fn(&Joint) -> Transform3D) -> Vec<Transform3D> {
+        let mut global: Vec<Transform3D> = Vec::with_capacity(self.joints.len());
+        for joint in &self.joints {
+            let transform = match joint.parent {
+                // сначала преобразование сустава, затем родителя
+                Some(parent) => local(joint).multiply(global[parent]),
+                None => local(joint),
+            };
+            global.push(transform);
+        }
+        global
+    }
+}
+
+#[cfg(test)]
+mod skeleton_tests {
+    use super::*;
+    use crate::Point3;
+
+    const TOLERANCE: f32 = 1e-5;
+
+    #[test]
+    fn test_global_transforms_hierarchy() {
+        let mut skeleton = Skeleton::new();
+        let root = skeleton.add_joint("root", None, Transform3D::translation(1.0, 0.0, 0.0));
+        let child =
+            skeleton.add_joint("child", Some(root), Transform3D::translation(0.0, 2.0, 0.0));
+        assert_eq!(skeleton.find_joint("child"), Some(child));
+        assert_eq!(skeleton.find_joint("missing"), None);
+
+        let global = skeleton.bind_global_transforms();
+        let point = Point3::zero().apply_transform(global[child]).unwrap();
+        assert!(
+            point.approx_equal(Point3::new(1.0, 2.0, 0.0), TOLERANCE),
+            "сустав-потомок должен учитывать смещение родителя, но получено {}",
+            point
+        );
+    }
+
+    #[test]
+    fn test_skinning_matrices_identity_in_bind_pose() {
+        let mut skeleton = Skeleton::new();
+        let root = skeleton.add_joint("root", None, Transform3D::translation(0.0, 1.0, 0.0));
+        skeleton.add_joint("child", Some(root), Transform3D::rotation_z_deg(30.0));
+
+        for matrix in skeleton.skinning_matrices() {
+            let point = Point3::new(1.0, 2.0, 3.0).apply_transform(matrix).unwrap();
+            assert!(
+                point.approx_equal(Point3::new(1.0, 2.0, 3.0), TOLERANCE),
+                "в исходной позе скиннинг не должен двигать вершины"
+            );
+        }
+
+        skeleton.set_pose(root, Transform3D::translation(0.0, 3.0, 0.0));
+        let matrices = skeleton.skinning_matrices();
+        let point = Point3::zero().apply_transform(matrices[1]).unwrap();
+        assert!(
+            point.approx_equal(Point3::new(0.0, 2.0, 0.0), TOLERANCE),
+            "смещение родителя должно передаваться потомку, но получено {}",
+            point
+        );
+
+        skeleton.reset_pose();
+        assert_eq!(
+            skeleton.get_joint(root).pose_transform,
+            skeleton.get_joint(root).bind_transform
+        );
+    }
+}
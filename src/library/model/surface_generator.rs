@@ -1,4 +1,10 @@
-use crate::{Mesh, Point3, Polygon};
+use crate::library::primitives::bezier_point;
+use crate::{
+    LineStyle, Material, Mesh, Model, Palette, Point3, Polygon, Polyline3, TextureBlendMode, UVec3,
+    Vec3,
+};
+use image::DynamicImage;
+use std::collections::HashMap;
 
 #[derive(Default, Debug, Clone, Copy, PartialEq)]
 pub enum SurfaceFunction {
@@ -91,6 +97,280 @@ impl SurfaceFunction {
         // Используем from_polygons, которая сама сгенерирует нормали и текстуры
         Mesh::from_polygons(vertices, polygons)
     }
+
+    /// Модель графика функции, раскрашенная палитрой `palette` по высоте.
+    ///
+    /// Сетка та же, что у [`Self::generate_surface_mesh`], но текстурная координата `u`
+    /// каждой вершины - её высота, нормированная от минимальной (0) до максимальной (1),
+    /// а текстура материала - градиент палитры.
+    pub fn generate_height_colored_model(
+        &self,
+        x_range: (f32, f32),
+        y_range: (f32, f32),
+        divisions: (usize, usize),
+        palette: Palette,
+    ) -> Model {
+        let mesh = self.generate_surface_mesh(x_range, y_range, divisions);
+        let vertexes: Vec<Point3> = mesh.get_local_vertex_iter().collect();
+        let (min, max) = vertexes
+            .iter()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), vertex| {
+                (min.min(vertex.z), max.max(vertex.z))
+            });
+        let height = (max - min).max(f32::EPSILON);
+        let texture_coords = vertexes
+            .iter()
+            .map(|vertex| (((vertex.z - min) / height).clamp(0.0, 1.0), 0.5))
+            .collect();
+        let normals = mesh
+            .get_local_normals_iter()
+            .map(|normals| normals.collect());
+
+        let mut model =
+            Model::from_mesh(mesh.with_vertex_attributes(vertexes, normals, Some(texture_coords)));
+        model.material = Material {
+            texture: Some(palette.texture(256)),
+            blend_mode: TextureBlendMode::Replace,
+            ..Material::default()
+        };
+        model
+    }
+}
+
+// --------------------------------------------------
+// Изолинии
+// --------------------------------------------------
+
+/// Ребро сетки графика: `(i, j, false)` - от узла `(i, j)` к `(i + 1, j)`,
+/// `(i, j, true)` - от `(i, j)` к `(i, j + 1)`.
+type GridEdge = (usize, usize, bool);
+
+impl SurfaceFunction {
+    /// Изолинии `z = level` графика для каждого уровня из `levels`.
+    ///
+    /// Сетка та же, что и у [`Self::generate_surface_mesh`] с теми же параметрами, изолинии
+    /// строятся по ней методом marching squares и лежат на гранях графика. Каждая связная
+    /// линия возвращается отдельной ломаной, замкнутые линии повторяют первую точку в конце.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::{LineStyle, SurfaceFunction};
+    ///
+    /// // окружности x² + y² = 1 и x² + y² = 2
+    /// let isolines = SurfaceFunction::Paraboloid.generate_isolines(
+    ///     (-2.0, 2.0),
+    ///     (-2.0, 2.0),
+    ///     (20, 20),
+    ///     &[1.0, 2.0],
+    ///     LineStyle::default(),
+    /// );
+    /// assert_eq!(isolines.len(), 2);
+    /// ```
+    pub fn generate_isolines(
+        &self,
+        x_range: (f32, f32),
+        y_range: (f32, f32),
+        divisions: (usize, usize),
+        levels: &[f32],
+        style: LineStyle,
+    ) -> Vec<Polyline3> {
+        let (x0, x1) = x_range;
+        let (y0, y1) = y_range;
+        let (nx, ny) = divisions;
+        let dx = (x1 - x0) / nx as f32;
+        let dy = (y1 - y0) / ny as f32;
+
+        // значения в узлах сетки, как у вершин generate_surface_mesh
+        let node = |i: usize, j: usize| -> Point3 {
+            let (x, y) = (x0 + i as f32 * dx, y0 + j as f32 * dy);
+            let z = self.evaluate(x, y);
+            Point3::new(x, y, if z.is_finite() { z } else { 0.0 })
+        };
+        let nodes: Vec<Point3> = (0..=ny)
+            .flat_map(|j| (0..=nx).map(move |i| (i, j)))
+            .map(|(i, j)| node(i, j))
+            .collect();
+        let value = |i: usize, j: usize| nodes[j * (nx + 1) + i].z;
+
+        let mut polylines = Vec::new();
+        for &level in levels {
+            // точка пересечения ребра с уровнем
+            let crossing = |(i, j, vertical): GridEdge| -> Point3 {
+                let a = nodes[j * (nx + 1) + i];
+                let b = if vertical {
+                    nodes[(j + 1) * (nx + 1) + i]
+                } else {
+                    nodes[j * (nx + 1) + i + 1]
+                };
+                let t = ((level - a.z) / (b.z - a.z)).clamp(0.0, 1.0);
+                Point3::new(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t, level)
+            };
+
+            let mut segments = Vec::new();
+            for j in 0..ny {
+                for i in 0..nx {
+                    let corners = [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)];
+                    let above = corners.map(|(i, j)| value(i, j) >= level);
+                    // рёбра ячейки: edges[k] соединяет углы k и k + 1
+                    let edges: [GridEdge; 4] = [
+                        (i, j, false),
+                        (i + 1, j, true),
+                        (i, j + 1, false),
+                        (i, j, true),
+                    ];
+                    let crossed: Vec<usize> =
+                        (0..4).filter(|&k| above[k] != above[(k + 1) % 4]).collect();
+
+                    match crossed.len() {
+                        2 => segments.push((edges[crossed[0]], edges[crossed[1]])),
+                        4 => {
+                            // седловая ячейка: отсекаются углы, не совпадающие с центром
+                            let center =
+                                corners.iter().map(|&(i, j)| value(i, j)).sum::<f32>() / 4.0;
+                            for k in (0..4).filter(|&k| above[k] != (center >= level)) {
+                                segments.push((edges[(k + 3) % 4], edges[k]));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+
+            polylines.extend(chain_segments(&segments).into_iter().map(|chain| {
+                Polyline3::from_points(chain.into_iter().map(crossing).collect(), style)
+            }));
+        }
+        polylines
+    }
+}
+
+/// Собрать отрезки между рёбрами сетки в связные цепочки.
+///
+/// Каждое ребро сетки принадлежит не более чем двум ячейкам, поэтому цепочки - либо
+/// незамкнутые пути, либо циклы.
+fn chain_segments(segments: &[(GridEdge, GridEdge)]) -> Vec<Vec<GridEdge>> {
+    let mut neighbours: HashMap<GridEdge, Vec<GridEdge>> = HashMap::new();
+    for &(a, b) in segments {
+        neighbours.entry(a).or_default().push(b);
+        neighbours.entry(b).or_default().push(a);
+    }
+
+    // сначала незамкнутые пути от концов, затем оставшиеся циклы
+    let mut starts: Vec<GridEdge> = segments.iter().flat_map(|&(a, b)| [a, b]).collect();
+    starts.sort_by_key(|edge| neighbours[edge].len() != 1);
+
+    let mut chains = Vec::new();
+    for start in starts {
+        if neighbours[&start].is_empty() {
+            continue;
+        }
+        let mut chain = vec![start];
+        let mut current = start;
+        while let Some(next) = neighbours.get_mut(&current).and_then(|next| next.pop()) {
+            // убрать обратную связь
+            let back = neighbours.get_mut(&next).unwrap();
+            let position = back.iter().position(|&edge| edge == current).unwrap();
+            back.swap_remove(position);
+            chain.push(next);
+            current = next;
+        }
+        chains.push(chain);
+    }
+    chains
+}
+
+impl Mesh {
+    /// Создать ландшафт по карте высот.
+    ///
+    /// Каждый пиксель `image` становится вершиной сетки в плоскости XZ, высота вершины
+    /// по оси y - яркость пикселя, умноженная на `height_scale` (чёрный - 0, белый - `height_scale`).
+    /// `world_size` - размеры ландшафта по осям x и z, ландшафт центрирован в начале координат.
+    /// Текстурные координаты растягивают текстуру на весь ландшафт, нормали вычисляются
+    /// по разностям высот соседних пикселей.
+    pub fn from_heightmap(image: &DynamicImage, world_size: (f32, f32), height_scale: f32) -> Mesh {
+        let heights = image.to_luma16();
+        let (width, depth) = (heights.width() as usize, heights.height() as usize);
+        assert!(
+            width >= 2 && depth >= 2,
+            "карта высот {}x{} должна быть хотя бы 2x2 пикселя",
+            width,
+            depth
+        );
+
+        let height = |i: usize, j: usize| -> f32 {
+            heights.get_pixel(i as u32, j as u32).0[0] as f32 / u16::MAX as f32 * height_scale
+        };
+        let (size_x, size_z) = world_size;
+        let (dx, dz) = (size_x / (width - 1) as f32, size_z / (depth - 1) as f32);
+
+        let mut vertexes = Vec::with_capacity(width * depth);
+        let mut normals = Vec::with_capacity(width * depth);
+        let mut texture_coords = Vec::with_capacity(width * depth);
+        for j in 0..depth {
+            for i in 0..width {
+                vertexes.push(Point3::new(
+                    i as f32 * dx - size_x / 2.0,
+                    height(i, j),
+                    j as f32 * dz - size_z / 2.0,
+                ));
+                texture_coords.push((i as f32 / (width - 1) as f32, j as f32 / (depth - 1) as f32));
+
+                // центральные разности (односторонние на краях)
+                let (left, right) = (i.saturating_sub(1), (i + 1).min(width - 1));
+                let (back, front) = (j.saturating_sub(1), (j + 1).min(depth - 1));
+                let slope_x = (height(right, j) - height(left, j)) / ((right - left) as f32 * dx);
+                let slope_z = (height(i, front) - height(i, back)) / ((front - back) as f32 * dz);
+                normals.push(
+                    Vec3::new(-slope_x, 1.0, -slope_z)
+                        .normalize()
+                        .unwrap_or(UVec3::up()),
+                );
+            }
+        }
+
+        // треугольники против часовой стрелки, если смотреть сверху
+        let idx = |i: usize, j: usize| -> usize { j * width + i };
+        let mut polygons = Vec::with_capacity((width - 1) * (depth - 1) * 2);
+        for j in 0..depth - 1 {
+            for i in 0..width - 1 {
+                polygons.push(Polygon::triangle(
+                    idx(i, j),
+                    idx(i, j + 1),
+                    idx(i + 1, j + 1),
+                ));
+                polygons.push(Polygon::triangle(
+                    idx(i, j),
+                    idx(i + 1, j + 1),
+                    idx(i + 1, j),
+                ));
+            }
+        }
+
+        Mesh::new(vertexes, polygons, Some(normals), Some(texture_coords))
+    }
+}
+
+impl Mesh {
+    /// Создать Mesh из бикубического патча Безье.
+    ///
+    /// `control_points[j][i]` - сетка 4x4 контрольных точек, `i` идёт вдоль параметра `u`,
+    /// `j` - вдоль `v`. Патч проходит через 4 угловые точки сетки. `resolution` - количество
+    /// разбиений по каждому параметру, от него зависит гладкость результата.
+    pub fn from_bezier_patch(control_points: &[[Point3; 4]; 4], resolution: usize) -> Mesh {
+        Mesh::from_parametric(
+            |u, v| {
+                // сначала кривые вдоль u для каждого ряда, затем кривая вдоль v по их точкам
+                let column = control_points.map(|row| Point3::from(bezier_point(&row, u)));
+                Point3::from(bezier_point(&column, v))
+            },
+            (0.0, 1.0),
+            (0.0, 1.0),
+            resolution,
+            resolution,
+            false,
+            false,
+        )
+    }
 }
 
 #[cfg(test)]
@@ -121,4 +401,140 @@ mod test_surface_generator {
             );
         }
     }
+
+    #[test]
+    fn test_height_colored_model() {
+        use crate::{Palette, TextureBlendMode};
+        use egui::Color32;
+
+        let model = SurfaceFunction::Paraboloid.generate_height_colored_model(
+            (-1.0, 1.0),
+            (-1.0, 1.0),
+            (2, 2),
+            Palette::Viridis,
+        );
+        assert_eq!(model.material.blend_mode, TextureBlendMode::Replace);
+        // центр графика - минимум, углы - максимум
+        assert_eq!(model.mesh.get_texture_coord(4), Some((0.0, 0.5)));
+        assert_eq!(model.mesh.get_texture_coord(0), Some((1.0, 0.5)));
+        assert_eq!(
+            model.material.get_uv_color(0.0, 0.5),
+            Color32::from_rgb(68, 1, 84)
+        );
+    }
+
+    #[test]
+    fn test_isolines_follow_level() {
+        use crate::LineStyle;
+
+        let isolines = SurfaceFunction::Paraboloid.generate_isolines(
+            (-2.0, 2.0),
+            (-2.0, 2.0),
+            (16, 16),
+            &[1.0, 10.0],
+            LineStyle::default(),
+        );
+        // уровень 10 выше графика, уровень 1 - одна замкнутая окружность радиуса 1
+        assert_eq!(isolines.len(), 1);
+        let points = isolines[0].points();
+        assert_eq!(
+            points.first(),
+            points.last(),
+            "окружность должна быть замкнута"
+        );
+        for point in points {
+            assert_eq!(point.z, 1.0);
+            let radius = (point.x * point.x + point.y * point.y).sqrt();
+            assert!(
+                (radius - 1.0).abs() < 0.05,
+                "точка {} не на окружности",
+                point
+            );
+        }
+
+        // седло: две ветви гиперболы x² - y² = 1, каждая от края до края
+        let isolines = SurfaceFunction::Saddle.generate_isolines(
+            (-2.0, 2.0),
+            (-2.0, 2.0),
+            (16, 16),
+            &[1.0],
+            LineStyle::default(),
+        );
+        assert_eq!(isolines.len(), 2);
+    }
+
+    #[test]
+    fn test_from_heightmap() {
+        use crate::{Mesh, UVec3};
+        use image::{DynamicImage, GrayImage, Luma};
+
+        // плоскость с одним белым пикселем посередине
+        let mut image = GrayImage::new(3, 3);
+        image.put_pixel(1, 1, Luma([255]));
+        let mesh = Mesh::from_heightmap(&DynamicImage::ImageLuma8(image), (4.0, 2.0), 10.0);
+
+        assert_eq!(mesh.vertex_count(), 9);
+        assert_eq!(mesh.polygon_count(), 8);
+        let center = mesh.get_local_vertex(4);
+        assert!(
+            center.approx_equal(crate::Point3::new(0.0, 10.0, 0.0), 1e-4),
+            "центральная вершина должна подняться на height_scale, но получено {}",
+            center
+        );
+        let corner = mesh.get_local_vertex(0);
+        assert!(corner.approx_equal(crate::Point3::new(-2.0, 0.0, -1.0), 1e-4));
+
+        assert_eq!(mesh.get_texture_coord(8), Some((1.0, 1.0)));
+        // на вершине холма нормаль смотрит вверх, все грани лицом вверх
+        assert!(
+            mesh.get_local_normal(4)
+                .unwrap()
+                .approx_equal(UVec3::up(), 1e-4)
+        );
+        for i in 0..mesh.polygon_count() {
+            let normal = mesh.get_global_polygon_normal(i).unwrap();
+            assert!(normal.y > 0.0, "грань {} должна смотреть вверх", i);
+        }
+    }
+
+    #[test]
+    fn test_bezier_patch_interpolates_corners() {
+        use crate::{Mesh, Point3};
+
+        // плоская сетка с поднятыми центральными точками
+        let mut control_points = [[Point3::zero(); 4]; 4];
+        for (j, row) in control_points.iter_mut().enumerate() {
+            for (i, point) in row.iter_mut().enumerate() {
+                let height = if (1..=2).contains(&i) && (1..=2).contains(&j) {
+                    1.0
+                } else {
+                    0.0
+                };
+                *point = Point3::new(i as f32, height, j as f32);
+            }
+        }
+        let mesh = Mesh::from_bezier_patch(&control_points, 6);
+
+        assert_eq!(mesh.vertex_count(), 7 * 7);
+        assert_eq!(mesh.polygon_count(), 6 * 6 * 2);
+        assert!(
+            mesh.get_local_vertex(0)
+                .approx_equal(control_points[0][0], 1e-6)
+        );
+        assert!(
+            mesh.get_local_vertex(6)
+                .approx_equal(control_points[0][3], 1e-6)
+        );
+        assert!(
+            mesh.get_local_vertex(48)
+                .approx_equal(control_points[3][3], 1e-6)
+        );
+        // центр патча поднят, но ниже контрольных точек
+        let center = mesh.get_local_vertex(24);
+        assert!(
+            center.y > 0.3 && center.y < 1.0,
+            "центр патча должен быть между 0 и 1 по высоте, но получено {}",
+            center
+        );
+    }
 }
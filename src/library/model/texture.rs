@@ -1,15 +1,30 @@
 //! Объявление и реализация текстуры для 3D модели
 
-use crate::library::utils;
-use egui::Color32;
+use crate::library::{state_hash::StateHash, utils};
+use egui::{Color32, Pos2, Rect};
 use image::{DynamicImage, RgbImage};
+use std::hash::{DefaultHasher, Hasher};
+use std::sync::Arc;
 
 /// Текстура модели.
 ///
 /// Благодаря текстуре модель может быть обёрнута в какую-то картинку вместо сплошного цвета.
+///
+/// Текстура может быть прямоугольной областью большой картинки (атласа), см. [`Texture::sub_region`].
+/// Картинка при этом не копируется, а разделяется между всеми её областями.
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "TextureData", into = "TextureData")
+)]
 pub struct Texture {
-    image: RgbImage,
+    image: Arc<RgbImage>,
+    /// Хэш размеров и пикселей картинки. Картинка не меняется после создания текстуры,
+    /// поэтому хэш считается один раз, а не при каждом вычислении [`crate::Scene::state_hash`].
+    image_hash: u64,
+    /// Область картинки в UV-координатах всей картинки, которую занимает текстура.
+    region: Rect,
 }
 
 impl Texture {
@@ -20,7 +35,62 @@ impl Texture {
     pub fn new(image: DynamicImage) -> Self {
         // в RgbImage
         let image = image.to_rgb8();
-        Self { image }
+        let mut hasher = DefaultHasher::new();
+        hasher.write_u32(image.width());
+        hasher.write_u32(image.height());
+        hasher.write(image.as_raw());
+        Self {
+            image: Arc::new(image),
+            image_hash: hasher.finish(),
+            region: Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0)),
+        }
+    }
+
+    /// Получить текстуру из прямоугольной области `uv_rect` этой текстуры.
+    ///
+    /// `uv_rect` задаётся в UV-координатах этой текстуры и обрезается по её границам. UV-координаты
+    /// [0, 1] новой текстуры переводятся в эту область при чтении цвета, картинка не копируется.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use egui::{Color32, Pos2, Rect};
+    /// use g3d::Texture;
+    /// use image::{DynamicImage, Rgb, RgbImage};
+    ///
+    /// // атлас из двух спрайтов: красного слева и синего справа
+    /// let atlas = Texture::new(DynamicImage::ImageRgb8(RgbImage::from_fn(4, 2, |x, _| {
+    ///     if x < 2 { Rgb([255, 0, 0]) } else { Rgb([0, 0, 255]) }
+    /// })));
+    /// let right = atlas.sub_region(Rect::from_min_max(Pos2::new(0.5, 0.0), Pos2::new(1.0, 1.0)));
+    ///
+    /// assert_eq!(right.get_pixel_color(0.0, 0.0), Color32::BLUE);
+    /// assert_eq!(right.get_pixel_color(1.0, 1.0), Color32::BLUE);
+    /// assert!(right.shares_image_with(&atlas));
+    /// ```
+    pub fn sub_region(&self, uv_rect: Rect) -> Self {
+        let unit = Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0));
+        let uv_rect = uv_rect.intersect(unit);
+        let to_image = |p: Pos2| {
+            Pos2::new(
+                self.region.min.x + p.x * self.region.width(),
+                self.region.min.y + p.y * self.region.height(),
+            )
+        };
+        Self {
+            image: Arc::clone(&self.image),
+            image_hash: self.image_hash,
+            region: Rect::from_min_max(to_image(uv_rect.min), to_image(uv_rect.max)),
+        }
+    }
+
+    /// Область всей картинки в её UV-координатах, которую занимает текстура.
+    pub fn region(&self) -> Rect {
+        self.region
+    }
+
+    /// Является ли текстура областью той же картинки, что и `other`?
+    pub fn shares_image_with(&self, other: &Texture) -> bool {
+        Arc::ptr_eq(&self.image, &other.image)
     }
 
     /// Получить цвет текстуры в пикселе по UV-координатам.
@@ -33,7 +103,10 @@ impl Texture {
         utils::pixel_to_color(*self.image.get_pixel(x, y))
     }
 
-    /// Преобразовать UV-координаты в целочисленные.
+    /// Преобразовать UV-координаты в целочисленные координаты пикселя картинки.
+    ///
+    /// Крайние UV-координаты попадают в крайние пиксели области, так что соседние области
+    /// атласа не просвечивают.
     #[inline]
     fn transform_uv(&self, u: f32, v: f32) -> (u32, u32) {
         debug_assert!(
@@ -47,8 +120,142 @@ impl Texture {
             v
         );
 
-        let x = (u * (self.image.width() - 1) as f32).round() as u32;
-        let y = (v * (self.image.height() - 1) as f32).round() as u32;
+        let to_pixel = |t: f32, min: f32, max: f32, size: u32| {
+            let first = ((min * size as f32).floor() as u32).min(size - 1);
+            let last = ((max * size as f32).ceil() as u32).clamp(first + 1, size) - 1;
+            first + (t * (last - first) as f32).round() as u32
+        };
+        let x = to_pixel(u, self.region.min.x, self.region.max.x, self.image.width());
+        let y = to_pixel(v, self.region.min.y, self.region.max.y, self.image.height());
         (x, y)
     }
 }
+
+impl StateHash for Texture {
+    fn hash_state<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.image_hash);
+        for value in [self.region.min, self.region.max] {
+            value.x.hash_state(state);
+            value.y.hash_state(state);
+        }
+    }
+}
+
+/// Сериализуемое представление текстуры: размеры и RGB пиксели по строкам.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct TextureData {
+    width: u32,
+    height: u32,
+    rgb: Vec<u8>,
+    /// Область картинки `[min_u, min_v, max_u, max_v]`.
+    #[serde(default = "TextureData::full_region")]
+    region: [f32; 4],
+}
+
+#[cfg(feature = "serde")]
+impl TextureData {
+    fn full_region() -> [f32; 4] {
+        [0.0, 0.0, 1.0, 1.0]
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<Texture> for TextureData {
+    fn from(texture: Texture) -> Self {
+        let region = texture.region;
+        Self {
+            width: texture.image.width(),
+            height: texture.image.height(),
+            rgb: Arc::unwrap_or_clone(texture.image).into_raw(),
+            region: [region.min.x, region.min.y, region.max.x, region.max.y],
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<TextureData> for Texture {
+    type Error = String;
+
+    fn try_from(data: TextureData) -> Result<Self, Self::Error> {
+        let (width, height) = (data.width, data.height);
+        let [min_u, min_v, max_u, max_v] = data.region;
+        let texture = RgbImage::from_raw(width, height, data.rgb)
+            .map(|image| Self::new(DynamicImage::ImageRgb8(image)))
+            .ok_or_else(|| format!("данных недостаточно для текстуры {}x{}", width, height))?;
+        Ok(texture.sub_region(Rect::from_min_max(
+            Pos2::new(min_u, min_v),
+            Pos2::new(max_u, max_v),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod texture_tests {
+    use super::*;
+
+    /// Атлас 4x4 из четырёх спрайтов 2x2 разных цветов.
+    fn atlas() -> Texture {
+        Texture::new(DynamicImage::ImageRgb8(RgbImage::from_fn(4, 4, |x, y| {
+            image::Rgb([if x < 2 { 255 } else { 0 }, if y < 2 { 255 } else { 0 }, 0])
+        })))
+    }
+
+    #[test]
+    fn test_sub_region_does_not_bleed() {
+        let atlas = atlas();
+        let quarter = |x: f32, y: f32| {
+            atlas.sub_region(Rect::from_min_size(Pos2::new(x, y), egui::vec2(0.5, 0.5)))
+        };
+        for (region, expected) in [
+            (quarter(0.0, 0.0), Color32::from_rgb(255, 255, 0)),
+            (quarter(0.5, 0.0), Color32::from_rgb(0, 255, 0)),
+            (quarter(0.0, 0.5), Color32::from_rgb(255, 0, 0)),
+            (quarter(0.5, 0.5), Color32::from_rgb(0, 0, 0)),
+        ] {
+            for (u, v) in [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0), (0.5, 0.5)] {
+                assert_eq!(
+                    region.get_pixel_color(u, v),
+                    expected,
+                    "{:?}",
+                    region.region()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_nested_sub_region() {
+        let atlas = atlas();
+        let right = atlas.sub_region(Rect::from_min_max(Pos2::new(0.5, 0.0), Pos2::new(1.0, 1.0)));
+        let bottom_right =
+            right.sub_region(Rect::from_min_max(Pos2::new(0.0, 0.5), Pos2::new(2.0, 1.0)));
+
+        // область обрезается по границам текстуры и задаётся относительно неё
+        assert_eq!(
+            bottom_right.region(),
+            Rect::from_min_max(Pos2::new(0.5, 0.5), Pos2::new(1.0, 1.0))
+        );
+        assert_eq!(bottom_right.get_pixel_color(0.0, 0.0), Color32::BLACK);
+        assert!(bottom_right.shares_image_with(&atlas));
+    }
+
+    #[test]
+    fn test_state_hash_follows_image_content() {
+        let hash = |texture: &Texture| {
+            let mut state = DefaultHasher::new();
+            texture.hash_state(&mut state);
+            state.finish()
+        };
+        let atlas = atlas();
+
+        // хэш зависит от пикселей, а не от того, та же ли это картинка в памяти
+        assert_eq!(hash(&atlas), hash(&self::atlas()));
+        let mut image = atlas.image.as_ref().clone();
+        image.put_pixel(3, 3, image::Rgb([0, 0, 255]));
+        assert_ne!(hash(&atlas), hash(&Texture::new(image.into())));
+
+        let half = Rect::from_min_max(Pos2::ZERO, Pos2::new(0.5, 1.0));
+        assert_ne!(hash(&atlas), hash(&atlas.sub_region(half)));
+    }
+}
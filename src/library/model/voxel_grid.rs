@@ -0,0 +1,353 @@
+//! Воксельная сетка и построение Mesh'а по ней.
+
+use crate::{Aabb, Mesh, Point3, Polygon, Vec3};
+
+/// Способ построения Mesh'а по воксельной сетке.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoxelMeshing {
+    /// Кубики, как в Minecraft. Видимые грани соседних вокселей склеиваются в прямоугольники
+    /// (greedy meshing), внутренние грани не строятся.
+    #[default]
+    Blocks,
+    /// Гладкая поверхность уровня [`VoxelGrid::OCCUPIED_LEVEL`] через marching cubes,
+    /// см. [`Mesh::from_implicit`].
+    Surface,
+}
+
+/// Плотная трёхмерная сетка значений вокселей.
+///
+/// Значение вокселя лежит в `[0, 1]`: 0 - пусто, 1 - полностью заполнен. Воксель считается
+/// занятым, если его значение не меньше [`Self::OCCUPIED_LEVEL`]. Воксель `(i, j, k)` занимает
+/// куб от `origin + (i, j, k) * voxel_size` до `origin + (i + 1, j + 1, k + 1) * voxel_size`.
+///
+/// # Examples
+/// ```rust
+/// use g3d::{VoxelGrid, VoxelMeshing};
+///
+/// // столбик из трёх вокселей
+/// let mut grid = VoxelGrid::new((1, 3, 1), 1.0);
+/// for j in 0..3 {
+///     grid.set_occupied(0, j, 0, true);
+/// }
+/// let mesh = grid.to_mesh(VoxelMeshing::Blocks);
+/// // грани склеены: 6 прямоугольников по 2 треугольника
+/// assert_eq!(mesh.polygon_count(), 12);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoxelGrid {
+    /// Количество вокселей по осям x, y, z.
+    size: (usize, usize, usize),
+    /// Значения вокселей, `x` меняется быстрее всего.
+    values: Vec<f32>,
+    /// Минимальный угол сетки в локальных координатах.
+    pub origin: Point3,
+    /// Длина ребра вокселя.
+    pub voxel_size: f32,
+}
+
+impl VoxelGrid {
+    /// Значение, начиная с которого воксель считается занятым.
+    pub const OCCUPIED_LEVEL: f32 = 0.5;
+
+    /// Создать пустую сетку `size` вокселей с ребром `voxel_size` и углом в начале координат.
+    pub fn new(size: (usize, usize, usize), voxel_size: f32) -> Self {
+        debug_assert!(voxel_size > 0.0, "размер вокселя должен быть положительным");
+        Self {
+            size,
+            values: vec![0.0; size.0 * size.1 * size.2],
+            origin: Point3::zero(),
+            voxel_size,
+        }
+    }
+
+    /// Количество вокселей по осям x, y, z.
+    pub fn size(&self) -> (usize, usize, usize) {
+        self.size
+    }
+
+    /// Ограничивающий параллелепипед сетки.
+    pub fn bounds(&self) -> Aabb {
+        let (nx, ny, nz) = self.size;
+        let extent = Vec3::new(nx as f32, ny as f32, nz as f32) * self.voxel_size;
+        Aabb::new(self.origin, self.origin + extent)
+    }
+
+    /// Значение вокселя `(i, j, k)`, за пределами сетки - 0.
+    pub fn get(&self, i: usize, j: usize, k: usize) -> f32 {
+        self.index(i, j, k)
+            .map(|index| self.values[index])
+            .unwrap_or(0.0)
+    }
+
+    /// Задать значение вокселя `(i, j, k)`.
+    pub fn set(&mut self, i: usize, j: usize, k: usize, value: f32) {
+        debug_assert!(
+            (0.0..=1.0).contains(&value),
+            "значение вокселя должно быть в [0, 1]"
+        );
+        let index = self
+            .index(i, j, k)
+            .unwrap_or_else(|| panic!("воксель ({}, {}, {}) вне сетки {:?}", i, j, k, self.size));
+        self.values[index] = value;
+    }
+
+    /// Занят ли воксель `(i, j, k)`, за пределами сетки - нет.
+    pub fn is_occupied(&self, i: usize, j: usize, k: usize) -> bool {
+        self.get(i, j, k) >= Self::OCCUPIED_LEVEL
+    }
+
+    /// Занять (значение 1) или освободить (значение 0) воксель `(i, j, k)`.
+    pub fn set_occupied(&mut self, i: usize, j: usize, k: usize, occupied: bool) {
+        self.set(i, j, k, if occupied { 1.0 } else { 0.0 });
+    }
+
+    /// Заполнить сетку значениями функции `f` от центров вокселей.
+    ///
+    /// Значения обрезаются до `[0, 1]`.
+    pub fn fill(&mut self, f: impl Fn(Point3) -> f32) {
+        let (nx, ny, nz) = self.size;
+        for k in 0..nz {
+            for j in 0..ny {
+                for i in 0..nx {
+                    let value = f(self.voxel_center(i, j, k));
+                    self.values[(k * ny + j) * nx + i] = if value.is_nan() {
+                        0.0
+                    } else {
+                        value.clamp(0.0, 1.0)
+                    };
+                }
+            }
+        }
+    }
+
+    /// Центр вокселя `(i, j, k)`.
+    pub fn voxel_center(&self, i: usize, j: usize, k: usize) -> Point3 {
+        self.origin + Vec3::new(i as f32 + 0.5, j as f32 + 0.5, k as f32 + 0.5) * self.voxel_size
+    }
+
+    /// Построить Mesh по сетке способом `meshing`.
+    pub fn to_mesh(&self, meshing: VoxelMeshing) -> Mesh {
+        match meshing {
+            VoxelMeshing::Blocks => self.greedy_mesh(),
+            VoxelMeshing::Surface => self.surface_mesh(),
+        }
+    }
+
+    // --------------------------------------------------
+    // Вспомогательные методы
+    // --------------------------------------------------
+
+    fn index(&self, i: usize, j: usize, k: usize) -> Option<usize> {
+        let (nx, ny, nz) = self.size;
+        (i < nx && j < ny && k < nz).then(|| (k * ny + j) * nx + i)
+    }
+
+    /// Занятость вокселя с координатами, которые могут выходить за пределы сетки.
+    fn is_occupied_at(&self, cell: [isize; 3]) -> bool {
+        cell.iter().all(|&c| c >= 0)
+            && self.is_occupied(cell[0] as usize, cell[1] as usize, cell[2] as usize)
+    }
+
+    /// Кубики со склеенными гранями.
+    ///
+    /// Для каждой оси `d` перебираются плоскости между слоями вокселей. На плоскости строится
+    /// маска видимых граней (занятый воксель с одной стороны, пустой с другой), затем маска
+    /// жадно покрывается максимальными прямоугольниками одного направления.
+    fn greedy_mesh(&self) -> Mesh {
+        let dims = [self.size.0, self.size.1, self.size.2];
+        let mut vertexes = Vec::new();
+        let mut normals = Vec::new();
+        let mut texture_coords = Vec::new();
+        let mut polygons = Vec::new();
+
+        for d in 0..3 {
+            // оси плоскости, e_u x e_v = e_d
+            let (u, v) = ((d + 1) % 3, (d + 2) % 3);
+            let mut mask = vec![0i8; dims[u] * dims[v]];
+
+            for layer in 0..=dims[d] {
+                // 1 - грань смотрит по оси d, -1 - против, 0 - грани нет
+                for b in 0..dims[v] {
+                    for a in 0..dims[u] {
+                        let mut cell = [0isize; 3];
+                        cell[u] = a as isize;
+                        cell[v] = b as isize;
+                        cell[d] = layer as isize;
+                        let front = self.is_occupied_at(cell);
+                        cell[d] -= 1;
+                        let back = self.is_occupied_at(cell);
+                        mask[b * dims[u] + a] = match (back, front) {
+                            (true, false) => 1,
+                            (false, true) => -1,
+                            _ => 0,
+                        };
+                    }
+                }
+
+                for b in 0..dims[v] {
+                    let mut a = 0;
+                    while a < dims[u] {
+                        let direction = mask[b * dims[u] + a];
+                        if direction == 0 {
+                            a += 1;
+                            continue;
+                        }
+
+                        // ширина вдоль u, затем высота вдоль v, пока вся строка совпадает
+                        let mut width = 1;
+                        while a + width < dims[u] && mask[b * dims[u] + a + width] == direction {
+                            width += 1;
+                        }
+                        let mut height = 1;
+                        while b + height < dims[v]
+                            && (0..width).all(|w| mask[(b + height) * dims[u] + a + w] == direction)
+                        {
+                            height += 1;
+                        }
+                        for h in 0..height {
+                            for w in 0..width {
+                                mask[(b + h) * dims[u] + a + w] = 0;
+                            }
+                        }
+
+                        // углы прямоугольника против часовой стрелки, если смотреть по оси d
+                        let corner = |du: usize, dv: usize| {
+                            let mut coords = [0.0; 3];
+                            coords[d] = layer as f32;
+                            coords[u] = (a + du) as f32;
+                            coords[v] = (b + dv) as f32;
+                            self.origin
+                                + Vec3::new(coords[0], coords[1], coords[2]) * self.voxel_size
+                        };
+                        let mut corners = [
+                            (corner(0, 0), (0.0, 0.0)),
+                            (corner(width, 0), (1.0, 0.0)),
+                            (corner(width, height), (1.0, 1.0)),
+                            (corner(0, height), (0.0, 1.0)),
+                        ];
+                        if direction < 0 {
+                            corners.reverse();
+                        }
+                        let mut axis = [0.0; 3];
+                        axis[d] = direction as f32;
+                        let normal = Vec3::new(axis[0], axis[1], axis[2]).normalize().unwrap();
+
+                        let first = vertexes.len();
+                        for (position, uv) in corners {
+                            vertexes.push(position);
+                            normals.push(normal);
+                            texture_coords.push(uv);
+                        }
+                        polygons.push(Polygon::triangle(first, first + 1, first + 2));
+                        polygons.push(Polygon::triangle(first, first + 2, first + 3));
+
+                        a += width;
+                    }
+                }
+            }
+        }
+
+        Mesh::new(vertexes, polygons, Some(normals), Some(texture_coords))
+    }
+
+    /// Гладкая поверхность по трилинейно интерполированным значениям в центрах вокселей.
+    fn surface_mesh(&self) -> Mesh {
+        let (nx, ny, nz) = self.size;
+        // значение в точке: трилинейная интерполяция между центрами вокселей
+        let sample = |p: Point3| -> f32 {
+            let local = (p - self.origin) * (1.0 / self.voxel_size);
+            let coords = [local.x - 0.5, local.y - 0.5, local.z - 0.5];
+            let base = coords.map(|c| c.floor());
+            let t = [
+                coords[0] - base[0],
+                coords[1] - base[1],
+                coords[2] - base[2],
+            ];
+            let value = |di: usize, dj: usize, dk: usize| {
+                let cell = [
+                    base[0] as isize + di as isize,
+                    base[1] as isize + dj as isize,
+                    base[2] as isize + dk as isize,
+                ];
+                if cell.iter().any(|&c| c < 0) {
+                    0.0
+                } else {
+                    self.get(cell[0] as usize, cell[1] as usize, cell[2] as usize)
+                }
+            };
+            let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+            let plane = |dk: usize| {
+                lerp(
+                    lerp(value(0, 0, dk), value(1, 0, dk), t[0]),
+                    lerp(value(0, 1, dk), value(1, 1, dk), t[0]),
+                    t[1],
+                )
+            };
+            lerp(plane(0), plane(1), t[2])
+        };
+
+        // на воксель вокруг сетки больше, чтобы поверхность замыкалась на границах
+        let margin = Vec3::new(1.0, 1.0, 1.0) * self.voxel_size;
+        let bounds = self.bounds();
+        let bounds = Aabb::new(bounds.min + margin * -1.0, bounds.max + margin);
+        let resolution = (nx.max(ny).max(nz) + 2) * 2;
+        Mesh::from_implicit(|p| Self::OCCUPIED_LEVEL - sample(p), bounds, resolution)
+    }
+}
+
+#[cfg(test)]
+mod voxel_grid_tests {
+    use super::*;
+
+    #[test]
+    fn test_greedy_mesh_merges_faces() {
+        // плита 4x1x3 - те же 6 прямоугольников, что и у одного вокселя
+        let mut grid = VoxelGrid::new((4, 1, 3), 0.5);
+        for k in 0..3 {
+            for i in 0..4 {
+                grid.set_occupied(i, 0, k, true);
+            }
+        }
+        let mesh = grid.to_mesh(VoxelMeshing::Blocks);
+        assert_eq!(mesh.polygon_count(), 12);
+
+        let bounds = Aabb::from_points(mesh.get_local_vertex_iter()).unwrap();
+        assert!(bounds.max.approx_equal(Point3::new(2.0, 0.5, 1.5), 1e-6));
+        // грани смотрят наружу
+        for i in 0..mesh.polygon_count() {
+            let normal = mesh.get_global_polygon_normal(i).unwrap();
+            let vertex = mesh.get_global_vertex(mesh.get_polygon(i).get_mesh_vertex_index(0));
+            let outward = vertex - bounds.center();
+            assert!(
+                Vec3::from(normal).dot(outward) > 0.0,
+                "грань {} смотрит внутрь",
+                i
+            );
+        }
+
+        // без углового вокселя: по 2 прямоугольника сверху, снизу и на двух сторонах у выреза,
+        // по одному на двух других сторонах
+        grid.set_occupied(0, 0, 0, false);
+        let mesh = grid.to_mesh(VoxelMeshing::Blocks);
+        assert_eq!(mesh.polygon_count(), 10 * 2);
+    }
+
+    #[test]
+    fn test_surface_mesh_encloses_voxels() {
+        let mut grid = VoxelGrid::new((3, 3, 3), 1.0);
+        grid.set_occupied(1, 1, 1, true);
+        let mesh = grid.to_mesh(VoxelMeshing::Surface);
+        assert!(mesh.polygon_count() > 0);
+
+        // поверхность вокруг центра единственного вокселя
+        let center = grid.voxel_center(1, 1, 1);
+        for vertex in mesh.get_local_vertex_iter() {
+            let distance = (vertex - center).length();
+            assert!(
+                distance > 0.1 && distance < 1.0,
+                "вершина {} далеко от вокселя",
+                vertex
+            );
+        }
+    }
+}
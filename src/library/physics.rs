@@ -0,0 +1,457 @@
+//! Простая физика твёрдых тел: гравитация, скорости и столкновения моделей.
+
+use crate::{Model, Point3, Transform3D, Vec3};
+
+/// Форма, по которой тело сталкивается с другими телами.
+///
+/// Центр формы совпадает с точкой отсчёта локальной системы координат модели.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Collider {
+    /// Шар радиуса `radius`.
+    Sphere { radius: f32 },
+    /// Параллелепипед, выровненный по глобальным осям, с половинами сторон `half_extents`.
+    ///
+    /// Не поворачивается вместе с моделью.
+    Aabb { half_extents: Vec3 },
+}
+
+impl Collider {
+    /// Наименьший шар с центром в точке отсчёта модели, содержащий её вершины.
+    pub fn bounding_sphere(model: &Model) -> Self {
+        let origin = model.get_position();
+        let radius = model
+            .mesh
+            .get_global_vertex_iter()
+            .map(|vertex| (vertex - origin).length())
+            .fold(0.0, f32::max);
+        Self::Sphere { radius }
+    }
+
+    /// Наименьший параллелепипед с центром в точке отсчёта модели, содержащий её вершины.
+    pub fn bounding_box(model: &Model) -> Self {
+        let origin = model.get_position();
+        let half_extents =
+            model
+                .mesh
+                .get_global_vertex_iter()
+                .fold(Vec3::zero(), |extents, vertex| {
+                    let offset = vertex - origin;
+                    Vec3::new(
+                        extents.x.max(offset.x.abs()),
+                        extents.y.max(offset.y.abs()),
+                        extents.z.max(offset.z.abs()),
+                    )
+                });
+        Self::Aabb { half_extents }
+    }
+
+    /// Половины размеров описанного параллелепипеда по глобальным осям.
+    fn half_extents(&self) -> Vec3 {
+        match *self {
+            Self::Sphere { radius } => Vec3::new(radius, radius, radius),
+            Self::Aabb { half_extents } => half_extents,
+        }
+    }
+}
+
+/// Состояние твёрдого тела, привязанного к модели.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RigidBody {
+    /// Индекс модели в срезе, передаваемом в [`PhysicsWorld::step`].
+    pub model_index: usize,
+    /// Форма тела для столкновений.
+    pub collider: Collider,
+    /// Скорость в **глобальных** координатах, единиц в секунду.
+    pub velocity: Vec3,
+    /// Угловая скорость в **глобальных** координатах: направление - ось вращения,
+    /// длина - скорость вращения в радианах в секунду.
+    pub angular_velocity: Vec3,
+    /// Масса тела. Тела с неположительной массой неподвижны.
+    pub mass: f32,
+    /// Упругость в диапазоне `[0, 1]`: 0 - тело не отскакивает, 1 - абсолютно упругий удар.
+    pub restitution: f32,
+}
+
+impl RigidBody {
+    /// Создать подвижное тело массы 1 для модели с индексом `model_index`.
+    pub fn new(model_index: usize, collider: Collider) -> Self {
+        Self {
+            model_index,
+            collider,
+            velocity: Vec3::zero(),
+            angular_velocity: Vec3::zero(),
+            mass: 1.0,
+            restitution: 0.5,
+        }
+    }
+
+    /// Создать неподвижное тело (например, пол или стену).
+    pub fn fixed(model_index: usize, collider: Collider) -> Self {
+        Self {
+            mass: 0.0,
+            ..Self::new(model_index, collider)
+        }
+    }
+
+    /// Подвижно ли тело.
+    pub fn is_dynamic(&self) -> bool {
+        self.mass > 0.0
+    }
+
+    /// Обратная масса, 0 для неподвижных тел.
+    fn inverse_mass(&self) -> f32 {
+        if self.is_dynamic() {
+            1.0 / self.mass
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Контакт двух форм.
+struct Contact {
+    /// Нормаль контакта от первого тела ко второму.
+    normal: Vec3,
+    /// Глубина взаимного проникновения.
+    penetration: f32,
+}
+
+/// Мир с твёрдыми телами, который двигает модели сцены.
+///
+/// Упрощённая модель: столкновения меняют только скорости тел (без вращения от удара),
+/// формы задаются шарами и выровненными по осям параллелепипедами.
+///
+/// # Examples
+/// ```rust
+/// use g3d::{Collider, Mesh, Model, PhysicsWorld, Point3, RigidBody};
+///
+/// let mut ball = Model::from_mesh(Mesh::icosahedron());
+/// ball.set_position(Point3::new(0.0, 5.0, 0.0));
+/// let mut models = vec![ball];
+///
+/// let mut world = PhysicsWorld {
+///     ground_height: Some(0.0),
+///     ..Default::default()
+/// };
+/// world.bodies.push(RigidBody::new(0, Collider::bounding_sphere(&models[0])));
+///
+/// for _ in 0..200 {
+///     world.step(&mut models, 1.0 / 60.0);
+/// }
+/// // шар упал на землю и не провалился сквозь неё
+/// let y = models[0].get_position().y;
+/// assert!(0.0 < y && y < 5.0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhysicsWorld {
+    /// Ускорение свободного падения в **глобальных** координатах.
+    pub gravity: Vec3,
+    /// Тела мира.
+    pub bodies: Vec<RigidBody>,
+    /// Высота горизонтальной плоскости земли, если она есть.
+    pub ground_height: Option<f32>,
+    /// Упругость земли, см. [`RigidBody::restitution`].
+    pub ground_restitution: f32,
+}
+
+impl Default for PhysicsWorld {
+    fn default() -> Self {
+        Self {
+            gravity: Vec3::new(0.0, -9.81, 0.0),
+            bodies: Vec::new(),
+            ground_height: None,
+            ground_restitution: 0.5,
+        }
+    }
+}
+
+impl PhysicsWorld {
+    /// Создать пустой мир с земной гравитацией и без земли.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Продвинуть симуляцию на `dt` секунд.
+    ///
+    /// Скорости интегрируются явным методом Эйлера, затем столкновения разрешаются импульсами
+    /// с выталкиванием тел друг из друга. Положение и поворот записываются в локальные
+    /// системы координат моделей `models`.
+    pub fn step(&mut self, models: &mut [Model], dt: f32) {
+        debug_assert!(dt >= 0.0, "шаг времени {} не может быть отрицательным", dt);
+        self.bodies
+            .retain(|body| body.model_index < models.len() || !body.is_dynamic());
+
+        // движение
+        for body in self.bodies.iter_mut().filter(|body| body.is_dynamic()) {
+            body.velocity += self.gravity * dt;
+            let Some(model) = models.get_mut(body.model_index) else {
+                continue;
+            };
+            model.translate(body.velocity * dt);
+            if let Ok(axis) = body.angular_velocity.normalize() {
+                let angle = body.angular_velocity.length() * dt;
+                model
+                    .mesh
+                    .local_frame
+                    .rotate(Transform3D::rotation_around_axis(axis, angle));
+            }
+        }
+
+        // столкновения тел друг с другом
+        for i in 0..self.bodies.len() {
+            for j in i + 1..self.bodies.len() {
+                let (a, b) = (self.bodies[i], self.bodies[j]);
+                if !a.is_dynamic() && !b.is_dynamic() {
+                    continue;
+                }
+                let (Some(model_a), Some(model_b)) =
+                    (models.get(a.model_index), models.get(b.model_index))
+                else {
+                    continue;
+                };
+                let Some(contact) = collide(
+                    &a.collider,
+                    model_a.get_position(),
+                    &b.collider,
+                    model_b.get_position(),
+                ) else {
+                    continue;
+                };
+
+                let (velocity_a, velocity_b) = resolve_contact(&a, &b, &contact);
+                self.bodies[i].velocity = velocity_a;
+                self.bodies[j].velocity = velocity_b;
+
+                // выталкивание пропорционально обратным массам
+                let total = a.inverse_mass() + b.inverse_mass();
+                let correction = contact.normal * (contact.penetration / total);
+                models[a.model_index].translate(-correction * a.inverse_mass());
+                models[b.model_index].translate(correction * b.inverse_mass());
+            }
+        }
+
+        // столкновения с землёй
+        if let Some(ground) = self.ground_height {
+            for body in self.bodies.iter_mut().filter(|body| body.is_dynamic()) {
+                let Some(model) = models.get_mut(body.model_index) else {
+                    continue;
+                };
+                let bottom = model.get_position().y - body.collider.half_extents().y;
+                if bottom >= ground {
+                    continue;
+                }
+                model.translate(Vec3::new(0.0, ground - bottom, 0.0));
+                if body.velocity.y < 0.0 {
+                    let restitution = body.restitution.min(self.ground_restitution);
+                    body.velocity.y = -body.velocity.y * restitution;
+                }
+            }
+        }
+    }
+}
+
+/// Новые скорости тел `a` и `b` после удара по нормали контакта.
+fn resolve_contact(a: &RigidBody, b: &RigidBody, contact: &Contact) -> (Vec3, Vec3) {
+    let relative = (b.velocity - a.velocity).dot(contact.normal);
+    // тела уже расходятся
+    if relative >= 0.0 {
+        return (a.velocity, b.velocity);
+    }
+
+    let restitution = a.restitution.min(b.restitution);
+    let impulse = -(1.0 + restitution) * relative / (a.inverse_mass() + b.inverse_mass());
+    (
+        a.velocity - contact.normal * (impulse * a.inverse_mass()),
+        b.velocity + contact.normal * (impulse * b.inverse_mass()),
+    )
+}
+
+/// Пересечение форм `a` и `b` с центрами `center_a` и `center_b`.
+fn collide(a: &Collider, center_a: Point3, b: &Collider, center_b: Point3) -> Option<Contact> {
+    match (*a, *b) {
+        (Collider::Sphere { radius: radius_a }, Collider::Sphere { radius: radius_b }) => {
+            let offset = center_b - center_a;
+            let distance = offset.length();
+            let penetration = radius_a + radius_b - distance;
+            if penetration <= 0.0 {
+                return None;
+            }
+            // совпадающие центры расталкиваются вверх
+            let normal = offset
+                .normalize()
+                .map(Vec3::from)
+                .unwrap_or(Vec3::new(0.0, 1.0, 0.0));
+            Some(Contact {
+                normal,
+                penetration,
+            })
+        }
+        (Collider::Sphere { radius }, Collider::Aabb { half_extents }) => {
+            // от параллелепипеда к шару, затем нормаль разворачивается
+            let contact = sphere_box(center_a, radius, center_b, half_extents)?;
+            Some(Contact {
+                normal: -contact.normal,
+                ..contact
+            })
+        }
+        (Collider::Aabb { half_extents }, Collider::Sphere { radius }) => {
+            sphere_box(center_b, radius, center_a, half_extents)
+        }
+        (Collider::Aabb { .. }, Collider::Aabb { .. }) => {
+            box_box(center_a, a.half_extents(), center_b, b.half_extents())
+        }
+    }
+}
+
+/// Пересечение шара с параллелепипедом, нормаль от параллелепипеда к шару.
+fn sphere_box(
+    sphere_center: Point3,
+    radius: f32,
+    box_center: Point3,
+    half_extents: Vec3,
+) -> Option<Contact> {
+    let offset = sphere_center - box_center;
+    let closest = Vec3::new(
+        offset.x.clamp(-half_extents.x, half_extents.x),
+        offset.y.clamp(-half_extents.y, half_extents.y),
+        offset.z.clamp(-half_extents.z, half_extents.z),
+    );
+    let outside = offset - closest;
+    let distance = outside.length();
+    if distance > f32::EPSILON {
+        if distance >= radius {
+            return None;
+        }
+        return Some(Contact {
+            normal: outside / distance,
+            penetration: radius - distance,
+        });
+    }
+
+    // центр шара внутри параллелепипеда
+    let radius = Vec3::new(radius, radius, radius);
+    box_box(box_center, half_extents, sphere_center, radius)
+}
+
+/// Пересечение выровненных по осям параллелепипедов, нормаль от первого ко второму.
+///
+/// Тела выталкиваются вдоль оси наименьшего перекрытия.
+fn box_box(
+    center_a: Point3,
+    extents_a: Vec3,
+    center_b: Point3,
+    extents_b: Vec3,
+) -> Option<Contact> {
+    let offset = center_b - center_a;
+    let overlaps = [
+        (
+            extents_a.x + extents_b.x - offset.x.abs(),
+            Vec3::new(offset.x.signum(), 0.0, 0.0),
+        ),
+        (
+            extents_a.y + extents_b.y - offset.y.abs(),
+            Vec3::new(0.0, offset.y.signum(), 0.0),
+        ),
+        (
+            extents_a.z + extents_b.z - offset.z.abs(),
+            Vec3::new(0.0, 0.0, offset.z.signum()),
+        ),
+    ];
+    if overlaps.iter().any(|(overlap, _)| *overlap <= 0.0) {
+        return None;
+    }
+
+    let (penetration, normal) = overlaps.into_iter().min_by(|a, b| a.0.total_cmp(&b.0))?;
+    Some(Contact {
+        normal,
+        penetration,
+    })
+}
+
+#[cfg(test)]
+mod physics_tests {
+    use super::*;
+    use crate::Mesh;
+
+    fn ball_at(position: Point3) -> Model {
+        let mut ball = Model::from_mesh(Mesh::icosahedron());
+        ball.set_position(position);
+        ball
+    }
+
+    #[test]
+    fn test_ball_bounces_on_ground() {
+        let mut models = vec![ball_at(Point3::new(0.0, 2.0, 0.0))];
+        let mut world = PhysicsWorld {
+            ground_height: Some(0.0),
+            ground_restitution: 1.0,
+            ..Default::default()
+        };
+        let mut body = RigidBody::new(0, Collider::Sphere { radius: 0.5 });
+        body.restitution = 1.0;
+        world.bodies.push(body);
+
+        let mut bounced = false;
+        for _ in 0..120 {
+            world.step(&mut models, 1.0 / 120.0);
+            let y = models[0].get_position().y;
+            assert!(y >= 0.5 - 1e-4, "шар не проваливается под землю: {}", y);
+            bounced |= world.bodies[0].velocity.y > 0.0;
+        }
+        assert!(bounced, "шар отскочил от земли");
+    }
+
+    #[test]
+    fn test_equal_spheres_exchange_velocities() {
+        let mut models = vec![
+            ball_at(Point3::new(-0.45, 0.0, 0.0)),
+            ball_at(Point3::new(0.45, 0.0, 0.0)),
+        ];
+        let mut world = PhysicsWorld {
+            gravity: Vec3::zero(),
+            ..Default::default()
+        };
+        for (i, velocity) in [(0, 1.0), (1, 0.0)] {
+            let mut body = RigidBody::new(i, Collider::Sphere { radius: 0.5 });
+            body.velocity = Vec3::new(velocity, 0.0, 0.0);
+            body.restitution = 1.0;
+            world.bodies.push(body);
+        }
+
+        world.step(&mut models, 0.01);
+        assert!(world.bodies[0].velocity.x.abs() < 1e-4);
+        assert!((world.bodies[1].velocity.x - 1.0).abs() < 1e-4);
+        let distance = (models[1].get_position() - models[0].get_position()).length();
+        assert!(distance >= 1.0 - 1e-4, "шары вытолкнуты друг из друга");
+    }
+
+    #[test]
+    fn test_box_stops_on_fixed_box() {
+        let mut floor = Model::from_mesh(Mesh::hexahedron());
+        floor.scale_vec(Vec3::new(10.0, 1.0, 10.0));
+        let mut models = vec![floor, ball_at(Point3::new(0.0, 1.2, 0.0))];
+
+        let mut world = PhysicsWorld::new();
+        world
+            .bodies
+            .push(RigidBody::fixed(0, Collider::bounding_box(&models[0])));
+        let mut body = RigidBody::new(
+            1,
+            Collider::Aabb {
+                half_extents: Vec3::new(0.5, 0.5, 0.5),
+            },
+        );
+        body.restitution = 0.0;
+        world.bodies.push(body);
+
+        for _ in 0..120 {
+            world.step(&mut models, 1.0 / 60.0);
+        }
+        assert!(
+            (models[1].get_position().y - 1.0).abs() < 0.05,
+            "куб лежит на полу: {}",
+            models[1].get_position()
+        );
+        assert_eq!(models[0].get_position(), Point3::zero(), "пол неподвижен");
+    }
+}
@@ -0,0 +1,332 @@
+//! Ломаные линии на сцене: траектории, поля векторов, отладочные линии.
+
+use std::hash::{Hash, Hasher};
+
+use egui::Color32;
+
+use crate::library::state_hash::StateHash;
+use crate::{Camera, Canvas, HVec3, LineCap, Point3, ProjectionType, Transform3D, library::utils};
+
+/// Относительное смещение глубины линий в сторону камеры (см. [`utils::depth_biased`]),
+/// чтобы линии на поверхности не перекрывались ею.
+const POLYLINE_DEPTH_BIAS: f32 = 1e-3;
+
+/// Цвет и толщина отрезка ломаной.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LineStyle {
+    /// Цвет отрезка.
+    pub color: Color32,
+    /// Толщина отрезка в пикселях.
+    pub thickness: f32,
+}
+
+impl Default for LineStyle {
+    fn default() -> Self {
+        Self {
+            color: Color32::WHITE,
+            thickness: 1.0,
+        }
+    }
+}
+
+impl LineStyle {
+    /// Создать стиль отрезка.
+    pub fn new(color: Color32, thickness: f32) -> Self {
+        Self { color, thickness }
+    }
+}
+
+/// Ломаная линия в 3D пространстве.
+///
+/// Рисуется с проекцией камеры и проверкой по z-буферу, поэтому перекрывается гранями моделей,
+/// но сама z-буфер не меняет.
+///
+/// # Examples
+/// ```rust
+/// use egui::Color32;
+/// use g3d::{LineStyle, Point3, Polyline3};
+///
+/// let mut trajectory = Polyline3::new(Point3::zero(), LineStyle::new(Color32::RED, 2.0));
+/// trajectory.push(Point3::new(1.0, 0.0, 0.0));
+/// trajectory.push_styled(Point3::new(1.0, 1.0, 0.0), LineStyle::new(Color32::GREEN, 1.0));
+///
+/// assert_eq!(trajectory.segment_count(), 2);
+/// assert_eq!(trajectory.segment_style(0).color, Color32::RED);
+/// assert_eq!(trajectory.segment_style(1).color, Color32::GREEN);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polyline3 {
+    /// Вершины ломаной в **глобальных** координатах.
+    points: Vec<Point3>,
+    /// Стили отрезков: `i`-ый стиль для отрезка между `i` и `i + 1` вершинами.
+    ///
+    /// Если стилей меньше, чем отрезков, то последний стиль распространяется на оставшиеся.
+    styles: Vec<LineStyle>,
+    /// Отрисовывать ли ломаную.
+    pub visible: bool,
+}
+
+impl Polyline3 {
+    // --------------------------------------------------
+    // Конструкторы
+    // --------------------------------------------------
+
+    /// Создать ломаную из одной точки `start`, последующие отрезки рисуются стилем `style`.
+    pub fn new(start: Point3, style: LineStyle) -> Self {
+        Self {
+            points: vec![start],
+            styles: vec![style],
+            visible: true,
+        }
+    }
+
+    /// Создать ломаную по точкам `points`, все отрезки которой рисуются стилем `style`.
+    pub fn from_points(points: Vec<Point3>, style: LineStyle) -> Self {
+        Self {
+            points,
+            styles: vec![style],
+            visible: true,
+        }
+    }
+
+    /// Создать набор отдельных отрезков `[start, end]`, например для поля векторов.
+    ///
+    /// Между отрезками рисуются невидимые (нулевой толщины) соединения.
+    pub fn from_segments(segments: &[(Point3, Point3)], style: LineStyle) -> Self {
+        let hidden = LineStyle::new(Color32::TRANSPARENT, 0.0);
+        let mut points = Vec::with_capacity(segments.len() * 2);
+        let mut styles = Vec::with_capacity(segments.len() * 2);
+        for &(start, end) in segments {
+            points.push(start);
+            points.push(end);
+            styles.push(style);
+            styles.push(hidden);
+        }
+        // после последнего отрезка соединения нет
+        styles.pop();
+        Self {
+            points,
+            styles,
+            visible: true,
+        }
+    }
+
+    // --------------------------------------------------
+    // Изменение
+    // --------------------------------------------------
+
+    /// Продолжить ломаную до точки `point` стилем последнего отрезка.
+    pub fn push(&mut self, point: Point3) {
+        let style = self.styles.last().copied().unwrap_or_default();
+        self.push_styled(point, style);
+    }
+
+    /// Продолжить ломаную до точки `point` отрезком стиля `style`.
+    pub fn push_styled(&mut self, point: Point3, style: LineStyle) {
+        // стили всех предыдущих отрезков фиксируются, чтобы не поменять их новым стилем
+        let segment = self.points.len().saturating_sub(1);
+        while self.styles.len() < segment {
+            let last = self.styles.last().copied().unwrap_or_default();
+            self.styles.push(last);
+        }
+        self.styles.truncate(segment);
+        self.styles.push(style);
+        self.points.push(point);
+    }
+
+    /// Задать стиль `i`-го отрезка.
+    pub fn set_segment_style(&mut self, i: usize, style: LineStyle) {
+        debug_assert!(
+            i < self.segment_count(),
+            "отрезок {} вне ломаной из {} отрезков",
+            i,
+            self.segment_count()
+        );
+        while self.styles.len() <= i {
+            let last = self.styles.last().copied().unwrap_or_default();
+            self.styles.push(last);
+        }
+        self.styles[i] = style;
+    }
+
+    // --------------------------------------------------
+    // Доступ к данным
+    // --------------------------------------------------
+
+    /// Вершины ломаной в **глобальных** координатах.
+    pub fn points(&self) -> &[Point3] {
+        &self.points
+    }
+
+    /// Количество отрезков ломаной.
+    pub fn segment_count(&self) -> usize {
+        self.points.len().saturating_sub(1)
+    }
+
+    /// Стиль `i`-го отрезка.
+    pub fn segment_style(&self, i: usize) -> LineStyle {
+        self.styles
+            .get(i)
+            .or(self.styles.last())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Итератор по отрезкам ломаной с их стилями.
+    pub fn get_segment_iter(&self) -> impl Iterator<Item = (Point3, Point3, LineStyle)> {
+        self.points
+            .windows(2)
+            .enumerate()
+            .map(|(i, pair)| (pair[0], pair[1], self.segment_style(i)))
+    }
+
+    // --------------------------------------------------
+    // Отрисовка
+    // --------------------------------------------------
+
+    /// Нарисовать ломаную на холст камерой `camera`.
+    ///
+    /// При перспективной проекции отрезки обрезаются ближней плоскостью камеры.
+    pub fn draw(&self, camera: &Camera, projection_type: ProjectionType, canvas: &mut Canvas) {
+        let global_to_screen = camera.global_to_screen_transform(projection_type, canvas);
+        let near_plane =
+            (projection_type == ProjectionType::Perspective).then(|| camera.near_clip_plane());
+
+        for (start, end, style) in self.get_segment_iter() {
+            if style.thickness <= 0.0 {
+                continue;
+            }
+
+            let (start, end) = match &near_plane {
+                Some(plane) => {
+                    let start_distance = plane.distance_to_point(start);
+                    let end_distance = plane.distance_to_point(end);
+                    if start_distance < 0.0 && end_distance < 0.0 {
+                        continue;
+                    }
+                    let t = start_distance / (start_distance - end_distance);
+                    let cut = start + (end - start) * t;
+                    if start_distance < 0.0 {
+                        (cut, end)
+                    } else if end_distance < 0.0 {
+                        (start, cut)
+                    } else {
+                        (start, end)
+                    }
+                }
+                None => (start, end),
+            };
+
+            draw_thick_segment(canvas, global_to_screen, projection_type, start, end, style);
+        }
+    }
+}
+
+/// Нарисовать отрезок толщиной `style.thickness` пикселей со скруглёнными концами,
+/// чтобы соседние отрезки ломаной стыковались без зазоров.
+fn draw_thick_segment(
+    canvas: &mut Canvas,
+    global_to_screen: Transform3D,
+    projection_type: ProjectionType,
+    start: Point3,
+    end: Point3,
+    style: LineStyle,
+) {
+    let project = |point| {
+        utils::screen_point(
+            HVec3::from(point).apply_transform(global_to_screen),
+            projection_type,
+        )
+    };
+    let (Ok(start), Ok(end)) = (project(start), project(end)) else {
+        return;
+    };
+
+    canvas.draw_depth_tested_thick_line(
+        utils::depth_biased(start, POLYLINE_DEPTH_BIAS, projection_type),
+        utils::depth_biased(end, POLYLINE_DEPTH_BIAS, projection_type),
+        style.thickness.round().max(1.0),
+        LineCap::Round,
+        style.color,
+        0.0,
+    );
+}
+
+impl StateHash for LineStyle {
+    fn hash_state<H: Hasher>(&self, state: &mut H) {
+        self.color.hash(state);
+        self.thickness.hash_state(state);
+    }
+}
+
+impl StateHash for Polyline3 {
+    fn hash_state<H: Hasher>(&self, state: &mut H) {
+        self.points.hash_state(state);
+        self.styles.hash_state(state);
+        self.visible.hash_state(state);
+    }
+}
+
+#[cfg(test)]
+mod polyline_tests {
+    use super::*;
+
+    #[test]
+    fn test_segment_styles() {
+        let red = LineStyle::new(Color32::RED, 1.0);
+        let blue = LineStyle::new(Color32::BLUE, 3.0);
+        let mut polyline = Polyline3::new(Point3::zero(), red);
+        polyline.push(Point3::new(1.0, 0.0, 0.0));
+        polyline.push(Point3::new(2.0, 0.0, 0.0));
+        polyline.push_styled(Point3::new(3.0, 0.0, 0.0), blue);
+        polyline.push(Point3::new(4.0, 0.0, 0.0));
+
+        let styles: Vec<LineStyle> = polyline
+            .get_segment_iter()
+            .map(|(_, _, style)| style)
+            .collect();
+        assert_eq!(styles, vec![red, red, blue, blue]);
+
+        polyline.set_segment_style(0, blue);
+        assert_eq!(polyline.segment_style(0), blue);
+        assert_eq!(
+            polyline.segment_style(1),
+            red,
+            "остальные отрезки не меняются"
+        );
+    }
+
+    #[test]
+    fn test_draw_respects_depth_and_near_plane() {
+        let mut canvas = Canvas::new(60, 40);
+        canvas.clear(Color32::BLACK);
+        let camera = Camera::default();
+
+        // горизонтальная линия через центр экрана, второй конец позади камеры
+        let polyline = Polyline3::from_points(
+            vec![Point3::new(-3.0, 0.0, 0.0), Point3::new(3.0, 0.0, -20.0)],
+            LineStyle::new(Color32::RED, 3.0),
+        );
+        polyline.draw(&camera, ProjectionType::Perspective, &mut canvas);
+        let red = canvas
+            .pixels()
+            .iter()
+            .filter(|&&c| c == Color32::RED)
+            .count();
+        assert!(red > 0, "видимая часть отрезка нарисована");
+
+        // z-буфер, полностью перекрывающий линию
+        let mut covered = Canvas::new(60, 40);
+        covered.clear(Color32::BLACK);
+        for y in 0..40 {
+            for x in 0..60 {
+                covered.test_and_set_z(x, y, 10.0);
+            }
+        }
+        polyline.draw(&camera, ProjectionType::Perspective, &mut covered);
+        assert!(
+            covered.pixels().iter().all(|&c| c == Color32::BLACK),
+            "линия за гранями не рисуется"
+        );
+    }
+}
@@ -0,0 +1,207 @@
+//! Объявление и реализация структуры `Aabb`.
+
+use super::{Point3, Vec3};
+
+/// Параллелепипед, выровненный по осям координат (axis-aligned bounding box).
+///
+/// Задаётся минимальной и максимальной точками. Используется для быстрой проверки
+/// пересечений перед точными, но медленными проверками.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Aabb {
+    /// Точка с наименьшими координатами.
+    pub min: Point3,
+    /// Точка с наибольшими координатами.
+    pub max: Point3,
+}
+
+impl Aabb {
+    /// Создать параллелепипед по минимальной и максимальной точкам.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::{Aabb, Point3};
+    ///
+    /// let aabb = Aabb::new(Point3::new(-1.0, -1.0, -1.0), Point3::new(1.0, 3.0, 1.0));
+    ///
+    /// assert!(aabb.center().approx_equal(Point3::new(0.0, 1.0, 0.0), 1.0e-6));
+    /// assert!(aabb.contains(Point3::new(0.5, 2.5, -0.5)));
+    /// assert!(!aabb.contains(Point3::new(0.5, 3.5, -0.5)));
+    /// ```
+    pub fn new(min: Point3, max: Point3) -> Self {
+        debug_assert!(
+            min.x <= max.x && min.y <= max.y && min.z <= max.z,
+            "минимальная точка {:?} должна быть не больше максимальной {:?}",
+            min,
+            max
+        );
+
+        Self { min, max }
+    }
+
+    /// Создать наименьший параллелепипед, содержащий точки `points`.
+    ///
+    /// Для пустого набора точек возвращает `None`.
+    pub fn from_points(points: impl IntoIterator<Item = Point3>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(Self::new(first, first), |aabb, point| {
+            aabb.expanded_to(point)
+        }))
+    }
+
+    // --------------------------------------------------
+    // Характеристики
+    // --------------------------------------------------
+
+    /// Центр параллелепипеда.
+    pub fn center(&self) -> Point3 {
+        self.min + (self.max - self.min) * 0.5
+    }
+
+    /// Половины длин сторон параллелепипеда.
+    pub fn half_extents(&self) -> Vec3 {
+        (self.max - self.min) * 0.5
+    }
+
+    /// Наименьший параллелепипед, содержащий этот параллелепипед и точку `point`.
+    pub fn expanded_to(&self, point: Point3) -> Self {
+        Self {
+            min: Point3::new(
+                self.min.x.min(point.x),
+                self.min.y.min(point.y),
+                self.min.z.min(point.z),
+            ),
+            max: Point3::new(
+                self.max.x.max(point.x),
+                self.max.y.max(point.y),
+                self.max.z.max(point.z),
+            ),
+        }
+    }
+
+    // --------------------------------------------------
+    // Геометрические запросы
+    // --------------------------------------------------
+
+    /// Находится ли точка внутри параллелепипеда (включая границу).
+    pub fn contains(&self, point: Point3) -> bool {
+        (self.min.x..=self.max.x).contains(&point.x)
+            && (self.min.y..=self.max.y).contains(&point.y)
+            && (self.min.z..=self.max.z).contains(&point.z)
+    }
+
+    /// Ближайшая к `point` точка параллелепипеда.
+    pub fn closest_point(&self, point: Point3) -> Point3 {
+        Point3::new(
+            point.x.clamp(self.min.x, self.max.x),
+            point.y.clamp(self.min.y, self.max.y),
+            point.z.clamp(self.min.z, self.max.z),
+        )
+    }
+
+    /// Пересекаются ли параллелепипеды (касание считается пересечением).
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        self.min.x <= other.max.x
+            && other.min.x <= self.max.x
+            && self.min.y <= other.max.y
+            && other.min.y <= self.max.y
+            && self.min.z <= other.max.z
+            && other.min.z <= self.max.z
+    }
+
+    /// Пересекается ли параллелепипед с шаром с центром `center` и радиусом `radius`.
+    pub fn intersects_sphere(&self, center: Point3, radius: f32) -> bool {
+        (self.closest_point(center) - center).length() <= radius
+    }
+
+    /// Пересекается ли параллелепипед с отрезком `[start, end]`.
+    pub fn intersects_segment(&self, start: Point3, end: Point3) -> bool {
+        let direction = end - start;
+        let (mut t_min, mut t_max) = (0.0_f32, 1.0_f32);
+        let axes = [
+            (start.x, direction.x, self.min.x, self.max.x),
+            (start.y, direction.y, self.min.y, self.max.y),
+            (start.z, direction.z, self.min.z, self.max.z),
+        ];
+        for (origin, direction, min, max) in axes {
+            if direction.abs() < f32::EPSILON {
+                // отрезок параллелен паре граней
+                if origin < min || origin > max {
+                    return false;
+                }
+                continue;
+            }
+            let (t1, t2) = ((min - origin) / direction, (max - origin) / direction);
+            t_min = t_min.max(t1.min(t2));
+            t_max = t_max.min(t1.max(t2));
+            if t_min > t_max {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// 12 рёбер параллелепипеда.
+    pub fn edges(&self) -> [(Point3, Point3); 12] {
+        let (min, max) = (self.min, self.max);
+        let corner = |x: bool, y: bool, z: bool| {
+            Point3::new(
+                if x { max.x } else { min.x },
+                if y { max.y } else { min.y },
+                if z { max.z } else { min.z },
+            )
+        };
+        [
+            (corner(false, false, false), corner(true, false, false)),
+            (corner(false, true, false), corner(true, true, false)),
+            (corner(false, false, true), corner(true, false, true)),
+            (corner(false, true, true), corner(true, true, true)),
+            (corner(false, false, false), corner(false, true, false)),
+            (corner(true, false, false), corner(true, true, false)),
+            (corner(false, false, true), corner(false, true, true)),
+            (corner(true, false, true), corner(true, true, true)),
+            (corner(false, false, false), corner(false, false, true)),
+            (corner(true, false, false), corner(true, false, true)),
+            (corner(false, true, false), corner(false, true, true)),
+            (corner(true, true, false), corner(true, true, true)),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod aabb_tests {
+    use super::*;
+
+    #[test]
+    fn test_intersections() {
+        let aabb = Aabb::new(Point3::zero(), Point3::new(1.0, 1.0, 1.0));
+
+        let touching = Aabb::new(Point3::new(1.0, 0.0, 0.0), Point3::new(2.0, 1.0, 1.0));
+        let apart = Aabb::new(Point3::new(1.1, 0.0, 0.0), Point3::new(2.0, 1.0, 1.0));
+        assert!(aabb.intersects(&touching), "касание считается пересечением");
+        assert!(!aabb.intersects(&apart));
+
+        assert!(aabb.intersects_sphere(Point3::new(1.5, 0.5, 0.5), 0.6));
+        assert!(!aabb.intersects_sphere(Point3::new(1.5, 1.5, 0.5), 0.6));
+
+        assert!(aabb.intersects_segment(Point3::new(-1.0, 0.5, 0.5), Point3::new(2.0, 0.5, 0.5)));
+        assert!(
+            !aabb.intersects_segment(Point3::new(-1.0, 0.5, 0.5), Point3::new(-0.1, 0.5, 0.5)),
+            "отрезок заканчивается до параллелепипеда"
+        );
+    }
+
+    #[test]
+    fn test_from_points() {
+        let points = [
+            Point3::new(1.0, -2.0, 0.0),
+            Point3::new(-1.0, 3.0, 0.5),
+            Point3::new(0.0, 0.0, -4.0),
+        ];
+        let aabb = Aabb::from_points(points).unwrap();
+        assert_eq!(aabb.min, Point3::new(-1.0, -2.0, -4.0));
+        assert_eq!(aabb.max, Point3::new(1.0, 3.0, 0.5));
+        assert!(Aabb::from_points([]).is_none());
+    }
+}
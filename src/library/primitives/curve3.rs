@@ -0,0 +1,159 @@
+//! Объявление и реализация структуры `Curve3`.
+
+use super::{Point3, Vec3};
+
+/// Гладкая кривая в 3D пространстве.
+///
+/// Кривая параметризуется `t` из `[0, 1]`: `t = 0` - начало кривой, `t = 1` - конец.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Curve3 {
+    /// Кубическая кривая Безье по 4-м контрольным точкам.
+    ///
+    /// Проходит через первую и последнюю точки, средние точки задают касательные.
+    Bezier([Point3; 4]),
+    /// Сплайн Катмулла-Рома, проходящий через все точки.
+    ///
+    /// Точек должно быть не менее 2-х, каждый отрезок между соседними точками
+    /// занимает равную долю параметра `t`.
+    CatmullRom(Vec<Point3>),
+}
+
+impl Curve3 {
+    /// Точка кривой при параметре `t` из `[0, 1]`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::{Curve3, Point3};
+    ///
+    /// let curve = Curve3::Bezier([
+    ///     Point3::new(0.0, 0.0, 0.0),
+    ///     Point3::new(0.0, 1.0, 0.0),
+    ///     Point3::new(1.0, 1.0, 0.0),
+    ///     Point3::new(1.0, 0.0, 0.0),
+    /// ]);
+    ///
+    /// assert!(curve.point_at(0.0).approx_equal(Point3::new(0.0, 0.0, 0.0), 1.0e-6));
+    /// assert!(curve.point_at(0.5).approx_equal(Point3::new(0.5, 0.75, 0.0), 1.0e-6));
+    /// assert!(curve.point_at(1.0).approx_equal(Point3::new(1.0, 0.0, 0.0), 1.0e-6));
+    /// ```
+    pub fn point_at(&self, t: f32) -> Point3 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Bezier(points) => Point3::from(bezier_point(points, t)),
+            Self::CatmullRom(points) => {
+                let (segment, local_t) = catmull_rom_segment(points, t);
+                let [p0, p1, p2, p3] = catmull_rom_points(points, segment);
+                let (t2, t3) = (local_t * local_t, local_t * local_t * local_t);
+                Point3::from(
+                    (p1 * 2.0
+                        + (p2 - p0) * local_t
+                        + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2
+                        + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3)
+                        * 0.5,
+                )
+            }
+        }
+    }
+
+    /// Касательный вектор (производная по `t`) кривой при параметре `t` из `[0, 1]`.
+    ///
+    /// Вектор не нормализован, его длина пропорциональна скорости движения по кривой.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::{Curve3, Point3, Vec3};
+    ///
+    /// let curve = Curve3::CatmullRom(vec![Point3::new(0.0, 0.0, 0.0), Point3::new(2.0, 0.0, 0.0)]);
+    ///
+    /// assert!(curve.tangent_at(0.5).approx_equal(Vec3::new(2.0, 0.0, 0.0), 1.0e-6));
+    /// ```
+    pub fn tangent_at(&self, t: f32) -> Vec3 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Bezier(points) => {
+                let p: [Vec3; 4] = points.map(Vec3::from);
+                let s = 1.0 - t;
+                ((p[1] - p[0]) * (s * s) + (p[2] - p[1]) * (2.0 * s * t) + (p[3] - p[2]) * (t * t))
+                    * 3.0
+            }
+            Self::CatmullRom(points) => {
+                let (segment, local_t) = catmull_rom_segment(points, t);
+                let [p0, p1, p2, p3] = catmull_rom_points(points, segment);
+                let t2 = local_t * local_t;
+                let derivative = ((p2 - p0)
+                    + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * (2.0 * local_t)
+                    + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * (3.0 * t2))
+                    * 0.5;
+                // производная по локальному параметру отрезка -> по общему `t`
+                derivative * (points.len() - 1) as f32
+            }
+        }
+    }
+
+    /// Разбить кривую на `segments` отрезков равного шага параметра.
+    ///
+    /// Возвращает `segments + 1` точку, первая и последняя совпадают с концами кривой.
+    /// Результат можно использовать как профиль для моделей вращения.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::{Curve3, Point3};
+    ///
+    /// let points = vec![
+    ///     Point3::new(0.0, 0.0, 0.0),
+    ///     Point3::new(1.0, 1.0, 0.0),
+    ///     Point3::new(2.0, 0.0, 0.0),
+    /// ];
+    /// let curve = Curve3::CatmullRom(points);
+    /// let polyline = curve.sample(8);
+    ///
+    /// assert_eq!(polyline.len(), 9);
+    /// // сплайн Катмулла-Рома проходит через исходные точки
+    /// assert!(polyline[4].approx_equal(Point3::new(1.0, 1.0, 0.0), 1.0e-6));
+    /// ```
+    pub fn sample(&self, segments: usize) -> Vec<Point3> {
+        debug_assert!(segments >= 1, "количество отрезков должно быть не менее 1");
+        let segments = segments.max(1);
+        (0..=segments)
+            .map(|i| self.point_at(i as f32 / segments as f32))
+            .collect()
+    }
+}
+
+/// Точка кубической кривой Безье по формуле Бернштейна.
+pub(crate) fn bezier_point(points: &[Point3; 4], t: f32) -> Vec3 {
+    let s = 1.0 - t;
+    let p: [Vec3; 4] = points.map(Vec3::from);
+    p[0] * (s * s * s) + p[1] * (3.0 * s * s * t) + p[2] * (3.0 * s * t * t) + p[3] * (t * t * t)
+}
+
+/// Номер отрезка сплайна Катмулла-Рома и параметр внутри него для общего параметра `t`.
+fn catmull_rom_segment(points: &[Point3], t: f32) -> (usize, f32) {
+    debug_assert!(
+        points.len() >= 2,
+        "сплайн Катмулла-Рома должен содержать хотя бы 2 точки, но содержит {}",
+        points.len()
+    );
+    let segments = points.len() - 1;
+    let scaled = t * segments as f32;
+    let segment = (scaled.floor() as usize).min(segments - 1);
+    (segment, scaled - segment as f32)
+}
+
+/// 4 опорные точки отрезка сплайна. На концах недостающие точки отражаются
+/// относительно крайних, чтобы касательная на концах смотрела вдоль крайнего отрезка.
+fn catmull_rom_points(points: &[Point3], segment: usize) -> [Vec3; 4] {
+    let p1 = Vec3::from(points[segment]);
+    let p2 = Vec3::from(points[segment + 1]);
+    let p0 = if segment > 0 {
+        Vec3::from(points[segment - 1])
+    } else {
+        p1 * 2.0 - p2
+    };
+    let p3 = if segment + 2 < points.len() {
+        Vec3::from(points[segment + 2])
+    } else {
+        p2 * 2.0 - p1
+    };
+    [p0, p1, p2, p3]
+}
@@ -0,0 +1,466 @@
+//! Объявление и реализация примитивов двойной точности `DVec3`, `DPoint3` и `DTransform3D`.
+//!
+//! Рендер работает в `f32`, и при больших координатах (например, при построении графиков
+//! на большом диапазоне) точности `f32` не хватает: вершины "дрожат" и слипаются.
+//! Поэтому координаты можно хранить и преобразовывать в `f64`, а в `f32` переводить
+//! уже относительно точки отсчёта рядом с камерой (см. [`DPoint3::relative_to`] и
+//! [`DTransform3D::relative_to`]): разности получаются небольшими и точными.
+
+use super::{Point3, Transform3D, Vec3};
+use std::{
+    fmt::Display,
+    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+};
+
+// --------------------------------------------------
+// DVec3
+// --------------------------------------------------
+
+/// Направление в 3D пространстве с координатами двойной точности.
+///
+/// Аналог `Vec3` с координатами `f64`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DVec3 {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl DVec3 {
+    /// Создать вектор по 3-м координатам.
+    pub fn new(x: f64, y: f64, z: f64) -> Self {
+        Self { x, y, z }
+    }
+
+    /// Получить нулевой вектор (0.0, 0.0, 0.0).
+    pub fn zero() -> Self {
+        Self::new(0.0, 0.0, 0.0)
+    }
+
+    /// Скалярное произведение векторов.
+    pub fn dot(self, other: Self) -> f64 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    /// Векторное произведение векторов.
+    pub fn cross(self, other: Self) -> Self {
+        Self::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    /// Длина вектора.
+    pub fn length(self) -> f64 {
+        self.dot(self).sqrt()
+    }
+
+    /// Нормированный вектор или `None` для нулевого вектора.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::DVec3;
+    ///
+    /// let vec = DVec3::new(0.0, 3.0, 4.0).normalize().unwrap();
+    /// assert!((vec.y - 0.6).abs() < 1.0e-12);
+    /// assert!(DVec3::zero().normalize().is_none());
+    /// ```
+    pub fn normalize(self) -> Option<Self> {
+        let length = self.length();
+        if length.is_normal() {
+            Some(self * (1.0 / length))
+        } else {
+            None
+        }
+    }
+
+    /// Приблизительное сравнение векторов на равенство.
+    pub fn approx_equal(self, other: Self, tolerance: f64) -> bool {
+        (self.x - other.x).abs() < tolerance
+            && (self.y - other.y).abs() < tolerance
+            && (self.z - other.z).abs() < tolerance
+    }
+}
+
+impl Display for DVec3 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "DVec3(x: {}, y: {}, z: {})", self.x, self.y, self.z)
+    }
+}
+
+impl Add for DVec3 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    }
+}
+
+impl AddAssign for DVec3 {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for DVec3 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+impl SubAssign for DVec3 {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl Neg for DVec3 {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl Mul<f64> for DVec3 {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Self::new(self.x * rhs, self.y * rhs, self.z * rhs)
+    }
+}
+
+impl From<Vec3> for DVec3 {
+    fn from(vec: Vec3) -> Self {
+        Self::new(vec.x as f64, vec.y as f64, vec.z as f64)
+    }
+}
+
+impl From<DVec3> for Vec3 {
+    /// Перевести вектор в одинарную точность, младшие разряды координат теряются.
+    fn from(vec: DVec3) -> Self {
+        Vec3::new(vec.x as f32, vec.y as f32, vec.z as f32)
+    }
+}
+
+// --------------------------------------------------
+// DPoint3
+// --------------------------------------------------
+
+/// Точка в 3D пространстве с координатами двойной точности.
+///
+/// Аналог `Point3` с координатами `f64`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DPoint3 {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl DPoint3 {
+    /// Создать новую точку `(x, y, z)` по 3-м координатам.
+    pub fn new(x: f64, y: f64, z: f64) -> Self {
+        Self { x, y, z }
+    }
+
+    /// Создать точку `(0.0, 0.0, 0.0)`.
+    pub fn zero() -> Self {
+        Self::new(0.0, 0.0, 0.0)
+    }
+
+    /// Приблизительное сравнение точек на равенство.
+    pub fn approx_equal(self, other: Self, tolerance: f64) -> bool {
+        (self.x - other.x).abs() < tolerance
+            && (self.y - other.y).abs() < tolerance
+            && (self.z - other.z).abs() < tolerance
+    }
+
+    /// Применить преобразование `transform` к точке.
+    ///
+    /// Возвращает `None`, если после преобразования `w <= 0` (точка на бесконечности
+    /// или позади камеры), как и `Point3::apply_transform`.
+    pub fn apply_transform(self, transform: DTransform3D) -> Option<Self> {
+        let m = &transform.m;
+        let x = self.x * m[0] + self.y * m[4] + self.z * m[8] + m[12];
+        let y = self.x * m[1] + self.y * m[5] + self.z * m[9] + m[13];
+        let z = self.x * m[2] + self.y * m[6] + self.z * m[10] + m[14];
+        let w = self.x * m[3] + self.y * m[7] + self.z * m[11] + m[15];
+        if w > 0.0 && w.is_finite() {
+            Some(Self::new(x / w, y / w, z / w))
+        } else {
+            None
+        }
+    }
+
+    /// Точка в одинарной точности относительно точки отсчёта `origin`.
+    ///
+    /// Разность считается в `f64`, поэтому для точек рядом с `origin` результат точен
+    /// даже при очень больших абсолютных координатах.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::DPoint3;
+    ///
+    /// let origin = DPoint3::new(1.0e8, 0.0, 0.0);
+    /// let point = DPoint3::new(1.0e8 + 0.25, 0.0, 0.0);
+    ///
+    /// // в f32 обе точки слипаются в одну
+    /// assert_eq!(g3d::Point3::from(point), g3d::Point3::from(origin));
+    /// // а относительно origin разница сохраняется
+    /// assert_eq!(point.relative_to(origin).x, 0.25);
+    /// ```
+    pub fn relative_to(self, origin: DPoint3) -> Point3 {
+        Point3::from(DPoint3::zero() + (self - origin))
+    }
+}
+
+impl Display for DPoint3 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "DPoint3(x: {}, y: {}, z: {})", self.x, self.y, self.z)
+    }
+}
+
+impl Sub for DPoint3 {
+    type Output = DVec3;
+
+    /// Вектор из правой точки в левую.
+    fn sub(self, rhs: Self) -> Self::Output {
+        DVec3::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+impl Add<DVec3> for DPoint3 {
+    type Output = Self;
+
+    fn add(self, rhs: DVec3) -> Self::Output {
+        Self::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    }
+}
+
+impl AddAssign<DVec3> for DPoint3 {
+    fn add_assign(&mut self, rhs: DVec3) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub<DVec3> for DPoint3 {
+    type Output = Self;
+
+    fn sub(self, rhs: DVec3) -> Self::Output {
+        Self::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+impl From<Point3> for DPoint3 {
+    fn from(point: Point3) -> Self {
+        Self::new(point.x as f64, point.y as f64, point.z as f64)
+    }
+}
+
+impl From<DPoint3> for Point3 {
+    /// Перевести точку в одинарную точность, младшие разряды координат теряются.
+    ///
+    /// Для больших координат лучше использовать [`DPoint3::relative_to`].
+    fn from(point: DPoint3) -> Self {
+        Point3::new(point.x as f32, point.y as f32, point.z as f32)
+    }
+}
+
+// --------------------------------------------------
+// DTransform3D
+// --------------------------------------------------
+
+/// Матрица преобразования 4x4 двойной точности.
+///
+/// Аналог `Transform3D` с элементами `f64` и тем же (транспонированным) расположением:
+/// вектор-строка умножается на матрицу справа, перемещение хранится в последней строке.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DTransform3D {
+    /// Матрица преобразования 4x4 в виде одномерного массива в row-major порядке.
+    pub m: [f64; 16],
+}
+
+impl Default for DTransform3D {
+    /// Создаёт тождественную (единичную) матрицу как матрицу по-умолчанию.
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl DTransform3D {
+    /// Создать матрицу преобразования из массива `m`.
+    pub fn new(m: [f64; 16]) -> Self {
+        Self { m }
+    }
+
+    /// Создает единичную (тождественную) матрицу преобразования.
+    pub fn identity() -> Self {
+        Self::scale(1.0, 1.0, 1.0)
+    }
+
+    /// Создает матрицу перемещения на `(dx, dy, dz)`.
+    pub fn translation(dx: f64, dy: f64, dz: f64) -> Self {
+        Self {
+            m: [
+                1.0, 0.0, 0.0, 0.0, // первая строка
+                0.0, 1.0, 0.0, 0.0, // вторая строка
+                0.0, 0.0, 1.0, 0.0, // третья строка
+                dx, dy, dz, 1.0, // перемещение
+            ],
+        }
+    }
+
+    /// Создает матрицу перемещения на вектор `delta`.
+    pub fn translation_vec(delta: DVec3) -> Self {
+        Self::translation(delta.x, delta.y, delta.z)
+    }
+
+    /// Создает матрицу масштабирования с разными коэффициентами по осям.
+    pub fn scale(sx: f64, sy: f64, sz: f64) -> Self {
+        Self {
+            m: [
+                sx, 0.0, 0.0, 0.0, // первая строка
+                0.0, sy, 0.0, 0.0, // вторая строка
+                0.0, 0.0, sz, 0.0, // третья строка
+                0.0, 0.0, 0.0, 1.0, // перемещение
+            ],
+        }
+    }
+
+    /// Создает матрицу поворота вокруг оси X (в радианах).
+    pub fn rotation_x_rad(angle: f64) -> Self {
+        let (sin_a, cos_a) = angle.sin_cos();
+        Self {
+            m: [
+                1.0, 0.0, 0.0, 0.0, // первая строка
+                0.0, cos_a, sin_a, 0.0, // вторая строка
+                0.0, -sin_a, cos_a, 0.0, // третья строка
+                0.0, 0.0, 0.0, 1.0, // перемещение
+            ],
+        }
+    }
+
+    /// Создает матрицу поворота вокруг оси Y (в радианах).
+    pub fn rotation_y_rad(angle: f64) -> Self {
+        let (sin_a, cos_a) = angle.sin_cos();
+        Self {
+            m: [
+                cos_a, 0.0, -sin_a, 0.0, // первая строка
+                0.0, 1.0, 0.0, 0.0, // вторая строка
+                sin_a, 0.0, cos_a, 0.0, // третья строка
+                0.0, 0.0, 0.0, 1.0, // перемещение
+            ],
+        }
+    }
+
+    /// Создает матрицу поворота вокруг оси Z (в радианах).
+    pub fn rotation_z_rad(angle: f64) -> Self {
+        let (sin_a, cos_a) = angle.sin_cos();
+        Self {
+            m: [
+                cos_a, sin_a, 0.0, 0.0, // первая строка
+                -sin_a, cos_a, 0.0, 0.0, // вторая строка
+                0.0, 0.0, 1.0, 0.0, // третья строка
+                0.0, 0.0, 0.0, 1.0, // перемещение
+            ],
+        }
+    }
+
+    /// Умножение (композиция) матриц преобразования.
+    ///
+    /// При композиции двух матриц, правая (`other`) применяется **после** левой.
+    pub fn multiply(self, other: Self) -> Self {
+        let mut result = [0.0; 16];
+        for i in 0..4 {
+            for j in 0..4 {
+                for k in 0..4 {
+                    result[i * 4 + j] += self.m[i * 4 + k] * other.m[k * 4 + j];
+                }
+            }
+        }
+        Self { m: result }
+    }
+
+    /// Преобразование в одинарной точности для рендера относительно точки отсчёта `origin`.
+    ///
+    /// Возвращает `translation(-origin) * self`: вместо глобальных координат получаются
+    /// координаты относительно `origin`. Перемещение считается в `f64`, поэтому модель с
+    /// огромным перемещением рядом с `origin` отрисуется без потери точности. Камеру для
+    /// рендера нужно поставить в `camera_position.relative_to(origin)`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::{DPoint3, DTransform3D, Point3};
+    ///
+    /// let origin = DPoint3::new(1.0e9, -1.0e9, 0.0);
+    /// let model = DTransform3D::translation(1.0e9 + 0.5, -1.0e9, 2.0);
+    ///
+    /// let transform = model.relative_to(origin);
+    /// let vertex = Point3::zero().apply_transform(transform).unwrap();
+    /// assert!(vertex.approx_equal(Point3::new(0.5, 0.0, 2.0), 1.0e-6));
+    /// ```
+    pub fn relative_to(self, origin: DPoint3) -> Transform3D {
+        let rebased = self.multiply(Self::translation(-origin.x, -origin.y, -origin.z));
+        Transform3D::from(rebased)
+    }
+}
+
+impl From<Transform3D> for DTransform3D {
+    fn from(transform: Transform3D) -> Self {
+        Self::new(transform.m.map(|value| value as f64))
+    }
+}
+
+impl From<DTransform3D> for Transform3D {
+    /// Перевести матрицу в одинарную точность.
+    ///
+    /// Для больших перемещений лучше использовать [`DTransform3D::relative_to`].
+    fn from(transform: DTransform3D) -> Self {
+        Transform3D::new(transform.m.map(|value| value as f32))
+    }
+}
+
+#[cfg(test)]
+mod double_tests {
+    use super::*;
+
+    #[test]
+    fn test_transform_matches_f32() {
+        let transform = Transform3D::rotation_x_rad(0.3)
+            .multiply(Transform3D::rotation_y_rad(-1.2))
+            .multiply(Transform3D::rotation_z_rad(2.0))
+            .multiply(Transform3D::scale(2.0, 0.5, 3.0))
+            .multiply(Transform3D::translation(1.0, -2.0, 3.0));
+        let double = DTransform3D::rotation_x_rad(0.3)
+            .multiply(DTransform3D::rotation_y_rad(-1.2))
+            .multiply(DTransform3D::rotation_z_rad(2.0))
+            .multiply(DTransform3D::scale(2.0, 0.5, 3.0))
+            .multiply(DTransform3D::translation(1.0, -2.0, 3.0));
+
+        let point = Point3::new(0.5, -1.5, 4.0);
+        let expected = point.apply_transform(transform).unwrap();
+        let got = DPoint3::from(point).apply_transform(double).unwrap();
+        assert!(Point3::from(got).approx_equal(expected, 1.0e-5));
+        assert_eq!(Transform3D::from(DTransform3D::from(transform)), transform);
+    }
+
+    #[test]
+    fn test_relative_to_keeps_precision() {
+        let origin = DPoint3::new(5.0e7, 5.0e7, -5.0e7);
+        let offsets = [0.125, 0.25, 0.375];
+        let points: Vec<Point3> = offsets
+            .iter()
+            .map(|&d| DPoint3::new(5.0e7 + d, 5.0e7 - d, -5.0e7).relative_to(origin))
+            .collect();
+        for (point, &d) in points.iter().zip(&offsets) {
+            assert!(point.approx_equal(Point3::new(d as f32, -d as f32, 0.0), 1.0e-6));
+        }
+
+        // наивный перевод в f32 теряет эти смещения
+        let naive = Point3::from(DPoint3::new(5.0e7 + 0.125, 0.0, 0.0));
+        assert_eq!(naive.x, 5.0e7);
+    }
+}
@@ -14,6 +14,7 @@ use std::{
 /// Поскольку это 4D вектор, отвечающий за 3D пространство, у этого вектора отсутсвуют базовые операции по типу длины,
 /// сложения и им подобным, ибо за это отвечает обычный `Vec3` или `Point3`.
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HVec3 {
     pub x: f32,
     pub y: f32,
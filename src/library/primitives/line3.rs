@@ -1,6 +1,9 @@
 //! Объявление и реализация структуры `Line3`.
 
-use super::{Point3, UVec3};
+use super::{Plane, Point3, UVec3, Vec3};
+
+/// Точность, с которой прямая считается параллельной плоскости (или другой прямой).
+const PARALLEL_EPSILON: f32 = 1e-6;
 
 /// Линия в 3D пространстве.
 ///
@@ -51,4 +54,227 @@ impl Line3 {
         let direction = (p2 - p1).normalize().unwrap();
         Self::new(p1, direction)
     }
+
+    // --------------------------------------------------
+    // Геометрические запросы
+    // --------------------------------------------------
+
+    /// Точка пересечения прямой с плоскостью.
+    ///
+    /// Прямая считается бесконечной в обе стороны. Если прямая параллельна плоскости
+    /// (в том числе лежит в ней), то возвращает `None`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::{Line3, Plane, Point3, UVec3};
+    ///
+    /// let line = Line3::new(Point3::new(1.0, 5.0, 2.0), UVec3::down());
+    /// let plane = Plane::new(Point3::new(0.0, 1.0, 0.0), UVec3::up());
+    ///
+    /// let point = line.intersect_plane(&plane).unwrap();
+    /// assert!(point.approx_equal(Point3::new(1.0, 1.0, 2.0), 1.0e-6));
+    /// ```
+    pub fn intersect_plane(&self, plane: &Plane) -> Option<Point3> {
+        let denominator = self.direction.dot(plane.normal);
+        if denominator.abs() < PARALLEL_EPSILON {
+            return None;
+        }
+        let t = -plane.distance_to_point(self.origin) / denominator;
+        Some(self.origin + self.direction * t)
+    }
+
+    /// Ближайшая к `point` точка прямой.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::{Line3, Point3, UVec3};
+    ///
+    /// let line = Line3::new(Point3::new(0.0, 0.0, 0.0), UVec3::plus_x());
+    ///
+    /// let closest = line.closest_point_to(Point3::new(3.0, 4.0, 0.0));
+    /// assert!(closest.approx_equal(Point3::new(3.0, 0.0, 0.0), 1.0e-6));
+    /// ```
+    pub fn closest_point_to(&self, point: Point3) -> Point3 {
+        let t = (point - self.origin).dot(Vec3::from(self.direction));
+        self.origin + self.direction * t
+    }
+
+    /// Расстояние от прямой до точки.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::{Line3, Point3, UVec3};
+    ///
+    /// let line = Line3::new(Point3::new(0.0, 0.0, 0.0), UVec3::plus_x());
+    ///
+    /// assert!((line.distance_to_point(Point3::new(3.0, 4.0, 0.0)) - 4.0).abs() < 1.0e-6);
+    /// ```
+    pub fn distance_to_point(&self, point: Point3) -> f32 {
+        (point - self.closest_point_to(point)).length()
+    }
+
+    /// Расстояние между отрезками `[p1, q1]` и `[p2, q2]`.
+    ///
+    /// Отрезки могут быть вырождены в точку.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::{Line3, Point3};
+    ///
+    /// // скрещивающиеся отрезки на высоте 0 и 2
+    /// let distance = Line3::segment_distance(
+    ///     Point3::new(-1.0, 0.0, 0.0),
+    ///     Point3::new(1.0, 0.0, 0.0),
+    ///     Point3::new(0.0, 2.0, -1.0),
+    ///     Point3::new(0.0, 2.0, 1.0),
+    /// );
+    /// assert!((distance - 2.0).abs() < 1.0e-6);
+    /// ```
+    pub fn segment_distance(p1: Point3, q1: Point3, p2: Point3, q2: Point3) -> f32 {
+        let (c1, c2) = closest_points_on_segments(p1, q1, p2, q2);
+        (c2 - c1).length()
+    }
+}
+
+/// Ближайшие друг к другу точки отрезков `[p1, q1]` и `[p2, q2]`.
+///
+/// Алгоритм из "Real-Time Collision Detection" (К. Эриксон): минимизация расстояния
+/// по параметрам обоих отрезков с последующим ограничением параметров до `[0, 1]`.
+fn closest_points_on_segments(p1: Point3, q1: Point3, p2: Point3, q2: Point3) -> (Point3, Point3) {
+    let d1 = q1 - p1;
+    let d2 = q2 - p2;
+    let r = p1 - p2;
+    let a = d1.dot(d1);
+    let e = d2.dot(d2);
+    let f = d2.dot(r);
+
+    let (s, t) = if a <= PARALLEL_EPSILON && e <= PARALLEL_EPSILON {
+        // оба отрезка - точки
+        (0.0, 0.0)
+    } else if a <= PARALLEL_EPSILON {
+        // первый отрезок - точка
+        (0.0, (f / e).clamp(0.0, 1.0))
+    } else {
+        let c = d1.dot(r);
+        if e <= PARALLEL_EPSILON {
+            // второй отрезок - точка
+            ((-c / a).clamp(0.0, 1.0), 0.0)
+        } else {
+            let b = d1.dot(d2);
+            let denominator = a * e - b * b;
+            // для параллельных отрезков подойдёт любая точка первого, берём начало
+            let mut s = if denominator > PARALLEL_EPSILON {
+                ((b * f - c * e) / denominator).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let mut t = (b * s + f) / e;
+            if t < 0.0 {
+                t = 0.0;
+                s = (-c / a).clamp(0.0, 1.0);
+            } else if t > 1.0 {
+                t = 1.0;
+                s = ((b - c) / a).clamp(0.0, 1.0);
+            }
+            (s, t)
+        }
+    };
+
+    (p1 + d1 * s, p2 + d2 * t)
+}
+
+#[cfg(test)]
+mod line3_tests {
+    use super::*;
+
+    const TOLERANCE: f32 = 1e-5;
+
+    #[test]
+    fn test_intersect_plane_parallel() {
+        let plane = Plane::new(Point3::zero(), UVec3::up());
+
+        // параллельно плоскости
+        let line = Line3::new(Point3::new(0.0, 1.0, 0.0), UVec3::plus_x());
+        assert!(line.intersect_plane(&plane).is_none());
+        // лежит в плоскости
+        let line = Line3::new(Point3::zero(), UVec3::plus_z());
+        assert!(line.intersect_plane(&plane).is_none());
+    }
+
+    #[test]
+    fn test_intersect_plane_behind_origin() {
+        // плоскость позади начала прямой всё равно пересекается
+        let plane = Plane::new(Point3::new(0.0, 0.0, -3.0), UVec3::plus_z());
+        let direction = Vec3::new(1.0, 0.0, 1.0).normalize().unwrap();
+        let line = Line3::new(Point3::zero(), direction);
+
+        let point = line.intersect_plane(&plane).unwrap();
+        assert!(
+            point.approx_equal(Point3::new(-3.0, 0.0, -3.0), TOLERANCE),
+            "ожидалась точка (-3, 0, -3), но получено {}",
+            point
+        );
+        assert!(plane.distance_to_point(point).abs() < TOLERANCE);
+    }
+
+    #[test]
+    fn test_closest_point_to_point_on_line() {
+        let line = Line3::from_points(Point3::new(1.0, 1.0, 1.0), Point3::new(2.0, 2.0, 2.0));
+        let point = Point3::new(5.0, 5.0, 5.0);
+        assert!(line.closest_point_to(point).approx_equal(point, TOLERANCE));
+        assert!(line.distance_to_point(point) < TOLERANCE);
+    }
+
+    #[test]
+    fn test_segment_distance_endpoints() {
+        // ближайшие точки - концы отрезков
+        let distance = Line3::segment_distance(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(2.0, 1.0, 0.0),
+            Point3::new(3.0, 1.0, 0.0),
+        );
+        assert!(
+            (distance - 2.0_f32.sqrt()).abs() < TOLERANCE,
+            "ожидалось расстояние sqrt(2), но получено {}",
+            distance
+        );
+    }
+
+    #[test]
+    fn test_segment_distance_parallel_and_crossing() {
+        // параллельные перекрывающиеся отрезки
+        let distance = Line3::segment_distance(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 3.0),
+            Point3::new(5.0, 0.0, 3.0),
+        );
+        assert!((distance - 3.0).abs() < TOLERANCE);
+
+        // пересекающиеся отрезки
+        let distance = Line3::segment_distance(
+            Point3::new(-1.0, -1.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(-1.0, 1.0, 0.0),
+            Point3::new(1.0, -1.0, 0.0),
+        );
+        assert!(distance < TOLERANCE);
+    }
+
+    #[test]
+    fn test_segment_distance_degenerate() {
+        let point = Point3::new(0.0, 3.0, 0.0);
+        // точка и отрезок
+        let distance = Line3::segment_distance(
+            point,
+            point,
+            Point3::new(-1.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+        );
+        assert!((distance - 3.0).abs() < TOLERANCE);
+        // две точки
+        let distance = Line3::segment_distance(point, point, Point3::zero(), Point3::zero());
+        assert!((distance - 3.0).abs() < TOLERANCE);
+    }
 }
@@ -1,19 +1,27 @@
 //! Модуль с примитивами для 3D графики по типу точек, векторов и подобных объектов.
 
 // объявление модулей-примитивов
+mod aabb;
+mod curve3;
+mod double;
 mod hvec3;
 mod line3;
 mod plane;
 mod point3;
+mod section_box;
 mod transform3;
 mod uvec3;
 mod vec3;
 
 // re-export модулей в этот модуль
+pub use aabb::*;
+pub use curve3::*;
+pub use double::*;
 pub use hvec3::*;
 pub use line3::*;
 pub use plane::*;
 pub use point3::*;
+pub use section_box::*;
 pub use transform3::*;
 pub use uvec3::*;
 pub use vec3::*;
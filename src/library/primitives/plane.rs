@@ -1,6 +1,9 @@
 //! Объявление и реализация структуры `Plane`.
 
-use super::{Point3, UVec3};
+use super::{Line3, Point3, UVec3, Vec3};
+
+/// Точность, с которой плоскости считаются параллельными.
+const PARALLEL_EPSILON: f32 = 1e-6;
 
 /// Плоскость в 3D пространстве.
 ///
@@ -28,4 +31,142 @@ impl Plane {
     pub fn new(origin: Point3, normal: UVec3) -> Self {
         Self { origin, normal }
     }
+
+    // --------------------------------------------------
+    // Геометрические запросы
+    // --------------------------------------------------
+
+    /// Расстояние со знаком от плоскости до точки.
+    ///
+    /// Положительно, если точка находится со стороны нормали, и отрицательно иначе.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::{Plane, Point3, UVec3};
+    ///
+    /// let plane = Plane::new(Point3::new(0.0, 1.0, 0.0), UVec3::up());
+    ///
+    /// assert!((plane.distance_to_point(Point3::new(5.0, 4.0, 2.0)) - 3.0).abs() < 1.0e-6);
+    /// assert!((plane.distance_to_point(Point3::new(5.0, -1.0, 2.0)) + 2.0).abs() < 1.0e-6);
+    /// ```
+    pub fn distance_to_point(&self, point: Point3) -> f32 {
+        (point - self.origin).dot(Vec3::from(self.normal))
+    }
+
+    /// Ортогональная проекция точки на плоскость.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::{Plane, Point3, UVec3};
+    ///
+    /// let plane = Plane::new(Point3::new(0.0, 1.0, 0.0), UVec3::up());
+    ///
+    /// let projected = plane.project_point(Point3::new(5.0, 4.0, 2.0));
+    /// assert!(projected.approx_equal(Point3::new(5.0, 1.0, 2.0), 1.0e-6));
+    /// ```
+    pub fn project_point(&self, point: Point3) -> Point3 {
+        point + self.normal * -self.distance_to_point(point)
+    }
+
+    /// Прямая пересечения двух плоскостей.
+    ///
+    /// Направление прямой - векторное произведение нормалей `self` и `other`,
+    /// начало прямой - ближайшая к `self.origin` точка пересечения.
+    /// Для параллельных (и совпадающих) плоскостей возвращает `None`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::{Plane, Point3, UVec3};
+    ///
+    /// let floor = Plane::new(Point3::new(0.0, 0.0, 0.0), UVec3::up());
+    /// let wall = Plane::new(Point3::new(2.0, 0.0, 0.0), UVec3::plus_x());
+    ///
+    /// let line = floor.intersect_plane(&wall).unwrap();
+    /// assert!(line.origin.approx_equal(Point3::new(2.0, 0.0, 0.0), 1.0e-6));
+    /// assert!(line.direction.cross(UVec3::plus_z()).length() < 1.0e-6);
+    /// ```
+    pub fn intersect_plane(&self, other: &Plane) -> Option<Line3> {
+        let n1 = Vec3::from(self.normal);
+        let n2 = Vec3::from(other.normal);
+        let direction = n1.cross(n2);
+        let denominator = direction.dot(direction);
+        if denominator < PARALLEL_EPSILON {
+            return None;
+        }
+
+        // точка на обеих плоскостях, ближайшая к self.origin: сдвиг из self.origin
+        // внутри self до other вдоль проекции n2 на self
+        let along = n2 - n1 * n1.dot(n2);
+        let t = -other.distance_to_point(self.origin) / along.dot(n2);
+        let origin = self.origin + along * t;
+
+        Some(Line3::new(origin, direction.normalize().ok()?))
+    }
+
+    /// Точка пересечения плоскости с прямой.
+    ///
+    /// То же самое, что и [`Line3::intersect_plane`].
+    pub fn intersect_line(&self, line: &Line3) -> Option<Point3> {
+        line.intersect_plane(self)
+    }
+}
+
+#[cfg(test)]
+mod plane_tests {
+    use super::*;
+
+    const TOLERANCE: f32 = 1e-5;
+
+    fn tilted_plane() -> Plane {
+        Plane::new(
+            Point3::new(1.0, 2.0, 3.0),
+            Vec3::new(1.0, 2.0, -1.0).normalize().unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_project_point_lies_on_plane() {
+        let plane = tilted_plane();
+        let point = Point3::new(-4.0, 7.0, 0.5);
+
+        let projected = plane.project_point(point);
+        assert!(
+            plane.distance_to_point(projected).abs() < TOLERANCE,
+            "проекция {} должна лежать на плоскости",
+            projected
+        );
+        // смещение при проекции параллельно нормали
+        let offset = point - projected;
+        assert!(offset.cross(Vec3::from(plane.normal)).length() < TOLERANCE);
+        assert!((offset.length() - plane.distance_to_point(point).abs()).abs() < TOLERANCE);
+    }
+
+    #[test]
+    fn test_intersect_plane_lies_on_both() {
+        let a = tilted_plane();
+        let b = Plane::new(
+            Point3::new(-2.0, 0.0, 1.0),
+            Vec3::new(0.0, 1.0, 1.0).normalize().unwrap(),
+        );
+
+        let line = a.intersect_plane(&b).unwrap();
+        for t in [-5.0, 0.0, 3.0] {
+            let point = line.origin + line.direction * t;
+            assert!(a.distance_to_point(point).abs() < TOLERANCE);
+            assert!(b.distance_to_point(point).abs() < TOLERANCE);
+        }
+        // начало прямой - ближайшая к a.origin точка
+        assert!(
+            line.closest_point_to(a.origin)
+                .approx_equal(line.origin, TOLERANCE)
+        );
+    }
+
+    #[test]
+    fn test_intersect_plane_parallel() {
+        let a = Plane::new(Point3::zero(), UVec3::up());
+        let b = Plane::new(Point3::new(0.0, 3.0, 0.0), UVec3::down());
+        assert!(a.intersect_plane(&b).is_none());
+        assert!(a.intersect_plane(&a).is_none());
+    }
 }
@@ -12,6 +12,7 @@ use std::{
 /// 3D пространстве. Для направления надо использовать `Vec3`. О координатной системе
 /// подробнее можно узнать в `CoordFrame`.
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Point3 {
     pub x: f32,
     pub y: f32,
@@ -247,6 +248,10 @@ impl TryFrom<HVec3> for Point3 {
     /// `HVec3` описывает какую-то точку 3D пространства только если `w != 0`, в противном случае
     /// `HVec3` представляет собой направление, но не точку пространства.
     ///
+    /// Кроме того, `w < 0` после перспективной проекции означает, что точка находится позади камеры:
+    /// деление на такую `w` даёт зеркально отражённую точку, поэтому такое преобразование тоже
+    /// считается ошибкой. Такие точки нужно заранее отсекать (см. `utils::clip_segment_by_w`).
+    ///
     /// # Examples
     /// ```rust
     /// use g3d::{Point3, PointError, HVec3};
@@ -264,9 +269,13 @@ impl TryFrom<HVec3> for Point3 {
     /// let err = Point3::try_from(hvec_direction).unwrap_err();
     ///
     /// assert_eq!(err, PointError::new(hvec_direction));
+    ///
+    /// // если hvec - точка позади камеры
+    /// let hvec_behind = HVec3::new(1.0, 2.0, 3.0, -1.0);
+    /// assert!(Point3::try_from(hvec_behind).is_err());
     /// ```
     fn try_from(value: HVec3) -> Result<Self, Self::Error> {
-        if value.w == 0.0 {
+        if value.w.is_nan() || value.w <= 0.0 {
             Err(PointError(value))
         } else {
             Ok(Self::new(
@@ -281,7 +290,7 @@ impl TryFrom<HVec3> for Point3 {
 /// Ошибка при преобразовании `HVec3` в `Point3`.
 ///
 /// Возникает когда компонента `w=0`, то есть `HVec3` обозначает направление,
-/// поэтому не может быть точкой.
+/// поэтому не может быть точкой, либо когда `w<0`, то есть точка оказалась позади камеры.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct PointError(HVec3);
 
@@ -295,8 +304,8 @@ impl Display for PointError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "{} не может быть преобразован в Point3 из-за w=0",
-            self.0
+            "{} не может быть преобразован в Point3 из-за w={} <= 0",
+            self.0, self.0.w
         )
     }
 }
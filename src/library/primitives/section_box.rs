@@ -0,0 +1,161 @@
+//! Объявление и реализация структуры `SectionBox`.
+
+use super::{Plane, Point3, UVec3, Vec3};
+
+/// Секущий параллелепипед (section box) в 3D пространстве.
+///
+/// Задаётся центром, половинами длин сторон и ориентацией. Всё, что находится вне параллелепипеда,
+/// отсекается при отрисовке модели, что позволяет заглянуть внутрь неё без изменения самой модели.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SectionBox {
+    /// Центр параллелепипеда в глобальных координатах.
+    pub center: Point3,
+    /// Половины длин сторон вдоль направлений `right`, `up` и `forward` соответственно.
+    pub half_extents: Vec3,
+    /// Направление первой стороны.
+    right: UVec3,
+    /// Направление второй стороны.
+    up: UVec3,
+    /// Направление третьей стороны.
+    forward: UVec3,
+}
+
+impl SectionBox {
+    /// Создать параллелепипед, выровненный по глобальным осям, по минимальной и максимальной точкам.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::{Point3, SectionBox};
+    ///
+    /// let section_box = SectionBox::axis_aligned(Point3::new(-1.0, -1.0, -1.0), Point3::new(1.0, 3.0, 1.0));
+    ///
+    /// assert!(section_box.center.approx_equal(Point3::new(0.0, 1.0, 0.0), 1.0e-6));
+    /// assert!(section_box.contains(Point3::new(0.5, 2.5, -0.5)));
+    /// assert!(!section_box.contains(Point3::new(0.5, 3.5, -0.5)));
+    /// ```
+    pub fn axis_aligned(min: Point3, max: Point3) -> Self {
+        debug_assert!(
+            min.x <= max.x && min.y <= max.y && min.z <= max.z,
+            "минимальная точка {:?} должна быть не больше максимальной {:?}",
+            min,
+            max
+        );
+
+        let half_extents = (max - min) * 0.5;
+        Self {
+            center: min + half_extents,
+            half_extents,
+            right: UVec3::plus_x(),
+            up: UVec3::plus_y(),
+            forward: UVec3::plus_z(),
+        }
+    }
+
+    /// Создать произвольно ориентированный параллелепипед.
+    ///
+    /// `center` - центр параллелепипеда;
+    /// `half_extents` - половины длин сторон вдоль `right`, `up` и `forward`;
+    /// `forward` - направление третьей стороны;
+    /// `up` - направление второй стороны, должно быть перпендикулярно `forward`;
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::{Point3, SectionBox, UVec3, Vec3};
+    ///
+    /// // параллелепипед, повёрнутый на 45 градусов вокруг оси Y
+    /// let section_box = SectionBox::oriented(
+    ///     Point3::zero(),
+    ///     Vec3::new(1.0, 1.0, 1.0),
+    ///     UVec3::new(1.0, 0.0, 1.0),
+    ///     UVec3::plus_y(),
+    /// );
+    ///
+    /// assert!(section_box.contains(Point3::new(0.0, 0.0, 1.3)));
+    /// assert!(!section_box.contains(Point3::new(0.9, 0.0, 0.9)));
+    /// ```
+    pub fn oriented(center: Point3, half_extents: Vec3, forward: UVec3, up: UVec3) -> Self {
+        debug_assert!(
+            half_extents.x >= 0.0 && half_extents.y >= 0.0 && half_extents.z >= 0.0,
+            "размеры параллелепипеда {:?} не могут быть отрицательными",
+            half_extents
+        );
+        debug_assert!(
+            forward.dot(up).abs() < 1e-4,
+            "направления forward и up должны быть перпендикулярны"
+        );
+
+        let right = up
+            .cross(forward)
+            .normalize()
+            .expect("направления forward и up не должны быть параллельны");
+        Self {
+            center,
+            half_extents,
+            right,
+            up,
+            forward,
+        }
+    }
+
+    /// Направления сторон параллелепипеда в порядке `right`, `up`, `forward`.
+    pub fn axes(&self) -> [UVec3; 3] {
+        [self.right, self.up, self.forward]
+    }
+
+    /// Находится ли точка внутри параллелепипеда (включая границу).
+    pub fn contains(&self, point: Point3) -> bool {
+        let offset = point - self.center;
+        let extents = [
+            self.half_extents.x,
+            self.half_extents.y,
+            self.half_extents.z,
+        ];
+        self.axes()
+            .iter()
+            .zip(extents)
+            .all(|(&axis, extent)| offset.dot(axis.into()).abs() <= extent)
+    }
+
+    /// Получить 6 граничных плоскостей параллелепипеда.
+    ///
+    /// Нормали плоскостей направлены внутрь параллелепипеда.
+    pub fn planes(&self) -> [Plane; 6] {
+        let [right, up, forward] = self.axes();
+        let h = self.half_extents;
+        [
+            Plane::new(self.center + right * -h.x, right),
+            Plane::new(self.center + right * h.x, -right),
+            Plane::new(self.center + up * -h.y, up),
+            Plane::new(self.center + up * h.y, -up),
+            Plane::new(self.center + forward * -h.z, forward),
+            Plane::new(self.center + forward * h.z, -forward),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod section_box_tests {
+    use super::*;
+
+    #[test]
+    fn test_planes_face_inside() {
+        let section_box =
+            SectionBox::axis_aligned(Point3::new(-1.0, -2.0, -3.0), Point3::new(1.0, 2.0, 3.0));
+        for plane in section_box.planes() {
+            let to_center = section_box.center - plane.origin;
+            assert!(
+                to_center.dot(plane.normal.into()) > 0.0,
+                "нормаль плоскости {:?} должна смотреть внутрь",
+                plane
+            );
+        }
+    }
+
+    #[test]
+    fn test_contains_boundary() {
+        let section_box = SectionBox::axis_aligned(Point3::zero(), Point3::new(1.0, 1.0, 1.0));
+        assert!(section_box.contains(Point3::new(1.0, 0.5, 0.0)));
+        assert!(!section_box.contains(Point3::new(1.01, 0.5, 0.0)));
+    }
+}
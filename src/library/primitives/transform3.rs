@@ -2,6 +2,9 @@
 
 use super::{HVec3, Line3, Plane, Point3, UVec3, Vec3};
 
+/// Относительная погрешность при проверке состава преобразования (движение, масштабирование).
+const DECOMPOSITION_TOLERANCE: f32 = 1e-4;
+
 /// Матрица преобразования 4x4 для 3D пространства.
 ///
 /// Поскольку матрица 4x4, к ней нужны 4D вектора для применения преобразования.
@@ -15,6 +18,7 @@ use super::{HVec3, Line3, Plane, Point3, UVec3, Vec3};
 ///                 | m41 m42 m43 m44 |
 /// ```
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Transform3D {
     /// Матрица преобразования 4x4 в виде одномерного массива в row-major порядке.
     pub m: [f32; 16],
@@ -450,6 +454,57 @@ impl Transform3D {
             ],
         }
     }
+
+    /// Создает матрицу сдвига (скоса), при котором координата X смещается на `factor * y`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::{Transform3D, HVec3};
+    ///
+    /// let hvec = HVec3::new(1.0, 2.0, 3.0, 1.0);
+    /// let res = hvec * Transform3D::shear_xy(0.5);
+    ///
+    /// assert!((res.x - 2.0).abs() < 1.0e-6);
+    /// assert!((res.y - 2.0).abs() < 1.0e-6);
+    /// assert!((res.z - 3.0).abs() < 1.0e-6);
+    /// ```
+    pub fn shear_xy(factor: f32) -> Self {
+        Self::shear(0, 1, factor)
+    }
+
+    /// Создает матрицу сдвига, при котором координата X смещается на `factor * z`.
+    pub fn shear_xz(factor: f32) -> Self {
+        Self::shear(0, 2, factor)
+    }
+
+    /// Создает матрицу сдвига, при котором координата Y смещается на `factor * x`.
+    pub fn shear_yx(factor: f32) -> Self {
+        Self::shear(1, 0, factor)
+    }
+
+    /// Создает матрицу сдвига, при котором координата Y смещается на `factor * z`.
+    pub fn shear_yz(factor: f32) -> Self {
+        Self::shear(1, 2, factor)
+    }
+
+    /// Создает матрицу сдвига, при котором координата Z смещается на `factor * x`.
+    pub fn shear_zx(factor: f32) -> Self {
+        Self::shear(2, 0, factor)
+    }
+
+    /// Создает матрицу сдвига, при котором координата Z смещается на `factor * y`.
+    pub fn shear_zy(factor: f32) -> Self {
+        Self::shear(2, 1, factor)
+    }
+
+    /// Матрица сдвига координаты с индексом `target` на `factor`, умноженный
+    /// на координату с индексом `source`.
+    fn shear(target: usize, source: usize, factor: f32) -> Self {
+        let mut transform = Self::identity();
+        // вектор умножается на матрицу слева, поэтому вклад `source` лежит в его строке
+        transform.m[source * 4 + target] = factor;
+        transform
+    }
 }
 
 // --------------------------------------------------
@@ -480,6 +535,33 @@ impl Transform3D {
             .multiply(Self::translation(anchor.x, anchor.y, anchor.z))
     }
 
+    /// Полное аффинное преобразование: масштабирование `scale`, затем поворот `rotation`,
+    /// затем перемещение на `translation`.
+    ///
+    /// `rotation` должен содержать только вращение (например, [`Transform3D::rotation_around_axis`]).
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::{Transform3D, HVec3, Vec3};
+    ///
+    /// let transform = Transform3D::affine(
+    ///     Vec3::new(1.0, 0.0, 0.0),
+    ///     Transform3D::rotation_z_deg(90.0),
+    ///     Vec3::new(2.0, 1.0, 1.0),
+    /// );
+    /// let res = HVec3::new(1.0, 0.0, 0.0, 1.0) * transform;
+    ///
+    /// // (1, 0, 0) -> (2, 0, 0) -> (0, 2, 0) -> (1, 2, 0)
+    /// assert!((res.x - 1.0).abs() < 1.0e-6);
+    /// assert!((res.y - 2.0).abs() < 1.0e-6);
+    /// assert!((res.z - 0.0).abs() < 1.0e-6);
+    /// ```
+    pub fn affine(translation: Vec3, rotation: Self, scale: Vec3) -> Self {
+        Self::scale(scale.x, scale.y, scale.z)
+            .multiply(rotation)
+            .multiply(Self::translation_vec(translation))
+    }
+
     /// Отражение относительно плоскости XY.
     ///
     /// # Examples
@@ -627,6 +709,71 @@ impl Transform3D {
         // Композиция операций
         to_origin.multiply(rotate).multiply(from_origin)
     }
+
+    /// Матрица вида: переход из глобальных координат в координаты наблюдателя,
+    /// который находится в точке `eye` и смотрит на точку `target`.
+    ///
+    /// В координатах наблюдателя он находится в начале координат, направление на `target`
+    /// совпадает с -z, `up` (спроецированный на плоскость взгляда) совпадает с +y.
+    /// Это та же матрица, что и у камеры, созданной через `Camera::new` с такими же параметрами.
+    /// `up` не должен быть параллелен направлению взгляда.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::{HVec3, Point3, Transform3D, UVec3};
+    ///
+    /// let eye = Point3::new(0.0, 0.0, 5.0);
+    /// let view = Transform3D::look_at(eye, Point3::zero(), UVec3::up());
+    ///
+    /// // цель оказывается перед наблюдателем по -z
+    /// let target = HVec3::from(Point3::zero()) * view;
+    /// assert!(target.approx_equal(HVec3::new(0.0, 0.0, -5.0, 1.0), 1.0e-6));
+    /// ```
+    pub fn look_at(eye: Point3, target: Point3, up: UVec3) -> Self {
+        let (backward, right, up) = look_at_basis(eye, target, up);
+        Self::translation_vec(-Vec3::from(eye))
+            .multiply(Self::rotation_to_basis(backward, right, up))
+    }
+
+    /// Обратная к [`Transform3D::look_at`] матрица: из координат наблюдателя в глобальные.
+    ///
+    /// Подходит для ориентации объектов: модель с этой матрицей будет находиться в точке `eye`
+    /// и смотреть своей локальной осью -z на `target` (например, billboard, повёрнутый
+    /// осью +z к камере, получается при `target`, отражённом от камеры относительно `eye`).
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::{HVec3, Point3, Transform3D, UVec3};
+    ///
+    /// let eye = Point3::new(1.0, 2.0, 3.0);
+    /// let target = Point3::new(-4.0, 0.0, 1.0);
+    /// let view = Transform3D::look_at(eye, target, UVec3::up());
+    /// let inverse = Transform3D::look_at_inverse(eye, target, UVec3::up());
+    ///
+    /// let point = HVec3::new(5.0, -1.0, 2.0, 1.0);
+    /// assert!((point * view * inverse).approx_equal(point, 1.0e-5));
+    /// ```
+    pub fn look_at_inverse(eye: Point3, target: Point3, up: UVec3) -> Self {
+        let (backward, right, up) = look_at_basis(eye, target, up);
+        Self::rotation_from_basis(backward, right, up)
+            .multiply(Self::translation_vec(Vec3::from(eye)))
+    }
+}
+
+/// Ортонормированный базис (назад, вправо, вверх) наблюдателя в `eye`, смотрящего на `target`.
+fn look_at_basis(eye: Point3, target: Point3, up: UVec3) -> (UVec3, UVec3, UVec3) {
+    let backward = (eye - target)
+        .normalize()
+        .expect("точки eye и target не должны совпадать");
+    let right = up
+        .cross(backward)
+        .normalize()
+        .expect("up не должен быть параллелен направлению взгляда");
+    let up = backward
+        .cross(right)
+        .normalize()
+        .expect("backward и right ортогональны, поэтому их произведение не нулевое");
+    (backward, right, up)
 }
 
 // --------------------------------------------------
@@ -779,6 +926,74 @@ impl Transform3D {
         }
     }
 
+    /// Проверенный вариант [`Transform3D::parallel`].
+    ///
+    /// В отличие от него, некорректные параметры не проверяются одним лишь `debug_assert!`,
+    /// а возвращаются как ошибка и в release-сборке, вместо матрицы с NaN.
+    pub fn try_parallel(
+        left: f32,
+        right: f32,
+        bottom: f32,
+        top: f32,
+        near: f32,
+        far: f32,
+    ) -> Result<Self, ProjectionError> {
+        if ![left, right, bottom, top, near, far]
+            .iter()
+            .all(|value| value.is_finite())
+        {
+            return Err(ProjectionError::NonFiniteParameter);
+        }
+        if left >= right || bottom >= top {
+            return Err(ProjectionError::EmptyViewVolume);
+        }
+        check_clip_planes(near, far)?;
+
+        Ok(Self::parallel(left, right, bottom, top, near, far))
+    }
+
+    /// Проверенный вариант [`Transform3D::parallel_from_fov`].
+    pub fn try_parallel_from_fov(
+        fov_rad: f32,
+        aspect: f32,
+        near: f32,
+        far: f32,
+    ) -> Result<Self, ProjectionError> {
+        check_fov_and_aspect(fov_rad, aspect)?;
+        check_clip_planes(near, far)?;
+
+        Ok(Self::parallel_from_fov(fov_rad, aspect, near, far))
+    }
+
+    /// Проверенный вариант [`Transform3D::perspective`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use g3d::{ProjectionError, Transform3D};
+    ///
+    /// assert!(Transform3D::try_perspective(1.0, 1.5, 0.1, 100.0).is_ok());
+    /// assert_eq!(
+    ///     Transform3D::try_perspective(1.0, 1.5, 0.0, 100.0),
+    ///     Err(ProjectionError::InvalidNearPlane)
+    /// );
+    /// assert_eq!(
+    ///     Transform3D::try_perspective(1.0, 0.0, 0.1, 100.0),
+    ///     Err(ProjectionError::InvalidAspectRatio)
+    /// );
+    /// ```
+    pub fn try_perspective(
+        fov_rad: f32,
+        aspect: f32,
+        near: f32,
+        far: f32,
+    ) -> Result<Self, ProjectionError> {
+        check_fov_and_aspect(fov_rad, aspect)?;
+        check_clip_planes(near, far)?;
+
+        Ok(Self::perspective(fov_rad, aspect, near, far))
+    }
+
     /// Создает матрицу аксонометрической проекции с произвольными углами.
     pub fn axonometric(angle_x_deg: f32, angle_y_deg: f32, angle_z_deg: f32) -> Self {
         let angle_x = angle_x_deg.to_radians();
@@ -791,6 +1006,70 @@ impl Transform3D {
     }
 }
 
+/// Ошибки построения матрицы проекции.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProjectionError {
+    /// Один из параметров равен NaN или бесконечности.
+    NonFiniteParameter,
+    /// Ближняя плоскость отсечения не положительна.
+    InvalidNearPlane,
+    /// Дальняя плоскость отсечения не дальше ближней.
+    InvalidFarPlane,
+    /// Соотношение сторон не положительно.
+    InvalidAspectRatio,
+    /// Угол обзора вне интервала (0, π).
+    InvalidFov,
+    /// Границы объёма видимости пусты (left >= right или bottom >= top).
+    EmptyViewVolume,
+    /// Область вывода (canvas) имеет нулевую ширину или высоту.
+    DegenerateViewport,
+}
+
+impl std::fmt::Display for ProjectionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            Self::NonFiniteParameter => "параметр проекции не является конечным числом",
+            Self::InvalidNearPlane => "ближняя плоскость отсечения должна быть положительной",
+            Self::InvalidFarPlane => "дальняя плоскость отсечения должна быть дальше ближней",
+            Self::InvalidAspectRatio => "соотношение сторон должно быть положительным",
+            Self::InvalidFov => "угол обзора должен лежать в интервале (0, π)",
+            Self::EmptyViewVolume => "объём видимости пуст",
+            Self::DegenerateViewport => "область вывода имеет нулевой размер",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+impl std::error::Error for ProjectionError {}
+
+/// Проверка плоскостей отсечения для проекций.
+fn check_clip_planes(near: f32, far: f32) -> Result<(), ProjectionError> {
+    if !near.is_finite() || !far.is_finite() {
+        return Err(ProjectionError::NonFiniteParameter);
+    }
+    if near <= 0.0 {
+        return Err(ProjectionError::InvalidNearPlane);
+    }
+    if far <= near {
+        return Err(ProjectionError::InvalidFarPlane);
+    }
+    Ok(())
+}
+
+/// Проверка угла обзора и соотношения сторон для проекций.
+fn check_fov_and_aspect(fov_rad: f32, aspect: f32) -> Result<(), ProjectionError> {
+    if !fov_rad.is_finite() || !aspect.is_finite() {
+        return Err(ProjectionError::NonFiniteParameter);
+    }
+    if fov_rad <= 0.0 || std::f32::consts::PI <= fov_rad {
+        return Err(ProjectionError::InvalidFov);
+    }
+    if aspect <= 0.0 {
+        return Err(ProjectionError::InvalidAspectRatio);
+    }
+    Ok(())
+}
+
 // --------------------------------------------------
 // Вспомогательные функции
 // --------------------------------------------------
@@ -865,6 +1144,58 @@ impl Transform3D {
         )
     }
 
+    /// Применить преобразование к массиву однородных векторов.
+    ///
+    /// Результат совпадает с поэлементным `apply_to_hvec`, но векторы обрабатываются
+    /// пачками по 4: координаты пачки раскладываются по отдельным массивам, что позволяет
+    /// компилятору использовать SIMD-инструкции. Выгодно для больших Mesh'ей.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::{HVec3, Transform3D};
+    ///
+    /// let transform = Transform3D::translation(1.0, 2.0, 3.0);
+    /// let hvecs = vec![HVec3::new(1.0, 1.0, 1.0, 1.0); 5];
+    ///
+    /// let transformed = transform.apply_to_slice(&hvecs);
+    /// assert_eq!(transformed.len(), 5);
+    /// assert!(transformed[4].approx_equal(HVec3::new(2.0, 3.0, 4.0, 1.0), 1.0e-6));
+    /// ```
+    pub fn apply_to_slice(self, hvecs: &[HVec3]) -> Vec<HVec3> {
+        let mut result = hvecs.to_vec();
+        self.apply_to_slice_in_place(&mut result);
+        result
+    }
+
+    /// Применить преобразование к массиву однородных векторов на месте.
+    ///
+    /// То же самое, что и [`Transform3D::apply_to_slice`], но без выделения памяти.
+    pub fn apply_to_slice_in_place(self, hvecs: &mut [HVec3]) {
+        let m = &self.m;
+        let mut chunks = hvecs.chunks_exact_mut(4);
+        for chunk in &mut chunks {
+            // раскладка пачки по координатам (structure of arrays)
+            let x: [f32; 4] = std::array::from_fn(|k| chunk[k].x);
+            let y: [f32; 4] = std::array::from_fn(|k| chunk[k].y);
+            let z: [f32; 4] = std::array::from_fn(|k| chunk[k].z);
+            let w: [f32; 4] = std::array::from_fn(|k| chunk[k].w);
+
+            let column = |c: usize| -> [f32; 4] {
+                std::array::from_fn(|k| {
+                    x[k] * m[c] + y[k] * m[4 + c] + z[k] * m[8 + c] + w[k] * m[12 + c]
+                })
+            };
+            let (nx, ny, nz, nw) = (column(0), column(1), column(2), column(3));
+
+            for (k, hvec) in chunk.iter_mut().enumerate() {
+                *hvec = HVec3::new(nx[k], ny[k], nz[k], nw[k]);
+            }
+        }
+        for hvec in chunks.into_remainder() {
+            *hvec = self.apply_to_hvec(*hvec);
+        }
+    }
+
     /// Возвращает транспонированную матрицу.
     pub fn transpose(self) -> Self {
         Self {
@@ -878,13 +1209,98 @@ impl Transform3D {
     }
 
     /// Возвращает обратную матрицу (если возможно).
+    ///
+    /// Для аффинных преобразований (последний столбец `(0, 0, 0, 1)`) используется быстрый
+    /// расчёт через обратную 3x3 матрицу, для остальных (например, проекций) - полный
+    /// расчёт через алгебраические дополнения. Для вырожденной матрицы возвращает `None`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::{HVec3, Transform3D};
+    ///
+    /// let projection = Transform3D::perspective(1.0, 1.5, 0.1, 100.0);
+    /// let inverse = projection.inverse().unwrap();
+    ///
+    /// let point = HVec3::new(1.0, -2.0, -5.0, 1.0);
+    /// assert!((point * projection * inverse).approx_equal(point, 1.0e-4));
+    ///
+    /// assert!(Transform3D::scale(1.0, 0.0, 1.0).inverse().is_none());
+    /// ```
     pub fn inverse(self) -> Option<Self> {
+        if self.is_affine() {
+            self.inverse_affine()
+        } else {
+            self.inverse_general()
+        }
+    }
+
+    /// Является ли преобразование аффинным (последний столбец равен `(0, 0, 0, 1)`).
+    pub fn is_affine(&self) -> bool {
+        self.m[3] == 0.0 && self.m[7] == 0.0 && self.m[11] == 0.0 && self.m[15] == 1.0
+    }
+
+    /// Является ли преобразование движением: поворотом и перемещением без масштабирования,
+    /// скоса и отражения (с точностью до ошибок округления).
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::Transform3D;
+    ///
+    /// let transform = Transform3D::rotation_y_deg(30.0) * Transform3D::translation(1.0, 2.0, 3.0);
+    /// assert!(transform.is_rigid());
+    /// assert!(!(Transform3D::scale_uniform(2.0) * transform).is_rigid());
+    /// assert!(!Transform3D::reflection_xy().is_rigid());
+    /// ```
+    pub fn is_rigid(&self) -> bool {
+        self.is_affine()
+            && self.has_orthogonal_axes()
+            && self
+                .linear_rows()
+                .iter()
+                .all(|row| (row.length() - 1.0).abs() <= DECOMPOSITION_TOLERANCE)
+            && self.determinant() > 0.0
+    }
+
+    /// Масштабирует ли преобразование одинаково по всем осям: составлено ли оно из поворота
+    /// (возможно, с отражением), равномерного масштабирования и перемещения.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::Transform3D;
+    ///
+    /// assert!((Transform3D::scale_uniform(3.0) * Transform3D::rotation_x_deg(45.0)).has_uniform_scale());
+    /// assert!(!Transform3D::scale(1.0, 2.0, 1.0).has_uniform_scale());
+    /// assert!(!Transform3D::shear_xy(0.5).has_uniform_scale());
+    /// ```
+    pub fn has_uniform_scale(&self) -> bool {
+        let [x, y, z] = self.linear_rows().map(|row| row.length());
+        self.is_affine()
+            && self.has_orthogonal_axes()
+            && x > 0.0
+            && (x - y).abs() <= DECOMPOSITION_TOLERANCE * x
+            && (x - z).abs() <= DECOMPOSITION_TOLERANCE * x
+    }
+
+    /// Строки линейной части (3x3) матрицы - образы базисных векторов.
+    fn linear_rows(&self) -> [Vec3; 3] {
+        [0, 1, 2].map(|i| Vec3::new(self.m[i * 4], self.m[i * 4 + 1], self.m[i * 4 + 2]))
+    }
+
+    /// Перпендикулярны ли друг другу образы базисных векторов (нет скоса).
+    fn has_orthogonal_axes(&self) -> bool {
+        let [x, y, z] = self.linear_rows();
+        [(x, y), (x, z), (y, z)]
+            .iter()
+            .all(|(a, b)| a.dot(*b).abs() <= DECOMPOSITION_TOLERANCE * a.length() * b.length())
+    }
+
+    /// Обратная матрица для аффинного преобразования.
+    fn inverse_affine(self) -> Option<Self> {
         let det = self.determinant();
-        if det == 0.0 {
+        if !det.is_normal() {
             return None;
         }
 
-        // Для афинных преобразований можно использовать упрощенный расчет
         let inv_det = 1.0 / det;
         let mut result = [0.0; 16];
 
@@ -914,6 +1330,60 @@ impl Transform3D {
         Some(Self { m: result })
     }
 
+    /// Обратная матрица общего вида через алгебраические дополнения.
+    ///
+    /// Расчёт не зависит от того, хранится матрица по строкам или по столбцам:
+    /// обратная к транспонированной - это транспонированная обратная.
+    fn inverse_general(self) -> Option<Self> {
+        let m = &self.m;
+
+        // миноры 2x2 из первых двух и последних двух строк
+        let s0 = m[0] * m[5] - m[4] * m[1];
+        let s1 = m[0] * m[6] - m[4] * m[2];
+        let s2 = m[0] * m[7] - m[4] * m[3];
+        let s3 = m[1] * m[6] - m[5] * m[2];
+        let s4 = m[1] * m[7] - m[5] * m[3];
+        let s5 = m[2] * m[7] - m[6] * m[3];
+
+        let c5 = m[10] * m[15] - m[14] * m[11];
+        let c4 = m[9] * m[15] - m[13] * m[11];
+        let c3 = m[9] * m[14] - m[13] * m[10];
+        let c2 = m[8] * m[15] - m[12] * m[11];
+        let c1 = m[8] * m[14] - m[12] * m[10];
+        let c0 = m[8] * m[13] - m[12] * m[9];
+
+        let det = s0 * c5 - s1 * c4 + s2 * c3 + s3 * c2 - s4 * c1 + s5 * c0;
+        if !det.is_normal() {
+            return None;
+        }
+        let inv_det = 1.0 / det;
+
+        #[rustfmt::skip]
+        let result = [
+            ( m[5] * c5 - m[6] * c4 + m[7] * c3) * inv_det,
+            (-m[1] * c5 + m[2] * c4 - m[3] * c3) * inv_det,
+            ( m[13] * s5 - m[14] * s4 + m[15] * s3) * inv_det,
+            (-m[9] * s5 + m[10] * s4 - m[11] * s3) * inv_det,
+
+            (-m[4] * c5 + m[6] * c2 - m[7] * c1) * inv_det,
+            ( m[0] * c5 - m[2] * c2 + m[3] * c1) * inv_det,
+            (-m[12] * s5 + m[14] * s2 - m[15] * s1) * inv_det,
+            ( m[8] * s5 - m[10] * s2 + m[11] * s1) * inv_det,
+
+            ( m[4] * c4 - m[5] * c2 + m[7] * c0) * inv_det,
+            (-m[0] * c4 + m[1] * c2 - m[3] * c0) * inv_det,
+            ( m[12] * s4 - m[13] * s2 + m[15] * s0) * inv_det,
+            (-m[8] * s4 + m[9] * s2 - m[11] * s0) * inv_det,
+
+            (-m[4] * c3 + m[5] * c1 - m[6] * c0) * inv_det,
+            ( m[0] * c3 - m[1] * c1 + m[2] * c0) * inv_det,
+            (-m[12] * s3 + m[13] * s1 - m[14] * s0) * inv_det,
+            ( m[8] * s3 - m[9] * s1 + m[10] * s0) * inv_det,
+        ];
+
+        Some(Self { m: result })
+    }
+
     /// Вычисляет определитель матрицы.
     pub fn determinant(self) -> f32 {
         // Для 4x4 матрицы
@@ -1078,6 +1548,24 @@ mod tests {
         assert_hvecs(transformed_zero, zero_vec, TOLERANCE);
     }
 
+    #[test]
+    fn test_shear() {
+        let hvec = HVec3::new(1.0, 2.0, 3.0, 1.0);
+        let cases = [
+            (Transform3D::shear_xy(2.0), HVec3::new(5.0, 2.0, 3.0, 1.0)),
+            (Transform3D::shear_xz(2.0), HVec3::new(7.0, 2.0, 3.0, 1.0)),
+            (Transform3D::shear_yx(2.0), HVec3::new(1.0, 4.0, 3.0, 1.0)),
+            (Transform3D::shear_yz(2.0), HVec3::new(1.0, 8.0, 3.0, 1.0)),
+            (Transform3D::shear_zx(2.0), HVec3::new(1.0, 2.0, 5.0, 1.0)),
+            (Transform3D::shear_zy(2.0), HVec3::new(1.0, 2.0, 7.0, 1.0)),
+        ];
+        for (shear, expected) in cases {
+            assert_hvecs(shear.apply_to_hvec(hvec), expected, TOLERANCE);
+            // сдвиг сохраняет объём
+            assert_floats(shear.determinant(), 1.0, TOLERANCE);
+        }
+    }
+
     #[test]
     fn test_rotation_x_90() {
         // Поворот на 90 градусов вокруг оси X
@@ -1211,6 +1699,24 @@ mod tests {
         assert_hvecs(transformed, expected, TOLERANCE);
     }
 
+    #[test]
+    fn test_affine_matches_composition() {
+        let translation = Vec3::new(1.0, -2.0, 3.0);
+        let rotation = Transform3D::rotation_around_axis(UVec3::new(1.0, 1.0, 0.0), 0.7);
+        let scale = Vec3::new(2.0, 0.5, 3.0);
+        let affine = Transform3D::affine(translation, rotation, scale);
+        let composed =
+            Transform3D::scale(2.0, 0.5, 3.0) * rotation * Transform3D::translation(1.0, -2.0, 3.0);
+
+        let point = HVec3::new(0.3, -1.2, 2.5, 1.0);
+        assert_hvecs(
+            affine.apply_to_hvec(point),
+            composed.apply_to_hvec(point),
+            TOLERANCE,
+        );
+        assert!(affine.is_affine());
+    }
+
     #[test]
     fn test_reflection_xy() {
         // Отражение относительно плоскости XY
@@ -1518,4 +2024,162 @@ mod tests {
         let translation = Transform3D::translation(1.0, 2.0, 3.0);
         assert_floats(translation.determinant(), 1.0, TOLERANCE);
     }
+
+    // --------------------------------------------------
+    // Тесты проверенных конструкторов проекций
+    // --------------------------------------------------
+
+    #[test]
+    fn test_try_perspective_valid_matches_unchecked() {
+        let checked = Transform3D::try_perspective(1.0, 1.5, 0.1, 100.0).unwrap();
+        let unchecked = Transform3D::perspective(1.0, 1.5, 0.1, 100.0);
+        assert_eq!(
+            checked, unchecked,
+            "проверенная матрица должна совпадать с обычной"
+        );
+    }
+
+    #[test]
+    fn test_try_perspective_invalid() {
+        assert_eq!(
+            Transform3D::try_perspective(1.0, 1.0, 0.0, 10.0),
+            Err(ProjectionError::InvalidNearPlane)
+        );
+        assert_eq!(
+            Transform3D::try_perspective(1.0, 1.0, 1.0, 1.0),
+            Err(ProjectionError::InvalidFarPlane)
+        );
+        assert_eq!(
+            Transform3D::try_perspective(1.0, 0.0, 0.1, 10.0),
+            Err(ProjectionError::InvalidAspectRatio)
+        );
+        assert_eq!(
+            Transform3D::try_perspective(0.0, 1.0, 0.1, 10.0),
+            Err(ProjectionError::InvalidFov)
+        );
+        assert_eq!(
+            Transform3D::try_perspective(1.0, f32::NAN, 0.1, 10.0),
+            Err(ProjectionError::NonFiniteParameter)
+        );
+    }
+
+    #[test]
+    fn test_try_parallel_invalid() {
+        assert!(Transform3D::try_parallel(-1.0, 1.0, -1.0, 1.0, 0.1, 10.0).is_ok());
+        assert_eq!(
+            Transform3D::try_parallel(1.0, 1.0, -1.0, 1.0, 0.1, 10.0),
+            Err(ProjectionError::EmptyViewVolume)
+        );
+        assert_eq!(
+            Transform3D::try_parallel_from_fov(1.0, 0.0, 0.1, 10.0),
+            Err(ProjectionError::InvalidAspectRatio)
+        );
+    }
+
+    #[test]
+    fn test_look_at_basis_mapping() {
+        let eye = Point3::new(3.0, 4.0, -2.0);
+        let target = Point3::new(-1.0, 1.0, 5.0);
+        let view = Transform3D::look_at(eye, target, UVec3::up());
+
+        // наблюдатель в начале координат, цель на -z
+        let origin = HVec3::from(eye) * view;
+        assert_hvecs(origin, HVec3::new(0.0, 0.0, 0.0, 1.0), 1e-5);
+        let distance = (target - eye).length();
+        let target_view = HVec3::from(target) * view;
+        assert_hvecs(target_view, HVec3::new(0.0, 0.0, -distance, 1.0), 1e-5);
+
+        // глобальный up остаётся в верхней полуплоскости
+        let up = HVec3::new(0.0, 1.0, 0.0, 0.0) * view;
+        assert!(up.y > 0.0 && up.x.abs() < 1e-5);
+        assert_floats(view.determinant(), 1.0, 1e-5);
+    }
+
+    #[test]
+    fn test_look_at_inverse() {
+        let eye = Point3::new(3.0, 4.0, -2.0);
+        let target = Point3::new(-1.0, 1.0, 5.0);
+        let view = Transform3D::look_at(eye, target, UVec3::up());
+        let inverse = Transform3D::look_at_inverse(eye, target, UVec3::up());
+
+        let identity = view.multiply(inverse);
+        for (got, expected) in identity.m.iter().zip(Transform3D::identity().m) {
+            assert_floats(*got, expected, 1e-5);
+        }
+    }
+
+    fn assert_identity(got: Transform3D, tolerance: f32) {
+        for (i, (got, expected)) in got.m.iter().zip(Transform3D::identity().m).enumerate() {
+            assert!(
+                (got - expected).abs() <= tolerance,
+                "элемент {} произведения матрицы на обратную равен {}, а не {}",
+                i,
+                got,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_inverse_perspective() {
+        let projection =
+            Transform3D::perspective(std::f32::consts::FRAC_PI_3, 16.0 / 9.0, 0.1, 100.0);
+        assert!(!projection.is_affine());
+
+        let inverse = projection
+            .inverse()
+            .expect("перспектива должна быть обратима");
+        assert_identity(projection.multiply(inverse), 1e-4);
+        assert_identity(inverse.multiply(projection), 1e-4);
+    }
+
+    #[test]
+    fn test_inverse_general_matches_affine() {
+        let transform = Transform3D::scale(2.0, 3.0, 0.5)
+            .multiply(Transform3D::rotation_around_axis(
+                Vec3::new(1.0, 1.0, 0.0).normalize().unwrap(),
+                0.7,
+            ))
+            .multiply(Transform3D::translation(1.0, -2.0, 3.0));
+        assert!(transform.is_affine());
+
+        let affine = transform.inverse_affine().unwrap();
+        let general = transform.inverse_general().unwrap();
+        for (a, g) in affine.m.iter().zip(general.m) {
+            assert_floats(*a, g, 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_inverse_singular() {
+        assert!(Transform3D::scale(1.0, 1.0, 0.0).inverse().is_none());
+
+        // необратимая неаффинная матрица: две одинаковые строки
+        let mut m = Transform3D::perspective(1.0, 1.0, 0.1, 10.0).m;
+        m.copy_within(0..4, 4);
+        assert!(Transform3D::new(m).inverse().is_none());
+    }
+
+    #[test]
+    fn test_apply_to_slice_matches_apply_to_hvec() {
+        let transform = Transform3D::perspective(1.0, 1.5, 0.1, 100.0)
+            .multiply(Transform3D::rotation_x_rad(0.3))
+            .multiply(Transform3D::translation(1.0, -2.0, 3.0));
+        // 7 векторов: одна полная пачка и остаток
+        let hvecs: Vec<HVec3> = (0..7)
+            .map(|i| HVec3::new(i as f32, -(i as f32) * 0.5, 2.0 + i as f32, 1.0))
+            .collect();
+
+        let batched = transform.apply_to_slice(&hvecs);
+        assert_eq!(batched.len(), hvecs.len());
+        for (got, hvec) in batched.into_iter().zip(&hvecs) {
+            assert_hvecs(got, transform.apply_to_hvec(*hvec), TOLERANCE);
+        }
+
+        let mut in_place = hvecs.clone();
+        transform.apply_to_slice_in_place(&mut in_place);
+        for (got, hvec) in in_place.into_iter().zip(&hvecs) {
+            assert_hvecs(got, transform.apply_to_hvec(*hvec), TOLERANCE);
+        }
+    }
 }
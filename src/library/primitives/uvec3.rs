@@ -2,6 +2,7 @@
 
 use super::{HVec3, Point3, Transform3D, Vec3};
 use std::{
+    f32::consts::{FRAC_PI_2, PI},
     fmt::Display,
     ops::{Add, Div, Mul, Neg, Sub},
 };
@@ -12,6 +13,7 @@ use std::{
 /// 3D пространстве, при этом строго **единичной длины**. Для произвольного направления лучше
 /// подойдёт `Vec3`, для положения `Point3`. О координатной системе подробнее можно узнать в `CoordFrame`.
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UVec3 {
     pub x: f32,
     pub y: f32,
@@ -144,6 +146,76 @@ impl UVec3 {
     pub fn backward() -> Self {
         Self::minus_z()
     }
+
+    /// Создать unit-вектор по сферическим координатам.
+    ///
+    /// `theta` - полярный угол от направления вверх `+y` в радианах из [0, π],
+    /// `phi` - азимут вокруг `+y` от `+x` к `+z` в радианах.
+    /// Обратное преобразование - [`Vec3::to_spherical`].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::f32::consts::FRAC_PI_2;
+    /// use g3d::UVec3;
+    ///
+    /// assert!(UVec3::from_spherical(0.0, 1.0).approx_equal(UVec3::up(), 1.0e-6));
+    /// assert!(UVec3::from_spherical(FRAC_PI_2, 0.0).approx_equal(UVec3::plus_x(), 1.0e-6));
+    /// assert!(UVec3::from_spherical(FRAC_PI_2, FRAC_PI_2).approx_equal(UVec3::plus_z(), 1.0e-6));
+    /// ```
+    pub fn from_spherical(theta: f32, phi: f32) -> Self {
+        let (sin_theta, cos_theta) = theta.sin_cos();
+        let (sin_phi, cos_phi) = phi.sin_cos();
+        Self::new(sin_theta * cos_phi, cos_theta, sin_theta * sin_phi)
+    }
+
+    /// Создать unit-вектор по широте и долготе в радианах.
+    ///
+    /// Широта `latitude` отсчитывается от горизонта (`π/2` - вверх, `-π/2` - вниз),
+    /// долгота `longitude` совпадает с азимутом `phi` в [`UVec3::from_spherical`].
+    pub fn from_lat_lon(latitude: f32, longitude: f32) -> Self {
+        Self::from_spherical(FRAC_PI_2 - latitude, longitude)
+    }
+
+    /// Направления в центры ячеек сетки широта-долгота из `latitude_steps` x `longitude_steps`
+    /// ячеек, по строкам от верхнего полюса к нижнему.
+    ///
+    /// Ячейки у полюсов меньше по площади, поэтому при усреднении по сфере направления
+    /// нужно взвешивать синусом полярного угла.
+    pub fn lat_lon_grid(latitude_steps: usize, longitude_steps: usize) -> Vec<Self> {
+        let d_theta = PI / latitude_steps as f32;
+        let d_phi = 2.0 * PI / longitude_steps as f32;
+        (0..latitude_steps)
+            .flat_map(|i| {
+                let theta = (i as f32 + 0.5) * d_theta;
+                (0..longitude_steps)
+                    .map(move |j| Self::from_spherical(theta, (j as f32 + 0.5) * d_phi))
+            })
+            .collect()
+    }
+
+    /// `count` направлений, почти равномерно покрывающих сферу (спираль Фибоначчи).
+    ///
+    /// В отличие от [`UVec3::lat_lon_grid`], направления не сгущаются у полюсов,
+    /// поэтому подходят для выборки освещения и расстановки источников света без весов.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let directions = g3d::UVec3::fibonacci_sphere(100);
+    /// assert_eq!(directions.len(), 100);
+    /// // в верхней полусфере примерно половина направлений
+    /// let upper = directions.iter().filter(|direction| direction.y > 0.0).count();
+    /// assert_eq!(upper, 50);
+    /// ```
+    pub fn fibonacci_sphere(count: usize) -> Vec<Self> {
+        // угол золотого сечения
+        let golden_angle = PI * (3.0 - 5_f32.sqrt());
+        (0..count)
+            .map(|i| {
+                let y = 1.0 - 2.0 * (i as f32 + 0.5) / count as f32;
+                Self::from_spherical(y.acos(), golden_angle * i as f32)
+            })
+            .collect()
+    }
 }
 
 // ========================================
@@ -279,6 +351,47 @@ impl UVec3 {
     pub fn apply_transform(self, transform: Transform3D) -> Result<Self, UVecError> {
         UVec3::try_from(HVec3::from(self) * transform)
     }
+
+    /// Сферическая линейная интерполяция от текущего вектора к `other`.
+    ///
+    /// Вектор поворачивается по наименьшей дуге с постоянной угловой скоростью:
+    /// при `t = 0` получается `self`, при `t = 1` - `other`. Для противоположных
+    /// векторов поворот производится вокруг произвольной перпендикулярной оси.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::UVec3;
+    ///
+    /// let half = UVec3::plus_x().slerp(UVec3::plus_y(), 0.5);
+    /// let expected = UVec3::new(1.0, 1.0, 0.0);
+    ///
+    /// assert!(half.approx_equal(expected, 1.0e-6));
+    /// ```
+    pub fn slerp(self, other: Self, t: f32) -> Self {
+        let cos = self.dot(other).clamp(-1.0, 1.0);
+        let angle = cos.acos();
+
+        // почти совпадающие векторы - достаточно линейной интерполяции
+        if angle < 1.0e-4 {
+            return (self * (1.0 - t) + other * t).normalize().unwrap_or(self);
+        }
+
+        // почти противоположные векторы - дуга не определена, берём любую перпендикулярную ось
+        let other_dir = if std::f32::consts::PI - angle < 1.0e-4 {
+            let axis = if self.x.abs() < 0.9 {
+                UVec3::plus_x()
+            } else {
+                UVec3::plus_y()
+            };
+            self.cross(axis).normalize().unwrap()
+        } else {
+            other.rejection(self).normalize().unwrap()
+        };
+
+        (self * (angle * t).cos() + other_dir * (angle * t).sin())
+            .normalize()
+            .unwrap_or(self)
+    }
 }
 
 impl Neg for UVec3 {
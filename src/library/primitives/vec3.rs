@@ -13,6 +13,7 @@ use std::{
 /// 3D пространстве. Для положения надо использовать `Point3`. О координатной системе
 /// подробнее можно узнать в `CoordFrame`.
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vec3 {
     pub x: f32,
     pub y: f32,
@@ -57,6 +58,31 @@ impl Vec3 {
 // ========================================
 
 impl Vec3 {
+    /// Сферические координаты вектора `(r, theta, phi)`: длина, полярный угол от `+y`
+    /// из [0, π] и азимут от `+x` к `+z` из [0, 2π), см. [`UVec3::from_spherical`].
+    ///
+    /// Для нулевого вектора все координаты нулевые.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::f32::consts::FRAC_PI_2;
+    ///
+    /// let (r, theta, phi) = g3d::Vec3::new(0.0, 0.0, 2.0).to_spherical();
+    ///
+    /// assert!((r - 2.0).abs() < 1.0e-6);
+    /// assert!((theta - FRAC_PI_2).abs() < 1.0e-6);
+    /// assert!((phi - FRAC_PI_2).abs() < 1.0e-6);
+    /// ```
+    pub fn to_spherical(self) -> (f32, f32, f32) {
+        let r = self.length();
+        if r == 0.0 {
+            return (0.0, 0.0, 0.0);
+        }
+        let theta = (self.y / r).clamp(-1.0, 1.0).acos();
+        let phi = self.z.atan2(self.x).rem_euclid(2.0 * std::f32::consts::PI);
+        (r, theta, phi)
+    }
+
     /// Получить проекцию вектора на плоскость XY в **глобальных** координатах.
     ///
     /// # Examples
@@ -868,6 +894,27 @@ mod tests {
         assert_vectors(result3, Vec3::zero(), 1e-6);
     }
 
+    #[test]
+    fn test_spherical_round_trip() {
+        for direction in UVec3::lat_lon_grid(6, 8)
+            .into_iter()
+            .chain(UVec3::fibonacci_sphere(20))
+        {
+            let (r, theta, phi) = Vec3::from(direction).to_spherical();
+            assert_floats(r, 1.0, 1e-5);
+            assert!((0.0..=std::f32::consts::PI).contains(&theta));
+            assert!((0.0..2.0 * std::f32::consts::PI).contains(&phi));
+            assert_uvectors(UVec3::from_spherical(theta, phi), direction, 1e-5);
+        }
+
+        assert_uvectors(
+            UVec3::from_lat_lon(-std::f32::consts::FRAC_PI_2, 0.3),
+            UVec3::down(),
+            1e-6,
+        );
+        assert_eq!(Vec3::zero().to_spherical(), (0.0, 0.0, 0.0));
+    }
+
     #[test]
     fn test_edge_cases() {
         // Очень маленькие векторы
@@ -0,0 +1,499 @@
+//! Альтернативный рендер сцены трассировкой лучей.
+//!
+//! Медленнее растеризации, но честно считает тени и отражения, поэтому подходит как эталон
+//! для сравнения с [`crate::SceneRenderer`].
+
+use std::{fmt::Display, time::Instant};
+
+use crate::{
+    Canvas, Line3, Model, Point3, ProjectionType, RenderStats, Scene, UVec3, Vec3,
+    library::{
+        bvh::{Bvh, TriangleHit},
+        utils,
+    },
+};
+use egui::Color32;
+
+/// Смещение начала вторичных лучей от поверхности, чтобы они не пересекали её же.
+const SURFACE_OFFSET: f32 = 1e-3;
+
+/// Модель освещения при трассировке лучей.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RaytraceShading {
+    /// Только рассеянный свет по Ламберту.
+    Lambert,
+    /// Рассеянный свет и блики по Фонгу с показателем блеска `shininess`.
+    Phong { shininess: f32 },
+}
+
+impl Default for RaytraceShading {
+    fn default() -> Self {
+        Self::Phong { shininess: 32.0 }
+    }
+}
+
+impl Display for RaytraceShading {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Lambert => f.write_str("Ламберт"),
+            Self::Phong { .. } => f.write_str("Фонг"),
+        }
+    }
+}
+
+/// Рендер сцены трассировкой лучей из камеры через каждый пиксель холста.
+///
+/// Использует те же [`Scene`] и [`Canvas`], что и [`crate::SceneRenderer`], но рисует только
+/// грани видимых моделей: без каркаса, сетки и прочих вспомогательных элементов. Модели
+/// трассируются в своём основном уровне детализации, без billboard'ов и шейдеров вершин,
+/// плоскости отсечения камеры не учитываются.
+///
+/// # Examples
+/// ```rust
+/// use egui::Color32;
+/// use g3d::{Background, Canvas, LightSource, Mesh, Model, Point3, RaytraceRenderer, Scene};
+///
+/// let scene = Scene {
+///     models: vec![Model::from_mesh(Mesh::hexahedron())],
+///     lights: vec![LightSource::new(Point3::new(0.0, 0.0, -10.0), Color32::WHITE, 1.0)],
+///     background: Background::Solid(Color32::BLACK),
+///     ..Default::default()
+/// };
+/// let mut canvas = Canvas::new(40, 30);
+/// let stats = RaytraceRenderer::default().render(&scene, &mut canvas);
+///
+/// assert!(stats.pixels_shaded > 0);
+/// assert_ne!(canvas[(20, 15)], Color32::BLACK, "куб в центре кадра");
+/// assert_eq!(canvas[(0, 0)], Color32::BLACK, "фон по краям");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct RaytraceRenderer {
+    /// Тип проекции на камеру.
+    pub projection_type: ProjectionType,
+    /// Модель освещения.
+    pub shading: RaytraceShading,
+    /// Отбрасывают ли модели жёсткие тени (см. [`crate::ModelFlags::casts_shadows`]).
+    pub shadows: bool,
+    /// Максимальное количество отражений луча, 0 - без отражений.
+    ///
+    /// Отражают только материалы с ненулевой [`crate::Material::reflectivity`].
+    pub max_bounces: usize,
+    /// Доля фонового освещения в диапазоне [0, 1], которое не зависит от источников света.
+    pub ambient: f32,
+}
+
+impl Default for RaytraceRenderer {
+    fn default() -> Self {
+        Self {
+            projection_type: ProjectionType::default(),
+            shading: RaytraceShading::default(),
+            shadows: true,
+            max_bounces: 2,
+            ambient: 0.1,
+        }
+    }
+}
+
+/// Данные сцены, подготовленные для трассировки одного кадра.
+struct TraceContext<'a> {
+    scene: &'a Scene,
+    bvh: Bvh,
+    /// Нормали вершин моделей в **глобальных** координатах, если они есть.
+    normals: Vec<Option<Vec<UVec3>>>,
+}
+
+impl RaytraceRenderer {
+    /// Нарисовать сцену на холст.
+    ///
+    /// Возвращает статистику кадра: `polygons_rendered` - количество треугольников в дереве,
+    /// `pixels_shaded` - количество пикселей, в которых луч попал в модель.
+    pub fn render(&self, scene: &Scene, canvas: &mut Canvas) -> RenderStats {
+        let frame_start = Instant::now();
+        let mut stats = RenderStats::default();
+
+        if canvas.width() == 0 || canvas.height() == 0 {
+            #[cfg(debug_assertions)]
+            eprintln!("Warning: холст нулевого размера, рендер пропущен");
+            return stats;
+        }
+        let scissor = canvas.begin_flipped_frame();
+        canvas.clear(Color32::GRAY);
+
+        let camera = &scene.camera;
+        scene.background.paint(canvas, camera);
+
+        // дерево треугольников всех видимых моделей
+        let stage_start = Instant::now();
+        let context = Self::prepare(scene, &mut stats);
+        stats.geometry_time = stage_start.elapsed();
+
+        let Some(screen_to_global) = camera
+            .global_to_screen_transform(self.projection_type, canvas)
+            .inverse()
+        else {
+            #[cfg(debug_assertions)]
+            eprintln!("Warning: матрица проекции камеры вырождена, рендер пропущен");
+            canvas.end_flipped_frame(scissor);
+            return stats;
+        };
+        let camera_position = camera.get_position();
+        let camera_direction = Vec3::from(camera.get_direction());
+
+        let stage_start = Instant::now();
+        let (xs, ys) = canvas.scissor_bounds();
+        for y in ys {
+            for x in xs.clone() {
+                // луч через центр пикселя по двум точкам на разной глубине
+                let unproject = |z: f32| {
+                    Point3::new(x as f32 + 0.5, y as f32 + 0.5, z)
+                        .apply_transform(screen_to_global)
+                        .ok()
+                };
+                let (Some(near), Some(far)) = (unproject(0.0), unproject(0.5)) else {
+                    continue;
+                };
+                let Ok(mut direction) = (far - near).normalize() else {
+                    continue;
+                };
+                if Vec3::from(direction).dot(camera_direction) < 0.0 {
+                    direction = -direction;
+                }
+                let origin = match self.projection_type {
+                    ProjectionType::Perspective => camera_position,
+                    // при параллельной проекции лучи начинаются в плоскости камеры
+                    _ => {
+                        let depth = (near - camera_position).dot(camera_direction)
+                            / Vec3::from(direction).dot(camera_direction);
+                        near + direction * -depth
+                    }
+                };
+
+                let ray = Line3::new(origin, direction);
+                if let Some(color) = self.trace(&context, &ray, 0) {
+                    canvas[(x, y)] = color;
+                    stats.pixels_shaded += 1;
+                }
+            }
+        }
+        stats.shading_time = stage_start.elapsed();
+
+        canvas.end_flipped_frame(scissor);
+        stats.total_time = frame_start.elapsed();
+        stats
+    }
+
+    /// Собрать треугольники и нормали видимых моделей сцены.
+    fn prepare<'a>(scene: &'a Scene, stats: &mut RenderStats) -> TraceContext<'a> {
+        let is_traced = |model: &Model| model.flags.visible && !model.flags.wireframe_only;
+        let bvh = Bvh::from_models(&scene.models, is_traced);
+        let normals = scene
+            .models
+            .iter()
+            .map(|model| {
+                if !is_traced(model) {
+                    return None;
+                }
+                stats.models_rendered += 1;
+                model
+                    .mesh
+                    .get_global_normals_iter()
+                    .map(|normals| normals.collect())
+            })
+            .collect();
+        stats.polygons_submitted = bvh.triangles.len();
+        stats.polygons_rendered = bvh.triangles.len();
+
+        TraceContext {
+            scene,
+            bvh,
+            normals,
+        }
+    }
+
+    /// Цвет луча `ray`, отражённого `bounce` раз, или `None`, если он ни во что не попал.
+    fn trace(&self, context: &TraceContext, ray: &Line3, bounce: usize) -> Option<Color32> {
+        let hit = context.bvh.intersect(ray, f32::INFINITY, |_| true)?;
+        Some(self.shade(context, ray, &hit, bounce))
+    }
+
+    /// Цвет поверхности в точке пересечения `hit` луча `ray`.
+    fn shade(
+        &self,
+        context: &TraceContext,
+        ray: &Line3,
+        hit: &TriangleHit,
+        bounce: usize,
+    ) -> Color32 {
+        let triangle = &context.bvh.triangles[hit.triangle];
+        let model = &context.scene.models[triangle.model_index];
+        let [a, b, c] = triangle.indexes;
+        let bary = Point3::new(1.0 - hit.u - hit.v, hit.u, hit.v);
+
+        let position = ray.origin + ray.direction * hit.distance;
+        let normal = match &context.normals[triangle.model_index] {
+            Some(normals) => utils::interpolate_vec(
+                bary,
+                normals[a].into(),
+                normals[b].into(),
+                normals[c].into(),
+            )
+            .normalize()
+            .ok(),
+            None => None,
+        }
+        .or_else(|| {
+            let [va, vb, vc] = triangle.vertexes;
+            (vb - va).cross(vc - va).normalize().ok()
+        });
+        let Some(mut normal) = normal else {
+            return model.material.color;
+        };
+        // грани видны с обеих сторон
+        if normal.dot(ray.direction) > 0.0 {
+            normal = -normal;
+        }
+
+        let base_color = match triangle.texture_coords {
+            Some([ta, tb, tc]) => {
+                let u = utils::interpolate_float(bary, ta.0, tb.0, tc.0);
+                let v = utils::interpolate_float(bary, ta.1, tb.1, tc.1);
+                model.material.get_uv_color(u, v)
+            }
+            _ => model.material.color,
+        };
+
+        let mut color = self.lighting(context, position, normal, ray.direction, base_color);
+
+        // отражение окружения
+        let reflectivity = model.material.reflectivity.clamp(0.0, 1.0);
+        if reflectivity > 0.0 && bounce < self.max_bounces {
+            let direction = Vec3::from(ray.direction);
+            let normal = Vec3::from(normal);
+            let reflected = direction - normal * (2.0 * direction.dot(normal));
+            if let Ok(reflected) = reflected.normalize() {
+                let reflected_ray = Line3::new(position + normal * SURFACE_OFFSET, reflected);
+                let reflected_color = self
+                    .trace(context, &reflected_ray, bounce + 1)
+                    .unwrap_or_else(|| context.scene.background.sample(reflected.into()));
+                color = utils::lerp_color(color, reflected_color, reflectivity);
+            }
+        }
+        color
+    }
+
+    /// Освещённость точки `position` с нормалью `normal` при взгляде вдоль `view`.
+    ///
+    /// К фоновому освещению добавляется свет окружения сцены, если оно задано. Если нет
+    /// ни источников света, ни окружения, то возвращает цвет поверхности без освещения.
+    fn lighting(
+        &self,
+        context: &TraceContext,
+        position: Point3,
+        normal: UVec3,
+        view: UVec3,
+        base_color: Color32,
+    ) -> Color32 {
+        let lights: Vec<_> = context.scene.enabled_lights().collect();
+        let environment = context.scene.environment.as_ref();
+        if lights.is_empty() && environment.is_none() {
+            return base_color;
+        }
+
+        let mut diffuse = [self.ambient; 3];
+        if let Some(environment) = environment {
+            let irradiance = environment.irradiance(normal).to_array();
+            for channel in 0..3 {
+                diffuse[channel] += irradiance[channel] as f32 / 255.0;
+            }
+        }
+        let mut specular = [0.0; 3];
+        let shadow_origin = position + normal * SURFACE_OFFSET;
+        for light in lights {
+            let to_light = light.position - position;
+            let distance = to_light.length();
+            let Ok(light_direction) = to_light.normalize() else {
+                continue;
+            };
+            let cos = normal.dot(light_direction);
+            if cos <= 0.0 {
+                continue;
+            }
+
+            // жёсткая тень: между точкой и источником есть другая поверхность
+            if self.shadows {
+                let shadow_ray = Line3::new(shadow_origin, light_direction);
+                let occluded = context
+                    .bvh
+                    .intersect(&shadow_ray, distance - SURFACE_OFFSET, |triangle| {
+                        context.scene.models[triangle.model_index]
+                            .flags
+                            .casts_shadows
+                    })
+                    .is_some();
+                if occluded {
+                    continue;
+                }
+            }
+
+            let light_color = light.color.to_array().map(|channel| channel as f32 / 255.0);
+            let diffuse_strength = light.intensity * cos;
+            let specular_strength = match self.shading {
+                RaytraceShading::Lambert => 0.0,
+                RaytraceShading::Phong { shininess } => {
+                    let reflected = Vec3::from(normal) * (2.0 * cos) - Vec3::from(light_direction);
+                    let cos = reflected.dot(-Vec3::from(view)).max(0.0);
+                    light.intensity * cos.powf(shininess)
+                }
+            };
+            for channel in 0..3 {
+                diffuse[channel] += light_color[channel] * diffuse_strength;
+                specular[channel] += light_color[channel] * specular_strength;
+            }
+        }
+
+        let base_color = base_color.to_array();
+        let channel = |i: usize| {
+            let value = base_color[i] as f32 * diffuse[i] + 255.0 * specular[i];
+            value.clamp(0.0, 255.0) as u8
+        };
+        Color32::from_rgba_unmultiplied(channel(0), channel(1), channel(2), base_color[3])
+    }
+}
+
+#[cfg(test)]
+mod raytrace_renderer_tests {
+    use super::*;
+    use crate::{Background, LightSource, Mesh, Model, Polygon, SceneRenderer};
+
+    /// Квадрат со стороной 20 в плоскости `z = 5`, перпендикулярный взгляду камеры по умолчанию.
+    fn wall() -> Model {
+        let vertexes = vec![
+            Point3::new(-10.0, -10.0, 5.0),
+            Point3::new(10.0, -10.0, 5.0),
+            Point3::new(10.0, 10.0, 5.0),
+            Point3::new(-10.0, 10.0, 5.0),
+        ];
+        Model::from_mesh(Mesh::from_polygons(
+            vertexes,
+            vec![Polygon::from_list(&[0, 1, 2, 3])],
+        ))
+    }
+
+    fn brightness(canvas: &Canvas) -> u64 {
+        canvas
+            .pixels()
+            .iter()
+            .map(|color| color.r() as u64 + color.g() as u64 + color.b() as u64)
+            .sum()
+    }
+
+    #[test]
+    fn test_shadows_darken_image() {
+        let mut cube = Model::from_mesh(Mesh::hexahedron());
+        cube.set_position(Point3::new(0.0, 0.0, 3.0));
+        let scene = Scene {
+            models: vec![wall(), cube],
+            lights: vec![LightSource::new(
+                Point3::new(6.0, 0.0, -4.0),
+                Color32::WHITE,
+                1.0,
+            )],
+            background: Background::Solid(Color32::BLACK),
+            ..Default::default()
+        };
+
+        let mut renderer = RaytraceRenderer {
+            shadows: false,
+            ..Default::default()
+        };
+        let mut lit = Canvas::new(60, 40);
+        renderer.render(&scene, &mut lit);
+
+        renderer.shadows = true;
+        let mut shadowed = Canvas::new(60, 40);
+        let stats = renderer.render(&scene, &mut shadowed);
+
+        assert_eq!(stats.models_rendered, 2);
+        assert!(
+            brightness(&shadowed) < brightness(&lit),
+            "тень куба на стене темнее освещённой стены"
+        );
+    }
+
+    #[test]
+    fn test_reflection_bounce_limit() {
+        let mut mirror = wall();
+        mirror.material.reflectivity = 1.0;
+        let scene = Scene {
+            models: vec![mirror],
+            background: Background::Solid(Color32::RED),
+            ..Default::default()
+        };
+        let mut canvas = Canvas::new(20, 20);
+
+        // без отражений стена рисуется своим цветом
+        let renderer = RaytraceRenderer {
+            max_bounces: 0,
+            ..Default::default()
+        };
+        renderer.render(&scene, &mut canvas);
+        assert_eq!(canvas[(10, 10)], Color32::WHITE);
+
+        // луч отражается обратно к камере и уходит в фон
+        let renderer = RaytraceRenderer {
+            max_bounces: 1,
+            ..Default::default()
+        };
+        renderer.render(&scene, &mut canvas);
+        assert_eq!(canvas[(10, 10)], Color32::RED, "зеркало отражает фон");
+    }
+
+    #[test]
+    fn test_silhouette_matches_rasterizer() {
+        let mut cube = Model::from_mesh(Mesh::hexahedron());
+        cube.set_position(Point3::new(1.5, 1.0, -4.0));
+        cube.material.color = Color32::RED;
+        let scene = Scene {
+            models: vec![cube],
+            background: Background::Solid(Color32::BLACK),
+            ..Default::default()
+        };
+
+        let mut traced = Canvas::new(80, 60);
+        let raytracer = RaytraceRenderer {
+            projection_type: ProjectionType::Perspective,
+            ..Default::default()
+        };
+        raytracer.render(&scene, &mut traced);
+        let mut rasterized = Canvas::new(80, 60);
+        let rasterizer = SceneRenderer {
+            projection_type: ProjectionType::Perspective,
+            render_wireframe: false,
+            render_solid: true,
+            ..Default::default()
+        };
+        rasterizer.render(
+            &scene,
+            &mut rasterized,
+            false,
+            Point3::zero(),
+            Point3::zero(),
+        );
+
+        let is_red = |color: &Color32| *color == Color32::RED;
+        let traced_count = traced.pixels().iter().filter(|c| is_red(c)).count();
+        let common = traced
+            .pixels()
+            .iter()
+            .zip(rasterized.pixels())
+            .filter(|(a, b)| is_red(a) && is_red(b))
+            .count();
+        assert!(traced_count > 0, "куб попал в кадр");
+        assert!(
+            common as f32 > traced_count as f32 * 0.9,
+            "силуэт совпадает с растеризацией: {} из {}",
+            common,
+            traced_count
+        );
+    }
+}
@@ -0,0 +1,215 @@
+//! Постепенный (прерываемый) рендер тяжёлых сцен по плиткам.
+
+use std::time::{Duration, Instant};
+
+use egui::{Pos2, Rect};
+
+use crate::{Canvas, Point3, RenderStats, Scene, SceneRenderer};
+
+/// Размер плитки постепенного рендера по умолчанию в пикселях.
+pub const DEFAULT_TILE_SIZE: usize = 64;
+
+/// Ход выполнения задачи постепенного рендера.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderProgress {
+    /// Количество уже отрисованных плиток.
+    pub completed_tiles: usize,
+    /// Общее количество плиток кадра.
+    pub total_tiles: usize,
+}
+
+impl RenderProgress {
+    /// Доля отрисованных плиток из [0, 1].
+    pub fn fraction(&self) -> f32 {
+        if self.total_tiles == 0 {
+            return 1.0;
+        }
+        self.completed_tiles as f32 / self.total_tiles as f32
+    }
+
+    /// Отрисован ли весь кадр.
+    pub fn is_done(&self) -> bool {
+        self.completed_tiles >= self.total_tiles
+    }
+}
+
+/// Задача постепенного рендера: кадр рисуется плитками по несколько за вызов
+/// [`RenderJob::step`], так что интерактивное приложение может показывать частичный результат
+/// и не подвисать на тяжёлых сценах.
+///
+/// Каждая плитка рисуется обычным [`SceneRenderer::render`] с областью отсечения холста,
+/// а эффекты, которым нужен весь кадр (например, свечение), накладываются после последней
+/// плитки через [`SceneRenderer::finish_tiled_frame`], поэтому готовый кадр совпадает
+/// с нарисованным за один раз. Задача хранит копии сцены
+/// и параметров рендера; если они изменились, то задачу нужно создать заново.
+///
+/// # Examples
+/// ```rust
+/// use std::time::Duration;
+/// use g3d::{RenderJob, Scene, SceneRenderer};
+///
+/// let mut job = RenderJob::new(Scene::default(), SceneRenderer::default(), 100, 80, 32);
+/// assert_eq!(job.progress().total_tiles, 4 * 3);
+///
+/// // за нулевой бюджет рисуется ровно одна плитка
+/// assert_eq!(job.step(Duration::ZERO).completed_tiles, 1);
+/// while !job.step(Duration::from_millis(10)).is_done() {}
+/// let canvas = job.into_canvas();
+/// assert_eq!(canvas.size(), [100, 80]);
+/// ```
+pub struct RenderJob {
+    /// Отрисовываемая сцена.
+    scene: Scene,
+    /// Параметры рендера.
+    renderer: SceneRenderer,
+    /// Холст с частично отрисованным кадром.
+    canvas: Canvas,
+    /// Плитки кадра по строкам сверху вниз.
+    tiles: Vec<Rect>,
+    /// Индекс следующей плитки.
+    next_tile: usize,
+    /// Статистика, накопленная по всем отрисованным плиткам.
+    stats: RenderStats,
+}
+
+impl RenderJob {
+    /// Создать задачу рендера кадра `width` x `height` плитками `tile_size` x `tile_size`
+    /// (плитки у правого и нижнего краёв могут быть меньше).
+    pub fn new(
+        scene: Scene,
+        renderer: SceneRenderer,
+        width: usize,
+        height: usize,
+        tile_size: usize,
+    ) -> Self {
+        debug_assert!(tile_size > 0, "размер плитки должен быть больше 0");
+        let tile_size = tile_size.max(1);
+
+        let mut tiles = Vec::new();
+        for y in (0..height).step_by(tile_size) {
+            for x in (0..width).step_by(tile_size) {
+                tiles.push(Rect::from_min_max(
+                    Pos2::new(x as f32, y as f32),
+                    Pos2::new(
+                        (x + tile_size).min(width) as f32,
+                        (y + tile_size).min(height) as f32,
+                    ),
+                ));
+            }
+        }
+
+        Self {
+            scene,
+            renderer,
+            canvas: Canvas::new(width, height),
+            tiles,
+            next_tile: 0,
+            stats: RenderStats::default(),
+        }
+    }
+
+    /// Нарисовать следующие плитки, пока не истечёт `budget`.
+    ///
+    /// За вызов рисуется хотя бы одна плитка (если кадр ещё не готов), поэтому
+    /// задача продвигается даже при нулевом бюджете.
+    pub fn step(&mut self, budget: Duration) -> RenderProgress {
+        let start = Instant::now();
+        let was_done = self.progress().is_done();
+        while self.next_tile < self.tiles.len() {
+            self.canvas.set_scissor(Some(self.tiles[self.next_tile]));
+            let stats = self.renderer.render(
+                &self.scene,
+                &mut self.canvas,
+                false,
+                Point3::zero(),
+                Point3::zero(),
+            );
+            self.stats += stats;
+            self.next_tile += 1;
+            if start.elapsed() >= budget {
+                break;
+            }
+        }
+        self.canvas.set_scissor(None);
+        if !was_done && self.progress().is_done() {
+            self.renderer
+                .finish_tiled_frame(&self.scene, &mut self.canvas);
+        }
+        self.progress()
+    }
+
+    /// Текущий ход выполнения.
+    pub fn progress(&self) -> RenderProgress {
+        RenderProgress {
+            completed_tiles: self.next_tile,
+            total_tiles: self.tiles.len(),
+        }
+    }
+
+    /// Холст с уже отрисованными плитками. Неотрисованные плитки пустые.
+    pub fn canvas(&self) -> &Canvas {
+        &self.canvas
+    }
+
+    /// Забрать холст с кадром.
+    pub fn into_canvas(self) -> Canvas {
+        self.canvas
+    }
+
+    /// Статистика, накопленная по всем отрисованным плиткам.
+    ///
+    /// Модели, полигоны и фрагменты учитываются в каждой плитке заново, поэтому их счётчики
+    /// больше, чем у кадра, нарисованного за один раз.
+    pub fn stats(&self) -> RenderStats {
+        self.stats
+    }
+}
+
+#[cfg(test)]
+mod render_job_tests {
+    use super::*;
+    use crate::{DitherMode, IndexedPalette, Mesh, Model, PaletteOutput, ProjectionType};
+
+    #[test]
+    fn test_progressive_render_matches_full_render() {
+        let mut cube = Model::from_mesh(Mesh::hexahedron());
+        cube.uniform_scale(3.0);
+        cube.rotate_local_y(30_f32.to_radians());
+        // свечение размывает кадр через границы плиток
+        cube.material.emission = egui::Color32::from_rgb(255, 128, 0);
+        let scene = Scene {
+            models: vec![cube],
+            ..Default::default()
+        };
+        let mut renderer = SceneRenderer {
+            render_solid: true,
+            projection_type: ProjectionType::Perspective,
+            ..Default::default()
+        };
+        renderer.bloom.enabled = true;
+        // диффузия ошибки переносит ошибку через границы плиток
+        renderer.palette_output = Some(PaletteOutput::new(
+            IndexedPalette::uniform_rgb(3),
+            DitherMode::FloydSteinberg,
+        ));
+        let mut full = Canvas::new(150, 100);
+        renderer.render(&scene, &mut full, false, Point3::zero(), Point3::zero());
+
+        let mut job = RenderJob::new(scene, renderer, 150, 100, 40);
+        let mut steps = 0;
+        loop {
+            let progress = job.step(Duration::ZERO);
+            steps += 1;
+            assert_eq!(progress.completed_tiles, steps);
+            if progress.is_done() {
+                break;
+            }
+        }
+        assert_eq!(steps, 4 * 3);
+        assert_eq!(job.progress().fraction(), 1.0);
+        // готовая задача больше ничего не рисует
+        assert_eq!(job.step(Duration::ZERO).completed_tiles, steps);
+        assert!(job.canvas().pixels() == full.pixels());
+        assert!(job.stats().models_rendered >= steps);
+    }
+}
@@ -0,0 +1,333 @@
+//! Цель рендера: холст вместе с дополнительными именованными буферами.
+
+use std::collections::HashMap;
+
+use crate::{Canvas, Vec3};
+use egui::Color32;
+
+/// Содержимое дополнительного буфера цели рендера.
+///
+/// Все буферы хранят значения построчно, по одному значению на пиксель.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RenderBuffer {
+    /// Цвета пикселей.
+    Color(Vec<Color32>),
+    /// Скалярные значения (глубина, затенение и т.п.).
+    Float(Vec<f32>),
+    /// Целочисленные идентификаторы.
+    Id(Vec<u32>),
+    /// Векторы (например, нормали).
+    Vector(Vec<Vec3>),
+}
+
+impl RenderBuffer {
+    /// Количество значений в буфере.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Color(values) => values.len(),
+            Self::Float(values) => values.len(),
+            Self::Id(values) => values.len(),
+            Self::Vector(values) => values.len(),
+        }
+    }
+
+    /// Пуст ли буфер.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Получить буфер только для чтения.
+    pub fn view(&self) -> BufferView<'_> {
+        match self {
+            Self::Color(values) => BufferView::Color(values),
+            Self::Float(values) => BufferView::Float(values),
+            Self::Id(values) => BufferView::Id(values),
+            Self::Vector(values) => BufferView::Vector(values),
+        }
+    }
+}
+
+/// Буфер цели рендера, доступный только для чтения.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BufferView<'a> {
+    Color(&'a [Color32]),
+    Float(&'a [f32]),
+    Id(&'a [u32]),
+    Vector(&'a [Vec3]),
+}
+
+/// Цель рендера.
+///
+/// Объединяет холст (цвет и глубина) с буфером идентификаторов моделей, буфером нормалей
+/// и произвольными именованными буферами. Последующие проходы (SSAO, обводка, постобработка)
+/// могут читать ранее записанные буферы по имени.
+pub struct RenderTarget {
+    /// Холст с цветом и z-буфером.
+    canvas: Canvas,
+    /// Дополнительные буферы по именам.
+    buffers: HashMap<String, RenderBuffer>,
+}
+
+impl RenderTarget {
+    /// Имя буфера цвета (пиксели холста).
+    pub const COLOR_BUFFER: &'static str = "color";
+    /// Имя буфера глубины (z-буфер холста).
+    pub const DEPTH_BUFFER: &'static str = "depth";
+    /// Имя буфера идентификаторов: индекс модели в сцене или [`RenderTarget::NO_ID`].
+    pub const ID_BUFFER: &'static str = "id";
+    /// Имя буфера нормалей в **глобальных** координатах.
+    pub const NORMAL_BUFFER: &'static str = "normal";
+    /// Идентификатор пикселя, в котором нет ни одной модели.
+    pub const NO_ID: u32 = u32::MAX;
+
+    /// Создать цель рендера указанного размера с пустыми буферами идентификаторов и нормалей.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self::from_canvas(Canvas::new(width, height))
+    }
+
+    /// Создать цель рендера на основе существующего холста.
+    pub fn from_canvas(canvas: Canvas) -> Self {
+        let len = canvas.width() * canvas.height();
+        let mut buffers = HashMap::new();
+        buffers.insert(
+            Self::ID_BUFFER.to_string(),
+            RenderBuffer::Id(vec![Self::NO_ID; len]),
+        );
+        buffers.insert(
+            Self::NORMAL_BUFFER.to_string(),
+            RenderBuffer::Vector(vec![Vec3::zero(); len]),
+        );
+        Self { canvas, buffers }
+    }
+
+    /// Размеры цели рендера вида [ширина, высота].
+    pub fn size(&self) -> [usize; 2] {
+        self.canvas.size()
+    }
+
+    /// Ширина цели рендера.
+    pub fn width(&self) -> usize {
+        self.canvas.width()
+    }
+
+    /// Высота цели рендера.
+    pub fn height(&self) -> usize {
+        self.canvas.height()
+    }
+
+    /// Холст с цветом и глубиной.
+    pub fn canvas(&self) -> &Canvas {
+        &self.canvas
+    }
+
+    /// Холст с цветом и глубиной.
+    pub fn canvas_mut(&mut self) -> &mut Canvas {
+        &mut self.canvas
+    }
+
+    /// Забрать холст, отбросив остальные буферы.
+    pub fn into_canvas(self) -> Canvas {
+        self.canvas
+    }
+
+    // --------------------------------------------------
+    // Именованные буферы
+    // --------------------------------------------------
+
+    /// Получить буфер по имени.
+    ///
+    /// Помимо дополнительных буферов, доступны [`RenderTarget::COLOR_BUFFER`]
+    /// и [`RenderTarget::DEPTH_BUFFER`] холста.
+    pub fn buffer(&self, name: &str) -> Option<BufferView<'_>> {
+        match name {
+            Self::COLOR_BUFFER => Some(BufferView::Color(self.canvas.pixels())),
+            Self::DEPTH_BUFFER => Some(BufferView::Float(self.canvas.depth_buffer())),
+            _ => self.buffers.get(name).map(RenderBuffer::view),
+        }
+    }
+
+    /// Получить дополнительный буфер по имени для изменения.
+    ///
+    /// Буферы холста изменяются через [`RenderTarget::canvas_mut`].
+    pub fn buffer_mut(&mut self, name: &str) -> Option<&mut RenderBuffer> {
+        self.buffers.get_mut(name)
+    }
+
+    /// Добавить или заменить дополнительный буфер, возвращает прошлый буфер с таким именем.
+    ///
+    /// Размер буфера должен совпадать с количеством пикселей, а имя не должно совпадать
+    /// с именами буферов холста.
+    pub fn insert_buffer(&mut self, name: &str, buffer: RenderBuffer) -> Option<RenderBuffer> {
+        debug_assert_eq!(
+            buffer.len(),
+            self.width() * self.height(),
+            "размер буфера {} не совпадает с размером цели рендера",
+            name
+        );
+        debug_assert!(
+            name != Self::COLOR_BUFFER && name != Self::DEPTH_BUFFER,
+            "имя {} зарезервировано за буфером холста",
+            name
+        );
+
+        self.buffers.insert(name.to_string(), buffer)
+    }
+
+    /// Удалить дополнительный буфер.
+    pub fn remove_buffer(&mut self, name: &str) -> Option<RenderBuffer> {
+        self.buffers.remove(name)
+    }
+
+    /// Имена всех доступных буферов, включая буферы холста.
+    pub fn buffer_names(&self) -> Vec<&str> {
+        let mut names = vec![Self::COLOR_BUFFER, Self::DEPTH_BUFFER];
+        let mut extra: Vec<&str> = self.buffers.keys().map(String::as_str).collect();
+        extra.sort();
+        names.extend(extra);
+        names
+    }
+
+    // --------------------------------------------------
+    // Буферы идентификаторов и нормалей
+    // --------------------------------------------------
+
+    /// Идентификатор модели в пикселе `(x, y)`, если он там есть.
+    pub fn id_at(&self, x: usize, y: usize) -> Option<usize> {
+        match self.buffer(Self::ID_BUFFER)? {
+            BufferView::Id(ids) => {
+                let id = *ids.get(y * self.width() + x)?;
+                (id != Self::NO_ID).then_some(id as usize)
+            }
+            _ => None,
+        }
+    }
+
+    /// Нормаль поверхности в пикселе `(x, y)`, если в нём есть модель.
+    pub fn normal_at(&self, x: usize, y: usize) -> Option<Vec3> {
+        self.id_at(x, y)?;
+        match self.buffer(Self::NORMAL_BUFFER)? {
+            BufferView::Vector(normals) => normals.get(y * self.width() + x).copied(),
+            _ => None,
+        }
+    }
+
+    /// Очистить холст, буфер идентификаторов и буфер нормалей.
+    ///
+    /// Остальные дополнительные буферы перезаписываются своими проходами и не очищаются.
+    pub fn clear(&mut self, color: Color32) {
+        self.canvas.clear(color);
+        self.clear_geometry_buffers();
+    }
+
+    /// Очистить только буферы идентификаторов и нормалей.
+    pub(crate) fn clear_geometry_buffers(&mut self) {
+        let len = self.width() * self.height();
+        self.buffers.insert(
+            Self::ID_BUFFER.to_string(),
+            RenderBuffer::Id(vec![Self::NO_ID; len]),
+        );
+        self.buffers.insert(
+            Self::NORMAL_BUFFER.to_string(),
+            RenderBuffer::Vector(vec![Vec3::zero(); len]),
+        );
+    }
+
+    /// Записать идентификатор и нормаль в пиксель `(x, y)`.
+    pub(crate) fn set_geometry(&mut self, x: usize, y: usize, id: u32, normal: Vec3) {
+        let index = y * self.width() + x;
+        if let Some(RenderBuffer::Id(ids)) = self.buffers.get_mut(Self::ID_BUFFER) {
+            ids[index] = id;
+        }
+        if let Some(RenderBuffer::Vector(normals)) = self.buffers.get_mut(Self::NORMAL_BUFFER) {
+            normals[index] = normal;
+        }
+    }
+
+    /// Инвертировать по оси Y буферы идентификаторов и нормалей.
+    pub(crate) fn invert_geometry_y(&mut self) {
+        let (width, height) = (self.width(), self.height());
+        for name in [Self::ID_BUFFER, Self::NORMAL_BUFFER] {
+            let Some(buffer) = self.buffers.get_mut(name) else {
+                continue;
+            };
+            for y in 0..(height / 2) {
+                let op_y = height - y - 1;
+                for x in 0..width {
+                    let (index, op_index) = (y * width + x, op_y * width + x);
+                    match buffer {
+                        RenderBuffer::Color(values) => values.swap(index, op_index),
+                        RenderBuffer::Float(values) => values.swap(index, op_index),
+                        RenderBuffer::Id(values) => values.swap(index, op_index),
+                        RenderBuffer::Vector(values) => values.swap(index, op_index),
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Default for RenderTarget {
+    fn default() -> Self {
+        Self::from_canvas(Canvas::default())
+    }
+}
+
+#[cfg(test)]
+mod render_target_tests {
+    use super::*;
+
+    #[test]
+    fn test_standard_buffers() {
+        let target = RenderTarget::new(4, 3);
+        assert_eq!(
+            target.buffer_names(),
+            vec!["color", "depth", "id", "normal"],
+            "по умолчанию должны быть буферы холста, идентификаторов и нормалей"
+        );
+        assert!(matches!(
+            target.buffer(RenderTarget::COLOR_BUFFER),
+            Some(BufferView::Color(pixels)) if pixels.len() == 12
+        ));
+        assert!(matches!(
+            target.buffer(RenderTarget::DEPTH_BUFFER),
+            Some(BufferView::Float(depth)) if depth.len() == 12
+        ));
+        assert_eq!(target.id_at(1, 1), None, "изначально моделей нет");
+        assert_eq!(target.normal_at(1, 1), None);
+        assert!(target.buffer("ssao").is_none());
+    }
+
+    #[test]
+    fn test_custom_buffer() {
+        let mut target = RenderTarget::new(2, 2);
+        assert!(
+            target
+                .insert_buffer("ssao", RenderBuffer::Float(vec![0.5; 4]))
+                .is_none()
+        );
+
+        if let Some(RenderBuffer::Float(values)) = target.buffer_mut("ssao") {
+            values[3] = 1.0;
+        }
+        assert_eq!(
+            target.buffer("ssao"),
+            Some(BufferView::Float(&[0.5, 0.5, 0.5, 1.0]))
+        );
+
+        // очистка не трогает пользовательские буферы
+        target.clear(Color32::BLACK);
+        assert!(target.buffer("ssao").is_some());
+        assert!(target.remove_buffer("ssao").is_some());
+        assert!(target.buffer("ssao").is_none());
+    }
+
+    #[test]
+    fn test_geometry_invert_y() {
+        let mut target = RenderTarget::new(2, 3);
+        target.set_geometry(1, 0, 7, Vec3::new(0.0, 0.0, 1.0));
+        target.invert_geometry_y();
+        assert_eq!(target.id_at(1, 0), None);
+        assert_eq!(target.id_at(1, 2), Some(7));
+        assert_eq!(target.normal_at(1, 2), Some(Vec3::new(0.0, 0.0, 1.0)));
+    }
+}
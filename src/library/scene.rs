@@ -1,4 +1,9 @@
-use crate::{Camera, LightSource, Model};
+use crate::library::{bvh::Bvh, state_hash::StateHash};
+use crate::{
+    Background, Camera, CommandStack, EnvironmentLight, LightSource, Line3, Mesh, Model, ModelId,
+    Point3, Polyline3, RayHit, TextureProjector, Transform3D, UVec3, Vec3,
+};
+use std::hash::{DefaultHasher, Hasher};
 
 /// Сцена в 3-х мерном пространстве с 3-х мерными объектами (моделями).
 #[derive(Debug, Clone)]
@@ -9,6 +14,20 @@ pub struct Scene {
     pub camera: Camera,
     /// Источики света.
     pub lights: Vec<LightSource>,
+    /// Рассеянное освещение картой окружения, добавляется к свету источников.
+    pub environment: Option<EnvironmentLight>,
+    /// Фон, на котором рисуются модели.
+    pub background: Background,
+    /// Карта, отражающаяся в материалах с ненулевой [`crate::Material::reflectivity`],
+    /// например проба отражений (см. [`crate::SceneRenderer::capture_reflection_probe`]).
+    /// Если не задана, то отражается карта окружения [`Scene::environment`].
+    pub reflection_map: Option<Background>,
+    /// Ломаные линии, рисуемые поверх фона с проверкой глубины.
+    pub polylines: Vec<Polyline3>,
+    /// Проекторы текстур, накладывающие картинки на поверхности моделей.
+    pub projectors: Vec<TextureProjector>,
+    /// История изменений сцены для отмены и повтора, см. [`Scene::execute`].
+    pub history: CommandStack,
 }
 
 impl Default for Scene {
@@ -17,6 +36,728 @@ impl Default for Scene {
             models: Vec::new(),
             camera: Default::default(),
             lights: Vec::new(),
+            environment: None,
+            background: Background::default(),
+            reflection_map: None,
+            polylines: Vec::new(),
+            projectors: Vec::new(),
+            history: CommandStack::default(),
         }
     }
 }
+
+/// Способ определения момента касания при опускании модели.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub enum DropMode {
+    /// По ограничивающим параллелепипедам моделей: быстро, но грубо.
+    BoundingBox,
+    /// По самой геометрии моделей: модель опускается до касания поверхности.
+    #[default]
+    Mesh,
+}
+
+// --------------------------------------------------
+// Добавление и поиск моделей
+// --------------------------------------------------
+
+impl Scene {
+    /// Добавить модель на сцену и выдать ей новый идентификатор.
+    ///
+    /// Идентификатор не меняется при удалении других моделей, в отличие от индекса.
+    /// Копия уже добавленной модели получает свой идентификатор.
+    pub fn add_model(&mut self, mut model: Model) -> ModelId {
+        let id = model.assign_id();
+        self.models.push(model);
+        id
+    }
+
+    /// Добавить модель с именем `name` на сцену, см. [`Scene::add_model`].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use g3d::{Mesh, Model, Scene};
+    ///
+    /// let mut scene = Scene::default();
+    /// let floor = scene.add_model_named(Model::from_mesh(Mesh::hexahedron()), "floor");
+    /// let cube = scene.add_model(Model::from_mesh(Mesh::hexahedron()));
+    ///
+    /// scene.remove_by_id(floor);
+    /// assert_eq!(scene.model_index(cube), Some(0));
+    /// assert!(scene.model_by_name("floor").is_none());
+    /// ```
+    pub fn add_model_named(&mut self, mut model: Model, name: impl Into<String>) -> ModelId {
+        model.name = Some(name.into());
+        self.add_model(model)
+    }
+
+    /// Текущий индекс модели с идентификатором `id` в `scene.models`.
+    pub fn model_index(&self, id: ModelId) -> Option<usize> {
+        self.models.iter().position(|model| model.id() == Some(id))
+    }
+
+    /// Идентификатор модели с индексом `index` в `scene.models`.
+    ///
+    /// Буферы идентификаторов холста и цели рендера хранят индексы моделей в сцене
+    /// на момент отрисовки (см. [`crate::Canvas::id`]), а индексы меняются при удалении
+    /// моделей, поэтому выбранную в них модель следует запоминать по этому идентификатору.
+    /// Возвращает `None`, если индекс вне сцены или модель добавлена без идентификатора.
+    pub fn id_at(&self, index: usize) -> Option<ModelId> {
+        self.models.get(index)?.id()
+    }
+
+    /// Модель с идентификатором `id`.
+    pub fn model(&self, id: ModelId) -> Option<&Model> {
+        self.models.iter().find(|model| model.id() == Some(id))
+    }
+
+    /// Модель с идентификатором `id` (мутабельно).
+    pub fn model_mut(&mut self, id: ModelId) -> Option<&mut Model> {
+        self.models.iter_mut().find(|model| model.id() == Some(id))
+    }
+
+    /// Первая модель с именем `name`.
+    pub fn model_by_name(&self, name: &str) -> Option<&Model> {
+        self.models
+            .iter()
+            .find(|model| model.name.as_deref() == Some(name))
+    }
+
+    /// Первая модель с именем `name` (мутабельно).
+    pub fn model_by_name_mut(&mut self, name: &str) -> Option<&mut Model> {
+        self.models
+            .iter_mut()
+            .find(|model| model.name.as_deref() == Some(name))
+    }
+
+    /// Удалить со сцены модель с идентификатором `id` и вернуть её.
+    ///
+    /// Индексы следующих за ней моделей уменьшаются на 1, идентификаторы не меняются.
+    pub fn remove_by_id(&mut self, id: ModelId) -> Option<Model> {
+        let index = self.model_index(id)?;
+        Some(self.models.remove(index))
+    }
+}
+
+// --------------------------------------------------
+// Размещение моделей
+// --------------------------------------------------
+
+impl Scene {
+    /// Найти ближайшее пересечение луча с видимыми моделями сцены.
+    ///
+    /// `ignore` - индекс модели, которая не учитывается (например, перемещаемая модель).
+    /// Возвращает индекс модели и пересечение с ней.
+    pub fn raycast(&self, ray: &Line3, ignore: Option<usize>) -> Option<(usize, RayHit)> {
+        self.models
+            .iter()
+            .enumerate()
+            .filter(|(index, model)| Some(*index) != ignore && model.flags.visible)
+            .filter_map(|(index, model)| Some((index, model.mesh.raycast(ray)?)))
+            .min_by(|(_, a), (_, b)| a.distance.total_cmp(&b.distance))
+    }
+
+    /// Опустить модель вертикально вниз (по `-y`) до касания с другой видимой моделью
+    /// или с плоскостью земли `y = ground_height`, если она задана.
+    ///
+    /// Возвращает расстояние, на которое опустилась модель, или `None`, если под моделью
+    /// ничего нет и она осталась на месте.
+    pub fn drop_to_floor(
+        &mut self,
+        model_index: usize,
+        mode: DropMode,
+        ground_height: Option<f32>,
+    ) -> Option<f32> {
+        let model = &self.models[model_index];
+        let (min, max) = model.global_bounds();
+
+        let mut distance: Option<f32> = ground_height
+            .map(|ground| min.y - ground)
+            .filter(|distance| *distance >= 0.0);
+        let mut update = |candidate: f32| {
+            if candidate >= 0.0 && distance.is_none_or(|distance| candidate < distance) {
+                distance = Some(candidate);
+            }
+        };
+
+        for (index, other) in self.models.iter().enumerate() {
+            if index == model_index || !other.flags.visible {
+                continue;
+            }
+
+            let (other_min, other_max) = other.global_bounds();
+            // модели не пересекаются в проекции на плоскость XZ
+            if max.x < other_min.x
+                || other_max.x < min.x
+                || max.z < other_min.z
+                || other_max.z < min.z
+            {
+                continue;
+            }
+
+            match mode {
+                DropMode::BoundingBox => update(min.y - other_max.y),
+                DropMode::Mesh => {
+                    // вершины модели падают на другую модель
+                    for vertex in model.mesh.get_global_vertex_iter() {
+                        let ray = Line3::new(vertex, UVec3::down());
+                        if let Some(hit) = other.mesh.raycast(&ray) {
+                            update(hit.distance);
+                        }
+                    }
+                    // вершины другой модели, торчащие вверх, упираются в модель
+                    for vertex in other.mesh.get_global_vertex_iter() {
+                        let ray = Line3::new(vertex, UVec3::up());
+                        if let Some(hit) = model.mesh.raycast(&ray) {
+                            update(hit.distance);
+                        }
+                    }
+                }
+            }
+        }
+
+        let distance = distance?;
+        self.models[model_index].move_y(-distance);
+        Some(distance)
+    }
+
+    /// Повернуть и передвинуть модель так, чтобы её полигон `polygon_index` лёг
+    /// на поверхность в точке `target_point` с нормалью `target_normal`.
+    ///
+    /// Нормаль полигона становится противоположной нормали поверхности, а центр полигона
+    /// совмещается с `target_point`. Удобно вместе с [`Scene::raycast`].
+    pub fn align_to_surface(
+        &mut self,
+        model_index: usize,
+        polygon_index: usize,
+        target_point: Point3,
+        target_normal: UVec3,
+    ) {
+        let model = &mut self.models[model_index];
+        let Some(normal) = model.mesh.get_global_polygon_normal(polygon_index) else {
+            #[cfg(debug_assertions)]
+            eprintln!(
+                "Warning: полигон {} вырожден, выравнивание невозможно",
+                polygon_index
+            );
+            return;
+        };
+
+        model.rotate(normal, -target_normal);
+        let center = model.mesh.get_global_polygon_center(polygon_index);
+        model.translate(target_point - center);
+    }
+
+    /// Разнесённый вид сцены: копия, в которой модели раздвинуты от общего центра.
+    ///
+    /// Каждая модель сдвигается на `factor`, умноженный на смещение центра её ограничивающего
+    /// параллелепипеда от среднего центров всех моделей. Если на сцене одна модель, то
+    /// раздвигаются связные компоненты её Mesh'а (см. [`Mesh::explode_components`]).
+    /// При `factor = 0` сцена не меняется.
+    pub fn exploded_view(&self, factor: f32) -> Scene {
+        let mut scene = self.clone();
+        if let [model] = scene.models.as_mut_slice() {
+            model.mesh.explode_components(factor);
+            return scene;
+        }
+
+        let centers: Vec<Point3> = scene
+            .models
+            .iter()
+            .map(|model| {
+                let (min, max) = model.global_bounds();
+                min + (max - min) / 2.0
+            })
+            .collect();
+        let center = crate::library::utils::calculate_center(&centers);
+        for (model, model_center) in scene.models.iter_mut().zip(centers) {
+            model.translate((model_center - center) * factor);
+        }
+        scene
+    }
+
+    /// Навести камеру `camera` так, чтобы в кадр с небольшим отступом поместились все
+    /// видимые модели сцены (см. [`Camera::frame_aabb`]).
+    ///
+    /// Модели без записи глубины (небо) не учитываются. Если учитывать нечего,
+    /// то камера не меняется и возвращается `false`.
+    pub fn frame_all(&self, camera: &mut Camera) -> bool {
+        let bounds = self
+            .models
+            .iter()
+            .filter(|model| model.flags.visible && model.flags.depth_write)
+            .filter_map(|model| model.mesh.global_aabb())
+            .reduce(|a, b| a.expanded_to(b.min).expanded_to(b.max));
+        let Some(bounds) = bounds else {
+            return false;
+        };
+        camera.frame_aabb(&bounds, FRAME_ALL_MARGIN);
+        true
+    }
+}
+
+/// Отступ вокруг моделей при наведении камеры на всю сцену, доля размера сцены.
+const FRAME_ALL_MARGIN: f32 = 0.1;
+
+// --------------------------------------------------
+// Затенение окружением
+// --------------------------------------------------
+
+/// Смещение начала лучей от вершины вдоль нормали, чтобы они не пересекали её же полигоны.
+const OCCLUSION_RAY_OFFSET: f32 = 1e-3;
+
+impl Scene {
+    /// Запечь затенение окружением (ambient occlusion) в вершины видимых моделей.
+    ///
+    /// Из каждой вершины выпускается `samples` лучей по полусфере вокруг её нормали
+    /// (с косинусным распределением), затенение вершины - доля лучей, попавших в видимую
+    /// геометрию сцены не дальше диагонали ограничивающего параллелепипеда модели.
+    /// Направления лучей детерминированы, поэтому повторное запекание даёт тот же результат.
+    /// Запекаются и уровни детализации. Модели без нормалей пропускаются.
+    ///
+    /// Результат сохраняется в Mesh'ах (см. [`Mesh::get_ambient_occlusion`]) и приглушает
+    /// освещение при шейдинге. После перемещения моделей затенение нужно запечь заново.
+    pub fn bake_ambient_occlusion(&mut self, samples: usize) {
+        debug_assert!(samples > 0, "количество лучей должно быть больше 0");
+
+        let bvh = Bvh::from_models(&self.models, |model| {
+            model.flags.visible && !model.flags.wireframe_only
+        });
+        let directions = hemisphere_directions(samples);
+
+        for model in &mut self.models {
+            if !model.flags.visible {
+                continue;
+            }
+            let (min, max) = model.global_bounds();
+            let radius = (max - min).length();
+            let transform = model.mesh.local_frame.local_to_global_matrix();
+
+            bake_mesh_occlusion(&mut model.mesh, transform, &bvh, &directions, radius);
+            for (_, lod_mesh) in &mut model.lod_group.levels {
+                bake_mesh_occlusion(lod_mesh, transform, &bvh, &directions, radius);
+            }
+        }
+    }
+}
+
+/// Запечь затенение окружением одного Mesh'а, вершины которого переводятся
+/// в глобальные координаты преобразованием `transform`.
+fn bake_mesh_occlusion(
+    mesh: &mut Mesh,
+    transform: Transform3D,
+    bvh: &Bvh,
+    directions: &[Vec3],
+    radius: f32,
+) {
+    let Some(normals) = mesh.get_local_normals_iter() else {
+        #[cfg(debug_assertions)]
+        eprintln!("Warning: у Mesh'а нет нормалей, затенение окружением не запечено");
+        return;
+    };
+
+    let occlusion = mesh
+        .get_local_vertex_iter()
+        .zip(normals)
+        .map(|(vertex, normal)| {
+            let vertex = vertex.apply_transform(transform).unwrap();
+            let normal = normal.apply_transform(transform).unwrap();
+            let origin = vertex + Vec3::from(normal) * OCCLUSION_RAY_OFFSET;
+
+            // базис касательной плоскости вершины
+            let helper = if normal.x.abs() < 0.9 {
+                UVec3::new(1.0, 0.0, 0.0)
+            } else {
+                UVec3::new(0.0, 1.0, 0.0)
+            };
+            let tangent = normal.cross(helper);
+            let bitangent = Vec3::from(normal).cross(tangent);
+
+            let hits = directions
+                .iter()
+                .filter(|direction| {
+                    let world = tangent * direction.x
+                        + bitangent * direction.y
+                        + Vec3::from(normal) * direction.z;
+                    let Ok(world) = world.normalize() else {
+                        return false;
+                    };
+                    bvh.intersect(&Line3::new(origin, world), radius, |_| true)
+                        .is_some()
+                })
+                .count();
+            hits as f32 / directions.len() as f32
+        })
+        .collect();
+    mesh.set_ambient_occlusion(occlusion);
+}
+
+/// Направления на полусфере вокруг оси `z` с косинусным распределением.
+///
+/// Точки раскладываются по спирали с золотым углом, поэтому покрывают полусферу
+/// равномерно без случайных чисел.
+fn hemisphere_directions(samples: usize) -> Vec<Vec3> {
+    let golden_angle = std::f32::consts::PI * (3.0 - 5.0_f32.sqrt());
+    (0..samples)
+        .map(|i| {
+            let t = (i as f32 + 0.5) / samples as f32;
+            let r = t.sqrt();
+            let phi = golden_angle * i as f32;
+            Vec3::new(r * phi.cos(), r * phi.sin(), (1.0 - t).sqrt())
+        })
+        .collect()
+}
+
+// --------------------------------------------------
+// Источники света
+// --------------------------------------------------
+
+impl Scene {
+    /// Включённые источники света, которые учитываются при рендере.
+    pub fn enabled_lights(&self) -> impl Iterator<Item = &LightSource> {
+        self.lights.iter().filter(|light| light.enabled)
+    }
+
+    /// Найти источник света по имени.
+    pub fn light(&self, name: &str) -> Option<&LightSource> {
+        self.lights.iter().find(|light| light.name == name)
+    }
+
+    /// Найти источник света по имени (мутабельно).
+    pub fn light_mut(&mut self, name: &str) -> Option<&mut LightSource> {
+        self.lights.iter_mut().find(|light| light.name == name)
+    }
+
+    /// Источники света группы `group`.
+    pub fn light_group(&self, group: &str) -> impl Iterator<Item = &LightSource> {
+        self.lights
+            .iter()
+            .filter(move |light| light.group.as_deref() == Some(group))
+    }
+
+    /// Включить или выключить все источники света группы `group`.
+    ///
+    /// Возвращает количество источников в группе.
+    pub fn set_light_group_enabled(&mut self, group: &str, enabled: bool) -> usize {
+        let mut count = 0;
+        for light in &mut self.lights {
+            if light.group.as_deref() == Some(group) {
+                light.enabled = enabled;
+                count += 1;
+            }
+        }
+        count
+    }
+}
+
+// --------------------------------------------------
+// Отслеживание изменений
+// --------------------------------------------------
+
+impl Scene {
+    /// Хэш всего состояния сцены, которое влияет на изображение:
+    /// геометрии, материалов и флагов моделей, камеры, источников света, окружения и фона.
+    ///
+    /// Одинаковые сцены дают одинаковый хэш, поэтому по нему можно понять, что
+    /// сцена не изменилась с прошлого кадра. Вычисление линейно по количеству вершин
+    /// и полигонов сцены, пиксели текстур не перебираются: хэш картинки считается
+    /// один раз при создании текстуры.
+    pub fn state_hash(&self) -> u64 {
+        let mut state = DefaultHasher::new();
+        self.models.hash_state(&mut state);
+        self.camera.hash_state(&mut state);
+        self.lights.hash_state(&mut state);
+        self.environment.hash_state(&mut state);
+        self.background.hash_state(&mut state);
+        self.reflection_map.hash_state(&mut state);
+        self.polylines.hash_state(&mut state);
+        self.projectors.hash_state(&mut state);
+        state.finish()
+    }
+}
+
+#[cfg(test)]
+mod scene_tests {
+    use super::*;
+    use crate::Aabb;
+
+    const TOLERANCE: f32 = 1e-4;
+
+    /// Сцена с широкой плитой на `y = 0` и кубом над ней.
+    fn slab_and_cube() -> Scene {
+        let mut slab = Model::from_mesh(Mesh::hexahedron());
+        slab.scale_vec(Vec3::new(10.0, 1.0, 10.0));
+        slab.set_position(Point3::new(0.0, -0.5, 0.0));
+
+        let mut cube = Model::from_mesh(Mesh::hexahedron());
+        cube.set_position(Point3::new(1.0, 5.0, 1.0));
+
+        Scene {
+            models: vec![slab, cube],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_raycast_scene() {
+        let scene = slab_and_cube();
+        let ray = Line3::new(Point3::new(1.0, 10.0, 1.0), UVec3::down());
+
+        let (index, hit) = scene.raycast(&ray, None).unwrap();
+        assert_eq!(index, 1, "первым на пути луча стоит куб");
+        assert!((hit.distance - 4.5).abs() < TOLERANCE);
+
+        let (index, hit) = scene.raycast(&ray, Some(1)).unwrap();
+        assert_eq!(index, 0);
+        assert!(
+            hit.point
+                .approx_equal(Point3::new(1.0, 0.0, 1.0), TOLERANCE)
+        );
+    }
+
+    #[test]
+    fn test_drop_to_floor() {
+        for mode in [DropMode::BoundingBox, DropMode::Mesh] {
+            let mut scene = slab_and_cube();
+            let distance = scene.drop_to_floor(1, mode, None).unwrap();
+            assert!(
+                (distance - 4.5).abs() < TOLERANCE,
+                "режим {:?}: ожидалось падение на 4.5, но получено {}",
+                mode,
+                distance
+            );
+            let (min, _) = scene.models[1].global_bounds();
+            assert!(min.y.abs() < TOLERANCE, "куб должен стоять на плите");
+        }
+    }
+
+    #[test]
+    fn test_drop_to_ground_plane() {
+        let mut scene = slab_and_cube();
+        // куб за пределами плиты
+        scene.models[1].set_position(Point3::new(20.0, 5.0, 0.0));
+        assert!(scene.drop_to_floor(1, DropMode::Mesh, None).is_none());
+        assert!(
+            scene.models[1]
+                .get_position()
+                .approx_equal(Point3::new(20.0, 5.0, 0.0), TOLERANCE)
+        );
+
+        let distance = scene.drop_to_floor(1, DropMode::Mesh, Some(-2.0)).unwrap();
+        assert!((distance - 6.5).abs() < TOLERANCE);
+    }
+
+    #[test]
+    fn test_exploded_view() {
+        let mut scene = slab_and_cube();
+        scene.models[0].set_position(Point3::new(-1.0, 0.0, 1.0));
+        scene.models[1].set_position(Point3::new(3.0, 0.0, 1.0));
+
+        let exploded = scene.exploded_view(1.0);
+        let positions: Vec<Point3> = exploded.models.iter().map(Model::get_position).collect();
+        assert!(positions[0].approx_equal(Point3::new(-3.0, 0.0, 1.0), TOLERANCE));
+        assert!(positions[1].approx_equal(Point3::new(5.0, 0.0, 1.0), TOLERANCE));
+        // исходная сцена не меняется
+        assert!(
+            scene.models[1]
+                .get_position()
+                .approx_equal(Point3::new(3.0, 0.0, 1.0), TOLERANCE)
+        );
+
+        // одна модель раздвигается по компонентам
+        let mut right = Mesh::hexahedron();
+        right.apply_transform(Transform3D::translation(4.0, 0.0, 0.0));
+        let single = Scene {
+            models: vec![Model::from_mesh(Mesh::merge(
+                &[&Mesh::hexahedron(), &right],
+                false,
+            ))],
+            ..Default::default()
+        };
+        let (min, max) = single.exploded_view(0.5).models[0].global_bounds();
+        assert!((min.x + 1.5).abs() < TOLERANCE && (max.x - 5.5).abs() < TOLERANCE);
+    }
+
+    #[test]
+    fn test_model_ids_survive_removal() {
+        let mut scene = Scene::default();
+        let a = scene.add_model(Model::from_mesh(Mesh::hexahedron()));
+        let b = scene.add_model_named(Model::from_mesh(Mesh::tetrahedron()), "b");
+        // модель без идентификатора, добавленная напрямую
+        scene.models.push(Model::from_mesh(Mesh::octahedron()));
+        let c = scene.add_model(scene.models[0].clone());
+        assert_ne!(a, c, "копия модели получила тот же идентификатор");
+
+        assert_eq!(scene.remove_by_id(a).and_then(|model| model.id()), Some(a));
+        assert_eq!(scene.model_index(b), Some(0));
+        assert_eq!(scene.model_index(c), Some(2));
+        assert!(scene.model(a).is_none() && scene.remove_by_id(a).is_none());
+        assert_eq!(scene.model_by_name("b").and_then(Model::id), Some(b));
+
+        assert_eq!(scene.id_at(0), Some(b));
+        assert_eq!(scene.id_at(1), None, "модель без идентификатора");
+        assert_eq!(scene.id_at(2), Some(c));
+        assert_eq!(scene.id_at(3), None);
+
+        scene.model_by_name_mut("b").unwrap().name = None;
+        assert!(scene.model_by_name("b").is_none());
+        assert!(scene.model_mut(b).is_some());
+    }
+
+    #[test]
+    fn test_frame_all() {
+        let mut scene = slab_and_cube();
+        scene.models[1].set_position(Point3::new(30.0, 5.0, 20.0));
+        let mut camera = Camera::default();
+        assert!(scene.frame_all(&mut camera));
+
+        // все углы моделей попадают в область видимости камеры
+        let tan_y = (camera.get_fov() / 2.0).tan();
+        let tan_x = tan_y * camera.get_aspect_ratio();
+        for model in &scene.models {
+            let (min, max) = model.global_bounds();
+            for corner in Aabb::new(min, max)
+                .edges()
+                .iter()
+                .flat_map(|&(a, b)| [a, b])
+            {
+                let local = corner
+                    .apply_transform(camera.local_frame.global_to_local_matrix())
+                    .unwrap();
+                let depth = -local.z;
+                assert!(depth > camera.get_near_plane() && depth < camera.get_far_plane());
+                assert!(local.x.abs() / depth < tan_x && local.y.abs() / depth < tan_y);
+            }
+        }
+        // направление камеры не меняется
+        assert_eq!(camera.forward(), Camera::default().forward());
+
+        let empty = Scene::default();
+        assert!(!empty.frame_all(&mut camera));
+    }
+
+    #[test]
+    fn test_align_to_surface() {
+        let mut scene = slab_and_cube();
+        let target_point = Point3::new(3.0, 2.0, 0.0);
+        // выравниваем по стене с нормалью -x
+        scene.align_to_surface(1, 0, target_point, UVec3::minus_x());
+
+        let cube = &scene.models[1];
+        let normal = cube.mesh.get_global_polygon_normal(0).unwrap();
+        let center = cube.mesh.get_global_polygon_center(0);
+        assert!(
+            normal.approx_equal(UVec3::plus_x(), TOLERANCE),
+            "нормаль грани должна смотреть в стену, но получено {:?}",
+            normal
+        );
+        assert!(center.approx_equal(target_point, TOLERANCE));
+    }
+
+    #[test]
+    fn test_state_hash_tracks_changes() {
+        let scene = slab_and_cube();
+        let hash = scene.state_hash();
+        assert_eq!(
+            hash,
+            scene.clone().state_hash(),
+            "копия сцены должна давать тот же хэш"
+        );
+
+        let mut moved = scene.clone();
+        moved.models[1].translate(Vec3::new(0.0, 0.1, 0.0));
+        assert_ne!(
+            hash,
+            moved.state_hash(),
+            "перемещение модели должно менять хэш"
+        );
+
+        let mut recolored = scene.clone();
+        recolored.models[0].material.color = egui::Color32::RED;
+        assert_ne!(hash, recolored.state_hash());
+
+        let mut camera_moved = scene.clone();
+        camera_moved.camera.set_clip_planes(0.5, 50.0);
+        assert_ne!(hash, camera_moved.state_hash());
+
+        let mut hidden = scene.clone();
+        hidden.models[0].flags.visible = false;
+        assert_ne!(hash, hidden.state_hash());
+
+        let mut background = scene.clone();
+        background.background = Background::VerticalGradient {
+            top: egui::Color32::WHITE,
+            bottom: egui::Color32::GRAY,
+        };
+        assert_ne!(hash, background.state_hash());
+
+        let mut with_line = scene;
+        with_line.polylines.push(crate::Polyline3::from_points(
+            vec![Point3::zero(), Point3::new(1.0, 0.0, 0.0)],
+            crate::LineStyle::default(),
+        ));
+        assert_ne!(hash, with_line.state_hash());
+    }
+
+    #[test]
+    fn test_bake_ambient_occlusion() {
+        let mut scene = slab_and_cube();
+        scene.models[1].set_position(Point3::new(1.0, 0.5, 1.0));
+        scene.bake_ambient_occlusion(64);
+
+        let cube = &scene.models[1].mesh;
+        assert!(cube.has_ambient_occlusion());
+        // вершины куба, стоящие на плите, закрыты сильнее верхних
+        let (mut bottom, mut top) = (0.0_f32, 0.0_f32);
+        for i in 0..cube.vertex_count() {
+            let occlusion = cube.get_ambient_occlusion(i).unwrap();
+            assert!((0.0..=1.0).contains(&occlusion));
+            if cube.get_global_vertex(i).y < 0.5 {
+                bottom += occlusion;
+            } else {
+                top += occlusion;
+            }
+        }
+        assert!(
+            bottom > top,
+            "нижние вершины ({}) должны быть затенены сильнее верхних ({})",
+            bottom,
+            top
+        );
+
+        let hash = scene.state_hash();
+        scene.bake_ambient_occlusion(64);
+        assert_eq!(hash, scene.state_hash(), "запекание детерминировано");
+    }
+
+    #[test]
+    fn test_light_groups() {
+        let mut scene = Scene::default();
+        let light = LightSource::new(Point3::zero(), egui::Color32::WHITE, 1.0);
+        scene
+            .lights
+            .push(light.clone().with_name("ключевой").with_group("студия"));
+        scene
+            .lights
+            .push(light.clone().with_name("заполняющий").with_group("студия"));
+        scene.lights.push(light.with_name("солнце"));
+
+        let hash = scene.state_hash();
+        assert_eq!(scene.set_light_group_enabled("студия", false), 2);
+        assert_ne!(
+            hash,
+            scene.state_hash(),
+            "выключение света меняет изображение"
+        );
+        let enabled: Vec<&str> = scene
+            .enabled_lights()
+            .map(|light| light.name.as_str())
+            .collect();
+        assert_eq!(enabled, ["солнце"]);
+        assert_eq!(scene.light_group("студия").count(), 2);
+        assert!(!scene.light("ключевой").unwrap().enabled);
+
+        scene.light_mut("ключевой").unwrap().enabled = true;
+        assert_eq!(scene.enabled_lights().count(), 2);
+        assert_eq!(scene.set_light_group_enabled("нет такой", true), 0);
+    }
+}
@@ -0,0 +1,433 @@
+//! Обратимые изменения сцены (команды) и история отмены/повтора.
+
+use std::collections::VecDeque;
+use std::fmt::Display;
+
+use crate::{CoordFrame, LightSource, Material, ModelId, Scene};
+
+/// Количество команд в истории по умолчанию.
+pub const DEFAULT_HISTORY_LIMIT: usize = 100;
+
+/// Обратимое изменение сцены.
+///
+/// Команда хранит состояние до и после изменения, поэтому её можно применять и отменять
+/// сколько угодно раз без копирования всей сцены. Обычно команды выполняются через
+/// [`Scene::execute`], чтобы попасть в историю [`Scene::history`].
+#[derive(Debug, Clone)]
+pub enum SceneCommand {
+    /// Изменение положения, поворота и масштаба модели.
+    SetTransform {
+        /// Идентификатор модели на сцене.
+        model: ModelId,
+        /// Локальная система координат модели до изменения.
+        before: CoordFrame,
+        /// Локальная система координат модели после изменения.
+        after: CoordFrame,
+    },
+    /// Изменение материала модели.
+    SetMaterial {
+        /// Идентификатор модели на сцене.
+        model: ModelId,
+        /// Материал до изменения.
+        before: Box<Material>,
+        /// Материал после изменения.
+        after: Box<Material>,
+    },
+    /// Изменение источника света.
+    SetLight {
+        /// Индекс источника в `scene.lights`.
+        light: usize,
+        /// Источник до изменения.
+        before: LightSource,
+        /// Источник после изменения.
+        after: LightSource,
+    },
+}
+
+/// Ошибки применения команд к сцене.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SceneCommandError {
+    /// Модели с таким идентификатором нет на сцене.
+    ModelNotFound(ModelId),
+    /// Источника света с таким индексом нет на сцене.
+    LightNotFound(usize),
+}
+
+impl Display for SceneCommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ModelNotFound(id) => write!(f, "модели {} нет на сцене", id),
+            Self::LightNotFound(index) => {
+                write!(f, "источника света с индексом {} нет на сцене", index)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SceneCommandError {}
+
+impl SceneCommand {
+    // --------------------------------------------------
+    // Конструкторы
+    // --------------------------------------------------
+
+    /// Команда, задающая модели `model` локальную систему координат `after`.
+    /// Состояние до изменения берётся из сцены `scene`.
+    pub fn set_transform(
+        scene: &Scene,
+        model: ModelId,
+        after: CoordFrame,
+    ) -> Result<Self, SceneCommandError> {
+        let before = scene
+            .model(model)
+            .ok_or(SceneCommandError::ModelNotFound(model))?
+            .mesh
+            .local_frame;
+        Ok(Self::SetTransform {
+            model,
+            before,
+            after,
+        })
+    }
+
+    /// Команда, задающая модели `model` материал `after`.
+    /// Состояние до изменения берётся из сцены `scene`.
+    pub fn set_material(
+        scene: &Scene,
+        model: ModelId,
+        after: Material,
+    ) -> Result<Self, SceneCommandError> {
+        let before = scene
+            .model(model)
+            .ok_or(SceneCommandError::ModelNotFound(model))?
+            .material
+            .clone();
+        Ok(Self::SetMaterial {
+            model,
+            before: Box::new(before),
+            after: Box::new(after),
+        })
+    }
+
+    /// Команда, заменяющая источник света `light` на `after`.
+    /// Состояние до изменения берётся из сцены `scene`.
+    pub fn set_light(
+        scene: &Scene,
+        light: usize,
+        after: LightSource,
+    ) -> Result<Self, SceneCommandError> {
+        let before = scene
+            .lights
+            .get(light)
+            .ok_or(SceneCommandError::LightNotFound(light))?
+            .clone();
+        Ok(Self::SetLight {
+            light,
+            before,
+            after,
+        })
+    }
+
+    // --------------------------------------------------
+    // Применение
+    // --------------------------------------------------
+
+    /// Применить изменение к сцене `scene`.
+    pub fn apply(&self, scene: &mut Scene) -> Result<(), SceneCommandError> {
+        self.set_state(scene, false)
+    }
+
+    /// Отменить изменение: вернуть сцене `scene` состояние до команды.
+    pub fn revert(&self, scene: &mut Scene) -> Result<(), SceneCommandError> {
+        self.set_state(scene, true)
+    }
+
+    /// Записать в сцену состояние до (`before`) или после команды.
+    fn set_state(&self, scene: &mut Scene, before: bool) -> Result<(), SceneCommandError> {
+        match self {
+            Self::SetTransform {
+                model,
+                before: frame_before,
+                after,
+            } => {
+                let model = scene
+                    .model_mut(*model)
+                    .ok_or(SceneCommandError::ModelNotFound(*model))?;
+                model.mesh.local_frame = if before { *frame_before } else { *after };
+            }
+            Self::SetMaterial {
+                model,
+                before: material_before,
+                after,
+            } => {
+                let model = scene
+                    .model_mut(*model)
+                    .ok_or(SceneCommandError::ModelNotFound(*model))?;
+                let material = if before { material_before } else { after };
+                model.material = material.as_ref().clone();
+            }
+            Self::SetLight {
+                light,
+                before: light_before,
+                after,
+            } => {
+                let light = scene
+                    .lights
+                    .get_mut(*light)
+                    .ok_or(SceneCommandError::LightNotFound(*light))?;
+                *light = if before { light_before } else { after }.clone();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// История выполненных команд для отмены и повтора.
+///
+/// Хранит не больше `limit` последних команд: самые старые забываются.
+/// Выполнение новой команды очищает список команд для повтора.
+///
+/// # Examples
+/// ```rust
+/// use g3d::{Mesh, Model, Point3, Scene, SceneCommand};
+///
+/// let mut scene = Scene::default();
+/// let cube = scene.add_model(Model::from_mesh(Mesh::hexahedron()));
+///
+/// let mut frame = scene.models[0].mesh.local_frame;
+/// frame.origin = Point3::new(1.0, 2.0, 3.0);
+/// let command = SceneCommand::set_transform(&scene, cube, frame)?;
+/// scene.execute(command)?;
+/// assert_eq!(scene.models[0].get_position(), Point3::new(1.0, 2.0, 3.0));
+///
+/// assert!(scene.undo()?);
+/// assert_eq!(scene.models[0].get_position(), Point3::zero());
+/// assert!(scene.redo()?);
+/// assert_eq!(scene.models[0].get_position(), Point3::new(1.0, 2.0, 3.0));
+/// # Ok::<(), g3d::SceneCommandError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct CommandStack {
+    /// Выполненные команды, последняя - в конце.
+    done: VecDeque<SceneCommand>,
+    /// Отменённые команды, последняя отменённая - в конце.
+    undone: Vec<SceneCommand>,
+    /// Максимальное количество команд для отмены.
+    pub limit: usize,
+}
+
+impl Default for CommandStack {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_LIMIT)
+    }
+}
+
+impl CommandStack {
+    /// Создать пустую историю не больше чем на `limit` команд.
+    pub fn new(limit: usize) -> Self {
+        Self {
+            done: VecDeque::new(),
+            undone: Vec::new(),
+            limit,
+        }
+    }
+
+    /// Применить команду к сцене и запомнить её для отмены.
+    ///
+    /// Если команда не применилась, то история не меняется.
+    pub fn execute(
+        &mut self,
+        scene: &mut Scene,
+        command: SceneCommand,
+    ) -> Result<(), SceneCommandError> {
+        command.apply(scene)?;
+        self.push(command);
+        Ok(())
+    }
+
+    /// Запомнить уже применённую к сцене команду для отмены.
+    ///
+    /// Удобно, когда изменение делается постепенно (например, перетаскиванием гизмо),
+    /// а в историю попадает только итог.
+    pub fn push(&mut self, command: SceneCommand) {
+        self.undone.clear();
+        self.done.push_back(command);
+        while self.done.len() > self.limit {
+            self.done.pop_front();
+        }
+    }
+
+    /// Отменить последнюю выполненную команду.
+    ///
+    /// Возвращает `false`, если отменять нечего. Если команда не отменилась
+    /// (например, модель удалили со сцены), то она пропадает из истории.
+    pub fn undo(&mut self, scene: &mut Scene) -> Result<bool, SceneCommandError> {
+        let Some(command) = self.done.pop_back() else {
+            return Ok(false);
+        };
+        command.revert(scene)?;
+        self.undone.push(command);
+        Ok(true)
+    }
+
+    /// Повторить последнюю отменённую команду.
+    ///
+    /// Возвращает `false`, если повторять нечего. Если команда не применилась,
+    /// то она пропадает из истории.
+    pub fn redo(&mut self, scene: &mut Scene) -> Result<bool, SceneCommandError> {
+        let Some(command) = self.undone.pop() else {
+            return Ok(false);
+        };
+        command.apply(scene)?;
+        self.done.push_back(command);
+        Ok(true)
+    }
+
+    /// Есть ли команды для отмены.
+    pub fn can_undo(&self) -> bool {
+        !self.done.is_empty()
+    }
+
+    /// Есть ли команды для повтора.
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    /// Забыть всю историю, например после загрузки другой сцены.
+    pub fn clear(&mut self) {
+        self.done.clear();
+        self.undone.clear();
+    }
+}
+
+// --------------------------------------------------
+// История сцены
+// --------------------------------------------------
+
+impl Scene {
+    /// Применить команду к сцене и запомнить её в истории [`Scene::history`].
+    pub fn execute(&mut self, command: SceneCommand) -> Result<(), SceneCommandError> {
+        self.with_history(|history, scene| history.execute(scene, command))
+    }
+
+    /// Отменить последнюю команду из истории. Возвращает `false`, если отменять нечего.
+    pub fn undo(&mut self) -> Result<bool, SceneCommandError> {
+        self.with_history(CommandStack::undo)
+    }
+
+    /// Повторить последнюю отменённую команду. Возвращает `false`, если повторять нечего.
+    pub fn redo(&mut self) -> Result<bool, SceneCommandError> {
+        self.with_history(CommandStack::redo)
+    }
+
+    /// Вызвать `f` с историей, временно вынутой из сцены, чтобы команды могли менять сцену.
+    fn with_history<T>(&mut self, f: impl FnOnce(&mut CommandStack, &mut Scene) -> T) -> T {
+        let mut history = std::mem::take(&mut self.history);
+        let result = f(&mut history, self);
+        self.history = history;
+        result
+    }
+}
+
+#[cfg(test)]
+mod scene_command_tests {
+    use super::*;
+    use crate::{Mesh, Model, Point3};
+    use egui::Color32;
+
+    fn scene_with_cube_and_light() -> (Scene, ModelId) {
+        let mut scene = Scene::default();
+        let cube = scene.add_model(Model::from_mesh(Mesh::hexahedron()));
+        scene
+            .lights
+            .push(LightSource::new(Point3::zero(), Color32::WHITE, 1.0));
+        (scene, cube)
+    }
+
+    #[test]
+    fn test_undo_redo_material_and_light() {
+        let (mut scene, cube) = scene_with_cube_and_light();
+        let original_color = scene.models[0].material.color;
+
+        let material = Material {
+            color: Color32::RED,
+            ..Default::default()
+        };
+        let command = SceneCommand::set_material(&scene, cube, material).unwrap();
+        scene.execute(command).unwrap();
+        let mut light = scene.lights[0].clone();
+        light.intensity = 0.25;
+        let command = SceneCommand::set_light(&scene, 0, light).unwrap();
+        scene.execute(command).unwrap();
+        assert_eq!(scene.models[0].material.color, Color32::RED);
+        assert_eq!(scene.lights[0].intensity, 0.25);
+
+        // отмена идёт в обратном порядке
+        assert!(scene.undo().unwrap());
+        assert_eq!(scene.lights[0].intensity, 1.0);
+        assert_eq!(scene.models[0].material.color, Color32::RED);
+        assert!(scene.undo().unwrap());
+        assert_eq!(scene.models[0].material.color, original_color);
+        assert!(!scene.undo().unwrap(), "отменять больше нечего");
+
+        assert!(scene.redo().unwrap());
+        assert_eq!(scene.models[0].material.color, Color32::RED);
+
+        // новая команда очищает повтор
+        let mut frame = scene.models[0].mesh.local_frame;
+        frame.origin = Point3::new(0.0, 1.0, 0.0);
+        let command = SceneCommand::set_transform(&scene, cube, frame).unwrap();
+        scene.execute(command).unwrap();
+        assert!(!scene.history.can_redo());
+        assert!(!scene.redo().unwrap());
+    }
+
+    #[test]
+    fn test_command_follows_model_after_removal_of_others() {
+        let (mut scene, cube) = scene_with_cube_and_light();
+        let other = scene.add_model(Model::from_mesh(Mesh::tetrahedron()));
+        scene.models.swap(0, 1);
+
+        let material = Material {
+            color: Color32::BLUE,
+            ..Default::default()
+        };
+        let command = SceneCommand::set_material(&scene, cube, material).unwrap();
+        scene.remove_by_id(other);
+        scene.execute(command).unwrap();
+        assert_eq!(scene.model(cube).unwrap().material.color, Color32::BLUE);
+
+        assert!(scene.undo().unwrap());
+        assert_ne!(scene.model(cube).unwrap().material.color, Color32::BLUE);
+    }
+
+    #[test]
+    fn test_history_limit_and_errors() {
+        let (mut scene, cube) = scene_with_cube_and_light();
+        scene.history.limit = 2;
+        for x in 1..=3 {
+            let mut frame = scene.models[0].mesh.local_frame;
+            frame.origin = Point3::new(x as f32, 0.0, 0.0);
+            let command = SceneCommand::set_transform(&scene, cube, frame).unwrap();
+            scene.execute(command).unwrap();
+        }
+        // самая старая команда забыта, поэтому модель не возвращается в начало координат
+        while scene.undo().unwrap() {}
+        assert_eq!(scene.models[0].get_position(), Point3::new(1.0, 0.0, 0.0));
+
+        assert_eq!(
+            SceneCommand::set_light(&scene, 3, scene.lights[0].clone()).unwrap_err(),
+            SceneCommandError::LightNotFound(3)
+        );
+        let command = SceneCommand::set_transform(&scene, cube, CoordFrame::default()).unwrap();
+        scene.models.clear();
+        assert_eq!(
+            scene.execute(command),
+            Err(SceneCommandError::ModelNotFound(cube))
+        );
+        assert!(
+            !scene.history.can_undo(),
+            "неприменённая команда попала в историю"
+        );
+    }
+}
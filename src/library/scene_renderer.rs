@@ -1,31 +1,62 @@
-use std::fmt::Display;
+use std::{borrow::Cow, fmt::Display, time::Instant};
 
 use crate::{
-    Camera, Canvas, LightSource, Model, Point3, Polygon, ProjectionType, Scene, Transform3D, UVec3,
-    Vec3, library::utils,
+    Camera, Canvas, CullMode, DepthFunc, DitherMode, Gizmo, HdrOptions, LayerMask, LightSource,
+    LineStyle, Model, PaletteOutput, Plane, Point3, Polygon, Polyline3, ProjectionType,
+    RenderTarget, Scene, Transform3D, UVec3, Vec3, library::utils,
 };
 use egui::{Color32, Pos2};
 
+mod auto_clip;
+mod auto_lod;
+mod bloom;
+mod debug_view;
+mod depth_shader;
+mod fragment_shader;
+mod frame_state;
+mod geometry_pass;
 mod gouraud_lambert_shader;
+mod grid;
+mod light_gizmos;
+mod lighting;
+mod mesh_cache;
 mod normals_shader;
+mod outline;
 mod phong_toon_shader;
+mod reflections;
+mod render_stats;
 mod solid_shader;
+mod ssao;
+mod vertex_shader;
 mod wireframe_shader;
 
+pub use auto_clip::AutoClipPlanes;
+pub use auto_lod::AutoLod;
+pub use bloom::BloomOptions;
+pub use debug_view::{DEBUG_MISSING_COLOR, DebugView};
+pub use fragment_shader::{FragmentInput, FragmentShader, SharedFragmentShader};
+pub use frame_state::FrameState;
+pub use grid::{GridOptions, GridPlane};
+pub use light_gizmos::LightGizmoOptions;
+pub use mesh_cache::RenderMeshCache;
+pub use outline::{OutlineMode, OutlineOptions};
+pub use render_stats::RenderStats;
+pub use ssao::SsaoOptions;
+pub use vertex_shader::{SharedVertexShader, VertexInput, VertexOutput, VertexShader};
+
 pub trait Shader {
     /// Применить шейдинг к модели.
     ///
     /// `model` - модель, к которой применяется шейдинг;
+    /// `geometry` - вершины и нормали модели, преобразованные для текущего кадра;
     /// `polygons` - набор полигонов к отрисовке;
-    /// `camera` - камера, на которую присходит проекция;
     /// `lights` - освещение на сцене;
     /// `canvas` - холст, на котором отрисовывается сцена;
     fn shade_model(
         &self,
         model: &Model,
+        geometry: &RenderMeshCache,
         polygons: &Vec<Polygon>,
-        camera: &Camera,
-        projection_type: ProjectionType,
         lights: &Vec<LightSource>,
         canvas: &mut Canvas,
     );
@@ -55,35 +86,151 @@ impl Display for ShadingType {
     }
 }
 
+/// Способ упорядочивания граней по глубине.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub enum DepthMode {
+    /// Попиксельная проверка глубины через z-буфер.
+    #[default]
+    ZBuffer,
+    /// Алгоритм художника: полигоны всех моделей сортируются по глубине центра
+    /// и рисуются от дальних к ближним без z-буфера.
+    ///
+    /// Пересекающиеся или циклически перекрывающие друг друга полигоны могут
+    /// отображаться неверно, так как они не разбиваются.
+    Painter,
+    /// Без упорядочивания: полигоны рисуются в порядке моделей на сцене.
+    None,
+}
+
+impl Display for DepthMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ZBuffer => f.write_str("Z-буфер"),
+            Self::Painter => f.write_str("Алгоритм художника"),
+            Self::None => f.write_str("Отсутствует"),
+        }
+    }
+}
+
 /// Структура для отрисовки сцены. Содержит в себе параметры рендера.
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct SceneRenderer {
     /// Отрисовывать ли каркас модели.
     pub render_wireframe: bool,
     /// Отрисовывать ли нормали вершин.
     pub render_normals: bool,
+    /// Отладочная отрисовка источников света.
+    pub light_gizmos: LightGizmoOptions,
     /// Отрисовывать ли грани модели.
     pub render_solid: bool,
     /// Тип проекции на камеру.
     pub projection_type: ProjectionType,
     /// Тип шейдинга. Ни на что не влияет, если `render_solid = false`.
     pub shading_type: ShadingType,
+    /// Пользовательский шейдер фрагментов. Если задан, то заменяет `shading_type`.
+    ///
+    /// [`SceneRenderer::render_if_changed`] не отслеживает изменения внутреннего состояния
+    /// шейдера, в этом случае нужно сбросить [`FrameState`].
+    pub fragment_shader: Option<SharedFragmentShader>,
+    /// Пользовательский шейдер вершин, деформирующий модели перед проекцией.
+    pub vertex_shader: Option<SharedVertexShader>,
+    /// Время в секундах, передаваемое в шейдер вершин.
+    pub shader_time: f32,
     /// Производить ли отсечение нелицевых граней.
     pub backface_culling: bool,
-    /// Использовать ли z-buffer для упорядочивания граней.
-    pub z_buffer_enabled: bool,
+    /// Способ упорядочивания граней по глубине.
+    pub depth_mode: DepthMode,
+    /// Условие проверки глубины z-буфера.
+    pub depth_func: DepthFunc,
+    /// Записывать ли глубину непрозрачных граней в z-буфер.
+    ///
+    /// Без записи глубины грани только проверяются по уже записанной (например, сетке или
+    /// проходу глубины), поэтому перекрываются в порядке отрисовки.
+    pub depth_write: bool,
+    /// Отладочный режим отображения: UV-координаты, нормали, глубина, сложность глубины и т.д.
+    ///
+    /// В отладочном режиме грани моделей растеризуются даже при `render_solid = false`.
+    pub debug_view: DebugView,
+    /// Скрывать ли невидимые (перекрытые гранями) рёбра каркаса.
+    ///
+    /// Если грани не рисуются через z-buffer, то перед отрисовкой производится отдельный проход глубины.
+    pub hidden_line_removal: bool,
+    /// Сглаживать ли рёбра каркаса (алгоритм Ву).
+    pub antialiased_lines: bool,
+    /// Параметры опорной сетки и координатных осей.
+    pub grid_options: GridOptions,
+    /// Цвет выделения выбранных моделей.
+    pub selection_color: Color32,
+    /// Затенение складок и мест касания моделей в экранном пространстве.
+    pub ssao: SsaoOptions,
+    /// Свечение ярких пикселей, светящихся материалов и источников света поверх кадра.
+    pub bloom: BloomOptions,
+    /// Вывод кадра в ограниченной палитре с дизерингом последним этапом рендера.
+    /// `None` - полноцветный кадр.
+    pub palette_output: Option<PaletteOutput>,
+    /// Обводка контуров выделенных или всех моделей поверх кадра.
+    pub outline: OutlineOptions,
+    /// Обводка силуэтных рёбер моделей (см. [`crate::Mesh::silhouette_edges`]) заданным стилем
+    /// с проверкой по z-буферу. `None` - без обводки.
+    ///
+    /// В отличие от [`SceneRenderer::outline`] силуэт строится по геометрии, а не по буферам
+    /// кадра, поэтому вместе с тун-шейдингом даёт ровные контуры заданной толщины.
+    pub silhouette: Option<LineStyle>,
+    /// HDR-режим холста: освещение накапливается без ограничения яркости и сжимается
+    /// тональной компрессией. `None` - обычный 8-битный холст.
+    pub hdr: Option<HdrOptions>,
+    /// Манипулятор, рисуемый поверх выделенных моделей.
+    pub gizmo: Option<Gizmo>,
+    /// Автоматическая подстройка плоскостей отсечения камеры под глубину сцены.
+    pub auto_clip_planes: AutoClipPlanes,
+    /// Автоматический выбор уровня детализации моделей по размеру на экране.
+    pub auto_lod: AutoLod,
+    /// Пользовательские плоскости отсечения в **глобальных** координатах.
+    ///
+    /// От моделей остаётся только часть со стороны нормали у всех плоскостей. Срез не
+    /// закрывается новыми гранями, поэтому через него видно внутренность замкнутых моделей.
+    /// Сетка, оси и манипуляторы плоскостями не отсекаются.
+    pub clip_planes: Vec<Plane>,
+    /// Рисуемые слои: модели и источники света из других слоёв пропускаются
+    /// (см. [`LayerMask`]). По умолчанию рисуются все слои.
+    pub layer_mask: LayerMask,
 }
 
+/// Количество фрагментов на пиксель, которое соответствует самому "горячему" цвету тепловой карты.
+pub const OVERDRAW_HEATMAP_LIMIT: u32 = 8;
+
 impl Default for SceneRenderer {
     fn default() -> Self {
         Self {
             render_wireframe: true,
             render_normals: false,
+            light_gizmos: LightGizmoOptions::default(),
             render_solid: false,
             projection_type: Default::default(),
             shading_type: Default::default(),
+            fragment_shader: None,
+            vertex_shader: None,
+            shader_time: 0.0,
             backface_culling: false,
-            z_buffer_enabled: true,
+            depth_mode: DepthMode::default(),
+            depth_func: DepthFunc::default(),
+            depth_write: true,
+            debug_view: DebugView::None,
+            hidden_line_removal: false,
+            antialiased_lines: false,
+            grid_options: GridOptions::default(),
+            selection_color: Color32::from_rgb(255, 165, 0),
+            ssao: SsaoOptions::default(),
+            bloom: BloomOptions::default(),
+            palette_output: None,
+            outline: OutlineOptions::default(),
+            silhouette: None,
+            hdr: None,
+            gizmo: None,
+            auto_clip_planes: AutoClipPlanes::default(),
+            auto_lod: AutoLod::default(),
+            clip_planes: Vec::new(),
+            layer_mask: LayerMask::ALL,
         }
     }
 }
@@ -91,7 +238,8 @@ impl Default for SceneRenderer {
 impl SceneRenderer {
     /// Нарисовать сцену на холст со всеми нужными преобразованиями.
     ///
-    /// Возвращает количество отрисованных полигонов.
+    /// Возвращает статистику кадра: количество полигонов на каждом этапе отсечения,
+    /// количество фрагментов и время этапов рендера.
     pub fn render(
         &self,
         scene: &Scene,
@@ -99,17 +247,40 @@ impl SceneRenderer {
         show_custom_axis: bool,
         axis_point1: Point3,
         axis_point2: Point3,
-    ) -> usize {
-        // Стереть прошлый кадр.
+    ) -> RenderStats {
+        let frame_start = Instant::now();
+        let mut stats = RenderStats::default();
+
+        // На холсте нулевого размера рисовать нечего (например, при запуске приложения).
+        if canvas.width() == 0 || canvas.height() == 0 {
+            #[cfg(debug_assertions)]
+            eprintln!("Warning: холст нулевого размера, рендер пропущен");
+            return stats;
+        }
+
+        // Стереть прошлый кадр (только в области отсечения холста, если она задана).
+        canvas.set_hdr(self.hdr);
+        canvas.set_depth_func(self.depth_func);
+        canvas.set_depth_write(self.depth_write);
+        let scissor = canvas.begin_flipped_frame();
         canvas.clear(Color32::GRAY);
 
+        // Камера текущего кадра, возможно с подстроенными плоскостями отсечения
+        let camera = &self.frame_camera(scene);
+
+        // Фон сцены
+        scene.background.paint(canvas, camera);
+
         // Матрица преобразования из глобальных координат в экранные
-        let global_to_screen_transform = scene
-            .camera
-            .global_to_screen_transform(self.projection_type, canvas);
+        let global_to_screen_transform =
+            camera.global_to_screen_transform(self.projection_type, canvas);
 
-        // Отрисовка глобальной координатной системы.
-        self.draw_coordinate_axes(canvas, global_to_screen_transform);
+        // Отрисовка опорной сетки и глобальной координатной системы.
+        self.grid_options.draw(
+            canvas,
+            global_to_screen_transform,
+            Vec3::from(camera.get_position()).length(),
+        );
 
         // Отрисовка пользовательской оси вращения, если имеется
         if show_custom_axis {
@@ -121,328 +292,877 @@ impl SceneRenderer {
             );
         }
 
-        draw_lights(&scene.lights, global_to_screen_transform, canvas);
+        self.light_gizmos.draw_markers(
+            &scene.lights,
+            self.layer_mask,
+            global_to_screen_transform,
+            canvas,
+        );
+
+        // проход глубины для скрытия невидимых рёбер, если z-буфер не будет заполнен гранями
+        let depth_prepass = self.hidden_line_removal
+            && !((self.render_solid || self.debug_view != DebugView::None)
+                && self.z_buffer_enabled());
+        if depth_prepass {
+            self.depth_prepass(scene, camera, canvas);
+        }
+
+        // полупрозрачные модели (с индексами в сцене) и их полигоны, отрисовываются после всех непрозрачных
+        let mut transparent_models: Vec<(usize, Cow<Model>, RenderMeshCache, Vec<Polygon>)> =
+            Vec::new();
+        // в режиме художника все модели рисуются после сортировки полигонов всей сцены
+        let painter = self.depth_mode == DepthMode::Painter;
+        let mut sorted_models: Vec<(usize, Cow<Model>, RenderMeshCache, Vec<Polygon>)> = Vec::new();
+        // силуэтные рёбра рисуются после всех непрозрачных граней, чтобы проверяться по их глубине
+        let mut silhouettes: Vec<Polyline3> = Vec::new();
 
-        // количество отрисованных полигонов.
-        let mut polygon_count: usize = 0;
+        // отрисовка моделей, модели без записи глубины (небо) рисуются первыми,
+        // чтобы остальные модели их перекрывали
+        let (background_models, foreground_models): (Vec<usize>, Vec<usize>) =
+            (0..scene.models.len()).partition(|&index| !scene.models[index].flags.depth_write);
+        for model_index in background_models.into_iter().chain(foreground_models) {
+            let model = &scene.models[model_index];
+            // скрытые модели и модели из других слоёв не отрисовываются
+            if !self.renders_model(model) {
+                continue;
+            }
 
-        // отрисовка моделей
-        for model in &scene.models {
-            // Полигоны к отрисовке
-            let polygons = if self.backface_culling {
-                // только видимые
-                self.model_backface_culling(scene.camera, model)
-            } else {
-                // все
-                model.mesh.get_polygon_iter().cloned().collect()
-            };
-            // отсечение полигонов вне камеры
-            let polygons = self.model_view_culling(
-                model,
-                polygons,
-                &scene.camera,
+            stats.models_rendered += 1;
+
+            // геометрия модели с учётом уровня детализации, секущего параллелепипеда и ближней плоскости камеры
+            let stage_start = Instant::now();
+            let clipped = self.model_geometry(model, camera, canvas, &mut stats);
+            let model = clipped.as_ref();
+            // вершины и нормали модели преобразуются один раз за кадр
+            let geometry = RenderMeshCache::new(
+                &model.mesh,
+                global_to_screen_transform,
                 self.projection_type,
-                canvas,
             );
+            stats.geometry_time += stage_start.elapsed();
 
-            polygon_count = polygons.len();
-
-            // заполнить модель
-            if self.render_solid {
-                match self.shading_type {
-                    ShadingType::None => {
-                        let shader = solid_shader::SolidShader::new(self.z_buffer_enabled);
-                        shader.shade_model(
-                            model,
-                            &polygons,
-                            &scene.camera,
-                            self.projection_type,
-                            &scene.lights,
-                            canvas,
-                        );
-                    }
-                    ShadingType::GouraudLambert => {
-                        let shader = gouraud_lambert_shader::GouraudLambertShader::new(
-                            self.z_buffer_enabled,
-                        );
-                        shader.shade_model(
-                            model,
-                            &polygons,
-                            &scene.camera,
-                            self.projection_type,
-                            &scene.lights,
-                            canvas,
-                        );
-                    }
-                    ShadingType::PhongToonShading(bands) => {
-                        let shader =
-                            phong_toon_shader::PhongToonShading::new(self.z_buffer_enabled, bands);
-                        shader.shade_model(
-                            model,
-                            &polygons,
-                            &scene.camera,
-                            self.projection_type,
-                            &scene.lights,
-                            canvas,
-                        );
-                    }
-                };
+            // Полигоны к отрисовке: без нелицевых (если включено) и вне камеры
+            let stage_start = Instant::now();
+            let polygons = self.culled_polygons(model, &geometry, camera, &mut stats);
+            stats.culling_time += stage_start.elapsed();
+            stats.polygons_rendered += polygons.len();
+
+            if painter {
+                sorted_models.push((model_index, clipped, geometry, polygons));
+                continue;
             }
 
-            // каркас модели
-            if self.render_wireframe {
-                let shader = wireframe_shader::WireframeShader::new();
-                shader.shade_model(
-                    model,
-                    &polygons,
-                    &scene.camera,
-                    self.projection_type,
-                    &scene.lights,
-                    canvas,
-                );
+            // пиксели модели помечаются её индексом в буфере идентификаторов холста,
+            // идентификатор модели по нему даёт Scene::id_at
+            canvas.set_current_id(Some(model_index));
+            canvas.set_depth_write(self.depth_write && model.flags.depth_write);
+
+            // заполнить модель, полупрозрачные модели откладываются до отрисовки всех непрозрачных
+            let fill = self.fills_model(model);
+            let transparent = fill && model.material.is_transparent();
+            if fill && !transparent {
+                let stage_start = Instant::now();
+                self.shade_solid(model, &geometry, &polygons, scene, camera, canvas);
+                stats.shading_time += stage_start.elapsed();
             }
 
-            // нормали модели
-            if self.render_normals {
-                let shader = normals_shader::NormalsShader::new();
-                shader.shade_model(
-                    model,
-                    &polygons,
-                    &scene.camera,
-                    self.projection_type,
-                    &scene.lights,
-                    canvas,
-                );
+            let stage_start = Instant::now();
+            self.shade_overlays(model, &geometry, &polygons, scene, canvas);
+            stats.wireframe_time += stage_start.elapsed();
+            canvas.set_depth_write(self.depth_write);
+
+            if let Some(style) = self.silhouette {
+                silhouettes.extend(self.model_silhouette(model, camera, style));
+            }
+
+            if transparent {
+                transparent_models.push((model_index, clipped, geometry, polygons));
             }
         }
-        canvas.invert_y();
-        polygon_count
-    }
+        canvas.set_current_id(None);
 
-    /// Отрисовка глобальной координатной системы.
-    fn draw_coordinate_axes(&self, canvas: &mut Canvas, global_to_screen_transform: Transform3D) {
-        let axis_length = 5.0; // Длина осей
-        let origin = Point3::zero();
+        if painter {
+            // грани всех моделей от дальних к ближним, затем каркас поверх них
+            let stage_start = Instant::now();
+            let filled: Vec<_> = sorted_models
+                .iter()
+                .filter(|(_, model, _, _)| self.fills_model(model))
+                .collect();
+            self.render_depth_sorted(&filled, camera, scene, canvas);
+            stats.shading_time += stage_start.elapsed();
 
-        let x_axis_end = Point3::new(axis_length, 0.0, 0.0);
-        let y_axis_end = Point3::new(0.0, axis_length, 0.0);
-        let z_axis_end = Point3::new(0.0, 0.0, axis_length);
+            let stage_start = Instant::now();
+            for (model_index, model, geometry, polygons) in &sorted_models {
+                canvas.set_current_id(Some(*model_index));
+                self.shade_overlays(model, geometry, polygons, scene, canvas);
+            }
+            canvas.set_current_id(None);
+            stats.wireframe_time += stage_start.elapsed();
+        }
 
-        // Рисуем оси с разными цветами
-        // Ось X - красная
-        utils::render_line(
-            global_to_screen_transform,
-            origin,
-            x_axis_end,
-            Color32::RED,
-            canvas,
-        );
+        // проекторы текстур накладываются на непрозрачные грани по z-буферу
+        if self.debug_view == DebugView::None {
+            crate::library::texture_projector::apply_projectors(
+                &scene.projectors,
+                canvas,
+                camera,
+                self.projection_type,
+                global_to_screen_transform,
+            );
+        }
 
-        // Ось Y - зелёная
-        utils::render_line(
-            global_to_screen_transform,
-            origin,
-            y_axis_end,
-            Color32::GREEN,
-            canvas,
-        );
+        // ломаные линии сцены проверяются по глубине непрозрачных граней
+        let stage_start = Instant::now();
+        for polyline in scene.polylines.iter().filter(|polyline| polyline.visible) {
+            polyline.draw(camera, self.projection_type, canvas);
+        }
+        for silhouette in &silhouettes {
+            silhouette.draw(camera, self.projection_type, canvas);
+        }
+        stats.wireframe_time += stage_start.elapsed();
 
-        // Ось Z - синяя
-        utils::render_line(
-            global_to_screen_transform,
-            origin,
-            z_axis_end,
-            Color32::BLUE,
-            canvas,
+        // полупрозрачные полигоны всех моделей от дальних к ближним
+        let stage_start = Instant::now();
+        self.render_transparent(&transparent_models, camera, scene, canvas);
+        stats.transparent_time = stage_start.elapsed();
+
+        stats.pixels_shaded = canvas.total_fragment_count();
+        stats.depth_rejections = canvas.depth_rejection_count();
+
+        // отладочные режимы по буферам холста
+        match self.debug_view {
+            DebugView::Overdraw => canvas.apply_overdraw_heatmap(OVERDRAW_HEATMAP_LIMIT),
+            DebugView::Depth => canvas.apply_depth_grayscale(),
+            _ => {}
+        }
+
+        // затенение окружением и обводка контуров по вспомогательному проходу геометрии
+        if self.debug_view == DebugView::None {
+            self.apply_ssao(scene, camera, canvas);
+        }
+        self.draw_outlines(scene, camera, canvas);
+
+        // отладочные сферы и направления источников света
+        self.light_gizmos
+            .draw_overlay(scene, self.layer_mask, global_to_screen_transform, canvas);
+
+        // манипуляторы выделенных моделей поверх всего изображения
+        if let Some(gizmo) = &self.gizmo {
+            for model in scene
+                .models
+                .iter()
+                .filter(|model| self.renders_model(model) && model.flags.selected)
+            {
+                gizmo.draw(&model.mesh.local_frame, global_to_screen_transform, canvas);
+            }
+        }
+
+        // свечение размывает кадр за пределы области отсечения, поэтому при рендере
+        // по частям накладывается на готовый кадр в `finish_tiled_frame`
+        if self.debug_view == DebugView::None && scissor.is_none() {
+            self.apply_bloom(scene, camera, canvas);
+        }
+
+        canvas.end_flipped_frame(scissor);
+
+        // квантование в палитру в экранных координатах, чтобы узор дизеринга не переворачивался;
+        // диффузия ошибки переносит ошибку через границы области отсечения, поэтому при рендере
+        // по частям делается на готовом кадре в `finish_tiled_frame`
+        if let Some(output) = &self.palette_output
+            && (scissor.is_none() || output.dither != DitherMode::FloydSteinberg)
+        {
+            canvas.quantize(output);
+        }
+        stats.total_time = frame_start.elapsed();
+        stats
+    }
+
+    /// Нарисовать сцену в цель рендера.
+    ///
+    /// Помимо цвета и глубины, заполняет буфер идентификаторов (индекс модели в `scene.models`)
+    /// и буфер нормалей ближайших к камере поверхностей видимых моделей.
+    ///
+    /// Возвращает статистику кадра, см. [`SceneRenderer::render`].
+    pub fn render_to_target(
+        &self,
+        scene: &Scene,
+        target: &mut RenderTarget,
+        show_custom_axis: bool,
+        axis_point1: Point3,
+        axis_point2: Point3,
+    ) -> RenderStats {
+        let stats = self.render(
+            scene,
+            target.canvas_mut(),
+            show_custom_axis,
+            axis_point1,
+            axis_point2,
         );
+        self.geometry_pass(scene, &self.frame_camera(scene), target);
+        stats
+    }
+
+    /// Проверяется ли глубина через z-буфер.
+    fn z_buffer_enabled(&self) -> bool {
+        self.depth_mode == DepthMode::ZBuffer
+    }
+
+    /// Рисуется ли модель: видима и лежит в одном из рисуемых слоёв.
+    fn renders_model(&self, model: &Model) -> bool {
+        model.flags.visible && model.flags.layers.intersects(self.layer_mask)
+    }
+
+    /// Освещает ли источник сцену: включён и лежит в одном из рисуемых слоёв.
+    fn renders_light(&self, light: &LightSource) -> bool {
+        light.enabled && light.layers.intersects(self.layer_mask)
+    }
+
+    /// Заполняются ли грани модели.
+    fn fills_model(&self, model: &Model) -> bool {
+        (self.render_solid || self.debug_view != DebugView::None) && !model.flags.wireframe_only
     }
 
-    /// Отсечение нелицевых граней модели
+    /// Отрисовать каркас и нормали модели поверх её граней.
     ///
-    /// Возвращает вектор полигонов только с лицевыми гранями.
-    fn model_backface_culling(&self, camera: Camera, model: &Model) -> Vec<Polygon> {
-        let global_normals: Vec<UVec3> = model.mesh.get_global_normals_iter().unwrap().collect();
-        let global_vertexes: Vec<Point3> = model.mesh.get_global_vertex_iter().collect();
-        let mut visible_polygons = Vec::new();
-        for polygon in model.mesh.get_polygon_iter() {
-            let mut polygon_normal = Vec3::zero();
-            let indexes: Vec<usize> = polygon.get_mesh_vertex_index_iter().collect();
-            for vertex_index in indexes.clone() {
-                polygon_normal += global_normals[vertex_index];
+    /// Выделенные модели обводятся каркасом цвета выделения.
+    fn shade_overlays(
+        &self,
+        model: &Model,
+        geometry: &RenderMeshCache,
+        polygons: &Vec<Polygon>,
+        scene: &Scene,
+        canvas: &mut Canvas,
+    ) {
+        if self.render_wireframe || model.flags.wireframe_only || model.flags.selected {
+            let mut shader = wireframe_shader::WireframeShader::new(
+                self.hidden_line_removal,
+                self.antialiased_lines,
+            );
+            if model.flags.selected {
+                shader = shader.with_color(self.selection_color);
             }
+            shader.shade_model(model, geometry, polygons, &scene.lights, canvas);
+        }
 
-            // Если нормаль есть, производим отсечение
-            if polygon_normal.length_squared() > 0.0 {
-                let polygon_normal = (polygon_normal / polygon.vertex_count() as f32)
-                    .normalize()
-                    .unwrap();
-
-                let camera_direction = match self.projection_type {
-                    ProjectionType::Parallel => camera.get_direction(),
-                    ProjectionType::Perspective => {
-                        let mut polygon_pos = Point3::zero();
-                        for vertex_index in indexes {
-                            polygon_pos += Vec3::from(global_vertexes[vertex_index]);
-                        }
-                        polygon_pos =
-                            Point3::from(Vec3::from(polygon_pos) / polygon.vertex_count() as f32);
-                        (polygon_pos - camera.get_position()).normalize().unwrap()
-                    }
-                };
+        if self.render_normals {
+            let shader = normals_shader::NormalsShader::new();
+            shader.shade_model(model, geometry, polygons, &scene.lights, canvas);
+        }
+    }
 
-                // Если нормаль направлена в сторону камеры, то оставляем полигон
-                let dot_product = polygon_normal.dot(camera_direction);
-                if dot_product < 0.0 {
-                    visible_polygons.push(polygon.clone());
-                }
-            }
+    /// Заполнить полигоны модели выбранным шейдером.
+    ///
+    /// Если обратные грани модели видны (см. [`Model::flips_back_face_normals`]), то они
+    /// освещаются с развёрнутыми нормалями.
+    fn shade_solid(
+        &self,
+        model: &Model,
+        geometry: &RenderMeshCache,
+        polygons: &Vec<Polygon>,
+        scene: &Scene,
+        camera: &Camera,
+        canvas: &mut Canvas,
+    ) {
+        if !model.flips_back_face_normals() || geometry.global_normals.is_none() {
+            self.shade_polygons(model, geometry, polygons, scene, camera, canvas);
+            return;
         }
 
-        visible_polygons
+        // обратные грани освещаются с развёрнутыми нормалями
+        let projection_direction = camera.projection_direction(self.projection_type);
+        let (front, back): (Vec<Polygon>, Vec<Polygon>) =
+            polygons.iter().cloned().partition(|polygon| {
+                self::polygon_facing(polygon, geometry, camera, projection_direction) != Some(false)
+            });
+        self.shade_polygons(model, geometry, &front, scene, camera, canvas);
+        if !back.is_empty() {
+            let flipped = geometry.with_flipped_normals();
+            self.shade_polygons(model, &flipped, &back, scene, camera, canvas);
+        }
     }
 
-    /// Отсечение полигонов, которые находятся за границами near и far камеры
-    fn model_view_culling(
+    /// Закрасить полигоны модели выбранным шейдером, нормали берутся из `geometry` как есть.
+    fn shade_polygons(
         &self,
         model: &Model,
-        polygons: Vec<Polygon>,
+        geometry: &RenderMeshCache,
+        polygons: &Vec<Polygon>,
+        scene: &Scene,
         camera: &Camera,
-        projection_type: ProjectionType,
-        canvas: &Canvas,
-    ) -> Vec<Polygon> {
-        // матрица преобразования на экран
-        let global_to_screen_transform = camera.global_to_screen_transform(projection_type, canvas);
-        // проекция вершин на экран
-        let projected_vertexes: Vec<Point3> = model
-            .mesh
-            .get_global_vertex_iter()
-            .map(|v| {
-                v.apply_transform(global_to_screen_transform)
-                    .unwrap_or(Point3::new(0.0, 0.0, -999.9))
-            })
-            .collect();
+        canvas: &mut Canvas,
+    ) {
+        // выключенные источники и источники из других слоёв не учитываются,
+        // копия нужна, только если они есть
+        let enabled_lights: Vec<LightSource>;
+        let lights = if scene.lights.iter().all(|light| self.renders_light(light)) {
+            &scene.lights
+        } else {
+            enabled_lights = scene
+                .lights
+                .iter()
+                .filter(|light| self.renders_light(light))
+                .cloned()
+                .collect();
+            &enabled_lights
+        };
+        // отладочный режим заменяет и шейдинг, и пользовательский шейдер фрагментов
+        let debug_shader = self.debug_view.fragment_shader();
+        if let Some(fragment_shader) = debug_shader.as_ref().or(self.fragment_shader.as_ref()) {
+            let shader = fragment_shader::ProgrammableShader::new(
+                fragment_shader,
+                self.z_buffer_enabled(),
+                scene.environment.as_ref(),
+            );
+            shader.shade_model(model, geometry, polygons, lights, canvas);
+            return;
+        }
 
-        let mut res = Vec::new();
-        for polygon in polygons {
-            let mut is_inside = true;
-            for index in polygon.get_mesh_vertex_index_iter() {
-                // если хоть одна вершина вне камеры, то исключаем полигон
-                if projected_vertexes[index].z < -1.0 && 1.0 < projected_vertexes[index].z {
-                    is_inside = false;
-                    break;
-                }
+        match self.shading_type {
+            ShadingType::None => {
+                let shader = solid_shader::SolidShader::new(self.z_buffer_enabled());
+                shader.shade_model(model, geometry, polygons, lights, canvas);
             }
-            if is_inside {
-                res.push(polygon);
+            ShadingType::GouraudLambert => {
+                let shader = gouraud_lambert_shader::GouraudLambertShader::new(
+                    self.z_buffer_enabled(),
+                    scene.environment.as_ref(),
+                    reflections::Reflections::new(scene, camera, self.projection_type),
+                );
+                shader.shade_model(model, geometry, polygons, lights, canvas);
             }
-        }
-
-        res
+            ShadingType::PhongToonShading(bands) => {
+                let shader = phong_toon_shader::PhongToonShading::new(
+                    self.z_buffer_enabled(),
+                    bands,
+                    scene.environment.as_ref(),
+                    reflections::Reflections::new(scene, camera, self.projection_type),
+                );
+                shader.shade_model(model, geometry, polygons, lights, canvas);
+            }
+        };
     }
-}
 
-// --------------------------------------------------
-// Вспомогательные методы
-// --------------------------------------------------
+    /// Отрисовать полупрозрачные полигоны всех моделей, отсортированные от дальних к ближним.
+    ///
+    /// Полигоны разных моделей сортируются вместе, поэтому пересекающиеся полупрозрачные объекты
+    /// смешиваются в правильном порядке. Z-буфер при этом только проверяется, но не обновляется.
+    fn render_transparent(
+        &self,
+        transparent_models: &[(usize, Cow<Model>, RenderMeshCache, Vec<Polygon>)],
+        camera: &Camera,
+        scene: &Scene,
+        canvas: &mut Canvas,
+    ) {
+        if transparent_models.is_empty() {
+            return;
+        }
 
-/// Преобразует глобальные координаты точки в координаты экрана.
-fn project_point(point: Point3, view_proj_matrix: Transform3D) -> Pos2 {
-    let proj_point: Point3 = point.apply_transform(view_proj_matrix).unwrap();
-    Pos2::new(proj_point.x, proj_point.y)
-}
+        let depth_write = canvas.depth_write();
+        canvas.set_depth_write(false);
+        let models: Vec<_> = transparent_models.iter().collect();
+        self.render_depth_sorted(&models, camera, scene, canvas);
+        canvas.set_depth_write(depth_write);
+    }
 
-/// Отрисовка пользовательской оси для вращения
-fn draw_custom_axis_line(
-    canvas: &mut Canvas,
-    global_to_screen_transform: Transform3D,
-    point1: Point3,
-    point2: Point3,
-) {
-    // Проецируем точки в 2D используя нашу систему проекций
-    let screen_point1 = project_point(point1, global_to_screen_transform);
-    let screen_point2 = project_point(point2, global_to_screen_transform);
+    /// Отрисовать грани моделей, отсортировав полигоны всех моделей от дальних к ближним
+    /// по глубине их центров.
+    ///
+    /// Полупрозрачные модели смешиваются с уже нарисованным изображением.
+    /// Первый элемент кортежа - индекс модели в сцене для буфера идентификаторов холста.
+    fn render_depth_sorted(
+        &self,
+        models: &[&(usize, Cow<Model>, RenderMeshCache, Vec<Polygon>)],
+        camera: &Camera,
+        scene: &Scene,
+        canvas: &mut Canvas,
+    ) {
+        // (индекс модели, полигон, глубина центра полигона вдоль направления камеры)
+        let camera_position = camera.get_position();
+        let camera_direction = Vec3::from(camera.get_direction());
+        let mut queue: Vec<(usize, &Polygon, f32)> = Vec::new();
+        for (model_index, (_, _, geometry, polygons)) in models.iter().enumerate() {
+            for polygon in polygons {
+                let center: Vec<Point3> = polygon
+                    .get_mesh_vertex_index_iter()
+                    .map(|index| geometry.global_vertexes[index])
+                    .collect();
+                let center = utils::calculate_center(&center);
+                let depth = (center - camera_position).dot(camera_direction);
+                queue.push((model_index, polygon, depth));
+            }
+        }
+        queue.sort_by(|a, b| b.2.total_cmp(&a.2));
 
-    // Вычисляем направление линии
-    let direction = (screen_point2 - screen_point1).normalized();
+        // подряд идущие полигоны одной модели отрисовываются за один вызов шейдера
+        let mut start = 0;
+        while start < queue.len() {
+            let model_index = queue[start].0;
+            let mut end = start;
+            while end < queue.len() && queue[end].0 == model_index {
+                end += 1;
+            }
 
-    // Удлиняем линию для лучшей видимости
-    let extension_length = 500.0;
-    let extended_start = screen_point1 - direction * extension_length;
-    let extended_end = screen_point2 + direction * extension_length;
+            let (scene_index, model, geometry, _) = models[model_index];
+            let polygons: Vec<Polygon> = queue[start..end]
+                .iter()
+                .map(|&(_, polygon, _)| polygon.clone())
+                .collect();
+            let opacity = if model.material.is_transparent() {
+                model.material.opacity.clamp(0.0, 1.0)
+            } else {
+                1.0
+            };
+            canvas.set_opacity(opacity);
+            canvas.set_current_id(Some(*scene_index));
+            self.shade_solid(model, geometry, &polygons, scene, camera, canvas);
 
-    let orange = Color32::from_rgb(255, 165, 0);
-    canvas.draw_sharp_line(extended_start, extended_end, orange);
+            start = end;
+        }
 
-    canvas.circle_filled(screen_point1, 4.0, Color32::GREEN);
-    canvas.circle_filled(screen_point2, 4.0, Color32::BLUE);
-}
+        canvas.set_opacity(1.0);
+        canvas.set_current_id(None);
+    }
 
-fn draw_lights(
-    lights: &Vec<LightSource>,
-    global_to_screen_transform: Transform3D,
-    canvas: &mut Canvas,
-) {
-    for light in lights {
-        let light_pos = light.position.apply_transform(global_to_screen_transform);
-        if let Ok(light_pos) = light_pos {
-            let pos = Pos2::new(light_pos.x, light_pos.y);
-            let radius = utils::lerp_float(6.0, 1.0, (light_pos.z + 1.0) / 2.0);
-            if pos.x < canvas.width() as f32 && pos.y < canvas.height() as f32 {
-                canvas.circle_filled(pos, radius, light.color);
-            }
-        } else {
-            #[cfg(debug_assertions)]
-            eprintln!(
-                "Warning: не удалось вывести свет на экран из-за ошибки: {}",
-                light_pos.unwrap_err()
+    /// Заполнить z-буфер гранями всех моделей сцены, не меняя цвета пикселей.
+    fn depth_prepass(&self, scene: &Scene, camera: &Camera, canvas: &mut Canvas) {
+        let shader = depth_shader::DepthShader::new();
+        let global_to_screen_transform =
+            camera.global_to_screen_transform(self.projection_type, canvas);
+        // статистика вспомогательного прохода не учитывается
+        let stats = &mut RenderStats::default();
+        // проход нужен только ради записи глубины
+        let depth_write = canvas.depth_write();
+        canvas.set_depth_write(true);
+        for model in scene
+            .models
+            .iter()
+            .filter(|model| self.renders_model(model) && model.flags.depth_write)
+        {
+            let model = self.model_geometry(model, camera, canvas, stats);
+            let model = model.as_ref();
+            let geometry = RenderMeshCache::new(
+                &model.mesh,
+                global_to_screen_transform,
+                self.projection_type,
             );
+            let polygons = self.culled_polygons(model, &geometry, camera, stats);
+            shader.shade_model(model, &geometry, &polygons, &scene.lights, canvas);
         }
+        canvas.set_depth_write(depth_write);
     }
-}
 
-#[cfg(test)]
-mod render_tests {
-    use crate::HVec3;
+    /// Заполнить буферы идентификаторов и нормалей цели рендера.
+    fn geometry_pass(&self, scene: &Scene, camera: &Camera, target: &mut RenderTarget) {
+        self.write_geometry_buffers(scene, camera, target);
+        // холст уже перевёрнут в конце рендера
+        target.invert_geometry_y();
+    }
 
-    use super::*;
+    /// Заполнить буферы идентификаторов и нормалей цели рендера в координатах холста
+    /// до переворота.
+    ///
+    /// Возвращает **глобальные** координаты видимых точек поверхностей, `None` там,
+    /// где моделей нет.
+    fn write_geometry_buffers(
+        &self,
+        scene: &Scene,
+        camera: &Camera,
+        target: &mut RenderTarget,
+    ) -> Vec<Option<Point3>> {
+        target.clear_geometry_buffers();
+        let mut positions = vec![None; target.width() * target.height()];
+        if target.width() == 0 || target.height() == 0 {
+            return positions;
+        }
 
-    const TOLERANCE: f32 = 1e-6;
+        let mut depth = vec![f32::MIN; target.width() * target.height()];
+        let global_to_screen_transform =
+            camera.global_to_screen_transform(self.projection_type, target.canvas());
+        // статистика вспомогательного прохода не учитывается
+        let stats = &mut RenderStats::default();
+        for (model_id, model) in scene.models.iter().enumerate() {
+            if !self.renders_model(model) {
+                continue;
+            }
+            let model = self.model_geometry(model, camera, target.canvas(), stats);
+            let model = model.as_ref();
+            let geometry = RenderMeshCache::new(
+                &model.mesh,
+                global_to_screen_transform,
+                self.projection_type,
+            );
+            let polygons = self.culled_polygons(model, &geometry, camera, stats);
+            geometry_pass::write_model_geometry(
+                &geometry,
+                model_id as u32,
+                &polygons,
+                &mut depth,
+                &mut positions,
+                target,
+            );
+        }
+        positions
+    }
 
-    fn assert_vecs(got: Vec3, expected: Vec3, tolerance: f32) {
-        assert!(
-            got.approx_equal(expected, tolerance),
-            "ожидался вектор {:?}, но получен вектор {:?}, одна из координат которого отличается более чем на {}",
-            expected,
-            got,
-            tolerance
-        );
+    /// Добавить пользовательскую плоскость отсечения, см. [`SceneRenderer::clip_planes`].
+    pub fn add_clip_plane(&mut self, plane: Plane) {
+        self.clip_planes.push(plane);
     }
 
-    fn assert_points(got: Point3, expected: Point3, tolerance: f32) {
-        assert!(
-            got.approx_equal(expected, tolerance),
-            "ожидалась точка {:?}, но получена {:?}, одна из координат которой отличается более чем на {}",
-            expected,
-            got,
-            tolerance
-        );
+    /// Убрать все пользовательские плоскости отсечения.
+    pub fn clear_clip_planes(&mut self) {
+        self.clip_planes.clear();
     }
 
-    fn assert_hvecs(got: HVec3, expected: HVec3, tolerance: f32) {
-        assert!(
-            got.approx_equal(expected, tolerance),
-            "ожидался вектор {:?}, но получен вектор {:?}, одна из координат которого отличается более чем на {}",
-            expected,
-            got,
-            tolerance
-        );
+    /// Наложить на кадр, нарисованный по частям через [`SceneRenderer::render`] с областью
+    /// отсечения холста, эффекты, которым нужен весь кадр: при рендере с областью отсечения
+    /// они пропускаются.
+    ///
+    /// Вызывается один раз после отрисовки последней части, как это делает [`crate::RenderJob`].
+    /// После этого кадр совпадает с нарисованным за один раз.
+    pub fn finish_tiled_frame(&self, scene: &Scene, canvas: &mut Canvas) {
+        let scissor = canvas.scissor();
+        canvas.set_scissor(None);
+        if self.debug_view == DebugView::None && self.bloom.enabled {
+            // свечение накладывается до переворота кадра, как в `render`
+            canvas.invert_y();
+            self.apply_bloom(scene, &self.frame_camera(scene), canvas);
+            canvas.invert_y();
+        }
+        if let Some(output) = &self.palette_output
+            && output.dither == DitherMode::FloydSteinberg
+        {
+            canvas.quantize(output);
+        }
+        canvas.set_scissor(scissor);
     }
 
-    #[test]
-    fn test_global_to_screen_transform_1() {
-        let camera = Camera::default();
-        let canvas = Canvas::new(900, 600);
-        let transform = camera.global_to_screen_transform(ProjectionType::Perspective, &canvas);
+    /// Камера, через которую рисуется текущий кадр.
+    ///
+    /// Если включена автоматическая подстройка, то плоскости отсечения сжимаются
+    /// до диапазона глубины видимых моделей сцены.
+    pub fn frame_camera(&self, scene: &Scene) -> Camera {
+        self.auto_clip_planes.apply(scene, &scene.camera)
+    }
+
+    /// Полигоны модели после отсечения граней по её режиму [`Model::cull_mode`] (если включено)
+    /// и граней вне камеры.
+    ///
+    /// Количество отброшенных полигонов добавляется в `stats`.
+    fn culled_polygons(
+        &self,
+        model: &Model,
+        geometry: &RenderMeshCache,
+        camera: &Camera,
+        stats: &mut RenderStats,
+    ) -> Vec<Polygon> {
+        let cull_mode = model.cull_mode();
+        let polygons = if self.backface_culling && cull_mode != CullMode::None {
+            // только видимые
+            self.model_face_culling(camera, model, geometry, cull_mode)
+        } else {
+            // все
+            model.mesh.get_polygon_iter().cloned().collect()
+        };
+        stats.polygons_backface_culled += model.mesh.polygon_count() - polygons.len();
+
+        let front_facing = polygons.len();
+        let polygons = self.model_view_culling(geometry, camera, polygons);
+        stats.polygons_view_culled += front_facing - polygons.len();
+        polygons
+    }
+
+    /// Геометрия модели в том виде, в котором она отрисовывается в этом кадре.
+    ///
+    /// Учитывает уровень детализации, деформацию, поворот billboard'ов к камере, шейдер вершин,
+    /// секущий параллелепипед, пользовательские плоскости отсечения и ближнюю плоскость камеры.
+    /// Количество полученных и обрезанных полигонов добавляется в `stats`.
+    fn model_geometry<'a>(
+        &self,
+        model: &'a Model,
+        camera: &Camera,
+        canvas: &Canvas,
+        stats: &mut RenderStats,
+    ) -> Cow<'a, Model> {
+        let lod = self
+            .auto_lod
+            .select_level(model, camera, self.projection_type, canvas);
+        let mut model = model.render_geometry_at_lod(lod);
+        if let Some(billboard) = model.billboard {
+            // при параллельной проекции модель поворачивается против направления проецирования
+            let to_camera = match camera.projection_direction(self.projection_type) {
+                Some(direction) => -Vec3::from(direction),
+                None => camera.get_position() - model.get_position(),
+            };
+            let frame = billboard.face_camera(&model.mesh.local_frame, to_camera);
+            model.to_mut().mesh.local_frame = frame;
+        }
+        if let Some(vertex_shader) = &self.vertex_shader {
+            let mesh = vertex_shader.apply(&model.mesh, self.shader_time);
+            model.to_mut().mesh = mesh;
+        }
+        let model = self.clip_by_user_planes(model);
+        let (model, clipped_count) = self.clip_by_near_plane(model, camera);
+        stats.polygons_submitted += model.mesh.polygon_count();
+        stats.polygons_clipped += clipped_count;
+        model
+    }
+
+    /// Силуэтные рёбра модели отрезками стиля `style`.
+    ///
+    /// При параллельных проекциях силуэт строится по направлению проецирования,
+    /// при перспективной - по направлениям из камеры на каждый полигон.
+    fn model_silhouette(&self, model: &Model, camera: &Camera, style: LineStyle) -> Vec<Polyline3> {
+        let edges = match camera.projection_direction(self.projection_type) {
+            Some(direction) => model.mesh.silhouette_edges(Vec3::from(direction)),
+            None => model.mesh.silhouette_edges_from(camera.get_position()),
+        };
+        edges
+            .into_iter()
+            .map(|(start, end)| {
+                let mut segment = Polyline3::new(start, style);
+                segment.push(end);
+                segment
+            })
+            .collect()
+    }
+
+    /// Отсечение геометрии модели пользовательскими плоскостями [`SceneRenderer::clip_planes`].
+    ///
+    /// Если вся модель со стороны нормали у всех плоскостей, то она не копируется.
+    fn clip_by_user_planes<'a>(&self, model: Cow<'a, Model>) -> Cow<'a, Model> {
+        let crossing: Vec<Plane> = self
+            .clip_planes
+            .iter()
+            .filter(|plane| {
+                let normal = Vec3::from(plane.normal);
+                model
+                    .mesh
+                    .get_global_vertex_iter()
+                    .any(|vertex| (vertex - plane.origin).dot(normal) < 0.0)
+            })
+            .copied()
+            .collect();
+        if crossing.is_empty() {
+            return model;
+        }
+
+        let mut clipped = model.into_owned();
+        clipped.mesh = clipped.mesh.clip_by_planes(&crossing);
+        Cow::Owned(clipped)
+    }
+
+    /// Отсечение геометрии модели ближней плоскостью камеры при перспективной проекции.
+    ///
+    /// Вершины позади камеры после перспективной проекции получают `w <= 0` и не могут быть
+    /// корректно спроецированы, поэтому пересекающие ближнюю плоскость полигоны обрезаются
+    /// заранее. Если вся модель перед камерой, то она не копируется.
+    ///
+    /// Также возвращает количество полигонов, пересекавших ближнюю плоскость.
+    fn clip_by_near_plane<'a>(
+        &self,
+        model: Cow<'a, Model>,
+        camera: &Camera,
+    ) -> (Cow<'a, Model>, usize) {
+        if self.projection_type != ProjectionType::Perspective {
+            return (model, 0);
+        }
+
+        let plane = camera.near_clip_plane();
+        let normal = Vec3::from(plane.normal);
+        let in_front: Vec<bool> = model
+            .mesh
+            .get_global_vertex_iter()
+            .map(|vertex| (vertex - plane.origin).dot(normal) >= 0.0)
+            .collect();
+        if in_front.iter().all(|&is_in_front| is_in_front) {
+            return (model, 0);
+        }
+
+        let crossing_count = model
+            .mesh
+            .get_polygon_iter()
+            .filter(|polygon| {
+                let mut indexes = polygon.get_mesh_vertex_index_iter();
+                let first = indexes.next().map(|index| in_front[index]);
+                indexes.any(|index| Some(in_front[index]) != first)
+            })
+            .count();
+
+        let mut clipped = model.into_owned();
+        clipped.mesh = clipped.mesh.clip_by_plane(&plane);
+        (Cow::Owned(clipped), crossing_count)
+    }
+
+    /// Отсечение граней модели
+    ///
+    /// Возвращает вектор полигонов без граней, отсекаемых режимом `cull_mode`.
+    /// Грани проверяются по уже преобразованной и обрезанной геометрии `geometry`,
+    /// вырожденные грани отсекаются всегда.
+    fn model_face_culling(
+        &self,
+        camera: &Camera,
+        model: &Model,
+        geometry: &RenderMeshCache,
+        cull_mode: CullMode,
+    ) -> Vec<Polygon> {
+        // при параллельных проекциях направление одно для всех полигонов
+        let projection_direction = camera.projection_direction(self.projection_type);
+        model
+            .mesh
+            .get_polygon_iter()
+            .filter(|polygon| {
+                match self::polygon_facing(polygon, geometry, camera, projection_direction) {
+                    Some(front_facing) => front_facing == (cull_mode == CullMode::Back),
+                    None => false,
+                }
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Отсечение полигонов, которые находятся за границами near и far камеры
+    fn model_view_culling(
+        &self,
+        geometry: &RenderMeshCache,
+        camera: &Camera,
+        polygons: Vec<Polygon>,
+    ) -> Vec<Polygon> {
+        // проекция вершин на экран
+        let projected_vertexes = &geometry.screen_vertexes;
+        // глубина ближней и дальней плоскостей в z-буфере
+        let (near, far) = match self.projection_type {
+            ProjectionType::Perspective => {
+                (1.0 / camera.get_near_plane(), 1.0 / camera.get_far_plane())
+            }
+            _ => (1.0, -1.0),
+        };
+
+        let mut res = Vec::new();
+        for polygon in polygons {
+            // если все вершины за ближней или все за дальней плоскостью, то исключаем полигон
+            let is_before_near = polygon
+                .get_mesh_vertex_index_iter()
+                .all(|index| near < projected_vertexes[index].z);
+            let is_after_far = polygon
+                .get_mesh_vertex_index_iter()
+                .all(|index| projected_vertexes[index].z < far);
+            if !is_before_near && !is_after_far {
+                res.push(polygon);
+            }
+        }
+
+        res
+    }
+}
+
+// --------------------------------------------------
+// Вспомогательные методы
+// --------------------------------------------------
+
+/// Преобразует глобальные координаты точки в координаты экрана.
+///
+/// Возвращает `None`, если точку нельзя спроецировать (например, она позади камеры).
+fn project_point(point: Point3, view_proj_matrix: Transform3D) -> Option<Pos2> {
+    let proj_point: Point3 = point.apply_transform(view_proj_matrix).ok()?;
+    Some(Pos2::new(proj_point.x, proj_point.y))
+}
+
+/// Направлена ли грань к камере.
+///
+/// Нормаль грани считается по её преобразованным вершинам (см. [`RenderMeshCache::polygon_normal`])
+/// и сравнивается с направлением взгляда на грань: при перспективной проекции это вектор
+/// от камеры к центру грани, при параллельной - общее направление проецирования
+/// `projection_direction`. Возвращает `None` для вырожденной грани.
+fn polygon_facing(
+    polygon: &Polygon,
+    geometry: &RenderMeshCache,
+    camera: &Camera,
+    projection_direction: Option<UVec3>,
+) -> Option<bool> {
+    let normal = geometry.polygon_normal(polygon)?;
+    let view_direction = match projection_direction {
+        Some(direction) => Vec3::from(direction),
+        None => geometry.polygon_center(polygon) - camera.get_position(),
+    };
+
+    // нормаль направлена в сторону камеры
+    Some(Vec3::from(normal).dot(view_direction) < 0.0)
+}
+
+/// Отрисовка пользовательской оси для вращения
+fn draw_custom_axis_line(
+    canvas: &mut Canvas,
+    global_to_screen_transform: Transform3D,
+    point1: Point3,
+    point2: Point3,
+) {
+    // Проецируем точки в 2D используя нашу систему проекций
+    let (Some(screen_point1), Some(screen_point2)) = (
+        project_point(point1, global_to_screen_transform),
+        project_point(point2, global_to_screen_transform),
+    ) else {
+        return;
+    };
+
+    // Вычисляем направление линии
+    let direction = (screen_point2 - screen_point1).normalized();
+
+    // Удлиняем линию для лучшей видимости
+    let extension_length = 500.0;
+    let extended_start = screen_point1 - direction * extension_length;
+    let extended_end = screen_point2 + direction * extension_length;
+
+    let orange = Color32::from_rgb(255, 165, 0);
+    canvas.draw_sharp_line(extended_start, extended_end, orange);
+
+    canvas.circle_filled(screen_point1, 4.0, Color32::GREEN);
+    canvas.circle_filled(screen_point2, 4.0, Color32::BLUE);
+}
+
+#[cfg(test)]
+mod render_tests {
+    use crate::{Background, EnvironmentLight, HVec3};
+
+    use super::*;
+
+    const TOLERANCE: f32 = 1e-6;
+
+    fn assert_vecs(got: Vec3, expected: Vec3, tolerance: f32) {
+        assert!(
+            got.approx_equal(expected, tolerance),
+            "ожидался вектор {:?}, но получен вектор {:?}, одна из координат которого отличается более чем на {}",
+            expected,
+            got,
+            tolerance
+        );
+    }
+
+    fn assert_points(got: Point3, expected: Point3, tolerance: f32) {
+        assert!(
+            got.approx_equal(expected, tolerance),
+            "ожидалась точка {:?}, но получена {:?}, одна из координат которой отличается более чем на {}",
+            expected,
+            got,
+            tolerance
+        );
+    }
+
+    fn assert_hvecs(got: HVec3, expected: HVec3, tolerance: f32) {
+        assert!(
+            got.approx_equal(expected, tolerance),
+            "ожидался вектор {:?}, но получен вектор {:?}, одна из координат которого отличается более чем на {}",
+            expected,
+            got,
+            tolerance
+        );
+    }
+
+    #[test]
+    fn test_global_to_screen_transform_1() {
+        let camera = Camera::default();
+        let canvas = Canvas::new(900, 600);
+        let transform = camera.global_to_screen_transform(ProjectionType::Perspective, &canvas);
 
         // точка по центру камеры
         let camera_pos = camera.get_position();
@@ -496,4 +1216,1361 @@ mod render_tests {
         assert!(proj_point.x > canvas.width() as f32 / 2.0 + TOLERANCE);
         assert!(proj_point.y > canvas.height() as f32 / 2.0 + TOLERANCE);
     }
+
+    /// Сцена из двух кубов, стоящих один за другим на оси взгляда камеры по умолчанию.
+    fn two_cubes_scene() -> Scene {
+        let mut front = Model::from_mesh(crate::Mesh::hexahedron());
+        front.set_position(Point3::new(0.0, 0.0, -3.0));
+        let mut back = Model::from_mesh(crate::Mesh::hexahedron());
+        back.set_position(Point3::new(0.0, 0.0, 3.0));
+
+        Scene {
+            models: vec![front, back],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_near_plane_clipping_removes_points_behind_camera() {
+        let camera = Camera::default();
+        let canvas = Canvas::new(300, 200);
+        let renderer = SceneRenderer {
+            projection_type: ProjectionType::Perspective,
+            ..Default::default()
+        };
+
+        // куб вокруг камеры: часть вершин позади неё
+        let mut model = Model::from_mesh(crate::Mesh::hexahedron());
+        model.set_position(camera.get_position());
+        model.uniform_scale(4.0);
+
+        let (clipped, crossing_count) = renderer.clip_by_near_plane(Cow::Borrowed(&model), &camera);
+        assert!(
+            matches!(clipped, Cow::Owned(_)),
+            "модель, пересекающая ближнюю плоскость, должна быть обрезана"
+        );
+        assert!(crossing_count > 0);
+        assert!(clipped.mesh.polygon_count() > 0);
+
+        let transform = camera.global_to_screen_transform(ProjectionType::Perspective, &canvas);
+        for vertex in clipped.mesh.get_global_vertex_iter() {
+            let hvec = HVec3::from(vertex).apply_transform(transform);
+            assert!(
+                hvec.w > 0.0,
+                "после отсечения вершина {} имеет w = {} <= 0",
+                vertex,
+                hvec.w
+            );
+        }
+
+        // модель целиком перед камерой не копируется
+        let mut model = Model::from_mesh(crate::Mesh::hexahedron());
+        model.set_position(camera.get_position() + camera.forward() * 10.0);
+        let (clipped, crossing_count) = renderer.clip_by_near_plane(Cow::Borrowed(&model), &camera);
+        assert!(matches!(clipped, Cow::Borrowed(_)));
+        assert_eq!(crossing_count, 0);
+    }
+
+    #[test]
+    fn test_render_to_target_fills_id_and_normal_buffers() {
+        let scene = two_cubes_scene();
+        let mut target = RenderTarget::new(300, 200);
+        let renderer = SceneRenderer {
+            render_solid: true,
+            ..Default::default()
+        };
+        renderer.render_to_target(&scene, &mut target, false, Point3::zero(), Point3::zero());
+
+        // в центре экрана оба куба, в буфер попадает ближайший
+        let id = target.id_at(150, 100);
+        assert!(id.is_some(), "в центре экрана должна быть модель");
+        let normal = target.normal_at(150, 100).unwrap();
+        assert!(
+            (normal.length() - 1.0).abs() < 1e-3,
+            "нормаль {:?} должна быть единичной",
+            normal
+        );
+
+        // углы экрана пусты
+        assert_eq!(target.id_at(0, 0), None);
+        assert_eq!(target.id_at(299, 199), None);
+
+        // скрытые модели не попадают в буфер идентификаторов
+        let mut hidden = two_cubes_scene();
+        for model in &mut hidden.models {
+            model.flags.visible = false;
+        }
+        renderer.render_to_target(&hidden, &mut target, false, Point3::zero(), Point3::zero());
+        assert_eq!(target.id_at(150, 100), None);
+    }
+
+    #[test]
+    fn test_render_degenerate_canvas() {
+        let scene = two_cubes_scene();
+        let mut canvas = Canvas::new(0, 0);
+        let renderer = SceneRenderer {
+            render_solid: true,
+            ..Default::default()
+        };
+        // не должно паниковать или зависать на вырожденной матрице
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        assert_eq!(canvas.size(), [0, 0]);
+    }
+
+    #[test]
+    fn test_overdraw_counts_fragments() {
+        let scene = two_cubes_scene();
+        let mut canvas = Canvas::new(300, 200);
+        let renderer = SceneRenderer {
+            render_wireframe: false,
+            depth_mode: DepthMode::None,
+            debug_view: DebugView::Overdraw,
+            ..Default::default()
+        };
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+
+        // по центру экрана оба куба: передняя и задняя грань у каждого
+        let center_count = canvas.fragment_count(150, 100);
+        assert!(
+            center_count >= 4,
+            "в центре ожидалось хотя бы 4 фрагмента, но получено {}",
+            center_count
+        );
+        // угол экрана пуст
+        assert_eq!(canvas.fragment_count(0, 0), 0);
+        assert_eq!(canvas[(0, 0)], Color32::BLACK);
+        assert_ne!(canvas[(150, 100)], Color32::BLACK);
+    }
+
+    #[test]
+    fn test_overdraw_without_z_buffer_counts_more() {
+        let scene = two_cubes_scene();
+        let mut with_z = Canvas::new(300, 200);
+        let mut without_z = Canvas::new(300, 200);
+        let mut renderer = SceneRenderer {
+            render_wireframe: false,
+            debug_view: DebugView::Overdraw,
+            ..Default::default()
+        };
+        renderer.render(&scene, &mut with_z, false, Point3::zero(), Point3::zero());
+        renderer.depth_mode = DepthMode::None;
+        renderer.render(&scene, &mut without_z, false, Point3::zero(), Point3::zero());
+
+        assert!(
+            without_z.fragment_count(150, 100) >= with_z.fragment_count(150, 100),
+            "без z-буфера фрагментов не может быть меньше"
+        );
+    }
+
+    #[test]
+    fn test_debug_view_depth_nearer_is_brighter() {
+        let mut near = Model::from_mesh(crate::Mesh::hexahedron());
+        near.set_position(Point3::new(-0.7, 0.0, -4.0));
+        let mut far = Model::from_mesh(crate::Mesh::hexahedron());
+        far.set_position(Point3::new(0.7, 0.0, 4.0));
+        let scene = Scene {
+            models: vec![near, far],
+            ..Default::default()
+        };
+        let mut canvas = Canvas::new(300, 200);
+        let renderer = SceneRenderer {
+            render_wireframe: false,
+            debug_view: DebugView::Depth,
+            grid_options: GridOptions {
+                show_axes: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+
+        // пиксели по центрам передних граней кубов
+        let transform = scene
+            .camera
+            .global_to_screen_transform(renderer.projection_type, &canvas);
+        let pixel = |point: Point3| {
+            let screen = point.apply_transform(transform).unwrap();
+            canvas[(screen.x as usize, screen.y as usize)]
+        };
+        let near_pixel = pixel(Point3::new(-0.7, 0.0, -4.5));
+        let far_pixel = pixel(Point3::new(0.7, 0.0, 3.5));
+        assert!(
+            near_pixel.r() > far_pixel.r(),
+            "ближний куб {:?} должен быть светлее дальнего {:?}",
+            near_pixel,
+            far_pixel
+        );
+        assert_eq!(near_pixel.r(), near_pixel.g());
+        assert_ne!(far_pixel, Color32::BLACK);
+        assert_eq!(canvas[(0, 0)], Color32::BLACK);
+    }
+
+    #[test]
+    fn test_debug_view_texture_coords_and_normals() {
+        let mut cube = Model::from_mesh(crate::Mesh::hexahedron());
+        cube.uniform_scale(4.0);
+        let mut scene = Scene {
+            models: vec![cube],
+            ..Default::default()
+        };
+        let mut renderer = SceneRenderer {
+            render_wireframe: false,
+            render_solid: false,
+            debug_view: DebugView::TextureCoords,
+            grid_options: GridOptions {
+                show_axes: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // по центру передней грани UV равны примерно (0.5, 0.5)
+        let mut canvas = Canvas::new(300, 200);
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        let center = canvas[(150, 100)];
+        assert!(center.r().abs_diff(128) <= 8, "{:?}", center);
+        assert!(center.g().abs_diff(128) <= 8, "{:?}", center);
+        assert_eq!(center.b(), 0);
+
+        // передняя грань смотрит на камеру: нормаль (0, 0, -1)
+        renderer.debug_view = DebugView::Normals;
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        let center = canvas[(150, 100)];
+        assert!(center.r().abs_diff(128) <= 1, "{:?}", center);
+        assert!(center.g().abs_diff(128) <= 1, "{:?}", center);
+        assert_eq!(center.b(), 0);
+
+        // без UV-координат фрагменты отмечаются отдельным цветом
+        let mesh = &scene.models[0].mesh;
+        let stripped =
+            mesh.with_vertex_attributes(mesh.get_local_vertex_iter().collect(), None, None);
+        scene.models[0].mesh = stripped;
+        renderer.debug_view = DebugView::TextureCoords;
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        assert_eq!(canvas[(150, 100)], DEBUG_MISSING_COLOR);
+    }
+
+    /// Количество пикселей указанного цвета на холсте.
+    fn count_pixels(canvas: &Canvas, color: Color32) -> usize {
+        let mut count = 0;
+        for y in 0..canvas.height() {
+            for x in 0..canvas.width() {
+                if canvas[(x, y)] == color {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    #[test]
+    fn test_hidden_line_removal_hides_back_edges() {
+        let mut cube = Model::from_mesh(crate::Mesh::hexahedron());
+        cube.uniform_scale(4.0);
+        cube.rotate_local_y(30.0_f32.to_radians());
+        cube.rotate_local_x(30.0_f32.to_radians());
+        let wireframe_color = utils::opposite_color(cube.material.color);
+        let scene = Scene {
+            models: vec![cube],
+            ..Default::default()
+        };
+
+        let mut renderer = SceneRenderer::default();
+        let mut all_edges = Canvas::new(300, 200);
+        renderer.render(
+            &scene,
+            &mut all_edges,
+            false,
+            Point3::zero(),
+            Point3::zero(),
+        );
+        renderer.hidden_line_removal = true;
+        let mut visible_edges = Canvas::new(300, 200);
+        renderer.render(
+            &scene,
+            &mut visible_edges,
+            false,
+            Point3::zero(),
+            Point3::zero(),
+        );
+
+        let all = count_pixels(&all_edges, wireframe_color);
+        let visible = count_pixels(&visible_edges, wireframe_color);
+        assert!(visible > 0, "видимые рёбра должны быть нарисованы");
+        assert!(
+            visible < all,
+            "невидимые рёбра должны быть скрыты: {} пикселей из {}",
+            visible,
+            all
+        );
+    }
+
+    #[test]
+    fn test_hidden_line_removal_far_from_camera() {
+        // модель далеко от камеры: глубины 1 / w вершин малы и близки друг к другу
+        let camera = Camera::new(
+            Point3::new(0.0, 0.0, -10.0),
+            UVec3::forward(),
+            UVec3::up(),
+            60.0_f32.to_radians(),
+            16.0 / 9.0,
+            1.0,
+            10000.0,
+        );
+        let mut cube = Model::from_mesh(crate::Mesh::hexahedron());
+        cube.set_position(camera.get_position() + camera.forward() * 1000.0);
+        cube.uniform_scale(300.0);
+        cube.rotate_local_y(30.0_f32.to_radians());
+        cube.rotate_local_x(30.0_f32.to_radians());
+        let wireframe_color = utils::opposite_color(cube.material.color);
+        let scene = Scene {
+            models: vec![cube],
+            camera,
+            ..Default::default()
+        };
+
+        let mut renderer = SceneRenderer {
+            projection_type: ProjectionType::Perspective,
+            ..Default::default()
+        };
+        renderer.grid_options.show_grid = false;
+        renderer.grid_options.show_axes = false;
+        let mut all_edges = Canvas::new(300, 200);
+        renderer.render(
+            &scene,
+            &mut all_edges,
+            false,
+            Point3::zero(),
+            Point3::zero(),
+        );
+        renderer.hidden_line_removal = true;
+        let mut visible_edges = Canvas::new(300, 200);
+        renderer.render(
+            &scene,
+            &mut visible_edges,
+            false,
+            Point3::zero(),
+            Point3::zero(),
+        );
+
+        let all = count_pixels(&all_edges, wireframe_color);
+        let visible = count_pixels(&visible_edges, wireframe_color);
+        assert!(visible > 0, "видимые рёбра должны быть нарисованы");
+        assert!(
+            visible * 10 < all * 9,
+            "невидимые рёбра должны быть скрыты: {} пикселей из {}",
+            visible,
+            all
+        );
+    }
+
+    #[test]
+    fn test_grid_toggle() {
+        let scene = Scene::default();
+        let mut renderer = SceneRenderer {
+            render_wireframe: false,
+            ..Default::default()
+        };
+        renderer.grid_options.grid_color = Color32::YELLOW;
+        renderer.grid_options.major_every = 0;
+
+        let mut canvas = Canvas::new(300, 200);
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        assert_eq!(count_pixels(&canvas, Color32::YELLOW), 0, "сетка выключена");
+
+        renderer.grid_options.show_grid = true;
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        assert!(count_pixels(&canvas, Color32::YELLOW) > 0, "сетка включена");
+
+        renderer.grid_options.show_axes = false;
+        renderer.grid_options.show_grid = false;
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        assert_eq!(count_pixels(&canvas, Color32::RED), 0, "оси выключены");
+    }
+
+    #[test]
+    fn test_model_flags() {
+        let mut cube = Model::from_mesh(crate::Mesh::hexahedron());
+        cube.material.color = Color32::WHITE;
+        let mut scene = Scene {
+            models: vec![cube],
+            ..Default::default()
+        };
+        let renderer = SceneRenderer {
+            render_wireframe: false,
+            render_solid: true,
+            selection_color: Color32::YELLOW,
+            ..Default::default()
+        };
+        let mut canvas = Canvas::new(300, 200);
+
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        assert_eq!(canvas[(150, 100)], Color32::WHITE, "модель видна");
+
+        scene.models[0].flags.visible = false;
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        assert_eq!(
+            canvas[(150, 100)],
+            Color32::GRAY,
+            "скрытая модель не рисуется"
+        );
+
+        scene.models[0].flags.visible = true;
+        scene.models[0].flags.wireframe_only = true;
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        assert_eq!(canvas[(150, 100)], Color32::GRAY, "грани не рисуются");
+        assert!(count_pixels(&canvas, Color32::BLACK) > 0, "каркас рисуется");
+
+        scene.models[0].flags.wireframe_only = false;
+        scene.models[0].flags.selected = true;
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        assert_eq!(canvas[(150, 100)], Color32::WHITE);
+        assert!(
+            count_pixels(&canvas, Color32::YELLOW) > 0,
+            "выделение рисуется"
+        );
+    }
+
+    /// Сцена из полупрозрачного синего куба перед непрозрачным красным.
+    fn transparent_scene() -> Scene {
+        let mut scene = two_cubes_scene();
+        scene.models[0].material.color = Color32::BLUE;
+        scene.models[0].material.opacity = 0.5;
+        scene.models[1].material.color = Color32::RED;
+        scene
+    }
+
+    #[test]
+    fn test_transparent_model_blends_with_background() {
+        let scene = transparent_scene();
+        let renderer = SceneRenderer {
+            render_wireframe: false,
+            render_solid: true,
+            ..Default::default()
+        };
+        let mut canvas = Canvas::new(300, 200);
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+
+        // сквозь синий куб виден красный
+        let color = canvas[(150, 100)];
+        assert!(
+            color.r() > 0 && color.b() > 0,
+            "ожидалось смешение красного и синего, но получен {:?}",
+            color
+        );
+    }
+
+    #[test]
+    fn test_transparent_sorting_across_models() {
+        let mut scene = transparent_scene();
+        scene.models[1].material.opacity = 0.5;
+        let renderer = SceneRenderer {
+            render_wireframe: false,
+            render_solid: true,
+            ..Default::default()
+        };
+
+        let mut canvas = Canvas::new(300, 200);
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        let expected = canvas[(150, 100)];
+
+        // порядок моделей на сцене не влияет на результат
+        scene.models.reverse();
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        assert_eq!(canvas[(150, 100)], expected);
+        // ближний синий куб смешивается последним
+        assert!(expected.b() > expected.r());
+    }
+
+    #[test]
+    fn test_environment_lights_model_without_lights() {
+        let mut scene = two_cubes_scene();
+        let renderer = SceneRenderer {
+            render_wireframe: false,
+            render_solid: true,
+            shading_type: ShadingType::GouraudLambert,
+            ..Default::default()
+        };
+        let mut canvas = Canvas::new(300, 200);
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        assert_eq!(
+            canvas[(150, 100)],
+            Color32::BLACK,
+            "без источников света модель чёрная"
+        );
+
+        scene.environment = Some(EnvironmentLight::new(
+            Background::Solid(Color32::WHITE),
+            0.5,
+        ));
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        let color = canvas[(150, 100)];
+        assert!(
+            color.r() > 100 && color.r() < 160,
+            "окружение освещает модель наполовину, получен {:?}",
+            color
+        );
+    }
+
+    #[test]
+    fn test_hdr_keeps_bright_light_from_clipping() {
+        let mut scene = two_cubes_scene();
+        scene.models[0].material.color = Color32::from_rgb(200, 100, 50);
+        for _ in 0..2 {
+            scene.lights.push(LightSource::new(
+                Point3::new(0.0, 0.0, -10.0),
+                Color32::WHITE,
+                2.0,
+            ));
+        }
+        let mut renderer = SceneRenderer {
+            render_wireframe: false,
+            render_solid: true,
+            shading_type: ShadingType::GouraudLambert,
+            ..Default::default()
+        };
+        let mut canvas = Canvas::new(300, 200);
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        let clipped = canvas[(150, 100)];
+        assert!(
+            clipped.r().abs_diff(200) <= 2 && clipped.g().abs_diff(100) <= 2,
+            "без HDR освещённость обрезается до 1, получен {:?}",
+            clipped
+        );
+        assert!(canvas.hdr_buffer().is_none());
+
+        renderer.hdr = Some(crate::HdrOptions {
+            tone_mapping: crate::ToneMapping::Reinhard,
+            exposure: 1.0,
+            gamma: 1.0,
+        });
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        let mapped = canvas[(150, 100)];
+        assert!(
+            mapped.r() > mapped.g() && mapped.g() > mapped.b() && mapped.b() > clipped.b(),
+            "яркий свет сжимается без потери оттенка, получен {:?}",
+            mapped
+        );
+        let hdr = canvas.hdr_buffer().expect("HDR-буфер включён")[100 * 300 + 150];
+        assert!(hdr.x > 1.0, "HDR-цвет не ограничен 1, получен {:?}", hdr);
+    }
+
+    #[test]
+    fn test_disabled_lights_are_skipped() {
+        let mut scene = two_cubes_scene();
+        let renderer = SceneRenderer {
+            render_wireframe: false,
+            render_solid: true,
+            shading_type: ShadingType::GouraudLambert,
+            ..Default::default()
+        };
+        let mut canvas = Canvas::new(300, 200);
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        let unlit = canvas[(150, 100)];
+
+        scene.lights.push(
+            LightSource::new(Point3::new(0.0, 0.0, -10.0), Color32::WHITE, 1.0)
+                .with_group("студия"),
+        );
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        assert_ne!(canvas[(150, 100)], unlit);
+
+        scene.set_light_group_enabled("студия", false);
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        assert_eq!(
+            canvas[(150, 100)],
+            unlit,
+            "выключенный свет не освещает модель"
+        );
+    }
+
+    #[test]
+    fn test_layer_mask_filters_models_and_lights() {
+        const HELPERS: LayerMask = LayerMask::layer(3);
+        let mut scene = two_cubes_scene();
+        scene.models[0].material.color = Color32::RED;
+        scene.models[0].flags.layers = HELPERS;
+        scene.models[1].material.color = Color32::BLUE;
+        let mut renderer = SceneRenderer {
+            render_wireframe: false,
+            render_solid: true,
+            ..Default::default()
+        };
+        let mut canvas = Canvas::new(300, 200);
+        let mut render = |renderer: &SceneRenderer, scene: &Scene| {
+            renderer.render(scene, &mut canvas, false, Point3::zero(), Point3::zero());
+            canvas[(150, 100)]
+        };
+
+        assert_eq!(render(&renderer, &scene), Color32::RED);
+        // без слоя вспомогательных моделей виден задний куб
+        renderer.layer_mask = !HELPERS;
+        assert_eq!(render(&renderer, &scene), Color32::BLUE);
+        renderer.layer_mask = HELPERS;
+        assert_eq!(render(&renderer, &scene), Color32::RED);
+
+        // источник из другого слоя не освещает модели
+        renderer.layer_mask = LayerMask::ALL;
+        renderer.shading_type = ShadingType::GouraudLambert;
+        let unlit = render(&renderer, &scene);
+        scene.lights.push(
+            LightSource::new(Point3::new(0.0, 0.0, -10.0), Color32::WHITE, 1.0)
+                .with_layers(HELPERS),
+        );
+        let lit = render(&renderer, &scene);
+        assert_ne!(lit, unlit);
+        renderer.layer_mask = LayerMask::DEFAULT | HELPERS;
+        assert_eq!(render(&renderer, &scene), lit);
+        scene.models[0].flags.layers = LayerMask::DEFAULT;
+        renderer.layer_mask = LayerMask::DEFAULT;
+        assert_eq!(render(&renderer, &scene), unlit);
+    }
+
+    #[test]
+    fn test_light_gizmos() {
+        let mut scene = two_cubes_scene();
+        scene.lights.push(LightSource::new(
+            Point3::new(0.5, 0.3, -5.0),
+            Color32::MAGENTA,
+            1.0,
+        ));
+        let mut renderer = SceneRenderer {
+            render_wireframe: false,
+            light_gizmos: LightGizmoOptions {
+                show_lights: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut canvas = Canvas::new(300, 200);
+        let count_light_pixels = |renderer: &SceneRenderer, canvas: &mut Canvas| {
+            renderer.render(&scene, canvas, false, Point3::zero(), Point3::zero());
+            (0..canvas.height())
+                .flat_map(|y| (0..canvas.width()).map(move |x| (x, y)))
+                .filter(|&(x, y)| canvas[(x, y)] == Color32::MAGENTA)
+                .count()
+        };
+        assert_eq!(count_light_pixels(&renderer, &mut canvas), 0);
+
+        renderer.light_gizmos.sphere_radius = 0.3;
+        let sphere = count_light_pixels(&renderer, &mut canvas);
+        assert!(sphere > 0, "сфера источника должна быть видна");
+
+        renderer.light_gizmos.show_directions = true;
+        assert!(
+            count_light_pixels(&renderer, &mut canvas) > sphere,
+            "стрелки к моделям добавляют линии"
+        );
+    }
+
+    #[test]
+    fn test_vertex_colors_replace_material_color() {
+        let mut scene = two_cubes_scene();
+        scene.models[0].material.color = Color32::RED;
+        let vertex_count = scene.models[0].mesh.vertex_count();
+        scene.models[0]
+            .mesh
+            .set_vertex_colors(vec![Color32::GREEN; vertex_count]);
+        let renderer = SceneRenderer {
+            render_wireframe: false,
+            render_solid: true,
+            ..Default::default()
+        };
+        let mut canvas = Canvas::new(300, 200);
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        assert_eq!(canvas[(150, 100)], Color32::GREEN);
+
+        scene.models[0].mesh.clear_vertex_colors();
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        assert_eq!(canvas[(150, 100)], Color32::RED);
+    }
+
+    #[test]
+    fn test_reflective_material_samples_reflection_map() {
+        let mut scene = two_cubes_scene();
+        scene.lights.push(LightSource::new(
+            Point3::new(0.0, 0.0, -10.0),
+            Color32::WHITE,
+            1.0,
+        ));
+        scene.reflection_map = Some(Background::Solid(Color32::GREEN));
+        let mut renderer = SceneRenderer {
+            render_wireframe: false,
+            render_solid: true,
+            ..Default::default()
+        };
+
+        for shading_type in [
+            ShadingType::GouraudLambert,
+            ShadingType::PhongToonShading(4),
+        ] {
+            renderer.shading_type = shading_type;
+            let mut canvas = Canvas::new(300, 200);
+            scene.models[0].material.reflectivity = 0.0;
+            renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+            let matte = canvas[(150, 100)];
+            assert!(
+                matte.g() == matte.r(),
+                "без отражений куб белый: {:?}",
+                matte
+            );
+
+            scene.models[0].material.reflectivity = 1.0;
+            renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+            assert_eq!(
+                canvas[(150, 100)],
+                Color32::GREEN,
+                "{}: зеркало отражает карту отражений",
+                shading_type
+            );
+        }
+    }
+
+    #[test]
+    fn test_render_stats() {
+        let scene = two_cubes_scene();
+        let mut canvas = Canvas::new(300, 200);
+        let renderer = SceneRenderer {
+            render_wireframe: false,
+            render_solid: true,
+            backface_culling: true,
+            ..Default::default()
+        };
+        let stats = renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+
+        assert_eq!(stats.models_rendered, 2);
+        assert_eq!(stats.polygons_submitted, 12, "у двух кубов 12 граней");
+        assert_eq!(stats.polygons_clipped, 0);
+        assert!(
+            stats.polygons_backface_culled >= 6,
+            "у куба видно не больше 3-х граней"
+        );
+        assert_eq!(
+            stats.polygons_rendered,
+            stats.polygons_submitted - stats.polygons_backface_culled - stats.polygons_view_culled
+        );
+        assert!(stats.polygons_rendered > 0);
+
+        // кубы стоят один за другим, часть фрагментов дальнего отбрасывается z-буфером
+        assert!(stats.pixels_shaded > 0);
+        assert!(stats.depth_rejections > 0);
+        assert!(stats.total_time >= stats.shading_time);
+        assert!(!stats.to_string().is_empty());
+
+        // скрытые модели не учитываются
+        let mut hidden = two_cubes_scene();
+        hidden.models[1].flags.visible = false;
+        let stats = renderer.render(&hidden, &mut canvas, false, Point3::zero(), Point3::zero());
+        assert_eq!(stats.models_rendered, 1);
+        assert_eq!(stats.polygons_submitted, 6);
+    }
+
+    #[test]
+    fn test_painter_depth_mode_orders_models() {
+        // ближний красный куб перед дальним синим, дальний рисуется последним
+        let mut scene = two_cubes_scene();
+        scene.models[0].material.color = Color32::RED;
+        scene.models[1].material.color = Color32::BLUE;
+        let mut renderer = SceneRenderer {
+            render_wireframe: false,
+            render_solid: true,
+            depth_mode: DepthMode::None,
+            ..Default::default()
+        };
+        let mut canvas = Canvas::new(300, 200);
+
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        assert_eq!(
+            canvas[(150, 100)],
+            Color32::BLUE,
+            "без упорядочивания последняя модель перекрывает остальные"
+        );
+
+        renderer.depth_mode = DepthMode::Painter;
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        assert_eq!(
+            canvas[(150, 100)],
+            Color32::RED,
+            "ближний куб должен быть виден"
+        );
+
+        renderer.depth_mode = DepthMode::ZBuffer;
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        assert_eq!(canvas[(150, 100)], Color32::RED);
+    }
+
+    #[test]
+    fn test_id_buffer_picks_visible_model() {
+        let mut scene = Scene::default();
+        let ids: Vec<_> = two_cubes_scene()
+            .models
+            .into_iter()
+            .map(|model| scene.add_model(model))
+            .collect();
+        let (front, back) = (ids[0], ids[1]);
+        // каркас белого куба рисуется чёрным
+        scene.models[0].material.color = Color32::WHITE;
+        scene.models[1].material.color = Color32::RED;
+        let mut renderer = SceneRenderer {
+            render_wireframe: false,
+            render_solid: true,
+            ..Default::default()
+        };
+        renderer.grid_options.show_grid = false;
+        renderer.grid_options.show_axes = false;
+        let mut canvas = Canvas::new(300, 200);
+
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        assert_eq!(canvas.id(150, 100), None, "буфер идентификаторов отключён");
+
+        canvas.set_id_buffer_enabled(true);
+        for depth_mode in [DepthMode::ZBuffer, DepthMode::Painter] {
+            renderer.depth_mode = depth_mode;
+            renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+            assert_eq!(canvas.id(150, 100), Some(0), "{}: ближний куб", depth_mode);
+            assert_eq!(canvas.id(0, 0), None, "{}: фон", depth_mode);
+        }
+        let picked = |canvas: &Canvas, scene: &Scene, x, y| {
+            canvas.id(x, y).and_then(|index| scene.id_at(index))
+        };
+        assert_eq!(picked(&canvas, &scene, 150, 100), Some(front));
+
+        // сквозь каркас ближнего куба виден дальний, но рёбра остаются у ближнего
+        renderer.depth_mode = DepthMode::ZBuffer;
+        scene.models[0].flags.wireframe_only = true;
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        assert_eq!(canvas.id(150, 100), Some(1), "дальний куб сквозь каркас");
+        assert_eq!(picked(&canvas, &scene, 150, 100), Some(back));
+        let edge = (0..canvas.width() * canvas.height())
+            .find(|&i| canvas.pixels()[i] == Color32::BLACK)
+            .expect("каркас ближнего куба нарисован");
+        let (x, y) = (edge % canvas.width(), edge / canvas.width());
+        assert_eq!(canvas.id(x, y), Some(0), "рёбра каркаса выбираются");
+    }
+
+    #[test]
+    fn test_fragment_shader_overrides_shading() {
+        /// Шейдер, проверяющий входные данные и закрашивающий грани одним цветом.
+        struct CheckInput;
+
+        impl FragmentShader for CheckInput {
+            fn shade(&self, input: FragmentInput) -> Color32 {
+                let (u, v) = input.uv.expect("у куба есть текстурные координаты");
+                assert!((-1e-4..=1.0 + 1e-4).contains(&u) && (-1e-4..=1.0 + 1e-4).contains(&v));
+                assert!(input.normal.is_some());
+                assert!(input.position.z.abs() <= 3.5 + 1e-3);
+                assert_eq!(input.base_color(), input.material.color);
+                Color32::GREEN
+            }
+        }
+
+        let scene = two_cubes_scene();
+        let renderer = SceneRenderer {
+            render_wireframe: false,
+            render_solid: true,
+            fragment_shader: Some(SharedFragmentShader::new(CheckInput)),
+            ..Default::default()
+        };
+        let mut canvas = Canvas::new(300, 200);
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+
+        assert_eq!(canvas[(150, 100)], Color32::GREEN);
+        assert_eq!(
+            canvas[(0, 0)],
+            Color32::GRAY,
+            "фон не закрашивается шейдером"
+        );
+        assert_eq!(renderer.clone(), renderer, "копия шейдера равна оригиналу");
+    }
+
+    #[test]
+    fn test_vertex_shader_deforms_without_mutating_mesh() {
+        /// Шейдер, сдвигающий вершины вдоль x со временем.
+        struct Slide;
+
+        impl VertexShader for Slide {
+            fn shade(&self, input: VertexInput) -> VertexOutput {
+                VertexOutput {
+                    position: input.position + Vec3::new(input.time, 0.0, 0.0),
+                    ..input.unchanged()
+                }
+            }
+        }
+
+        let scene = Scene {
+            models: vec![Model::from_mesh(crate::Mesh::hexahedron())],
+            ..Default::default()
+        };
+        let mut renderer = SceneRenderer {
+            render_wireframe: false,
+            render_solid: true,
+            vertex_shader: Some(SharedVertexShader::new(Slide)),
+            ..Default::default()
+        };
+        let mut canvas = Canvas::new(300, 200);
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        assert_ne!(canvas[(150, 100)], Color32::GRAY, "в момент 0 куб в центре");
+
+        renderer.shader_time = 100.0;
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        assert_eq!(canvas[(150, 100)], Color32::GRAY, "куб сдвинут шейдером");
+        assert_eq!(
+            scene.models[0].mesh.get_local_vertex(0),
+            crate::Mesh::hexahedron().get_local_vertex(0),
+            "Mesh модели не меняется"
+        );
+    }
+
+    #[test]
+    fn test_billboard_faces_camera() {
+        let texture = crate::Texture::new(image::DynamicImage::ImageRgb8(
+            image::RgbImage::from_pixel(2, 2, image::Rgb([255, 0, 0])),
+        ));
+        let mut billboard = Model::billboard(texture, 4.0);
+        // без поворота к камере квадрат был бы виден ребром
+        billboard.rotate_local_y(std::f32::consts::FRAC_PI_2);
+        let mut scene = Scene {
+            models: vec![billboard],
+            ..Default::default()
+        };
+        let renderer = SceneRenderer {
+            render_wireframe: false,
+            render_solid: true,
+            shading_type: ShadingType::None,
+            backface_culling: true,
+            ..Default::default()
+        };
+        let mut canvas = Canvas::new(300, 200);
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        assert_eq!(
+            canvas[(150, 100)],
+            Color32::RED,
+            "billboard повёрнут к камере"
+        );
+
+        scene.models[0].billboard = None;
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        assert_eq!(
+            canvas[(120, 100)],
+            Color32::GRAY,
+            "без billboard'а виден только край"
+        );
+    }
+
+    #[test]
+    fn test_cull_mode_and_double_sided() {
+        // треугольник, нормаль которого направлена от камеры
+        let mut mesh = crate::Mesh::from_polygons(
+            vec![
+                Point3::new(-2.0, -2.0, 0.0),
+                Point3::new(2.0, -2.0, 0.0),
+                Point3::new(0.0, 2.0, 0.0),
+            ],
+            vec![Polygon::triangle(0, 1, 2)],
+        );
+        mesh.generate_normals();
+        mesh.generate_texture_coord();
+        assert!(mesh.get_global_normal(0).unwrap().z > 0.0);
+        let mut model = Model::from_mesh(mesh);
+        model.material.color = Color32::RED;
+        let mut scene = Scene {
+            models: vec![model],
+            ..Default::default()
+        };
+
+        let mut renderer = SceneRenderer {
+            render_wireframe: false,
+            render_solid: true,
+            backface_culling: true,
+            ..Default::default()
+        };
+        let mut canvas = Canvas::new(300, 200);
+        let mut render = |scene: &Scene, renderer: &SceneRenderer| {
+            renderer.render(scene, &mut canvas, false, Point3::zero(), Point3::zero());
+            canvas[(150, 100)]
+        };
+        assert_ne!(
+            render(&scene, &renderer),
+            Color32::RED,
+            "обратная грань отсечена"
+        );
+
+        scene.models[0].flags.cull_mode = CullMode::Front;
+        assert_eq!(
+            render(&scene, &renderer),
+            Color32::RED,
+            "отсекаются только лицевые"
+        );
+        scene.models[0].flags.cull_mode = CullMode::Back;
+        scene.models[0].material.double_sided = true;
+        assert_eq!(scene.models[0].cull_mode(), CullMode::None);
+        assert_eq!(
+            render(&scene, &renderer),
+            Color32::RED,
+            "двусторонний материал"
+        );
+
+        // освещение спереди: у обратной грани развёрнутая нормаль смотрит на свет
+        scene.lights.push(LightSource::new(
+            Point3::new(0.0, 0.0, -10.0),
+            Color32::WHITE,
+            1.0,
+        ));
+        renderer.backface_culling = false;
+        renderer.shading_type = ShadingType::GouraudLambert;
+        let lit = render(&scene, &renderer);
+        scene.models[0].material.double_sided = false;
+        let unlit = render(&scene, &renderer);
+        assert!(
+            lit.r() > unlit.r(),
+            "двусторонняя грань освещена: {:?}, односторонняя: {:?}",
+            lit,
+            unlit
+        );
+    }
+
+    #[test]
+    fn test_face_culling_uses_face_normals() {
+        let camera = Camera::default();
+        let canvas = Canvas::new(300, 200);
+        let renderer = SceneRenderer {
+            backface_culling: true,
+            ..Default::default()
+        };
+
+        // грань почти ребром к камере: сама грань смотрит на камеру,
+        // а сглаженные нормали вершин - от неё
+        let angle = 80.0_f32.to_radians();
+        let along = Vec3::new(angle.cos(), 0.0, angle.sin());
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        let vertexes = vec![
+            Point3::zero() + (-along - up),
+            Point3::zero() + (along - up),
+            Point3::zero() + up,
+        ];
+        let vertex_normal = Vec3::new(1.0, 0.0, 0.3).normalize().unwrap();
+        let mesh = crate::Mesh::from_polygons(vertexes.clone(), vec![Polygon::triangle(0, 1, 2)])
+            .with_vertex_attributes(vertexes, Some(vec![vertex_normal; 3]), None);
+        let model = Model::from_mesh(mesh);
+        let transform = camera.global_to_screen_transform(renderer.projection_type, &canvas);
+        let geometry = RenderMeshCache::new(&model.mesh, transform, renderer.projection_type);
+        assert_eq!(
+            renderer
+                .model_face_culling(&camera, &model, &geometry, CullMode::Back)
+                .len(),
+            1,
+            "лицевая грань не отсекается"
+        );
+        assert!(
+            renderer
+                .model_face_culling(&camera, &model, &geometry, CullMode::Front)
+                .is_empty()
+        );
+
+        // боковая грань перпендикулярна направлению камеры, но при перспективе видна
+        let vertexes = vec![
+            Point3::new(3.0, -1.0, -1.0),
+            Point3::new(3.0, 1.0, -1.0),
+            Point3::new(3.0, 0.0, 1.0),
+        ];
+        let side_normal = Vec3::new(-1.0, 0.0, 0.0).normalize().unwrap();
+        let side = Model::from_mesh(
+            crate::Mesh::from_polygons(vertexes.clone(), vec![Polygon::triangle(0, 1, 2)])
+                .with_vertex_attributes(vertexes, Some(vec![side_normal; 3]), None),
+        );
+        for (projection_type, visible) in [
+            (ProjectionType::Parallel, 0),
+            (ProjectionType::Perspective, 1),
+        ] {
+            let renderer = SceneRenderer {
+                projection_type,
+                ..renderer.clone()
+            };
+            let transform = camera.global_to_screen_transform(projection_type, &canvas);
+            let geometry = RenderMeshCache::new(&side.mesh, transform, projection_type);
+            assert_eq!(
+                renderer
+                    .model_face_culling(&camera, &side, &geometry, CullMode::Back)
+                    .len(),
+                visible,
+                "{:?}",
+                projection_type
+            );
+        }
+    }
+
+    #[test]
+    fn test_depth_func_and_write() {
+        let mut scene = two_cubes_scene();
+        scene.models[0].material.color = Color32::BLUE;
+        scene.models[1].material.color = Color32::RED;
+        let mut renderer = SceneRenderer {
+            render_wireframe: false,
+            render_solid: true,
+            projection_type: ProjectionType::Perspective,
+            ..Default::default()
+        };
+        let mut canvas = Canvas::new(300, 200);
+        let mut render = |renderer: &SceneRenderer| {
+            renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+            canvas[(150, 100)]
+        };
+        assert_eq!(render(&renderer), Color32::BLUE, "виден ближний куб");
+
+        renderer.depth_func = DepthFunc::Greater;
+        assert_eq!(render(&renderer), Color32::RED, "виден дальний куб");
+
+        // без записи глубины грани перекрываются в порядке отрисовки
+        renderer.depth_func = DepthFunc::Less;
+        renderer.depth_write = false;
+        assert_eq!(render(&renderer), Color32::RED);
+    }
+
+    #[test]
+    fn test_silhouette_strokes() {
+        let mut cube = Model::from_mesh(crate::Mesh::hexahedron());
+        cube.mesh.generate_normals();
+        cube.uniform_scale(3.0);
+        cube.rotate_local_y(30_f32.to_radians());
+        cube.rotate_local_x(20_f32.to_radians());
+        cube.material.color = Color32::WHITE;
+        let scene = Scene {
+            models: vec![cube],
+            ..Default::default()
+        };
+        let mut renderer = SceneRenderer {
+            render_wireframe: false,
+            render_solid: true,
+            projection_type: ProjectionType::Perspective,
+            grid_options: GridOptions {
+                show_grid: false,
+                show_axes: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut canvas = Canvas::new(300, 200);
+        let mut stroked = |renderer: &SceneRenderer| {
+            renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+            let count = canvas
+                .pixels()
+                .iter()
+                .filter(|&&p| p == Color32::RED)
+                .count();
+            (count, canvas[(150, 100)])
+        };
+        assert_eq!(stroked(&renderer).0, 0);
+
+        renderer.silhouette = Some(LineStyle::new(Color32::RED, 1.0));
+        let (thin, center) = stroked(&renderer);
+        assert!(thin > 0, "силуэт должен быть обведён");
+        assert_ne!(center, Color32::RED, "внутренние рёбра не обводятся");
+
+        renderer.silhouette = Some(LineStyle::new(Color32::RED, 3.0));
+        let (thick, _) = stroked(&renderer);
+        assert!(
+            thick > 2 * thin,
+            "толстая обводка {} и тонкая {}",
+            thick,
+            thin
+        );
+    }
+
+    #[test]
+    fn test_user_clip_planes() {
+        let mut scene = two_cubes_scene();
+        scene.models[0].material.color = Color32::BLUE;
+        scene.models[1].material.color = Color32::RED;
+        let mut renderer = SceneRenderer {
+            render_wireframe: false,
+            render_solid: true,
+            projection_type: ProjectionType::Perspective,
+            backface_culling: true,
+            ..Default::default()
+        };
+        let mut canvas = Canvas::new(300, 200);
+        let mut render = |renderer: &SceneRenderer| {
+            renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+            canvas[(150, 100)]
+        };
+        assert_eq!(render(&renderer), Color32::BLUE);
+
+        // ближний куб целиком отсечён
+        renderer.add_clip_plane(Plane::new(Point3::zero(), UVec3::forward()));
+        assert_eq!(render(&renderer), Color32::RED);
+
+        // ближний куб разрезан посередине, и через срез без крышки виден дальний
+        renderer.clear_clip_planes();
+        renderer.add_clip_plane(Plane::new(Point3::new(0.0, 0.0, -3.0), UVec3::forward()));
+        assert_eq!(render(&renderer), Color32::RED);
+
+        // с обратной стороны плоскости остаётся передняя половина
+        renderer.clear_clip_planes();
+        renderer.add_clip_plane(Plane::new(Point3::new(0.0, 0.0, -3.0), -UVec3::forward()));
+        assert_eq!(render(&renderer), Color32::BLUE);
+    }
+
+    #[test]
+    fn test_render_uses_corner_texture_coords() {
+        // текстура 2x2 из разных цветов, передняя грань куба должна показать её целиком
+        let mut image = image::RgbImage::new(2, 2);
+        image.put_pixel(0, 0, image::Rgb([255, 0, 0]));
+        image.put_pixel(1, 0, image::Rgb([0, 255, 0]));
+        image.put_pixel(0, 1, image::Rgb([0, 0, 255]));
+        image.put_pixel(1, 1, image::Rgb([255, 255, 255]));
+        let mut cube = Model::from_mesh(crate::Mesh::hexahedron());
+        cube.uniform_scale(4.0);
+        cube.material.color = Color32::WHITE;
+        cube.material.texture = Some(crate::Texture::new(image::DynamicImage::ImageRgb8(image)));
+        let scene = Scene {
+            models: vec![cube],
+            ..Default::default()
+        };
+        let renderer = SceneRenderer {
+            render_solid: true,
+            ..Default::default()
+        };
+        let mut canvas = Canvas::new(300, 200);
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+
+        let mut colors: Vec<Color32> = [(-20, -20), (20, -20), (-20, 20), (20, 20)]
+            .iter()
+            .map(|(dx, dy)| canvas[((150 + dx) as usize, (100 + dy) as usize)])
+            .collect();
+        colors.sort_by_key(|color| color.to_array());
+        colors.dedup();
+        assert_eq!(colors.len(), 4, "{:?}", colors);
+    }
+
+    #[test]
+    fn test_planar_quad_matches_triangulated_quad() {
+        // цвета вершин меняются линейно, поэтому билинейная и барицентрическая
+        // интерполяции должны совпасть
+        let vertexes = vec![
+            Point3::new(-2.0, -2.0, 0.0),
+            Point3::new(2.0, -2.0, 0.0),
+            Point3::new(2.0, 2.0, 0.0),
+            Point3::new(-2.0, 2.0, 0.0),
+        ];
+        let colors = vec![
+            Color32::from_rgb(0, 0, 0),
+            Color32::from_rgb(200, 0, 0),
+            Color32::from_rgb(200, 200, 0),
+            Color32::from_rgb(0, 200, 0),
+        ];
+        let quad_model = |polygons: Vec<Polygon>| {
+            let mut mesh = crate::Mesh::from_polygons(vertexes.clone(), polygons);
+            mesh.set_vertex_colors(colors.clone());
+            let mut model = Model::from_mesh(mesh);
+            model.material.double_sided = true;
+            model
+        };
+        let quad = quad_model(vec![Polygon::from_list(&[0, 1, 2, 3])]);
+        assert!(quad.mesh.get_polygon(0).is_convex_quad(&quad.mesh));
+        let triangles = quad_model(vec![
+            Polygon::from_list(&[0, 1, 2]),
+            Polygon::from_list(&[0, 2, 3]),
+        ]);
+
+        for shading_type in [
+            ShadingType::None,
+            ShadingType::GouraudLambert,
+            ShadingType::PhongToonShading(4),
+        ] {
+            let renderer = SceneRenderer {
+                render_wireframe: false,
+                render_solid: true,
+                shading_type,
+                ..Default::default()
+            };
+            let render = |models: Vec<Model>| {
+                let scene = Scene {
+                    models,
+                    lights: vec![LightSource::new(
+                        Point3::new(0.0, 0.0, -10.0),
+                        Color32::WHITE,
+                        1.0,
+                    )],
+                    ..Default::default()
+                };
+                let mut canvas = Canvas::new(300, 200);
+                renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+                canvas
+            };
+            let background = render(Vec::new());
+            let bilinear = render(vec![quad.clone()]);
+            let barycentric = render(vec![triangles.clone()]);
+
+            let mut covered = 0;
+            let mut mismatched_edges = 0;
+            for y in 0..200 {
+                for x in 0..300 {
+                    let (a, b) = (bilinear[(x, y)], barycentric[(x, y)]);
+                    if a == background[(x, y)] || b == background[(x, y)] {
+                        // только пиксели границы могут отличаться покрытием
+                        mismatched_edges += (a != b) as usize;
+                        continue;
+                    }
+                    covered += 1;
+                    let difference = a
+                        .to_array()
+                        .iter()
+                        .zip(b.to_array())
+                        .map(|(&ca, cb)| ca.abs_diff(cb))
+                        .max()
+                        .unwrap();
+                    assert!(
+                        difference <= 2,
+                        "{}: пиксель ({}, {}): {:?} и {:?}",
+                        shading_type,
+                        x,
+                        y,
+                        a,
+                        b
+                    );
+                }
+            }
+            assert!(covered > 1000, "{}: квадрат на снимке", shading_type);
+            assert!(
+                mismatched_edges < covered / 20,
+                "{}: покрытие различается в {} пикселях",
+                shading_type,
+                mismatched_edges
+            );
+        }
+    }
+
+    #[test]
+    fn test_render_in_scissor() {
+        let scene = two_cubes_scene();
+        let renderer = SceneRenderer {
+            render_solid: true,
+            ..Default::default()
+        };
+        let mut full = Canvas::new(300, 200);
+        renderer.render(&scene, &mut full, false, Point3::zero(), Point3::zero());
+
+        // область не симметрична относительно середины холста по обеим осям
+        let mut canvas = Canvas::new(300, 200);
+        canvas.clear(Color32::RED);
+        let scissor = egui::Rect::from_min_max(Pos2::new(120.0, 20.0), Pos2::new(280.0, 110.0));
+        canvas.set_scissor(Some(scissor));
+        renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        assert_eq!(
+            canvas.scissor(),
+            Some(scissor),
+            "область отсечения восстановлена"
+        );
+
+        let (xs, ys) = canvas.scissor_bounds();
+        for y in 0..200 {
+            for x in 0..300 {
+                if xs.contains(&x) && ys.contains(&y) {
+                    assert_eq!(canvas[(x, y)], full[(x, y)], "пиксель ({}, {})", x, y);
+                } else {
+                    assert_eq!(canvas[(x, y)], Color32::RED, "пиксель ({}, {})", x, y);
+                }
+            }
+        }
+    }
 }
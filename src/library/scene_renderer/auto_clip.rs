@@ -0,0 +1,170 @@
+//! Автоматическая подстройка плоскостей отсечения камеры под глубину сцены.
+
+use crate::{Camera, Scene, Vec3, library::utils};
+
+/// Минимальное расстояние между ближней и дальней плоскостями.
+const MIN_DEPTH_RANGE: f32 = 0.1;
+
+/// Параметры автоматической подстройки ближней и дальней плоскостей отсечения.
+///
+/// Каждый кадр по ограничивающим параллелепипедам видимых моделей вычисляется диапазон
+/// глубины сцены вдоль направления камеры, и плоскости отсечения сжимаются до него.
+/// Чем уже диапазон, тем точнее z-буфер и тем меньше z-fighting между близкими гранями.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AutoClipPlanes {
+    /// Подстраивать ли плоскости отсечения.
+    pub enabled: bool,
+    /// Минимально допустимое расстояние до ближней плоскости.
+    pub min_near: f32,
+    /// Максимально допустимое расстояние до дальней плоскости.
+    pub max_far: f32,
+    /// Относительный запас глубины, чтобы модели на границах диапазона не отсекались.
+    pub margin: f32,
+}
+
+impl Default for AutoClipPlanes {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_near: 0.01,
+            max_far: 1000.0,
+            margin: 0.05,
+        }
+    }
+}
+
+impl AutoClipPlanes {
+    /// Вычислить плоскости отсечения `(near, far)` для сцены с камерой `camera`.
+    ///
+    /// Возвращает `None`, если перед камерой нет ни одной видимой модели.
+    pub fn fit(&self, scene: &Scene, camera: &Camera) -> Option<(f32, f32)> {
+        let position = camera.get_position();
+        let direction = Vec3::from(camera.get_direction());
+
+        let mut range: Option<(f32, f32)> = None;
+        for model in scene.models.iter().filter(|model| model.flags.visible) {
+            if model.mesh.vertex_count() == 0 {
+                continue;
+            }
+            let (min, max) = model.global_bounds();
+            for corner in utils::bounds_corners(min, max) {
+                let depth = (corner - position).dot(direction);
+                range = Some(match range {
+                    Some((nearest, farthest)) => (nearest.min(depth), farthest.max(depth)),
+                    None => (depth, depth),
+                });
+            }
+        }
+
+        let (nearest, farthest) = range?;
+        if farthest <= 0.0 {
+            return None;
+        }
+
+        let min_near = self.min_near.max(f32::EPSILON);
+        let max_far = self.max_far.max(min_near + MIN_DEPTH_RANGE);
+        let near = (nearest * (1.0 - self.margin)).clamp(min_near, max_far - MIN_DEPTH_RANGE);
+        let far = (farthest * (1.0 + self.margin)).clamp(near + MIN_DEPTH_RANGE, max_far);
+        Some((near, far))
+    }
+
+    /// Получить копию камеры с подстроенными плоскостями отсечения.
+    ///
+    /// Если подстройка выключена или перед камерой нет моделей, то камера не меняется.
+    pub fn apply(&self, scene: &Scene, camera: &Camera) -> Camera {
+        let mut camera = *camera;
+        if !self.enabled {
+            return camera;
+        }
+        if let Some((near, far)) = self.fit(scene, &camera) {
+            camera.set_clip_planes(near, far);
+        }
+        camera
+    }
+}
+
+#[cfg(test)]
+mod auto_clip_tests {
+    use super::*;
+    use crate::{Mesh, Model, Point3, UVec3};
+
+    const TOLERANCE: f32 = 1e-4;
+
+    fn scene_with_cube_at(z: f32) -> Scene {
+        let mut scene = Scene {
+            camera: Camera::new(
+                Point3::zero(),
+                UVec3::plus_z(),
+                UVec3::up(),
+                60.0_f32.to_radians(),
+                1.0,
+                0.1,
+                500.0,
+            ),
+            ..Default::default()
+        };
+        let mut cube = Model::from_mesh(Mesh::hexahedron());
+        cube.set_position(Point3::new(0.0, 0.0, z));
+        scene.models.push(cube);
+        scene
+    }
+
+    #[test]
+    fn test_fit_tightens_planes() {
+        let scene = scene_with_cube_at(10.0);
+        let options = AutoClipPlanes {
+            enabled: true,
+            margin: 0.0,
+            ..Default::default()
+        };
+
+        let (near, far) = options.fit(&scene, &scene.camera).unwrap();
+        assert!(
+            (near - 9.5).abs() < TOLERANCE && (far - 10.5).abs() < TOLERANCE,
+            "ожидались плоскости (9.5, 10.5), но получено ({}, {})",
+            near,
+            far
+        );
+
+        let camera = options.apply(&scene, &scene.camera);
+        assert!((camera.get_near_plane() - 9.5).abs() < TOLERANCE);
+        assert!((camera.get_far_plane() - 10.5).abs() < TOLERANCE);
+    }
+
+    #[test]
+    fn test_fit_respects_limits() {
+        let scene = scene_with_cube_at(10.0);
+        let options = AutoClipPlanes {
+            enabled: true,
+            min_near: 0.5,
+            max_far: 10.0,
+            margin: 0.0,
+        };
+        let (near, far) = options.fit(&scene, &scene.camera).unwrap();
+        assert!(near >= 0.5 && far <= 10.0 && near < far);
+
+        // камера внутри модели: ближняя плоскость упирается в минимум
+        let scene = scene_with_cube_at(0.0);
+        let (near, _) = options.fit(&scene, &scene.camera).unwrap();
+        assert!((near - 0.5).abs() < TOLERANCE);
+    }
+
+    #[test]
+    fn test_disabled_or_empty_keeps_camera() {
+        let scene = scene_with_cube_at(-10.0);
+        let options = AutoClipPlanes {
+            enabled: true,
+            ..Default::default()
+        };
+        assert!(
+            options.fit(&scene, &scene.camera).is_none(),
+            "модель позади камеры"
+        );
+        let camera = options.apply(&scene, &scene.camera);
+        assert_eq!(camera.get_near_plane(), scene.camera.get_near_plane());
+
+        let scene = scene_with_cube_at(10.0);
+        let camera = AutoClipPlanes::default().apply(&scene, &scene.camera);
+        assert_eq!(camera.get_far_plane(), scene.camera.get_far_plane());
+    }
+}
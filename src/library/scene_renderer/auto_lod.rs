@@ -0,0 +1,154 @@
+//! Автоматический выбор уровня детализации моделей по их размеру на экране.
+
+use crate::{Camera, Canvas, Model, ProjectionType, library::utils};
+
+/// Параметры автоматического выбора уровня детализации.
+///
+/// Для каждой модели оценивается площадь её ограничивающего параллелепипеда на экране,
+/// и выбирается самый грубый уровень, у которого на каждый треугольник приходится
+/// не больше `pixels_per_triangle` пикселей. Если автоматический выбор выключен, то уровень
+/// выбирается по расстояниям переключения из `Model::lod_group`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AutoLod {
+    /// Выбирать ли уровень детализации по размеру на экране, а не по расстоянию.
+    pub enabled: bool,
+    /// Желаемая площадь экрана в пикселях на один треугольник.
+    pub pixels_per_triangle: f32,
+}
+
+impl Default for AutoLod {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            pixels_per_triangle: 4.0,
+        }
+    }
+}
+
+impl AutoLod {
+    /// Выбрать уровень детализации модели. Уровень 0 - исходный Mesh.
+    pub fn select_level(
+        &self,
+        model: &Model,
+        camera: &Camera,
+        projection_type: ProjectionType,
+        canvas: &Canvas,
+    ) -> usize {
+        if model.lod_count() <= 1 || model.mesh.vertex_count() == 0 {
+            return 0;
+        }
+        if !self.enabled {
+            return model.select_lod_by_distance(camera.get_position());
+        }
+        let Some(area) = projected_area(model, camera, projection_type, canvas) else {
+            return 0;
+        };
+
+        let desired = area / self.pixels_per_triangle.max(f32::EPSILON);
+        let mut level = 0;
+        for lod in 1..model.lod_count() {
+            match model.get_lod_mesh(lod) {
+                Some(mesh) if mesh.triangle_count() as f32 >= desired => level = lod,
+                _ => break,
+            }
+        }
+        level
+    }
+}
+
+/// Площадь (в пикселях) экранного прямоугольника, описанного вокруг ограничивающего
+/// параллелепипеда модели, обрезанная по холсту.
+///
+/// Возвращает `None`, если параллелепипед не удаётся спроецировать (например, он позади камеры).
+fn projected_area(
+    model: &Model,
+    camera: &Camera,
+    projection_type: ProjectionType,
+    canvas: &Canvas,
+) -> Option<f32> {
+    let transform = camera.global_to_screen_transform(projection_type, canvas);
+    let (min, max) = model.global_bounds();
+
+    let (mut min_x, mut min_y) = (f32::MAX, f32::MAX);
+    let (mut max_x, mut max_y) = (f32::MIN, f32::MIN);
+    for corner in utils::bounds_corners(min, max) {
+        let projected = corner.apply_transform(transform).ok()?;
+        min_x = min_x.min(projected.x);
+        min_y = min_y.min(projected.y);
+        max_x = max_x.max(projected.x);
+        max_y = max_y.max(projected.y);
+    }
+
+    let (width, height) = (canvas.width() as f32, canvas.height() as f32);
+    let w = max_x.min(width) - min_x.max(0.0);
+    let h = max_y.min(height) - min_y.max(0.0);
+    Some(w.max(0.0) * h.max(0.0))
+}
+
+#[cfg(test)]
+mod auto_lod_tests {
+    use super::*;
+    use crate::{Point3, SurfaceFunction, UVec3};
+
+    fn camera_at_distance(distance: f32) -> Camera {
+        Camera::new(
+            Point3::new(0.0, 0.0, distance),
+            UVec3::new(0.0, 0.0, -1.0),
+            UVec3::up(),
+            60.0_f32.to_radians(),
+            1.0,
+            0.1,
+            1000.0,
+        )
+    }
+
+    #[test]
+    fn test_select_level() {
+        let mesh = SurfaceFunction::Wave.generate_surface_mesh((-1.0, 1.0), (-1.0, 1.0), (40, 40));
+        let mut model = Model::from_mesh(mesh);
+        model.generate_lods(3, 0.25, 10.0);
+        assert!(model.lod_count() > 1);
+
+        let canvas = Canvas::new(200, 200);
+        let options = AutoLod {
+            enabled: true,
+            ..Default::default()
+        };
+
+        let near = options.select_level(
+            &model,
+            &camera_at_distance(3.0),
+            ProjectionType::Perspective,
+            &canvas,
+        );
+        let far = options.select_level(
+            &model,
+            &camera_at_distance(200.0),
+            ProjectionType::Perspective,
+            &canvas,
+        );
+        assert!(
+            near < far,
+            "дальняя модель должна рисоваться грубее ближней, но уровни {} и {}",
+            near,
+            far
+        );
+        assert_eq!(far, model.lod_count() - 1);
+
+        // по расстоянию: уровни включаются с 10, 20 и 40
+        let by_distance = AutoLod::default();
+        for (distance, expected) in [(5.0, 0), (15.0, 1), (200.0, model.lod_count() - 1)] {
+            assert_eq!(
+                by_distance.select_level(
+                    &model,
+                    &camera_at_distance(distance),
+                    ProjectionType::Perspective,
+                    &canvas
+                ),
+                expected,
+                "неверный уровень на расстоянии {}",
+                distance
+            );
+        }
+    }
+}
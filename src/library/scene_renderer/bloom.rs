@@ -0,0 +1,248 @@
+//! Свечение (bloom) ярких пикселей, светящихся материалов и источников света.
+
+use crate::library::utils;
+use crate::{BufferView, Camera, Canvas, RenderTarget, Scene, Vec3};
+
+use super::SceneRenderer;
+
+/// Параметры свечения поверх кадра.
+///
+/// Свечение собирается из пикселей кадра ярче порога, пикселей светящихся материалов
+/// (см. [`crate::Material::emission`]) и видимых источников света, размывается
+/// и прибавляется к кадру. Размытие - самый дорогой этап, его цена растёт с радиусом,
+/// поэтому по умолчанию свечение выключено.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BloomOptions {
+    /// Включено ли свечение.
+    pub enabled: bool,
+    /// Линейная яркость пикселя (1 - белый), выше которой пиксель начинает светиться.
+    ///
+    /// Без HDR-режима яркость пикселей не больше 1, поэтому при пороге 1 светятся только
+    /// светящиеся материалы и источники света.
+    pub threshold: f32,
+    /// Сила свечения при наложении на кадр.
+    pub intensity: f32,
+    /// Радиус размытия свечения в пикселях.
+    pub radius: usize,
+    /// Светятся ли видимые источники света.
+    pub light_sources: bool,
+}
+
+impl Default for BloomOptions {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold: 1.0,
+            intensity: 1.0,
+            radius: 8,
+            light_sources: true,
+        }
+    }
+}
+
+/// Воспринимаемая яркость линейного цвета (Rec. 709).
+fn luminance(color: Vec3) -> f32 {
+    0.2126 * color.x + 0.7152 * color.y + 0.0722 * color.z
+}
+
+/// Нормированное гауссово ядро из `2 * radius + 1` весов, `sigma = radius / 2`.
+fn gaussian_kernel(radius: usize) -> Vec<f32> {
+    let sigma = (radius as f32 / 2.0).max(0.5);
+    let weights: Vec<f32> = (-(radius as isize)..=radius as isize)
+        .map(|offset| (-(offset * offset) as f32 / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let sum: f32 = weights.iter().sum();
+    weights.into_iter().map(|weight| weight / sum).collect()
+}
+
+/// Размыть буфер `width` x `height` ядром `kernel` сначала по строкам, затем по столбцам.
+///
+/// За границами буфера свечения нет.
+fn blur(buffer: &[Vec3], width: usize, height: usize, kernel: &[f32]) -> Vec<Vec3> {
+    let radius = (kernel.len() / 2) as isize;
+    let pass = |source: &[Vec3], step_x: isize, step_y: isize| -> Vec<Vec3> {
+        let mut result = vec![Vec3::zero(); source.len()];
+        for y in 0..height as isize {
+            for x in 0..width as isize {
+                let mut sum = Vec3::zero();
+                for (k, &weight) in kernel.iter().enumerate() {
+                    let offset = k as isize - radius;
+                    let (sx, sy) = (x + offset * step_x, y + offset * step_y);
+                    if sx < 0 || sy < 0 || sx >= width as isize || sy >= height as isize {
+                        continue;
+                    }
+                    sum += source[sy as usize * width + sx as usize] * weight;
+                }
+                result[y as usize * width + x as usize] = sum;
+            }
+        }
+        result
+    };
+    pass(&pass(buffer, 1, 0), 0, 1)
+}
+
+impl SceneRenderer {
+    /// Наложить свечение на холст (до переворота).
+    ///
+    /// Вспомогательный проход геометрии делается, только если на сцене есть светящиеся
+    /// модели или источники света.
+    pub(super) fn apply_bloom(&self, scene: &Scene, camera: &Camera, canvas: &mut Canvas) {
+        let options = &self.bloom;
+        let (width, height) = (canvas.width(), canvas.height());
+        if !options.enabled || options.intensity <= 0.0 || width == 0 || height == 0 {
+            return;
+        }
+
+        // пиксели кадра ярче порога
+        let mut glow: Vec<Vec3> = match canvas.hdr_buffer() {
+            Some(hdr) => hdr.to_vec(),
+            None => canvas
+                .pixels()
+                .iter()
+                .map(|&color| utils::color_to_vec(color))
+                .collect(),
+        };
+        for color in glow.iter_mut() {
+            let brightness = luminance(*color);
+            *color = if brightness > options.threshold {
+                *color * ((brightness - options.threshold) / brightness)
+            } else {
+                Vec3::zero()
+            };
+        }
+
+        let has_emissive = scene
+            .models
+            .iter()
+            .any(|model| self.renders_model(model) && model.material.is_emissive());
+        let lights: Vec<_> = scene
+            .lights
+            .iter()
+            .filter(|light| options.light_sources && self.renders_light(light))
+            .collect();
+        if has_emissive || !lights.is_empty() {
+            let mut target = RenderTarget::new(width, height);
+            let positions = self.write_geometry_buffers(scene, camera, &mut target);
+
+            // видимые пиксели светящихся моделей
+            if has_emissive
+                && let Some(BufferView::Id(ids)) = target.buffer(RenderTarget::ID_BUFFER)
+            {
+                for (index, &id) in ids.iter().enumerate() {
+                    if id != RenderTarget::NO_ID {
+                        glow[index] += scene.models[id as usize].material.emitted_light();
+                    }
+                }
+            }
+
+            // источники света - яркие круги, если их не заслоняют модели
+            let transform = camera.global_to_screen_transform(self.projection_type, canvas);
+            let eye = camera.get_position();
+            let forward = Vec3::from(camera.forward());
+            let splat_radius = (options.radius / 4).max(1) as isize;
+            for light in lights {
+                let distance = (light.position - eye).length();
+                if (light.position - eye).dot(forward) <= 0.0 {
+                    continue;
+                }
+                let Ok(screen) = light.position.apply_transform(transform) else {
+                    continue;
+                };
+                let light_color = utils::color_to_vec(light.color) * light.intensity;
+                let (cx, cy) = (screen.x.round() as isize, screen.y.round() as isize);
+                for dy in -splat_radius..=splat_radius {
+                    for dx in -splat_radius..=splat_radius {
+                        let (x, y) = (cx + dx, cy + dy);
+                        if dx * dx + dy * dy > splat_radius * splat_radius
+                            || x < 0
+                            || y < 0
+                            || x >= width as isize
+                            || y >= height as isize
+                        {
+                            continue;
+                        }
+                        let index = y as usize * width + x as usize;
+                        if positions[index]
+                            .is_some_and(|position| (position - eye).length() < distance)
+                        {
+                            continue;
+                        }
+                        glow[index] += light_color;
+                    }
+                }
+            }
+        }
+
+        // размытие дорогое, а светиться нечему
+        if glow.iter().all(|&color| color == Vec3::zero()) {
+            return;
+        }
+        let glow = blur(&glow, width, height, &gaussian_kernel(options.radius));
+        let (xs, ys) = canvas.scissor_bounds();
+        for y in ys {
+            for x in xs.clone() {
+                let light = glow[y * width + x] * options.intensity;
+                if light != Vec3::zero() {
+                    canvas.add_pixel_light(x, y, light);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod bloom_tests {
+    use super::*;
+    use crate::{Mesh, Model, Point3};
+    use egui::Color32;
+
+    #[test]
+    fn test_gaussian_kernel_is_normalized() {
+        let kernel = gaussian_kernel(4);
+        assert_eq!(kernel.len(), 9);
+        assert!((kernel.iter().sum::<f32>() - 1.0).abs() < 1e-5);
+        assert!(kernel[4] > kernel[3] && kernel[3] > kernel[0]);
+        assert_eq!(kernel[2], kernel[6]);
+    }
+
+    #[test]
+    fn test_emissive_model_glows_around_silhouette() {
+        let mut cube = Model::from_mesh(Mesh::hexahedron());
+        cube.material.color = Color32::from_rgb(40, 40, 40);
+        let mut scene = Scene {
+            models: vec![cube],
+            ..Default::default()
+        };
+        let mut renderer = SceneRenderer {
+            render_wireframe: false,
+            render_solid: true,
+            projection_type: crate::ProjectionType::Perspective,
+            ..Default::default()
+        };
+        renderer.grid_options.show_grid = false;
+        renderer.grid_options.show_axes = false;
+        renderer.bloom.enabled = true;
+        let render = |renderer: &SceneRenderer, scene: &Scene| {
+            let mut canvas = Canvas::new(100, 100);
+            renderer.render(scene, &mut canvas, false, Point3::zero(), Point3::zero());
+            canvas
+        };
+
+        // без свечения материала и источников света тёмный кадр не меняется
+        let plain = render(&renderer, &scene);
+        let mut without_bloom = renderer.clone();
+        without_bloom.bloom.enabled = false;
+        assert_eq!(plain.pixels(), render(&without_bloom, &scene).pixels());
+
+        scene.models[0].material.emission = Color32::from_rgb(255, 128, 0);
+        let glowing = render(&renderer, &scene);
+        let (x, y) = (50, 50);
+        let edge = (x..100)
+            .find(|&x| plain[(x, y)] != plain[(x - 1, y)])
+            .expect("край куба на снимке");
+        let outside = glowing[(edge + 2, y)];
+        assert_ne!(outside, plain[(edge + 2, y)], "ореол выходит за силуэт");
+        assert!(outside.r() > outside.b(), "ореол цвета свечения");
+        assert_eq!(glowing[(2, 2)], plain[(2, 2)], "вдали от модели ореола нет");
+    }
+}
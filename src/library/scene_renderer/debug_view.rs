@@ -0,0 +1,130 @@
+//! Отладочные режимы отображения сцены.
+
+use std::fmt::Display;
+
+use egui::Color32;
+
+use super::{FragmentInput, FragmentShader, SharedFragmentShader};
+
+/// Цвет фрагментов, у которых нет отображаемого атрибута (UV-координат или нормали).
+pub const DEBUG_MISSING_COLOR: Color32 = Color32::from_rgb(255, 0, 255);
+
+/// Отладочный режим отображения, заменяющий обычный шейдинг граней.
+///
+/// Предназначен для поиска ошибок в развёртке текстур, нормалях и порядке отрисовки.
+/// В любом режиме, кроме [`DebugView::None`], грани моделей растеризуются даже при
+/// `render_solid = false`, а шейдинг и пользовательский шейдер фрагментов не применяются.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub enum DebugView {
+    /// Обычная отрисовка.
+    #[default]
+    None,
+    /// UV-координаты цветом: u - красный канал, v - зелёный.
+    ///
+    /// Фрагменты без текстурных координат окрашиваются в [`DEBUG_MISSING_COLOR`].
+    TextureCoords,
+    /// Шахматная доска поверх цвета материала с заданным количеством клеток на единицу UV.
+    ///
+    /// Растяжения и разрывы развёртки видны по искажению клеток.
+    Checkerboard(usize),
+    /// Нормали в **глобальных** координатах цветом: x, y, z переводятся из [-1, 1] в RGB.
+    ///
+    /// Фрагменты без нормалей окрашиваются в [`DEBUG_MISSING_COLOR`].
+    Normals,
+    /// Содержимое z-буфера оттенками серого: ближайшая точка белая, самая дальняя тёмная,
+    /// пиксели без граней чёрные.
+    Depth,
+    /// Тепловая карта сложности глубины (overdraw): сколько фрагментов записано в пиксель.
+    Overdraw,
+}
+
+impl DebugView {
+    /// Все режимы для выбора в интерфейсе (шахматная доска с 8 клетками).
+    pub const ALL: [DebugView; 6] = [
+        Self::None,
+        Self::TextureCoords,
+        Self::Checkerboard(8),
+        Self::Normals,
+        Self::Depth,
+        Self::Overdraw,
+    ];
+
+    /// Встроенный шейдер фрагментов режима, если режим раскрашивает фрагменты.
+    ///
+    /// Режимы [`DebugView::Depth`] и [`DebugView::Overdraw`] строятся по буферам холста
+    /// после отрисовки кадра, поэтому шейдера у них нет.
+    pub(super) fn fragment_shader(self) -> Option<SharedFragmentShader> {
+        match self {
+            Self::TextureCoords => Some(SharedFragmentShader::new(TextureCoordsView)),
+            Self::Checkerboard(cells) => {
+                Some(SharedFragmentShader::new(CheckerboardView(cells.max(1))))
+            }
+            Self::Normals => Some(SharedFragmentShader::new(NormalsView)),
+            Self::None | Self::Depth | Self::Overdraw => None,
+        }
+    }
+}
+
+impl Display for DebugView {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::None => f.write_str("Отсутствует"),
+            Self::TextureCoords => f.write_str("UV-координаты"),
+            Self::Checkerboard(_) => f.write_str("Шахматная доска"),
+            Self::Normals => f.write_str("Нормали"),
+            Self::Depth => f.write_str("Глубина"),
+            Self::Overdraw => f.write_str("Сложность глубины (overdraw)"),
+        }
+    }
+}
+
+/// Перевести значение из [0, 1] в канал цвета.
+fn channel(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// UV-координаты цветом.
+struct TextureCoordsView;
+
+impl FragmentShader for TextureCoordsView {
+    fn shade(&self, input: FragmentInput) -> Color32 {
+        match input.uv {
+            Some((u, v)) => Color32::from_rgb(channel(u), channel(v), 0),
+            None => DEBUG_MISSING_COLOR,
+        }
+    }
+}
+
+/// Шахматная доска по UV-координатам поверх цвета материала.
+struct CheckerboardView(usize);
+
+impl FragmentShader for CheckerboardView {
+    fn shade(&self, input: FragmentInput) -> Color32 {
+        let base = input.base_color();
+        let Some((u, v)) = input.uv else {
+            return base;
+        };
+        let cell = |t: f32| (t * self.0 as f32).floor().min(self.0 as f32 - 1.0) as usize;
+        if (cell(u) + cell(v)) % 2 == 0 {
+            base
+        } else {
+            base.gamma_multiply(0.35).to_opaque()
+        }
+    }
+}
+
+/// Нормали цветом.
+struct NormalsView;
+
+impl FragmentShader for NormalsView {
+    fn shade(&self, input: FragmentInput) -> Color32 {
+        match input.normal {
+            Some(normal) => Color32::from_rgb(
+                channel((normal.x + 1.0) / 2.0),
+                channel((normal.y + 1.0) / 2.0),
+                channel((normal.z + 1.0) / 2.0),
+            ),
+            None => DEBUG_MISSING_COLOR,
+        }
+    }
+}
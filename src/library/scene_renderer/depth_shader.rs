@@ -0,0 +1,58 @@
+use crate::{Canvas, LightSource, Model, Point3, Polygon, library::utils};
+
+use super::{RenderMeshCache, Shader};
+
+/// Шейдер, заполняющий только z-буфер, без изменения цвета пикселей.
+///
+/// Нужен для предварительного прохода глубины, например при удалении невидимых рёбер каркаса.
+pub struct DepthShader;
+
+impl DepthShader {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Shader for DepthShader {
+    fn shade_model(
+        &self,
+        _model: &Model,
+        geometry: &RenderMeshCache,
+        polygons: &Vec<Polygon>,
+        _lights: &Vec<LightSource>,
+        canvas: &mut Canvas,
+    ) {
+        // проекция вершин на экран
+        let projected_vertexes = &geometry.screen_vertexes;
+
+        for polygon in polygons {
+            for triangle in polygon.triangles() {
+                // проекция вершин треугольника
+                let v0 = projected_vertexes[triangle[0]];
+                let v1 = projected_vertexes[triangle[1]];
+                let v2 = projected_vertexes[triangle[2]];
+
+                // ограничивающий прямоугольник
+                let min_x = v0.x.min(v1.x.min(v2.x)) as usize;
+                let max_x = v0.x.max(v1.x.max(v2.x)) as usize;
+                let min_y = v0.y.min(v1.y.min(v2.y)) as usize;
+                let max_y = v0.y.max(v1.y.max(v2.y)) as usize;
+
+                for y in min_y..=max_y.min(canvas.height() - 1) {
+                    for x in min_x..=max_x.min(canvas.width() - 1) {
+                        let p = Point3::new(x as f32, y as f32, 0.0);
+                        let bary = utils::barycentric_coordinates(&[v0, v1, v2], p);
+
+                        // точка на полигоне?
+                        if bary.x < 0.0 || bary.y < 0.0 || bary.z < 0.0 {
+                            continue;
+                        }
+
+                        let z = utils::interpolate_float(bary, v0.z, v1.z, v2.z);
+                        canvas.test_and_set_z(x, y, z);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,216 @@
+//! Пользовательский шейдинг фрагментов.
+
+use std::{fmt::Debug, sync::Arc};
+
+use egui::Color32;
+
+use crate::{
+    Canvas, EnvironmentLight, LightSource, Material, Model, Point3, Polygon, UVec3, library::utils,
+};
+
+use super::{RenderMeshCache, Shader, lighting};
+
+/// Данные фрагмента (пикселя полигона), переданные в [`FragmentShader`].
+///
+/// Все атрибуты интерполированы между вершинами полигона.
+#[derive(Debug, Clone, Copy)]
+pub struct FragmentInput<'a> {
+    /// Координата пикселя по горизонтали.
+    pub x: usize,
+    /// Координата пикселя по вертикали (до переворота холста в конце рендера).
+    pub y: usize,
+    /// Точка поверхности в **глобальных** координатах.
+    pub position: Point3,
+    /// Нормаль поверхности в **глобальных** координатах, если у Mesh'а есть нормали.
+    pub normal: Option<UVec3>,
+    /// Текстурные координаты, если они есть у Mesh'а.
+    pub uv: Option<(f32, f32)>,
+    /// Глубина фрагмента в экранных координатах (больше - ближе к камере).
+    pub depth: f32,
+    /// Запечённое затенение окружением: 0 - точка открыта, 1 - полностью закрыта.
+    /// Равно 0, если у Mesh'а оно не запечено.
+    pub ambient_occlusion: f32,
+    /// Цвет вершин, если они заданы у Mesh'а.
+    pub vertex_color: Option<Color32>,
+    /// Материал модели.
+    pub material: &'a Material,
+    /// Источники света сцены.
+    pub lights: &'a [LightSource],
+    /// Освещение окружением сцены, если оно задано.
+    pub environment: Option<&'a EnvironmentLight>,
+}
+
+impl FragmentInput<'_> {
+    /// Цвет материала в точке фрагмента с учётом текстуры и цветов вершин.
+    pub fn base_color(&self) -> Color32 {
+        match self.uv {
+            Some((u, v)) => self.material.get_vertex_uv_color(u, v, self.vertex_color),
+            None => self.vertex_color.unwrap_or(self.material.color),
+        }
+    }
+}
+
+/// Пользовательский шейдер фрагментов.
+///
+/// Подключается к рендеру через [`super::SceneRenderer::fragment_shader`] и заменяет
+/// встроенный шейдинг граней. Проверка глубины и смешивание с прозрачностью
+/// выполняются рендером.
+///
+/// # Examples
+/// ```rust
+/// use g3d::{FragmentInput, FragmentShader, SceneRenderer, SharedFragmentShader};
+///
+/// /// Раскраска поверхности по нормали.
+/// struct NormalColors;
+///
+/// impl FragmentShader for NormalColors {
+///     fn shade(&self, input: FragmentInput) -> egui::Color32 {
+///         let Some(normal) = input.normal else {
+///             return input.base_color();
+///         };
+///         let channel = |value: f32| ((value + 1.0) * 127.5) as u8;
+///         egui::Color32::from_rgb(channel(normal.x), channel(normal.y), channel(normal.z))
+///     }
+/// }
+///
+/// let renderer = SceneRenderer {
+///     render_solid: true,
+///     fragment_shader: Some(SharedFragmentShader::new(NormalColors)),
+///     ..Default::default()
+/// };
+/// ```
+pub trait FragmentShader {
+    /// Цвет фрагмента.
+    fn shade(&self, input: FragmentInput) -> Color32;
+}
+
+/// Разделяемая ссылка на пользовательский [`FragmentShader`].
+///
+/// Параметры рендера сравниваются между кадрами, поэтому шейдеры сравниваются по ссылке:
+/// изменение внутреннего состояния шейдера не считается изменением параметров.
+#[derive(Clone)]
+pub struct SharedFragmentShader(Arc<dyn FragmentShader>);
+
+impl SharedFragmentShader {
+    /// Обернуть шейдер для передачи в [`super::SceneRenderer`].
+    pub fn new(shader: impl FragmentShader + 'static) -> Self {
+        Self(Arc::new(shader))
+    }
+}
+
+impl Debug for SharedFragmentShader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SharedFragmentShader")
+    }
+}
+
+impl PartialEq for SharedFragmentShader {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Растеризация граней с цветом из пользовательского шейдера фрагментов.
+pub struct ProgrammableShader<'a> {
+    fragment_shader: &'a dyn FragmentShader,
+    z_buffer_enabled: bool,
+    environment: Option<&'a EnvironmentLight>,
+}
+
+impl<'a> ProgrammableShader<'a> {
+    pub fn new(
+        fragment_shader: &'a SharedFragmentShader,
+        z_buffer_enabled: bool,
+        environment: Option<&'a EnvironmentLight>,
+    ) -> Self {
+        Self {
+            fragment_shader: fragment_shader.0.as_ref(),
+            z_buffer_enabled,
+            environment,
+        }
+    }
+}
+
+impl Shader for ProgrammableShader<'_> {
+    fn shade_model(
+        &self,
+        model: &Model,
+        geometry: &RenderMeshCache,
+        polygons: &Vec<Polygon>,
+        lights: &Vec<LightSource>,
+        canvas: &mut Canvas,
+    ) {
+        let projected_vertexes = &geometry.screen_vertexes;
+        let normals = geometry.global_normals.as_deref();
+
+        // все полигоны триангулируются, атрибуты интерполируются барицентрически
+        for polygon in polygons {
+            for corners in polygon.corner_triangles() {
+                let [i0, i1, i2] = corners.map(|i| polygon.get_mesh_vertex_index(i));
+                let (v0, v1, v2) = (
+                    projected_vertexes[i0],
+                    projected_vertexes[i1],
+                    projected_vertexes[i2],
+                );
+
+                let min_x = v0.x.min(v1.x.min(v2.x)) as usize;
+                let max_x = v0.x.max(v1.x.max(v2.x)) as usize;
+                let min_y = v0.y.min(v1.y.min(v2.y)) as usize;
+                let max_y = v0.y.max(v1.y.max(v2.y)) as usize;
+
+                for y in min_y..=max_y.min(canvas.height().saturating_sub(1)) {
+                    for x in min_x..=max_x.min(canvas.width().saturating_sub(1)) {
+                        let p = Point3::new(x as f32, y as f32, 0.0);
+                        let bary = utils::barycentric_coordinates(&[v0, v1, v2], p);
+
+                        // точка на полигоне?
+                        if bary.x < 0.0 || bary.y < 0.0 || bary.z < 0.0 {
+                            continue;
+                        }
+
+                        let depth = utils::interpolate_float(bary, v0.z, v1.z, v2.z);
+                        if self.z_buffer_enabled && !canvas.test_and_set_z(x, y, depth) {
+                            continue;
+                        }
+
+                        let uv = match corners.map(|i| polygon.get_texture_coord(&model.mesh, i)) {
+                            [Some(t0), Some(t1), Some(t2)] => Some((
+                                utils::interpolate_float(bary, t0.0, t1.0, t2.0),
+                                utils::interpolate_float(bary, t0.1, t1.1, t2.1),
+                            )),
+                            _ => None,
+                        };
+
+                        let input = FragmentInput {
+                            x,
+                            y,
+                            position: utils::interpolate_point(
+                                bary,
+                                geometry.global_vertexes[i0],
+                                geometry.global_vertexes[i1],
+                                geometry.global_vertexes[i2],
+                            ),
+                            normal: normals.map(|normals| {
+                                utils::interpolate_uvec(bary, normals[i0], normals[i1], normals[i2])
+                            }),
+                            uv,
+                            depth,
+                            ambient_occlusion: utils::interpolate_float(
+                                bary,
+                                model.mesh.get_ambient_occlusion(i0).unwrap_or(0.0),
+                                model.mesh.get_ambient_occlusion(i1).unwrap_or(0.0),
+                                model.mesh.get_ambient_occlusion(i2).unwrap_or(0.0),
+                            ),
+                            vertex_color: lighting::vertex_colors(model, [i0, i1, i2])
+                                .map(|[c0, c1, c2]| utils::interpolate_color(bary, c0, c1, c2)),
+                            material: &model.material,
+                            lights,
+                            environment: self.environment,
+                        };
+                        canvas.set_fragment(x, y, self.fragment_shader.shade(input));
+                    }
+                }
+            }
+        }
+    }
+}
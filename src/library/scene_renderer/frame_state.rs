@@ -0,0 +1,158 @@
+//! Состояние последнего отрисованного кадра для повторного использования холста.
+
+use super::{RenderStats, SceneRenderer};
+use crate::{Canvas, Point3, Scene};
+
+/// Состояние, с которым был отрисован последний кадр.
+///
+/// Используется в [`SceneRenderer::render_if_changed`]: если ни сцена, ни параметры рендера,
+/// ни размер холста не изменились, то холст с прошлого кадра переиспользуется без перерисовки.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FrameState {
+    /// Ключ последнего кадра, `None` если кадр ещё не рисовался.
+    last_frame: Option<FrameKey>,
+    /// Статистика последнего отрисованного кадра.
+    stats: RenderStats,
+}
+
+/// Всё, от чего зависит изображение кадра.
+#[derive(Debug, Clone, PartialEq)]
+struct FrameKey {
+    renderer: SceneRenderer,
+    scene_hash: u64,
+    canvas_size: [usize; 2],
+    custom_axis: Option<(Point3, Point3)>,
+}
+
+impl FrameState {
+    /// Создать состояние, при котором следующий кадр обязательно будет отрисован.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Сбросить состояние, чтобы следующий кадр был отрисован заново.
+    ///
+    /// Нужно, если холст был изменён в обход рендера.
+    pub fn invalidate(&mut self) {
+        self.last_frame = None;
+    }
+
+    /// Статистика последнего отрисованного кадра.
+    ///
+    /// Пропущенные кадры её не меняют.
+    pub fn stats(&self) -> &RenderStats {
+        &self.stats
+    }
+}
+
+impl SceneRenderer {
+    /// Нарисовать сцену, только если с прошлого кадра что-то изменилось.
+    ///
+    /// Перерисовка происходит, если изменилось состояние сцены (см. [`Scene::state_hash`]),
+    /// параметры рендера, размер холста или пользовательская ось. Иначе холст остаётся
+    /// нетронутым с прошлого вызова. `frame_state` хранит состояние между вызовами и должен
+    /// использоваться только с одним холстом.
+    ///
+    /// Возвращает `true`, если кадр был перерисован.
+    pub fn render_if_changed(
+        &self,
+        scene: &Scene,
+        canvas: &mut Canvas,
+        frame_state: &mut FrameState,
+        show_custom_axis: bool,
+        axis_point1: Point3,
+        axis_point2: Point3,
+    ) -> bool {
+        let key = FrameKey {
+            renderer: self.clone(),
+            scene_hash: scene.state_hash(),
+            canvas_size: canvas.size(),
+            custom_axis: show_custom_axis.then_some((axis_point1, axis_point2)),
+        };
+        if frame_state.last_frame.as_ref() == Some(&key) {
+            return false;
+        }
+
+        frame_state.stats = self.render(scene, canvas, show_custom_axis, axis_point1, axis_point2);
+        frame_state.last_frame = Some(key);
+        true
+    }
+}
+
+#[cfg(test)]
+mod frame_state_tests {
+    use super::*;
+    use crate::{Mesh, Model, Vec3};
+
+    fn cube_scene() -> Scene {
+        Scene {
+            models: vec![Model::from_mesh(Mesh::hexahedron())],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_render_if_changed_skips_unchanged_frames() {
+        let renderer = SceneRenderer::default();
+        let mut scene = cube_scene();
+        let mut canvas = Canvas::new(120, 80);
+        let mut frame_state = FrameState::new();
+        let origin = Point3::zero();
+
+        assert!(renderer.render_if_changed(
+            &scene,
+            &mut canvas,
+            &mut frame_state,
+            false,
+            origin,
+            origin
+        ));
+        assert!(
+            !renderer.render_if_changed(
+                &scene,
+                &mut canvas,
+                &mut frame_state,
+                false,
+                origin,
+                origin
+            ),
+            "неизменная сцена не должна перерисовываться"
+        );
+
+        // изменение сцены
+        scene.models[0].translate(Vec3::new(0.5, 0.0, 0.0));
+        assert!(renderer.render_if_changed(
+            &scene,
+            &mut canvas,
+            &mut frame_state,
+            false,
+            origin,
+            origin
+        ));
+
+        // изменение параметров рендера
+        let solid = SceneRenderer {
+            render_solid: true,
+            ..renderer
+        };
+        assert!(solid.render_if_changed(
+            &scene,
+            &mut canvas,
+            &mut frame_state,
+            false,
+            origin,
+            origin
+        ));
+
+        // сброс состояния
+        frame_state.invalidate();
+        assert!(solid.render_if_changed(
+            &scene,
+            &mut canvas,
+            &mut frame_state,
+            false,
+            origin,
+            origin
+        ));
+    }
+}
@@ -0,0 +1,73 @@
+//! Проход геометрии: заполнение буферов идентификаторов и нормалей цели рендера.
+
+use super::RenderMeshCache;
+use crate::{Point3, Polygon, RenderTarget, Vec3, library::utils};
+
+/// Записать в цель рендера идентификатор модели и нормали её видимых пикселей.
+///
+/// `depth` - собственный z-буфер прохода размером с цель рендера, общий для всех моделей сцены.
+/// В `positions` записываются **глобальные** координаты видимых точек поверхности.
+/// Запись производится в координатах холста до инвертирования по оси Y.
+pub(super) fn write_model_geometry(
+    geometry: &RenderMeshCache,
+    model_id: u32,
+    polygons: &[Polygon],
+    depth: &mut [f32],
+    positions: &mut [Option<Point3>],
+    target: &mut RenderTarget,
+) {
+    let (width, height) = (target.width(), target.height());
+    let projected_vertexes = &geometry.screen_vertexes;
+
+    for polygon in polygons {
+        for triangle in polygon.triangles() {
+            let [i0, i1, i2] = triangle;
+            let (v0, v1, v2) = (
+                projected_vertexes[i0],
+                projected_vertexes[i1],
+                projected_vertexes[i2],
+            );
+
+            // ограничивающий прямоугольник
+            let min_x = v0.x.min(v1.x.min(v2.x)).max(0.0) as usize;
+            let max_x = v0.x.max(v1.x.max(v2.x)) as usize;
+            let min_y = v0.y.min(v1.y.min(v2.y)).max(0.0) as usize;
+            let max_y = v0.y.max(v1.y.max(v2.y)) as usize;
+
+            for y in min_y..=max_y.min(height - 1) {
+                for x in min_x..=max_x.min(width - 1) {
+                    let p = Point3::new(x as f32, y as f32, 0.0);
+                    let bary = utils::barycentric_coordinates(&[v0, v1, v2], p);
+
+                    // точка на полигоне?
+                    if bary.x < 0.0 || bary.y < 0.0 || bary.z < 0.0 {
+                        continue;
+                    }
+
+                    let z = utils::interpolate_float(bary, v0.z, v1.z, v2.z);
+                    let index = y * width + x;
+                    if z <= depth[index] {
+                        continue;
+                    }
+                    depth[index] = z;
+                    positions[index] = Some(utils::interpolate_point(
+                        bary,
+                        geometry.global_vertexes[i0],
+                        geometry.global_vertexes[i1],
+                        geometry.global_vertexes[i2],
+                    ));
+
+                    let normal = match &geometry.global_normals {
+                        Some(normals) => {
+                            let normal =
+                                normals[i0] * bary.x + normals[i1] * bary.y + normals[i2] * bary.z;
+                            normal.normalize().map(Vec3::from).unwrap_or(Vec3::zero())
+                        }
+                        None => Vec3::zero(),
+                    };
+                    target.set_geometry(x, y, model_id, normal);
+                }
+            }
+        }
+    }
+}
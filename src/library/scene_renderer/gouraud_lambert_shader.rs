@@ -1,64 +1,82 @@
 use crate::{
-    Camera, Canvas, LightSource, Model, Point3, Polygon, ProjectionType, Shader, UVec3,
-    library::utils,
+    Canvas, EnvironmentLight, LightSource, Model, Point3, Polygon, UVec3, Vec3, library::utils,
 };
 
-pub struct GouraudLambertShader {
+use super::{
+    RenderMeshCache, Shader, lighting,
+    reflections::{Reflections, blend_reflection},
+};
+
+pub struct GouraudLambertShader<'a> {
     z_buffer_enabled: bool,
+    environment: Option<&'a EnvironmentLight>,
+    reflections: Option<Reflections<'a>>,
 }
 
-impl GouraudLambertShader {
-    pub fn new(z_buffer_enabled: bool) -> Self {
-        Self { z_buffer_enabled }
+impl<'a> GouraudLambertShader<'a> {
+    pub fn new(
+        z_buffer_enabled: bool,
+        environment: Option<&'a EnvironmentLight>,
+        reflections: Option<Reflections<'a>>,
+    ) -> Self {
+        Self {
+            z_buffer_enabled,
+            environment,
+            reflections,
+        }
     }
 
-    /// Считает освещённость вершины по модели Ламберта.
+    /// Считает освещённость вершины по модели Ламберта (линейный RGB без ограничения яркости).
+    ///
+    /// К свету источников добавляется рассеянный свет окружения, если оно задано.
+    /// `occlusion` - запечённое затенение окружением вершины, на долю которого
+    /// освещённость приглушается.
     fn lambert_diffuse(
+        &self,
         vertex_pos: Point3,
         vertex_normal: UVec3,
-        lights: &Vec<LightSource>,
-    ) -> egui::Color32 {
-        if lights.is_empty() {
-            return egui::Color32::BLACK;
-        }
-
-        let mut light_color = egui::Color32::BLACK;
-        // Влияние каждого источника
-        for light in lights {
-            let light_dir = (light.position - vertex_pos).normalize().unwrap();
-            let cos = vertex_normal.cos(light_dir).max(0.0);
-            light_color = light_color + light.color.gamma_multiply(light.intensity * cos);
-        }
+        occlusion: f32,
+        lights: &[LightSource],
+    ) -> Vec3 {
+        let environment_light = self.environment.map_or(Vec3::zero(), |environment| {
+            utils::color_to_vec(environment.irradiance(vertex_normal))
+        });
+        let light_color =
+            environment_light + lighting::lambert_light(vertex_pos, vertex_normal, lights);
 
-        light_color
+        light_color * (1.0 - occlusion)
     }
 }
 
-impl Shader for GouraudLambertShader {
+impl Shader for GouraudLambertShader<'_> {
     fn shade_model(
         &self,
         model: &Model,
+        geometry: &RenderMeshCache,
         polygons: &Vec<Polygon>,
-        camera: &Camera,
-        projection_type: ProjectionType,
         lights: &Vec<LightSource>,
         canvas: &mut Canvas,
     ) {
-        // матрица преобразования на экран
-        let global_to_screen_transform = camera.global_to_screen_transform(projection_type, canvas);
         // проекция вершин на экран
-        let projected_vertexes: Vec<Point3> = model
-            .mesh
-            .get_global_vertex_iter()
-            .map(|v| {
-                v.apply_transform(global_to_screen_transform)
-                    .unwrap_or(Point3::new(0.0, 0.0, -999.9))
+        let projected_vertexes = &geometry.screen_vertexes;
+        // собственное свечение не зависит от освещения и одинаково по всей модели
+        let emission = model.material.emitted_light();
+
+        // отражения считаются в вершинах и интерполируются, как и освещённость
+        let reflections = self
+            .reflections
+            .filter(|_| model.material.reflectivity > 0.0);
+        let reflectivity = reflections.map_or(0.0, |_| model.material.reflectivity);
+        let reflect = |position, normal| {
+            reflections.map_or(egui::Color32::BLACK, |reflections| {
+                reflections.sample(position, normal)
             })
-            .collect();
+        };
 
         for polygon in polygons {
-            // если четырёхугольник - билинейная интерполяция
-            if polygon.is_quad() {
+            // если плоский выпуклый четырёхугольник - билинейная интерполяция,
+            // иначе билинейная интерполяция искажает грань и нужна триангуляция
+            if polygon.is_convex_quad(&model.mesh) {
                 // индексы вершин в Mesh
                 let i0 = polygon.get_mesh_vertex_index(0);
                 let i1 = polygon.get_mesh_vertex_index(1);
@@ -72,28 +90,49 @@ impl Shader for GouraudLambertShader {
                 let v3 = projected_vertexes[i3];
 
                 // текстурные UV-координаты вершин треугольника
-                let tx0 = model.mesh.get_texture_coord(0).unwrap();
-                let tx1 = model.mesh.get_texture_coord(1).unwrap();
-                let tx2 = model.mesh.get_texture_coord(2).unwrap();
-                let tx3 = model.mesh.get_texture_coord(3).unwrap();
+                let tx0 = polygon
+                    .get_texture_coord(&model.mesh, 0)
+                    .unwrap_or((0.0, 0.0));
+                let tx1 = polygon
+                    .get_texture_coord(&model.mesh, 1)
+                    .unwrap_or((0.0, 0.0));
+                let tx2 = polygon
+                    .get_texture_coord(&model.mesh, 2)
+                    .unwrap_or((0.0, 0.0));
+                let tx3 = polygon
+                    .get_texture_coord(&model.mesh, 3)
+                    .unwrap_or((0.0, 0.0));
+                let colors = lighting::vertex_colors(model, [i0, i1, i2, i3]);
 
                 // глобальные координаты вершин
-                let gv0 = model.mesh.get_global_vertex(0);
-                let gv1 = model.mesh.get_global_vertex(1);
-                let gv2 = model.mesh.get_global_vertex(2);
-                let gv3 = model.mesh.get_global_vertex(3);
+                let gv0 = geometry.global_vertexes[i0];
+                let gv1 = geometry.global_vertexes[i1];
+                let gv2 = geometry.global_vertexes[i2];
+                let gv3 = geometry.global_vertexes[i3];
 
                 // глобальные нормали
-                let n0 = model.mesh.get_global_normal(0).unwrap();
-                let n1 = model.mesh.get_global_normal(1).unwrap();
-                let n2 = model.mesh.get_global_normal(2).unwrap();
-                let n3 = model.mesh.get_global_normal(3).unwrap();
+                let n0 = geometry.normals()[i0];
+                let n1 = geometry.normals()[i1];
+                let n2 = geometry.normals()[i2];
+                let n3 = geometry.normals()[i3];
+
+                // затенение окружением вершин
+                let occlusion0 = model.mesh.get_ambient_occlusion(i0).unwrap_or(0.0);
+                let occlusion1 = model.mesh.get_ambient_occlusion(i1).unwrap_or(0.0);
+                let occlusion2 = model.mesh.get_ambient_occlusion(i2).unwrap_or(0.0);
+                let occlusion3 = model.mesh.get_ambient_occlusion(i3).unwrap_or(0.0);
 
                 // освещённость вершин треугольника
-                let light0 = Self::lambert_diffuse(gv0, n0, lights);
-                let light1 = Self::lambert_diffuse(gv1, n1, lights);
-                let light2 = Self::lambert_diffuse(gv2, n2, lights);
-                let light3 = Self::lambert_diffuse(gv3, n3, lights);
+                let light0 = self.lambert_diffuse(gv0, n0, occlusion0, lights);
+                let light1 = self.lambert_diffuse(gv1, n1, occlusion1, lights);
+                let light2 = self.lambert_diffuse(gv2, n2, occlusion2, lights);
+                let light3 = self.lambert_diffuse(gv3, n3, occlusion3, lights);
+
+                // отражения в вершинах
+                let reflection0 = reflect(gv0, n0);
+                let reflection1 = reflect(gv1, n1);
+                let reflection2 = reflect(gv2, n2);
+                let reflection3 = reflect(gv3, n3);
 
                 // ограничивающий прямоугольник
                 let min_x = *vec![v0.x as usize, v1.x as usize, v2.x as usize, v3.x as usize]
@@ -133,56 +172,85 @@ impl Shader for GouraudLambertShader {
                             }
 
                             if self.z_buffer_enabled {
-                                let z = utils::bilerp_float(v0.z, v1.z, v2.z, v3.z, alpha, beta);
+                                let z = utils::bilerp_float(v0.z, v1.z, v3.z, v2.z, alpha, beta);
                                 if !canvas.test_and_set_z(x, y, z) {
                                     continue;
                                 }
                             }
 
                             // текстурные координаты пикселя
-                            let u = utils::bilerp_float(tx0.0, tx1.0, tx2.0, tx3.0, alpha, beta);
-                            let v = utils::bilerp_float(tx0.1, tx1.1, tx2.0, tx3.0, alpha, beta);
-                            let base_color = model.material.get_uv_color(u, v);
+                            let u = utils::bilerp_float(tx0.0, tx1.0, tx3.0, tx2.0, alpha, beta);
+                            let v = utils::bilerp_float(tx0.1, tx1.1, tx3.1, tx2.1, alpha, beta);
+                            let base_color = model.material.get_vertex_uv_color(
+                                u,
+                                v,
+                                colors.map(|[c0, c1, c2, c3]| {
+                                    utils::bilerp_color(c0, c1, c3, c2, alpha, beta)
+                                }),
+                            );
 
                             // освещённость в данной точке
                             let light =
-                                utils::bilerp_color(light0, light1, light2, light3, alpha, beta);
-                            canvas[(x, y)] = base_color * light;
+                                utils::bilerp_vec(light0, light1, light3, light2, alpha, beta);
+                            let reflection = utils::bilerp_color(
+                                reflection0,
+                                reflection1,
+                                reflection3,
+                                reflection2,
+                                alpha,
+                                beta,
+                            );
+                            let color = blend_reflection(
+                                lighting::surface_color(canvas, base_color, light),
+                                reflection,
+                                reflectivity,
+                            );
+                            canvas.set_fragment_hdr(x, y, color + emission);
                         }
                     }
                 }
             } else {
                 // иначе барицентрическая интерполяция с триангуляцией
-                let indexes: Vec<usize> = polygon.get_mesh_vertex_index_iter().collect();
-                for triangle in utils::triangulate_polygon(&indexes) {
+                for corners in polygon.corner_triangles() {
                     // индексы вершин
-                    let i0 = triangle[0];
-                    let i1 = triangle[1];
-                    let i2 = triangle[2];
+                    let [i0, i1, i2] = corners.map(|i| polygon.get_mesh_vertex_index(i));
 
                     // проекция вершин треугольника
                     let v0 = projected_vertexes[i0];
                     let v1 = projected_vertexes[i1];
                     let v2 = projected_vertexes[i2];
                     // текстурные UV-координаты вершин треугольника
-                    let tx0 = model.mesh.get_texture_coord(i0).unwrap();
-                    let tx1 = model.mesh.get_texture_coord(i1).unwrap();
-                    let tx2 = model.mesh.get_texture_coord(i2).unwrap();
+                    let [tx0, tx1, tx2] = corners.map(|i| {
+                        polygon
+                            .get_texture_coord(&model.mesh, i)
+                            .unwrap_or((0.0, 0.0))
+                    });
+                    let colors = lighting::vertex_colors(model, [i0, i1, i2]);
 
                     // глобальные координаты вершин
-                    let gv0 = model.mesh.get_global_vertex(i0);
-                    let gv1 = model.mesh.get_global_vertex(i1);
-                    let gv2 = model.mesh.get_global_vertex(i2);
+                    let gv0 = geometry.global_vertexes[i0];
+                    let gv1 = geometry.global_vertexes[i1];
+                    let gv2 = geometry.global_vertexes[i2];
 
                     // глобальные нормали
-                    let n0 = model.mesh.get_global_normal(i0).unwrap();
-                    let n1 = model.mesh.get_global_normal(i1).unwrap();
-                    let n2 = model.mesh.get_global_normal(i2).unwrap();
+                    let n0 = geometry.normals()[i0];
+                    let n1 = geometry.normals()[i1];
+                    let n2 = geometry.normals()[i2];
+
+                    // затенение окружением вершин
+                    let occlusion0 = model.mesh.get_ambient_occlusion(i0).unwrap_or(0.0);
+                    let occlusion1 = model.mesh.get_ambient_occlusion(i1).unwrap_or(0.0);
+                    let occlusion2 = model.mesh.get_ambient_occlusion(i2).unwrap_or(0.0);
 
                     // освещённость вершин треугольника
-                    let light0 = Self::lambert_diffuse(gv0, n0, lights);
-                    let light1 = Self::lambert_diffuse(gv1, n1, lights);
-                    let light2 = Self::lambert_diffuse(gv2, n2, lights);
+                    let light0 = self.lambert_diffuse(gv0, n0, occlusion0, lights);
+                    let light1 = self.lambert_diffuse(gv1, n1, occlusion1, lights);
+                    let light2 = self.lambert_diffuse(gv2, n2, occlusion2, lights);
+
+                    // отражения в вершинах
+                    let reflection0 = reflect(gv0, n0);
+                    let reflection1 = reflect(gv1, n1);
+                    let reflection2 = reflect(gv2, n2);
 
                     // описывающий прямоугольник
                     let min_x = v0.x.min(v1.x.min(v2.x)) as usize;
@@ -212,14 +280,33 @@ impl Shader for GouraudLambertShader {
                                 }
                             }
 
+                            // атрибуты вершин интерполируются с поправкой на перспективу
+                            let bary = geometry.attribute_barycentric(bary, [i0, i1, i2]);
+
                             // текстурные коодринаты пикселя
                             let u = utils::interpolate_float(bary, tx0.0, tx1.0, tx2.0);
                             let v = utils::interpolate_float(bary, tx0.1, tx1.1, tx2.1);
-                            let base_color = model.material.get_uv_color(u, v);
+                            let base_color = model.material.get_vertex_uv_color(
+                                u,
+                                v,
+                                colors
+                                    .map(|[c0, c1, c2]| utils::interpolate_color(bary, c0, c1, c2)),
+                            );
 
                             // освещённость в данной точке
-                            let light = utils::interpolate_color(bary, light0, light1, light2);
-                            canvas[(x, y)] = base_color * light;
+                            let light = utils::interpolate_vec(bary, light0, light1, light2);
+                            let reflection = utils::interpolate_color(
+                                bary,
+                                reflection0,
+                                reflection1,
+                                reflection2,
+                            );
+                            let color = blend_reflection(
+                                lighting::surface_color(canvas, base_color, light),
+                                reflection,
+                                reflectivity,
+                            );
+                            canvas.set_fragment_hdr(x, y, color + emission);
                         }
                     }
                 }
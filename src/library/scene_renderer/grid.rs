@@ -0,0 +1,258 @@
+//! Отрисовка опорной сетки и координатных осей.
+
+use std::fmt::Display;
+
+use crate::{Canvas, Point3, Transform3D, library::utils};
+use egui::{Color32, Pos2};
+
+/// Плоскость, в которой рисуется опорная сетка.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub enum GridPlane {
+    /// Плоскость XY (перпендикулярна оси Z).
+    XY,
+    /// Плоскость XZ (пол, перпендикулярна оси Y).
+    #[default]
+    XZ,
+    /// Плоскость YZ (перпендикулярна оси X).
+    YZ,
+}
+
+impl GridPlane {
+    /// Перевести координаты `(a, b)` на плоскости в точку 3D пространства.
+    fn point(&self, a: f32, b: f32) -> Point3 {
+        match self {
+            Self::XY => Point3::new(a, b, 0.0),
+            Self::XZ => Point3::new(a, 0.0, b),
+            Self::YZ => Point3::new(0.0, a, b),
+        }
+    }
+}
+
+impl Display for GridPlane {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::XY => f.write_str("XY"),
+            Self::XZ => f.write_str("XZ"),
+            Self::YZ => f.write_str("YZ"),
+        }
+    }
+}
+
+/// Параметры отрисовки опорной сетки и координатных осей.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GridOptions {
+    /// Рисовать ли опорную сетку.
+    pub show_grid: bool,
+    /// Рисовать ли координатные оси.
+    pub show_axes: bool,
+    /// Подписывать ли концы координатных осей.
+    pub show_axis_labels: bool,
+    /// Плоскость сетки.
+    pub plane: GridPlane,
+    /// Половина размера сетки: сетка занимает [-extent, extent] по обеим осям плоскости.
+    pub extent: f32,
+    /// Расстояние между соседними линиями сетки.
+    pub spacing: f32,
+    /// Каждая какая линия сетки выделяется как основная. 0 - не выделять.
+    pub major_every: usize,
+    /// Подстраивать ли шаг и размер сетки под расстояние до камеры (степенями 10).
+    pub adaptive: bool,
+    /// Длина координатных осей.
+    pub axis_length: f32,
+    /// Цвет обычных линий сетки.
+    pub grid_color: Color32,
+    /// Цвет основных линий сетки.
+    pub major_grid_color: Color32,
+    /// Цвет оси X.
+    pub x_axis_color: Color32,
+    /// Цвет оси Y.
+    pub y_axis_color: Color32,
+    /// Цвет оси Z.
+    pub z_axis_color: Color32,
+}
+
+impl Default for GridOptions {
+    fn default() -> Self {
+        Self {
+            show_grid: false,
+            show_axes: true,
+            show_axis_labels: true,
+            plane: GridPlane::default(),
+            extent: 10.0,
+            spacing: 1.0,
+            major_every: 5,
+            adaptive: true,
+            axis_length: 5.0,
+            grid_color: Color32::from_gray(110),
+            major_grid_color: Color32::from_gray(80),
+            x_axis_color: Color32::RED,
+            y_axis_color: Color32::GREEN,
+            z_axis_color: Color32::BLUE,
+        }
+    }
+}
+
+impl GridOptions {
+    /// Масштаб сетки (степень 10) для камеры на расстоянии `camera_distance` от начала координат.
+    ///
+    /// Без адаптивности всегда 1. Иначе подбирается так, чтобы сетка занимала примерно тот же экран,
+    /// что и при расстоянии `extent`.
+    pub fn scale_for_distance(&self, camera_distance: f32) -> f32 {
+        if !self.adaptive || camera_distance <= self.extent {
+            return 1.0;
+        }
+        10.0_f32.powf((camera_distance / self.extent).log10().floor())
+    }
+
+    /// Нарисовать опорную сетку и координатные оси.
+    ///
+    /// `global_to_screen_transform` - преобразование из глобальных координат на экран;
+    /// `camera_distance` - расстояние от камеры до начала координат, нужно для адаптивной сетки;
+    pub(super) fn draw(
+        &self,
+        canvas: &mut Canvas,
+        global_to_screen_transform: Transform3D,
+        camera_distance: f32,
+    ) {
+        if self.show_grid {
+            self.draw_grid(canvas, global_to_screen_transform, camera_distance);
+        }
+        if self.show_axes {
+            self.draw_axes(canvas, global_to_screen_transform);
+        }
+    }
+
+    /// Отрисовка опорной сетки.
+    fn draw_grid(
+        &self,
+        canvas: &mut Canvas,
+        global_to_screen_transform: Transform3D,
+        camera_distance: f32,
+    ) {
+        debug_assert!(self.spacing > 0.0, "шаг сетки должен быть положительным");
+        debug_assert!(self.extent > 0.0, "размер сетки должен быть положительным");
+
+        let scale = self.scale_for_distance(camera_distance);
+        let spacing = self.spacing * scale;
+        let extent = self.extent * scale;
+        let half_lines = (extent / spacing).floor() as i32;
+
+        for i in -half_lines..=half_lines {
+            // линии по центру перекрываются осями
+            if i == 0 && self.show_axes {
+                continue;
+            }
+
+            let color = if self.major_every > 0 && i % self.major_every as i32 == 0 {
+                self.major_grid_color
+            } else {
+                self.grid_color
+            };
+
+            let offset = i as f32 * spacing;
+            utils::render_line(
+                global_to_screen_transform,
+                self.plane.point(offset, -extent),
+                self.plane.point(offset, extent),
+                color,
+                canvas,
+            );
+            utils::render_line(
+                global_to_screen_transform,
+                self.plane.point(-extent, offset),
+                self.plane.point(extent, offset),
+                color,
+                canvas,
+            );
+        }
+    }
+
+    /// Отрисовка глобальной координатной системы.
+    fn draw_axes(&self, canvas: &mut Canvas, global_to_screen_transform: Transform3D) {
+        let origin = Point3::zero();
+        let axes = [
+            (
+                Point3::new(self.axis_length, 0.0, 0.0),
+                self.x_axis_color,
+                'X',
+            ),
+            (
+                Point3::new(0.0, self.axis_length, 0.0),
+                self.y_axis_color,
+                'Y',
+            ),
+            (
+                Point3::new(0.0, 0.0, self.axis_length),
+                self.z_axis_color,
+                'Z',
+            ),
+        ];
+
+        for (axis_end, color, label) in axes {
+            utils::render_line(global_to_screen_transform, origin, axis_end, color, canvas);
+
+            if self.show_axis_labels
+                && let Ok(end) = axis_end.apply_transform(global_to_screen_transform)
+            {
+                draw_axis_label(canvas, Pos2::new(end.x + 6.0, end.y + 6.0), label, color);
+            }
+        }
+    }
+}
+
+/// Размер буквы подписи оси в пикселях.
+const LABEL_SIZE: f32 = 8.0;
+
+/// Нарисовать букву `X`, `Y` или `Z` отрезками, `pos` - левый нижний угол буквы на холсте.
+///
+/// Холст в конце отрисовки переворачивается по Y, поэтому здесь ось Y направлена вверх.
+fn draw_axis_label(canvas: &mut Canvas, pos: Pos2, label: char, color: Color32) {
+    let s = LABEL_SIZE;
+    let p = |x: f32, y: f32| Pos2::new(pos.x + x * s, pos.y + y * s);
+
+    let strokes: &[(Pos2, Pos2)] = match label {
+        'X' => &[(p(0.0, 0.0), p(1.0, 1.0)), (p(0.0, 1.0), p(1.0, 0.0))],
+        'Y' => &[
+            (p(0.0, 1.0), p(0.5, 0.5)),
+            (p(1.0, 1.0), p(0.5, 0.5)),
+            (p(0.5, 0.5), p(0.5, 0.0)),
+        ],
+        'Z' => &[
+            (p(0.0, 1.0), p(1.0, 1.0)),
+            (p(1.0, 1.0), p(0.0, 0.0)),
+            (p(0.0, 0.0), p(1.0, 0.0)),
+        ],
+        _ => &[],
+    };
+
+    for &(start, end) in strokes {
+        canvas.draw_sharp_line(start, end, color);
+    }
+}
+
+#[cfg(test)]
+mod grid_tests {
+    use super::*;
+
+    #[test]
+    fn test_scale_for_distance() {
+        let options = GridOptions::default();
+        assert_eq!(options.scale_for_distance(5.0), 1.0);
+        assert_eq!(options.scale_for_distance(50.0), 1.0);
+        assert_eq!(options.scale_for_distance(150.0), 10.0);
+        assert_eq!(options.scale_for_distance(1500.0), 100.0);
+
+        let fixed = GridOptions {
+            adaptive: false,
+            ..Default::default()
+        };
+        assert_eq!(fixed.scale_for_distance(1500.0), 1.0);
+    }
+
+    #[test]
+    fn test_grid_plane_point() {
+        assert_eq!(GridPlane::XY.point(1.0, 2.0), Point3::new(1.0, 2.0, 0.0));
+        assert_eq!(GridPlane::XZ.point(1.0, 2.0), Point3::new(1.0, 0.0, 2.0));
+        assert_eq!(GridPlane::YZ.point(1.0, 2.0), Point3::new(0.0, 1.0, 2.0));
+    }
+}
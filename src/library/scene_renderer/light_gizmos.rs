@@ -0,0 +1,175 @@
+//! Отладочная отрисовка источников света.
+
+use egui::{Color32, Pos2};
+
+use crate::{
+    Canvas, LayerMask, LightSource, Point3, Scene, Transform3D, UVec3, Vec3, library::utils,
+};
+
+/// Количество отрезков в окружности каркасной сферы.
+const SPHERE_SEGMENTS: usize = 24;
+
+/// Параметры отладочной отрисовки источников света.
+///
+/// Источники света точечные, поэтому у них нет собственного направления, конуса или
+/// радиуса затухания: направление показывается стрелками к освещаемым моделям.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LightGizmoOptions {
+    /// Рисовать ли источники кружками их цвета (под моделями).
+    pub show_lights: bool,
+    /// Рисовать ли выключенные источники серыми кружками.
+    pub show_disabled: bool,
+    /// Радиус каркасных сфер вокруг источников в глобальных координатах, 0 - без сфер.
+    pub sphere_radius: f32,
+    /// Рисовать ли поверх моделей стрелки от включённых источников к центрам видимых моделей.
+    pub show_directions: bool,
+}
+
+impl Default for LightGizmoOptions {
+    fn default() -> Self {
+        Self {
+            show_lights: true,
+            show_disabled: false,
+            sphere_radius: 0.0,
+            show_directions: false,
+        }
+    }
+}
+
+impl LightGizmoOptions {
+    /// Нарисовать кружки источников света. Рисуется до моделей, поэтому модели их перекрывают.
+    pub(super) fn draw_markers(
+        &self,
+        lights: &[LightSource],
+        layer_mask: LayerMask,
+        global_to_screen_transform: Transform3D,
+        canvas: &mut Canvas,
+    ) {
+        if !self.show_lights {
+            return;
+        }
+        for light in lights
+            .iter()
+            .filter(|light| light.layers.intersects(layer_mask))
+        {
+            let color = match (light.enabled, self.show_disabled) {
+                (true, _) => light.color,
+                (false, true) => Color32::DARK_GRAY,
+                (false, false) => continue,
+            };
+            let light_pos = light.position.apply_transform(global_to_screen_transform);
+            if let Ok(light_pos) = light_pos {
+                let pos = Pos2::new(light_pos.x, light_pos.y);
+                let radius = utils::lerp_float(6.0, 1.0, (light_pos.z + 1.0) / 2.0);
+                if pos.x < canvas.width() as f32 && pos.y < canvas.height() as f32 {
+                    canvas.circle_filled(pos, radius, color);
+                }
+            } else {
+                #[cfg(debug_assertions)]
+                eprintln!(
+                    "Warning: не удалось вывести свет на экран из-за ошибки: {}",
+                    light_pos.unwrap_err()
+                );
+            }
+        }
+    }
+
+    /// Нарисовать сферы и стрелки направлений включённых источников поверх изображения.
+    pub(super) fn draw_overlay(
+        &self,
+        scene: &Scene,
+        layer_mask: LayerMask,
+        global_to_screen_transform: Transform3D,
+        canvas: &mut Canvas,
+    ) {
+        for light in scene
+            .enabled_lights()
+            .filter(|light| light.layers.intersects(layer_mask))
+        {
+            if self.sphere_radius > 0.0 {
+                draw_wire_sphere(
+                    light.position,
+                    self.sphere_radius,
+                    light.color,
+                    global_to_screen_transform,
+                    canvas,
+                );
+            }
+
+            if self.show_directions {
+                for model in scene.models.iter().filter(|model| {
+                    model.flags.visible && model.flags.layers.intersects(layer_mask)
+                }) {
+                    let (min, max) = model.global_bounds();
+                    let target = min + (max - min) * 0.5;
+                    draw_arrow(
+                        light.position,
+                        target,
+                        light.color,
+                        global_to_screen_transform,
+                        canvas,
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Каркасная сфера из трёх окружностей в координатных плоскостях.
+fn draw_wire_sphere(
+    center: Point3,
+    radius: f32,
+    color: Color32,
+    global_to_screen_transform: Transform3D,
+    canvas: &mut Canvas,
+) {
+    let axes = [
+        (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0)),
+        (Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 0.0, 1.0)),
+        (Vec3::new(0.0, 0.0, 1.0), Vec3::new(1.0, 0.0, 0.0)),
+    ];
+    for (u, v) in axes {
+        let point = |i: usize| {
+            let angle = i as f32 / SPHERE_SEGMENTS as f32 * std::f32::consts::TAU;
+            center + (u * angle.cos() + v * angle.sin()) * radius
+        };
+        for i in 0..SPHERE_SEGMENTS {
+            utils::render_line(
+                global_to_screen_transform,
+                point(i),
+                point(i + 1),
+                color,
+                canvas,
+            );
+        }
+    }
+}
+
+/// Стрелка от `start` к `end` с наконечником длиной в пятую часть стрелки.
+fn draw_arrow(
+    start: Point3,
+    end: Point3,
+    color: Color32,
+    global_to_screen_transform: Transform3D,
+    canvas: &mut Canvas,
+) {
+    let Ok(direction) = (end - start).normalize() else {
+        return;
+    };
+    utils::render_line(global_to_screen_transform, start, end, color, canvas);
+
+    // наконечник в плоскости, проходящей через стрелку и вертикаль (или ось x для вертикальных стрелок)
+    let reference = if direction.dot(UVec3::up()).abs() > 0.99 {
+        Vec3::new(1.0, 0.0, 0.0)
+    } else {
+        Vec3::new(0.0, 1.0, 0.0)
+    };
+    let Ok(side) = Vec3::from(direction).cross(reference).normalize() else {
+        return;
+    };
+    let head = (end - start).length() * 0.2;
+    for sign in [-1.0, 1.0] {
+        let tip = end + (Vec3::from(direction) * -1.0 + Vec3::from(side) * (0.5 * sign)) * head;
+        utils::render_line(global_to_screen_transform, end, tip, color, canvas);
+    }
+}
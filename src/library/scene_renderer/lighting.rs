@@ -0,0 +1,38 @@
+//! Освещённость поверхностей в линейном RGB без ограничения яркости.
+
+use egui::Color32;
+
+use crate::{Canvas, LightSource, Model, Point3, UVec3, Vec3, library::utils};
+
+/// Освещённость точки `position` с нормалью `normal` точечными источниками по модели Ламберта.
+///
+/// Каналы вектора - яркость света (1 соответствует 255), свет нескольких источников
+/// складывается без ограничения.
+pub fn lambert_light(position: Point3, normal: UVec3, lights: &[LightSource]) -> Vec3 {
+    lights.iter().fold(Vec3::zero(), |light_color, light| {
+        let light_dir = (light.position - position).normalize().unwrap();
+        let cos = normal.dot(light_dir).max(0.0);
+        light_color + utils::color_to_vec(light.color) * (light.intensity * cos)
+    })
+}
+
+/// Цвет поверхности с цветом материала `base_color` при освещённости `light`.
+///
+/// Если холст не в HDR-режиме, освещённость сначала обрезается до 1, как при сложении
+/// 8-битных цветов, иначе яркость выше 1 сохраняется до тональной компрессии.
+pub fn surface_color(canvas: &Canvas, base_color: Color32, light: Vec3) -> Vec3 {
+    let light = if canvas.hdr_options().is_some() {
+        light
+    } else {
+        Vec3::new(light.x.min(1.0), light.y.min(1.0), light.z.min(1.0))
+    };
+    utils::modulate(utils::color_to_vec(base_color), light)
+}
+
+/// Цвета вершин `indexes` Mesh'а модели, если они заданы.
+pub fn vertex_colors<const N: usize>(model: &Model, indexes: [usize; N]) -> Option<[Color32; N]> {
+    if !model.mesh.has_vertex_colors() {
+        return None;
+    }
+    Some(indexes.map(|i| model.mesh.get_vertex_color(i).unwrap()))
+}
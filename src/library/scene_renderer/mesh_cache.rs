@@ -0,0 +1,232 @@
+//! Кэш преобразованной геометрии модели на один кадр.
+
+use crate::{
+    HVec3, Mesh, Point3, Polygon, ProjectionType, Transform3D, UVec3, Vec3, library::utils,
+};
+
+/// Вершины и нормали Mesh'а, преобразованные в глобальные и экранные координаты.
+///
+/// Строится один раз на модель за кадр, после чего используется отсечением граней
+/// и всеми шейдерами вместо повторного пересчёта координат.
+#[derive(Debug, Clone)]
+pub struct RenderMeshCache {
+    /// Матрица преобразования из глобальных координат в экранные, с которой построен кэш.
+    pub global_to_screen_transform: Transform3D,
+    /// Вершины Mesh'а в **глобальных** координатах.
+    pub global_vertexes: Vec<Point3>,
+    /// Нормали вершин Mesh'а в **глобальных** координатах, если они есть.
+    pub global_normals: Option<Vec<UVec3>>,
+    /// Вершины Mesh'а в экранных координатах, `z` - глубина для z-буфера (см. [`utils::screen_point`]).
+    ///
+    /// Вершины, которые не удалось спроецировать (позади камеры), заменяются на точку
+    /// далеко за пределами видимого объёма.
+    pub screen_vertexes: Vec<Point3>,
+    /// Тип проекции, с которой построен кэш.
+    pub projection_type: ProjectionType,
+}
+
+impl RenderMeshCache {
+    /// Преобразовать геометрию Mesh'а для отрисовки с матрицей `global_to_screen_transform`
+    /// проекции типа `projection_type`.
+    pub fn new(
+        mesh: &Mesh,
+        global_to_screen_transform: Transform3D,
+        projection_type: ProjectionType,
+    ) -> Self {
+        let mut hvecs: Vec<HVec3> = mesh.get_local_vertex_iter().map(HVec3::from).collect();
+        mesh.local_frame
+            .local_to_global_matrix()
+            .apply_to_slice_in_place(&mut hvecs);
+        let global_vertexes = hvecs
+            .iter()
+            .map(|&hvec| Point3::new(hvec.x, hvec.y, hvec.z))
+            .collect();
+
+        global_to_screen_transform.apply_to_slice_in_place(&mut hvecs);
+        let screen_vertexes = hvecs
+            .into_iter()
+            .map(|hvec| {
+                utils::screen_point(hvec, projection_type).unwrap_or(Point3::new(0.0, 0.0, -999.9))
+            })
+            .collect();
+
+        Self {
+            global_to_screen_transform,
+            global_vertexes,
+            global_normals: mesh.get_global_normals_iter().map(Iterator::collect),
+            screen_vertexes,
+            projection_type,
+        }
+    }
+
+    /// Барицентрические координаты для интерполяции атрибутов вершин `indexes` треугольника
+    /// (текстурных координат, нормалей, цветов) в пикселе с экранными барицентрическими
+    /// координатами `bary`.
+    ///
+    /// При перспективной проекции атрибуты меняются по экрану нелинейно: линейно меняются
+    /// `attr / w` и `1 / w` (глубина экранных вершин), поэтому интерполируется `attr / w`
+    /// и делится на интерполированное `1 / w`. При параллельных проекциях `bary` не меняется.
+    pub fn attribute_barycentric(&self, bary: Point3, indexes: [usize; 3]) -> Point3 {
+        if self.projection_type != ProjectionType::Perspective {
+            return bary;
+        }
+
+        let [w0, w1, w2] = indexes.map(|index| self.screen_vertexes[index].z);
+        let (a, b, c) = (bary.x * w0, bary.y * w1, bary.z * w2);
+        let sum = a + b + c;
+        if !sum.is_normal() {
+            return bary;
+        }
+        Point3::new(a / sum, b / sum, c / sum)
+    }
+
+    /// Копия кэша с развёрнутыми нормалями вершин, для освещения обратных граней.
+    pub fn with_flipped_normals(&self) -> Self {
+        Self {
+            global_normals: self
+                .global_normals
+                .as_ref()
+                .map(|normals| normals.iter().map(|&normal| -normal).collect()),
+            ..self.clone()
+        }
+    }
+
+    /// Нормаль полигона по его **глобальным** вершинам.
+    ///
+    /// Плоскость определяется самими вершинами, а не нормалями вершин, поэтому нормаль
+    /// верна и после обрезки и деформации геометрии. Направление согласуется с нормалями
+    /// вершин, если они есть, иначе определяется порядком обхода.
+    /// Для вырожденного полигона возвращает `None`.
+    pub fn polygon_normal(&self, polygon: &Polygon) -> Option<UVec3> {
+        let vertexes: Vec<Point3> = polygon
+            .get_mesh_vertex_index_iter()
+            .map(|index| self.global_vertexes[index])
+            .collect();
+        let normal = utils::newell_normal(&vertexes)?;
+
+        if let Some(normals) = &self.global_normals {
+            let vertex_normals = polygon
+                .get_mesh_vertex_index_iter()
+                .fold(Vec3::zero(), |acc, index| acc + normals[index]);
+            if Vec3::from(normal).dot(vertex_normals) < 0.0 {
+                return Some(-normal);
+            }
+        }
+        Some(normal)
+    }
+
+    /// Центр полигона (среднее его вершин) в **глобальных** координатах.
+    pub fn polygon_center(&self, polygon: &Polygon) -> Point3 {
+        let sum = polygon
+            .get_mesh_vertex_index_iter()
+            .fold(Vec3::zero(), |acc, index| {
+                acc + Vec3::from(self.global_vertexes[index])
+            });
+        Point3::from(sum / polygon.vertex_count().max(1) as f32)
+    }
+
+    /// Глобальные нормали вершин.
+    ///
+    /// Паникует, если у Mesh'а нет нормалей.
+    pub fn normals(&self) -> &[UVec3] {
+        self.global_normals
+            .as_deref()
+            .expect("у Mesh'а должны быть нормали")
+    }
+}
+
+#[cfg(test)]
+mod mesh_cache_tests {
+    use super::*;
+    use crate::{Camera, Canvas, Model, ProjectionType};
+
+    #[test]
+    fn test_cache_matches_mesh_transforms() {
+        let camera = Camera::default();
+        let canvas = Canvas::new(300, 200);
+        let mut model = Model::from_mesh(Mesh::icosahedron());
+        model.set_position(camera.get_position() + camera.forward() * 5.0);
+        model.uniform_scale(1.5);
+
+        let transform = camera.global_to_screen_transform(ProjectionType::Perspective, &canvas);
+        let cache = RenderMeshCache::new(&model.mesh, transform, ProjectionType::Perspective);
+
+        let normals = cache.normals();
+        for (i, vertex) in model.mesh.get_global_vertex_iter().enumerate() {
+            assert!(cache.global_vertexes[i].approx_equal(vertex, 1e-4));
+            assert!(normals[i].approx_equal(model.mesh.get_global_normal(i).unwrap(), 1e-4));
+
+            let expected = vertex.apply_transform(transform).unwrap();
+            let screen = cache.screen_vertexes[i];
+            assert!(
+                (screen.x - expected.x).abs() < 1e-3 && (screen.y - expected.y).abs() < 1e-3,
+                "экранная вершина {} не совпадает с {}",
+                screen,
+                expected
+            );
+
+            // глубина - величина, обратная глубине вершины в пространстве камеры
+            let view_depth = (vertex - camera.get_position()).dot(camera.forward().into());
+            assert!(
+                (1.0 / screen.z - view_depth).abs() < 1e-3,
+                "глубина {} не соответствует {}",
+                screen.z,
+                view_depth
+            );
+        }
+    }
+
+    #[test]
+    fn test_attribute_barycentric_is_perspective_correct() {
+        let camera = Camera::default();
+        let canvas = Canvas::new(300, 200);
+        // треугольник, уходящий в глубину: вершины на разном расстоянии от камеры
+        let vertexes = vec![
+            Point3::new(-1.0, -1.0, 0.0),
+            Point3::new(1.0, -1.0, 6.0),
+            Point3::new(0.0, 1.0, 3.0),
+        ];
+        let mut model = Model::from_mesh(Mesh::from_polygons(
+            vertexes,
+            vec![Polygon::from_list(&[0, 1, 2])],
+        ));
+        model.set_position(camera.get_position() + camera.forward() * 5.0);
+
+        let transform = camera.global_to_screen_transform(ProjectionType::Perspective, &canvas);
+        let cache = RenderMeshCache::new(&model.mesh, transform, ProjectionType::Perspective);
+
+        // центр треугольника на экране
+        let bary = Point3::new(1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0);
+        let [s0, s1, s2] = [0, 1, 2].map(|i| cache.screen_vertexes[i]);
+        let screen_center = utils::interpolate_point(bary, s0, s1, s2);
+
+        // точка поверхности, восстановленная по исправленным координатам, должна
+        // проецироваться обратно в тот же пиксель, в отличие от аффинной интерполяции
+        let corrected = cache.attribute_barycentric(bary, [0, 1, 2]);
+        let [g0, g1, g2] = [0, 1, 2].map(|i| cache.global_vertexes[i]);
+        let projected = utils::interpolate_point(corrected, g0, g1, g2)
+            .apply_transform(transform)
+            .unwrap();
+        assert!(
+            (projected.x - screen_center.x).abs() < 1e-2
+                && (projected.y - screen_center.y).abs() < 1e-2,
+            "{} не совпадает с {}",
+            projected,
+            screen_center
+        );
+
+        let affine = utils::interpolate_point(bary, g0, g1, g2)
+            .apply_transform(transform)
+            .unwrap();
+        assert!((affine.x - screen_center.x).abs() + (affine.y - screen_center.y).abs() > 1.0);
+
+        // при параллельной проекции координаты не меняются
+        let transform = camera.global_to_screen_transform(ProjectionType::Parallel, &canvas);
+        let cache = RenderMeshCache::new(&model.mesh, transform, ProjectionType::Parallel);
+        assert!(
+            cache
+                .attribute_barycentric(bary, [0, 1, 2])
+                .approx_equal(bary, 1e-6)
+        );
+    }
+}
@@ -1,7 +1,6 @@
-use crate::{
-    Camera, Canvas, LightSource, Model, Point3, Polygon, ProjectionType, Shader, UVec3, Vec3,
-    library::utils,
-};
+use crate::{Canvas, LightSource, Model, Point3, Polygon, Vec3, library::utils};
+
+use super::{RenderMeshCache, Shader};
 
 pub struct NormalsShader {
     vertex_normal_color: egui::Color32,
@@ -20,17 +19,15 @@ impl NormalsShader {
 impl Shader for NormalsShader {
     fn shade_model(
         &self,
-        model: &Model,
+        _model: &Model,
+        geometry: &RenderMeshCache,
         polygons: &Vec<Polygon>,
-        camera: &Camera,
-        projection_type: ProjectionType,
         _lights: &Vec<LightSource>,
         canvas: &mut Canvas,
     ) {
-        // матрица преобразования на экран
-        let global_to_screen_transform = camera.global_to_screen_transform(projection_type, canvas);
-        let global_normals: Vec<UVec3> = model.mesh.get_global_normals_iter().unwrap().collect();
-        let global_positions: Vec<Point3> = model.mesh.get_global_vertex_iter().collect();
+        let global_to_screen_transform = geometry.global_to_screen_transform;
+        let global_normals = geometry.normals();
+        let global_positions = &geometry.global_vertexes;
 
         // индексы используемых нормалей
         let mut indexes: Vec<usize> = polygons
@@ -0,0 +1,260 @@
+//! Обводка контуров моделей в экранном пространстве.
+
+use std::fmt::Display;
+
+use egui::Color32;
+
+use crate::{BufferView, Camera, Canvas, RenderTarget, Scene, Vec3};
+
+use super::SceneRenderer;
+
+/// Какие контуры обводятся после отрисовки кадра.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub enum OutlineMode {
+    /// Без обводки.
+    #[default]
+    None,
+    /// Обводка силуэтов выделенных моделей цветом [`SceneRenderer::selection_color`]
+    /// по буферу идентификаторов.
+    Selection,
+    /// Контуры всех моделей по разрывам идентификаторов, глубины и нормалей (как в тун-шейдинге).
+    Contours,
+}
+
+impl Display for OutlineMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::None => f.write_str("Отсутствует"),
+            Self::Selection => f.write_str("Выделенные модели"),
+            Self::Contours => f.write_str("Контуры"),
+        }
+    }
+}
+
+/// Параметры обводки контуров.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OutlineOptions {
+    /// Какие контуры обводятся.
+    pub mode: OutlineMode,
+    /// Толщина обводки в пикселях.
+    pub width: usize,
+    /// Цвет контуров в режиме [`OutlineMode::Contours`].
+    pub color: Color32,
+    /// Относительный скачок расстояния до камеры между соседними пикселями,
+    /// начиная с которого между ними проходит контур.
+    pub depth_threshold: f32,
+    /// Угол между нормалями соседних пикселей в радианах, начиная с которого
+    /// между ними проходит контур.
+    pub normal_threshold: f32,
+}
+
+impl Default for OutlineOptions {
+    fn default() -> Self {
+        Self {
+            mode: OutlineMode::default(),
+            width: 2,
+            color: Color32::BLACK,
+            depth_threshold: 0.1,
+            normal_threshold: 60_f32.to_radians(),
+        }
+    }
+}
+
+/// Вспомогательные буферы кадра, по которым ищутся контуры.
+struct OutlineBuffers<'a> {
+    width: usize,
+    ids: &'a [u32],
+    normals: &'a [Vec3],
+    distances: &'a [f32],
+}
+
+impl OutlineBuffers<'_> {
+    /// Проходит ли контур между пикселями с индексами `a` и `b`.
+    fn is_contour(&self, a: usize, b: usize, options: &OutlineOptions) -> bool {
+        if self.ids[a] != self.ids[b] {
+            return true;
+        }
+        if self.ids[a] == RenderTarget::NO_ID {
+            return false;
+        }
+
+        let (da, db) = (self.distances[a], self.distances[b]);
+        if (da - db).abs() > options.depth_threshold * da.min(db) {
+            return true;
+        }
+        // у моделей без нормалей нулевые нормали, по ним контуры не ищутся
+        let (na, nb) = (self.normals[a], self.normals[b]);
+        na != Vec3::zero() && nb != Vec3::zero() && na.dot(nb) < options.normal_threshold.cos()
+    }
+}
+
+impl SceneRenderer {
+    /// Обвести контуры на холсте (до переворота) по вспомогательному проходу геометрии.
+    pub(super) fn draw_outlines(&self, scene: &Scene, camera: &Camera, canvas: &mut Canvas) {
+        let options = &self.outline;
+        if options.mode == OutlineMode::None || options.width == 0 {
+            return;
+        }
+
+        let (width, height) = (canvas.width(), canvas.height());
+        let mut target = RenderTarget::new(width, height);
+        let eye = camera.get_position();
+        let distances: Vec<f32> = self
+            .write_geometry_buffers(scene, camera, &mut target)
+            .into_iter()
+            .map(|position| position.map_or(f32::INFINITY, |position| (position - eye).length()))
+            .collect();
+        let (Some(BufferView::Id(ids)), Some(BufferView::Vector(normals))) = (
+            target.buffer(RenderTarget::ID_BUFFER),
+            target.buffer(RenderTarget::NORMAL_BUFFER),
+        ) else {
+            return;
+        };
+        let buffers = OutlineBuffers {
+            width,
+            ids,
+            normals,
+            distances: &distances,
+        };
+        let is_selected =
+            |id: u32| id != RenderTarget::NO_ID && scene.models[id as usize].flags.selected;
+        if options.mode == OutlineMode::Selection && !ids.iter().any(|&id| is_selected(id)) {
+            return;
+        }
+
+        let radius = options.width as isize;
+        let (xs, ys) = canvas.scissor_bounds();
+        for y in ys {
+            for x in xs.clone() {
+                let index = y * width + x;
+                let mut outlined = false;
+                'window: for dy in -radius..=radius {
+                    for dx in -radius..=radius {
+                        let (nx, ny) = (x as isize + dx, y as isize + dy);
+                        if nx < 0 || ny < 0 || nx >= width as isize || ny >= height as isize {
+                            continue;
+                        }
+                        let neighbor = ny as usize * buffers.width + nx as usize;
+                        outlined = match options.mode {
+                            // обводка снаружи силуэта выделенной модели
+                            OutlineMode::Selection => {
+                                is_selected(ids[neighbor]) && ids[neighbor] != ids[index]
+                            }
+                            OutlineMode::Contours => {
+                                // контур рисуется с одной стороны разрыва
+                                neighbor > index && buffers.is_contour(index, neighbor, options)
+                            }
+                            OutlineMode::None => false,
+                        };
+                        if outlined {
+                            break 'window;
+                        }
+                    }
+                }
+
+                if outlined {
+                    canvas[(x, y)] = match options.mode {
+                        OutlineMode::Selection => self.selection_color,
+                        _ => options.color,
+                    };
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod outline_tests {
+    use super::*;
+    use crate::{Mesh, Model, Point3};
+
+    /// Сцена с одним кубом перед камерой по умолчанию.
+    fn cube_scene() -> Scene {
+        let mut cube = Model::from_mesh(Mesh::hexahedron());
+        cube.set_position(Point3::new(0.0, 0.0, -7.0));
+        Scene {
+            models: vec![cube],
+            ..Default::default()
+        }
+    }
+
+    fn render(renderer: &SceneRenderer, scene: &Scene) -> Canvas {
+        let mut canvas = Canvas::new(100, 100);
+        renderer.render(scene, &mut canvas, false, Point3::zero(), Point3::zero());
+        canvas
+    }
+
+    #[test]
+    fn test_selection_outline() {
+        let mut scene = cube_scene();
+        let mut renderer = SceneRenderer {
+            render_wireframe: false,
+            render_solid: true,
+            projection_type: crate::ProjectionType::Perspective,
+            ..Default::default()
+        };
+        renderer.outline.mode = OutlineMode::Selection;
+        renderer.selection_color = Color32::GREEN;
+        renderer.grid_options.show_grid = false;
+        renderer.grid_options.show_axes = false;
+
+        let count = |canvas: &Canvas| {
+            canvas
+                .pixels()
+                .iter()
+                .filter(|&&color| color == Color32::GREEN)
+                .count()
+        };
+        assert_eq!(
+            count(&render(&renderer, &scene)),
+            0,
+            "без выделения нет обводки"
+        );
+
+        scene.models[0].flags.selected = true;
+        let canvas = render(&renderer, &scene);
+        assert!(count(&canvas) > 0, "выделенная модель обводится");
+        assert_ne!(
+            canvas[(50, 50)],
+            Color32::GREEN,
+            "обводка снаружи силуэта, а не поверх модели"
+        );
+    }
+
+    #[test]
+    fn test_contours_follow_silhouette() {
+        let scene = cube_scene();
+        let mut renderer = SceneRenderer {
+            render_wireframe: false,
+            render_solid: true,
+            projection_type: crate::ProjectionType::Perspective,
+            ..Default::default()
+        };
+        renderer.grid_options.show_grid = false;
+        renderer.grid_options.show_axes = false;
+        let plain = render(&renderer, &scene);
+
+        renderer.outline = OutlineOptions {
+            mode: OutlineMode::Contours,
+            width: 1,
+            color: Color32::RED,
+            ..Default::default()
+        };
+        let outlined = render(&renderer, &scene);
+        let changed: Vec<usize> = (0..plain.pixels().len())
+            .filter(|&i| plain.pixels()[i] != outlined.pixels()[i])
+            .collect();
+        assert!(!changed.is_empty(), "контуры нарисованы");
+        assert!(
+            changed
+                .iter()
+                .all(|&i| outlined.pixels()[i] == Color32::RED),
+            "меняются только пиксели контура"
+        );
+        assert_eq!(
+            outlined[(50, 50)],
+            plain[(50, 50)],
+            "внутри грани контура нет"
+        );
+    }
+}
@@ -1,80 +1,83 @@
 use crate::{
-    Camera, Canvas, LightSource, Model, Point3, Polygon, ProjectionType, Shader, UVec3,
-    library::utils,
+    Canvas, EnvironmentLight, LightSource, Model, Point3, Polygon, UVec3, Vec3, library::utils,
 };
 
-pub struct PhongToonShading {
+use super::{
+    RenderMeshCache, Shader, lighting,
+    reflections::{Reflections, blend_reflection},
+};
+
+pub struct PhongToonShading<'a> {
     z_buffer_enabled: bool,
     bands: usize,
+    environment: Option<&'a EnvironmentLight>,
+    reflections: Option<Reflections<'a>>,
 }
 
-impl PhongToonShading {
-    pub fn new(z_buffer_enabled: bool, bands: usize) -> Self {
+impl<'a> PhongToonShading<'a> {
+    pub fn new(
+        z_buffer_enabled: bool,
+        bands: usize,
+        environment: Option<&'a EnvironmentLight>,
+        reflections: Option<Reflections<'a>>,
+    ) -> Self {
         Self {
             z_buffer_enabled,
             bands,
+            environment,
+            reflections,
         }
     }
 
+    /// Рассеянный свет окружения в точке с нормалью `normal`. Не квантуется по оттенкам.
+    fn environment_light(&self, normal: UVec3) -> Vec3 {
+        self.environment.map_or(Vec3::zero(), |environment| {
+            utils::color_to_vec(environment.irradiance(normal))
+        })
+    }
+
     /// Считает освещённость каждой вершины по модели Toon Shading.
     ///
     /// Для каждой вершины считает значение `интенсивность * цвет света * угол между поверхностью и светом`.
-    fn toon_shading(
-        position: Point3,
-        normal: UVec3,
-        lights: &Vec<LightSource>,
-        bands: usize,
-    ) -> egui::Color32 {
+    /// Яркость квантуется по оттенкам, но не ограничивается сверху.
+    fn toon_shading(position: Point3, normal: UVec3, lights: &[LightSource], bands: usize) -> Vec3 {
         if lights.is_empty() {
-            return egui::Color32::BLACK;
+            return Vec3::zero();
         }
 
-        let mut light_color = egui::Color32::BLACK;
-        // Влияние каждого источника
-        for light in lights {
-            let light_dir = (light.position - position).normalize().unwrap();
-            let cos = normal.dot(light_dir).max(0.0);
-            let diff = light.intensity * cos;
-            light_color = light_color + light.color.gamma_multiply(diff);
-        }
+        let light_color = lighting::lambert_light(position, normal, lights);
 
         // уменьшаем количество оттенков для toon-shading
-        let brightness = light_color.intensity();
+        let brightness = utils::vec_to_color(light_color).intensity();
         let band_index = (brightness * bands as f32).floor().min(bands as f32 - 1.0);
         let q_brightness = (band_index + 0.5) / bands as f32;
-        egui::Color32::from_rgb(
-            (light_color.r() as f32 * q_brightness) as u8,
-            (light_color.g() as f32 * q_brightness) as u8,
-            (light_color.b() as f32 * q_brightness) as u8,
-        )
+        light_color * q_brightness
     }
 }
 
-impl Shader for PhongToonShading {
+impl Shader for PhongToonShading<'_> {
     fn shade_model(
         &self,
         model: &Model,
+        geometry: &RenderMeshCache,
         polygons: &Vec<Polygon>,
-        camera: &Camera,
-        projection_type: ProjectionType,
         lights: &Vec<LightSource>,
         canvas: &mut Canvas,
     ) {
-        // матрица преобразования на экран
-        let global_to_screen_transform = camera.global_to_screen_transform(projection_type, canvas);
         // проекция вершин на экран
-        let projected_vertexes: Vec<Point3> = model
-            .mesh
-            .get_global_vertex_iter()
-            .map(|v| {
-                v.apply_transform(global_to_screen_transform)
-                    .unwrap_or(Point3::new(0.0, 0.0, -999.9))
-            })
-            .collect();
+        let projected_vertexes = &geometry.screen_vertexes;
+        // собственное свечение не зависит от освещения и одинаково по всей модели
+        let emission = model.material.emitted_light();
+
+        // отражения считаются в каждом пикселе
+        let reflections = self
+            .reflections
+            .filter(|_| model.material.reflectivity > 0.0);
 
         for polygon in polygons {
-            // если четырёхугольник - билинейная интерполяция
-            if polygon.is_quad() {
+            // если плоский выпуклый четырёхугольник - билинейная интерполяция,
+            // иначе билинейная интерполяция искажает грань и нужна триангуляция
+            if polygon.is_convex_quad(&model.mesh) {
                 // индексы вершин в Mesh
                 let i0 = polygon.get_mesh_vertex_index(0);
                 let i1 = polygon.get_mesh_vertex_index(1);
@@ -88,22 +91,37 @@ impl Shader for PhongToonShading {
                 let v3 = projected_vertexes[i3];
 
                 // текстурные UV-координаты вершин треугольника
-                let tx0 = model.mesh.get_texture_coord(i0).unwrap();
-                let tx1 = model.mesh.get_texture_coord(i1).unwrap();
-                let tx2 = model.mesh.get_texture_coord(i2).unwrap();
-                let tx3 = model.mesh.get_texture_coord(i3).unwrap();
+                let tx0 = polygon
+                    .get_texture_coord(&model.mesh, 0)
+                    .unwrap_or((0.0, 0.0));
+                let tx1 = polygon
+                    .get_texture_coord(&model.mesh, 1)
+                    .unwrap_or((0.0, 0.0));
+                let tx2 = polygon
+                    .get_texture_coord(&model.mesh, 2)
+                    .unwrap_or((0.0, 0.0));
+                let tx3 = polygon
+                    .get_texture_coord(&model.mesh, 3)
+                    .unwrap_or((0.0, 0.0));
+                let colors = lighting::vertex_colors(model, [i0, i1, i2, i3]);
 
                 // глобальные координаты вершин
-                let gv0 = model.mesh.get_global_vertex(i0);
-                let gv1 = model.mesh.get_global_vertex(i1);
-                let gv2 = model.mesh.get_global_vertex(i2);
-                let gv3 = model.mesh.get_global_vertex(i3);
+                let gv0 = geometry.global_vertexes[i0];
+                let gv1 = geometry.global_vertexes[i1];
+                let gv2 = geometry.global_vertexes[i2];
+                let gv3 = geometry.global_vertexes[i3];
 
                 // глобальные нормали
-                let n0 = model.mesh.get_global_normal(i0).unwrap();
-                let n1 = model.mesh.get_global_normal(i1).unwrap();
-                let n2 = model.mesh.get_global_normal(i2).unwrap();
-                let n3 = model.mesh.get_global_normal(i3).unwrap();
+                let n0 = geometry.normals()[i0];
+                let n1 = geometry.normals()[i1];
+                let n2 = geometry.normals()[i2];
+                let n3 = geometry.normals()[i3];
+
+                // затенение окружением вершин
+                let occlusion0 = model.mesh.get_ambient_occlusion(i0).unwrap_or(0.0);
+                let occlusion1 = model.mesh.get_ambient_occlusion(i1).unwrap_or(0.0);
+                let occlusion2 = model.mesh.get_ambient_occlusion(i2).unwrap_or(0.0);
+                let occlusion3 = model.mesh.get_ambient_occlusion(i3).unwrap_or(0.0);
 
                 // ограничивающий прямоугольник
                 let min_x = *vec![v0.x as usize, v1.x as usize, v2.x as usize, v3.x as usize]
@@ -143,35 +161,52 @@ impl Shader for PhongToonShading {
                             }
 
                             if self.z_buffer_enabled {
-                                let z = utils::bilerp_float(v0.z, v1.z, v2.z, v3.z, alpha, beta);
+                                let z = utils::bilerp_float(v0.z, v1.z, v3.z, v2.z, alpha, beta);
                                 if !canvas.test_and_set_z(x, y, z) {
                                     continue;
                                 }
                             }
 
-                            let position = utils::bilerp_point(gv0, gv1, gv2, gv3, alpha, beta);
-                            let normal = utils::bilerp_uvec(n0, n1, n2, n3, alpha, beta);
+                            let position = utils::bilerp_point(gv0, gv1, gv3, gv2, alpha, beta);
+                            let normal = utils::bilerp_uvec(n0, n1, n3, n2, alpha, beta);
 
                             // текстурные координаты пикселя
-                            let u = utils::bilerp_float(tx0.0, tx1.0, tx2.0, tx3.0, alpha, beta);
-                            let v = utils::bilerp_float(tx0.1, tx1.1, tx2.0, tx3.0, alpha, beta);
-                            let base_color = model.material.get_uv_color(u, v);
+                            let u = utils::bilerp_float(tx0.0, tx1.0, tx3.0, tx2.0, alpha, beta);
+                            let v = utils::bilerp_float(tx0.1, tx1.1, tx3.1, tx2.1, alpha, beta);
+                            let base_color = model.material.get_vertex_uv_color(
+                                u,
+                                v,
+                                colors.map(|[c0, c1, c2, c3]| {
+                                    utils::bilerp_color(c0, c1, c3, c2, alpha, beta)
+                                }),
+                            );
 
-                            // освещённость в данной точке
+                            // освещённость в данной точке с учётом затенения окружением
+                            let occlusion = utils::bilerp_float(
+                                occlusion0, occlusion1, occlusion3, occlusion2, alpha, beta,
+                            );
                             let light =
-                                Self::toon_shading(position.into(), normal, lights, self.bands);
-                            canvas[(x, y)] = base_color * light;
+                                Self::toon_shading(position.into(), normal, lights, self.bands)
+                                    + self.environment_light(normal);
+                            let light = light * (1.0 - occlusion);
+                            let color = lighting::surface_color(canvas, base_color, light);
+                            let color = match reflections {
+                                Some(reflections) => blend_reflection(
+                                    color,
+                                    reflections.sample(position, normal),
+                                    model.material.reflectivity,
+                                ),
+                                None => color,
+                            };
+                            canvas.set_fragment_hdr(x, y, color + emission);
                         }
                     }
                 }
             } else {
                 // иначе барицентрическая интерполяция с триангуляцией
-                let indexes: Vec<usize> = polygon.get_mesh_vertex_index_iter().collect();
-                for triangle in utils::triangulate_polygon(&indexes) {
+                for corners in polygon.corner_triangles() {
                     // индексы вершин в Mesh
-                    let i0 = triangle[0];
-                    let i1 = triangle[1];
-                    let i2 = triangle[2];
+                    let [i0, i1, i2] = corners.map(|i| polygon.get_mesh_vertex_index(i));
 
                     // проекция вершин треугольника
                     let v0 = projected_vertexes[i0];
@@ -179,19 +214,27 @@ impl Shader for PhongToonShading {
                     let v2 = projected_vertexes[i2];
 
                     // текстурные UV-координаты вершин треугольника
-                    let tx0 = model.mesh.get_texture_coord(i0).unwrap();
-                    let tx1 = model.mesh.get_texture_coord(i1).unwrap();
-                    let tx2 = model.mesh.get_texture_coord(i2).unwrap();
+                    let [tx0, tx1, tx2] = corners.map(|i| {
+                        polygon
+                            .get_texture_coord(&model.mesh, i)
+                            .unwrap_or((0.0, 0.0))
+                    });
+                    let colors = lighting::vertex_colors(model, [i0, i1, i2]);
 
                     // глобальные координаты вершин
-                    let gv0 = model.mesh.get_global_vertex(i0);
-                    let gv1 = model.mesh.get_global_vertex(i1);
-                    let gv2 = model.mesh.get_global_vertex(i2);
+                    let gv0 = geometry.global_vertexes[i0];
+                    let gv1 = geometry.global_vertexes[i1];
+                    let gv2 = geometry.global_vertexes[i2];
 
                     // глобальные нормали
-                    let n0 = model.mesh.get_global_normal(i0).unwrap();
-                    let n1 = model.mesh.get_global_normal(i1).unwrap();
-                    let n2 = model.mesh.get_global_normal(i2).unwrap();
+                    let n0 = geometry.normals()[i0];
+                    let n1 = geometry.normals()[i1];
+                    let n2 = geometry.normals()[i2];
+
+                    // затенение окружением вершин
+                    let occlusion0 = model.mesh.get_ambient_occlusion(i0).unwrap_or(0.0);
+                    let occlusion1 = model.mesh.get_ambient_occlusion(i1).unwrap_or(0.0);
+                    let occlusion2 = model.mesh.get_ambient_occlusion(i2).unwrap_or(0.0);
 
                     let min_x = v0.x.min(v1.x.min(v2.x)) as usize;
                     let max_x = v0.x.max(v1.x.max(v2.x)) as usize;
@@ -220,18 +263,39 @@ impl Shader for PhongToonShading {
                                 }
                             }
 
+                            // атрибуты вершин интерполируются с поправкой на перспективу
+                            let bary = geometry.attribute_barycentric(bary, [i0, i1, i2]);
+
                             let position = utils::interpolate_point(bary, gv0, gv1, gv2);
                             let normal = utils::interpolate_uvec(bary, n0, n1, n2);
 
                             // текстурные коодринаты пикселя
                             let u = utils::interpolate_float(bary, tx0.0, tx1.0, tx2.0);
                             let v = utils::interpolate_float(bary, tx0.1, tx1.1, tx2.1);
-                            let base_color = model.material.get_uv_color(u, v);
+                            let base_color = model.material.get_vertex_uv_color(
+                                u,
+                                v,
+                                colors
+                                    .map(|[c0, c1, c2]| utils::interpolate_color(bary, c0, c1, c2)),
+                            );
 
-                            // освещённость в данной точке
+                            // освещённость в данной точке с учётом затенения окружением
+                            let occlusion =
+                                utils::interpolate_float(bary, occlusion0, occlusion1, occlusion2);
                             let light =
-                                Self::toon_shading(position.into(), normal, lights, self.bands);
-                            canvas[(x, y)] = base_color * light;
+                                Self::toon_shading(position.into(), normal, lights, self.bands)
+                                    + self.environment_light(normal);
+                            let light = light * (1.0 - occlusion);
+                            let color = lighting::surface_color(canvas, base_color, light);
+                            let color = match reflections {
+                                Some(reflections) => blend_reflection(
+                                    color,
+                                    reflections.sample(position, normal),
+                                    model.material.reflectivity,
+                                ),
+                                None => color,
+                            };
+                            canvas.set_fragment_hdr(x, y, color + emission);
                         }
                     }
                 }
@@ -0,0 +1,183 @@
+//! Отражения окружения в растеризаторе по карте окружения или пробе отражений.
+
+use egui::Color32;
+
+use crate::{
+    Background, Camera, Canvas, Point3, ProjectionType, Scene, Texture, UVec3, Vec3, library::utils,
+};
+
+use super::SceneRenderer;
+
+/// Карта, отражающаяся в материалах с ненулевой [`crate::Material::reflectivity`],
+/// и положение наблюдателя для текущего кадра.
+#[derive(Debug, Clone, Copy)]
+pub struct Reflections<'a> {
+    map: &'a Background,
+    eye: Point3,
+    /// Направление взгляда при параллельной проекции, при перспективе `None`.
+    view_direction: Option<UVec3>,
+}
+
+impl<'a> Reflections<'a> {
+    /// Отражения сцены: [`Scene::reflection_map`], если она задана, иначе карта окружения.
+    ///
+    /// Возвращает `None`, если отражать нечего.
+    pub fn new(scene: &'a Scene, camera: &Camera, projection_type: ProjectionType) -> Option<Self> {
+        let map = scene.reflection_map.as_ref().or(scene
+            .environment
+            .as_ref()
+            .map(|environment| environment.map()))?;
+        Some(Self {
+            map,
+            eye: camera.get_position(),
+            view_direction: camera.projection_direction(projection_type),
+        })
+    }
+
+    /// Цвет карты вдоль отражённого от поверхности луча взгляда.
+    ///
+    /// `position` и `normal` - точка поверхности и нормаль в ней в **глобальных** координатах.
+    pub fn sample(&self, position: Point3, normal: UVec3) -> Color32 {
+        let view = match self.view_direction {
+            Some(direction) => Vec3::from(direction),
+            None => position - self.eye,
+        };
+        let normal = Vec3::from(normal);
+        self.map.sample(view - normal * (2.0 * view.dot(normal)))
+    }
+}
+
+/// Смешать цвет поверхности `color` (линейный RGB, 1 соответствует 255) с отражением
+/// `reflection` в доле `reflectivity`.
+pub fn blend_reflection(color: Vec3, reflection: Color32, reflectivity: f32) -> Vec3 {
+    if reflectivity <= 0.0 {
+        return color;
+    }
+    utils::lerp_vec(
+        color,
+        utils::color_to_vec(reflection),
+        reflectivity.min(1.0),
+    )
+}
+
+impl SceneRenderer {
+    /// Снять пробу отражений: отрендерить сцену во все 6 сторон из точки `position`.
+    ///
+    /// Возвращает кубическую карту с гранями `resolution x resolution`, которую можно
+    /// назначить в [`Scene::reflection_map`]. `ignore` - индекс модели, которая не рисуется
+    /// (обычно сама отражающая модель, в центре которой находится проба). Сетка, каркас,
+    /// манипуляторы и выделение в пробу не попадают.
+    pub fn capture_reflection_probe(
+        &self,
+        scene: &Scene,
+        position: Point3,
+        resolution: usize,
+        ignore: Option<usize>,
+    ) -> Background {
+        debug_assert!(resolution > 0, "разрешение пробы должно быть больше 0");
+
+        let renderer = SceneRenderer {
+            render_wireframe: false,
+            render_normals: false,
+            render_solid: true,
+            debug_view: super::DebugView::None,
+            projection_type: ProjectionType::Perspective,
+            grid_options: super::GridOptions {
+                show_grid: false,
+                show_axes: false,
+                ..self.grid_options
+            },
+            light_gizmos: super::LightGizmoOptions {
+                show_lights: false,
+                show_disabled: false,
+                sphere_radius: 0.0,
+                show_directions: false,
+            },
+            gizmo: None,
+            ..self.clone()
+        };
+        let mut probe_scene = scene.clone();
+        for (index, model) in probe_scene.models.iter_mut().enumerate() {
+            model.flags.selected = false;
+            if Some(index) == ignore {
+                model.flags.visible = false;
+            }
+        }
+
+        // направления взгляда и верха для граней в порядке +x, -x, +y, -y, +z, -z
+        let faces = [
+            (UVec3::new(1.0, 0.0, 0.0), UVec3::up()),
+            (UVec3::new(-1.0, 0.0, 0.0), UVec3::up()),
+            (UVec3::up(), UVec3::new(0.0, 0.0, -1.0)),
+            (UVec3::down(), UVec3::new(0.0, 0.0, 1.0)),
+            (UVec3::new(0.0, 0.0, 1.0), UVec3::up()),
+            (UVec3::new(0.0, 0.0, -1.0), UVec3::up()),
+        ];
+        let mut canvas = Canvas::new(resolution, resolution);
+        let textures = faces.map(|(direction, up)| {
+            probe_scene.camera = Camera::new(
+                position,
+                direction,
+                up,
+                std::f32::consts::FRAC_PI_2,
+                1.0,
+                scene.camera.get_near_plane(),
+                scene.camera.get_far_plane(),
+            );
+            renderer.render(&probe_scene, &mut canvas, false, position, position);
+
+            let mut image = image::RgbImage::new(resolution as u32, resolution as u32);
+            for (pixel, color) in image.pixels_mut().zip(canvas.pixels()) {
+                *pixel = image::Rgb([color.r(), color.g(), color.b()]);
+            }
+            Texture::new(image::DynamicImage::ImageRgb8(image))
+        });
+        Background::CubeMap(Box::new(textures))
+    }
+}
+
+#[cfg(test)]
+mod reflections_tests {
+    use super::*;
+    use crate::{Mesh, Model};
+
+    #[test]
+    fn test_blend_reflection() {
+        let color = Vec3::new(0.8, 0.0, 0.0);
+        assert_eq!(blend_reflection(color, Color32::BLUE, 0.0), color);
+        assert_eq!(
+            blend_reflection(color, Color32::BLUE, 1.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            "зеркало полностью отражает окружение"
+        );
+    }
+
+    #[test]
+    fn test_probe_sees_model_in_its_direction() {
+        let mut cube = Model::from_mesh(Mesh::hexahedron());
+        cube.material.color = Color32::RED;
+        cube.set_position(Point3::new(3.0, 0.0, 1.0));
+        let scene = Scene {
+            models: vec![cube],
+            background: Background::Solid(Color32::BLUE),
+            ..Default::default()
+        };
+
+        let renderer = SceneRenderer::default();
+        let probe = renderer.capture_reflection_probe(&scene, Point3::zero(), 16, None);
+        assert_eq!(probe.sample(Vec3::new(3.0, 0.0, 1.0)), Color32::RED);
+        assert_eq!(
+            probe.sample(Vec3::new(3.0, 0.0, -1.0)),
+            Color32::BLUE,
+            "проба не отражена зеркально"
+        );
+        assert_eq!(probe.sample(Vec3::new(-1.0, 0.0, 0.0)), Color32::BLUE);
+
+        let probe = renderer.capture_reflection_probe(&scene, Point3::zero(), 16, Some(0));
+        assert_eq!(
+            probe.sample(Vec3::new(3.0, 0.0, 1.0)),
+            Color32::BLUE,
+            "пропущенная модель не попадает в пробу"
+        );
+    }
+}
@@ -0,0 +1,89 @@
+//! Статистика отрисовки кадра.
+
+use std::{fmt::Display, ops::AddAssign, time::Duration};
+
+/// Статистика одного кадра, возвращаемая [`super::SceneRenderer::render`].
+///
+/// Счётчики полигонов суммируются по всем видимым моделям сцены. Счётчики фрагментов
+/// берутся с холста, поэтому учитывают и вспомогательные проходы (например, проход глубины).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RenderStats {
+    /// Количество отрисованных (видимых) моделей.
+    pub models_rendered: usize,
+    /// Полигоны моделей после выбора уровня детализации и отсечения ближней плоскостью.
+    pub polygons_submitted: usize,
+    /// Полигоны, пересекавшие ближнюю плоскость камеры и обрезанные ею.
+    pub polygons_clipped: usize,
+    /// Полигоны, отброшенные отсечением нелицевых граней.
+    pub polygons_backface_culled: usize,
+    /// Полигоны, отброшенные как целиком лежащие за ближней или дальней плоскостью.
+    pub polygons_view_culled: usize,
+    /// Полигоны, переданные шейдерам.
+    pub polygons_rendered: usize,
+    /// Количество фрагментов, записанных на холст.
+    pub pixels_shaded: u64,
+    /// Количество фрагментов, отброшенных проверкой z-буфера.
+    pub depth_rejections: u64,
+    /// Время подготовки геометрии: уровни детализации, отсечение и преобразование вершин.
+    pub geometry_time: Duration,
+    /// Время отсечения полигонов.
+    pub culling_time: Duration,
+    /// Время заполнения непрозрачных граней.
+    pub shading_time: Duration,
+    /// Время отрисовки полупрозрачных граней.
+    pub transparent_time: Duration,
+    /// Время отрисовки каркаса и нормалей.
+    pub wireframe_time: Duration,
+    /// Полное время кадра.
+    pub total_time: Duration,
+}
+
+/// Сложение статистики нескольких проходов, например плиток [`crate::RenderJob`].
+impl AddAssign for RenderStats {
+    fn add_assign(&mut self, other: Self) {
+        self.models_rendered += other.models_rendered;
+        self.polygons_submitted += other.polygons_submitted;
+        self.polygons_clipped += other.polygons_clipped;
+        self.polygons_backface_culled += other.polygons_backface_culled;
+        self.polygons_view_culled += other.polygons_view_culled;
+        self.polygons_rendered += other.polygons_rendered;
+        self.pixels_shaded += other.pixels_shaded;
+        self.depth_rejections += other.depth_rejections;
+        self.geometry_time += other.geometry_time;
+        self.culling_time += other.culling_time;
+        self.shading_time += other.shading_time;
+        self.transparent_time += other.transparent_time;
+        self.wireframe_time += other.wireframe_time;
+        self.total_time += other.total_time;
+    }
+}
+
+impl Display for RenderStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Моделей: {}", self.models_rendered)?;
+        writeln!(
+            f,
+            "Полигонов: {} отправлено, {} обрезано, {} нелицевых, {} вне камеры, {} отрисовано",
+            self.polygons_submitted,
+            self.polygons_clipped,
+            self.polygons_backface_culled,
+            self.polygons_view_culled,
+            self.polygons_rendered
+        )?;
+        writeln!(
+            f,
+            "Фрагментов: {} записано, {} отброшено z-буфером",
+            self.pixels_shaded, self.depth_rejections
+        )?;
+        write!(
+            f,
+            "Время: геометрия {:.2?}, отсечение {:.2?}, грани {:.2?}, прозрачные {:.2?}, каркас {:.2?}, всего {:.2?}",
+            self.geometry_time,
+            self.culling_time,
+            self.shading_time,
+            self.transparent_time,
+            self.wireframe_time,
+            self.total_time
+        )
+    }
+}
@@ -1,6 +1,6 @@
-use crate::{
-    Camera, Canvas, LightSource, Model, Point3, Polygon, ProjectionType, Shader, library::utils,
-};
+use crate::{Canvas, LightSource, Model, Point3, Polygon, library::utils};
+
+use super::{RenderMeshCache, Shader, lighting};
 
 pub struct SolidShader {
     z_buffer_enabled: bool,
@@ -16,28 +16,19 @@ impl Shader for SolidShader {
     fn shade_model(
         &self,
         model: &Model,
+        geometry: &RenderMeshCache,
         polygons: &Vec<Polygon>,
-        camera: &Camera,
-        projection_type: ProjectionType,
         _lights: &Vec<LightSource>,
         canvas: &mut Canvas,
     ) {
-        // матрица преобразования на экран
-        let global_to_screen_transform = camera.global_to_screen_transform(projection_type, canvas);
         // проекция вершин на экран
-        let projected_vertexes: Vec<Point3> = model
-            .mesh
-            .get_global_vertex_iter()
-            .map(|v| {
-                v.apply_transform(global_to_screen_transform)
-                    .unwrap_or(Point3::new(0.0, 0.0, -999.9))
-            })
-            .collect();
+        let projected_vertexes = &geometry.screen_vertexes;
 
         // отрисовка каждого полигона
         for polygon in polygons {
-            // если четырёхугольник - билинейная интерполяция
-            if polygon.is_quad() {
+            // если плоский выпуклый четырёхугольник - билинейная интерполяция,
+            // иначе билинейная интерполяция искажает грань и нужна триангуляция
+            if polygon.is_convex_quad(&model.mesh) {
                 // индексы вершин
                 let i0 = polygon.get_mesh_vertex_index(0);
                 let i1 = polygon.get_mesh_vertex_index(1);
@@ -51,10 +42,19 @@ impl Shader for SolidShader {
                 let v3 = projected_vertexes[i3];
 
                 // текстурные UV-координаты вершин треугольника
-                let tx0 = model.mesh.get_texture_coord(i0).unwrap();
-                let tx1 = model.mesh.get_texture_coord(i1).unwrap();
-                let tx2 = model.mesh.get_texture_coord(i2).unwrap();
-                let tx3 = model.mesh.get_texture_coord(i3).unwrap();
+                let tx0 = polygon
+                    .get_texture_coord(&model.mesh, 0)
+                    .unwrap_or((0.0, 0.0));
+                let tx1 = polygon
+                    .get_texture_coord(&model.mesh, 1)
+                    .unwrap_or((0.0, 0.0));
+                let tx2 = polygon
+                    .get_texture_coord(&model.mesh, 2)
+                    .unwrap_or((0.0, 0.0));
+                let tx3 = polygon
+                    .get_texture_coord(&model.mesh, 3)
+                    .unwrap_or((0.0, 0.0));
+                let colors = lighting::vertex_colors(model, [i0, i1, i2, i3]);
 
                 // ограничивающий прямоугольник
                 let min_x = *vec![v0.x as usize, v1.x as usize, v2.x as usize, v3.x as usize]
@@ -95,29 +95,32 @@ impl Shader for SolidShader {
 
                             // z-буфер, если есть
                             if self.z_buffer_enabled {
-                                let z = utils::bilerp_float(v0.z, v1.z, v2.z, v3.z, alpha, beta);
+                                let z = utils::bilerp_float(v0.z, v1.z, v3.z, v2.z, alpha, beta);
                                 if !canvas.test_and_set_z(x, y, z) {
                                     continue;
                                 }
                             }
 
                             // текстурные координаты пикселя
-                            let u = utils::bilerp_float(tx0.0, tx1.0, tx2.0, tx3.0, alpha, beta);
-                            let v = utils::bilerp_float(tx0.1, tx1.1, tx2.0, tx3.0, alpha, beta);
-
-                            let base_color = model.material.get_uv_color(u, v);
-                            canvas[(x, y)] = base_color;
+                            let u = utils::bilerp_float(tx0.0, tx1.0, tx3.0, tx2.0, alpha, beta);
+                            let v = utils::bilerp_float(tx0.1, tx1.1, tx3.1, tx2.1, alpha, beta);
+
+                            let base_color = model.material.get_vertex_uv_color(
+                                u,
+                                v,
+                                colors.map(|[c0, c1, c2, c3]| {
+                                    utils::bilerp_color(c0, c1, c3, c2, alpha, beta)
+                                }),
+                            );
+                            canvas.set_fragment(x, y, base_color);
                         }
                     }
                 }
             } else {
                 // иначе барицентрическая интерполяция с триангуляцией
-                let indexes: Vec<usize> = polygon.get_mesh_vertex_index_iter().collect();
-                for triangle in utils::triangulate_polygon(&indexes) {
+                for corners in polygon.corner_triangles() {
                     // индексы вершин
-                    let i0 = triangle[0];
-                    let i1 = triangle[1];
-                    let i2 = triangle[2];
+                    let [i0, i1, i2] = corners.map(|i| polygon.get_mesh_vertex_index(i));
 
                     // проекция вершин треугольника
                     let v0 = projected_vertexes[i0];
@@ -125,9 +128,12 @@ impl Shader for SolidShader {
                     let v2 = projected_vertexes[i2];
 
                     // текстурные UV-координаты вершин треугольника
-                    let tx0 = model.mesh.get_texture_coord(i0).unwrap();
-                    let tx1 = model.mesh.get_texture_coord(i1).unwrap();
-                    let tx2 = model.mesh.get_texture_coord(i2).unwrap();
+                    let [tx0, tx1, tx2] = corners.map(|i| {
+                        polygon
+                            .get_texture_coord(&model.mesh, i)
+                            .unwrap_or((0.0, 0.0))
+                    });
+                    let colors = lighting::vertex_colors(model, [i0, i1, i2]);
 
                     // ограничивающий прямоугольник
                     let min_x = v0.x.min(v1.x.min(v2.x)) as usize;
@@ -157,12 +163,20 @@ impl Shader for SolidShader {
                                 }
                             }
 
+                            // атрибуты вершин интерполируются с поправкой на перспективу
+                            let bary = geometry.attribute_barycentric(bary, [i0, i1, i2]);
+
                             // текстурные коодринаты пикселя
                             let u = utils::interpolate_float(bary, tx0.0, tx1.0, tx2.0);
                             let v = utils::interpolate_float(bary, tx0.1, tx1.1, tx2.1);
 
-                            let base_color = model.material.get_uv_color(u, v);
-                            canvas[(x, y)] = base_color;
+                            let base_color = model.material.get_vertex_uv_color(
+                                u,
+                                v,
+                                colors
+                                    .map(|[c0, c1, c2]| utils::interpolate_color(bary, c0, c1, c2)),
+                            );
+                            canvas.set_fragment(x, y, base_color);
                         }
                     }
                 }
@@ -0,0 +1,210 @@
+//! Затенение окружением в экранном пространстве (SSAO).
+
+use std::f32::consts::TAU;
+
+use egui::Color32;
+
+use crate::library::utils;
+use crate::{BufferView, Camera, Canvas, Point3, RenderTarget, Scene, Transform3D, Vec3};
+
+use super::SceneRenderer;
+
+/// Угол между соседними выборками спирали (золотой угол), чтобы выборки равномерно
+/// покрывали круг при любом их количестве.
+const GOLDEN_ANGLE: f32 = 2.399_963;
+
+/// Параметры затенения окружением в экранном пространстве.
+///
+/// Для каждого пикселя модели по буферам глубины и нормалей проверяются соседние пиксели
+/// в круге радиусом `radius`: чем больше соседних поверхностей над точкой со стороны её
+/// нормали, тем темнее пиксель. Так затеняются складки, углы и места касания моделей.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SsaoOptions {
+    /// Включено ли затенение.
+    pub enabled: bool,
+    /// Радиус поиска затеняющих поверхностей в **глобальных** единицах.
+    pub radius: f32,
+    /// Сила затенения: 0 - без затенения, 1 - полностью закрытая точка становится чёрной.
+    pub strength: f32,
+    /// Количество выборок на пиксель.
+    pub samples: usize,
+    /// Косинус угла над касательной плоскостью, ниже которого поверхности не затеняют точку.
+    /// Убирает самозатенение плоских и слабо изогнутых поверхностей.
+    pub bias: f32,
+}
+
+impl Default for SsaoOptions {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            radius: 0.5,
+            strength: 1.0,
+            samples: 16,
+            bias: 0.05,
+        }
+    }
+}
+
+/// Радиус в пикселях, в который проецируется отрезок `offset` из точки `position`.
+fn screen_radius(position: Point3, offset: Vec3, transform: Transform3D) -> Option<f32> {
+    let center = position.apply_transform(transform).ok()?;
+    let edge = (position + offset).apply_transform(transform).ok()?;
+    Some((edge.x - center.x).hypot(edge.y - center.y))
+}
+
+/// Псевдослучайный поворот спирали выборок в пикселе (x, y) из [0, 1).
+///
+/// Соседние пиксели получают разные повороты, поэтому вместо полос от малого количества
+/// выборок остаётся мелкий равномерный шум.
+fn interleaved_noise(x: usize, y: usize) -> f32 {
+    (52.982_918 * (0.067_110_56 * x as f32 + 0.005_837_15 * y as f32).fract()).fract()
+}
+
+impl SceneRenderer {
+    /// Затенить складки и места касания моделей на холсте (до переворота)
+    /// по вспомогательному проходу геометрии.
+    pub(super) fn apply_ssao(&self, scene: &Scene, camera: &Camera, canvas: &mut Canvas) {
+        let options = &self.ssao;
+        if !options.enabled
+            || options.samples == 0
+            || options.radius <= 0.0
+            || options.strength <= 0.0
+        {
+            return;
+        }
+
+        let (width, height) = (canvas.width(), canvas.height());
+        let mut target = RenderTarget::new(width, height);
+        let positions = self.write_geometry_buffers(scene, camera, &mut target);
+        let Some(BufferView::Vector(normals)) = target.buffer(RenderTarget::NORMAL_BUFFER) else {
+            return;
+        };
+        let transform = camera.global_to_screen_transform(self.projection_type, canvas);
+        let radius_offset = Vec3::from(camera.right()) * options.radius;
+
+        let (xs, ys) = canvas.scissor_bounds();
+        for y in ys {
+            for x in xs.clone() {
+                let index = y * width + x;
+                // у моделей без нормалей нулевые нормали, они не затеняются
+                let (Some(position), normal) = (positions[index], normals[index]) else {
+                    continue;
+                };
+                if normal == Vec3::zero() {
+                    continue;
+                }
+                let Some(pixel_radius) = screen_radius(position, radius_offset, transform) else {
+                    continue;
+                };
+                if pixel_radius < 1.0 {
+                    continue;
+                }
+
+                let rotation = interleaved_noise(x, y) * TAU;
+                let mut occlusion = 0.0;
+                for k in 0..options.samples {
+                    // спираль выборок, равномерно заполняющая круг
+                    let t = (k as f32 + 0.5) / options.samples as f32;
+                    let angle = k as f32 * GOLDEN_ANGLE + rotation;
+                    let (sx, sy) = (
+                        x as f32 + t.sqrt() * pixel_radius * angle.cos(),
+                        y as f32 + t.sqrt() * pixel_radius * angle.sin(),
+                    );
+                    if sx < 0.0 || sy < 0.0 || sx >= width as f32 || sy >= height as f32 {
+                        continue;
+                    }
+                    let Some(sample) = positions[sy as usize * width + sx as usize] else {
+                        continue;
+                    };
+
+                    let offset = sample - position;
+                    let distance = offset.length();
+                    if distance < f32::EPSILON {
+                        continue;
+                    }
+                    // поверхности дальше радиуса затеняют всё слабее
+                    let falloff = (options.radius / distance).powi(2).min(1.0);
+                    occlusion += (normal.dot(offset) / distance - options.bias).max(0.0) * falloff;
+                }
+
+                let occlusion =
+                    (options.strength * occlusion / options.samples as f32).clamp(0.0, 1.0);
+                if occlusion > 0.0 {
+                    canvas[(x, y)] = utils::lerp_color(canvas[(x, y)], Color32::BLACK, occlusion);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod ssao_tests {
+    use super::*;
+    use crate::{Mesh, Model, UVec3};
+
+    /// Квадрат со стороной `2 * half_size` в плоскости `z`, обращённый к камере.
+    fn panel(half_size: f32, z: f32, color: Color32) -> Model {
+        let mut panel = Model::from_mesh(Mesh::subdivided_quad(
+            [
+                Point3::new(-half_size, -half_size, z),
+                Point3::new(half_size, -half_size, z),
+                Point3::new(half_size, half_size, z),
+                Point3::new(-half_size, half_size, z),
+            ],
+            UVec3::backward(),
+            1,
+        ));
+        panel.material.color = color;
+        panel
+    }
+
+    /// Панель перед стеной.
+    fn panel_on_wall_scene() -> Scene {
+        Scene {
+            models: vec![
+                panel(1.0, -1.0, Color32::from_rgb(200, 60, 60)),
+                panel(20.0, 1.0, Color32::from_rgb(200, 200, 200)),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_ssao_darkens_contact_area_only() {
+        let scene = panel_on_wall_scene();
+        let mut renderer = SceneRenderer {
+            render_wireframe: false,
+            render_solid: true,
+            projection_type: crate::ProjectionType::Perspective,
+            ..Default::default()
+        };
+        renderer.grid_options.show_grid = false;
+        renderer.grid_options.show_axes = false;
+        let render = |renderer: &SceneRenderer| {
+            let mut canvas = Canvas::new(200, 200);
+            renderer.render(&scene, &mut canvas, false, Point3::zero(), Point3::zero());
+            canvas
+        };
+        let plain = render(&renderer);
+
+        renderer.ssao = SsaoOptions {
+            enabled: true,
+            radius: 2.0,
+            ..Default::default()
+        };
+        let shaded = render(&renderer);
+        let brightness = |color: Color32| color.r() as u32 + color.g() as u32 + color.b() as u32;
+
+        // стена у края панели темнеет, а плоские середина панели и дальняя часть стены - нет
+        let (edge_x, _) = (100..200)
+            .map(|x| (x, plain[(x, 100)]))
+            .find(|&(x, color)| color != plain[(x - 1, 100)])
+            .expect("край панели на снимке");
+        assert!(brightness(shaded[(edge_x + 1, 100)]) < brightness(plain[(edge_x + 1, 100)]));
+        assert_eq!(shaded[(100, 100)], plain[(100, 100)]);
+        assert_eq!(shaded[(195, 5)], plain[(195, 5)]);
+
+        renderer.ssao.enabled = false;
+        assert_eq!(render(&renderer).pixels(), plain.pixels());
+    }
+}
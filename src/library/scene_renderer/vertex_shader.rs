@@ -0,0 +1,145 @@
+//! Пользовательская обработка вершин перед проекцией.
+
+use std::{fmt::Debug, sync::Arc};
+
+use crate::{Mesh, Point3, UVec3};
+
+/// Атрибуты вершины, переданные в [`VertexShader`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VertexInput {
+    /// Индекс вершины в Mesh'е (с разрезанными швами развёртки, см. [`SharedVertexShader::apply`]).
+    pub index: usize,
+    /// Положение вершины в **локальных** координатах Mesh'а.
+    pub position: Point3,
+    /// Нормаль вершины в **локальных** координатах, если у Mesh'а есть нормали.
+    pub normal: Option<UVec3>,
+    /// Текстурные координаты, если они есть у Mesh'а.
+    pub uv: Option<(f32, f32)>,
+    /// Время кадра в секундах, см. [`super::SceneRenderer::shader_time`].
+    pub time: f32,
+}
+
+/// Атрибуты вершины, которые будут отрисованы вместо исходных.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VertexOutput {
+    /// Положение вершины в **локальных** координатах Mesh'а.
+    pub position: Point3,
+    /// Нормаль вершины в **локальных** координатах. Игнорируется, если у Mesh'а нет нормалей.
+    pub normal: Option<UVec3>,
+    /// Текстурные координаты. Игнорируются, если их нет у Mesh'а.
+    pub uv: Option<(f32, f32)>,
+}
+
+impl VertexInput {
+    /// Атрибуты вершины без изменений.
+    pub fn unchanged(&self) -> VertexOutput {
+        VertexOutput {
+            position: self.position,
+            normal: self.normal,
+            uv: self.uv,
+        }
+    }
+}
+
+/// Пользовательский шейдер вершин.
+///
+/// Подключается к рендеру через [`super::SceneRenderer::vertex_shader`] и вызывается для
+/// каждой вершины каждой видимой модели перед проекцией. Сам Mesh модели не меняется,
+/// поэтому деформацию можно анимировать по [`VertexInput::time`].
+///
+/// # Examples
+/// ```rust
+/// use g3d::{SceneRenderer, SharedVertexShader, Vec3, VertexInput, VertexOutput, VertexShader};
+///
+/// /// Волны вдоль оси x.
+/// struct Waves;
+///
+/// impl VertexShader for Waves {
+///     fn shade(&self, input: VertexInput) -> VertexOutput {
+///         let offset = (input.position.x * 2.0 + input.time).sin() * 0.1;
+///         VertexOutput {
+///             position: input.position + Vec3::new(0.0, offset, 0.0),
+///             ..input.unchanged()
+///         }
+///     }
+/// }
+///
+/// let renderer = SceneRenderer {
+///     vertex_shader: Some(SharedVertexShader::new(Waves)),
+///     shader_time: 1.5,
+///     ..Default::default()
+/// };
+/// ```
+pub trait VertexShader {
+    /// Новые атрибуты вершины.
+    fn shade(&self, input: VertexInput) -> VertexOutput;
+}
+
+/// Разделяемая ссылка на пользовательский [`VertexShader`].
+///
+/// Как и [`super::SharedFragmentShader`], сравнивается по ссылке.
+#[derive(Clone)]
+pub struct SharedVertexShader(Arc<dyn VertexShader>);
+
+impl SharedVertexShader {
+    /// Обернуть шейдер для передачи в [`super::SceneRenderer`].
+    pub fn new(shader: impl VertexShader + 'static) -> Self {
+        Self(Arc::new(shader))
+    }
+
+    /// Mesh с атрибутами вершин, обработанными шейдером в момент времени `time`.
+    ///
+    /// Шейдер работает с UV-координатами вершин, поэтому швы развёртки Mesh'а с UV-координатами
+    /// углов предварительно разрезаются (см. [`Mesh::split_texture_seams`]).
+    pub fn apply(&self, mesh: &Mesh, time: f32) -> Mesh {
+        if mesh.has_corner_texture_coords() {
+            return self.apply(&mesh.split_texture_seams(), time);
+        }
+        let normals = mesh.has_normals();
+        let texture_coords = mesh.has_texture_coords();
+
+        let outputs: Vec<VertexOutput> = mesh
+            .get_local_vertex_iter()
+            .enumerate()
+            .map(|(index, position)| {
+                self.0.shade(VertexInput {
+                    index,
+                    position,
+                    normal: mesh.get_local_normal(index),
+                    uv: mesh.get_texture_coord(index),
+                    time,
+                })
+            })
+            .collect();
+
+        // шейдер не может добавить атрибуты, которых нет у Mesh'а
+        let vertexes = outputs.iter().map(|output| output.position).collect();
+        let normals = normals.then(|| {
+            outputs
+                .iter()
+                .zip(mesh.get_local_normals_iter().into_iter().flatten())
+                .map(|(output, normal)| output.normal.unwrap_or(normal))
+                .collect()
+        });
+        let texture_coords = texture_coords.then(|| {
+            outputs
+                .iter()
+                .zip(mesh.get_texture_coord_iter().into_iter().flatten())
+                .map(|(output, uv)| output.uv.unwrap_or(uv))
+                .collect()
+        });
+        mesh.with_vertex_attributes(vertexes, normals, texture_coords)
+    }
+}
+
+impl Debug for SharedVertexShader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SharedVertexShader")
+    }
+}
+
+impl PartialEq for SharedVertexShader {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
@@ -1,12 +1,33 @@
-use crate::{
-    Camera, Canvas, LightSource, Model, Point3, Polygon, ProjectionType, Shader, library::utils,
-};
+use crate::{Canvas, LightSource, Model, Point3, Polygon, ProjectionType, library::utils};
 
-pub struct WireframeShader;
+use super::{RenderMeshCache, Shader};
+
+/// Относительное смещение глубины рёбер к камере (см. [`utils::depth_biased`]),
+/// чтобы они не перекрывались гранями, на которых лежат.
+const HIDDEN_LINE_DEPTH_BIAS: f32 = 1e-3;
+
+pub struct WireframeShader {
+    /// Проверять ли рёбра по z-буферу, скрывая невидимые.
+    hidden_line_removal: bool,
+    /// Сглаживать ли рёбра.
+    antialiased: bool,
+    /// Цвет каркаса. Если не задан, то берётся цвет, противоположный цвету материала.
+    color: Option<egui::Color32>,
+}
 
 impl WireframeShader {
-    pub fn new() -> Self {
-        Self
+    pub fn new(hidden_line_removal: bool, antialiased: bool) -> Self {
+        Self {
+            hidden_line_removal,
+            antialiased,
+            color: None,
+        }
+    }
+
+    /// Рисовать каркас указанным цветом.
+    pub fn with_color(mut self, color: egui::Color32) -> Self {
+        self.color = Some(color);
+        self
     }
 }
 
@@ -14,53 +35,67 @@ impl Shader for WireframeShader {
     fn shade_model(
         &self,
         model: &Model,
+        geometry: &RenderMeshCache,
         polygons: &Vec<Polygon>,
-        camera: &Camera,
-        projection_type: ProjectionType,
         _lights: &Vec<LightSource>,
         canvas: &mut Canvas,
     ) {
-        // матрица преобразования на экран
-        let global_to_screen_transform = camera.global_to_screen_transform(projection_type, canvas);
         // выбираем цвет для каркаса (чтобы потом не сливался с основной моделью)
-        let model_color = model.material.color;
-        let wireframe_color = utils::opposite_color(model_color);
+        let wireframe_color = self
+            .color
+            .unwrap_or_else(|| utils::opposite_color(model.material.color));
 
         // проекция вершин на экран
-        let projected_vertexes: Vec<Point3> = model
-            .mesh
-            .get_global_vertex_iter()
-            .map(|v| {
-                v.apply_transform(global_to_screen_transform)
-                    .unwrap_or(Point3::new(0.0, 0.0, -999.9))
-            })
-            .collect();
+        let projected_vertexes = &geometry.screen_vertexes;
 
         // Рисуем рёбра
         for polygon in polygons {
-            // Вершины полигона
-            let indexes: Vec<usize> = polygon.get_mesh_vertex_index_iter().collect();
-            let vertexes: Vec<Point3> = indexes
-                .iter()
-                .map(|&index| projected_vertexes[index])
-                .collect();
-
             // рисуем рёбра полигона
-            for i in 0..vertexes.len() {
-                let start = vertexes[i];
-                let end = vertexes[(i + 1) % vertexes.len()];
+            for (start, end) in polygon.edges() {
+                let start = projected_vertexes[start];
+                let end = projected_vertexes[end];
 
-                let start_pos = egui::Pos2::new(start.x, start.y);
-                let end_pos = egui::Pos2::new(end.x, end.y);
-                canvas.draw_sharp_line(start_pos, end_pos, wireframe_color);
+                if self.hidden_line_removal {
+                    let projection_type = geometry.projection_type;
+                    canvas.draw_depth_tested_line(
+                        utils::depth_biased(start, HIDDEN_LINE_DEPTH_BIAS, projection_type),
+                        utils::depth_biased(end, HIDDEN_LINE_DEPTH_BIAS, projection_type),
+                        wireframe_color,
+                        0.0,
+                    );
+                } else {
+                    let start_pos = egui::Pos2::new(start.x, start.y);
+                    let end_pos = egui::Pos2::new(end.x, end.y);
+                    canvas.draw_line(start_pos, end_pos, wireframe_color, self.antialiased);
+                }
             }
 
             // рисуем вершины полигона
-            for i in 0..vertexes.len() {
-                let vertex = vertexes[i];
+            for index in polygon.get_mesh_vertex_index_iter() {
+                let vertex = projected_vertexes[index];
+                if self.hidden_line_removal
+                    && !is_vertex_visible(vertex, geometry.projection_type, canvas)
+                {
+                    continue;
+                }
                 let pos = egui::Pos2::new(vertex.x, vertex.y);
                 canvas.circle_filled(pos, 3.0, wireframe_color);
             }
         }
     }
 }
+
+/// Видна ли спроецированная вершина с учётом z-буфера.
+fn is_vertex_visible(vertex: Point3, projection_type: ProjectionType, canvas: &Canvas) -> bool {
+    if vertex.x < 0.0 || vertex.y < 0.0 {
+        return false;
+    }
+    let (x, y) = (vertex.x.round() as usize, vertex.y.round() as usize);
+    x < canvas.width()
+        && y < canvas.height()
+        && canvas.test_z(
+            x,
+            y,
+            utils::depth_biased(vertex, HIDDEN_LINE_DEPTH_BIAS, projection_type).z,
+        )
+}
@@ -0,0 +1,495 @@
+//! Привязка (snapping) точек для редакторов сцены.
+//!
+//! Луч выбора из-под курсора привязывается к плоскости земли с шагом сетки или к ближайшей
+//! вершине, ребру или грани Mesh'а, если они на экране попадают в радиус захвата вокруг курсора.
+
+use egui::Pos2;
+
+use crate::{
+    Camera, Canvas, Line3, Mesh, Plane, Point3, ProjectionType, Scene, Transform3D, UVec3, Vec3,
+};
+
+/// Относительный допуск, с которым точка на поверхности не считается перекрытой ей же.
+const VISIBILITY_TOLERANCE: f32 = 1e-3;
+
+/// Расстояние вдоль луча до ближайшего пересечения с перекрывающей геометрией.
+type Occluder<'a> = dyn Fn(&Line3) -> Option<f32> + 'a;
+
+/// Элемент, к которому привязана точка.
+///
+/// Индексы вершин и полигонов относятся к Mesh'у, в котором найдена привязка.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapTarget {
+    /// Плоскость земли, точка округлена до шага сетки.
+    Ground,
+    /// Вершина с указанным индексом.
+    Vertex(usize),
+    /// Ребро между вершинами с указанными индексами.
+    Edge(usize, usize),
+    /// Полигон с указанным индексом.
+    Face(usize),
+}
+
+/// Результат привязки.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SnapResult {
+    /// Привязанная точка в **глобальных** координатах.
+    pub point: Point3,
+    /// Элемент, к которому привязана точка.
+    pub target: SnapTarget,
+    /// Индекс модели сцены, если точка привязана к модели через [`SnapOptions::snap_to_scene`].
+    pub model_index: Option<usize>,
+}
+
+/// Запрос привязки: луч выбора и положение курсора на экране.
+#[derive(Debug, Clone, Copy)]
+pub struct SnapQuery {
+    /// Луч выбора в **глобальных** координатах.
+    pub ray: Line3,
+    /// Положение курсора в экранных координатах `global_to_screen`.
+    pub cursor: Pos2,
+    /// Преобразование из глобальных координат в экранные, например
+    /// [`Camera::global_to_screen_transform`].
+    pub global_to_screen: Transform3D,
+}
+
+impl SnapQuery {
+    /// Запрос для пикселя `cursor` холста `canvas`, на который смотрит `camera`.
+    ///
+    /// Координаты курсора совпадают с [`Camera::global_to_screen_transform`], то есть
+    /// холст ещё не перевёрнут по вертикали, как в конце рендера. Луч начинается на
+    /// ближней плоскости отсечения. Возвращает `None`, если проекция вырождена.
+    pub fn from_screen(
+        camera: &Camera,
+        projection_type: ProjectionType,
+        canvas: &Canvas,
+        cursor: Pos2,
+    ) -> Option<Self> {
+        let global_to_screen = camera.global_to_screen_transform(projection_type, canvas);
+        let screen_to_global = global_to_screen.inverse()?;
+
+        // глубины ближней и дальней плоскостей на экране
+        let depth = |distance: f32| {
+            (camera.get_position() + camera.forward() * distance)
+                .apply_transform(global_to_screen)
+                .ok()
+                .map(|point| point.z)
+        };
+        let unproject = |z: f32| {
+            Point3::new(cursor.x, cursor.y, z)
+                .apply_transform(screen_to_global)
+                .ok()
+        };
+        let near = unproject(depth(camera.get_near_plane())?)?;
+        let far = unproject(depth(camera.get_far_plane())?)?;
+
+        Some(Self {
+            ray: Line3::new(near, (far - near).normalize().ok()?),
+            cursor,
+            global_to_screen,
+        })
+    }
+
+    /// Не перекрыта ли точка геометрией, если смотреть из начала луча.
+    ///
+    /// `occluder` возвращает расстояние до ближайшего пересечения луча с геометрией.
+    fn is_visible(&self, point: Point3, occluder: &Occluder) -> bool {
+        let offset = point - self.ray.origin;
+        let distance = offset.length();
+        let Ok(direction) = offset.normalize() else {
+            return true;
+        };
+        // точка на поверхности сама попадает под луч, поэтому нужен допуск
+        occluder(&Line3::new(self.ray.origin, direction))
+            .is_none_or(|hit| hit >= distance - VISIBILITY_TOLERANCE * distance.max(1.0))
+    }
+
+    /// Проекция точки на экран, если она находится перед началом луча.
+    fn project(&self, point: Point3) -> Option<Pos2> {
+        if (point - self.ray.origin).dot(Vec3::from(self.ray.direction)) < 0.0 {
+            return None;
+        }
+        let screen = point.apply_transform(self.global_to_screen).ok()?;
+        Some(Pos2::new(screen.x, screen.y))
+    }
+}
+
+/// Настройки привязки.
+///
+/// # Examples
+/// ```rust
+/// use g3d::{Line3, Point3, SnapOptions, SnapTarget, UVec3};
+///
+/// let options = SnapOptions {
+///     grid_step: 0.5,
+///     ..Default::default()
+/// };
+/// assert_eq!(options.snap_value(1.3), 1.5);
+///
+/// // луч сверху на землю
+/// let ray = Line3::new(Point3::new(1.2, 10.0, -0.3), UVec3::down());
+/// let snap = options.snap_to_ground(&ray).unwrap();
+/// assert_eq!(snap.point, Point3::new(1.0, 0.0, -0.5));
+/// assert_eq!(snap.target, SnapTarget::Ground);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SnapOptions {
+    /// Шаг сетки привязки на плоскости земли в **глобальных** координатах, 0 - без округления.
+    pub grid_step: f32,
+    /// Высота горизонтальной плоскости земли `y = ground_height`, `None` - без привязки к земле.
+    pub ground_height: Option<f32>,
+    /// Радиус захвата вершин и рёбер вокруг курсора в пикселях.
+    pub pixel_radius: f32,
+    /// Привязывать ли к вершинам.
+    pub vertices: bool,
+    /// Привязывать ли к рёбрам.
+    pub edges: bool,
+    /// Привязывать ли к граням.
+    pub faces: bool,
+}
+
+impl Default for SnapOptions {
+    fn default() -> Self {
+        Self {
+            grid_step: 1.0,
+            ground_height: Some(0.0),
+            pixel_radius: 8.0,
+            vertices: true,
+            edges: true,
+            faces: true,
+        }
+    }
+}
+
+impl SnapOptions {
+    // --------------------------------------------------
+    // Привязка к сетке
+    // --------------------------------------------------
+
+    /// Округлить значение до ближайшего кратного шагу сетки.
+    pub fn snap_value(&self, value: f32) -> f32 {
+        if self.grid_step > 0.0 {
+            (value / self.grid_step).round() * self.grid_step
+        } else {
+            value
+        }
+    }
+
+    /// Округлить все координаты точки до шага сетки.
+    pub fn snap_point(&self, point: Point3) -> Point3 {
+        Point3::new(
+            self.snap_value(point.x),
+            self.snap_value(point.y),
+            self.snap_value(point.z),
+        )
+    }
+
+    /// Пересечь луч с плоскостью земли и округлить точку до шага сетки по x и z.
+    ///
+    /// Возвращает `None`, если привязка к земле выключена, луч параллелен земле или
+    /// направлен от неё.
+    pub fn snap_to_ground(&self, ray: &Line3) -> Option<SnapResult> {
+        let ground_height = self.ground_height?;
+        let plane = Plane::new(Point3::new(0.0, ground_height, 0.0), UVec3::up());
+        let point = ray.intersect_plane(&plane)?;
+        if (point - ray.origin).dot(Vec3::from(ray.direction)) < 0.0 {
+            return None;
+        }
+        Some(SnapResult {
+            point: Point3::new(
+                self.snap_value(point.x),
+                ground_height,
+                self.snap_value(point.z),
+            ),
+            target: SnapTarget::Ground,
+            model_index: None,
+        })
+    }
+
+    // --------------------------------------------------
+    // Привязка к геометрии
+    // --------------------------------------------------
+
+    /// Привязать курсор к элементу Mesh'а.
+    ///
+    /// Сначала ищется ближайшая к курсору на экране вершина в радиусе захвата, затем
+    /// ближайшее ребро, и только потом грань под курсором, в которую попадает луч.
+    /// Вершины и рёбра, перекрытые гранями Mesh'а, не учитываются.
+    pub fn snap_to_mesh(&self, mesh: &Mesh, query: &SnapQuery) -> Option<SnapResult> {
+        let occluder = |ray: &Line3| mesh.raycast(ray).map(|hit| hit.distance);
+        self.snap_to_vertex(mesh, query, &occluder)
+            .or_else(|| self.snap_to_edge(mesh, query, &occluder))
+            .map(|(_, snap)| snap)
+            .or_else(|| self.snap_to_face(mesh, query).map(|(_, snap)| snap))
+    }
+
+    /// Привязать курсор к видимым моделям сцены, а если рядом ничего нет - к земле.
+    ///
+    /// Приоритет такой же, как у [`SnapOptions::snap_to_mesh`]: вершины всех моделей,
+    /// затем рёбра, затем ближайшая грань и в конце плоскость земли. Вершины и рёбра,
+    /// перекрытые любой видимой моделью, не учитываются.
+    pub fn snap_to_scene(&self, scene: &Scene, query: &SnapQuery) -> Option<SnapResult> {
+        let occluder = |ray: &Line3| scene.raycast(ray, None).map(|(_, hit)| hit.distance);
+        let visible = || {
+            scene
+                .models
+                .iter()
+                .enumerate()
+                .filter(|(_, model)| model.flags.visible)
+        };
+        // лучший кандидат среди моделей по ключу: расстояние на экране или вдоль луча
+        let nearest = |find: &dyn Fn(&Mesh) -> Option<(f32, SnapResult)>| {
+            visible()
+                .filter_map(|(index, model)| {
+                    let (key, snap) = find(&model.mesh)?;
+                    Some((
+                        key,
+                        SnapResult {
+                            model_index: Some(index),
+                            ..snap
+                        },
+                    ))
+                })
+                .min_by(|(a, _), (b, _)| a.total_cmp(b))
+                .map(|(_, snap)| snap)
+        };
+
+        nearest(&|mesh| self.snap_to_vertex(mesh, query, &occluder))
+            .or_else(|| nearest(&|mesh| self.snap_to_edge(mesh, query, &occluder)))
+            .or_else(|| nearest(&|mesh| self.snap_to_face(mesh, query)))
+            .or_else(|| self.snap_to_ground(&query.ray))
+    }
+
+    /// Ближайшая к курсору видимая вершина в радиусе захвата и расстояние до неё в пикселях.
+    fn snap_to_vertex(
+        &self,
+        mesh: &Mesh,
+        query: &SnapQuery,
+        occluder: &Occluder,
+    ) -> Option<(f32, SnapResult)> {
+        if !self.vertices {
+            return None;
+        }
+        mesh.get_global_vertex_iter()
+            .enumerate()
+            .filter_map(|(index, vertex)| {
+                let distance = query.project(vertex)?.distance(query.cursor);
+                (distance <= self.pixel_radius && query.is_visible(vertex, occluder)).then_some((
+                    distance,
+                    SnapResult {
+                        point: vertex,
+                        target: SnapTarget::Vertex(index),
+                        model_index: None,
+                    },
+                ))
+            })
+            .min_by(|(a, _), (b, _)| a.total_cmp(b))
+    }
+
+    /// Ближайшее к курсору видимое ребро в радиусе захвата и расстояние до него в пикселях.
+    ///
+    /// Точка на ребре - ближайшая к лучу выбора.
+    fn snap_to_edge(
+        &self,
+        mesh: &Mesh,
+        query: &SnapQuery,
+        occluder: &Occluder,
+    ) -> Option<(f32, SnapResult)> {
+        if !self.edges {
+            return None;
+        }
+        let vertexes: Vec<Point3> = mesh.get_global_vertex_iter().collect();
+        mesh.edge_list()
+            .into_iter()
+            .filter_map(|(a, b)| {
+                let point = closest_segment_point(vertexes[a], vertexes[b], &query.ray);
+                let distance = query.project(point)?.distance(query.cursor);
+                (distance <= self.pixel_radius && query.is_visible(point, occluder)).then_some((
+                    distance,
+                    SnapResult {
+                        point,
+                        target: SnapTarget::Edge(a, b),
+                        model_index: None,
+                    },
+                ))
+            })
+            .min_by(|(a, _), (b, _)| a.total_cmp(b))
+    }
+
+    /// Грань, в которую попадает луч выбора, и расстояние до неё вдоль луча.
+    fn snap_to_face(&self, mesh: &Mesh, query: &SnapQuery) -> Option<(f32, SnapResult)> {
+        if !self.faces {
+            return None;
+        }
+        let hit = mesh.raycast(&query.ray)?;
+        Some((
+            hit.distance,
+            SnapResult {
+                point: hit.point,
+                target: SnapTarget::Face(hit.polygon_index),
+                model_index: None,
+            },
+        ))
+    }
+}
+
+/// Ближайшая к прямой `line` точка отрезка `[start, end]`.
+fn closest_segment_point(start: Point3, end: Point3, line: &Line3) -> Point3 {
+    let segment = end - start;
+    let direction = Vec3::from(line.direction);
+    let offset = start - line.origin;
+
+    let length_squared = segment.dot(segment);
+    let along = segment.dot(direction);
+    // знаменатель обращается в 0 для вырожденного или параллельного прямой отрезка
+    let denominator = length_squared - along * along;
+    let t = if denominator > f32::EPSILON * length_squared.max(1.0) {
+        (along * direction.dot(offset) - segment.dot(offset)) / denominator
+    } else {
+        0.0
+    };
+    start + segment * t.clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod snapping_tests {
+    use super::*;
+    use crate::Model;
+
+    /// Камера сверху на плоскость XZ и холст 200x200.
+    fn top_view() -> (Camera, Canvas) {
+        let camera = Camera::new(
+            Point3::new(0.0, 10.0, 0.0),
+            UVec3::down(),
+            UVec3::forward(),
+            60_f32.to_radians(),
+            1.0,
+            1.0,
+            100.0,
+        );
+        (camera, Canvas::new(200, 200))
+    }
+
+    /// Запрос для пикселя, в который проецируется точка `point`, со сдвигом `offset`.
+    fn query_at(point: Point3, offset: egui::Vec2) -> SnapQuery {
+        let (camera, canvas) = top_view();
+        let screen = point
+            .apply_transform(
+                camera.global_to_screen_transform(ProjectionType::Perspective, &canvas),
+            )
+            .unwrap();
+        SnapQuery::from_screen(
+            &camera,
+            ProjectionType::Perspective,
+            &canvas,
+            Pos2::new(screen.x, screen.y) + offset,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_query_ray_passes_through_cursor_point() {
+        let point = Point3::new(1.5, 2.0, -2.0);
+        let query = query_at(point, egui::Vec2::ZERO);
+        assert!(query.ray.distance_to_point(point) < 1e-3);
+        assert!(query.ray.direction.dot(UVec3::down()) > 0.0);
+    }
+
+    #[test]
+    fn test_snap_to_ground_with_grid() {
+        let options = SnapOptions {
+            grid_step: 0.25,
+            ground_height: Some(-1.0),
+            ..Default::default()
+        };
+        let query = query_at(Point3::new(0.6, -1.0, 0.9), egui::Vec2::ZERO);
+        let snap = options.snap_to_ground(&query.ray).unwrap();
+        assert!(snap.point.approx_equal(Point3::new(0.5, -1.0, 1.0), 1e-5));
+
+        // луч от земли её не пересекает
+        let up = Line3::new(Point3::zero(), UVec3::up());
+        assert_eq!(options.snap_to_ground(&up), None);
+        let no_ground = SnapOptions {
+            ground_height: None,
+            ..options
+        };
+        assert_eq!(no_ground.snap_to_ground(&query.ray), None);
+    }
+
+    #[test]
+    fn test_snap_to_mesh_priority() {
+        let mut mesh = Mesh::hexahedron();
+        mesh.convert_units(4.0);
+        let options = SnapOptions::default();
+
+        // рядом с углом верхней грани - вершина
+        let corner = Point3::new(2.0, 2.0, 2.0);
+        let snap = options
+            .snap_to_mesh(&mesh, &query_at(corner, egui::vec2(3.0, -2.0)))
+            .unwrap();
+        assert_eq!(snap.point, corner);
+        assert!(matches!(snap.target, SnapTarget::Vertex(_)));
+
+        // рядом с серединой ребра - точка на ребре
+        let middle = Point3::new(0.0, 2.0, 2.0);
+        let snap = options
+            .snap_to_mesh(&mesh, &query_at(middle, egui::vec2(2.0, 3.0)))
+            .unwrap();
+        assert!(matches!(snap.target, SnapTarget::Edge(_, _)));
+        assert!((snap.point.y - 2.0).abs() < 1e-5 && (snap.point.z - 2.0).abs() < 1e-5);
+        assert!(snap.point.x.abs() < 0.2);
+
+        // в центре верхней грани - грань
+        let center = Point3::new(0.0, 2.0, 0.0);
+        let snap = options
+            .snap_to_mesh(&mesh, &query_at(center, egui::Vec2::ZERO))
+            .unwrap();
+        assert!(matches!(snap.target, SnapTarget::Face(_)));
+        assert!(snap.point.approx_equal(center, 1e-4));
+
+        // нижний угол закрыт верхней гранью
+        let hidden = Point3::new(2.0, -2.0, 2.0);
+        let snap = options
+            .snap_to_mesh(&mesh, &query_at(hidden, egui::Vec2::ZERO))
+            .unwrap();
+        assert!((snap.point.y - 2.0).abs() < 1e-4, "{:?}", snap);
+
+        // без привязки к вершинам угол привязывается к ребру
+        let edges_only = SnapOptions {
+            vertices: false,
+            ..options
+        };
+        let snap = edges_only
+            .snap_to_mesh(&mesh, &query_at(corner, egui::vec2(3.0, -2.0)))
+            .unwrap();
+        assert!(matches!(snap.target, SnapTarget::Edge(_, _)));
+    }
+
+    #[test]
+    fn test_snap_to_scene_falls_back_to_ground() {
+        let mut cube = Model::from_mesh(Mesh::hexahedron());
+        cube.set_position(Point3::new(3.0, 0.5, 0.0));
+        let scene = Scene {
+            models: vec![Model::from_mesh(Mesh::tetrahedron()), cube],
+            ..Default::default()
+        };
+        let options = SnapOptions::default();
+
+        let corner = Point3::new(3.5, 1.0, 0.5);
+        let snap = options
+            .snap_to_scene(&scene, &query_at(corner, egui::vec2(1.0, 1.0)))
+            .unwrap();
+        assert_eq!(snap.model_index, Some(1));
+        assert!(snap.point.approx_equal(corner, 1e-5));
+
+        let snap = options
+            .snap_to_scene(
+                &scene,
+                &query_at(Point3::new(-3.2, 0.0, 2.9), egui::Vec2::ZERO),
+            )
+            .unwrap();
+        assert_eq!(snap.target, SnapTarget::Ground);
+        assert_eq!(snap.model_index, None);
+        assert!(snap.point.approx_equal(Point3::new(-3.0, 0.0, 3.0), 1e-5));
+    }
+}
@@ -0,0 +1,210 @@
+//! Хэширование состояния сцены для отслеживания изменений между кадрами.
+//!
+//! Поля сцены и моделей открыты, поэтому изменения нельзя отследить флагами при записи.
+//! Вместо этого состояние, влияющее на изображение, сворачивается в хэш, который
+//! сравнивается с хэшем прошлого кадра.
+
+use std::hash::{Hash, Hasher};
+
+use crate::{
+    BoneWeights, Camera, CoordFrame, LightSource, LodGroup, Material, ModelFlags, Point3,
+    SectionBox, Skeleton, Transform3D, UVec3, Vec3,
+};
+
+/// Хэширование состояния, которое влияет на отрисовку.
+///
+/// В отличие от `Hash`, поддерживает числа с плавающей точкой: они хэшируются побитово.
+pub(crate) trait StateHash {
+    fn hash_state<H: Hasher>(&self, state: &mut H);
+}
+
+impl StateHash for f32 {
+    fn hash_state<H: Hasher>(&self, state: &mut H) {
+        state.write_u32(self.to_bits());
+    }
+}
+
+impl StateHash for egui::Color32 {
+    fn hash_state<H: Hasher>(&self, state: &mut H) {
+        self.hash(state);
+    }
+}
+
+impl StateHash for usize {
+    fn hash_state<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(*self);
+    }
+}
+
+impl StateHash for bool {
+    fn hash_state<H: Hasher>(&self, state: &mut H) {
+        state.write_u8(*self as u8);
+    }
+}
+
+impl<T: StateHash> StateHash for [T] {
+    fn hash_state<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len());
+        for item in self {
+            item.hash_state(state);
+        }
+    }
+}
+
+impl<T: StateHash, const N: usize> StateHash for [T; N] {
+    fn hash_state<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash_state(state);
+    }
+}
+
+impl<T: StateHash> StateHash for Vec<T> {
+    fn hash_state<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash_state(state);
+    }
+}
+
+impl<T: StateHash> StateHash for Option<T> {
+    fn hash_state<H: Hasher>(&self, state: &mut H) {
+        match self {
+            Some(value) => {
+                state.write_u8(1);
+                value.hash_state(state);
+            }
+            None => state.write_u8(0),
+        }
+    }
+}
+
+impl<A: StateHash, B: StateHash> StateHash for (A, B) {
+    fn hash_state<H: Hasher>(&self, state: &mut H) {
+        self.0.hash_state(state);
+        self.1.hash_state(state);
+    }
+}
+
+// --------------------------------------------------
+// Примитивы
+// --------------------------------------------------
+
+impl StateHash for Point3 {
+    fn hash_state<H: Hasher>(&self, state: &mut H) {
+        [self.x, self.y, self.z].hash_state(state);
+    }
+}
+
+impl StateHash for Vec3 {
+    fn hash_state<H: Hasher>(&self, state: &mut H) {
+        [self.x, self.y, self.z].hash_state(state);
+    }
+}
+
+impl StateHash for UVec3 {
+    fn hash_state<H: Hasher>(&self, state: &mut H) {
+        [self.x, self.y, self.z].hash_state(state);
+    }
+}
+
+impl StateHash for Transform3D {
+    fn hash_state<H: Hasher>(&self, state: &mut H) {
+        self.m.hash_state(state);
+    }
+}
+
+impl StateHash for SectionBox {
+    fn hash_state<H: Hasher>(&self, state: &mut H) {
+        self.center.hash_state(state);
+        self.half_extents.hash_state(state);
+        self.axes().hash_state(state);
+    }
+}
+
+impl StateHash for CoordFrame {
+    fn hash_state<H: Hasher>(&self, state: &mut H) {
+        [self.forward(), self.right(), self.up()].hash_state(state);
+        self.origin.hash_state(state);
+        self.scale.hash_state(state);
+    }
+}
+
+// --------------------------------------------------
+// Сцена
+// --------------------------------------------------
+
+impl StateHash for Camera {
+    fn hash_state<H: Hasher>(&self, state: &mut H) {
+        self.local_frame.hash_state(state);
+        [
+            self.get_fov(),
+            self.get_aspect_ratio(),
+            self.get_near_plane(),
+            self.get_far_plane(),
+        ]
+        .hash_state(state);
+    }
+}
+
+impl StateHash for LightSource {
+    fn hash_state<H: Hasher>(&self, state: &mut H) {
+        self.position.hash_state(state);
+        self.color.hash(state);
+        self.intensity.hash_state(state);
+        self.enabled.hash_state(state);
+        self.layers.hash(state);
+    }
+}
+
+// --------------------------------------------------
+// Модель
+// --------------------------------------------------
+
+impl StateHash for Material {
+    fn hash_state<H: Hasher>(&self, state: &mut H) {
+        self.color.hash(state);
+        self.texture.hash_state(state);
+        std::mem::discriminant(&self.blend_mode).hash(state);
+        self.opacity.hash_state(state);
+        self.reflectivity.hash_state(state);
+        self.double_sided.hash_state(state);
+        self.emission.hash(state);
+        self.emission_strength.hash_state(state);
+    }
+}
+
+impl StateHash for ModelFlags {
+    fn hash_state<H: Hasher>(&self, state: &mut H) {
+        [
+            self.visible,
+            self.casts_shadows,
+            self.wireframe_only,
+            self.selected,
+            self.depth_write,
+        ]
+        .hash_state(state);
+        std::mem::discriminant(&self.cull_mode).hash(state);
+        self.layers.hash(state);
+    }
+}
+
+impl StateHash for BoneWeights {
+    fn hash_state<H: Hasher>(&self, state: &mut H) {
+        self.bones.hash_state(state);
+        self.weights.hash_state(state);
+    }
+}
+
+impl StateHash for Skeleton {
+    fn hash_state<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.joint_count());
+        for joint in self.get_joint_iter() {
+            joint.parent.hash_state(state);
+            joint.bind_transform.hash_state(state);
+            joint.pose_transform.hash_state(state);
+        }
+    }
+}
+
+impl StateHash for LodGroup {
+    fn hash_state<H: Hasher>(&self, state: &mut H) {
+        self.levels.hash_state(state);
+    }
+}
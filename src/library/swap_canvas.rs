@@ -0,0 +1,146 @@
+//! Холст с двойной буферизацией для рендера в отдельном потоке.
+
+use std::sync::{
+    Mutex, MutexGuard,
+    atomic::{AtomicU64, Ordering},
+};
+
+use crate::Canvas;
+
+/// Холст с двойной буферизацией: передний буфер отображается, задний заполняется рендером.
+///
+/// Рассчитан на общий доступ через `Arc`: поток рендера рисует в [`SwapCanvas::back`]
+/// и вызывает [`SwapCanvas::swap`], а интерфейс читает готовый кадр из [`SwapCanvas::front`].
+/// Буферы меняются местами без копирования пикселей, а интерфейс никогда не видит
+/// недорисованный кадр.
+///
+/// Буферы защищены отдельными мьютексами, поэтому рендер и отображение не ждут друг друга,
+/// кроме момента смены буферов. Нельзя вызывать `swap`, удерживая `front` или `back`
+/// в том же потоке: это взаимная блокировка.
+///
+/// # Examples
+/// ```rust
+/// use std::sync::Arc;
+/// use egui::Color32;
+/// use g3d::SwapCanvas;
+///
+/// let canvas = Arc::new(SwapCanvas::new(4, 4));
+/// let render = {
+///     let canvas = Arc::clone(&canvas);
+///     std::thread::spawn(move || {
+///         canvas.back().clear(Color32::RED);
+///         canvas.swap();
+///     })
+/// };
+/// render.join().unwrap();
+///
+/// assert_eq!(canvas.frame(), 1);
+/// assert_eq!(canvas.front()[(0, 0)], Color32::RED);
+/// ```
+pub struct SwapCanvas {
+    /// Готовый кадр для отображения.
+    front: Mutex<Canvas>,
+    /// Кадр, в который идёт рендер.
+    back: Mutex<Canvas>,
+    /// Количество смен буферов.
+    frame: AtomicU64,
+}
+
+impl SwapCanvas {
+    /// Создать холст с двумя буферами указанного размера.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            front: Mutex::new(Canvas::new(width, height)),
+            back: Mutex::new(Canvas::new(width, height)),
+            frame: AtomicU64::new(0),
+        }
+    }
+
+    /// Передний буфер с последним готовым кадром.
+    pub fn front(&self) -> MutexGuard<'_, Canvas> {
+        Self::lock(&self.front)
+    }
+
+    /// Задний буфер, в который рисуется следующий кадр.
+    ///
+    /// Содержит кадр, бывший передним до последней смены буферов.
+    pub fn back(&self) -> MutexGuard<'_, Canvas> {
+        Self::lock(&self.back)
+    }
+
+    /// Поменять буферы местами: нарисованный задний кадр становится передним.
+    pub fn swap(&self) {
+        let mut back = self.back();
+        let mut front = self.front();
+        std::mem::swap(&mut *back, &mut *front);
+        self.frame.fetch_add(1, Ordering::Release);
+    }
+
+    /// Количество смен буферов. По нему интерфейс узнаёт, что появился новый кадр,
+    /// и не обновляет текстуру лишний раз.
+    pub fn frame(&self) -> u64 {
+        self.frame.load(Ordering::Acquire)
+    }
+
+    /// Изменить размер заднего буфера, если он отличается от `width` x `height`.
+    ///
+    /// Передний буфер получит новый размер после ближайшей смены буферов.
+    pub fn resize_back(&self, width: usize, height: usize) {
+        self.back().resize(width, height);
+    }
+
+    /// Захватить мьютекс буфера. Паника в другом потоке не портит холст,
+    /// поэтому отравленный мьютекс просто разблокируется.
+    fn lock(buffer: &Mutex<Canvas>) -> MutexGuard<'_, Canvas> {
+        buffer
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod swap_canvas_tests {
+    use std::sync::Arc;
+
+    use egui::Color32;
+
+    use super::*;
+
+    #[test]
+    fn test_front_never_shows_unfinished_frame() {
+        let canvas = Arc::new(SwapCanvas::new(8, 8));
+        canvas.front().clear(Color32::BLUE);
+
+        let render = {
+            let canvas = Arc::clone(&canvas);
+            std::thread::spawn(move || {
+                for color in [Color32::RED, Color32::GREEN] {
+                    canvas.back().clear(color);
+                    canvas.swap();
+                }
+            })
+        };
+        while canvas.frame() < 2 {
+            // передний кадр всегда целиком одного цвета
+            let front = canvas.front();
+            let first = front.pixels()[0];
+            assert!(front.pixels().iter().all(|&pixel| pixel == first));
+        }
+        render.join().unwrap();
+
+        assert_eq!(canvas.front()[(3, 3)], Color32::GREEN);
+        // в заднем буфере остался предыдущий кадр
+        assert_eq!(canvas.back()[(3, 3)], Color32::RED);
+    }
+
+    #[test]
+    fn test_resize_back() {
+        let canvas = SwapCanvas::new(4, 4);
+        canvas.resize_back(6, 2);
+        assert_eq!(canvas.back().size(), [6, 2]);
+        assert_eq!(canvas.front().size(), [4, 4]);
+
+        canvas.swap();
+        assert_eq!(canvas.front().size(), [6, 2]);
+    }
+}
@@ -0,0 +1,356 @@
+//! Эталонные сцены и сравнение рендера с эталонными изображениями (golden-тесты).
+//!
+//! Эталонные сцены полностью детерминированы: одна и та же сцена с тем же рендером
+//! всегда даёт одно и то же изображение. Эталонные PNG встроены в библиотеку, поэтому
+//! изменения в рендере, меняющие картинку, сразу видны по упавшим тестам.
+//!
+//! Если изменение картинки намеренное, эталоны перегенерируются командой
+//! `cargo test update_golden_images -- --ignored`.
+
+use std::{fmt::Display, io::Cursor};
+
+use egui::Color32;
+use image::{ImageFormat, RgbaImage};
+
+use crate::{
+    Camera, Canvas, GridOptions, LightSource, Mesh, Model, Point3, ProjectionType, Scene,
+    SceneRenderer, ShadingType, UVec3,
+};
+
+/// Размер эталонных изображений вида [ширина, высота].
+pub const REFERENCE_SIZE: [usize; 2] = [160, 120];
+
+/// Допустимое отличие каналов цвета пикселя от эталона по умолчанию.
+pub const DEFAULT_TOLERANCE: u8 = 2;
+
+// --------------------------------------------------
+// Эталонные сцены
+// --------------------------------------------------
+
+/// Эталонная сцена: куб, источник света и камера, отрисованные одним из способов.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceScene {
+    /// Сплошной куб без шейдинга.
+    SolidCube,
+    /// Куб с шейдингом Гуро для модели Ламберта.
+    LambertCube,
+    /// Куб с туншейдингом Фонга.
+    ToonCube,
+    /// Каркас куба.
+    WireframeCube,
+}
+
+impl ReferenceScene {
+    /// Все эталонные сцены.
+    pub const ALL: [ReferenceScene; 4] = [
+        Self::SolidCube,
+        Self::LambertCube,
+        Self::ToonCube,
+        Self::WireframeCube,
+    ];
+
+    /// Имя сцены, оно же имя файла эталона без расширения.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::SolidCube => "solid_cube",
+            Self::LambertCube => "lambert_cube",
+            Self::ToonCube => "toon_cube",
+            Self::WireframeCube => "wireframe_cube",
+        }
+    }
+
+    /// Построить сцену: повёрнутый куб в начале координат, белый источник света
+    /// сверху слева и камера на оси z.
+    pub fn scene(self) -> Scene {
+        let mut cube = Model::from_mesh(Mesh::hexahedron());
+        cube.material.color = Color32::from_rgb(200, 120, 60);
+        cube.uniform_scale(3.0);
+        cube.rotate_local_y(30_f32.to_radians());
+        cube.rotate_local_x(20_f32.to_radians());
+
+        let camera = Camera::new(
+            Point3::new(0.0, 0.0, -10.0),
+            UVec3::forward(),
+            UVec3::up(),
+            60_f32.to_radians(),
+            REFERENCE_SIZE[0] as f32 / REFERENCE_SIZE[1] as f32,
+            1.0,
+            100.0,
+        );
+
+        Scene {
+            models: vec![cube],
+            camera,
+            lights: vec![
+                LightSource::new(Point3::new(-6.0, 6.0, -8.0), Color32::WHITE, 1.0)
+                    .with_name("key"),
+            ],
+            ..Default::default()
+        }
+    }
+
+    /// Настройки рендера сцены.
+    pub fn renderer(self) -> SceneRenderer {
+        let renderer = SceneRenderer {
+            render_wireframe: false,
+            render_solid: true,
+            projection_type: ProjectionType::Perspective,
+            backface_culling: true,
+            // оси и подписи к ним не относятся к проверяемому рендеру моделей
+            grid_options: GridOptions {
+                show_axes: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        match self {
+            Self::SolidCube => renderer,
+            Self::LambertCube => SceneRenderer {
+                shading_type: ShadingType::GouraudLambert,
+                ..renderer
+            },
+            Self::ToonCube => SceneRenderer {
+                shading_type: ShadingType::PhongToonShading(4),
+                ..renderer
+            },
+            Self::WireframeCube => SceneRenderer {
+                render_wireframe: true,
+                render_solid: false,
+                ..renderer
+            },
+        }
+    }
+
+    /// Отрисовать сцену в буфер размера [`REFERENCE_SIZE`].
+    pub fn render(self) -> Canvas {
+        render_headless(
+            &self.scene(),
+            &self.renderer(),
+            REFERENCE_SIZE[0],
+            REFERENCE_SIZE[1],
+        )
+    }
+
+    /// Встроенный эталон сцены в формате PNG.
+    pub fn golden_png(self) -> &'static [u8] {
+        match self {
+            Self::SolidCube => include_bytes!("testing/golden/solid_cube.png"),
+            Self::LambertCube => include_bytes!("testing/golden/lambert_cube.png"),
+            Self::ToonCube => include_bytes!("testing/golden/toon_cube.png"),
+            Self::WireframeCube => include_bytes!("testing/golden/wireframe_cube.png"),
+        }
+    }
+
+    /// Отрисовать сцену и сравнить со встроенным эталоном.
+    pub fn compare_with_golden(self, tolerance: u8) -> Result<ImageDiff, GoldenError> {
+        compare_with_png(&self.render(), self.golden_png(), tolerance)
+    }
+}
+
+/// Отрисовать сцену без окна в новый буфер размера `width` x `height`.
+pub fn render_headless(
+    scene: &Scene,
+    renderer: &SceneRenderer,
+    width: usize,
+    height: usize,
+) -> Canvas {
+    let mut canvas = Canvas::new(width, height);
+    renderer.render(scene, &mut canvas, false, Point3::zero(), Point3::zero());
+    canvas
+}
+
+// --------------------------------------------------
+// Сравнение изображений
+// --------------------------------------------------
+
+/// Результат сравнения изображения с эталоном.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageDiff {
+    /// Наибольшее отличие канала цвета среди всех пикселей.
+    pub max_difference: u8,
+    /// Количество пикселей, отличающихся от эталона больше допустимого.
+    pub mismatched_pixels: usize,
+    /// Общее количество пикселей.
+    pub total_pixels: usize,
+}
+
+impl ImageDiff {
+    /// Совпадает ли изображение с эталоном с учётом допуска.
+    pub fn is_match(&self) -> bool {
+        self.mismatched_pixels == 0
+    }
+}
+
+/// Ошибки сравнения с эталоном.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GoldenError {
+    /// Эталон не удалось декодировать или закодировать.
+    InvalidImage(String),
+    /// Размеры изображения и эталона вида [ширина, высота] не совпадают.
+    SizeMismatch {
+        expected: [usize; 2],
+        actual: [usize; 2],
+    },
+}
+
+impl Display for GoldenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidImage(error) => write!(f, "некорректное изображение: {}", error),
+            Self::SizeMismatch { expected, actual } => write!(
+                f,
+                "размер изображения {}x{} не совпадает с эталоном {}x{}",
+                actual[0], actual[1], expected[0], expected[1]
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GoldenError {}
+
+/// Сравнить холст с эталоном `expected`.
+///
+/// Пиксель считается несовпадающим, если хотя бы один из каналов RGBA отличается
+/// больше чем на `tolerance`.
+///
+/// # Examples
+/// ```rust
+/// use egui::Color32;
+/// use g3d::{Canvas, testing};
+///
+/// let mut canvas = Canvas::new(2, 2);
+/// canvas.clear(Color32::from_gray(100));
+/// let mut expected = testing::canvas_to_image(&canvas);
+/// expected.put_pixel(0, 0, image::Rgba([103, 100, 100, 255]));
+///
+/// let diff = testing::compare_with_image(&canvas, &expected, 2).unwrap();
+/// assert_eq!(diff.max_difference, 3);
+/// assert_eq!(diff.mismatched_pixels, 1);
+/// assert!(testing::compare_with_image(&canvas, &expected, 3).unwrap().is_match());
+/// ```
+pub fn compare_with_image(
+    canvas: &Canvas,
+    expected: &RgbaImage,
+    tolerance: u8,
+) -> Result<ImageDiff, GoldenError> {
+    let expected_size = [expected.width() as usize, expected.height() as usize];
+    if expected_size != canvas.size() {
+        return Err(GoldenError::SizeMismatch {
+            expected: expected_size,
+            actual: canvas.size(),
+        });
+    }
+
+    let mut diff = ImageDiff {
+        max_difference: 0,
+        mismatched_pixels: 0,
+        total_pixels: canvas.pixels().len(),
+    };
+    for (actual, expected) in canvas.pixels().iter().zip(expected.pixels()) {
+        let difference = actual
+            .to_array()
+            .iter()
+            .zip(expected.0)
+            .map(|(&a, b)| a.abs_diff(b))
+            .max()
+            .unwrap_or(0);
+        diff.max_difference = diff.max_difference.max(difference);
+        if difference > tolerance {
+            diff.mismatched_pixels += 1;
+        }
+    }
+    Ok(diff)
+}
+
+/// Сравнить холст с эталоном в формате PNG.
+pub fn compare_with_png(
+    canvas: &Canvas,
+    png: &[u8],
+    tolerance: u8,
+) -> Result<ImageDiff, GoldenError> {
+    let expected = image::load_from_memory_with_format(png, ImageFormat::Png)
+        .map_err(|e| GoldenError::InvalidImage(e.to_string()))?;
+    compare_with_image(canvas, &expected.to_rgba8(), tolerance)
+}
+
+/// Преобразовать холст в RGBA изображение.
+pub fn canvas_to_image(canvas: &Canvas) -> RgbaImage {
+    let mut image = RgbaImage::new(canvas.width() as u32, canvas.height() as u32);
+    for (pixel, color) in image.pixels_mut().zip(canvas.pixels()) {
+        *pixel = image::Rgba(color.to_array());
+    }
+    image
+}
+
+/// Закодировать холст в PNG, например для сохранения нового эталона.
+pub fn canvas_to_png(canvas: &Canvas) -> Result<Vec<u8>, GoldenError> {
+    let mut png = Vec::new();
+    canvas_to_image(canvas)
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .map_err(|e| GoldenError::InvalidImage(e.to_string()))?;
+    Ok(png)
+}
+
+#[cfg(test)]
+mod testing_tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_scenes_match_golden() {
+        for reference in ReferenceScene::ALL {
+            let diff = reference
+                .compare_with_golden(DEFAULT_TOLERANCE)
+                .unwrap_or_else(|e| panic!("эталон {}: {}", reference.name(), e));
+            assert!(
+                diff.is_match(),
+                "рендер {} отличается от эталона: {:?}; если изменение намеренное, \
+                 перегенерируйте эталоны: cargo test update_golden_images -- --ignored",
+                reference.name(),
+                diff
+            );
+        }
+    }
+
+    #[test]
+    fn test_reference_render_is_deterministic() {
+        for reference in ReferenceScene::ALL {
+            let first = reference.render();
+            let second = reference.render();
+            assert!(first.pixels() == second.pixels(), "{}", reference.name());
+            // сцена не пустая
+            let background = first.pixels()[0];
+            assert!(first.pixels().iter().any(|&pixel| pixel != background));
+        }
+    }
+
+    #[test]
+    fn test_png_round_trip() {
+        let canvas = ReferenceScene::LambertCube.render();
+        let png = canvas_to_png(&canvas).unwrap();
+        let diff = compare_with_png(&canvas, &png, 0).unwrap();
+        assert_eq!(diff.max_difference, 0);
+        assert!(diff.is_match());
+
+        let smaller = Canvas::new(4, 4);
+        assert_eq!(
+            compare_with_png(&smaller, &png, 0),
+            Err(GoldenError::SizeMismatch {
+                expected: REFERENCE_SIZE,
+                actual: [4, 4],
+            })
+        );
+    }
+
+    /// Перезаписать эталоны текущим рендером.
+    #[test]
+    #[ignore = "перезаписывает эталонные изображения"]
+    fn update_golden_images() {
+        let dir =
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src/library/testing/golden");
+        std::fs::create_dir_all(&dir).unwrap();
+        for reference in ReferenceScene::ALL {
+            let png = canvas_to_png(&reference.render()).unwrap();
+            std::fs::write(dir.join(format!("{}.png", reference.name())), png).unwrap();
+        }
+    }
+}
@@ -0,0 +1,347 @@
+//! Проекторы текстур: наложение картинки на поверхности сцены без UV-развёртки.
+
+use std::hash::Hasher;
+
+use egui::Color32;
+
+use crate::library::state_hash::StateHash;
+use crate::{Camera, Canvas, Point3, ProjectionType, Texture, Transform3D, Vec3};
+
+/// Проектор текстуры, как диапроектор: картинка проецируется из точки камеры `camera`
+/// внутри её области видимости на все непрозрачные поверхности, которые в неё попадают.
+///
+/// Подходит для нанесения логотипов, масок теней или измерительных сеток на любую геометрию
+/// без редактирования текстурных координат. Проекция накладывается в экранном пространстве
+/// по z-буферу после отрисовки непрозрачных граней, поэтому не затеняется другими моделями
+/// (проходит сквозь них) и ограничивается только ближней и дальней плоскостями камеры проектора.
+///
+/// # Examples
+/// ```rust
+/// use egui::Color32;
+/// use g3d::{Camera, Point3, Scene, Texture, TextureProjector, UVec3};
+///
+/// let image = image::RgbImage::from_pixel(2, 2, image::Rgb([255, 0, 0]));
+/// let camera = Camera::new(
+///     Point3::new(0.0, 5.0, 0.0),
+///     UVec3::down(),
+///     UVec3::forward(),
+///     30_f32.to_radians(),
+///     1.0,
+///     0.1,
+///     20.0,
+/// );
+/// let projector = TextureProjector::new(camera, Texture::new(image.into()));
+///
+/// assert_eq!(projector.texture_coords(Point3::zero()), Some((0.5, 0.5)));
+/// assert_eq!(projector.texture_coords(Point3::new(0.0, 10.0, 0.0)), None);
+///
+/// let mut scene = Scene::default();
+/// scene.projectors.push(projector);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TextureProjector {
+    /// Положение, направление, угол обзора, соотношение сторон и плоскости отсечения проектора.
+    pub camera: Camera,
+    /// Проецируемая картинка. Верх картинки направлен вверх камеры проектора.
+    pub texture: Texture,
+    /// Непрозрачность проекции в диапазоне [0, 1].
+    pub opacity: f32,
+    /// Цвет текстуры, который не проецируется (прозрачный фон логотипа), если задан.
+    pub mask_color: Option<Color32>,
+    /// Высота области проецирования при параллельном проецировании.
+    /// `None` - перспективное проецирование по углу обзора камеры.
+    pub orthographic_height: Option<f32>,
+    /// Включён ли проектор.
+    pub enabled: bool,
+}
+
+impl TextureProjector {
+    /// Создать включённый перспективный проектор с полной непрозрачностью.
+    pub fn new(camera: Camera, texture: Texture) -> Self {
+        Self {
+            camera,
+            texture,
+            opacity: 1.0,
+            mask_color: None,
+            orthographic_height: None,
+            enabled: true,
+        }
+    }
+
+    /// UV-координаты текстуры, попадающие в точку `point` в **глобальных** координатах,
+    /// или `None`, если точка вне области видимости проектора.
+    pub fn texture_coords(&self, point: Point3) -> Option<(f32, f32)> {
+        let local = point
+            .apply_transform(self.camera.local_frame.global_to_local_matrix())
+            .ok()?;
+        // камера смотрит вдоль -z, вправо направлена -x
+        let depth = -local.z;
+        if depth < self.camera.get_near_plane() || depth > self.camera.get_far_plane() {
+            return None;
+        }
+
+        let half_height = match self.orthographic_height {
+            Some(height) => height / 2.0,
+            None => depth * (self.camera.get_fov() / 2.0).tan(),
+        };
+        let half_width = half_height * self.camera.get_aspect_ratio();
+        let u = 0.5 - local.x / (2.0 * half_width);
+        let v = 0.5 - local.y / (2.0 * half_height);
+        ((0.0..=1.0).contains(&u) && (0.0..=1.0).contains(&v)).then_some((u, v))
+    }
+
+    /// Цвет проекции в точке `point`, если она попадает в проектор и не замаскирована.
+    fn color_at(&self, point: Point3) -> Option<Color32> {
+        let (u, v) = self.texture_coords(point)?;
+        let color = self.texture.get_pixel_color(u, v);
+        (Some(color) != self.mask_color).then_some(color)
+    }
+}
+
+/// Наложить проекторы на пиксели холста с записанной глубиной.
+///
+/// `global_to_screen` - преобразование, которым камерой `camera` с проекцией
+/// `projection_type` отрисован кадр.
+pub(crate) fn apply_projectors(
+    projectors: &[TextureProjector],
+    canvas: &mut Canvas,
+    camera: &Camera,
+    projection_type: ProjectionType,
+    global_to_screen: Transform3D,
+) {
+    let projectors: Vec<&TextureProjector> = projectors
+        .iter()
+        .filter(|projector| projector.enabled && projector.opacity > 0.0)
+        .collect();
+    if projectors.is_empty() {
+        return;
+    }
+    let Some(screen_to_global) = global_to_screen.inverse() else {
+        return;
+    };
+
+    let eye = camera.get_position();
+    let forward = Vec3::from(camera.forward());
+
+    let cleared = canvas.depth_func().clear_value();
+    let (xs, ys) = canvas.scissor_bounds();
+    for y in ys {
+        for x in xs.clone() {
+            let depth = canvas.depth_buffer()[y * canvas.width() + x];
+            if depth == cleared {
+                continue;
+            }
+            let Some(point) = pixel_point(
+                x as f32,
+                y as f32,
+                depth,
+                projection_type,
+                screen_to_global,
+                eye,
+                forward,
+            ) else {
+                continue;
+            };
+            for projector in &projectors {
+                if let Some(color) = projector.color_at(point) {
+                    canvas.blend_pixel(x, y, color, projector.opacity.min(1.0));
+                }
+            }
+        }
+    }
+}
+
+/// Точка поверхности в **глобальных** координатах, отрисованная в пикселе (x, y)
+/// с глубиной `depth` из z-буфера.
+fn pixel_point(
+    x: f32,
+    y: f32,
+    depth: f32,
+    projection_type: ProjectionType,
+    screen_to_global: Transform3D,
+    eye: Point3,
+    forward: Vec3,
+) -> Option<Point3> {
+    match projection_type {
+        // при перспективе z-буфер хранит 1 / w, а не экранную z: точка ищется
+        // на луче камеры через пиксель по глубине в пространстве камеры
+        ProjectionType::Perspective => {
+            let on_ray = Point3::new(x, y, 0.5)
+                .apply_transform(screen_to_global)
+                .ok()?;
+            let direction = on_ray - eye;
+            let along = direction.dot(forward);
+            if depth <= 0.0 || along.abs() < f32::EPSILON {
+                return None;
+            }
+            Some(eye + direction * (1.0 / depth / along))
+        }
+        _ => Point3::new(x, y, depth)
+            .apply_transform(screen_to_global)
+            .ok(),
+    }
+}
+
+impl StateHash for TextureProjector {
+    fn hash_state<H: Hasher>(&self, state: &mut H) {
+        self.camera.hash_state(state);
+        self.texture.hash_state(state);
+        self.opacity.hash_state(state);
+        self.mask_color.hash_state(state);
+        self.orthographic_height.hash_state(state);
+        self.enabled.hash_state(state);
+    }
+}
+
+#[cfg(test)]
+mod texture_projector_tests {
+    use super::*;
+    use crate::{Mesh, Model, ProjectionType, Scene, SceneRenderer, UVec3};
+
+    #[test]
+    fn test_projector_stamps_texture_on_surface() {
+        let mut cube = Model::from_mesh(Mesh::hexahedron());
+        cube.uniform_scale(4.0);
+        cube.material.color = Color32::WHITE;
+        let mut scene = Scene {
+            models: vec![cube],
+            ..Default::default()
+        };
+        // узкий проектор из точки камеры: посередине красная полоса, сверху и снизу фон
+        let image = image::RgbImage::from_fn(1, 3, |_, y| match y {
+            1 => image::Rgb([255, 0, 0]),
+            _ => image::Rgb([0, 255, 0]),
+        });
+        let camera = Camera::new(
+            scene.camera.get_position(),
+            UVec3::forward(),
+            UVec3::up(),
+            10_f32.to_radians(),
+            1.0,
+            1.0,
+            100.0,
+        );
+        let mut projector = TextureProjector::new(camera, Texture::new(image.into()));
+        projector.mask_color = Some(Color32::GREEN);
+        scene.projectors.push(projector);
+
+        let renderer = SceneRenderer {
+            render_wireframe: false,
+            render_solid: true,
+            projection_type: ProjectionType::Perspective,
+            ..Default::default()
+        };
+        let mut canvas = Canvas::new(300, 200);
+        let mut render = |scene: &Scene| {
+            renderer.render(scene, &mut canvas, false, Point3::zero(), Point3::zero());
+            (canvas[(150, 100)], canvas[(150, 111)], canvas[(150, 130)])
+        };
+
+        // середина проекции красная, замаскированный край и поверхность вне проектора белые
+        assert_eq!(
+            render(&scene),
+            (Color32::RED, Color32::WHITE, Color32::WHITE)
+        );
+
+        scene.projectors[0].opacity = 0.5;
+        let (center, _, _) = render(&scene);
+        assert!(center.r() == 255 && center.g() > 100 && center.g() < 150);
+
+        scene.projectors[0].enabled = false;
+        assert_eq!(render(&scene).0, Color32::WHITE);
+    }
+
+    #[test]
+    fn test_off_axis_projector_stays_inside_frustum() {
+        let mut cube = Model::from_mesh(Mesh::hexahedron());
+        cube.uniform_scale(4.0);
+        cube.material.color = Color32::WHITE;
+        let mut scene = Scene {
+            models: vec![cube],
+            ..Default::default()
+        };
+        // узкий проектор сверху: пятно только на верхней грани куба
+        let image = image::RgbImage::from_pixel(1, 1, image::Rgb([255, 0, 0]));
+        let fov = 7_f32.to_radians();
+        let camera = Camera::new(
+            Point3::new(0.0, 10.0, 0.0),
+            UVec3::down(),
+            UVec3::forward(),
+            fov,
+            1.0,
+            1.0,
+            100.0,
+        );
+        scene
+            .projectors
+            .push(TextureProjector::new(camera, Texture::new(image.into())));
+
+        let mut renderer = SceneRenderer {
+            render_wireframe: false,
+            render_solid: true,
+            projection_type: ProjectionType::Perspective,
+            ..Default::default()
+        };
+        renderer.grid_options.show_grid = false;
+        renderer.grid_options.show_axes = false;
+        let mut canvas = Canvas::new(300, 200);
+        let mut red_pixels = |scene: &Scene| {
+            renderer.render(scene, &mut canvas, false, Point3::zero(), Point3::zero());
+            let mut red = Vec::new();
+            for y in 0..canvas.height() {
+                for x in 0..canvas.width() {
+                    if canvas[(x, y)] == Color32::RED {
+                        red.push((x as f32, y as f32));
+                    }
+                }
+            }
+            red
+        };
+
+        // камера по умолчанию видит только переднюю грань, на которую проектор не светит
+        assert_eq!(red_pixels(&scene), Vec::new());
+
+        // сверху пятно видно и не выходит за проекцию области видимости проектора
+        scene.camera.set_position(Point3::new(0.0, 8.0, -10.0));
+        scene.camera.set_target(Point3::zero());
+        let red = red_pixels(&scene);
+        assert!(!red.is_empty(), "пятно проектора на верхней грани");
+
+        let top = scene.models[0].global_bounds().1.y;
+        let radius = (10.0 - top) * (fov / 2.0).tan() * std::f32::consts::SQRT_2;
+        let transform = scene
+            .camera
+            .global_to_screen_transform(ProjectionType::Perspective, &canvas);
+        let corners: Vec<Point3> = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+            .iter()
+            .map(|&(dx, dz)| {
+                Point3::new(dx * radius, top, dz * radius)
+                    .apply_transform(transform)
+                    .unwrap()
+            })
+            .collect();
+        let min_x = corners.iter().map(|p| p.x).fold(f32::INFINITY, f32::min) - 1.0;
+        let max_x = corners
+            .iter()
+            .map(|p| p.x)
+            .fold(f32::NEG_INFINITY, f32::max)
+            + 1.0;
+        let min_y = corners.iter().map(|p| p.y).fold(f32::INFINITY, f32::min) - 1.0;
+        let max_y = corners
+            .iter()
+            .map(|p| p.y)
+            .fold(f32::NEG_INFINITY, f32::max)
+            + 1.0;
+        // готовый кадр перевёрнут по вертикали
+        let flip = |y: f32| (canvas.height() - 1) as f32 - y;
+        let (min_y, max_y) = (flip(max_y), flip(min_y));
+        for (x, y) in red {
+            assert!(
+                (min_x..=max_x).contains(&x) && (min_y..=max_y).contains(&y),
+                "пиксель ({}, {}) вне области проектора",
+                x,
+                y
+            );
+        }
+    }
+}
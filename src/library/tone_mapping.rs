@@ -0,0 +1,152 @@
+//! Тональная компрессия HDR-цветов холста в отображаемые 8-битные цвета.
+
+use std::fmt::Display;
+
+use egui::Color32;
+
+use crate::Vec3;
+
+/// Кривая, сжимающая неограниченную яркость в диапазон `[0, 1]`.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub enum ToneMapping {
+    /// Значения больше 1 просто обрезаются, как в обычном 8-битном холсте.
+    Clamp,
+    /// Оператор Рейнхарда `x / (1 + x)`: мягко сжимает яркие цвета, но заметно
+    /// приглушает средние тона.
+    Reinhard,
+    /// Аппроксимация кривой ACES (Narkowicz): контрастнее Рейнхарда и почти не
+    /// меняет тёмные и средние тона.
+    #[default]
+    Aces,
+}
+
+impl Display for ToneMapping {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Clamp => f.write_str("Обрезка"),
+            Self::Reinhard => f.write_str("Рейнхард"),
+            Self::Aces => f.write_str("ACES"),
+        }
+    }
+}
+
+impl ToneMapping {
+    /// Сжать линейное значение канала `value >= 0` в `[0, 1]`.
+    pub fn apply(self, value: f32) -> f32 {
+        let value = value.max(0.0);
+        match self {
+            Self::Clamp => value.min(1.0),
+            Self::Reinhard => value / (1.0 + value),
+            Self::Aces => {
+                let mapped = value * (2.51 * value + 0.03) / (value * (2.43 * value + 0.59) + 0.14);
+                mapped.clamp(0.0, 1.0)
+            }
+        }
+    }
+}
+
+/// Параметры HDR-режима холста.
+///
+/// HDR-цвета хранятся по каналам (r, g, b) в линейном пространстве, где 1 соответствует
+/// 255 в [`Color32`], но могут быть и больше 1. При выводе на экран цвет умножается
+/// на экспозицию, сжимается кривой [`ToneMapping`] и гамма-корректируется.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HdrOptions {
+    /// Кривая тональной компрессии.
+    pub tone_mapping: ToneMapping,
+    /// Множитель яркости перед тональной компрессией.
+    pub exposure: f32,
+    /// Гамма экрана, 1 - без гамма-коррекции.
+    pub gamma: f32,
+}
+
+impl Default for HdrOptions {
+    fn default() -> Self {
+        Self {
+            tone_mapping: ToneMapping::default(),
+            exposure: 1.0,
+            gamma: 2.2,
+        }
+    }
+}
+
+impl HdrOptions {
+    /// Перевести линейный HDR-цвет в отображаемый цвет.
+    pub fn map(&self, color: Vec3) -> Color32 {
+        let to_channel = |value: f32| {
+            let mapped = self.tone_mapping.apply(value * self.exposure);
+            (mapped.powf(1.0 / self.gamma) * 255.0).round() as u8
+        };
+        Color32::from_rgb(
+            to_channel(color.x),
+            to_channel(color.y),
+            to_channel(color.z),
+        )
+    }
+
+    /// Перевести отображаемый цвет обратно в линейное пространство (без обращения кривой).
+    ///
+    /// Нужно, чтобы цвета, записанные на холст в обход HDR (фон, непрозрачные 8-битные
+    /// фрагменты), смешивались с полупрозрачными HDR-фрагментами.
+    pub fn linearize(&self, color: Color32) -> Vec3 {
+        let to_linear = |channel: u8| (channel as f32 / 255.0).powf(self.gamma);
+        Vec3::new(
+            to_linear(color.r()),
+            to_linear(color.g()),
+            to_linear(color.b()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tone_mapping_tests {
+    use super::*;
+
+    #[test]
+    fn test_curves_are_monotonic_and_bounded() {
+        for tone_mapping in [ToneMapping::Clamp, ToneMapping::Reinhard, ToneMapping::Aces] {
+            assert_eq!(
+                tone_mapping.apply(0.0),
+                0.0,
+                "{}: чёрный остаётся чёрным",
+                tone_mapping
+            );
+            let mut previous = 0.0;
+            for i in 1..100 {
+                let value = tone_mapping.apply(i as f32 * 0.1);
+                assert!(value >= previous, "{}: кривая не убывает", tone_mapping);
+                assert!(value <= 1.0, "{}: кривая не больше 1", tone_mapping);
+                previous = value;
+            }
+        }
+    }
+
+    #[test]
+    fn test_bright_colors_keep_hue() {
+        let options = HdrOptions {
+            tone_mapping: ToneMapping::Reinhard,
+            exposure: 1.0,
+            gamma: 1.0,
+        };
+        // два ярких канала разной яркости не сливаются в белый, как при обрезке
+        let color = options.map(Vec3::new(4.0, 2.0, 0.0));
+        assert!(
+            color.r() > color.g() && color.g() > color.b(),
+            "{:?}",
+            color
+        );
+        assert_eq!(
+            options.map(Vec3::new(1.0, 0.0, 0.0)),
+            Color32::from_rgb(128, 0, 0)
+        );
+
+        let clamp = HdrOptions {
+            tone_mapping: ToneMapping::Clamp,
+            ..options
+        };
+        assert_eq!(
+            clamp.map(Vec3::new(4.0, 2.0, 0.0)),
+            Color32::from_rgb(255, 255, 0)
+        );
+    }
+}
@@ -0,0 +1,207 @@
+//! Анимация вращения (turntable) и запись кадров в PNG файлы.
+
+use std::f32::consts::TAU;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+use crate::library::testing::canvas_to_image;
+use crate::{Canvas, Line3, Point3, Scene, SceneRenderer, Transform3D};
+
+/// Что вращается в анимации [`Turntable`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TurntableSubject {
+    /// Камера облетает ось, сцена неподвижна.
+    Camera,
+    /// Модель с этим индексом в `scene.models` вращается вокруг оси перед неподвижной камерой.
+    Model(usize),
+}
+
+/// Анимация полного оборота камеры или модели вокруг оси за `frame_count` кадров.
+///
+/// Кадр `i` повёрнут на угол `2π * i / frame_count`, поэтому последний кадр не повторяет
+/// первый и последовательность зацикливается без рывка (удобно для GIF).
+#[derive(Debug, Clone, Copy)]
+pub struct Turntable {
+    /// Количество кадров на полный оборот.
+    pub frame_count: usize,
+    /// Ось вращения в **глобальных** координатах.
+    pub axis: Line3,
+    /// Что вращается.
+    pub subject: TurntableSubject,
+    /// Ширина кадра в пикселях.
+    pub width: usize,
+    /// Высота кадра в пикселях.
+    pub height: usize,
+}
+
+impl Turntable {
+    /// Создать облёт камерой оси `axis` за `frame_count` кадров размером 640x480.
+    pub fn new(frame_count: usize, axis: Line3) -> Self {
+        Self {
+            frame_count,
+            axis,
+            subject: TurntableSubject::Camera,
+            width: 640,
+            height: 480,
+        }
+    }
+
+    /// Угол поворота кадра `index` в радианах.
+    pub fn angle(&self, index: usize) -> f32 {
+        if self.frame_count == 0 {
+            return 0.0;
+        }
+        TAU * index as f32 / self.frame_count as f32
+    }
+
+    /// Копия сцены `scene` с камерой или моделью, повёрнутой для кадра `index`.
+    ///
+    /// Если модели с индексом из [`TurntableSubject::Model`] нет, то сцена не меняется.
+    pub fn frame_scene(&self, scene: &Scene, index: usize) -> Scene {
+        let rotation = Transform3D::rotation_around_line(self.axis, self.angle(index));
+        let mut scene = scene.clone();
+        match self.subject {
+            TurntableSubject::Camera => scene.camera.local_frame.apply_transform(rotation),
+            TurntableSubject::Model(model_index) => {
+                if let Some(model) = scene.models.get_mut(model_index) {
+                    model.mesh.local_frame.apply_transform(rotation);
+                }
+            }
+        }
+        scene
+    }
+}
+
+/// Ошибки записи кадров анимации.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TurntableError {
+    /// Модели с таким индексом нет на сцене.
+    ModelNotFound(usize),
+    /// Не удалось создать папку для кадров.
+    CreateDir(String),
+    /// Не удалось закодировать или записать кадр.
+    WriteFrame { path: PathBuf, error: String },
+}
+
+impl Display for TurntableError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ModelNotFound(index) => write!(f, "модели с индексом {} нет на сцене", index),
+            Self::CreateDir(error) => write!(f, "не удалось создать папку для кадров: {}", error),
+            Self::WriteFrame { path, error } => {
+                write!(f, "не удалось записать кадр {}: {}", path.display(), error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TurntableError {}
+
+/// Нарисовать все кадры анимации `turntable` сцены `scene` и записать их в папку
+/// `output_dir` как PNG файлы `frame_0000.png`, `frame_0001.png`, ...
+///
+/// Папка создаётся, если её нет, существующие кадры перезаписываются.
+/// Возвращает пути записанных файлов по порядку кадров.
+///
+/// # Examples
+/// ```rust,no_run
+/// use g3d::{Line3, Point3, Scene, SceneRenderer, Turntable, UVec3, render_turntable};
+///
+/// let scene = Scene::default();
+/// let turntable = Turntable::new(36, Line3::new(Point3::zero(), UVec3::up()));
+/// let frames = render_turntable(&scene, &SceneRenderer::default(), &turntable, "turntable")?;
+/// assert_eq!(frames.len(), 36);
+/// # Ok::<(), g3d::TurntableError>(())
+/// ```
+pub fn render_turntable(
+    scene: &Scene,
+    renderer: &SceneRenderer,
+    turntable: &Turntable,
+    output_dir: impl AsRef<Path>,
+) -> Result<Vec<PathBuf>, TurntableError> {
+    if let TurntableSubject::Model(index) = turntable.subject
+        && index >= scene.models.len()
+    {
+        return Err(TurntableError::ModelNotFound(index));
+    }
+    let output_dir = output_dir.as_ref();
+    std::fs::create_dir_all(output_dir).map_err(|e| TurntableError::CreateDir(e.to_string()))?;
+
+    // номера кадров одной ширины, чтобы файлы сортировались по порядку
+    let digits = turntable.frame_count.to_string().len().max(4);
+    let mut canvas = Canvas::new(turntable.width, turntable.height);
+    let mut paths = Vec::with_capacity(turntable.frame_count);
+    for index in 0..turntable.frame_count {
+        let frame = turntable.frame_scene(scene, index);
+        renderer.render(&frame, &mut canvas, false, Point3::zero(), Point3::zero());
+
+        let path = output_dir.join(format!("frame_{:0digits$}.png", index));
+        canvas_to_image(&canvas)
+            .save(&path)
+            .map_err(|e| TurntableError::WriteFrame {
+                path: path.clone(),
+                error: e.to_string(),
+            })?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod turntable_tests {
+    use super::*;
+    use crate::{Mesh, Model, UVec3};
+
+    #[test]
+    fn test_frame_scene_rotates_subject() {
+        let mut scene = Scene {
+            models: vec![Model::from_mesh(Mesh::hexahedron())],
+            ..Default::default()
+        };
+        scene.models[0].set_position(Point3::new(2.0, 0.0, 0.0));
+        let mut turntable = Turntable::new(4, Line3::new(Point3::zero(), UVec3::up()));
+
+        // за четверть оборота камера оказывается сбоку от оси и всё так же смотрит на неё
+        let camera = turntable.frame_scene(&scene, 1).camera;
+        let position = camera.get_position();
+        assert!(position.y.abs() < 1e-4 && position.z.abs() < 1e-4);
+        assert!((position.x.abs() - 10.0).abs() < 1e-4);
+        assert!(camera.forward().x * position.x < 0.0);
+
+        turntable.subject = TurntableSubject::Model(0);
+        let frame = turntable.frame_scene(&scene, 2);
+        assert!(
+            frame.models[0]
+                .get_position()
+                .approx_equal(Point3::new(-2.0, 0.0, 0.0), 1e-4)
+        );
+        assert_eq!(frame.camera.get_position(), scene.camera.get_position());
+    }
+
+    #[test]
+    fn test_render_turntable_writes_numbered_frames() {
+        let scene = Scene {
+            models: vec![Model::from_mesh(Mesh::hexahedron())],
+            ..Default::default()
+        };
+        let mut turntable = Turntable::new(3, Line3::new(Point3::zero(), UVec3::up()));
+        turntable.width = 32;
+        turntable.height = 24;
+        let output_dir = std::env::temp_dir().join("g3d_turntable_test");
+        let _ = std::fs::remove_dir_all(&output_dir);
+
+        let paths =
+            render_turntable(&scene, &SceneRenderer::default(), &turntable, &output_dir).unwrap();
+        assert_eq!(paths.len(), 3);
+        assert!(paths[2].ends_with("frame_0002.png"));
+        let image = image::open(&paths[0]).unwrap();
+        assert_eq!((image.width(), image.height()), (32, 24));
+
+        turntable.subject = TurntableSubject::Model(5);
+        assert_eq!(
+            render_turntable(&scene, &SceneRenderer::default(), &turntable, &output_dir),
+            Err(TurntableError::ModelNotFound(5))
+        );
+        let _ = std::fs::remove_dir_all(&output_dir);
+    }
+}
@@ -1,6 +1,6 @@
 //! Всякие вспомогательные функции.
 
-use crate::{Canvas, Point3, Transform3D, UVec3, Vec3};
+use crate::{Canvas, HVec3, Point3, PointError, ProjectionType, Transform3D, UVec3, Vec3};
 
 /// Вычислить центр точек как среднее арифметическое.
 pub fn calculate_center(points: &Vec<Point3>) -> Point3 {
@@ -39,6 +39,36 @@ pub fn calculate_bounds(points: &Vec<Point3>) -> (Point3, Point3) {
     (min, max)
 }
 
+/// Нормаль многоугольника методом Ньюэлла, направление определяется порядком обхода вершин.
+///
+/// Устойчива и для невыпуклых и почти плоских многоугольников.
+/// Для вырожденного многоугольника возвращает `None`.
+pub fn newell_normal(vertexes: &[Point3]) -> Option<UVec3> {
+    let mut normal = Vec3::zero();
+    for k in 0..vertexes.len() {
+        let cur = vertexes[k];
+        let next = vertexes[(k + 1) % vertexes.len()];
+        normal.x += (cur.y - next.y) * (cur.z + next.z);
+        normal.y += (cur.z - next.z) * (cur.x + next.x);
+        normal.z += (cur.x - next.x) * (cur.y + next.y);
+    }
+    normal.normalize().ok()
+}
+
+/// Все 8 вершин параллелепипеда, заданного минимальной и максимальной точками.
+pub fn bounds_corners(min: Point3, max: Point3) -> [Point3; 8] {
+    [
+        Point3::new(min.x, min.y, min.z),
+        Point3::new(max.x, min.y, min.z),
+        Point3::new(min.x, max.y, min.z),
+        Point3::new(max.x, max.y, min.z),
+        Point3::new(min.x, min.y, max.z),
+        Point3::new(max.x, min.y, max.z),
+        Point3::new(min.x, max.y, max.z),
+        Point3::new(max.x, max.y, max.z),
+    ]
+}
+
 /// Убирает ошибки с плавающей точкой из заданного ортонормированного 3D базиса.
 /// Иными словами, пересчитывает базис на основе указанного.
 ///
@@ -67,6 +97,29 @@ pub fn opposite_color(color: egui::Color32) -> egui::Color32 {
     egui::Color32::from_rgb(255 - color.r(), 255 - color.g(), 255 - color.b())
 }
 
+/// Цвет тепловой карты для значения `t` в диапазоне [0, 1].
+///
+/// 0 - чёрный, далее синий -> голубой -> зелёный -> жёлтый -> красный, 1 и выше - белый.
+pub fn heat_color(t: f32) -> egui::Color32 {
+    if t <= 0.0 {
+        return egui::Color32::BLACK;
+    }
+    if t >= 1.0 {
+        return egui::Color32::WHITE;
+    }
+
+    let ramp = [
+        egui::Color32::BLUE,
+        egui::Color32::from_rgb(0, 255, 255),
+        egui::Color32::GREEN,
+        egui::Color32::YELLOW,
+        egui::Color32::RED,
+    ];
+    let scaled = t * (ramp.len() - 1) as f32;
+    let i = (scaled.floor() as usize).min(ramp.len() - 2);
+    lerp_color(ramp[i], ramp[i + 1], scaled - i as f32)
+}
+
 pub fn is_inside_polygon(vertexes: &Vec<Vec3>, indexes: &Vec<usize>, pos: Vec3) -> bool {
     let mut sign = None;
     for i in 0..indexes.len() {
@@ -88,9 +141,75 @@ pub fn is_inside_polygon(vertexes: &Vec<Vec3>, indexes: &Vec<usize>, pos: Vec3)
     true
 }
 
+/// Минимальное значение однородной компоненты `w`, до которого отсекаются отрезки.
+///
+/// Точки с `w <= 0` находятся на уровне камеры или позади неё и не имеют корректной проекции.
+pub const MIN_CLIP_W: f32 = 1e-5;
+
+/// Отсечь отрезок в однородных координатах (до деления на `w`) по плоскости `w = MIN_CLIP_W`.
+///
+/// Возвращает видимую часть отрезка или `None`, если отрезок целиком позади камеры.
+pub fn clip_segment_by_w(start: HVec3, end: HVec3) -> Option<(HVec3, HVec3)> {
+    let start_inside = start.w >= MIN_CLIP_W;
+    let end_inside = end.w >= MIN_CLIP_W;
+
+    match (start_inside, end_inside) {
+        (true, true) => Some((start, end)),
+        (false, false) => None,
+        _ => {
+            // точка пересечения отрезка с плоскостью w = MIN_CLIP_W
+            let t = (MIN_CLIP_W - start.w) / (end.w - start.w);
+            let intersection = HVec3::new(
+                lerp_float(start.x, end.x, t),
+                lerp_float(start.y, end.y, t),
+                lerp_float(start.z, end.z, t),
+                MIN_CLIP_W,
+            );
+            if start_inside {
+                Some((start, intersection))
+            } else {
+                Some((intersection, end))
+            }
+        }
+    }
+}
+
+/// Экранная точка по однородным координатам вершины после проекции.
+///
+/// `x` и `y` делятся на `w`, а `z` заменяется глубиной для z-буфера, которая растёт к камере.
+/// При перспективной проекции это `1 / w`, величина, обратная глубине в пространстве камеры:
+/// в отличие от самой глубины она линейно интерполируется по экрану, поэтому пересекающиеся
+/// полигоны перекрываются верно. При параллельных проекциях `w = 1` и глубиной остаётся `z`,
+/// которая и так линейна по глубине в пространстве камеры.
+///
+/// Возвращает ошибку для точек, которые нельзя спроецировать (см. `Point3::try_from`).
+pub fn screen_point(hvec: HVec3, projection_type: ProjectionType) -> Result<Point3, PointError> {
+    let point = Point3::try_from(hvec)?;
+    match projection_type {
+        ProjectionType::Perspective => Ok(Point3::new(point.x, point.y, 1.0 / hvec.w)),
+        _ => Ok(point),
+    }
+}
+
+/// Точка `point` в экранных координатах (см. [`screen_point`]) с глубиной, смещённой
+/// к камере на долю `bias`.
+///
+/// При перспективной проекции глубина `1 / w` увеличивается в `1 + bias` раз, то есть точка
+/// приближается на одну и ту же долю расстояния до камеры независимо от удалённости модели.
+/// При параллельных проекциях глубина линейна и смещается на `bias`. Смещение линейно
+/// по глубине, поэтому смещённые концы отрезка дают то же, что смещение каждого пикселя.
+pub fn depth_biased(point: Point3, bias: f32, projection_type: ProjectionType) -> Point3 {
+    let z = match projection_type {
+        ProjectionType::Perspective => point.z * (1.0 + bias),
+        _ => point.z + bias,
+    };
+    Point3::new(point.x, point.y, z)
+}
+
 /// Рендерить линию, образованную точками `start` и `end`.
 ///
-/// Сами точки `start` и `end` должны указываться в **глобальных** координатах
+/// Сами точки `start` и `end` должны указываться в **глобальных** координатах.
+/// Часть линии позади камеры отсекается.
 pub fn render_line(
     global_to_screen_transform: Transform3D,
     start: Point3,
@@ -98,11 +217,12 @@ pub fn render_line(
     color: egui::Color32,
     canvas: &mut Canvas,
 ) {
-    let start = start.apply_transform(global_to_screen_transform);
-    let end = end.apply_transform(global_to_screen_transform);
+    let start = HVec3::from(start).apply_transform(global_to_screen_transform);
+    let end = HVec3::from(end).apply_transform(global_to_screen_transform);
 
-    if let Ok(start) = start
-        && let Ok(end) = end
+    if let Some((start, end)) = clip_segment_by_w(start, end)
+        && let Ok(start) = Point3::try_from(start)
+        && let Ok(end) = Point3::try_from(end)
     {
         let start_pos = egui::Pos2::new(start.x, start.y);
         let end_pos = egui::Pos2::new(end.x, end.y);
@@ -141,9 +261,16 @@ pub fn barycentric_coordinates(triangle: &[Point3], point: Point3) -> Point3 {
     Point3::new(u, v, w)
 }
 
-/// Находит uv-координаты для билинейной интерполяции.
+/// Находит координаты `(alpha, beta)` точки `cur` внутри четырёхугольника для билинейной
+/// интерполяции (обратное билинейное отображение).
+///
+/// `p0`, `p1`, `p2`, `p3` - вершины в порядке обхода. `alpha` отсчитывается вдоль ребра
+/// `p0 -> p1`, `beta` - вдоль ребра `p0 -> p3`, поэтому значения в вершинах передаются
+/// в [`bilerp_float`] и подобные функции в порядке `p0, p1, p3, p2`. Если точка внутри
+/// четырёхугольника, то обе координаты из [0, 1].
 ///
 /// Все точки являются проекциями на экран, z-компонента не учитывается.
+/// Возвращает `None` для вырожденного четырёхугольника.
 pub fn find_uv_for_bilerp(
     p0: Point3,
     p1: Point3,
@@ -151,31 +278,54 @@ pub fn find_uv_for_bilerp(
     p3: Point3,
     cur: Point3,
 ) -> Option<(f32, f32)> {
-    // let p0p1 = p1 - p0;
-    // let p0p3 = p3 - p0;
-    // let det = p0p3.x * p0p1.y - p0p3.y * p0p1.x;
-    // if det.abs() <= f32::EPSILON {
-    //     return None;
-    // }
-    // let det_u = (cur.x - p0.x) * p0p1.y - (cur.y - p0.y) * p0p1.x;
-    // let det_v = p0p3.x * (cur.y - p0.y) - p0p3.y * (cur.x - p0.x);
-    // Some((det_u / det, det_v / det))
-
-    let a = Vec3::from(p0);
-    let e1 = p3 - p0;
-    let e2 = p1 - p0;
-
-    let n = e1.cross(e2);
-    let m = e2.cross(a);
-    let l = a.cross(e1);
-
-    let det = cur.x * n.x + cur.y * n.y + n.z;
-    if det.abs() <= f32::EPSILON {
+    let cross = |a: (f32, f32), b: (f32, f32)| a.0 * b.1 - a.1 * b.0;
+    // cur = p0 + alpha * e + beta * f + alpha * beta * g
+    let e = (p1.x - p0.x, p1.y - p0.y);
+    let f = (p3.x - p0.x, p3.y - p0.y);
+    let g = (p0.x - p1.x + p2.x - p3.x, p0.y - p1.y + p2.y - p3.y);
+    let h = (cur.x - p0.x, cur.y - p0.y);
+
+    // квадратное уравнение на beta
+    let k2 = cross(g, f);
+    let k1 = cross(e, f) + cross(h, g);
+    let k0 = cross(h, e);
+
+    // alpha по найденной beta, по той оси, где знаменатель больше
+    let alpha_for = |beta: f32| {
+        let (dx, dy) = (e.0 + g.0 * beta, e.1 + g.1 * beta);
+        if dx.abs() >= dy.abs() {
+            (dx.abs() > f32::EPSILON).then(|| (h.0 - f.0 * beta) / dx)
+        } else {
+            Some((h.1 - f.1 * beta) / dy)
+        }
+    };
+    let inside = |t: f32| (0.0..=1.0).contains(&t);
+
+    // у параллелограмма уравнение линейное
+    if k2.abs() <= 1e-6 * k1.abs() {
+        if k1.abs() <= f32::EPSILON {
+            return None;
+        }
+        let beta = -k0 / k1;
+        return alpha_for(beta).map(|alpha| (alpha, beta));
+    }
+
+    let discriminant = k1 * k1 - 4.0 * k0 * k2;
+    if discriminant < 0.0 {
         return None;
     }
-    let det_u = cur.x * m.x + cur.y * m.y + m.z;
-    let det_v = cur.x * l.x + cur.y * l.y + l.z;
-    Some((det_u / det, det_v / det))
+    let root = discriminant.sqrt();
+    let candidates = [(-k1 - root) / (2.0 * k2), (-k1 + root) / (2.0 * k2)];
+    let solutions: Vec<(f32, f32)> = candidates
+        .into_iter()
+        .filter_map(|beta| alpha_for(beta).map(|alpha| (alpha, beta)))
+        .collect();
+    // из двух корней нужен тот, что внутри четырёхугольника
+    solutions
+        .iter()
+        .copied()
+        .find(|&(alpha, beta)| inside(alpha) && inside(beta))
+        .or_else(|| solutions.first().copied())
 }
 
 /// Интерполяция вещественного числа через барицентрические координаты.
@@ -236,6 +386,37 @@ pub fn bilerp_color(
     lerp_color(top, bottom, beta)
 }
 
+/// RGB-компоненты цвета в виде вектора, где 1 соответствует 255.
+pub fn color_to_vec(color: egui::Color32) -> Vec3 {
+    Vec3::new(color.r() as f32, color.g() as f32, color.b() as f32) / 255.0
+}
+
+/// Непрозрачный цвет по RGB-вектору (1 соответствует 255), значения вне `[0, 1]` обрезаются.
+pub fn vec_to_color(color: Vec3) -> egui::Color32 {
+    let to_channel = |value: f32| (value * 255.0).clamp(0.0, 255.0) as u8;
+    egui::Color32::from_rgb(
+        to_channel(color.x),
+        to_channel(color.y),
+        to_channel(color.z),
+    )
+}
+
+/// Покомпонентное произведение RGB-векторов (например, цвета материала на освещённость).
+pub fn modulate(a: Vec3, b: Vec3) -> Vec3 {
+    Vec3::new(a.x * b.x, a.y * b.y, a.z * b.z)
+}
+
+/// Умножить RGB-компоненты цвета на `factor`, не меняя его прозрачность.
+pub fn scale_color(color: egui::Color32, factor: f32) -> egui::Color32 {
+    let scale = |channel: u8| (channel as f32 * factor).clamp(0.0, 255.0) as u8;
+    egui::Color32::from_rgba_premultiplied(
+        scale(color.r()),
+        scale(color.g()),
+        scale(color.b()),
+        color.a(),
+    )
+}
+
 /// Линейная интерполяция цвета.
 pub fn lerp_color(a: egui::Color32, b: egui::Color32, t: f32) -> egui::Color32 {
     egui::Color32::from_rgb(
@@ -286,7 +467,7 @@ pub fn bilerp_vec(
 }
 
 /// Линейная интерполяция вектора.
-fn lerp_vec(a: Vec3, b: Vec3, t: f32) -> Vec3 {
+pub fn lerp_vec(a: Vec3, b: Vec3, t: f32) -> Vec3 {
     a + (b - a) * t
 }
 
@@ -330,24 +511,32 @@ fn lerp_point(a: Point3, b: Point3, t: f32) -> Point3 {
     Point3::from(a + (b - a) * t)
 }
 
-/// Триангуляция полигона.
-/// `polygon` - полигон, заданный индексами вершин.
-///
-/// Пока что примитивная веерная триангуляция.
-pub fn triangulate_polygon(polygon: &[usize]) -> Vec<[usize; 3]> {
-    #[cfg(debug_assertions)]
-    {
-        if polygon.len() < 3 {
-            eprintln!(
-                "Warning: триангуляция полигона с {} вершинами",
-                polygon.len()
-            );
-        }
-    }
+/// Псевдослучайное значение из `[-1, 1]` в узле целочисленной решётки.
+fn lattice_value(x: i32, y: i32, z: i32, seed: u32) -> f32 {
+    // целочисленный хэш, чтобы соседние узлы не коррелировали
+    let mut h = seed
+        ^ (x as u32).wrapping_mul(0x8da6_b343)
+        ^ (y as u32).wrapping_mul(0xd816_3841)
+        ^ (z as u32).wrapping_mul(0xcb1a_b31f);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0x5bd1_e995);
+    h ^= h >> 15;
+    (h as f32 / u32::MAX as f32) * 2.0 - 1.0
+}
 
-    let mut triangles = vec![];
-    for i in 1..polygon.len() - 1 {
-        triangles.push([polygon[0], polygon[i], polygon[i + 1]]);
-    }
-    triangles
+/// Гладкий шум значений: трилинейная интерполяция значений в узлах решётки
+/// со сглаживанием `smoothstep`. Результат из `[-1, 1]`.
+pub(crate) fn value_noise(p: Vec3, seed: u32) -> f32 {
+    let (x0, y0, z0) = (p.x.floor(), p.y.floor(), p.z.floor());
+    let smooth = |t: f32| t * t * (3.0 - 2.0 * t);
+    let (tx, ty, tz) = (smooth(p.x - x0), smooth(p.y - y0), smooth(p.z - z0));
+    let (x0, y0, z0) = (x0 as i32, y0 as i32, z0 as i32);
+
+    let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+    let corner = |dx: i32, dy: i32, dz: i32| lattice_value(x0 + dx, y0 + dy, z0 + dz, seed);
+    let x00 = lerp(corner(0, 0, 0), corner(1, 0, 0), tx);
+    let x10 = lerp(corner(0, 1, 0), corner(1, 1, 0), tx);
+    let x01 = lerp(corner(0, 0, 1), corner(1, 0, 1), tx);
+    let x11 = lerp(corner(0, 1, 1), corner(1, 1, 1), tx);
+    lerp(lerp(x00, x10, ty), lerp(x01, x11, ty), tz)
 }